@@ -0,0 +1,105 @@
+//! A `--plugin` for stylus-analyzer with two house rules: comments that leave
+//! work for later, and addresses written into the contract. Build it with
+//! `cargo build --example todo_plugin`, then run
+//! `stylus-analyzer audit contract.rs --plugin target/debug/examples/libtodo_plugin.so`
+//! (`.dylib` on macOS, `todo_plugin.dll` on Windows).
+//!
+//! A plugin is a `cdylib` exporting the symbols below; see
+//! `src/audit/plugin.rs` for the ABI. Requests and findings cross it as JSON
+//! strings, so the plugin doesn't depend on this crate's types.
+
+use std::ffi::{c_char, CStr, CString};
+use serde_json::{json, Value};
+
+/// The plugin ABI this plugin was built against
+#[no_mangle]
+pub static STYLUS_ANALYZER_PLUGIN_ABI: u32 = 1;
+
+/// Describes the plugin and its rules
+///
+/// # Safety
+///
+/// Called by stylus-analyzer; the returned string must go back through `free_string`
+#[no_mangle]
+pub unsafe extern "C" fn register_rules(_host_abi: u32) -> *mut c_char {
+    respond(json!({
+        "name": "todo-plugin",
+        "capabilities": ["check"],
+        "rules": [
+            {
+                "id": "TODO-001",
+                "name": "Unresolved TODO",
+                "severity": "low",
+                "description": "TODO and FIXME comments mark work left undone before deployment",
+            },
+            {
+                "id": "TODO-002",
+                "name": "Hardcoded Address",
+                "severity": "medium",
+                "description": "An address written into the code can't be changed without redeploying",
+            },
+        ],
+    }))
+}
+
+/// Runs `rule` against the `len` bytes of UTF-8 source at `content`
+///
+/// # Safety
+///
+/// `rule` must be a NUL-terminated string and `content` must point to `len`
+/// readable bytes; the returned string must go back through `free_string`
+#[no_mangle]
+pub unsafe extern "C" fn check_rule(rule: *const c_char, content: *const u8, len: usize) -> *mut c_char {
+    let rule = CStr::from_ptr(rule).to_string_lossy();
+    let content = String::from_utf8_lossy(std::slice::from_raw_parts(content, len));
+    respond(check(&rule, &content))
+}
+
+/// Frees a string returned by `register_rules` or `check_rule`
+///
+/// # Safety
+///
+/// `response` must come from one of those functions and not be freed twice
+#[no_mangle]
+pub unsafe extern "C" fn free_string(response: *mut c_char) {
+    if !response.is_null() {
+        drop(CString::from_raw(response));
+    }
+}
+
+fn respond(response: Value) -> *mut c_char {
+    CString::new(response.to_string()).expect("JSON has no NUL bytes").into_raw()
+}
+
+fn check(rule: &str, content: &str) -> Value {
+    let mut findings = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let found = match rule {
+            "TODO-001" => ["TODO", "FIXME"].iter()
+                .filter_map(|marker| line.find(&format!("// {}", marker)))
+                .min()
+                .map(|at| (at, "Unresolved TODO", "Finish the work or track it in an issue, then remove the comment")),
+            "TODO-002" => address_literal(line)
+                .map(|at| (at, "Hardcoded Address", "Take the address as a constructor or initializer argument")),
+            _ => return json!({ "error": format!("unknown rule {}", rule) }),
+        };
+        if let Some((at, name, recommendation)) = found {
+            findings.push(json!({
+                "name": name,
+                "recommendation": recommendation,
+                "line": index + 1,
+                "column": line[..at].chars().count() + 1,
+                "snippet": line.trim(),
+            }));
+        }
+    }
+    json!({ "findings": findings })
+}
+
+/// Offset of a `0x` literal of exactly 40 hex digits
+fn address_literal(line: &str) -> Option<usize> {
+    line.match_indices("0x").map(|(at, _)| at).find(|&at| {
+        let digits = line[at + 2..].chars().take_while(char::is_ascii_hexdigit).count();
+        digits == 40 && !line[..at].ends_with(|c: char| c.is_alphanumeric())
+    })
+}
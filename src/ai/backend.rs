@@ -0,0 +1,236 @@
+//! Where prompts are sent: OpenAI (or any OpenAI-compatible endpoint) or a
+//! local Ollama server, so contract source never has to leave the machine
+
+use crate::error::{AnalyzerError, Result};
+use rig::{completion::Prompt, model::Model, providers::openai};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Ollama answers on the local machine, but a large model can take minutes
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// api.openai.com or an OpenAI-compatible endpoint (OPENAI_BASE_URL)
+    #[default]
+    #[value(name = "openai")]
+    OpenAi,
+    /// A local Ollama server (OLLAMA_HOST, default localhost:11434)
+    Ollama,
+}
+
+impl Provider {
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "openai",
+            Provider::Ollama => "ollama",
+        }
+    }
+
+    /// Model used unless `--model` or the config file names another
+    pub fn default_model(self) -> &'static str {
+        match self {
+            Provider::OpenAi => openai::GPT_4_TURBO_PREVIEW,
+            Provider::Ollama => "codellama",
+        }
+    }
+
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "https://api.openai.com/v1",
+            Provider::Ollama => "http://localhost:11434",
+        }
+    }
+
+    /// Environment variable that overrides the endpoint
+    pub fn base_url_var(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "OPENAI_BASE_URL",
+            Provider::Ollama => "OLLAMA_HOST",
+        }
+    }
+
+    /// Only OpenAI needs `OPENAI_API_KEY`
+    pub fn needs_api_key(self) -> bool {
+        self == Provider::OpenAi
+    }
+
+    /// The provider a run uses: `--provider`, else `STYLUS_ANALYZER_PROVIDER`
+    /// (`from_env`, blank counts as unset), else the config file, else OpenAI
+    pub fn select(flag: Option<Provider>, from_env: Option<&str>, configured: Option<Provider>) -> Result<Provider> {
+        if let Some(provider) = flag {
+            return Ok(provider);
+        }
+        match from_env.filter(|name| !name.trim().is_empty()) {
+            Some(name) => name.parse(),
+            None => Ok(configured.unwrap_or_default()),
+        }
+    }
+
+    /// The endpoint: the provider's environment variable (`from_env`), else
+    /// the config file, else the default. A bare `host:port`, common for
+    /// OLLAMA_HOST, gets `http://`
+    pub fn resolve_base_url(self, from_env: Option<&str>, configured: Option<&str>) -> String {
+        let url = from_env.filter(|url| !url.trim().is_empty())
+            .or(configured)
+            .unwrap_or(self.default_base_url());
+        let url = url.trim().trim_end_matches('/');
+        if url.contains("://") { url.to_string() } else { format!("http://{}", url) }
+    }
+}
+
+impl std::str::FromStr for Provider {
+    type Err = AnalyzerError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "openai" => Ok(Provider::OpenAi),
+            "ollama" => Ok(Provider::Ollama),
+            other => Err(AnalyzerError::Config(format!("unknown AI provider '{}'; expected openai or ollama", other))),
+        }
+    }
+}
+
+/// The backend for `provider`; `api_key` is only asked for when the provider needs one
+pub fn for_provider(provider: Provider, model: &str, base_url: &str, api_key: impl FnOnce() -> Result<String>) -> Result<Box<dyn CompletionBackend>> {
+    Ok(match provider {
+        Provider::OpenAi => Box::new(OpenAiBackend::new(&api_key()?, model, base_url)),
+        Provider::Ollama => Box::new(OllamaBackend::new(model, base_url)?),
+    })
+}
+
+/// Something that answers a prompt. Prompt building and response cleaning
+/// stay in `ai`, so every backend gets the same treatment
+#[async_trait::async_trait]
+pub trait CompletionBackend: Send + Sync {
+    /// Sends one prompt and returns the raw answer
+    async fn complete(&self, prompt: &str) -> Result<String>;
+
+    /// `provider:model`, for error messages and cache keys
+    fn describe(&self) -> String;
+}
+
+pub struct OpenAiBackend {
+    gpt: Model<openai::CompletionModel>,
+    model: String,
+    base_url: String,
+}
+
+impl OpenAiBackend {
+    pub fn new(api_key: &str, model: &str, base_url: &str) -> Self {
+        // rig adds the `/v1/...` path itself; OpenAI SDKs expect it in the base URL
+        let client = openai::Client::from_url(api_key, base_url.strip_suffix("/v1").unwrap_or(base_url));
+        Self { gpt: client.model(model).build(), model: model.to_string(), base_url: base_url.to_string() }
+    }
+}
+
+#[async_trait::async_trait]
+impl CompletionBackend for OpenAiBackend {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        // Errors name the model, so a typo in `--model` reads as one
+        self.gpt.prompt(prompt).await
+            .map_err(|e| AnalyzerError::Ai(format!("model '{}' at {}: {}", self.model, self.base_url, e)))
+    }
+
+    fn describe(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+}
+
+pub struct OllamaBackend {
+    client: reqwest::Client,
+    model: String,
+    base_url: String,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    #[serde(default)]
+    response: String,
+    error: Option<String>,
+}
+
+impl OllamaBackend {
+    pub fn new(model: &str, base_url: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(OLLAMA_TIMEOUT)
+            .build()
+            .map_err(|e| AnalyzerError::Ai(format!("cannot build an HTTP client: {}", e)))?;
+        Ok(Self { client, model: model.to_string(), base_url: base_url.to_string() })
+    }
+}
+
+#[async_trait::async_trait]
+impl CompletionBackend for OllamaBackend {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        let url = format!("{}/api/generate", self.base_url);
+        let fail = |message: String| AnalyzerError::Ai(format!("model '{}' at {}: {}", self.model, self.base_url, message));
+        let response = self.client.post(&url)
+            .json(&GenerateRequest { model: &self.model, prompt, stream: false })
+            .send().await
+            .map_err(|e| fail(format!("Ollama is unreachable ({}); is `ollama serve` running?", e)))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| fail(e.to_string()))?;
+        // Ollama reports a missing model as {"error": "model 'x' not found"} with a 404
+        let answer: GenerateResponse = serde_json::from_str(&body)
+            .map_err(|_| fail(format!("unexpected answer ({}): {}", status, body.chars().take(200).collect::<String>())))?;
+        match answer.error {
+            Some(error) => Err(fail(error)),
+            None if !status.is_success() => Err(fail(format!("HTTP {}", status))),
+            None => Ok(answer.response),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_flag_beats_the_environment_which_beats_the_config() {
+        assert_eq!(Provider::select(None, None, None).unwrap(), Provider::OpenAi);
+        assert_eq!(Provider::select(None, None, Some(Provider::Ollama)).unwrap(), Provider::Ollama);
+        assert_eq!(Provider::select(None, Some(" "), Some(Provider::Ollama)).unwrap(), Provider::Ollama);
+        assert_eq!(Provider::select(None, Some("Ollama"), Some(Provider::OpenAi)).unwrap(), Provider::Ollama);
+        assert_eq!(Provider::select(Some(Provider::OpenAi), Some("ollama"), Some(Provider::Ollama)).unwrap(), Provider::OpenAi);
+        let Err(AnalyzerError::Config(message)) = Provider::select(None, Some("claude"), None) else { panic!("unknown provider accepted") };
+        assert!(message.contains("unknown AI provider 'claude'"), "{}", message);
+    }
+
+    #[test]
+    fn endpoints_come_from_the_environment_then_the_config() {
+        assert_eq!(Provider::Ollama.resolve_base_url(None, None), "http://localhost:11434");
+        assert_eq!(Provider::Ollama.resolve_base_url(Some("gpu-box:11434"), Some("http://other:1")), "http://gpu-box:11434");
+        assert_eq!(Provider::OpenAi.resolve_base_url(Some(""), Some("http://localhost:8000/v1/")), "http://localhost:8000/v1");
+        assert_eq!(Provider::OpenAi.resolve_base_url(None, None), "https://api.openai.com/v1");
+    }
+
+    #[test]
+    fn each_provider_gets_its_own_backend() {
+        let ollama = for_provider(Provider::Ollama, "codellama", "http://localhost:11434", || panic!("Ollama asked for an API key"));
+        assert_eq!(ollama.unwrap().describe(), "ollama:codellama");
+
+        let openai = for_provider(Provider::OpenAi, "gpt-4o", "https://api.openai.com/v1", || Ok("sk-test".to_string()));
+        assert_eq!(openai.unwrap().describe(), "openai:gpt-4o");
+        let missing = for_provider(Provider::OpenAi, "gpt-4o", "https://api.openai.com/v1", || Err(AnalyzerError::MissingApiKey));
+        assert!(matches!(missing, Err(AnalyzerError::MissingApiKey)));
+    }
+
+    #[test]
+    fn the_config_file_names_providers_in_lowercase() {
+        let config: crate::config::Config = toml::from_str("provider = \"ollama\"\nmodel = \"llama3\"").unwrap();
+        assert_eq!(config.provider, Some(Provider::Ollama));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::{AnalyzerError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// How long a cached answer stays valid unless `--cache-ttl` says otherwise
+pub const DEFAULT_TTL_HOURS: u64 = 24 * 7;
+
+/// Shown above an analysis that was answered from the cache
+pub const CACHED_NOTE: &str = "ℹ️  Cached result: the contract is unchanged, so the previous AI answer was reused (--no-cache to refresh)";
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+static TTL_SECS: AtomicU64 = AtomicU64::new(DEFAULT_TTL_HOURS * 3600);
+
+/// Bypasses the cache for the rest of the run (`--no-cache`); nothing is read or written
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Entries older than `hours` are ignored and overwritten (`--cache-ttl`)
+pub fn set_ttl_hours(hours: u64) {
+    TTL_SECS.store(hours.saturating_mul(3600), Ordering::Relaxed);
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Unix seconds when the answer was stored
+    created: u64,
+    response: String,
+}
+
+/// Cleaned AI answers on disk, one JSON file per (contract, analysis, model)
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl_secs: u64,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl_secs: u64) -> Self {
+        Self { dir: dir.into(), ttl_secs }
+    }
+
+    /// The per-user cache, or None under `--no-cache` or without a home directory
+    pub fn open() -> Option<Self> {
+        if DISABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(Self::new(default_dir()?, TTL_SECS.load(Ordering::Relaxed)))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key for one request: the same contract, analysis and model give the same answer
+    pub fn key(content: &str, contract_type: &str, model: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [model, contract_type, content] {
+            hasher.update(part.len().to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// The stored answer for `key`, unless it is missing, unreadable or expired
+    pub fn get(&self, key: &str) -> Option<String> {
+        let entry: Entry = serde_json::from_str(&std::fs::read_to_string(self.path(key)).ok()?).ok()?;
+        if now().saturating_sub(entry.created) > self.ttl_secs {
+            debug!(key, "AI cache entry expired");
+            return None;
+        }
+        Some(entry.response)
+    }
+
+    pub fn put(&self, key: &str, response: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = Entry { created: now(), response: response.to_string() };
+        let json = serde_json::to_string(&entry).map_err(|e| AnalyzerError::Config(e.to_string()))?;
+        // Written aside and renamed, so concurrent analyzers never read half an entry
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, self.path(key))?;
+        Ok(())
+    }
+
+    /// The cached answer for `key`, or `compute`'s answer, stored when `keep`
+    /// accepts it. The flag is true on a hit. A cache that can't be written
+    /// only costs a warning.
+    pub async fn get_or_compute<F>(&self, key: &str, keep: impl Fn(&str) -> bool, compute: F) -> Result<(String, bool)>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        if let Some(response) = self.get(key) {
+            debug!(key, "AI cache hit");
+            return Ok((response, true));
+        }
+        let response = compute.await?;
+        if keep(&response) {
+            if let Err(e) = self.put(key, &response) {
+                warn!("⚠️  Could not write the AI cache in {}: {}", self.dir.display(), e);
+            }
+        }
+        Ok((response, false))
+    }
+
+    /// Deletes every entry and returns how many there were
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json" || ext == "tmp") {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+/// `$XDG_CACHE_HOME/stylus-analyzer`, falling back to `~/.cache/stylus-analyzer`
+pub fn default_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME").filter(|home| !home.is_empty())?).join(".cache"),
+    };
+    Some(base.join("stylus-analyzer"))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, ttl_secs: u64) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("ai-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        ResponseCache::new(dir, ttl_secs)
+    }
+
+    #[test]
+    fn keys_change_with_the_contract_analysis_and_model() {
+        let key = ResponseCache::key("contract C {}", "ERC-20", "openai:gpt-4o");
+        assert_eq!(key, ResponseCache::key("contract C {}", "ERC-20", "openai:gpt-4o"));
+        assert_eq!(key.len(), 64);
+        for other in [
+            ResponseCache::key("contract C { }", "ERC-20", "openai:gpt-4o"),
+            ResponseCache::key("contract C {}", "ERC-721", "openai:gpt-4o"),
+            ResponseCache::key("contract C {}", "ERC-20", "ollama:gpt-4o"),
+        ] {
+            assert_ne!(key, other);
+        }
+        // Parts are length-prefixed, so moving text from one part to the next is a different key
+        assert_ne!(ResponseCache::key("b", "a", ""), ResponseCache::key("", "ab", ""));
+    }
+
+    #[tokio::test]
+    async fn answers_are_reused_until_they_expire() {
+        let cache = temp_cache("reuse", 3600);
+        let key = ResponseCache::key("contract C {}", "security", "openai:gpt-4o");
+        let (answer, hit) = cache.get_or_compute(&key, |_| true, async { Ok("first".to_string()) }).await.unwrap();
+        assert_eq!((answer.as_str(), hit), ("first", false));
+        let (answer, hit) = cache.get_or_compute(&key, |_| true, async { panic!("recomputed a cached answer") }).await.unwrap();
+        assert_eq!((answer.as_str(), hit), ("first", true));
+
+        // An entry older than the TTL is a miss, and the fresh answer replaces it
+        std::fs::write(cache.path(&key), r#"{"created":0,"response":"stale"}"#).unwrap();
+        assert_eq!(cache.get(&key), None);
+        let (answer, hit) = cache.get_or_compute(&key, |_| true, async { Ok("fresh".to_string()) }).await.unwrap();
+        assert_eq!((answer.as_str(), hit), ("fresh", false));
+        assert_eq!(cache.get(&key).as_deref(), Some("fresh"));
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get(&key), None);
+        assert_eq!(cache.clear().unwrap(), 0);
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejected_answers_are_not_stored() {
+        let cache = temp_cache("rejected", 3600);
+        let key = ResponseCache::key("contract C {}", "security", "ollama:codellama");
+        let (answer, hit) = cache.get_or_compute(&key, |answer| !answer.is_empty(), async { Ok(String::new()) }).await.unwrap();
+        assert_eq!((answer.as_str(), hit), ("", false));
+        assert_eq!(cache.get(&key), None);
+        assert!(!cache.dir().exists());
+    }
+}
@@ -0,0 +1,205 @@
+//! AI-proposed rewrite of the function a finding points at. The rewrite is
+//! only accepted if the file still parses and the rule that raised the
+//! finding no longer fires inside the function
+
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionTy, SourceUnitPart};
+use crate::audit::fix::{self, TextEdit};
+use crate::audit::source;
+use crate::audit::vulnerabilities::Vulnerability;
+use crate::audit::{erc, patterns, AuditResult};
+use crate::error::{AnalyzerError, Result};
+
+/// Byte range and lines of the function a finding is in
+#[derive(Debug)]
+pub struct Target {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    /// 1-based first and last line
+    pub lines: (usize, usize),
+}
+
+/// The finding `selector` names: a 1-based index in report order or a
+/// fingerprint prefix
+pub fn select<'a>(result: &'a AuditResult, selector: &str) -> Option<&'a Vulnerability> {
+    if let Ok(index) = selector.parse::<usize>() {
+        if let Some(vuln) = index.checked_sub(1).and_then(|i| result.vulnerabilities().nth(i)) {
+            return Some(vuln);
+        }
+    }
+    let mut matches = result.vulnerabilities().filter(|vuln| !vuln.fingerprint.is_empty() && vuln.fingerprint.starts_with(selector));
+    match (matches.next(), matches.next()) {
+        (Some(vuln), None) => Some(vuln),
+        _ => None,
+    }
+}
+
+/// Numbered findings with their short fingerprints, for picking one
+pub fn format_choices(result: &AuditResult) -> String {
+    result.vulnerabilities().enumerate()
+        .map(|(i, vuln)| format!("  {:>3}. {} [{:?}] {}{}\n",
+            i + 1,
+            &vuln.fingerprint[..vuln.fingerprint.len().min(12)],
+            vuln.severity,
+            vuln.name,
+            vuln.line.map(|line| format!(" (line {})", line)).unwrap_or_default()))
+        .collect()
+}
+
+/// The function containing the finding's line, or named by its `function`
+pub fn enclosing_function(source: &str, vuln: &Vulnerability) -> Option<Target> {
+    let candidates = match solang_parser::parse(source, 0) {
+        Ok((unit, _)) => solidity_functions(&unit, source),
+        Err(_) => rust_functions(source),
+    };
+    let by_line = vuln.line.and_then(|line| candidates.iter()
+        .filter(|target| target.lines.0 <= line && line <= target.lines.1)
+        // Innermost first, for nested Rust functions
+        .min_by_key(|target| target.end - target.start));
+    let by_name = || vuln.function.as_deref()
+        .and_then(|name| candidates.iter().find(|target| target.name == name));
+    let found = by_line.or_else(by_name)?;
+    Some(Target { name: found.name.clone(), ..*found })
+}
+
+fn solidity_functions(unit: &solang_parser::pt::SourceUnit, source: &str) -> Vec<Target> {
+    unit.0.iter()
+        .filter_map(|part| match part {
+            SourceUnitPart::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+        .flat_map(|contract| contract.parts.iter())
+        .filter_map(|part| {
+            let ContractPart::FunctionDefinition(func) = part else { return None };
+            let body = func.body.as_ref()?;
+            let name = match func.ty {
+                FunctionTy::Constructor => "constructor".to_string(),
+                _ => func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string()),
+            };
+            Some(target(source, name, func.loc.start(), body.loc().end()))
+        })
+        .collect()
+}
+
+fn rust_functions(source: &str) -> Vec<Target> {
+    source::rust_functions(source).into_iter()
+        .map(|(name, start, end)| target(source, name, start, end))
+        .collect()
+}
+
+fn target(source: &str, name: String, start: usize, end: usize) -> Target {
+    let line = |offset: usize| source[..offset].matches('\n').count() + 1;
+    Target { name, start, end, lines: (line(start), line(end)) }
+}
+
+/// The prompt asking for a corrected version of just `target`
+pub fn prompt(vuln: &Vulnerability, source: &str, target: &Target) -> String {
+    let language = if solang_parser::parse(source, 0).is_ok() { "Solidity" } else { "Rust (Arbitrum Stylus)" };
+    format!(
+        "You are fixing one finding from a smart contract security audit.\n\n\
+         Finding: {} ({:?})\n\
+         Risk: {}\n\
+         Recommended fix: {}\n\
+         Location: function `{}`, lines {}-{}\n\n\
+         Here is the {} function:\n\n{}\n\n\
+         Return the complete corrected function and nothing else: a single code block, \
+         with the same name and signature unless the fix requires changing them. \
+         Don't add new helper functions, imports or state.",
+        vuln.name,
+        vuln.severity,
+        vuln.risk_description,
+        vuln.recommendation,
+        target.name,
+        target.lines.0,
+        target.lines.1,
+        language,
+        &source[target.start..target.end],
+    )
+}
+
+/// Code from the model's answer, without a surrounding markdown fence
+pub fn extract_code(response: &str) -> &str {
+    let Some(fence) = response.find("```") else { return response.trim() };
+    let after = &response[fence + 3..];
+    // Skip the language tag after the opening fence
+    let body = after.find('\n').map_or(after, |i| &after[i + 1..]);
+    body.find("```").map_or(body, |end| &body[..end]).trim()
+}
+
+/// `code` with its own indentation replaced by `indent`; the first line
+/// continues the original one, which is already indented
+fn reindent(code: &str, indent: &str) -> String {
+    let own = code.lines().skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    code.lines().enumerate()
+        .map(|(i, line)| match i {
+            0 => line.trim_start().to_string(),
+            _ if line.trim().is_empty() => String::new(),
+            _ => format!("{}{}", indent, &line[own.min(line.len() - line.trim_start().len())..]),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Checks the model's rewrite of `target` and returns it as an edit: the
+/// patched file must parse and the finding's rule must stop firing there
+pub async fn validate(vuln: &Vulnerability, source: &str, target: &Target, response: &str) -> Result<TextEdit> {
+    let code = extract_code(response);
+    if code.is_empty() {
+        return Err(AnalyzerError::FixRejected("the model returned no code".to_string()));
+    }
+    let line_start = source[..target.start].rfind('\n').map_or(0, |i| i + 1);
+    let code = reindent(code, &source[line_start..target.start]);
+    let patched = format!("{}{}{}", &source[..target.start], code, &source[target.end..]);
+    if fix::check_parses(source, &patched).is_err() {
+        return Err(AnalyzerError::FixRejected(format!("the proposed `{}` doesn't parse", target.name)));
+    }
+
+    let rewritten = self::target(&patched, target.name.clone(), target.start, target.start + code.len());
+    let findings = rerun_rule(vuln, &patched).await?;
+    let still_fires = findings.iter()
+        .filter(|found| found.name == vuln.name)
+        .find(|found| match (found.line, found.function.as_deref()) {
+            (Some(line), _) => rewritten.lines.0 <= line && line <= rewritten.lines.1,
+            (None, Some(function)) => function == target.name,
+            // Whole-file findings can't be pinned to the function, so any hit counts
+            (None, None) => true,
+        });
+    match still_fires {
+        Some(found) => Err(AnalyzerError::FixRejected(format!("the proposed `{}` still triggers {}{}",
+            target.name,
+            found.name,
+            found.line.map(|line| format!(" on line {}", line)).unwrap_or_default()))),
+        None => Ok(minimal_edit(source, target, &code)),
+    }
+}
+
+/// Replacement of `target` by `code`, trimmed to the lines that differ so
+/// the diff shows only those
+fn minimal_edit(source: &str, target: &Target, code: &str) -> TextEdit {
+    let old: Vec<&str> = source[target.start..target.end].split_inclusive('\n').collect();
+    let new: Vec<&str> = code.split_inclusive('\n').collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let bytes = |lines: &[&str]| lines.iter().map(|line| line.len()).sum::<usize>();
+    TextEdit {
+        start: target.start + bytes(&old[..prefix]),
+        end: target.end - bytes(&old[old.len() - suffix..]),
+        replacement: new[prefix..new.len() - suffix].concat(),
+    }
+}
+
+/// Findings of the rule that raised `vuln`, run over `source`
+async fn rerun_rule(vuln: &Vulnerability, source: &str) -> Result<Vec<Vulnerability>> {
+    let name = vuln.rule.as_deref().unwrap_or_default();
+    if name == erc::RULE_NAME {
+        return Ok(erc::check(source, None)?.map(|conformance| conformance.findings()).unwrap_or_default());
+    }
+    let rule = patterns::all_rules().into_iter()
+        .find(|rule| rule.name() == name)
+        .ok_or_else(|| AnalyzerError::Rule { rule: name.to_string(), message: "rule not found in registry".to_string() })?;
+    rule.check(source).await
+}
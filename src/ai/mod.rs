@@ -1,7 +1,89 @@
-use std::error::Error;
-use rig::{completion::Prompt, providers::openai};
+use crate::error::{AnalyzerError, Result};
 use colored::*;
 use dotenv::dotenv;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, info, trace};
+
+pub mod backend;
+pub mod cache;
+pub mod fix;
+pub mod redact;
+pub(crate) mod response;
+
+use backend::{CompletionBackend, Provider};
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+static PROVIDER: OnceLock<Provider> = OnceLock::new();
+static MODEL: OnceLock<String> = OnceLock::new();
+static BASE_URL: OnceLock<String> = OnceLock::new();
+
+/// Turns every AI call off for the rest of the run (`--no-ai`)
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// False under `--no-ai`; analyzers then report only their static checks
+pub fn enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed)
+}
+
+/// Sends every request of the run to `provider` (`--provider`,
+/// `STYLUS_ANALYZER_PROVIDER` or the config file). Only the first call counts
+pub fn set_provider(provider: Provider) {
+    let _ = PROVIDER.set(provider);
+}
+
+pub fn provider() -> Provider {
+    PROVIDER.get().copied().unwrap_or_default()
+}
+
+/// Sends every request of the run to `model` (`--model` or the config file).
+/// Only the first call counts
+pub fn set_model(model: &str) {
+    let _ = MODEL.set(model.to_string());
+}
+
+/// The model requests go to, by default the provider's
+pub fn model() -> &'static str {
+    MODEL.get().map(String::as_str).unwrap_or(provider().default_model())
+}
+
+/// Endpoint from the config file, used when the provider's environment
+/// variable is unset. Only the first call counts
+pub fn set_base_url(url: &str) {
+    let _ = BASE_URL.set(url.to_string());
+}
+
+/// The provider's endpoint: `OPENAI_BASE_URL` or `OLLAMA_HOST` (also read
+/// from `.env`), else the config file, else the provider's default
+pub fn base_url() -> String {
+    dotenv().ok();
+    let provider = provider();
+    provider.resolve_base_url(std::env::var(provider.base_url_var()).ok().as_deref(), BASE_URL.get().map(String::as_str))
+}
+
+/// The backend for the configured provider, model and endpoint
+pub fn backend() -> Result<Box<dyn CompletionBackend>> {
+    backend::for_provider(provider(), model(), &base_url(), api_key)
+}
+
+/// The OpenAI key from the environment or a `.env` file; an empty key counts as missing
+pub fn api_key() -> Result<String> {
+    dotenv().ok();
+    std::env::var("OPENAI_API_KEY").ok()
+        .filter(|key| !key.trim().is_empty())
+        .ok_or(AnalyzerError::MissingApiKey)
+}
+
+/// Refuses a request under `--no-ai`, so nothing reaches the provider by mistake
+fn ensure_enabled() -> Result<()> {
+    if enabled() {
+        Ok(())
+    } else {
+        Err(AnalyzerError::Ai("AI analysis is turned off (--no-ai)".to_string()))
+    }
+}
 
 #[derive(Debug)]
 pub struct AnalysisContext {
@@ -20,6 +102,12 @@ pub struct ChatMessage {
     pub content: String,
 }
 
+impl Default for AnalysisContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AnalysisContext {
     pub fn new() -> Self {
         Self {
@@ -127,11 +215,18 @@ impl AnalysisContext {
     }
 }
 
-pub async fn analyze_with_context(content: &str, context: &mut AnalysisContext) -> Result<String, Box<dyn Error + Send + Sync>> {
-    dotenv().ok();
-    let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY must be set in .env file");
-    let openai_client = openai::Client::new(api_key.as_str());
-    let gpt = openai_client.model("gpt-4-turbo-preview").build();
+pub async fn analyze_with_context(content: &str, context: &mut AnalysisContext) -> Result<String> {
+    ensure_enabled()?;
+    analyze_with_backend(backend()?.as_ref(), content, context).await
+}
+
+/// `analyze_with_context` against an explicit backend
+pub async fn analyze_with_backend<B: CompletionBackend + ?Sized>(backend: &B, content: &str, context: &mut AnalysisContext) -> Result<String> {
+    ensure_enabled()?;
+    // Never ship megabytes of source to the provider
+    crate::input::check_ai_input(content)?;
+    let redacted = redact::redact(content);
+    redacted.announce();
 
     // Add system message to chat history
     context.add_chat_message(
@@ -173,26 +268,37 @@ pub async fn analyze_with_context(content: &str, context: &mut AnalysisContext)
         context.security_concerns.join(", "),
         context.optimization_suggestions.join(", "),
         context.complexity_metrics.join(", "),
-        content
+        redacted.text
     );
 
     // Add analysis request to chat history
     context.add_chat_message("user", "Please analyze this smart contract.");
 
-    let response = gpt.prompt(&contextual_prompt).await?;
-
-    // Clean up any remaining markdown syntax from the response
-    let cleaned_response = response
-        .lines()
-        .map(|line| {
-            line.trim_start_matches("###")
-                .trim_start_matches("**")
-                .trim_end_matches("**")
-                .trim_start_matches("- ")
-                .trim()
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+    let request = async {
+        let mut cleaned_response = response::clean_response(&prompt_logged(backend, &contextual_prompt).await?);
+
+        // Empty answers and refusals get one retry with a corrective instruction
+        if response::is_unusable(&cleaned_response) {
+            debug!("unusable AI response; retrying once");
+            let retry_prompt = format!("{}{}", contextual_prompt, response::RETRY_INSTRUCTION);
+            cleaned_response = response::clean_response(&prompt_logged(backend, &retry_prompt).await?);
+        }
+        Ok(cleaned_response)
+    };
+    // Keyed on the contract alone: the rest of the prompt is fixed per analysis type
+    let (cleaned_response, cached) = match cache::ResponseCache::open() {
+        Some(cache) => {
+            let key = cache::ResponseCache::key(content, &context.contract_type, &backend.describe());
+            cache.get_or_compute(&key, |answer| !response::is_unusable(answer), request).await?
+        }
+        None => (request.await?, false),
+    };
+    if cached {
+        info!("ℹ️  {}: cached result reused", context.contract_type);
+    }
+    if response::is_unusable(&cleaned_response) {
+        return Ok(response::unavailable_section(&context.contract_type));
+    }
 
     // Add AI response to chat history
     context.add_chat_message("assistant", &cleaned_response);
@@ -200,7 +306,30 @@ pub async fn analyze_with_context(content: &str, context: &mut AnalysisContext)
     update_context_from_response(&cleaned_response, context);
 
     // Return the combined analysis
-    Ok(format!("{}\n\n{}", cleaned_response, context.generate_summary()))
+    let note = if cached { format!("{}\n\n", cache::CACHED_NOTE.cyan().bold()) } else { String::new() };
+    Ok(format!("{}{}\n\n{}", note, cleaned_response, context.generate_summary()))
+}
+
+/// Sends one standalone prompt and returns the raw answer. Never cached, so
+/// asking again gets a fresh answer.
+pub async fn complete(prompt: &str) -> Result<String> {
+    ensure_enabled()?;
+    crate::input::check_ai_input(prompt)?;
+
+    // Code in the answer gets the real values back before it is applied
+    let redacted = redact::redact(prompt);
+    redacted.announce();
+    let answer = prompt_logged(backend()?.as_ref(), &redacted.text).await?;
+    Ok(redacted.restore(&answer))
+}
+
+/// Sends `prompt`, tracing the sizes of the request and the answer
+async fn prompt_logged<B: CompletionBackend + ?Sized>(backend: &B, prompt: &str) -> Result<String> {
+    trace!(prompt_bytes = prompt.len(), backend = %backend.describe(), "sending AI request");
+    let started = std::time::Instant::now();
+    let answer = backend.complete(prompt).await?;
+    trace!(response_bytes = answer.len(), elapsed_ms = started.elapsed().as_millis() as u64, "AI response received");
+    Ok(answer)
 }
 
 fn update_context_from_response(response: &str, context: &mut AnalysisContext) {
@@ -250,55 +379,55 @@ fn update_context_from_response(response: &str, context: &mut AnalysisContext) {
     }
 }
 
-pub async fn analyze_gas_usage(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_gas_usage(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Gas Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_contract_size(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_contract_size(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Size Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_security_issues(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_security_issues(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Security Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_upgrade_patterns(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_upgrade_patterns(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Upgrade Pattern Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_function_complexity(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_function_complexity(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Function Complexity Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_contract_interactions(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_contract_interactions(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Contract Interactions Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_stylus_patterns(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_stylus_patterns(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Stylus Pattern Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_error_patterns(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_error_patterns(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Error Pattern Analysis".to_string();
     analyze_with_context(content, &mut context).await
 }
 
-pub async fn analyze_code_quality(content: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+pub async fn analyze_code_quality(content: &str) -> Result<String> {
     let mut context = AnalysisContext::new();
     context.contract_type = "Code Quality Analysis".to_string();
     analyze_with_context(content, &mut context).await
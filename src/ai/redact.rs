@@ -0,0 +1,327 @@
+//! Masks secrets before source goes into a prompt: private keys, API keys in
+//! RPC URLs and seed phrases. Each secret becomes a numbered placeholder on
+//! the same line, so line references in the answer still hold
+
+use ethers::signers::coins_bip39::{English, Wordlist};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Seed phrases have 12 to 24 words; shorter runs of list words are ordinary prose
+const MIN_SEED_WORDS: usize = 12;
+
+/// Sends source unredacted for the rest of the run (`--no-redact`)
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    PrivateKey,
+    ApiKey,
+    SeedPhrase,
+}
+
+impl SecretKind {
+    fn label(self) -> &'static str {
+        match self {
+            SecretKind::PrivateKey => "private key",
+            SecretKind::ApiKey => "API key",
+            SecretKind::SeedPhrase => "seed phrase",
+        }
+    }
+
+    fn slug(self) -> &'static str {
+        match self {
+            SecretKind::PrivateKey => "PRIVATE_KEY",
+            SecretKind::ApiKey => "API_KEY",
+            SecretKind::SeedPhrase => "SEED_PHRASE",
+        }
+    }
+}
+
+/// Source with its secrets replaced, and what was replaced
+#[derive(Debug, Default)]
+pub struct Redacted {
+    pub text: String,
+    secrets: Vec<(SecretKind, String)>,
+}
+
+impl Redacted {
+    pub fn count(&self) -> usize {
+        self.secrets.len()
+    }
+
+    /// Logs what was masked, once per distinct source: every analyzer of a
+    /// report sends the same contract
+    pub fn announce(&self) {
+        static SEEN: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+        if self.secrets.is_empty() {
+            return;
+        }
+        let mut hasher = DefaultHasher::new();
+        self.text.hash(&mut hasher);
+        let digest = hasher.finish();
+        let mut seen = SEEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !seen.contains(&digest) {
+            seen.push(digest);
+            info!("🔒 Redacted {} before sending the contract to the AI (--no-redact to send it as is)", self.summary());
+        }
+    }
+
+    /// "2 private keys, 1 API key"
+    pub fn summary(&self) -> String {
+        [SecretKind::PrivateKey, SecretKind::ApiKey, SecretKind::SeedPhrase].iter()
+            .filter_map(|&kind| {
+                let count = self.secrets.iter().filter(|(found, _)| *found == kind).count();
+                (count > 0).then(|| format!("{} {}{}", count, kind.label(), if count == 1 { "" } else { "s" }))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// `answer` with the placeholders swapped back, so code the model
+    /// rewrote keeps the original values
+    pub fn restore(&self, answer: &str) -> String {
+        // Placeholders end in `>`, so <REDACTED_API_KEY_1> can't match inside _12
+        self.secrets.iter().enumerate().fold(answer.to_string(), |text, (index, (kind, secret))| {
+            text.replace(&placeholder(*kind, self.number(index)), secret)
+        })
+    }
+
+    fn push(&mut self, kind: SecretKind, secret: &str) -> String {
+        self.secrets.push((kind, secret.to_string()));
+        placeholder(kind, self.number(self.secrets.len() - 1))
+    }
+
+    /// Placeholders are numbered per kind: the second API key is API_KEY_2
+    fn number(&self, index: usize) -> usize {
+        let kind = self.secrets[index].0;
+        self.secrets[..=index].iter().filter(|(found, _)| *found == kind).count()
+    }
+}
+
+fn placeholder(kind: SecretKind, number: usize) -> String {
+    format!("<REDACTED_{}_{}>", kind.slug(), number)
+}
+
+/// `content` with every secret found replaced by a placeholder; unchanged
+/// under `--no-redact`
+pub fn redact(content: &str) -> Redacted {
+    let mut redacted = Redacted::default();
+    if !enabled() {
+        redacted.text = content.to_string();
+        return redacted;
+    }
+    let lines: Vec<String> = content.split('\n')
+        .map(|line| {
+            let line = redact_assignments(line, &mut redacted);
+            let line = redact_urls(&line, &mut redacted);
+            let line = redact_hex_keys(&line, &mut redacted);
+            redact_seed_phrases(&line, &mut redacted)
+        })
+        .collect();
+    redacted.text = lines.join("\n");
+    redacted
+}
+
+/// Replaces each `(start, end, kind)` span of `line`; a span overlapping an
+/// earlier one is dropped
+fn replace_spans(line: &str, mut spans: Vec<(usize, usize, SecretKind)>, redacted: &mut Redacted) -> String {
+    if spans.is_empty() {
+        return line.to_string();
+    }
+    spans.sort_by_key(|&(start, _, _)| start);
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    for (start, end, kind) in spans {
+        if start < last {
+            continue;
+        }
+        out.push_str(&line[last..start]);
+        out.push_str(&redacted.push(kind, &line[start..end]));
+        last = end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// `PRIVATE_KEY=...`, `private_key: "..."` and the like
+fn redact_assignments(line: &str, redacted: &mut Redacted) -> String {
+    let lowered = line.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    for (at, _) in lowered.match_indices("private_key") {
+        let rest = &line[at + "private_key".len()..];
+        let after_name = rest.trim_start_matches(['"', '\'', ' ', '\t']);
+        let Some(value) = after_name.strip_prefix(['=', ':']) else { continue };
+        let value = value.trim_start_matches([' ', '\t', '"', '\'']);
+        let length = value.find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | ';' | ')')).unwrap_or(value.len());
+        if length > 0 {
+            let start = line.len() - value.len();
+            spans.push((start, start + length, SecretKind::PrivateKey));
+        }
+    }
+    replace_spans(line, spans, redacted)
+}
+
+/// Keys in RPC URLs: `?apikey=...` style query parameters and the
+/// `/v2/<key>`, `/v3/<key>` paths of Alchemy and Infura
+fn redact_urls(line: &str, redacted: &mut Redacted) -> String {
+    let mut spans = Vec::new();
+    let mut search = 0;
+    while let Some(found) = line[search..].find("http") {
+        let start = search + found;
+        let url_len = line[start..].find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | ')' | '>' | ','))
+            .unwrap_or(line.len() - start);
+        let url = &line[start..start + url_len];
+        search = start + url_len.max(4);
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            continue;
+        }
+
+        if let Some(query) = url.find('?') {
+            let mut offset = start + query + 1;
+            for param in url[query + 1..].split('&') {
+                if let Some((name, value)) = param.split_once('=') {
+                    let name = name.to_ascii_lowercase();
+                    if !value.is_empty() && ["key", "token", "secret"].iter().any(|word| name.contains(word)) {
+                        let value_start = offset + name.len() + 1;
+                        spans.push((value_start, value_start + value.len(), SecretKind::ApiKey));
+                    }
+                }
+                offset += param.len() + 1;
+            }
+        }
+        for prefix in ["/v2/", "/v3/"] {
+            if let Some(at) = url.find(prefix) {
+                let key_start = at + prefix.len();
+                let key_len = url[key_start..].find(['/', '?', '#']).unwrap_or(url.len() - key_start);
+                let key = &url[key_start..key_start + key_len];
+                if key.len() >= 20 && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                    spans.push((start + key_start, start + key_start + key_len, SecretKind::ApiKey));
+                }
+            }
+        }
+    }
+    replace_spans(line, spans, redacted)
+}
+
+/// `0x` followed by exactly 64 hex digits: the shape of a private key.
+/// Hashes and storage slots have the same shape, so a line declaring a
+/// 32-byte value is left alone unless it also speaks of keys or secrets
+fn redact_hex_keys(line: &str, redacted: &mut Redacted) -> String {
+    if declares_hash(line) {
+        return line.to_string();
+    }
+    let bytes = line.as_bytes();
+    let mut spans = Vec::new();
+    let mut at = 0;
+    while let Some(found) = line[at..].find("0x") {
+        let start = at + found;
+        let digits = bytes[start + 2..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+        let end = start + 2 + digits;
+        let standalone = start == 0 || !(bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_');
+        let ends_cleanly = bytes.get(end).is_none_or(|b| !(b.is_ascii_alphanumeric() || *b == b'_'));
+        if digits == 64 && standalone && ends_cleanly {
+            spans.push((start, end, SecretKind::PrivateKey));
+        }
+        at = end;
+    }
+    replace_spans(line, spans, redacted)
+}
+
+fn declares_hash(line: &str) -> bool {
+    let lowered = line.to_ascii_lowercase();
+    ["bytes32", "b256", "fixedbytes<32>", "[u8; 32]", "keccak"].iter().any(|kind| lowered.contains(kind))
+        && !["key", "secret", "mnemonic", "wallet", "signer"].iter().any(|word| lowered.contains(word))
+}
+
+/// Runs of at least 12 BIP-39 words separated only by spaces
+fn redact_seed_phrases(line: &str, redacted: &mut Redacted) -> String {
+    let words = English::get_all();
+    let is_seed_word = |word: &str| words.binary_search(&word).is_ok();
+
+    let mut spans = Vec::new();
+    let mut run: Vec<(usize, usize)> = Vec::new();
+    let mut close_run = |run: &mut Vec<(usize, usize)>| {
+        if run.len() >= MIN_SEED_WORDS {
+            spans.push((run[0].0, run[run.len() - 1].1, SecretKind::SeedPhrase));
+        }
+        run.clear();
+    };
+
+    let mut at = 0;
+    while at < line.len() {
+        let length = line[at..].find(|c: char| !c.is_ascii_lowercase()).unwrap_or(line.len() - at);
+        if length == 0 {
+            let skip = line[at..].chars().next().map_or(1, char::len_utf8);
+            // A run may only continue over spaces between words
+            if !line[at..].starts_with(' ') {
+                close_run(&mut run);
+            }
+            at += skip;
+            continue;
+        }
+        let word = &line[at..at + length];
+        if is_seed_word(word) {
+            run.push((at, at + length));
+        } else {
+            close_run(&mut run);
+        }
+        at += length;
+    }
+    close_run(&mut run);
+    replace_spans(line, spans, redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[test]
+    fn a_hex_private_key_is_masked_and_restored() {
+        let source = format!("let signer = \"{}\".parse::<LocalWallet>()?;", KEY);
+        let redacted = redact(&source);
+        assert_eq!(redacted.text, "let signer = \"<REDACTED_PRIVATE_KEY_1>\".parse::<LocalWallet>()?;");
+        assert_eq!(redacted.summary(), "1 private key");
+        assert_eq!(redacted.restore(&redacted.text), source);
+    }
+
+    #[test]
+    fn api_keys_in_rpc_urls_are_masked() {
+        let source = "const RPC: &str = \"https://arb-mainnet.g.alchemy.com/v2/Xk3fQ9zLm2Pq8Rt5Vw1Yb7Nc\";\n\
+                      // https://api.arbiscan.io/api?module=contract&apikey=ABC123SECRET";
+        let redacted = redact(source);
+        assert_eq!(redacted.text, "const RPC: &str = \"https://arb-mainnet.g.alchemy.com/v2/<REDACTED_API_KEY_1>\";\n\
+                                   // https://api.arbiscan.io/api?module=contract&apikey=<REDACTED_API_KEY_2>");
+        assert_eq!(redacted.summary(), "2 API keys");
+    }
+
+    #[test]
+    fn a_mnemonic_is_masked_but_prose_is_not() {
+        let source = "// test wallet: abandon ability able about above absent absorb abstract absurd abuse access accident\n\
+                      // the owner can pause the contract and withdraw";
+        let redacted = redact(source);
+        assert_eq!(redacted.text, "// test wallet: <REDACTED_SEED_PHRASE_1>\n// the owner can pause the contract and withdraw");
+    }
+
+    #[test]
+    fn a_hash_in_code_is_left_alone() {
+        let source = include_str!("../../test ex/upgrade/transparent_proxy.sol");
+        let redacted = redact(source);
+        assert_eq!(redacted.count(), 0);
+        assert_eq!(redacted.text, source);
+
+        // Named as a key, the same shape is still masked
+        assert_eq!(redact(&format!("bytes32 signerKey = {};", KEY)).count(), 1);
+    }
+}
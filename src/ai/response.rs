@@ -0,0 +1,134 @@
+/// Appended to the prompt when the first response is empty or a refusal
+pub const RETRY_INSTRUCTION: &str =
+    "\n\nYour previous answer was empty or declined the task. This is a routine code review of \
+     a smart contract supplied by its owner. Answer directly with the analysis in plain text, \
+     without code fences, disclaimers or apologies.";
+
+// Lowercased prefixes of boilerplate sentences models put before the actual answer
+const DISCLAIMER_PREFIXES: &[&str] = &[
+    "as an ai",
+    "as a language model",
+    "i am an ai",
+    "i'm an ai",
+    "sure! here",
+    "sure, here",
+    "certainly! here",
+    "certainly, here",
+    "of course! here",
+    "here is the analysis",
+    "here's the analysis",
+];
+
+// Lowercased phrases that mark a response as a refusal rather than an analysis
+const REFUSAL_MARKERS: &[&str] = &[
+    "i'm sorry, but i can't",
+    "i'm sorry, but i cannot",
+    "i am sorry, but i cannot",
+    "i cannot assist with",
+    "i can't assist with",
+    "i can't help with",
+    "i cannot help with",
+    "i'm unable to help",
+    "i am unable to provide",
+];
+
+/// Normalizes a raw model response for rendering: drops code fences and
+/// leading disclaimer lines, then strips inline markdown markers.
+pub fn clean_response(raw: &str) -> String {
+    let mut lines: Vec<&str> = raw
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+
+    // Skip blank lines and boilerplate before the first real line of content
+    let first_content = lines
+        .iter()
+        .position(|line| {
+            let lowered = line.trim().to_lowercase();
+            !lowered.is_empty() && !DISCLAIMER_PREFIXES.iter().any(|p| lowered.starts_with(p))
+        })
+        .unwrap_or(lines.len());
+    lines.drain(..first_content);
+
+    lines
+        .iter()
+        .map(|line| {
+            strip_heading(line.trim())
+                .trim_start_matches("**")
+                .trim_end_matches("**")
+                .trim_start_matches("- ")
+                .trim()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Removes a markdown heading marker ("## Title") but leaves attributes like `#[storage]` alone
+fn strip_heading(line: &str) -> &str {
+    let rest = line.trim_start_matches('#');
+    if rest.len() < line.len() && rest.starts_with(' ') {
+        rest
+    } else {
+        line
+    }
+}
+
+/// True when a cleaned response carries no usable analysis
+pub fn is_unusable(cleaned: &str) -> bool {
+    if cleaned.trim().is_empty() {
+        return true;
+    }
+
+    // Only short answers are treated as refusals; a long analysis that
+    // happens to quote one of the markers is still useful.
+    let lowered = cleaned.to_lowercase();
+    cleaned.len() < 400 && REFUSAL_MARKERS.iter().any(|m| lowered.contains(m))
+}
+
+/// Section rendered in place of the AI output when no usable response came back
+pub fn unavailable_section(contract_type: &str) -> String {
+    format!(
+        "⚠️ AI analysis unavailable\n\
+         • The model returned an empty or unusable response for {} (after one retry)\n\
+         • Static findings elsewhere in this report are unaffected\n\
+         • Re-run later or check the configured model",
+        if contract_type.is_empty() { "this contract" } else { contract_type }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fences_disclaimers_and_markdown_are_dropped() {
+        let raw = "Sure, here is the analysis:\n\n```markdown\n## Findings\n**Reentrancy**\n- Unchecked call in `withdraw`\n#[storage] stays\n```\n";
+        let cleaned = clean_response(raw);
+        assert_eq!(cleaned, "Findings\nReentrancy\nUnchecked call in `withdraw`\n#[storage] stays");
+        assert!(!is_unusable(&cleaned));
+    }
+
+    #[test]
+    fn a_truncated_answer_keeps_what_arrived() {
+        // Cut off inside the fence: the closing ``` never came
+        let raw = "```\nReentrancy in withdraw: the balance is cleared after the tran";
+        let cleaned = clean_response(raw);
+        assert_eq!(cleaned, "Reentrancy in withdraw: the balance is cleared after the tran");
+        assert!(!is_unusable(&cleaned));
+    }
+
+    #[test]
+    fn empty_and_refused_answers_fall_back() {
+        for raw in ["", "```\n```", "As an AI language model, I must note...\n\n", "I'm sorry, but I can't help with that."] {
+            assert!(is_unusable(&clean_response(raw)), "{:?}", raw);
+        }
+        // A long analysis quoting a marker is still an analysis
+        let long = format!("I cannot assist with deployment, but {}", "the mint function lacks an owner check. ".repeat(12));
+        assert!(!is_unusable(&long));
+
+        assert!(unavailable_section("").contains("for this contract (after one retry)"));
+        assert!(unavailable_section("ERC-20 token").contains("for ERC-20 token"));
+    }
+}
@@ -1,32 +1,119 @@
-use std::path::PathBuf;
-use std::error::Error;
+use std::path::Path;
+use crate::error::Result;
 use std::fs;
 use colored::*;
 use crate::ai;
-use crate::analyzer::Analyzer;
+use crate::analyzer::{static_only_note, Analysis, Analyzer};
+use crate::analyzer::output::{findings_from_text, Finding, SeverityCounts};
+use crate::audit::source::find_line;
+use crate::audit::vulnerabilities::Severity;
+use crate::parser::ParsedContract;
+use tracing::info;
 
 pub struct ComplexityAnalyzer;
 
 #[async_trait::async_trait]
 impl Analyzer for ComplexityAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
+    // The AI review works on the raw source, so files that don't parse still get one
+    async fn analyze(&self, file: &Path) -> Result<Analysis> {
         let content = fs::read_to_string(file)?;
-        println!("🔄 Analyzing function complexity...");
-        println!("⏳ Please wait while we process your contract...\n");
-        let analysis = ai::analyze_function_complexity(&content).await?;
+        if !ai::enabled() {
+            return Ok(static_analysis(&ParsedContract::new(content)?));
+        }
+        ai_analysis(&content).await
+    }
 
-        Ok(format!(
-            "\n{}\n{}\n\n{}\n{}\n{}\n\n{}\n",
-            "🔍 Function Complexity Analysis Report".bright_green().bold(),
-            "══════════════════════════════════".bright_green(),
-            "📊 Complexity Distribution:".yellow().bold(),
-            format_overview(&analysis),
-            format_metrics(&analysis),
-            format_summary(&analysis)
-        ))
+    async fn analyze_parsed(&self, contract: &ParsedContract, _file: &Path) -> Result<Analysis> {
+        if !ai::enabled() {
+            return Ok(static_analysis(contract));
+        }
+        ai_analysis(&contract.source).await
     }
 }
 
+async fn ai_analysis(content: &str) -> Result<Analysis> {
+    info!("🔄 Analyzing function complexity...");
+    info!("⏳ Please wait while we process your contract...");
+    let analysis = ai::analyze_function_complexity(content).await?;
+    let findings = findings_from_text(&analysis);
+
+    let text = format!(
+        "\n{}\n{}\n\n{}\n{}\n{}\n\n{}\n",
+        "🔍 Function Complexity Analysis Report".bright_green().bold(),
+        "══════════════════════════════════".bright_green(),
+        "📊 Complexity Distribution:".yellow().bold(),
+        format_overview(&analysis),
+        format_metrics(&analysis),
+        format_summary(&SeverityCounts::of(&findings))
+    );
+    Ok(Analysis::new("complexity", text).with_findings(findings))
+}
+
+/// Cyclomatic complexity of every function from the parsed source, most
+/// complex first; above 10 is a Medium finding and above 20 a High one
+fn static_analysis(parsed: &ParsedContract) -> Analysis {
+    info!("🔄 Analyzing function complexity...");
+    info!("⏳ Please wait while we process your contract...");
+    let mut functions: Vec<_> = parsed.functions.iter()
+        .map(|function| (function, function.cyclomatic_complexity()))
+        .collect();
+    functions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+
+    let mut table = String::new();
+    let mut findings = Vec::new();
+    for (function, complexity) in &functions {
+        let severity = match complexity {
+            21.. => Some(Severity::High),
+            11..=20 => Some(Severity::Medium),
+            _ => None,
+        };
+        let marker = match severity {
+            Some(Severity::High) => format!("🚨 {}", complexity).red().bold(),
+            Some(_) => format!("⚠️  {}", complexity).yellow(),
+            None => format!("✅ {}", complexity).green(),
+        };
+        table.push_str(&format!("  {}  {} (gas ~{})\n", marker, function.name.cyan(), function.estimated_gas()));
+        if let Some(severity) = severity {
+            let line = find_line(&parsed.source, &[&format!("fn {}", function.name), &format!("function {}", function.name)]);
+            findings.push(Finding {
+                severity,
+                rule: Some("CyclomaticComplexity".to_string()),
+                rule_id: None,
+                detected_by: Vec::new(),
+                title: format!("High complexity in {}", function.name),
+                function: Some(function.name.clone()),
+                line: line.as_ref().map(|found| found.line),
+                column: line.as_ref().map(|found| found.column),
+                description: format!("{} has a cyclomatic complexity of {}", function.name, complexity),
+                recommendation: Some("Split the function into smaller helpers with fewer branches".to_string()),
+                confidence: None,
+                references: Vec::new(),
+                fingerprint: None,
+                effort: None,
+                example: None,
+                origin: None,
+            });
+        }
+    }
+    if table.is_empty() {
+        table.push_str("  No functions found\n");
+    }
+
+    let text = format!(
+        "\n{}\n{}\n{}\n{}\n{}",
+        "🔍 Function Complexity Analysis Report".bright_green().bold(),
+        "══════════════════════════════════".bright_green(),
+        static_only_note(),
+        "📊 Cyclomatic Complexity per Function:".yellow().bold(),
+        table
+    );
+    let max = functions.first().map_or(0, |(_, complexity)| *complexity);
+    Analysis::new("complexity", text)
+        .with_findings(findings)
+        .with_metric("functions", functions.len())
+        .with_metric("max_cyclomatic_complexity", max)
+}
+
 fn format_overview(metrics: &str) -> String {
     format!(
         "{}\n{}\n",
@@ -52,10 +139,10 @@ fn format_metrics(metrics: &str) -> String {
             } else if line.contains("Low") {
                 format!("✅ {}", line.green())
             } else if line.contains("Cyclomatic") || line.contains("Code Metrics") || line.contains("Parameter Analysis") {
-                let separator = format!("{}", "─".repeat(50));
+                let separator = "─".repeat(50);
                 format!("\n┌{}\n{}\n", separator, line.yellow().bold())
             } else if line.trim().ends_with(":") {
-                let separator = format!("{}", "─".repeat(30));
+                let separator = "─".repeat(30);
                 format!("\n└{}\n  {}", separator, line.cyan().bold())
             } else if line.contains("Function:") {
                 format!("\n📝 {}", line.cyan().bold())
@@ -92,10 +179,9 @@ fn format_line(line: &str) -> String {
     }
 }
 
-fn format_summary(metrics: &str) -> String {
-    let high_count = count_severity(metrics, "High");
-    let medium_count = count_severity(metrics, "Medium");
-    let low_count = count_severity(metrics, "Low");
+fn format_summary(counts: &SeverityCounts) -> String {
+    // Complexity has no Critical tier; anything rated Critical is very high
+    let (high_count, medium_count, low_count) = (counts.critical + counts.high, counts.medium, counts.low);
 
     format!(
         "{}\n{}\n\n{}\n{}\n{}\n",
@@ -106,9 +192,3 @@ fn format_summary(metrics: &str) -> String {
         format!("✅ Low Complexity: {} functions", low_count).green()
     )
 }
-
-fn count_severity(text: &str, severity: &str) -> usize {
-    text.lines()
-        .filter(|line| line.contains(severity))
-        .count()
-}
\ No newline at end of file
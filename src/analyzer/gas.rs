@@ -1,21 +1,22 @@
-use std::path::PathBuf;
-use std::error::Error;
-use std::fs;
+use std::path::Path;
+use crate::error::Result;
 use colored::*;
 use crate::ai;
-use crate::analyzer::Analyzer;
+use crate::analyzer::{gas_rules, static_only_note, Analysis, Analyzer};
+use crate::analyzer::output::{findings_from_text, Finding, SeverityCounts};
+use crate::audit::vulnerabilities::Severity;
 use crate::parser::ParsedContract;
 use crate::parser::ContractType;
+use tracing::info;
 
 pub struct GasAnalyzer;
 
 #[async_trait::async_trait]
 impl Analyzer for GasAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-        println!("\n🔍 Starting Stylus Contract Analysis...");
+    async fn analyze_parsed(&self, parsed: &ParsedContract, _file: &Path) -> Result<Analysis> {
+        info!("🔍 Starting Stylus Contract Analysis...");
 
-        let content = fs::read_to_string(file)?;
-        let parsed = ParsedContract::new(content.clone())?;
+        let content = &parsed.source;
 
         // Initialize AI context with contract type
         let mut context = crate::ai::AnalysisContext::new();
@@ -24,48 +25,64 @@ impl Analyzer for GasAnalyzer {
             ContractType::Stylus => "Stylus".to_string(),
         };
 
-        println!("⚡ Analyzing gas patterns...");
-        let analysis = ai::analyze_gas_usage(&content).await?;
+        info!("⚡ Analyzing gas patterns...");
+        if !ai::enabled() {
+            return Ok(static_analysis(content, parsed));
+        }
+        let analysis = ai::analyze_gas_usage(content).await?;
 
         let contract_patterns = parsed.analyze_patterns();
         let gas_patterns = parsed.analyze_gas_patterns();
 
         // Enhanced analysis with L2-specific insights
-        let l2_analysis = analyze_l2_patterns(&content);
-        let stylus_patterns = format_stylus_patterns(&analysis, &parsed);
-        let memory_analysis = analyze_memory_patterns(&content);
+        let l2_analysis = analyze_l2_patterns(content);
+        let stylus_patterns = format_stylus_patterns(&analysis, parsed);
+        let memory_analysis = analyze_memory_patterns(content);
+        let event_indexing = format_event_indexing(content);
+        let (gas_findings, language) = match parsed.contract_type {
+            ContractType::Solidity => (gas_rules::solidity_gas_findings(content), "Solidity"),
+            ContractType::Stylus => (gas_rules::stylus_gas_findings(content), "Stylus"),
+        };
+        let micro_optimizations = gas_rules::format_gas_findings(&gas_findings, language);
         let environmental = format_environmental_impact(&analysis);
-        let recommendations = generate_recommendations(&contract_patterns, &gas_patterns, &parsed);
-        let summary = format_summary(&analysis);
+        let recommendations = generate_recommendations(&contract_patterns, &gas_patterns, parsed);
+        let ai_findings = findings_from_text(&analysis);
+        let summary = format_summary(&SeverityCounts::of(&ai_findings));
 
-        println!("📊 Generating final report...");
-        println!("✨ Analysis complete!\n");
+        info!("📊 Generating final report...");
+        info!("✨ Analysis complete!");
 
         // Include follow-up questions and improvements in the report
-        let follow_ups = self.get_follow_up_questions(&analysis, &parsed)
+        let follow_ups = self.get_follow_up_questions(&analysis, parsed)
             .iter()
             .map(|q| format!("❓ {}", q))
             .collect::<Vec<_>>()
             .join("\n");
 
-        let improvements = self.get_suggested_improvements(&analysis, &parsed)
+        let improvements = self.get_suggested_improvements(&analysis, parsed)
             .iter()
             .map(|i| format!("💡 {}", i))
             .collect::<Vec<_>>()
             .join("\n");
 
-        Ok(format!(
-            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n",
+        let text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n🤔 Follow-up Questions:\n{}\n✨ Suggested Improvements:\n{}\n",
             format_l2_metrics(&analysis),
             l2_analysis,
+            micro_optimizations,
             stylus_patterns,
             memory_analysis,
+            event_indexing,
             environmental,
             recommendations,
             summary,
-            format!("🤔 Follow-up Questions:\n{}", follow_ups),
-            format!("✨ Suggested Improvements:\n{}", improvements)
-        ))
+            follow_ups,
+            improvements
+        );
+        Ok(Analysis::new("gas", text)
+            .with_findings(located_findings(&gas_findings))
+            .with_findings(ai_findings)
+            .with_metric("estimated_gas", extract_total_gas(&analysis)))
     }
 
     fn get_follow_up_questions(&self, analysis: &str, parsed: &ParsedContract) -> Vec<String> {
@@ -136,6 +153,50 @@ impl Analyzer for GasAnalyzer {
     }
 }
 
+/// The gas report without the AI review: the source-level checks, with the
+/// conversion guide keyed on the contract itself and the static gas estimate
+fn static_analysis(content: &str, parsed: &ParsedContract) -> Analysis {
+    let (gas_findings, language) = match parsed.contract_type {
+        ContractType::Solidity => (gas_rules::solidity_gas_findings(content), "Solidity"),
+        ContractType::Stylus => (gas_rules::stylus_gas_findings(content), "Stylus"),
+    };
+    let micro_optimizations = gas_rules::format_gas_findings(&gas_findings, language);
+    let text = format!(
+        "{}{}\n{}\n{}\n{}\n{}\n{}\n",
+        static_only_note(),
+        analyze_l2_patterns(content),
+        micro_optimizations,
+        format_stylus_patterns(content, parsed),
+        analyze_memory_patterns(content),
+        format_event_indexing(content),
+        generate_recommendations(&parsed.analyze_patterns(), &parsed.analyze_gas_patterns(), parsed)
+    );
+    Analysis::new("gas", text)
+        .with_findings(located_findings(&gas_findings))
+        .with_metric("estimated_gas", parsed.functions.iter().map(|function| function.estimated_gas()).sum::<u64>())
+}
+
+fn located_findings(gas_findings: &[gas_rules::GasFinding]) -> Vec<Finding> {
+    gas_findings.iter().map(|finding| Finding {
+        severity: Severity::Low,
+        rule: Some(format!("{:?}", finding.rule)),
+        rule_id: None,
+        detected_by: Vec::new(),
+        title: finding.rule.title().to_string(),
+        function: None,
+        line: Some(finding.line),
+        column: None,
+        description: finding.message.clone(),
+        recommendation: Some(finding.suggestion.clone()),
+        confidence: None,
+        references: Vec::new(),
+        fingerprint: None,
+        effort: None,
+        example: None,
+        origin: None,
+    }).collect()
+}
+
 fn format_l2_metrics(operations: &str) -> String {
     let mut formatted = String::new();
     formatted.push_str("\n🚀 Stylus Optimization Summary\n");
@@ -196,14 +257,6 @@ fn analyze_optimization_patterns(content: &str) -> Vec<OptimizationPattern> {
         });
     }
 
-    // Event patterns
-    if content.contains("emit") && !content.contains("indexed") {
-        patterns.push(OptimizationPattern {
-            severity: "Low".to_string(),
-            message: "Non-indexed events - consider selective indexing for efficiency".to_string(),
-        });
-    }
-
     patterns
 }
 
@@ -299,8 +352,8 @@ fn analyze_memory_patterns(content: &str) -> String {
         // Format concerns
         for (issue, severity, description) in concerns {
             let (icon, colored_text) = match severity {
-                "High" => ("⚠️", format!("{}", issue).red().bold()),
-                _ => ("ℹ️", format!("{}", issue).blue()),
+                "High" => ("⚠️", issue.to_string().red().bold()),
+                _ => ("ℹ️", issue.to_string().blue()),
             };
             analysis.push_str(&format!("{} {} ({} Impact)\n  • {}\n\n", 
                 icon, colored_text, severity, description));
@@ -318,6 +371,29 @@ fn analyze_memory_patterns(content: &str) -> String {
     analysis
 }
 
+/// Indexed parameter count of every event, with the ones filters can't use
+fn format_event_indexing(content: &str) -> String {
+    let events = crate::audit::events::event_indexing(content);
+    if events.is_empty() {
+        return String::new();
+    }
+    let mut formatted = String::from("\n📣 Event Indexing\n═══════════════\n");
+    for event in &events {
+        let line = if event.line > 0 { format!(" (line {})", event.line) } else { String::new() };
+        formatted.push_str(&format!("  • {}{}: {} of {} parameter(s) indexed\n", event.name.bold(), line, event.indexed(), event.params.len()));
+        if event.unfilterable() {
+            formatted.push_str(&format!("    {}\n", "⚠️ no indexed address: logs can't be filtered by account".yellow()));
+        }
+        if event.too_many_indexed() {
+            formatted.push_str(&format!("    {}\n", format!("❌ more than {} indexed parameters won't compile", event.max_indexed()).red()));
+        }
+        for param in event.indexed_dynamic() {
+            formatted.push_str(&format!("    {}\n", format!("ℹ️ indexed {} is stored as a hash and can't be read back", param.ty).blue()));
+        }
+    }
+    formatted
+}
+
 fn format_environmental_impact(analysis: &str) -> String {
     // Updated CO2 calculations based on more accurate estimates
     let co2_per_gas = 0.0000002; // kg CO2 per gas unit (refined estimate)
@@ -340,11 +416,11 @@ fn format_environmental_impact(analysis: &str) -> String {
     };
 
     format!(
-        "\n🌱 Environmental Impact\n{}\n\n{}\n{}\n{}\n{}\n{}\n\n{}\n",
+        "\n🌱 Environmental Impact\n{}\n\n⚡ Gas Usage: {} units\n💨 CO2 Emission: {:.4} kg\n🔋 Energy Consumption: {:.4} kWh\n{}\n{}\n\n{}\n",
         "═".repeat(35).bright_yellow(),
-        format!("⚡ Gas Usage: {} units", total_gas.to_string().green()),
-        format!("💨 CO2 Emission: {:.4} kg", total_co2),
-        format!("🔋 Energy Consumption: {:.4} kWh", energy_kwh),
+        total_gas.to_string().green(),
+        total_co2,
+        energy_kwh,
         co2_comparison,
         energy_comparison,
         "Note: Estimates based on average network conditions".bright_black()
@@ -453,11 +529,8 @@ fn generate_recommendations(patterns: &[String], gas_patterns: &[String], parsed
     recommendations
 }
 
-fn format_summary(operations: &str) -> String {
-    let critical_count = count_severity(operations, "Critical");
-    let high_count = count_severity(operations, "High");
-    let medium_count = count_severity(operations, "Medium");
-    let low_count = count_severity(operations, "Low");
+fn format_summary(counts: &SeverityCounts) -> String {
+    let SeverityCounts { critical: critical_count, high: high_count, medium: medium_count, low: low_count, .. } = *counts;
 
     // Calculate overall severity based on findings
     let severity_status = if critical_count > 0 {
@@ -508,20 +581,6 @@ fn format_summary(operations: &str) -> String {
     summary
 }
 
-fn count_severity(operations: &str, severity: &str) -> usize {
-    operations.lines()
-        .filter(|line| {
-            let contains_severity = line.contains(severity);
-            let is_header = line.contains("Impact:") || 
-                          line.contains("Summary") || 
-                          line.contains("═");
-            let is_suggestion = line.contains("Consider") || 
-                              line.contains("Implement");
-            contains_severity && !is_header && !is_suggestion
-        })
-        .count()
-}
-
 fn analyze_l2_patterns(content: &str) -> String {
     let mut analysis = String::new();
     analysis.push_str("\n🚀 Layer 2 Optimization Analysis\n");
@@ -565,6 +624,15 @@ fn analyze_l2_patterns(content: &str) -> String {
         ));
     }
 
+    // Inline assembly is hand-tuned for gas but skips the compiler's checks
+    if !crate::audit::assembly::assembly_blocks(content).is_empty() {
+        insights.push((
+            "Inline Assembly",
+            "Medium",
+            "• Assembly blocks skip overflow and memory-safety checks; audit them by hand\n  • Static gas estimates don't look inside them\n  • When porting to Stylus, rewrite them as plain Rust"
+        ));
+    }
+
     // Format insights
     if insights.is_empty() {
         analysis.push_str("✅ No specific L2 concerns detected\n");
@@ -0,0 +1,755 @@
+//! Deterministic Solidity and Stylus gas micro-optimizations, each located
+//! by line and priced from the table in `GasRule::savings`
+
+use colored::*;
+use solang_parser::pt::{
+    CodeLocation, ContractDefinition, ContractPart, Expression, FunctionAttribute, FunctionDefinition,
+    FunctionTy, Loc, SourceUnitPart, Statement, StorageLocation, Type, Visibility,
+};
+use crate::audit::solc_version::{Capabilities, SolcVersion};
+use crate::audit::source::{assigns, identifier_offsets, line_of, slice, state_variables};
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr, Member};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasRule {
+    CacheStorageRead,
+    RepeatedStorageRead,
+    CalldataParameter,
+    PreIncrement,
+    NonZeroComparison,
+    CacheArrayLength,
+    LongRevertString,
+}
+
+impl GasRule {
+    pub const ALL: [GasRule; 7] = [
+        GasRule::CacheStorageRead,
+        GasRule::RepeatedStorageRead,
+        GasRule::CalldataParameter,
+        GasRule::CacheArrayLength,
+        GasRule::PreIncrement,
+        GasRule::NonZeroComparison,
+        GasRule::LongRevertString,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            GasRule::CacheStorageRead => "Storage read in loop",
+            GasRule::RepeatedStorageRead => "Storage read repeated in function",
+            GasRule::CalldataParameter => "memory parameter could be calldata",
+            GasRule::PreIncrement => "Post-increment loop counter",
+            GasRule::NonZeroComparison => "`> 0` on unsigned integer",
+            GasRule::CacheArrayLength => "Array length read in loop condition",
+            GasRule::LongRevertString => "Revert string over 32 bytes",
+        }
+    }
+
+    /// Estimated saving in gas and what it is counted per. Figures are for
+    /// the Shanghai/Cancun schedule with the optimizer on:
+    ///
+    /// | Rule | Gas | Basis |
+    /// |------|-----|-------|
+    /// | Storage read in loop | 97 | warm SLOAD (100) replaced by MLOAD (3), per read per iteration |
+    /// | Storage read repeated | 97 | the same, per read after the first |
+    /// | memory → calldata | 60 | CALLDATACOPY and memory expansion avoided, per 32-byte word per call |
+    /// | Array length in loop | 97 / 3 | warm SLOAD for storage arrays, MLOAD/CALLDATALOAD otherwise, per iteration |
+    /// | Post-increment counter | 5 | the copy `i++` keeps of the old value, per iteration |
+    /// | `> 0` on uints | 6 | the extra ISZERO/NOT before 0.8.13, per check |
+    /// | Revert string > 32 bytes | 200 | bytecode per extra 32-byte word at deployment |
+    pub fn savings(self) -> (u64, &'static str) {
+        match self {
+            GasRule::CacheStorageRead => (97, "per read per iteration"),
+            GasRule::RepeatedStorageRead => (97, "per read after the first"),
+            GasRule::CalldataParameter => (60, "per 32-byte word per call"),
+            GasRule::PreIncrement => (5, "per iteration"),
+            GasRule::NonZeroComparison => (6, "per check"),
+            GasRule::CacheArrayLength => (97, "per iteration for storage arrays, 3 otherwise"),
+            GasRule::LongRevertString => (200, "per extra 32-byte word at deployment"),
+        }
+    }
+}
+
+/// One located optimization
+#[derive(Debug, Clone)]
+pub struct GasFinding {
+    pub rule: GasRule,
+    pub line: usize,
+    /// Source text the rule matched
+    pub code: String,
+    /// Storage variable the finding is about, for rules that name one
+    pub variable: Option<String>,
+    pub message: String,
+    pub suggestion: String,
+}
+
+/// Compiler from which a for-loop's `++i`/`i++` costs the same (unchecked by default)
+const LOOP_INCREMENT_FREE: SolcVersion = SolcVersion::new(0, 8, 22);
+/// Compiler from which the optimizer emits the same code for `> 0` and `!= 0`
+const NONZERO_FREE: SolcVersion = SolcVersion::new(0, 8, 13);
+/// Custom errors exist from this compiler on
+const CUSTOM_ERRORS: SolcVersion = SolcVersion::new(0, 8, 4);
+
+/// Runs every rule over Solidity `source`; empty for Stylus and unparsable input
+pub fn solidity_gas_findings(source: &str) -> Vec<GasFinding> {
+    let Ok((unit, _)) = solang_parser::parse(source, 0) else { return Vec::new() };
+    // Without a pragma the compiler is unknown, so version-gated rules assume an older one
+    let version = Capabilities::detect(&unit).map_or(SolcVersion::new(0, 0, 0), |caps| caps.version);
+    let mut findings = Vec::new();
+
+    for part in &unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        for part in &contract.parts {
+            let ContractPart::FunctionDefinition(func) = part else { continue };
+            let Some(body) = &func.body else { continue };
+            let name = match func.ty {
+                FunctionTy::Constructor => "constructor".to_string(),
+                _ => func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string()),
+            };
+            let mut check = FunctionCheck { contract, func, source, version, name, findings: &mut findings };
+            check.calldata_parameters();
+            check.statement(body);
+        }
+    }
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+struct FunctionCheck<'a> {
+    contract: &'a ContractDefinition,
+    func: &'a FunctionDefinition,
+    source: &'a str,
+    version: SolcVersion,
+    name: String,
+    findings: &'a mut Vec<GasFinding>,
+}
+
+impl FunctionCheck<'_> {
+    fn push(&mut self, rule: GasRule, loc: &Loc, message: String, suggestion: String) -> &mut GasFinding {
+        self.findings.push(GasFinding {
+            rule,
+            line: line_of(loc, self.source),
+            code: slice(loc, self.source).to_string(),
+            variable: None,
+            message,
+            suggestion,
+        });
+        self.findings.last_mut().expect("just pushed")
+    }
+
+    fn calldata_parameters(&mut self) {
+        let external = self.func.attributes.iter().any(|attr| matches!(attr,
+            FunctionAttribute::Visibility(Visibility::External(_) | Visibility::Public(_))));
+        if !external || self.func.ty != FunctionTy::Function {
+            return;
+        }
+        let body = self.func.body.as_ref().map_or("", |b| slice(&b.loc(), self.source));
+        for (_, param) in &self.func.params {
+            let Some(param) = param else { continue };
+            let (Some(StorageLocation::Memory(_)), Some(name)) = (&param.storage, &param.name) else { continue };
+            // A parameter the function modifies has to live in memory
+            if assigns(body, &name.name) {
+                continue;
+            }
+            self.push(
+                GasRule::CalldataParameter,
+                &param.loc,
+                format!("{} copies `{}` into memory but never modifies it", self.name, param.ty),
+                format!("Declare `{} calldata {}`", param.ty, name.name),
+            );
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Block { statements, .. } => {
+                for statement in statements {
+                    self.statement(statement);
+                }
+            }
+            Statement::If(_, condition, then, otherwise) => {
+                self.expression(condition);
+                self.statement(then);
+                if let Some(otherwise) = otherwise {
+                    self.statement(otherwise);
+                }
+            }
+            Statement::For(loc, init, condition, next, body) => {
+                if let Some(init) = init {
+                    self.statement(init);
+                }
+                self.loop_checks(loc, condition.as_deref(), body.as_deref());
+                if let Some(next) = next.as_deref() {
+                    self.post_increment(next);
+                }
+                if let Some(body) = body {
+                    self.statement(body);
+                }
+            }
+            Statement::While(loc, condition, body) | Statement::DoWhile(loc, body, condition) => {
+                self.loop_checks(loc, Some(condition), Some(body));
+                self.statement(body);
+            }
+            Statement::Expression(_, expression) | Statement::VariableDefinition(_, _, Some(expression)) => {
+                self.expression(expression);
+            }
+            Statement::Revert(loc, None, args) => self.revert_string(loc, args.first()),
+            Statement::Try(_, _, Some((_, ok)), _) => self.statement(ok),
+            _ => {}
+        }
+    }
+
+    /// `require(x > 0, "...")` and `revert("...")` calls
+    fn expression(&mut self, expression: &Expression) {
+        let Expression::FunctionCall(loc, callee, args) = expression else { return };
+        if matches!(callee.as_ref(), Expression::Variable(id) if id.name == "revert") {
+            return self.revert_string(loc, args.first());
+        }
+        if !matches!(callee.as_ref(), Expression::Variable(id) if id.name == "require") {
+            return;
+        }
+        if let Some(condition) = args.first() {
+            self.nonzero_comparison(loc, condition);
+        }
+        self.revert_string(loc, args.get(1));
+    }
+
+    fn nonzero_comparison(&mut self, loc: &Loc, condition: &Expression) {
+        if self.version >= NONZERO_FREE {
+            return;
+        }
+        match condition {
+            Expression::And(_, left, right) | Expression::Or(_, left, right) => {
+                self.nonzero_comparison(loc, left);
+                self.nonzero_comparison(loc, right);
+            }
+            Expression::More(_, left, right) if is_zero(right) && self.is_unsigned(left) => {
+                self.push(
+                    GasRule::NonZeroComparison,
+                    loc,
+                    format!("{} checks `{} > 0` under Solidity {}", self.name, left, self.version),
+                    format!("Use `{} != 0`; for unsigned integers it means the same", left),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn revert_string(&mut self, loc: &Loc, message: Option<&Expression>) {
+        let Some(Expression::StringLiteral(parts)) = message else { return };
+        let len: usize = parts.iter().map(|part| part.string.len()).sum();
+        if len <= 32 {
+            return;
+        }
+        let suggestion = if self.version >= CUSTOM_ERRORS {
+            "Replace the string with a custom error, e.g. `error InsufficientBalance();`".to_string()
+        } else {
+            "Shorten the message to 32 bytes, or move to 0.8.4+ and use a custom error".to_string()
+        };
+        self.push(
+            GasRule::LongRevertString,
+            loc,
+            format!("{} reverts with a {}-byte string, stored in {} words of bytecode", self.name, len, len.div_ceil(32)),
+            suggestion,
+        );
+    }
+
+    fn post_increment(&mut self, next: &Expression) {
+        if self.version >= LOOP_INCREMENT_FREE {
+            return;
+        }
+        if let Expression::PostIncrement(loc, var) | Expression::PostDecrement(loc, var) = next {
+            let op = if matches!(next, Expression::PostIncrement(..)) { "++" } else { "--" };
+            self.push(
+                GasRule::PreIncrement,
+                loc,
+                format!("{} advances its loop with `{}{}` under Solidity {}", self.name, var, op, self.version),
+                format!("Use `{}{}`, or compile with 0.8.22+ where the loop increment is unchecked", op, var),
+            );
+        }
+    }
+
+    /// Storage reads and `.length` evaluated on every iteration
+    fn loop_checks(&mut self, loc: &Loc, condition: Option<&Expression>, body: Option<&Statement>) {
+        let condition_text = condition.map_or("", |c| slice(&c.loc(), self.source));
+        let body_text = body.map_or("", |b| slice(&b.loc(), self.source));
+        let storage: Vec<(String, bool)> = state_variables(self.contract).into_iter()
+            .map(|(name, var)| (name, is_collection(&var.ty)))
+            .collect();
+
+        for (name, collection) in &storage {
+            // Indexed collections can't be cached whole, and written values would need writing back
+            if *collection || assigns(body_text, name) {
+                continue;
+            }
+            // Members like `x.name` are other variables
+            let reads = [condition_text, body_text].iter()
+                .map(|text| identifier_offsets(text, name).filter(|&at| !text[..at].ends_with('.')).count())
+                .sum::<usize>();
+            if reads > 0 {
+                self.push(
+                    GasRule::CacheStorageRead,
+                    loc,
+                    format!("{} reads storage variable `{}` {} time(s) on every iteration", self.name, name, reads),
+                    format!("Copy `{}` into a local variable before the loop", name),
+                ).variable = Some(name.clone());
+            }
+        }
+
+        for (i, _) in condition_text.match_indices(".length") {
+            let array = condition_text[..i].rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).next().unwrap_or("");
+            let in_storage = storage.iter().any(|(name, _)| name == array);
+            self.push(
+                GasRule::CacheArrayLength,
+                loc,
+                format!("{} evaluates `{}.length` on every iteration{}", self.name, array,
+                    if in_storage { ", a storage read each time" } else { "" }),
+                format!("Cache it before the loop: `uint256 len = {}.length;`", array),
+            );
+        }
+    }
+
+    fn is_unsigned(&self, expression: &Expression) -> bool {
+        let Expression::Variable(id) = expression else { return false };
+        let unsigned = |ty: &Expression| matches!(ty, Expression::Type(_, Type::Uint(_)));
+        let param = self.func.params.iter()
+            .filter_map(|(_, param)| param.as_ref())
+            .any(|param| param.name.as_ref().is_some_and(|n| n.name == id.name) && unsigned(&param.ty));
+        let state = state_variables(self.contract).iter().any(|(name, var)| *name == id.name && unsigned(&var.ty));
+        // Locals are declared as `uint256 name`
+        let body = self.func.body.as_ref().map_or("", |b| slice(&b.loc(), self.source));
+        let local = body.match_indices(&format!(" {}", id.name)).any(|(i, _)| {
+            body[..i].rsplit(|c: char| c.is_whitespace() || c == '(' || c == '{' || c == ';').next()
+                .is_some_and(|ty| ty.starts_with("uint"))
+        });
+        param || state || local
+    }
+}
+
+fn is_zero(expression: &Expression) -> bool {
+    matches!(expression, Expression::NumberLiteral(_, value, exponent, _) if value == "0" && exponent.is_empty())
+}
+
+fn is_collection(ty: &Expression) -> bool {
+    matches!(ty, Expression::Type(_, Type::Mapping { .. }) | Expression::ArraySubscript(..))
+}
+
+
+/// Stylus storage methods that change what a later read returns
+const STORAGE_WRITES: [&str; 7] = ["set", "insert", "setter", "push", "pop", "erase", "delete"];
+
+/// One `self.field...` storage call in a function body
+struct StorageAccess {
+    field: String,
+    /// The `.get(...)` arguments without whitespace, e.g. `&sender`; None for writes
+    key: Option<String>,
+    /// Local variables the key reads
+    key_variables: Vec<String>,
+    line: usize,
+    /// The innermost loop around the call, an index into `FunctionAccesses::loops`
+    in_loop: Option<usize>,
+}
+
+/// A `for`, `while` or `loop` expression
+struct Loop {
+    line: usize,
+    /// Variables that can hold a different value on each iteration: the
+    /// `for` pattern, bindings inside the loop and variables it assigns
+    varying: Vec<String>,
+}
+
+/// Storage accesses and loops in one function body, in evaluation order:
+/// a read counts where its call starts, a write once its arguments have run
+#[derive(Default)]
+struct FunctionAccesses {
+    accesses: Vec<StorageAccess>,
+    loops: Vec<Loop>,
+    /// Loops enclosing the node being visited, innermost last
+    open_loops: Vec<usize>,
+}
+
+impl FunctionAccesses {
+    fn of(block: &syn::Block) -> Self {
+        let mut accesses = Self::default();
+        accesses.visit_block(block);
+        accesses
+    }
+
+    /// Visits a loop's per-iteration parts with the loop open
+    fn in_loop(&mut self, span: proc_macro2::Span, visit: impl FnOnce(&mut Self)) {
+        self.loops.push(Loop { line: span.start().line, varying: Vec::new() });
+        self.open_loops.push(self.loops.len() - 1);
+        visit(self);
+        self.open_loops.pop();
+    }
+
+    /// Marks the variables `pat` binds as varying in the innermost loop
+    fn bind(&mut self, pat: &syn::Pat) {
+        let Some(&innermost) = self.open_loops.last() else { return };
+        let mut bindings = Bindings::default();
+        bindings.visit_pat(pat);
+        self.loops[innermost].varying.extend(bindings.0);
+    }
+
+    fn assign(&mut self, target: &Expr) {
+        if let (Some(&innermost), Expr::Path(path)) = (self.open_loops.last(), target) {
+            if let Some(variable) = path.path.get_ident() {
+                self.loops[innermost].varying.push(variable.to_string());
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FunctionAccesses {
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        let Some(field) = storage_field(&call.receiver) else {
+            return visit::visit_expr_method_call(self, call);
+        };
+        let line = call.span().start().line;
+        let in_loop = self.open_loops.last().copied();
+        if method == "get" {
+            let mut variables = Bindings::default();
+            call.args.iter().for_each(|arg| variables.visit_expr(arg));
+            let key = call.args.to_token_stream().to_string().split_whitespace().collect();
+            self.accesses.push(StorageAccess { field, key: Some(key), key_variables: variables.0, line, in_loop });
+            visit::visit_expr_method_call(self, call);
+        } else {
+            visit::visit_expr_method_call(self, call);
+            if STORAGE_WRITES.contains(&method.as_str()) {
+                self.accesses.push(StorageAccess { field, key: None, key_variables: Vec::new(), line, in_loop });
+            }
+        }
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        // The iterator is built once, before the first iteration
+        self.visit_expr(&expr.expr);
+        self.in_loop(expr.for_token.span, |this| {
+            this.bind(&expr.pat);
+            this.visit_block(&expr.body);
+        });
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.in_loop(expr.while_token.span, |this| {
+            this.visit_expr(&expr.cond);
+            this.visit_block(&expr.body);
+        });
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        self.in_loop(expr.loop_token.span, |this| this.visit_block(&expr.body));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        self.bind(&local.pat);
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_let(&mut self, expr: &'ast syn::ExprLet) {
+        self.bind(&expr.pat);
+        visit::visit_expr_let(self, expr);
+    }
+
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        self.bind(&arm.pat);
+        visit::visit_arm(self, arm);
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        closure.inputs.iter().for_each(|input| self.bind(input));
+        visit::visit_expr_closure(self, closure);
+    }
+
+    fn visit_expr_assign(&mut self, expr: &'ast syn::ExprAssign) {
+        self.assign(&expr.left);
+        visit::visit_expr_assign(self, expr);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if matches!(expr.op, BinOp::AddAssign(_) | BinOp::SubAssign(_) | BinOp::MulAssign(_) | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_) | BinOp::BitXorAssign(_) | BinOp::BitAndAssign(_) | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_) | BinOp::ShrAssign(_))
+        {
+            self.assign(&expr.left);
+        }
+        visit::visit_expr_binary(self, expr);
+    }
+
+    // Nested functions are visited on their own
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+/// Single-identifier paths and pattern bindings, e.g. `holder` in `Some(holder)` or `&holder`
+#[derive(Default)]
+struct Bindings(Vec<String>);
+
+impl<'ast> Visit<'ast> for Bindings {
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.0.push(pat.ident.to_string());
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+        if let Some(ident) = expr.path.get_ident() {
+            self.0.push(ident.to_string());
+        }
+    }
+}
+
+/// `balances` for `self.balances`, `erc20.balances` for `self.erc20.balances`;
+/// `.0` wrapper fields are looked through
+fn storage_field(receiver: &Expr) -> Option<String> {
+    let mut names = Vec::new();
+    let mut expr = receiver;
+    while let Expr::Field(field) = expr {
+        if let Member::Named(name) = &field.member {
+            names.push(name.to_string());
+        }
+        expr = &field.base;
+    }
+    let Expr::Path(base) = expr else { return None };
+    if !base.path.is_ident("self") || names.is_empty() {
+        return None;
+    }
+    names.reverse();
+    Some(names.join("."))
+}
+
+/// Every function and method with a body in `file`, by name
+#[derive(Default)]
+struct Functions<'ast>(Vec<(String, &'ast syn::Block)>);
+
+impl<'ast> Visit<'ast> for Functions<'ast> {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.0.push((item.sig.ident.to_string(), &item.block));
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.0.push((item.sig.ident.to_string(), &item.block));
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        if let Some(block) = &item.default {
+            self.0.push((item.sig.ident.to_string(), block));
+        }
+        visit::visit_trait_item_fn(self, item);
+    }
+}
+
+/// Runs the Stylus rules over Rust `source`, per function: the same storage
+/// key read twice with no write to it in between, and storage reads inside
+/// `for`, `while` and `loop` bodies, each against its innermost loop. Empty
+/// for unparsable input
+pub fn stylus_gas_findings(source: &str) -> Vec<GasFinding> {
+    let Ok(file) = syn::parse_file(source) else { return Vec::new() };
+    let code_at = |line: usize| source.lines().nth(line.saturating_sub(1)).unwrap_or_default().trim().to_string();
+    let mut functions = Functions::default();
+    functions.visit_file(&file);
+    let mut findings = Vec::new();
+
+    for (name, block) in functions.0 {
+        let FunctionAccesses { accesses, loops, .. } = FunctionAccesses::of(block);
+
+        // Reads of one field and key between writes to that field: (field, key, each read's line)
+        let mut runs: Vec<(String, String, Vec<usize>)> = Vec::new();
+        let mut open_runs: Vec<(String, String, Vec<usize>)> = Vec::new();
+        for access in &accesses {
+            match &access.key {
+                None => {
+                    let (closed, kept): (Vec<_>, Vec<_>) = open_runs.into_iter().partition(|(field, ..)| *field == access.field);
+                    runs.extend(closed);
+                    open_runs = kept;
+                }
+                Some(key) => match open_runs.iter_mut().find(|(field, read, _)| *field == access.field && read == key) {
+                    Some((.., lines)) => lines.push(access.line),
+                    None => open_runs.push((access.field.clone(), key.clone(), vec![access.line])),
+                },
+            }
+        }
+        runs.extend(open_runs);
+        for (field, key, lines) in runs.into_iter().filter(|(.., lines)| lines.len() > 1) {
+            let first = lines[0];
+            let mut listed: Vec<String> = lines.iter().map(usize::to_string).collect();
+            let last = listed.pop().unwrap_or_default();
+            findings.push(GasFinding {
+                rule: GasRule::RepeatedStorageRead,
+                line: first,
+                code: code_at(first),
+                variable: Some(field.clone()),
+                message: format!("`{}` reads `self.{}.get({})` on lines {} and {} with no write to `{}` in between",
+                    name, field, key, listed.join(", "), last, field),
+                suggestion: format!("Read it once into a local, e.g. `let value = self.{}.get({});`, and reuse that", field, key),
+            });
+        }
+
+        for access in &accesses {
+            let (Some(key), Some(innermost)) = (&access.key, access.in_loop) else { continue };
+            let lp = &loops[innermost];
+            let varies = access.key_variables.iter().any(|variable| lp.varying.contains(variable));
+            let suggestion = match varies {
+                true => format!("The key changes every iteration, so each one pays a storage read; \
+                    a StorageMap keyed by what the loop looks for replaces the scan over `{}`", access.field),
+                false => format!("Read `self.{}.get({})` once before the loop and reuse the local", access.field, key),
+            };
+            findings.push(GasFinding {
+                rule: GasRule::CacheStorageRead,
+                line: access.line,
+                code: code_at(access.line),
+                variable: Some(access.field.clone()),
+                message: format!("`{}` reads `{}` from storage on every iteration of the loop on line {} (read on line {})",
+                    name, access.field, lp.line, access.line),
+                suggestion,
+            });
+        }
+    }
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+/// Findings grouped by rule, with each rule's estimated saving
+pub fn format_gas_findings(findings: &[GasFinding], language: &str) -> String {
+    let title = format!("⛽ {} Gas Micro-Optimizations", language);
+    let mut output = format!("\n{}\n{}\n", title, "═".repeat(title.chars().count()));
+    if findings.is_empty() {
+        output.push_str(&format!("{}\n", "✅ No micro-optimizations found".green()));
+        return output;
+    }
+    for rule in GasRule::ALL {
+        let matched: Vec<&GasFinding> = findings.iter().filter(|f| f.rule == rule).collect();
+        if matched.is_empty() {
+            continue;
+        }
+        let (gas, basis) = rule.savings();
+        output.push_str(&format!("\n{} {}\n", rule.title().bold(), format!("(~{} gas {})", gas, basis).dimmed()));
+        for finding in matched {
+            output.push_str(&format!("  • line {}: {}\n    💡 {}\n", finding.line, finding.message, finding.suggestion));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loop_reads(source: &str) -> Vec<(usize, String)> {
+        stylus_gas_findings(source).into_iter()
+            .filter(|finding| finding.rule == GasRule::CacheStorageRead)
+            .map(|finding| (finding.line, finding.suggestion))
+            .collect()
+    }
+
+    #[test]
+    fn keys_bound_inside_the_loop_vary_per_iteration() {
+        let source = r#"
+impl Token {
+    pub fn holder_balances(&self) -> Vec<u64> {
+        let mut balances = Vec::new();
+        for i in 0..self.holders.len() {
+            if let Some(holder) = self.holders.get(i) {
+                balances.push(self.balances.get(&holder));
+            }
+        }
+        balances
+    }
+}
+"#;
+        let reads = loop_reads(source);
+        assert_eq!(reads.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [6, 7]);
+        assert!(reads.iter().all(|(_, suggestion)| suggestion.starts_with("The key changes every iteration")));
+    }
+
+    #[test]
+    fn reads_are_reported_against_their_innermost_loop_once() {
+        let source = r#"
+impl Pool {
+    pub fn sweep(&mut self, rounds: u32) {
+        let mut n = 0;
+        while n < rounds {
+            for user in self.users.iter() {
+                let fee = self.fees.get(&n);
+                self.paid.insert(user, fee);
+            }
+            n += 1;
+        }
+    }
+}
+"#;
+        let findings = stylus_gas_findings(source);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 7);
+        assert!(findings[0].message.ends_with("loop on line 6 (read on line 7)"));
+        // `n` changes with the outer loop only, so the read can move above the inner one
+        assert!(findings[0].suggestion.starts_with("Read `self.fees.get(&n)` once before the loop"));
+    }
+
+    #[test]
+    fn counters_assigned_in_a_while_loop_vary() {
+        let source = r#"
+impl Pool {
+    pub fn total(&self, count: u32) -> u64 {
+        let (mut i, mut sum) = (0, 0);
+        while i < count {
+            sum += self.amounts.get(i);
+            i += 1;
+        }
+        sum
+    }
+}
+"#;
+        let reads = loop_reads(source);
+        assert_eq!(reads.len(), 1);
+        assert!(reads[0].1.starts_with("The key changes every iteration"));
+    }
+
+    #[test]
+    fn repeated_reads_reset_on_a_write() {
+        let source = r#"
+impl Token {
+    pub fn transfer(&mut self, to: Address, amount: u64) {
+        let sender = msg::sender();
+        if self.balances.get(sender) < amount { return; }
+        let before = self.balances.get(sender);
+        self.balances.insert(sender, before - amount);
+        let after = self.balances.get(sender);
+        log(after);
+    }
+}
+"#;
+        let repeated: Vec<_> = stylus_gas_findings(source).into_iter()
+            .filter(|finding| finding.rule == GasRule::RepeatedStorageRead)
+            .collect();
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].line, 5);
+        assert!(repeated[0].message.contains("on lines 5 and 6 with no write"));
+    }
+
+    const PAYROLL: &str = include_str!("../../test ex/gas/micro_optimizations.sol");
+
+    #[test]
+    fn each_solidity_rule_fires_once_and_the_tuned_functions_are_clean() {
+        let found: Vec<(GasRule, usize)> = solidity_gas_findings(PAYROLL).iter().map(|f| (f.rule, f.line)).collect();
+        assert_eq!(found, [
+            (GasRule::CacheStorageRead, 12),
+            (GasRule::CacheArrayLength, 12),
+            (GasRule::PreIncrement, 12),
+            (GasRule::CalldataParameter, 17),
+            (GasRule::NonZeroComparison, 24),
+            (GasRule::LongRevertString, 24),
+        ]);
+    }
+
+    #[test]
+    fn version_gated_rules_follow_the_pragma() {
+        let modern = PAYROLL.replace("pragma solidity ^0.8.10;", "pragma solidity ^0.8.22;");
+        let rules: Vec<GasRule> = solidity_gas_findings(&modern).iter().map(|f| f.rule).collect();
+        assert!(!rules.contains(&GasRule::PreIncrement) && !rules.contains(&GasRule::NonZeroComparison));
+        assert!(rules.contains(&GasRule::LongRevertString));
+    }
+}
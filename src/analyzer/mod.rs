@@ -1,18 +1,36 @@
-use std::path::PathBuf;
-use std::error::Error;
+use std::path::Path;
+use colored::*;
+use crate::error::Result;
 
 pub mod gas;
+pub(crate) mod gas_rules;
 pub mod size;
 pub mod security;
 pub mod complexity;
 pub mod interactions;
 pub mod quality;
+pub mod natspec;
+pub mod output;
 
 use crate::parser::ParsedContract;
+pub use output::Analysis;
+
+/// Line heading a report built without the AI model (`--no-ai`)
+pub fn static_only_note() -> String {
+    format!("{}\n", "ℹ️  Static analysis only: the AI review was skipped (--no-ai)".cyan().bold())
+}
 
 #[async_trait::async_trait]
-pub trait Analyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>>;
+pub trait Analyzer: Sync {
+    /// Reads and parses `file`, then analyzes it
+    async fn analyze(&self, file: &Path) -> Result<Analysis> {
+        let contract = ParsedContract::new(std::fs::read_to_string(file)?)?;
+        self.analyze_parsed(&contract, file).await
+    }
+
+    /// Analyzes a contract that is already parsed, so commands running several
+    /// analyzers read the file once; `file` only labels the results
+    async fn analyze_parsed(&self, contract: &ParsedContract, file: &Path) -> Result<Analysis>;
 
     fn get_follow_up_questions(&self, analysis: &str, _parsed: &ParsedContract) -> Vec<String> {
         let mut questions = Vec::new();
@@ -0,0 +1,128 @@
+use colored::*;
+use crate::parser::{ContractType, Function, ParsedContract};
+
+/// What an externally visible function's NatSpec is missing
+#[derive(Debug)]
+pub struct FunctionDocs {
+    pub name: String,
+    /// Has `@notice` or `@dev` (or `@inheritdoc`)
+    pub described: bool,
+    pub undocumented_params: Vec<String>,
+    pub undocumented_returns: usize,
+    /// Documented items out of description + parameters + return values
+    pub covered: usize,
+    pub total: usize,
+}
+
+impl FunctionDocs {
+    pub fn is_complete(&self) -> bool {
+        self.covered == self.total
+    }
+}
+
+/// NatSpec coverage of the public and external functions of a Solidity contract
+#[derive(Debug)]
+pub struct DocCoverage {
+    pub functions: Vec<FunctionDocs>,
+}
+
+impl DocCoverage {
+    /// Share of documented items, 100 when there is nothing to document
+    pub fn percent(&self) -> f64 {
+        let total: usize = self.functions.iter().map(|f| f.total).sum();
+        let covered: usize = self.functions.iter().map(|f| f.covered).sum();
+        if total == 0 {
+            100.0
+        } else {
+            covered as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+/// NatSpec coverage of `parsed`; `None` for Stylus contracts, which don't use NatSpec
+pub fn doc_coverage(parsed: &ParsedContract) -> Option<DocCoverage> {
+    if !matches!(parsed.contract_type, ContractType::Solidity) {
+        return None;
+    }
+    let functions = parsed.functions.iter()
+        .filter(|f| f.visibility == "public" || f.visibility == "external")
+        .map(function_docs)
+        .collect();
+    Some(DocCoverage { functions })
+}
+
+fn function_docs(function: &Function) -> FunctionDocs {
+    let tag = |name: &'static str| function.docs.iter().filter(move |doc| doc.tag == name);
+    // `@inheritdoc Base` documents the whole function
+    let inherited = tag("inheritdoc").next().is_some();
+
+    let described = inherited || tag("notice").chain(tag("dev")).any(|doc| !doc.value.trim().is_empty());
+    let documented_params: Vec<&str> = tag("param")
+        .filter_map(|doc| doc.value.split_whitespace().next())
+        .collect();
+    // Params are recorded as `name: Type`
+    let undocumented_params: Vec<String> = function.params.iter()
+        .filter_map(|param| param.split(':').next())
+        .filter(|name| !inherited && !documented_params.contains(name))
+        .map(str::to_string)
+        .collect();
+    let undocumented_returns = if inherited { 0 } else { function.returns.saturating_sub(tag("return").count()) };
+
+    let total = 1 + function.params.len() + function.returns;
+    let covered = total - usize::from(!described) - undocumented_params.len() - undocumented_returns;
+    FunctionDocs {
+        name: function.name.clone(),
+        described,
+        undocumented_params,
+        undocumented_returns,
+        covered,
+        total,
+    }
+}
+
+pub fn format_doc_coverage(coverage: &DocCoverage) -> String {
+    let percent = coverage.percent();
+    let headline = format!("📚 NatSpec coverage: {:.1}% of {} public/external function(s)", percent, coverage.functions.len());
+    let mut output = format!("{}\n", if percent >= 80.0 { headline.green().bold() } else { headline.yellow().bold() });
+
+    for function in coverage.functions.iter().filter(|f| !f.is_complete()) {
+        let mut missing = Vec::new();
+        if !function.described {
+            missing.push("@notice/@dev".to_string());
+        }
+        for param in &function.undocumented_params {
+            missing.push(format!("@param {}", param));
+        }
+        if function.undocumented_returns > 0 {
+            missing.push(format!("{} @return", function.undocumented_returns));
+        }
+        output.push_str(&format!("  • {} ({}/{}) missing {}\n", function.name.bold(), function.covered, function.total, missing.join(", ")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_counts_descriptions_params_and_returns() {
+        let parsed = ParsedContract::new(include_str!("../../test ex/natspec/half_documented.sol").to_string()).unwrap();
+        let coverage = doc_coverage(&parsed).unwrap();
+        let functions: Vec<(&str, usize, usize)> = coverage.functions.iter()
+            .map(|docs| (docs.name.as_str(), docs.covered, docs.total))
+            .collect();
+        assert_eq!(functions, [
+            ("deposit", 1, 1), ("withdraw", 2, 2), ("balanceOf", 3, 3),
+            ("transfer", 1, 3), ("sweep", 0, 2), ("totalHeld", 0, 2),
+        ]);
+        assert_eq!(coverage.functions[3].undocumented_params, ["to", "amount"]);
+        assert_eq!(format!("{:.1}", coverage.percent()), "53.8");
+    }
+
+    #[test]
+    fn stylus_contracts_have_no_natspec() {
+        let parsed = ParsedContract::new("pub fn f() {}".to_string()).unwrap();
+        assert!(doc_coverage(&parsed).is_none());
+    }
+}
@@ -0,0 +1,366 @@
+//! Structured analyzer results and the versioned JSON document printed for
+//! `--format json`.
+//!
+//! The document is a contract with scripts: within one `schema_version`
+//! fields are only ever added, never renamed, removed or retyped, and every
+//! field is always present (`null` when unknown). Anything else bumps
+//! `SCHEMA_VERSION`. Consumers should ignore fields they don't know
+
+use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::Value;
+use crate::audit::vulnerabilities::{Effort, FixExample, Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+/// Version of the `--format json` document layout
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// One issue reported by an analyzer
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Rule that produced the finding; `null` for AI-reported findings
+    pub rule: Option<String>,
+    /// Stable id of the audit rule, e.g. `SA-REENTRANCY-001`; `null` outside the audit
+    pub rule_id: Option<String>,
+    /// Ids of every rule that reported the finding when several did; empty otherwise
+    pub detected_by: Vec<String>,
+    pub title: String,
+    /// Function the finding is in, when known
+    pub function: Option<String>,
+    /// 1-based line in the analyzed file, when known
+    pub line: Option<usize>,
+    /// 1-based column on `line`, when known
+    pub column: Option<usize>,
+    pub description: String,
+    pub recommendation: Option<String>,
+    /// How likely the finding is real, 0.0 to 1.0; `null` outside the audit
+    pub confidence: Option<f64>,
+    /// SWC registry and CWE ids, e.g. `SWC-107`; empty when none applies
+    pub references: Vec<String>,
+    /// Identity that survives line churn and rule renames, the key baselines
+    /// and `fix --finding` use; `null` outside the audit
+    pub fingerprint: Option<String>,
+    /// Remediation effort: `Quick`, `Moderate` or `Involved`; `null` outside the audit
+    pub effort: Option<Effort>,
+    /// Before/after code for the recommendation, when the fix has a canonical shape
+    pub example: Option<FixExample>,
+    /// Plugin whose rule reported the finding, e.g. `plugin todo-plugin`;
+    /// `null` otherwise
+    pub origin: Option<String>,
+}
+
+impl From<&Vulnerability> for Finding {
+    fn from(vuln: &Vulnerability) -> Self {
+        Finding {
+            severity: vuln.severity,
+            rule: vuln.rule.clone(),
+            rule_id: vuln.rule_id.clone(),
+            detected_by: vuln.detected_by.clone(),
+            title: vuln.name.clone(),
+            function: vuln.function.clone(),
+            line: vuln.line,
+            column: vuln.column,
+            description: vuln.risk_description.clone(),
+            recommendation: Some(vuln.recommendation.clone()).filter(|r| !r.is_empty()),
+            confidence: vuln.confidence,
+            references: vuln.references.clone(),
+            fingerprint: Some(vuln.fingerprint.clone()).filter(|fingerprint| !fingerprint.is_empty()),
+            effort: vuln.effort,
+            example: vuln.example.clone(),
+            origin: vuln.origin.clone(),
+        }
+    }
+}
+
+/// What an analyzer found: structured findings and metrics for `--format
+/// json`, and the rendered report for the terminal
+#[derive(Debug, Serialize)]
+pub struct Analysis {
+    /// Short analyzer id, e.g. `gas` or `size`
+    pub analyzer: &'static str,
+    pub findings: Vec<Finding>,
+    /// Numbers the analyzer measured, keyed by snake_case name
+    pub metrics: BTreeMap<&'static str, Value>,
+    /// Colored human-readable report
+    #[serde(skip)]
+    pub text: String,
+}
+
+impl Analysis {
+    pub fn new(analyzer: &'static str, text: String) -> Self {
+        Self { analyzer, findings: Vec::new(), metrics: BTreeMap::new(), text }
+    }
+
+    pub fn with_findings(mut self, findings: Vec<Finding>) -> Self {
+        self.findings.extend(findings);
+        self
+    }
+
+    pub fn with_metric(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        self.metrics.insert(name, value.into());
+        self
+    }
+}
+
+/// Number of findings at each severity
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct SeverityCounts {
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    pub fn count(severities: impl IntoIterator<Item = Severity>) -> Self {
+        let mut counts = Self::default();
+        for severity in severities {
+            *counts.slot(severity) += 1;
+        }
+        counts
+    }
+
+    pub fn of(findings: &[Finding]) -> Self {
+        Self::count(findings.iter().map(|finding| finding.severity))
+    }
+
+    /// Findings at `threshold` or more severe
+    pub fn at_least(&self, threshold: Severity) -> usize {
+        [
+            (Severity::Critical, self.critical),
+            (Severity::High, self.high),
+            (Severity::Medium, self.medium),
+            (Severity::Low, self.low),
+            (Severity::Info, self.info),
+        ]
+        .into_iter()
+        .filter(|(severity, _)| severity.at_least(threshold))
+        .map(|(_, count)| count)
+        .sum()
+    }
+
+    pub fn add(&mut self, other: SeverityCounts) {
+        self.critical += other.critical;
+        self.high += other.high;
+        self.medium += other.medium;
+        self.low += other.low;
+        self.info += other.info;
+    }
+
+    fn slot(&mut self, severity: Severity) -> &mut usize {
+        match severity {
+            Severity::Critical => &mut self.critical,
+            Severity::High => &mut self.high,
+            Severity::Medium => &mut self.medium,
+            Severity::Low => &mut self.low,
+            Severity::Info => &mut self.info,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Tool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    tool: Tool,
+    command: &'a str,
+    file: String,
+    /// Set when a cancelled run left some analyzers out
+    interrupted: bool,
+    analyses: &'a [Analysis],
+}
+
+/// The `--format json` document for `command` run on `file`
+pub fn to_json(command: &str, file: &std::path::Path, analyses: &[Analysis], interrupted: bool) -> Result<String> {
+    let document = Document {
+        schema_version: SCHEMA_VERSION,
+        tool: Tool { name: env!("CARGO_PKG_NAME"), version: env!("CARGO_PKG_VERSION") },
+        command,
+        file: file.display().to_string(),
+        interrupted,
+        analyses,
+    };
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize {} results: {}", command, e)))
+}
+
+/// Findings from an AI response: every line naming a severity starts one,
+/// a following `Recommendation:`-style line becomes its recommendation and
+/// other lines up to the next finding its description
+pub fn findings_from_text(analysis: &str) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut in_finding = false;
+    for line in analysis.lines() {
+        let cleaned = clean(line);
+        if cleaned.is_empty() || line.trim_start().starts_with('#') {
+            in_finding = false;
+            continue;
+        }
+        if let Some(severity) = severity_of(cleaned) {
+            findings.push(Finding {
+                severity,
+                rule: None,
+                rule_id: None,
+                detected_by: Vec::new(),
+                title: cleaned.to_string(),
+                function: None,
+                line: line_number(cleaned),
+                column: None,
+                description: String::new(),
+                recommendation: None,
+                confidence: None,
+                references: Vec::new(),
+                fingerprint: None,
+                effort: None,
+                example: None,
+                origin: None,
+            });
+            in_finding = true;
+            continue;
+        }
+        let Some(finding) = findings.last_mut().filter(|_| in_finding) else { continue };
+        if let Some(recommendation) = recommendation_of(cleaned) {
+            finding.recommendation = Some(recommendation.to_string());
+        } else {
+            if !finding.description.is_empty() {
+                finding.description.push(' ');
+            }
+            finding.description.push_str(cleaned);
+            finding.line = finding.line.or_else(|| line_number(cleaned));
+        }
+    }
+    for finding in &mut findings {
+        if finding.description.is_empty() {
+            finding.description = finding.title.clone();
+        }
+    }
+    findings
+}
+
+/// Line without list markers and markdown emphasis
+fn clean(line: &str) -> &str {
+    line.trim()
+        .trim_start_matches(['-', '*', '>', '•'])
+        .trim()
+        .trim_matches('*')
+        .trim()
+}
+
+/// Severity of a line written as a finding: the severity is the first
+/// word ("High: ...", "1. Critical - ..."), follows a label ("Severity:
+/// High", "Risk Level: Medium") or is bracketed ("[Low]", "(High)"). A word
+/// merely mentioned ("highly recommended", "Low-level calls are...")
+/// doesn't count, nor do headers ending in ':' or legends naming several
+/// severities. Size and quality reports say Major/Minor for High/Low
+fn severity_of(line: &str) -> Option<Severity> {
+    const LABELS: &[&str] = &["severity", "risk", "impact", "priority", "level"];
+    let word_severity = |word: &str| match word {
+        "Critical" => Some(Severity::Critical),
+        "High" | "Major" => Some(Severity::High),
+        "Medium" => Some(Severity::Medium),
+        "Low" | "Minor" => Some(Severity::Low),
+        _ => None,
+    };
+    if line.ends_with(':') {
+        return None;
+    }
+
+    // (byte offset, word), skipping list numbering like "1." or "2)"
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    for piece in line.split(|c: char| !c.is_alphanumeric()) {
+        let numbering = words.is_empty() && piece.chars().all(|c| c.is_ascii_digit());
+        if !piece.is_empty() && !numbering {
+            // Separators may be multi-byte (emoji), so take the offset from the slice itself
+            words.push((piece.as_ptr() as usize - line.as_ptr() as usize, piece));
+        }
+    }
+
+    let mut named = words.iter().filter_map(|(_, word)| word_severity(word)).collect::<Vec<_>>();
+    named.dedup();
+    if named.len() != 1 {
+        return None;
+    }
+
+    words.iter().enumerate().find_map(|(index, &(offset, word))| {
+        let severity = word_severity(word)?;
+        let first = index == 0;
+        let labelled = index > 0 && LABELS.contains(&words[index - 1].1.to_ascii_lowercase().as_str());
+        let bracketed = line[..offset].ends_with(['[', '(']) && line[offset + word.len()..].starts_with([']', ')']);
+        // "Low-level call" and "high-level overview" describe, they don't rate
+        let descriptive = ["-level", "-hanging"].iter().any(|suffix| line[offset + word.len()..].to_ascii_lowercase().starts_with(suffix));
+        ((first && !descriptive) || labelled || bracketed).then_some(severity)
+    })
+}
+
+fn recommendation_of(line: &str) -> Option<&str> {
+    ["Recommendation", "Mitigation", "Optimization", "Suggestion"].iter().find_map(|label| {
+        let rest = line.strip_prefix(label)?;
+        let rest = rest.trim_start_matches('s').trim_start_matches(':').trim_matches('*').trim();
+        Some(rest).filter(|rest| !rest.is_empty())
+    })
+}
+
+/// `N` from "line N", "Line N" or "lines N-M"
+fn line_number(text: &str) -> Option<usize> {
+    let lower = text.to_ascii_lowercase();
+    lower.match_indices("line").find_map(|(i, _)| {
+        let rest = lower[i + 4..].trim_start_matches('s').trim_start_matches([' ', ':', '#']);
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: [&str; 15] = [
+        "severity", "rule", "rule_id", "detected_by", "title", "function", "line", "column", "description",
+        "recommendation", "confidence", "references", "fingerprint", "effort", "example",
+    ];
+
+    #[test]
+    fn audit_findings_carry_fingerprint_effort_function_and_example() {
+        let vuln = Vulnerability {
+            name: "Unchecked Arithmetic".to_string(),
+            severity: Severity::High,
+            function: Some("withdraw".to_string()),
+            effort: Some(Effort::Quick),
+            example: Some(FixExample { before: "a - b".to_string(), after: "a.checked_sub(b)".to_string() }),
+            fingerprint: "0123456789abcdef".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(Finding::from(&vuln)).unwrap();
+        assert_eq!(json["function"], "withdraw");
+        assert_eq!(json["effort"], "Quick");
+        assert_eq!(json["example"]["after"], "a.checked_sub(b)");
+        assert_eq!(json["fingerprint"], "0123456789abcdef");
+    }
+
+    #[test]
+    fn every_field_is_present_even_when_unknown() {
+        let findings = findings_from_text("High: reentrancy in withdraw (line 12)\nRecommendation: update state first");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, Some(12));
+        assert_eq!(findings[0].recommendation.as_deref(), Some("update state first"));
+
+        let json = serde_json::to_value(&findings[0]).unwrap();
+        for field in FIELDS {
+            assert!(json.get(field).is_some(), "missing {}", field);
+        }
+        assert!(json["fingerprint"].is_null() && json["effort"].is_null() && json["example"].is_null());
+    }
+
+    #[test]
+    fn mentioned_severities_do_not_start_findings() {
+        assert!(findings_from_text("Low-level calls are highly recommended to be avoided").is_empty());
+        assert_eq!(findings_from_text("1. [Medium] Unbounded loop")[0].severity, Severity::Medium);
+    }
+}
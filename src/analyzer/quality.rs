@@ -1,28 +1,46 @@
-use std::path::PathBuf;
-use std::error::Error;
-use std::fs;
+use std::path::Path;
+use crate::error::Result;
 use colored::*;
 use crate::ai;
-use crate::analyzer::Analyzer;
+use crate::analyzer::{static_only_note, Analysis, Analyzer};
+use crate::analyzer::output::findings_from_text;
+use crate::analyzer::natspec::{doc_coverage, format_doc_coverage};
 use crate::parser::ParsedContract;
+use tracing::info;
 
 pub struct QualityAnalyzer;
 
 #[async_trait::async_trait]
 impl Analyzer for QualityAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let content = fs::read_to_string(file)?;
-        let _parsed = ParsedContract::new(content.clone())?;
+    async fn analyze_parsed(&self, parsed: &ParsedContract, _file: &Path) -> Result<Analysis> {
+        let content = &parsed.source;
+        let coverage = doc_coverage(parsed);
+        let docs = coverage.as_ref().map(|coverage| format!("{}\n", format_doc_coverage(coverage))).unwrap_or_default();
 
-        println!("📊 Analyzing code quality metrics...");
-        println!("⏳ Please wait while we process your contract...\n");
+        info!("📊 Analyzing code quality metrics...");
+        info!("⏳ Please wait while we process your contract...");
 
-        let analysis = ai::analyze_code_quality(&content).await?;
+        if !ai::enabled() {
+            let text = format!(
+                "\n{}\n{}\n{}\n{}",
+                "🎯 Code Quality Analysis Report".bright_green().bold(),
+                "═══════════════════════════".bright_green(),
+                static_only_note(),
+                docs
+            );
+            let result = Analysis::new("quality", text);
+            return Ok(match coverage {
+                Some(coverage) => result.with_metric("doc_coverage_percent", coverage.percent()),
+                None => result,
+            });
+        }
+        let analysis = ai::analyze_code_quality(content).await?;
 
-        Ok(format!(
-            "\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
+        let text = format!(
+            "\n{}\n{}\n\n{}{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
             "🎯 Code Quality Analysis Report".bright_green().bold(),
             "═══════════════════════════".bright_green(),
+            docs,
             "📊 Quality Metrics Overview:".yellow().bold(),
             format_metrics(&analysis),
             "💡 Best Practices Analysis:".yellow().bold(),
@@ -30,7 +48,12 @@ impl Analyzer for QualityAnalyzer {
             "⚠️  Areas for Improvement:".yellow().bold(),
             format_improvements(&analysis),
             format_summary(&analysis)
-        ))
+        );
+        let mut result = Analysis::new("quality", text).with_findings(findings_from_text(&analysis));
+        if let Some(coverage) = coverage {
+            result = result.with_metric("doc_coverage_percent", coverage.percent());
+        }
+        Ok(result)
     }
 }
 
@@ -48,10 +71,10 @@ fn format_metrics(analysis: &str) -> String {
             } else if line.contains("Poor") {
                 format!("⚠️  {}", line.red())
             } else if line.contains("Analysis:") || line.contains("Metrics:") {
-                let separator = format!("{}", "─".repeat(50));
+                let separator = "─".repeat(50);
                 format!("\n┌{}\n{}\n", separator, line.cyan().bold())
             } else if line.trim().ends_with(":") {
-                let separator = format!("{}", "─".repeat(30));
+                let separator = "─".repeat(30);
                 format!("\n└{}\n  {}", separator, line.yellow().bold())
             } else if line.contains("Score:") {
                 format!("📈 {}", line.cyan())
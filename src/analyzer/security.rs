@@ -1,33 +1,62 @@
-use std::path::PathBuf;
-use std::error::Error;
+use std::path::Path;
+use crate::error::Result;
 use std::fs;
 use colored::*;
 use crate::ai;
-use crate::analyzer::Analyzer;
+use crate::analyzer::{static_only_note, Analysis, Analyzer};
+use crate::analyzer::output::{findings_from_text, Finding, SeverityCounts};
+use crate::audit;
+use crate::options::AnalysisOptions;
+use crate::parser::ParsedContract;
+use tracing::info;
 
 pub struct SecurityAnalyzer;
 
 #[async_trait::async_trait]
 impl Analyzer for SecurityAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let content = fs::read_to_string(file)
-            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
-        println!("🔍 Analyzing security patterns...");
-        println!("⏳ Please wait while we process your contract...\n");
-        let analysis = ai::analyze_security_issues(&content).await?;
-
-        let output = format!(
-            "\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
-            "🔒 Security Analysis Report".bright_yellow().bold(),
-            "═".repeat(40).bright_yellow(),
-            "🔍 Security Findings:".yellow().bold(),
-            format_security_findings(&analysis),
-            "💡 Recommendations:".yellow().bold(),
-            format_recommendations(&analysis),
-            format_summary(&analysis)
-        );
-        Ok(output)
+    // Works on the raw source, so files that don't parse still get reviewed
+    async fn analyze(&self, file: &Path) -> Result<Analysis> {
+        analyze_source(&fs::read_to_string(file)?, file).await
     }
+
+    async fn analyze_parsed(&self, contract: &ParsedContract, file: &Path) -> Result<Analysis> {
+        analyze_source(&contract.source, file).await
+    }
+}
+
+async fn analyze_source(content: &str, file: &Path) -> Result<Analysis> {
+    info!("🔍 Analyzing security patterns...");
+    info!("⏳ Please wait while we process your contract...");
+    if !ai::enabled() {
+        return static_analysis(content, file).await;
+    }
+    let analysis = ai::analyze_security_issues(content).await?;
+    let findings = findings_from_text(&analysis);
+
+    let text = format!(
+        "\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
+        "🔒 Security Analysis Report".bright_yellow().bold(),
+        "═".repeat(40).bright_yellow(),
+        "🔍 Security Findings:".yellow().bold(),
+        format_security_findings(&analysis),
+        "💡 Recommendations:".yellow().bold(),
+        format_recommendations(&analysis),
+        format_summary(&SeverityCounts::of(&findings))
+    );
+    Ok(Analysis::new("security", text).with_findings(findings))
+}
+
+/// The audit pattern rules in place of the AI review
+async fn static_analysis(content: &str, file: &Path) -> Result<Analysis> {
+    let result = AnalysisOptions::new().analyzer()?.audit_source(content, &file.display().to_string()).await?;
+    let text = format!(
+        "\n{}\n{}\n{}\n{}",
+        "🔒 Security Analysis Report".bright_yellow().bold(),
+        "═".repeat(40).bright_yellow(),
+        static_only_note(),
+        audit::report::generate_full_report(&result)
+    );
+    Ok(Analysis::new("security", text).with_findings(result.vulnerabilities().map(Finding::from).collect()))
 }
 
 fn format_security_findings(analysis: &str) -> String {
@@ -64,7 +93,7 @@ fn format_security_findings(analysis: &str) -> String {
 
 fn format_recommendations(analysis: &str) -> String {
     let mut recommendations = String::new();
-    recommendations.push_str("\n");
+    recommendations.push('\n');
 
     // Extract and format recommendations
     for line in analysis.lines() {
@@ -84,11 +113,8 @@ fn format_recommendations(analysis: &str) -> String {
     recommendations
 }
 
-fn format_summary(analysis: &str) -> String {
-    // Count issues by severity
-    let critical = analysis.lines().filter(|line| line.contains("Critical")).count();
-    let high = analysis.lines().filter(|line| line.contains("High")).count();
-    let medium = analysis.lines().filter(|line| line.contains("Medium")).count();
+fn format_summary(counts: &SeverityCounts) -> String {
+    let SeverityCounts { critical, high, medium, .. } = *counts;
 
     format!(
         "\n📊 Security Summary\n{}\n\n{}\n{}\n{}\n\n{}\n{}\n",
@@ -1,24 +1,31 @@
-use std::path::PathBuf;
-use std::error::Error;
-use std::fs;
+use std::path::Path;
+use crate::error::Result;
 use colored::*;
 use crate::ai;
-use crate::analyzer::Analyzer;
+use crate::analyzer::{static_only_note, Analysis, Analyzer};
+use crate::analyzer::output::{findings_from_text, SeverityCounts};
 use crate::parser::ParsedContract;
+use tracing::info;
+
+/// Arbitrum's recommended maximum contract size in bytes
+const L2_SIZE_LIMIT: usize = 24576;
 
 pub struct SizeAnalyzer;
 
 #[async_trait::async_trait]
 impl Analyzer for SizeAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let content = fs::read_to_string(file)?;
-        let parsed = ParsedContract::new(content.clone())?;
+    async fn analyze_parsed(&self, parsed: &ParsedContract, _file: &Path) -> Result<Analysis> {
+        let content = &parsed.source;
 
-        println!("📏 Analyzing contract with {} functions and {} structs...", 
+        info!("📏 Analyzing contract with {} functions and {} structs...", 
                 parsed.function_count(), parsed.struct_count());
-        println!("⏳ Please wait while we process your contract...\n");
+        info!("⏳ Please wait while we process your contract...");
 
-        let analysis = ai::analyze_contract_size(&content).await?;
+        let analysis = match ai::enabled() {
+            true => Some(ai::analyze_contract_size(content).await?),
+            false => None,
+        };
+        let findings = findings_from_text(analysis.as_deref().unwrap_or_default());
 
         // Enhanced L2-specific size analysis
         let mut total_size = 0;
@@ -38,18 +45,49 @@ impl Analyzer for SizeAnalyzer {
             component_sizes.push(("Events", event_size));
         }
 
-        Ok(format!(
-            "\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
-            "📊 Contract Size Analysis Report".bright_green().bold(),
-            "════════════════════════════".bright_green(),
-            "🔍 Size Metrics:".yellow().bold(),
-            format_metrics(&component_sizes, total_size),
-            "🔍 Size Issues:".yellow().bold(),
-            format_issues(&analysis),
-            "💡 Optimization Suggestions:".yellow().bold(),
-            format_suggestions(&analysis),
-            format_summary(&analysis, total_size)
-        ))
+        let text = match &analysis {
+            Some(analysis) => format!(
+                "\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}\n",
+                "📊 Contract Size Analysis Report".bright_green().bold(),
+                "════════════════════════════".bright_green(),
+                "🔍 Size Metrics:".yellow().bold(),
+                format_metrics(&component_sizes, total_size),
+                "🔍 Size Issues:".yellow().bold(),
+                format_issues(analysis),
+                "💡 Optimization Suggestions:".yellow().bold(),
+                format_suggestions(analysis),
+                format_summary(&SeverityCounts::of(&findings), total_size)
+            ),
+            None => format!(
+                "\n{}\n{}\n{}\n{}\n{}\n\n{}\n{}\n",
+                "📊 Contract Size Analysis Report".bright_green().bold(),
+                "════════════════════════════".bright_green(),
+                static_only_note(),
+                "🔍 Size Metrics:".yellow().bold(),
+                format_metrics(&component_sizes, total_size),
+                "💡 Optimization Suggestions:".yellow().bold(),
+                format_suggestions("")
+            ),
+        };
+        let mut result = Analysis::new("size", text)
+            .with_findings(findings)
+            .with_metric("total_bytes", total_size)
+            .with_metric("within_l2_limit", total_size <= L2_SIZE_LIMIT)
+            .with_metric("functions", parsed.function_count())
+            .with_metric("structs", parsed.struct_count());
+        for (name, size) in component_sizes {
+            result = result.with_metric(component_metric(name), size);
+        }
+        Ok(result)
+    }
+}
+
+/// Metric key for a size component, e.g. `storage_bytes`
+fn component_metric(name: &str) -> &'static str {
+    match name {
+        "Functions" => "function_bytes",
+        "Storage" => "storage_bytes",
+        _ => "event_bytes",
     }
 }
 
@@ -67,13 +105,13 @@ fn format_metrics(components: &[(&str, usize)], total: usize) -> String {
 
         output.push_str(&format!("{}: {} bytes ({}%)\n", name, size, percentage));
         output.push_str(&format!("[{}{}]\n\n", 
-            bar.green().to_string(), 
+            bar.green(), 
             " ".repeat(50 - bar_length)
         ));
     }
 
     // Add L2-specific size analysis
-    if total > 24576 { // Arbitrum's recommended max size
+    if total > L2_SIZE_LIMIT {
         output.push_str(&"⚠️ ".yellow().to_string());
         output.push_str("Contract exceeds recommended L2 size limit\n");
         output.push_str("Consider splitting functionality into multiple contracts\n");
@@ -123,14 +161,12 @@ fn format_suggestions(content: &str) -> String {
     suggestions.join("\n")
 }
 
-fn format_summary(content: &str, total_size: usize) -> String {
-    let critical_count = count_severity(content, "Critical");
-    let major_count = count_severity(content, "Major");
-    let medium_count = count_severity(content, "Medium");
-    let minor_count = count_severity(content, "Minor");
+/// Size reports rate Major/Minor; the finding parser files those as High/Low
+fn format_summary(counts: &SeverityCounts, total_size: usize) -> String {
+    let SeverityCounts { critical: critical_count, high: major_count, medium: medium_count, low: minor_count, .. } = *counts;
 
     // Calculate size-related metrics
-    let size_severity = if total_size > 24576 {
+    let size_severity = if total_size > L2_SIZE_LIMIT {
         "Critical"
     } else if total_size > 16384 {
         "Major"
@@ -159,9 +195,3 @@ fn format_summary(content: &str, total_size: usize) -> String {
         l2_recommendations
     )
 }
-
-fn count_severity(text: &str, severity: &str) -> usize {
-    text.lines()
-        .filter(|line| line.contains(severity))
-        .count()
-}
\ No newline at end of file
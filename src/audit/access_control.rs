@@ -1,48 +1,85 @@
 use crate::audit::vulnerabilities::{Vulnerability, Severity};
-use crate::audit::rules::AuditRule;
-use std::error::Error;
+use crate::audit::rules::{AuditRule, HEURISTIC_CONFIDENCE};
+use crate::audit::source::{find_line, mask, public_mutating_methods};
+use crate::error::Result;
 use async_trait::async_trait;
 
+/// Calls and helpers in a function body that restrict who can call it
+const GUARDS: [&str; 7] = ["only_owner", "only_role", "has_role", "is_owner", "ensure!(is_owner", "require!(msg.sender", "#[access_control"];
+
 pub struct AccessControlRule;
 
+/// Public `&mut self` methods with no guard in their own body, as
+/// (name, line, signature); `None` when `content` isn't parseable Rust
+fn unguarded_functions(content: &str) -> Option<Vec<(String, usize, String)>> {
+    let file = syn::parse_file(content).ok()?;
+    let code = mask(content);
+    let unguarded = public_mutating_methods(&file, content).into_iter()
+        .filter(|(method, start, end)| {
+            let by_attribute = method.attrs.iter()
+                .filter_map(|attr| attr.path().segments.last())
+                .any(|segment| segment.ident == "access_control" || segment.ident == "only_owner");
+            !by_attribute && !is_guarded(&code[*start..*end])
+        })
+        .map(|(method, start, end)| {
+            let text = &content[start..end];
+            let signature = text.lines().next().unwrap_or_default().trim().trim_end_matches('{').trim().to_string();
+            (method.sig.ident.to_string(), content[..start].matches('\n').count() + 1, signature)
+        })
+        .collect();
+    Some(unguarded)
+}
+
+/// Whether a function body compares the caller or calls an ownership or role check
+pub(crate) fn is_guarded(body: &str) -> bool {
+    let compact: String = body.split_whitespace().collect();
+    let compares_sender = ["msg::sender()", "msg_sender()"].iter().any(|sender| {
+        ["==", "!="].iter().any(|op| compact.contains(&format!("{}{}", sender, op)) || compact.contains(&format!("{}{}", op, sender)))
+    });
+    compares_sender || GUARDS.iter().any(|guard| compact.contains(guard))
+}
+
 #[async_trait]
 impl AuditRule for AccessControlRule {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
-        // Check for public functions without access control
-        if content.contains("pub fn") && !content.contains("#[access_control") {
-            let has_role_check = content.contains("require!(msg.sender") || 
-                                content.contains("ensure!(is_owner") ||
-                                content.contains("only_owner");
-
-            if !has_role_check {
-                vulnerabilities.push(Vulnerability {
-                    name: "Missing Access Control".to_string(),
-                    severity: Severity::High,
-                    risk_description: "Functions can be called by unauthorized users".to_string(),
-                    recommendation: "Implement role-based access control using Stylus SDK".to_string(),
-                });
+        match unguarded_functions(content) {
+            Some(unguarded) => {
+                for (function, line, signature) in unguarded {
+                    vulnerabilities.push(Vulnerability {
+                        name: "Missing Access Control".to_string(),
+                        severity: Severity::High,
+                        risk_description: format!("{} changes contract state without checking who calls it", function),
+                        recommendation: format!("Check msg::sender() against the owner or a role at the start of {}", function),
+                        function: Some(function),
+                        line: Some(line),
+                        snippet: Some(signature),
+                        ..Default::default()
+                    });
+                }
             }
-        }
-
-        // Check for privileged operations
-        if content.contains("admin") || content.contains("owner") {
-            if !content.contains("initialize") || !content.contains("constructor") {
-                vulnerabilities.push(Vulnerability {
-                    name: "Uninitialized Admin Role".to_string(),
-                    severity: Severity::Critical,
-                    risk_description: "Contract may lack proper administrative controls".to_string(),
-                    recommendation: "Initialize admin roles in constructor or initialization function".to_string(),
-                });
+            // Not Rust we can parse: fall back to looking at the whole file
+            None => {
+                if code.contains("pub fn") && !code.contains("#[access_control")
+                    && !GUARDS.iter().any(|guard| code.contains(guard)) {
+                    vulnerabilities.push(Vulnerability {
+                        name: "Missing Access Control".to_string(),
+                        severity: Severity::High,
+                        risk_description: "Functions can be called by unauthorized users".to_string(),
+                        recommendation: "Implement role-based access control using Stylus SDK".to_string(),
+                        ..Default::default()
+                    }.located(find_line(content, &["pub fn"])));
+                }
             }
         }
 
         // Check for role management
-        if content.contains("role") || content.contains("permission") {
-            let has_role_management = content.contains("grant_role") || 
-                                    content.contains("revoke_role") ||
-                                    content.contains("renounce_role");
+        if code.contains("role") || code.contains("permission") {
+            let has_role_management = code.contains("grant_role") || 
+                                    code.contains("revoke_role") ||
+                                    code.contains("renounce_role");
 
             if !has_role_management {
                 vulnerabilities.push(Vulnerability {
@@ -50,7 +87,8 @@ impl AuditRule for AccessControlRule {
                     severity: Severity::Medium,
                     risk_description: "Unable to modify roles after deployment".to_string(),
                     recommendation: "Implement complete role management functionality".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["role", "roles", "permission", "permissions"])));
             }
         }
 
@@ -60,4 +98,24 @@ impl AuditRule for AccessControlRule {
     fn name(&self) -> &'static str {
         "Access Control Pattern Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-ACCESS-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Public state-changing functions without an ownership or role check"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Missing Access Control", "Incomplete Role Management"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_confidence(&self) -> f64 {
+        HEURISTIC_CONFIDENCE
+    }
 }
\ No newline at end of file
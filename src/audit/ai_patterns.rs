@@ -1,13 +1,50 @@
+use crate::audit::examples;
 use crate::audit::vulnerabilities::{Vulnerability, Severity};
 use crate::audit::rules::AuditRule;
-use std::error::Error;
-use std::collections::{HashMap, HashSet};
+use crate::audit::source::mask;
+use crate::error::Result;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Sources whose patterns stay cached; a directory audit past this many files
+/// drops the oldest, which only costs recomputing them in watch mode
+const MAX_CACHED_SOURCES: usize = 256;
+
+/// Patterns found per source, keyed by a hash of the whole source, so two
+/// contracts sharing their license header and imports never share an entry
+#[derive(Default)]
+struct PatternCache {
+    patterns: HashMap<u64, Vec<(String, f64)>>,
+    /// Keys oldest first, for eviction
+    order: VecDeque<u64>,
+}
+
+impl PatternCache {
+    fn insert(&mut self, key: u64, patterns: Vec<(String, f64)>) {
+        if self.patterns.insert(key, patterns).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > MAX_CACHED_SOURCES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.patterns.remove(&oldest);
+            }
+        }
+    }
+}
 
 pub struct AIPatternDetector {
-    pattern_cache: HashMap<String, Vec<(String, f64)>>,
+    /// A Mutex so audits of several files can share one detector
+    pattern_cache: Mutex<PatternCache>,
     pattern_weights: HashMap<String, f64>,
     learning_threshold: f64,
-    detected_vulnerabilities: HashSet<String>,
+}
+
+impl Default for AIPatternDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AIPatternDetector {
@@ -39,10 +76,9 @@ impl AIPatternDetector {
         pattern_weights.insert("timestamp_dependence".to_string(), 1.3);
 
         Self {
-            pattern_cache: HashMap::new(),
+            pattern_cache: Mutex::new(PatternCache::default()),
             pattern_weights,
             learning_threshold: 0.80, // Increased threshold for higher precision
-            detected_vulnerabilities: HashSet::new(),
         }
     }
 
@@ -58,26 +94,33 @@ impl AIPatternDetector {
             .collect()
     }
 
-    fn analyze_semantic_patterns(&mut self, content: &str) -> Vec<(String, f64)> {
-        let cache_key = content.get(0..100).unwrap_or(content).to_string();
-        if let Some(cached_patterns) = self.pattern_cache.get(&cache_key) {
-            return cached_patterns.clone();
+    fn analyze_semantic_patterns(&self, content: &str) -> Vec<(String, f64)> {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let cache_key = hasher.finish();
+        // A poisoned cache only costs a recomputation
+        if let Some(cached_patterns) = self.pattern_cache.lock().ok().and_then(|cache| cache.patterns.get(&cache_key).cloned()) {
+            debug!(patterns = cached_patterns.len(), "semantic pattern cache hit");
+            return cached_patterns;
         }
 
         let mut patterns = Vec::new();
+        let code = mask(content);
 
         // Enhanced pattern detection
-        self.detect_security_patterns(content, &mut patterns);
-        self.detect_l2_optimization_patterns(content, &mut patterns);
-        self.detect_stylus_specific_patterns(content, &mut patterns);
-        self.detect_advanced_patterns(content, &mut patterns); // New method
+        self.detect_security_patterns(&code, &mut patterns);
+        self.detect_l2_optimization_patterns(&code, &mut patterns);
+        self.detect_stylus_specific_patterns(&code, &mut patterns);
+        self.detect_advanced_patterns(&code, &mut patterns); // New method
 
         patterns = self.apply_pattern_weights(patterns);
-        self.pattern_cache.insert(cache_key, patterns.clone());
+        if let Ok(mut cache) = self.pattern_cache.lock() {
+            cache.insert(cache_key, patterns.clone());
+        }
         patterns
     }
 
-    fn detect_security_patterns(&mut self, content: &str, patterns: &mut Vec<(String, f64)>) {
+    fn detect_security_patterns(&self, content: &str, patterns: &mut Vec<(String, f64)>) {
         // Enhanced access control detection
         if content.contains("pub fn") || content.contains("public") || content.contains("external") {
             let mut confidence = 0.85;
@@ -151,7 +194,7 @@ impl AIPatternDetector {
         }
     }
 
-    fn detect_l2_optimization_patterns(&mut self, content: &str, patterns: &mut Vec<(String, f64)>) {
+    fn detect_l2_optimization_patterns(&self, content: &str, patterns: &mut Vec<(String, f64)>) {
         // Enhanced batch operations detection
         if content.contains("loop") || content.contains("for") || content.contains("while") {
             let mut confidence = 0.75;
@@ -189,7 +232,7 @@ impl AIPatternDetector {
         }
     }
 
-    fn detect_stylus_specific_patterns(&mut self, content: &str, patterns: &mut Vec<(String, f64)>) {
+    fn detect_stylus_specific_patterns(&self, content: &str, patterns: &mut Vec<(String, f64)>) {
         // Improved SDK integration detection
         if content.contains("stylus_sdk") {
             let mut confidence = 0.75;
@@ -227,7 +270,7 @@ impl AIPatternDetector {
         }
     }
 
-    fn detect_advanced_patterns(&mut self, content: &str, patterns: &mut Vec<(String, f64)>) {
+    fn detect_advanced_patterns(&self, content: &str, patterns: &mut Vec<(String, f64)>) {
         // Enhanced event validation patterns
         if content.contains("event") || content.contains("emit") || content.contains("#[event]") {
             let mut confidence = 0.80;
@@ -280,8 +323,7 @@ impl AIPatternDetector {
 
 #[async_trait::async_trait]
 impl AuditRule for AIPatternDetector {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
-        self.detected_vulnerabilities.clear();
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
         let patterns = self.analyze_semantic_patterns(content);
 
@@ -293,76 +335,95 @@ impl AuditRule for AIPatternDetector {
                         severity: Severity::High,
                         risk_description: "Functions lack proper access control mechanisms".to_string(),
                         recommendation: "Implement role-based access control using Stylus SDK's security features".to_string(),
+                        ..Default::default()
                     },
                     "Memory Safety Risk" => Vulnerability {
                         name: "Memory Safety Issue".to_string(),
                         severity: Severity::Critical,
                         risk_description: "Potential memory corruption from unsafe operations".to_string(),
                         recommendation: "Replace unsafe operations with safe alternatives and use Rust's ownership system".to_string(),
+                        ..Default::default()
                     },
                     "Reentrancy Risk" => Vulnerability {
                         name: "Reentrancy Vulnerability".to_string(),
                         severity: Severity::Critical,
                         risk_description: "Contract state could be manipulated through external calls".to_string(),
                         recommendation: "Implement reentrancy guards and follow checks-effects-interactions pattern".to_string(),
+                        ..Default::default()
                     },
                     "Arithmetic Safety Risk" => Vulnerability {
                         name: "Arithmetic Safety Risk".to_string(),
                         severity: Severity::High,
                         risk_description: "Potential integer overflow/underflow in calculations".to_string(),
                         recommendation: "Use checked arithmetic operations and consider using SafeMath equivalents".to_string(),
+                        ..Default::default()
                     },
                     "Batch Operations" => Vulnerability {
                         name: "Unoptimized Batch Operations".to_string(),
                         severity: Severity::Medium,
                         risk_description: "Inefficient gas usage in loop operations".to_string(),
                         recommendation: "Implement batch processing and optimize loop conditions".to_string(),
+                        ..Default::default()
                     },
                     "State Packing" => Vulnerability {
                         name: "Inefficient State Packing".to_string(),
                         severity: Severity::Low,
                         risk_description: "Suboptimal storage layout increases gas costs".to_string(),
                         recommendation: "Use packed structs and optimize storage slot usage".to_string(),
+                        ..Default::default()
                     },
                     "Event Validation" => Vulnerability {
                         name: "Insufficient Event Validation".to_string(),
                         severity: Severity::Medium,
                         risk_description: "Events may lack proper validation or indexing".to_string(),
                         recommendation: "Add proper event parameter validation and optimize indexing".to_string(),
+                        ..Default::default()
                     },
                     "Upgrade Safety" => Vulnerability {
                         name: "Upgrade Safety Concerns".to_string(),
                         severity: Severity::High,
                         risk_description: "Contract upgrades may introduce vulnerabilities".to_string(),
                         recommendation: "Implement proper upgrade patterns and storage layout checks".to_string(),
+                        ..Default::default()
                     },
                     "Cross-chain Security" => Vulnerability {
                         name: "Cross-chain Interaction Risks".to_string(),
                         severity: Severity::Critical,
                         risk_description: "Unsafe cross-chain message handling".to_string(),
                         recommendation: "Implement proper message verification and handle edge cases".to_string(),
+                        ..Default::default()
                     },
                     "DoS Risk" => Vulnerability {
                         name: "Denial of Service Risk".to_string(),
                         severity: Severity::High,
                         risk_description: "Potential for denial-of-service attacks due to unbounded loops or resource consumption.".to_string(),
                         recommendation: "Implement input validation and resource limits to prevent DoS attacks.".to_string(),
+                        ..Default::default()
                     },
                     "Input Validation Risk" => Vulnerability {
                         name: "Insufficient Input Validation".to_string(),
                         severity: Severity::High,
                         risk_description: "Lack of input validation can lead to unexpected behavior or vulnerabilities.".to_string(),
                         recommendation: "Implement robust input validation to sanitize and check all inputs before processing.".to_string(),
+                        ..Default::default()
                     },
                     "Timestamp Dependence" => Vulnerability {
                         name: "Timestamp Dependence Vulnerability".to_string(),
                         severity: Severity::Medium,
                         risk_description: "Contract logic relies on block timestamps, which can be manipulated by miners.".to_string(),
                         recommendation: "Avoid using block timestamps for critical logic; use timelocks or other mechanisms for predictable timing.".to_string(),
+                        ..Default::default()
                     },
                     _ => continue,
                 };
-                vulnerabilities.push(vuln);
+                let example = match pattern.as_str() {
+                    "Reentrancy Risk" => Some(examples::reentrancy(content)),
+                    "Arithmetic Safety Risk" => Some(examples::checked_arithmetic(content, syn::parse_file(content).is_ok())),
+                    _ => None,
+                };
+                // Two decimals, so exports read 0.95 rather than 0.9500000000000001
+                let confidence = (confidence * 100.0).round() / 100.0;
+                vulnerabilities.push(Vulnerability { example, confidence: Some(confidence), ..vuln });
             }
         }
 
@@ -372,4 +433,84 @@ impl AuditRule for AIPatternDetector {
     fn name(&self) -> &'static str {
         "AI-Powered Security & Pattern Analyzer"
     }
-}
\ No newline at end of file
+
+    fn id(&self) -> &'static str {
+        "SA-AI-PATTERN-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Weighted keyword heuristics for common vulnerability classes"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Missing Access Control",
+            "Memory Safety Issue",
+            "Reentrancy Vulnerability",
+            "Arithmetic Safety Risk",
+            "Unoptimized Batch Operations",
+            "Inefficient State Packing",
+            "Insufficient Event Validation",
+            "Upgrade Safety Concerns",
+            "Cross-chain Interaction Risks",
+            "Denial of Service Risk",
+            "Insufficient Input Validation",
+            "Timestamp Dependence Vulnerability",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_oldest_sources_are_evicted_past_the_limit() {
+        let mut cache = PatternCache::default();
+        for key in 0..MAX_CACHED_SOURCES as u64 + 10 {
+            cache.insert(key, vec![(format!("pattern {}", key), 0.9)]);
+        }
+        // Re-inserting a cached source doesn't queue it twice
+        cache.insert(MAX_CACHED_SOURCES as u64, Vec::new());
+
+        assert_eq!(cache.patterns.len(), MAX_CACHED_SOURCES);
+        assert_eq!(cache.order.len(), MAX_CACHED_SOURCES);
+        assert!((0..10).all(|key| !cache.patterns.contains_key(&key)));
+        assert!(cache.patterns.contains_key(&10) && cache.patterns.contains_key(&(MAX_CACHED_SOURCES as u64 + 9)));
+    }
+
+    #[test]
+    fn sources_sharing_a_header_get_their_own_entries() {
+        let header = format!("// SPDX-License-Identifier: MIT\n{}", "use stylus_sdk::prelude::*;\n".repeat(64));
+        let plain = format!("{}fn total(&self) -> U256 {{ self.total.get() }}\n", header);
+        let risky = format!("{}fn raw(&self) {{ unsafe {{ std::ptr::read(self.ptr) }} }}\n", header);
+        let detector = AIPatternDetector::new();
+
+        let risky_patterns = detector.analyze_semantic_patterns(&risky);
+        let plain_patterns = detector.analyze_semantic_patterns(&plain);
+
+        assert_ne!(risky_patterns, plain_patterns);
+        assert_eq!(detector.pattern_cache.lock().unwrap().patterns.len(), 2);
+        assert_eq!(detector.analyze_semantic_patterns(&risky), risky_patterns);
+    }
+
+    #[tokio::test]
+    async fn fixtures_with_the_same_header_are_audited_separately() {
+        use crate::audit::rules::found;
+
+        let plain = include_str!("../../test ex/cache/plain.rs");
+        let raw_pointer = include_str!("../../test ex/cache/raw_pointer.rs");
+        let memory_issue = |found: &[(String, usize)]| found.iter().any(|(name, _)| name == "Memory Safety Issue");
+        let detector = AIPatternDetector::new();
+
+        // Either order: the second source must not be answered from the first one's entry
+        assert!(memory_issue(&found(&detector, raw_pointer).await));
+        assert!(!memory_issue(&found(&detector, plain).await));
+        let detector = AIPatternDetector::new();
+        assert!(!memory_issue(&found(&detector, plain).await));
+        assert!(memory_issue(&found(&detector, raw_pointer).await));
+    }
+}
@@ -0,0 +1,326 @@
+//! Unchecked arithmetic on contract state in Stylus code. Contracts deploy as
+//! release builds, where Rust integer overflow wraps instead of panicking
+
+use async_trait::async_trait;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{mask, rust_functions};
+use crate::audit::vulnerabilities::{Effort, FixExample, Severity, Vulnerability};
+use crate::error::Result;
+
+/// Binary operators first, so `a + b` isn't also read as a compound `+=`
+const OPERATORS: [(&str, &str); 6] = [
+    (" + ", "checked_add"), (" - ", "checked_sub"), (" * ", "checked_mul"),
+    ("+=", "checked_add"), ("-=", "checked_sub"), ("*=", "checked_mul"),
+];
+/// Names of values whose overflow loses or mints funds
+const FUNDS: [&str; 7] = ["balance", "supply", "total", "stake", "reward", "deposit", "reserve"];
+
+/// An unchecked operation on a state value
+struct Unchecked {
+    function: String,
+    line: usize,
+    column: usize,
+    /// The operation as written, e.g. `self.total_staked + amount`
+    expression: String,
+    /// The same operation made overflow-safe
+    rewrite: String,
+    /// The source line, and the line with the operation rewritten
+    before: String,
+    after: String,
+}
+
+/// Overflow-prone +, - and * on storage values in Stylus contracts
+pub struct ArithmeticSafetyRule;
+
+#[async_trait]
+impl AuditRule for ArithmeticSafetyRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        if syn::parse_file(content).is_err() {
+            return Ok(Vec::new());
+        }
+        let vulnerabilities = unchecked_operations(content).into_iter()
+            .map(|found| {
+                let lower = found.expression.to_lowercase();
+                let severity = match FUNDS.iter().any(|name| lower.contains(name)) {
+                    true => Severity::High,
+                    false => Severity::Medium,
+                };
+                Vulnerability {
+                    name: "Unchecked Arithmetic".to_string(),
+                    severity,
+                    risk_description: format!("`{}` in {} isn't overflow-checked; release builds wrap silently instead of reverting",
+                        found.expression, found.function),
+                    recommendation: format!("Use `{}`", found.rewrite),
+                    function: Some(found.function),
+                    line: Some(found.line),
+                    column: Some(found.column),
+                    snippet: Some(found.before.clone()),
+                    example: Some(FixExample { before: found.before, after: found.after }),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Stylus Arithmetic Safety Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ARITHMETIC-002"
+    }
+
+    fn description(&self) -> &'static str {
+        "Unchecked +, - and * on contract state in Stylus code"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Unchecked Arithmetic"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// The first unchecked operation on each line that involves `self.` state or
+/// a local read from it; loop headers and already checked lines are skipped
+fn unchecked_operations(source: &str) -> Vec<Unchecked> {
+    let code = mask(source);
+    let mut found = Vec::new();
+    for (function, start, end) in rust_functions(source) {
+        let Some(open) = code[start..end].find('{').map(|i| start + i + 1) else { continue };
+        let first_line = source[..open].matches('\n').count() + 1;
+        let mut state_locals: Vec<String> = Vec::new();
+        let body: Vec<&str> = code[open..end].lines().collect();
+
+        for (i, (masked, line)) in code[open..end].lines().zip(source[open..end].lines()).enumerate() {
+            let text = masked.trim_start();
+            if let Some(local) = state_local(text) {
+                state_locals.push(local);
+            }
+            let checked = ["checked_", "saturating_", "wrapping_", "overflowing_"].iter().any(|call| text.contains(call));
+            if checked || text.starts_with("for ") || text.starts_with("while ") {
+                continue;
+            }
+            let is_state = |operand: &str| {
+                let operand = operand.trim_start_matches(['&', '*']);
+                operand.starts_with("self.") || state_locals.iter().any(|local| local == operand)
+            };
+            let operation = OPERATORS.iter().find_map(|(op, method)| {
+                let at = masked.find(op)?;
+                let lhs_end = masked[..at].trim_end().len();
+                let lhs_start = lhs_end - operand_before(masked, lhs_end).len();
+                if op.ends_with('=') {
+                    let rhs_end = masked[at..].find(';').map_or(masked.len(), |n| at + n);
+                    let rhs = line[at + 2..rhs_end].trim();
+                    let lhs = &line[lhs_start..lhs_end];
+                    if *op == "-=" && underflow_guarded(&body[..i], lhs, rhs) {
+                        return None;
+                    }
+                    let state = is_state(lhs) || rhs.split_whitespace().any(&is_state);
+                    state.then(|| (lhs_start, rhs_end, format!("{} {} {}", lhs, op.trim(), rhs),
+                        format!("{} = {}.{}({}).ok_or(Error::Overflow)?", lhs, lhs, method, rhs)))
+                } else {
+                    let rhs = operand_after(masked, at + op.len());
+                    let rhs_end = at + op.len() + rhs.len();
+                    let (lhs, rhs) = (&line[lhs_start..lhs_end], &line[at + op.len()..rhs_end]);
+                    if *op == " - " && underflow_guarded(&body[..i], lhs, rhs) {
+                        return None;
+                    }
+                    let state = !lhs.is_empty() && !rhs.is_empty() && (is_state(lhs) || is_state(rhs));
+                    state.then(|| (lhs_start, rhs_end, format!("{}{}{}", lhs, op, rhs),
+                        format!("{}.{}({}).ok_or(Error::Overflow)?", lhs, method, rhs)))
+                }
+            });
+            let Some((from, to, expression, rewrite)) = operation else { continue };
+            let after = format!("{}{}{}", &line[..from], rewrite, &line[to..]);
+            found.push(Unchecked {
+                function: function.clone(),
+                line: first_line + i,
+                column: line[..from].chars().count() + 1,
+                expression,
+                rewrite,
+                before: line.trim().to_string(),
+                after: after.trim().to_string(),
+            });
+        }
+    }
+    found
+}
+
+/// Whether the function body above (`before`, masked lines) already rules out
+/// `lhs - rhs` underflowing: an `if lhs < rhs` (or `rhs > lhs`) whose block
+/// returns or reverts, or an `assert!`-style `lhs >= rhs` (or `rhs <= lhs`).
+/// The check has to dominate the subtraction, so one inside a block that has
+/// since closed doesn't count
+fn underflow_guarded(before: &[&str], lhs: &str, rhs: &str) -> bool {
+    let spaced = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (lhs, rhs) = (spaced(lhs), spaced(rhs));
+    // `first op second` as whole operands, spaced or not
+    let compares = |line: &str, first: &str, op: &str, second: &str| {
+        let is_operand = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.');
+        [format!("{} {} {}", first, op, second), format!("{}{}{}", first, op, second)].iter().any(|pattern| {
+            line.match_indices(pattern.as_str()).any(|(at, _)| {
+                !line[..at].ends_with(is_operand)
+                    && !line[at + pattern.len()..].starts_with(|c: char| is_operand(c) || matches!(c, '(' | '='))
+            })
+        })
+    };
+
+    // Brace depth at the start of each line; the subtraction's line comes after the last
+    let mut depths = Vec::with_capacity(before.len() + 1);
+    let mut depth = 0i32;
+    for line in before {
+        depths.push(depth);
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+    }
+    depths.push(depth);
+
+    before.iter().enumerate().any(|(j, line)| {
+        // The block holding the check is still open at the subtraction
+        if depths[j + 1..].iter().any(|&later| later < depths[j]) {
+            return false;
+        }
+        let text = spaced(line);
+        if ["assert!(", "require!(", "ensure!("].iter().any(|call| text.contains(call)) {
+            return compares(&text, &lhs, ">=", &rhs) || compares(&text, &rhs, "<=", &lhs);
+        }
+        if !text.starts_with("if ") || !(compares(&text, &lhs, "<", &rhs) || compares(&text, &rhs, ">", &lhs)) {
+            return false;
+        }
+        // The `if` block has to close before the subtraction, and leave the function
+        let Some(close) = (j..before.len()).find(|&k| depths[k + 1] <= depths[j]) else { return false };
+        before[j..=close].iter()
+            .any(|line| ["return", "Err(", "revert", "panic!("].iter().any(|exit| line.contains(exit)))
+    })
+}
+
+/// `balance` in `let balance = self.balances.get(user);`
+fn state_local(text: &str) -> Option<String> {
+    let rest = text.strip_prefix("let ")?;
+    let rest = rest.strip_prefix("mut ").unwrap_or(rest);
+    let (name, value) = rest.split_once('=')?;
+    let name = name.split(':').next()?.trim();
+    let simple = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    (simple && value.trim_start().starts_with("self.")).then(|| name.to_string())
+}
+
+/// Operand ending at byte `end`: a path, field access or call, e.g. `self.stakes.get(&user)`
+fn operand_before(line: &str, end: usize) -> &str {
+    let bytes = line.as_bytes();
+    let mut start = end;
+    let mut depth = 0;
+    while start > 0 {
+        let c = bytes[start - 1];
+        match c {
+            b')' | b']' => depth += 1,
+            b'(' | b'[' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ if c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b':' | b'&' | b'*') => {}
+            _ => break,
+        }
+        start -= 1;
+    }
+    &line[start..end]
+}
+
+/// Operand starting at byte `start`, see `operand_before`
+fn operand_after(line: &str, start: usize) -> &str {
+    let bytes = line.as_bytes();
+    let mut end = start;
+    let mut depth = 0;
+    while end < bytes.len() {
+        let c = bytes[end];
+        match c {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ if c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b':' | b'&' | b'*') => {}
+            _ => break,
+        }
+        end += 1;
+    }
+    &line[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(source: &str) -> Vec<usize> {
+        unchecked_operations(source).iter().map(|found| found.line).collect()
+    }
+
+    #[test]
+    fn subtraction_after_an_early_return_check_is_not_flagged() {
+        let source = r#"
+impl Token {
+    pub fn transfer(&mut self, to: Address, amount: U256) -> Result<bool, Vec<u8>> {
+        let sender_balance = self.balances.get(msg::sender());
+        if sender_balance < amount {
+            return Ok(false);
+        }
+        self.balances.insert(msg::sender(), sender_balance - amount);
+        self.balances.insert(to, self.balances.get(to) + amount);
+        Ok(true)
+    }
+}
+"#;
+        assert_eq!(lines(source), [9]);
+    }
+
+    #[test]
+    fn assert_style_checks_guard_the_subtraction() {
+        let source = r#"
+impl Vault {
+    pub fn withdraw(&mut self, amount: U256) {
+        let balance = self.balance.get();
+        assert!(balance >= amount, "insufficient");
+        self.balance.set(balance - amount);
+    }
+}
+"#;
+        assert!(lines(source).is_empty());
+    }
+
+    #[test]
+    fn checks_that_do_not_dominate_are_ignored() {
+        let source = r#"
+impl Vault {
+    pub fn withdraw(&mut self, amount: U256, strict: bool) {
+        let balance = self.balance.get();
+        if strict {
+            if balance < amount {
+                return;
+            }
+        }
+        if balance < amount {
+            log(amount);
+        }
+        self.balance.set(balance - amount);
+    }
+}
+"#;
+        assert_eq!(lines(source), [13]);
+    }
+
+    #[test]
+    fn unguarded_subtraction_is_flagged() {
+        let source = r#"
+impl Vault {
+    pub fn withdraw(&mut self, amount: U256) {
+        self.total.set(self.total.get() - amount);
+    }
+}
+"#;
+        let found = unchecked_operations(source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rewrite, "self.total.get().checked_sub(amount).ok_or(Error::Overflow)?");
+    }
+}
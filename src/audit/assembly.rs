@@ -0,0 +1,273 @@
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::line_at;
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Solidity `assembly { ... }` blocks, which skip the compiler's overflow,
+/// memory and call checks, and the risky operations inside them
+pub struct AssemblyRule;
+
+/// One inline assembly block
+#[derive(Debug)]
+pub struct AssemblyBlock<'a> {
+    /// 1-based first and last line
+    pub lines: (usize, usize),
+    /// Text between the braces
+    pub body: &'a str,
+}
+
+/// Inline assembly blocks in Solidity `source`, skipping comments and strings
+pub fn assembly_blocks(source: &str) -> Vec<AssemblyBlock<'_>> {
+    let code = mask_comments(source);
+    let mut blocks = Vec::new();
+    let mut from = 0;
+    while let Some(found) = code[from..].find("assembly") {
+        let start = from + found;
+        from = start + "assembly".len();
+        let standalone = !code[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && !code[from..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        // `assembly "evmasm" ("memory-safe") {`
+        let Some(open) = code[from..].find('{').map(|i| from + i) else { break };
+        let header = code[from..open].trim();
+        if !standalone || header.contains(';') || header.contains('}') {
+            continue;
+        }
+
+        let mut depth = 0;
+        let Some(close) = code[open..].char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(open + i)
+        }) else { break };
+
+        blocks.push(AssemblyBlock {
+            lines: (line_at(source, start), line_at(source, close)),
+            body: &source[open + 1..close],
+        });
+        from = close;
+    }
+    blocks
+}
+
+/// `source` with comments and string literals blanked out, keeping byte offsets
+fn mask_comments(source: &str) -> String {
+    let bytes = source.as_bytes();
+    let mut masked = source.as_bytes().to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = if bytes[i..].starts_with(b"//") {
+            bytes[i..].iter().position(|&b| b == b'\n').map_or(bytes.len(), |n| i + n)
+        } else if bytes[i..].starts_with(b"/*") {
+            source[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4)
+        } else if bytes[i] == b'"' {
+            bytes[i + 1..].iter().position(|&b| b == b'"').map_or(bytes.len(), |n| i + n + 2)
+        } else {
+            i += 1;
+            continue;
+        };
+        for byte in &mut masked[i..end] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+        i = end;
+    }
+    // Only ASCII bytes were replaced, so the text stays valid UTF-8
+    String::from_utf8(masked).unwrap_or_else(|_| source.to_string())
+}
+
+/// Yul calls to `op`, with the line each starts on and its argument text
+fn yul_calls<'a>(block: &AssemblyBlock<'a>, op: &str) -> Vec<(usize, &'a str)> {
+    let body = block.body;
+    body.match_indices(op)
+        .filter(|(i, _)| !body[..*i].ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+        .filter_map(|(i, _)| {
+            let rest = body[i + op.len()..].trim_start();
+            let rest = rest.strip_prefix('(')?;
+            let mut depth = 1;
+            let end = rest.char_indices().find_map(|(j, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(j)
+            })?;
+            Some((block.lines.0 + body[..i].matches('\n').count(), &rest[..end]))
+        })
+        .collect()
+}
+
+/// First argument of a Yul call, respecting nested parentheses
+fn first_arg(args: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return args[..i].trim(),
+            _ => {}
+        }
+    }
+    args.trim()
+}
+
+fn parse_literal(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+#[async_trait]
+impl AuditRule for AssemblyRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        // Stylus contracts are Rust; `assembly` there is just an identifier
+        if syn::parse_file(content).is_ok() {
+            return Ok(Vec::new());
+        }
+        let mut vulnerabilities = Vec::new();
+
+        for block in assembly_blocks(content) {
+            let (first, last) = block.lines;
+            vulnerabilities.push(finding(
+                "Inline Assembly",
+                Severity::Info,
+                format!("assembly block on lines {}-{} bypasses the compiler's overflow, memory-safety and call checks", first, last),
+                "Review the block by hand and keep it as small as possible",
+                first,
+            ));
+
+            // Slots derived at runtime are invisible to layout tools and upgrade checks
+            for op in ["sstore", "sload"] {
+                for (line, args) in yul_calls(&block, op) {
+                    let slot = first_arg(args);
+                    let fixed = parse_literal(slot).is_some()
+                        || slot.ends_with(".slot")
+                        || slot.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+                    if !fixed {
+                        vulnerabilities.push(finding(
+                            "Raw Storage Access to Computed Slot",
+                            Severity::Medium,
+                            format!("{}({}) uses a computed slot; a collision with the declared layout or an upgrade's new variables corrupts state", op, slot),
+                            "Use a constant ERC-7201/ERC-1967-style slot or `.slot` of a declared variable, and document the layout",
+                            line,
+                        ));
+                    }
+                }
+            }
+
+            for (line, args) in yul_calls(&block, "delegatecall").into_iter().chain(yul_calls(&block, "callcode")) {
+                vulnerabilities.push(finding(
+                    "Delegatecall in Assembly",
+                    Severity::High,
+                    format!("assembly delegatecall to `{}` runs foreign code against this contract's storage, with none of the checks a high-level call gets",
+                        nth_arg(args, 1)),
+                    "Only delegatecall a trusted, immutable or access-controlled target, and check the returned success flag",
+                    line,
+                ));
+            }
+            for (line, args) in yul_calls(&block, "call") {
+                vulnerabilities.push(finding(
+                    "External Call in Assembly",
+                    Severity::High,
+                    format!("assembly call to `{}` hands control to another contract; state written after it is open to reentrancy and a failed call doesn't revert",
+                        nth_arg(args, 1)),
+                    "Follow checks-effects-interactions around the call, add a reentrancy guard, and check the success flag",
+                    line,
+                ));
+            }
+
+            // 0x00-0x3f is scratch space; 0x40 holds the free memory pointer and 0x60 must stay zero
+            for (line, args) in yul_calls(&block, "mstore") {
+                let offset = first_arg(args);
+                if let Some(offset @ 0x40..=0x7f) = parse_literal(offset) {
+                    let what = if offset < 0x60 { "the free memory pointer" } else { "the zero slot" };
+                    vulnerabilities.push(finding(
+                        "Reserved Memory Overwritten",
+                        Severity::Medium,
+                        format!("mstore(0x{:x}, ...) overwrites {}; later Solidity code allocates or reads memory incorrectly", offset, what),
+                        "Allocate from the free memory pointer instead, or restore the reserved word before leaving the block",
+                        line,
+                    ));
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Inline Assembly Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ASSEMBLY-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Inline assembly that bypasses overflow, memory and call-result checks"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Inline Assembly",
+            "Delegatecall in Assembly",
+            "External Call in Assembly",
+            "Raw Storage Access to Computed Slot",
+            "Reserved Memory Overwritten",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
+}
+
+/// `n`-th (0-based) argument of a Yul call
+fn nth_arg(args: &str, n: usize) -> &str {
+    let mut rest = args.trim_start();
+    for _ in 0..n {
+        let first = first_arg(rest);
+        rest = rest[first.len()..].trim_start().strip_prefix(',').unwrap_or("").trim_start();
+    }
+    first_arg(rest)
+}
+
+fn finding(name: &str, severity: Severity, risk_description: String, recommendation: &str, line: usize) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        line: Some(line),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    #[tokio::test]
+    async fn assembly_blocks_are_classified_by_what_they_do() {
+        let found = found(&AssemblyRule, include_str!("../../test ex/assembly/delegating_router.sol")).await;
+        assert_eq!(found.iter().map(|(name, line)| (name.as_str(), *line)).collect::<Vec<_>>(), [
+            ("Inline Assembly", 17),
+            ("Delegatecall in Assembly", 20),
+            ("Reserved Memory Overwritten", 22),
+            ("Inline Assembly", 28),
+            ("Raw Storage Access to Computed Slot", 29),
+            ("Inline Assembly", 34),
+            ("Raw Storage Access to Computed Slot", 35),
+        ]);
+    }
+}
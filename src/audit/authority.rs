@@ -0,0 +1,381 @@
+//! Who can change what: the privileged functions of a contract, what guards
+//! them and who holds that authority
+
+use quote::ToTokens;
+use solang_parser::pt::{
+    CodeLocation, ContractDefinition, ContractPart, ContractTy, FunctionAttribute, FunctionDefinition, FunctionTy,
+    SourceUnitPart,
+};
+use crate::audit::vulnerabilities::{Vulnerability, Severity};
+use crate::audit::rules::AuditRule;
+use crate::audit::initializer::is_initializer_name;
+use crate::audit::source::{assigns, is_externally_callable, line_of, modifier_names, rust_fn_line, slice, state_variables};
+use crate::audit::timelock::{classify, enforces_delay, Privileged};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// One privileged function and the authority that can call it
+#[derive(Debug, Clone)]
+pub struct Authority {
+    pub contract: String,
+    pub function: String,
+    pub kind: Privileged,
+    pub guard: Guard,
+    pub holder: Holder,
+    /// A delay sits between calling the function and the change taking effect
+    pub delayed: bool,
+    pub line: usize,
+}
+
+/// Access mechanism checked before the function runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Guard {
+    /// `msg.sender` compared against a stored address, e.g. `onlyOwner`
+    Owner(String),
+    /// A role constant, e.g. `onlyRole(UPGRADER_ROLE)`
+    Role(String),
+    Timelock,
+    /// A modifier whose check couldn't be attributed to an owner or a role
+    Modifier(String),
+    Nothing,
+}
+
+/// Who holds the authority the guard checks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Holder {
+    /// One address, typically an EOA; `set_in` says where it gets its value
+    SingleKey { set_in: String },
+    /// Granted and revoked through role administration
+    ConfigurableRole,
+    /// A timelock, governor or multisig contract
+    Governance,
+    Anyone,
+    Unknown,
+}
+
+impl Guard {
+    pub fn describe(&self) -> String {
+        match self {
+            Guard::Owner(var) => format!("owner check on `{}`", var),
+            Guard::Role(role) => format!("role {}", role),
+            Guard::Timelock => "timelock".to_string(),
+            Guard::Modifier(name) => format!("modifier `{}`", name),
+            Guard::Nothing => "nothing".to_string(),
+        }
+    }
+}
+
+impl Holder {
+    pub fn describe(&self) -> String {
+        match self {
+            Holder::SingleKey { set_in } => format!("single address {}", set_in),
+            Holder::ConfigurableRole => "configurable role".to_string(),
+            Holder::Governance => "governance contract".to_string(),
+            Holder::Anyone => "anyone".to_string(),
+            Holder::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+/// Names that suggest the stored admin is a contract that spreads or delays authority
+fn is_governance_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    ["timelock", "governance", "governor", "dao", "multisig", "safe"].iter().any(|hint| lower.contains(hint))
+}
+
+/// Privileged functions of every contract in Solidity or Stylus `source`
+pub fn enumerate(source: &str) -> Vec<Authority> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        let mut authorities = Vec::new();
+        for part in &unit.0 {
+            if let SourceUnitPart::ContractDefinition(contract) = part {
+                enumerate_contract(contract, source, &mut authorities);
+            }
+        }
+        return authorities;
+    }
+    if let Ok(file) = syn::parse_file(source) {
+        return enumerate_stylus(&file, source);
+    }
+    Vec::new()
+}
+
+fn enumerate_contract(contract: &ContractDefinition, source: &str, authorities: &mut Vec<Authority>) {
+    if matches!(contract.ty, ContractTy::Interface(_) | ContractTy::Library(_)) {
+        return;
+    }
+    let name = contract.name.as_ref().map(|n| n.name.clone()).unwrap_or_default();
+    let text = slice(&contract.loc, source);
+    let state_vars: Vec<String> = state_variables(contract).into_iter().map(|var| var.0).collect();
+    let functions: Vec<&FunctionDefinition> = contract.parts.iter()
+        .filter_map(|part| match part {
+            ContractPart::FunctionDefinition(func) => Some(func.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let body_of = |func: &FunctionDefinition| func.body.as_ref().map(|b| slice(&b.loc(), source)).unwrap_or("");
+
+    let delay_modifiers: Vec<String> = functions.iter()
+        .filter(|func| func.ty == FunctionTy::Modifier && enforces_delay(body_of(func)))
+        .filter_map(|func| func.name.as_ref().map(|n| n.name.clone()))
+        .collect();
+
+    // Where a stored admin address gets its value decides whether it's a fixed key
+    let set_in = |var: &str| -> String {
+        let setup = |func: &&&FunctionDefinition, ctor: bool| {
+            (func.ty == FunctionTy::Constructor) == ctor
+                && (ctor || func.name.as_ref().is_some_and(|n| is_initializer_name(&n.name)))
+                && assigns(body_of(func), var)
+        };
+        let setter = functions.iter()
+            .filter(|func| func.ty == FunctionTy::Function && assigns(body_of(func), var))
+            .find_map(|func| func.name.as_ref());
+        if functions.iter().any(|func| setup(&func, true)) {
+            "set in the constructor".to_string()
+        } else if functions.iter().any(|func| setup(&func, false)) {
+            "set in the initializer".to_string()
+        } else if let Some(setter) = setter {
+            format!("set by {}()", setter.name)
+        } else if state_vars.iter().any(|v| v == var) {
+            "that is never set".to_string()
+        } else if text.contains("Ownable") {
+            "set in Ownable's constructor".to_string()
+        } else {
+            "set outside this contract".to_string()
+        }
+    };
+
+    for func in &functions {
+        if func.ty != FunctionTy::Function || !is_externally_callable(func) {
+            continue;
+        }
+        let (Some(fn_name), Some(_)) = (&func.name, &func.body) else { continue };
+        if is_initializer_name(&fn_name.name) {
+            continue;
+        }
+        let body = body_of(func);
+        let written: Vec<&str> = state_vars.iter().map(String::as_str).filter(|var| assigns(body, var)).collect();
+        let Some(kind) = classify(&fn_name.name, &written) else { continue };
+
+        let modifiers = modifier_names(func);
+        let delayed = enforces_delay(body)
+            || modifiers.iter().any(|m| delay_modifiers.contains(m) || m.to_lowercase().contains("timelock"));
+        let guard = solidity_guard(func, body, &modifiers, &state_vars, source);
+        let holder = match &guard {
+            Guard::Owner(var) if is_governance_name(var) => Holder::Governance,
+            Guard::Owner(var) => Holder::SingleKey { set_in: set_in(var) },
+            Guard::Role(_) => Holder::ConfigurableRole,
+            Guard::Timelock => Holder::Governance,
+            Guard::Modifier(_) => Holder::Unknown,
+            Guard::Nothing => Holder::Anyone,
+        };
+        authorities.push(Authority {
+            contract: name.clone(),
+            function: fn_name.name.clone(),
+            kind,
+            guard,
+            holder,
+            delayed,
+            line: line_of(&func.loc, source),
+        });
+    }
+}
+
+fn solidity_guard(func: &FunctionDefinition, body: &str, modifiers: &[String], state_vars: &[String], source: &str) -> Guard {
+    // `onlyRole(UPGRADER_ROLE)`: the argument names the role
+    for attr in &func.attributes {
+        let FunctionAttribute::BaseOrModifier(_, base) = attr else { continue };
+        let is_role = base.name.identifiers.last().is_some_and(|id| id.name == "onlyRole");
+        if let (true, Some(arg)) = (is_role, base.args.as_ref().and_then(|args| args.first())) {
+            return Guard::Role(slice(&arg.loc(), source).to_string());
+        }
+    }
+    for modifier in modifiers {
+        let lower = modifier.to_lowercase();
+        if lower.contains("timelock") {
+            return Guard::Timelock;
+        }
+        // `onlyOwner` -> `owner`, matched against the declared variable's spelling
+        if let Some(subject) = lower.strip_prefix("only") {
+            let var = state_vars.iter()
+                .find(|var| var.trim_start_matches('_').to_lowercase() == subject)
+                .cloned()
+                .unwrap_or_else(|| subject.to_string());
+            return Guard::Owner(var);
+        }
+    }
+
+    let compact: String = body.split_whitespace().collect();
+    if let Some(start) = compact.find("hasRole(") {
+        let args = &compact[start + "hasRole(".len()..];
+        if let Some(role) = args.split(',').next().filter(|_| args.contains("msg.sender")) {
+            return Guard::Role(role.to_string());
+        }
+    }
+    for op in ["==", "!="] {
+        for pattern in [format!("msg.sender{}", op), format!("{}msg.sender", op)] {
+            let Some(i) = compact.find(&pattern) else { continue };
+            let other = if pattern.starts_with("msg") {
+                compact[i + pattern.len()..].split(|c: char| !(c.is_alphanumeric() || c == '_')).next()
+            } else {
+                compact[..i].rsplit(|c: char| !(c.is_alphanumeric() || c == '_')).next()
+            };
+            if let Some(var) = other.filter(|var| !var.is_empty()) {
+                return Guard::Owner(var.to_string());
+            }
+        }
+    }
+    match modifiers.first() {
+        Some(modifier) => Guard::Modifier(modifier.clone()),
+        None => Guard::Nothing,
+    }
+}
+
+fn enumerate_stylus(file: &syn::File, source: &str) -> Vec<Authority> {
+    let mut methods = Vec::new();
+    for item in &file.items {
+        let syn::Item::Impl(impl_item) = item else { continue };
+        let contract = impl_item.self_ty.to_token_stream().to_string();
+        for impl_fn in &impl_item.items {
+            if let syn::ImplItem::Fn(method) = impl_fn {
+                methods.push((contract.clone(), method));
+            }
+        }
+    }
+
+    // `self . owner . set (` in a constructor or init function fixes the key at deployment
+    let set_in = |field: &str| -> String {
+        let setter = format!(". {} . set (", field);
+        let setup = methods.iter().find(|(_, method)| {
+            let name = method.sig.ident.to_string();
+            let ctor = method.attrs.iter().any(|a| a.path().is_ident("constructor"));
+            (ctor || is_initializer_name(&name)) && method.block.to_token_stream().to_string().contains(&setter)
+        });
+        match setup {
+            Some((_, method)) if method.attrs.iter().any(|a| a.path().is_ident("constructor")) => "set in the constructor".to_string(),
+            Some(_) => "set in the initializer".to_string(),
+            None => "set outside the constructor and initializer".to_string(),
+        }
+    };
+
+    let mut authorities = Vec::new();
+    for (contract, method) in &methods {
+        let name = method.sig.ident.to_string();
+        if !matches!(method.vis, syn::Visibility::Public(_)) || is_initializer_name(&name) {
+            continue;
+        }
+        let body = method.block.to_token_stream().to_string();
+        let written: Vec<&str> = body.match_indices(" . set (")
+            .filter_map(|(i, _)| body[..i].rsplit(' ').next())
+            .collect();
+        let Some(kind) = classify(&name, &written) else { continue };
+
+        let admin_field = body.match_indices(" . get ()")
+            .filter_map(|(i, _)| body[..i].rsplit(' ').next())
+            .find(|field| ["owner", "admin", "governance", "timelock"].iter().any(|hint| field.to_lowercase().contains(hint)));
+        let checks_sender = body.contains("msg :: sender") || body.contains("msg_sender");
+        let guard = if body.contains("only_role") || body.contains("has_role") {
+            let role = body.split_whitespace().find(|token| token.ends_with("_ROLE")).unwrap_or("a role");
+            Guard::Role(role.to_string())
+        } else if let (true, Some(field)) = (checks_sender, admin_field) {
+            Guard::Owner(field.to_string())
+        } else if body.contains("only_owner") {
+            Guard::Owner("owner".to_string())
+        } else {
+            Guard::Nothing
+        };
+        let holder = match &guard {
+            Guard::Owner(field) if is_governance_name(field) => Holder::Governance,
+            Guard::Owner(field) => Holder::SingleKey { set_in: set_in(field) },
+            Guard::Role(_) => Holder::ConfigurableRole,
+            Guard::Nothing => Holder::Anyone,
+            Guard::Timelock | Guard::Modifier(_) => Holder::Unknown,
+        };
+        authorities.push(Authority {
+            contract: contract.clone(),
+            function: name.clone(),
+            kind,
+            guard,
+            holder,
+            delayed: enforces_delay(&body),
+            line: rust_fn_line(source, &name).unwrap_or(0),
+        });
+    }
+    authorities
+}
+
+/// Upgrades and treasury moves that one key can trigger instantly
+pub struct UpgradeAuthorityRule;
+
+#[async_trait]
+impl AuditRule for UpgradeAuthorityRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let vulnerabilities = enumerate(content).into_iter()
+            .filter(|authority| authority.kind.is_value_bearing() && !authority.delayed)
+            .filter(|authority| matches!(authority.holder, Holder::SingleKey { .. }))
+            .map(|authority| Vulnerability {
+                name: "Single-Key Upgrade Authority".to_string(),
+                severity: Severity::High,
+                risk_description: format!("{} {} and is callable by a {} ({}) with no delay; one leaked or malicious key takes over the contract's value",
+                    authority.function, authority.kind.describe(), authority.holder.describe(), authority.guard.describe()),
+                recommendation: "Move the authority to a multisig or TimelockController, or to a dedicated role held by one".to_string(),
+                line: (authority.line > 0).then_some(authority.line),
+                function: Some(authority.function),
+                ..Default::default()
+            })
+            .collect();
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Upgrade Authority Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-UPGRADE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Upgrade rights held by a single key instead of a multisig or governance"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Single-Key Upgrade Authority"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    const MIXED_ADMIN: &str = include_str!("../../test ex/authority/mixed_admin.sol");
+
+    #[test]
+    fn each_privileged_function_gets_its_guard_and_holder() {
+        let deployer = || Holder::SingleKey { set_in: "set in the constructor".to_string() };
+        let authorities: Vec<(String, Guard, Holder, bool)> = enumerate(MIXED_ADMIN).into_iter()
+            .map(|authority| (authority.function, authority.guard, authority.holder, authority.delayed))
+            .collect();
+        assert_eq!(authorities, [
+            ("upgradeTo".to_string(), Guard::Owner("owner".to_string()), deployer(), false),
+            ("setFeeBps".to_string(), Guard::Role("FEE_ROLE".to_string()), Holder::ConfigurableRole, false),
+            ("sweepTreasury".to_string(), Guard::Owner("owner".to_string()), deployer(), true),
+            ("setOracle".to_string(), Guard::Nothing, Holder::Anyone, false),
+            ("withdrawFees".to_string(), Guard::Owner("treasury".to_string()),
+                Holder::SingleKey { set_in: "that is never set".to_string() }, false),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn instant_single_key_authority_is_flagged() {
+        assert_eq!(found(&UpgradeAuthorityRule, MIXED_ADMIN).await, [
+            ("Single-Key Upgrade Authority".to_string(), 36),
+            ("Single-Key Upgrade Authority".to_string(), 52),
+        ]);
+    }
+}
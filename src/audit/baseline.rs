@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::audit::AuditResult;
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+/// Bumped whenever the baseline file layout changes incompatibly
+pub const BASELINE_FORMAT_VERSION: u32 = 3;
+
+/// Snapshot of reviewed findings that later audits are compared against
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub format_version: u32,
+    pub tool_version: String,
+    pub rule_set_hash: String,
+    pub findings: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub name: String,
+    pub severity: Severity,
+}
+
+/// Outcome of filtering an audit through a baseline, rendered by the report
+#[derive(Debug, Default)]
+pub struct BaselineSummary {
+    /// What the findings were compared against, e.g. "baseline .analyzer-baseline.json"
+    pub source: String,
+    /// Findings already present in the baseline, hidden from the main sections
+    pub suppressed: Vec<Vulnerability>,
+    /// Version or rule-set mismatches that make the comparison less reliable
+    pub warnings: Vec<String>,
+}
+
+impl Baseline {
+    /// Captures every finding of `result` as the new baseline
+    pub fn from_result(result: &AuditResult, rule_names: &[String]) -> Self {
+        Self {
+            format_version: BASELINE_FORMAT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            rule_set_hash: rule_set_hash(rule_names),
+            findings: result.vulnerabilities()
+                .map(|vuln| BaselineEntry {
+                    fingerprint: vuln.fingerprint.clone(),
+                    name: vuln.name.clone(),
+                    severity: vuln.severity,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AnalyzerError::Config(format!("cannot read baseline {}: {} (create it with --update-baseline)", path.display(), e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AnalyzerError::Config(format!("invalid baseline {}: {}", path.display(), e))
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AnalyzerError::Config(format!("cannot serialize baseline: {}", e)))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Moves findings already recorded in the baseline out of `result`,
+    /// leaving only the ones that are new since the snapshot.
+    pub fn apply(&self, result: &mut AuditResult, rule_names: &[String], source: String) -> BaselineSummary {
+        let mut summary = BaselineSummary { source, ..Default::default() };
+
+        if self.format_version != BASELINE_FORMAT_VERSION {
+            summary.warnings.push(format!(
+                "baseline format v{} differs from v{} used by this tool; regenerate it with --update-baseline",
+                self.format_version, BASELINE_FORMAT_VERSION
+            ));
+        }
+        if self.tool_version != env!("CARGO_PKG_VERSION") {
+            summary.warnings.push(format!(
+                "baseline was written by stylus-analyzer {} (running {}); findings may not match exactly",
+                self.tool_version, env!("CARGO_PKG_VERSION")
+            ));
+        }
+        if self.rule_set_hash != rule_set_hash(rule_names) {
+            summary.warnings.push(
+                "the audit rule set changed since the baseline was written; new rules report all their findings as new".to_string()
+            );
+        }
+
+        let known: HashSet<&str> = self.findings.iter().map(|f| f.fingerprint.as_str()).collect();
+        let mut suppressed = Vec::new();
+        result.retain(|vuln| {
+            if known.contains(vuln.fingerprint.as_str()) {
+                suppressed.push(vuln.clone());
+                false
+            } else {
+                true
+            }
+        });
+        summary.suppressed = suppressed;
+
+        summary
+    }
+}
+
+/// Contents of `file` as of `git_ref`, or `None` if the file didn't exist there
+pub fn read_at_git_ref(git_ref: &str, file: &Path) -> Result<Option<String>> {
+    let dir = file.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = file.file_name().ok_or_else(|| {
+        AnalyzerError::Config(format!("{} is not a file path", file.display()))
+    })?;
+
+    // `./name` makes git resolve the path relative to `dir` rather than the repo root
+    let output = std::process::Command::new("git")
+        .arg("show")
+        .arg(format!("{}:./{}", git_ref, name.to_string_lossy()))
+        .current_dir(dir)
+        .output()?;
+
+    if output.status.success() {
+        return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("exists on disk, but not in") || stderr.contains("does not exist in") {
+        return Ok(None);
+    }
+    Err(AnalyzerError::Config(format!("cannot read {} at {}: {}", file.display(), git_ref, stderr.trim())))
+}
+
+/// Order-independent hash of the rule names an audit ran with
+pub fn rule_set_hash(rule_names: &[String]) -> String {
+    let mut names: Vec<&String> = rule_names.iter().collect();
+    names.sort();
+
+    let mut hasher = Sha256::new();
+    for name in names {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(name: &str, fingerprint: &str) -> Vulnerability {
+        Vulnerability {
+            name: name.to_string(),
+            severity: Severity::High,
+            fingerprint: fingerprint.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn result(findings: &[(&str, &str)]) -> AuditResult {
+        let mut result = AuditResult::default();
+        for (name, fingerprint) in findings {
+            result.push(finding(name, fingerprint));
+        }
+        result
+    }
+
+    #[test]
+    fn a_saved_baseline_suppresses_the_same_findings() {
+        let rules = vec!["Reentrancy Pattern Checker".to_string(), "Unchecked Call Detector".to_string()];
+        let path = std::env::temp_dir().join(format!("baseline-{}.json", std::process::id()));
+        Baseline::from_result(&result(&[("Reentrancy", "aaa"), ("Unchecked Call", "bbb")]), &rules).save(&path).unwrap();
+        let baseline = Baseline::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut later = result(&[("Reentrancy", "aaa"), ("Unchecked Call", "bbb"), ("Reentrancy", "ccc")]);
+        // Rule order doesn't change the rule set
+        let reordered: Vec<String> = rules.iter().rev().cloned().collect();
+        let summary = baseline.apply(&mut later, &reordered, "baseline test".to_string());
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+        assert_eq!(summary.suppressed.len(), 2);
+        let left: Vec<&str> = later.vulnerabilities().map(|vuln| vuln.fingerprint.as_str()).collect();
+        assert_eq!(left, ["ccc"]);
+    }
+
+    #[test]
+    fn mismatched_versions_and_rule_sets_warn() {
+        let rules = vec!["Reentrancy Pattern Checker".to_string()];
+        let mut baseline = Baseline::from_result(&result(&[("Reentrancy", "aaa")]), &rules);
+        baseline.format_version = BASELINE_FORMAT_VERSION - 1;
+        baseline.tool_version = "0.0.1".to_string();
+
+        let mut later = result(&[("Reentrancy", "aaa")]);
+        let added = vec!["Reentrancy Pattern Checker".to_string(), "Unchecked Call Detector".to_string()];
+        let summary = baseline.apply(&mut later, &added, "baseline test".to_string());
+        assert_eq!(summary.warnings.len(), 3, "{:?}", summary.warnings);
+        assert!(summary.warnings[0].contains(&format!("format v{} differs", BASELINE_FORMAT_VERSION - 1)));
+        assert!(summary.warnings[1].contains("written by stylus-analyzer 0.0.1"));
+        assert!(summary.warnings[2].contains("rule set changed"));
+        // The findings still compare; the warnings only qualify the result
+        assert_eq!(summary.suppressed.len(), 1);
+    }
+}
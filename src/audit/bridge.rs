@@ -0,0 +1,172 @@
+//! Who sent an Arbitrum cross-chain message. A retryable ticket from an L1
+//! contract arrives on L2 with `msg.sender` set to the L1 address plus the
+//! alias offset, so handlers must compare against the aliased address. An
+//! L2-to-L1 message arrives on L1 from the Outbox, and only the Outbox's
+//! `l2ToL1Sender()` says which L2 contract sent it
+
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionAttribute, FunctionTy, Loc, Mutability, SourceUnitPart};
+use crate::audit::source::{
+    is_externally_callable, mask, modifier_names, reachable, scope_text, solidity_function_spans, stylus_function_spans,
+    SourceMatch,
+};
+
+const SENDERS: [&str; 3] = ["msg.sender", "msg::sender()", "msg_sender()"];
+/// AddressAliasHelper, its Rust ports, the alias offset itself and ArbSys's caller-aliasing queries; lowercased
+const ALIASING: [&str; 9] = [
+    "applyl1tol2alias", "apply_l1_to_l2_alias", "undol1tol2alias", "undo_l1_to_l2_alias", "addressaliashelper",
+    "1111000000000000000000000000000000001111", "wasmycallersaddressaliased", "was_my_callers_address_aliased",
+    "mycallersaddresswithoutaliasing",
+];
+/// The Outbox's record of the L2 sender; lowercased
+const OUTBOX_SENDER: [&str; 2] = ["l2tol1sender", "l2_to_l1_sender"];
+/// Function names of bridge entry points whose direction depends on which side the contract is on
+const ENTRY_POINTS: [&str; 6] = ["finalizeinboundtransfer", "finalize_inbound_transfer", "receivemessage", "receive_message", "handlemessage", "handle_message"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Direction {
+    /// A retryable ticket from L1, handled on L2
+    L1ToL2,
+    /// An L2-to-L1 message executed through the Outbox, handled on L1
+    L2ToL1,
+}
+
+pub(crate) enum SenderGap {
+    /// No check on who sent the message
+    Unvalidated(Direction),
+    /// `msg.sender` compared with the L1 address itself, which never matches on L2
+    Unaliased,
+    /// `msg.sender` checked on L1 without asking the Outbox which L2 contract sent the message
+    NoOutbox,
+}
+
+pub(crate) struct BridgeHandler {
+    pub gap: SenderGap,
+    pub function: String,
+    pub at: SourceMatch,
+}
+
+/// Cross-chain message handlers in Solidity or Stylus `source` that don't validate the sender Arbitrum's way
+pub(crate) fn sender_gaps(source: &str) -> Vec<BridgeHandler> {
+    let code = mask(source);
+    let lower = code.to_lowercase();
+    // Contracts that send to L1 through ArbSys live on L2; those that use the Inbox or Outbox live on L1
+    let on_l1 = ["outbox", "iinbox", "createretryableticket"].iter().any(|word| lower.contains(word))
+        && !["arbsys", "sendtxtol1"].iter().any(|word| lower.contains(word));
+
+    let mut gaps = Vec::new();
+    for (function, start, end, modifiers, scope) in handlers(source, &code) {
+        let name = function.to_lowercase();
+        let named = |words: &[&str]| {
+            words.iter().any(|word| name.contains(word) || modifiers.iter().any(|m| m.to_lowercase().contains(word)))
+        };
+        let comparison = sender_comparison(&scope);
+        let compared = comparison.as_deref().map(str::to_lowercase).unwrap_or_default();
+        let direction = if named(&["froml1", "from_l1", "l1tol2", "l1_to_l2", "onlyl1", "only_l1"]) || mentions_side(&compared, "l1") {
+            Direction::L1ToL2
+        } else if named(&["froml2", "from_l2", "l2tol1", "l2_to_l1", "onlyl2", "only_l2"]) || mentions_side(&compared, "l2") {
+            Direction::L2ToL1
+        } else if ENTRY_POINTS.contains(&name.as_str()) {
+            if on_l1 { Direction::L2ToL1 } else { Direction::L1ToL2 }
+        } else {
+            continue;
+        };
+
+        let scope_lower = scope.to_lowercase();
+        let gap = match direction {
+            Direction::L1ToL2 if ALIASING.iter().any(|alias| scope_lower.contains(alias)) => continue,
+            Direction::L2ToL1 if OUTBOX_SENDER.iter().any(|sender| scope_lower.contains(sender)) => continue,
+            Direction::L1ToL2 if comparison.is_some() => SenderGap::Unaliased,
+            Direction::L2ToL1 if comparison.is_some() => SenderGap::NoOutbox,
+            direction => SenderGap::Unvalidated(direction),
+        };
+        // Point at the comparison that is wrong, or at the handler that has none
+        let at = match &gap {
+            SenderGap::Unvalidated(_) => line_at(source, start, end, None),
+            _ => line_at(source, start, end, comparison.as_deref()).or_else(|| line_at(source, start, end, None)),
+        };
+        if let Some(at) = at {
+            gaps.push(BridgeHandler { gap, function, at });
+        }
+    }
+    gaps
+}
+
+/// Public state-changing functions as (name, body start, body end, modifiers,
+/// masked text of the body, its modifiers and every function it calls)
+fn handlers(source: &str, code: &str) -> Vec<(String, usize, usize, Vec<String>, String)> {
+    let mut found = Vec::new();
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        let spans = solidity_function_spans(&unit);
+        for part in &unit.0 {
+            let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+            let modifier_bodies: Vec<(String, &str)> = contract.parts.iter()
+                .filter_map(|part| match part {
+                    ContractPart::FunctionDefinition(func) if func.ty == FunctionTy::Modifier => {
+                        let Some(Loc::File(_, start, end)) = func.body.as_ref().map(|body| body.loc()) else { return None };
+                        Some((func.name.as_ref()?.name.clone(), &code[start..end]))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for part in &contract.parts {
+                let ContractPart::FunctionDefinition(func) = part else { continue };
+                let (Some(name), Some(Loc::File(_, start, end))) = (&func.name, func.body.as_ref().map(|body| body.loc())) else { continue };
+                let read_only = func.attributes.iter()
+                    .any(|attr| matches!(attr, FunctionAttribute::Mutability(Mutability::View(_) | Mutability::Pure(_))));
+                if func.ty != FunctionTy::Function || !is_externally_callable(func) || read_only {
+                    continue;
+                }
+                let modifiers = modifier_names(func);
+                let mut scope = spans.iter().position(|span| span.start == start)
+                    .map(|index| scope_text(&spans, &reachable(&spans, code, index), code))
+                    .unwrap_or_else(|| code[start..end].to_string());
+                for (_, body) in modifier_bodies.iter().filter(|(modifier, _)| modifiers.contains(modifier)) {
+                    scope.push('\n');
+                    scope.push_str(body);
+                }
+                found.push((name.name.clone(), func.loc.start(), end, modifiers, scope));
+            }
+        }
+        return found;
+    }
+    if syn::parse_file(source).is_err() {
+        return found;
+    }
+    let spans = stylus_function_spans(code);
+    for (index, span) in spans.iter().enumerate().filter(|(_, span)| span.public && !span.read_only) {
+        let scope = scope_text(&spans, &reachable(&spans, code, index), code);
+        found.push((span.name.clone(), span.start, span.end, Vec::new(), scope));
+    }
+    found
+}
+
+/// The first line comparing the caller with `==` or `!=`, trimmed
+fn sender_comparison(scope: &str) -> Option<String> {
+    scope.lines()
+        .find(|line| SENDERS.iter().any(|sender| line.contains(sender)) && (line.contains("==") || line.contains("!=")))
+        .map(|line| line.trim().to_string())
+}
+
+/// Whether `text` names something on the given side, as `l1Counterpart`, `L1_GATEWAY` or `l2_target` do
+fn mentions_side(text: &str, side: &str) -> bool {
+    text.split(|c: char| !c.is_alphanumeric()).any(|word| {
+        word.match_indices(side).any(|(at, _)| {
+            let before = word[..at].chars().next_back();
+            let after = word[at + side.len()..].chars().next();
+            before.is_none_or(|c| !c.is_ascii_alphabetic()) && after.is_none_or(|c| !c.is_ascii_digit())
+        })
+    })
+}
+
+/// The line of `source[start..end]` whose text is `line`, or the first line of the range
+fn line_at(source: &str, start: usize, end: usize, line: Option<&str>) -> Option<SourceMatch> {
+    let first_line = source[..start].matches('\n').count() + 1;
+    let masked = mask(source);
+    masked[start..end].lines().zip(source[start..end].lines()).enumerate()
+        .find(|(_, (code, _))| line.is_none_or(|wanted| code.trim() == wanted))
+        .map(|(i, (code, text))| {
+            let column = code.len() - code.trim_start().len();
+            let column = if i == 0 { column + source[..start].rsplit('\n').next().map_or(0, str::len) } else { column };
+            SourceMatch { line: first_line + i, column: column + 1, text: text.trim().to_string() }
+        })
+}
@@ -0,0 +1,310 @@
+//! The `audit` command around the rules: baselines, the findings database,
+//! SARIF, mechanical fixes and the AI-backed analyses, as its flags ask.
+//! Nothing here prints to stdout; the caller renders the [`AuditRun`] it
+//! gets back, and runs the interactive triage session from it
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{info, warn};
+use crate::analyzer::{Analysis, Analyzer};
+use crate::analyzer::gas::GasAnalyzer;
+use crate::analyzer::interactions::InteractionsAnalyzer;
+use crate::analyzer::security::SecurityAnalyzer;
+use crate::audit::baseline::{self, Baseline};
+use crate::audit::findings_db::{self, FindingsDb};
+use crate::audit::fix::{self, Applied, FixPlan};
+use crate::audit::triage::{self, Triage};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::audit::{audit_analysis, sarif, AuditAnalyzer, AuditProfile, AuditResult};
+use crate::cancel::run_or_cancel;
+use crate::error::{AnalyzerError, Result};
+use crate::input::{MAX_AI_INPUT_BYTES, STREAMING_THRESHOLD_BYTES};
+use crate::options::AnalysisOptions;
+use crate::parser::ParsedContract;
+
+/// What `audit` does besides running the rules; the default does nothing more
+#[derive(Debug, Clone, Default)]
+pub struct AuditRequest {
+    /// Hide the findings in this baseline snapshot
+    pub baseline: Option<PathBuf>,
+    /// Rewrite `baseline` with the current findings
+    pub update_baseline: bool,
+    /// Hide the findings the file had at this git ref
+    pub diff: Option<String>,
+    /// Severity at or above which findings new relative to `baseline` or `diff` block
+    pub fail_on_new: Option<Severity>,
+    pub import_db: Option<PathBuf>,
+    pub export_db: Option<PathBuf>,
+    /// Findings database the triage session resumes from and saves to
+    pub triage: Option<PathBuf>,
+    pub sarif: Option<PathBuf>,
+    /// Plan mechanical fixes; with `apply`, write them to the file and re-audit
+    pub fix: bool,
+    pub apply: bool,
+    /// Time the whole audit and one parse of the source
+    pub timings: bool,
+    /// Hide findings whose confidence is below this, from 0 to 1
+    pub min_confidence: Option<f64>,
+}
+
+/// One run of `audit` on a file
+pub struct AuditRun {
+    pub file: PathBuf,
+    pub result: AuditResult,
+    /// Names of the rules that ran, in registration order
+    pub rule_names: Vec<String>,
+    /// The audit, then the AI-backed gas, security and interaction analyses that finished
+    pub analyses: Vec<Analysis>,
+    /// Present with `--fix`
+    pub fix: Option<FixOutcome>,
+    /// Findings at or above `fail_on_new` left after the baseline, with that severity
+    pub blocking: Option<(Vec<Vulnerability>, Severity)>,
+    /// Set when the audit or an AI-backed analysis was cut short
+    pub interrupted: bool,
+    /// The session `triage` runs: the merged database and the fixes it offers
+    pending_triage: Option<(FindingsDb, FixPlan, String)>,
+}
+
+/// What `--fix` did
+pub struct FixOutcome {
+    pub plan: FixPlan,
+    pub applied: Applied,
+    /// The unified diff of the fixes, unless they were applied
+    pub diff: Option<String>,
+    /// With `apply`, the fixed findings the re-audit still reports, by name
+    pub remaining: Option<Vec<String>>,
+}
+
+/// Audits `file` with `options` and does what `request` adds: fails on
+/// flags that contradict each other before any rule runs. Writes the
+/// baseline, findings database and SARIF file asked for unless the audit
+/// was interrupted, and the fixes when `apply` is set
+pub async fn run(options: &AnalysisOptions, file: &Path, request: &AuditRequest) -> Result<AuditRun> {
+    if request.fail_on_new.is_some() && request.baseline.is_none() && request.diff.is_none() {
+        return Err(AnalyzerError::Config("--fail-on-new needs --baseline or --diff to tell which findings are new".to_string()));
+    }
+    if let Some(min) = request.min_confidence.filter(|min| !(0.0..=1.0).contains(min)) {
+        return Err(AnalyzerError::Config(format!("--min-confidence must be between 0 and 1, got {}", min)));
+    }
+    info!("Performing security audit for file: {}", file.display());
+
+    let analyzer = options.analyzer()?;
+    let rule_names = analyzer.rule_names();
+    let label = file.display().to_string();
+    let size = std::fs::metadata(file)?.len();
+    // Read once and shared with the fixer, triage and AI analyses; large inputs stream from disk
+    let mut source = (size <= STREAMING_THRESHOLD_BYTES)
+        .then(|| std::fs::read_to_string(file))
+        .transpose()?;
+    let started = Instant::now();
+    let mut result = match &source {
+        Some(content) => analyzer.audit_source(content, &label).await?,
+        None => analyzer.audit_file(file).await?,
+    };
+    if request.timings {
+        let total = started.elapsed();
+        // Timed on its own: the rules each parse the source, so one parse is the cost each of them pays
+        let parse = source.as_deref().map(|content| {
+            let started = Instant::now();
+            let _ = ParsedContract::new(content.to_string());
+            started.elapsed()
+        });
+        result.profile = Some(AuditProfile { total, parse });
+    }
+    if source.is_none() && (request.fix || request.triage.is_some()) {
+        source = Some(std::fs::read_to_string(file)?);
+    }
+
+    // Snapshot before filtering so the rewritten baseline covers every current finding
+    let snapshot = request.update_baseline.then(|| Baseline::from_result(&result, &rule_names));
+    if let Some(min) = request.min_confidence {
+        // Findings without a confidence come from exact checks
+        result.retain(|vuln| vuln.confidence.unwrap_or(1.0) >= min);
+    }
+    let findings_db = if request.import_db.is_some() || request.export_db.is_some() || request.triage.is_some() {
+        // A triage session resumes from its own database once it exists
+        let previous_path = request.import_db.as_deref().or(request.triage.as_deref().filter(|path| path.exists()));
+        let previous = previous_path.map(FindingsDb::load).transpose()?;
+        let (db, triage) = FindingsDb::merge(previous.as_ref(), &result, &label, &rule_names, findings_db::now());
+        result.triage = Some(triage);
+        Some(db)
+    } else {
+        None
+    };
+    if let Some(path) = &request.baseline {
+        // A missing file is fine when we are about to create it
+        if !(request.update_baseline || request.triage.is_some()) || path.exists() {
+            let source = format!("baseline {}", path.display());
+            let summary = Baseline::load(path)?.apply(&mut result, &rule_names, source);
+            result.baseline = Some(summary);
+        }
+    }
+    if let Some(git_ref) = &request.diff {
+        let previous = match baseline::read_at_git_ref(git_ref, file)? {
+            Some(content) => analyzer.audit_source(&content, &label).await?,
+            // New file: everything in it is new
+            None => AuditResult::default(),
+        };
+        let source = format!("{}:{}", git_ref, file.display());
+        let summary = Baseline::from_result(&previous, &rule_names).apply(&mut result, &rule_names, source);
+        result.baseline = Some(summary);
+    }
+
+    write_outputs(request, &result, file, &rule_names, snapshot, findings_db.as_ref())?;
+    let fix = match (request.fix, source.as_deref()) {
+        (true, Some(original)) => Some(fix(&analyzer, &result, file, original, request.apply).await?),
+        _ => None,
+    };
+    let pending_triage = match findings_db {
+        Some(db) if request.triage.is_some() && !result.interrupted => {
+            let source = source.clone().unwrap_or_default();
+            Some((db, analyzer.plan_fixes(&result, &source)?, source))
+        }
+        _ => None,
+    };
+
+    // Whatever is left after baseline filtering is new
+    let blocking = request.fail_on_new.map(|threshold| {
+        let blocking = result.vulnerabilities().filter(|vuln| vuln.severity.at_least(threshold)).cloned().collect();
+        (blocking, threshold)
+    });
+
+    let mut analyses = vec![audit_analysis(&result, String::new())];
+    if !analyzer.ai {
+        info!("ℹ️  Skipping AI-backed analyses: --no-ai");
+    } else if size > MAX_AI_INPUT_BYTES as u64 {
+        info!("ℹ️  Skipping AI-backed analyses: input exceeds the {} byte AI limit", MAX_AI_INPUT_BYTES);
+    } else {
+        analyses.extend(ai_analyses(&analyzer, file, source).await?);
+    }
+
+    let interrupted = result.interrupted || analyzer.cancel.is_cancelled();
+    Ok(AuditRun { file: file.to_path_buf(), result, rule_names, analyses, fix, blocking, interrupted, pending_triage })
+}
+
+impl AuditRun {
+    /// Walks through the open findings one by one, reading answers from
+    /// `input`, and saves the database to `db_path`, even when nothing was
+    /// decided so the next run finds it. None when `--triage` wasn't asked
+    /// for or the audit was interrupted
+    pub fn triage(&mut self, db_path: &Path, baseline: Option<&Path>, input: &mut impl BufRead, output: &mut impl Write)
+        -> Result<Option<triage::Outcome>> {
+        let Some((mut db, plan, source)) = self.pending_triage.take() else {
+            if self.result.interrupted {
+                warn!("⚠️  Triage skipped: the audit was interrupted");
+            }
+            return Ok(None);
+        };
+        let session = Triage { file: &self.file, db_path, baseline, rule_names: &self.rule_names };
+        let outcome = session.run(&self.result, &plan, &source, &mut db, input, output)?;
+        db.save(db_path)?;
+        Ok(Some(outcome))
+    }
+}
+
+/// Saves the baseline snapshot, findings database and SARIF log `request`
+/// asks for; an interrupted audit would save a partial picture, so it saves none
+fn write_outputs(request: &AuditRequest, result: &AuditResult, file: &Path, rule_names: &[String],
+    snapshot: Option<Baseline>, findings_db: Option<&FindingsDb>) -> Result<()> {
+    if let (Some(path), Some(snapshot)) = (&request.baseline, snapshot) {
+        if result.interrupted {
+            warn!("⚠️  Baseline not updated: the audit was interrupted");
+        } else {
+            snapshot.save(path)?;
+            info!("🧾 Baseline updated: {} findings written to {}", snapshot.findings.len(), path.display());
+        }
+    }
+    if let (Some(path), Some(db)) = (&request.export_db, findings_db) {
+        if result.interrupted {
+            warn!("⚠️  Findings database not written: the audit was interrupted");
+        } else {
+            db.save(path)?;
+            info!("🗂️  Findings database written: {} findings to {}", db.findings.len(), path.display());
+        }
+    }
+    if let Some(path) = &request.sarif {
+        if result.interrupted {
+            warn!("⚠️  SARIF not written: the audit was interrupted");
+        } else {
+            sarif::write(path, result, file, rule_names)?;
+            info!("🧾 SARIF written: {} findings to {}", result.vulnerabilities().count(), path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Plans the mechanical fixes and either diffs them or, with `apply`, writes
+/// them to `file` and re-audits it. Fails when the fixed source wouldn't parse
+async fn fix(analyzer: &AuditAnalyzer, result: &AuditResult, file: &Path, original: &str, apply: bool) -> Result<FixOutcome> {
+    let path = file.display().to_string();
+    let plan = analyzer.plan_fixes(result, original)?;
+    let applied = plan.apply(original);
+    fix::check_parses(original, &applied.source)?;
+    if applied.edits.is_empty() {
+        return Ok(FixOutcome { plan, applied, diff: None, remaining: None });
+    }
+    if !apply {
+        let diff = fix::unified_diff(&path, original, &applied.edits);
+        return Ok(FixOutcome { plan, applied, diff: Some(diff), remaining: None });
+    }
+    std::fs::write(file, &applied.source)?;
+    info!("✍️  Applied {} fix(es) to {}", applied.edits.len(), path);
+
+    // A fix that didn't remove its finding leaves the same fingerprint behind
+    let after = analyzer.audit_source(&applied.source, &path).await?;
+    let remaining = plan.fixes.iter()
+        .filter(|(vuln, _)| after.vulnerabilities().any(|a| a.fingerprint == vuln.fingerprint))
+        .map(|(vuln, _)| vuln.name.clone())
+        .collect();
+    Ok(FixOutcome { plan, applied, diff: None, remaining: Some(remaining) })
+}
+
+/// The gas, security and interaction analyses, skipping any that don't finish before an interrupt
+async fn ai_analyses(analyzer: &AuditAnalyzer, file: &Path, source: Option<String>) -> Result<Vec<Analysis>> {
+    let contract = match source {
+        Some(content) => ParsedContract::new(content)?,
+        None => ParsedContract::new(std::fs::read_to_string(file)?)?,
+    };
+    let cancel = &analyzer.cancel;
+    let gas = run_or_cancel(cancel, GasAnalyzer.analyze_parsed(&contract, file)).await.transpose()?;
+    let security = run_or_cancel(cancel, SecurityAnalyzer.analyze_parsed(&contract, file)).await.transpose()?;
+    let interactions = run_or_cancel(cancel, InteractionsAnalyzer.analyze_parsed(&contract, file)).await.transpose()?;
+    Ok(gas.into_iter().chain(security).chain(interactions).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_rewritten_baseline_leaves_nothing_new() {
+        let file = Path::new(env!("CARGO_MANIFEST_DIR")).join("test ex/vulnerable_staking.rs");
+        let baseline = std::env::temp_dir().join(format!("command-baseline-{}.json", std::process::id()));
+        let options = AnalysisOptions::new().ai(false);
+
+        let update = AuditRequest { baseline: Some(baseline.clone()), update_baseline: true, ..Default::default() };
+        let first = run(&options, &file, &update).await.unwrap();
+        let gate = AuditRequest { baseline: Some(baseline.clone()), fail_on_new: Some(Severity::Low), ..Default::default() };
+        let second = run(&options, &file, &gate).await;
+        std::fs::remove_file(&baseline).unwrap();
+        let second = second.unwrap();
+
+        assert!(first.result.vulnerabilities().count() > 0 && first.blocking.is_none());
+        let (blocking, threshold) = second.blocking.as_ref().unwrap();
+        assert!(blocking.is_empty() && *threshold == Severity::Low);
+        assert_eq!(second.result.baseline.as_ref().unwrap().suppressed.len(), first.result.vulnerabilities().count());
+        // Only the audit itself without the AI-backed analyses
+        assert_eq!(second.analyses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn contradicting_flags_fail_before_auditing() {
+        let missing = Path::new("does-not-exist.rs");
+        let new_without_reference = AuditRequest { fail_on_new: Some(Severity::High), ..Default::default() };
+        let out_of_range = AuditRequest { min_confidence: Some(1.5), ..Default::default() };
+        for request in [new_without_reference, out_of_range] {
+            let Err(AnalyzerError::Config(_)) = run(&AnalysisOptions::new(), missing, &request).await else { panic!("audit ran") };
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use solang_parser::pt::{
+    CodeLocation, ContractDefinition, ContractPart, ContractTy, FunctionDefinition, FunctionTy,
+    SourceUnitPart, Type, Expression,
+};
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{assigns, line_at, line_of, mentions, slice, state_variables};
+use crate::compare::abi::solidity_type;
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Solidity construction mistakes: state that is read but never set, constructor
+/// parameters that shadow state, and state that could be `immutable` or `constant`
+pub struct ConstructionRule;
+
+/// Gas for a cold SLOAD, saved on each transaction that reads an immutable instead
+const COLD_SLOAD_GAS: u64 = 2_100;
+/// Gas for the zero-to-nonzero SSTORE that deployment no longer pays
+const SSTORE_SET_GAS: u64 = 20_000;
+
+/// A function body with the contract it belongs to
+struct Body<'a> {
+    contract: &'a ContractDefinition,
+    func: &'a FunctionDefinition,
+    text: &'a str,
+    /// Byte offset of `text` in the source
+    start: usize,
+}
+
+#[async_trait]
+impl AuditRule for ConstructionRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Ok((unit, _)) = solang_parser::parse(content, 0) else { return Ok(Vec::new()) };
+        let contracts: Vec<&ContractDefinition> = unit.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract)
+                    if !matches!(contract.ty, ContractTy::Interface(_) | ContractTy::Library(_)) => Some(contract.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        // A derived contract in the same file may write what its base declares
+        let bodies: Vec<Body> = contracts.iter()
+            .flat_map(|contract| contract.parts.iter().filter_map(move |part| match part {
+                ContractPart::FunctionDefinition(func) => {
+                    let loc = func.body.as_ref()?.loc();
+                    Some(Body { contract, func, text: slice(&loc, content), start: loc.start() })
+                }
+                _ => None,
+            }))
+            .collect();
+        // Contract and interface types are addresses underneath, so they can be immutable too
+        let contract_names: Vec<&str> = unit.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) => contract.name.as_ref().map(|n| n.name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let mut vulnerabilities = Vec::new();
+        for contract in &contracts {
+            let (shadowed, reported) = check_shadowing(contract, &bodies, content, &mut vulnerabilities);
+
+            for (name, var) in state_variables(contract) {
+                let declared = line_of(&var.loc, content);
+                // Writes to a shadowed name inside the constructor hit the parameter
+                let writes: Vec<&Body> = bodies.iter()
+                    .filter(|body| writes_to(body.text, &name))
+                    .filter(|body| !(body.func.ty == FunctionTy::Constructor
+                        && std::ptr::eq(body.contract, *contract)
+                        && shadowed.contains(&name)))
+                    .collect();
+                let read = bodies.iter().any(|body| mentions(body.text, &name));
+
+                // A self-assignment was already reported for this variable
+                let self_assigned = reported.contains(&name);
+                if writes.is_empty() && var.initializer.is_none() && read && !is_collection(&var.ty) && !self_assigned {
+                    vulnerabilities.push(finding(
+                        "Uninitialized State Variable",
+                        Severity::Medium,
+                        format!("{} (declared on line {}) is read but never set at declaration, in a constructor or anywhere else, so it is always {}",
+                            name, declared, zero_value(&var.ty)),
+                        &format!("Set {} in the constructor or initializer, or make it a constant", name),
+                        None,
+                        declared,
+                    ));
+                    continue;
+                }
+
+                if !is_value_type(&var.ty, &contract_names) {
+                    continue;
+                }
+                let reads = bodies.iter()
+                    .filter(|body| body.func.ty != FunctionTy::Constructor && mentions(body.text, &name))
+                    .count();
+                let savings = format!("saves ~{} gas per transaction that reads it and ~{} at deployment",
+                    COLD_SLOAD_GAS, SSTORE_SET_GAS);
+                match (&var.initializer, writes.as_slice()) {
+                    // Literal at declaration and never written: compile-time constant
+                    (Some(init), []) if is_literal(init) => vulnerabilities.push(Vulnerability {
+                        name: "Constant Candidate".to_string(),
+                        severity: Severity::Info,
+                        risk_description: format!("{} (line {}) is fixed at declaration and never written; as `constant` it {} ({} reading function(s))",
+                            name, declared, savings, reads),
+                        recommendation: format!("Declare {} `constant`", name),
+                        line: Some(declared),
+                        effort: Some(Effort::Quick),
+                        ..Default::default()
+                    }),
+                    (_, writes) if !writes.is_empty() && writes.iter().all(|body| body.func.ty == FunctionTy::Constructor) => {
+                        let set_on = line_at(content, writes[0].start + writes[0].text.find(name.as_str()).unwrap_or(0));
+                        vulnerabilities.push(Vulnerability {
+                            name: "Immutable Candidate".to_string(),
+                            severity: Severity::Info,
+                            risk_description: format!("{} (line {}) is only set in the constructor (line {}); as `immutable` it {} ({} reading function(s))",
+                                name, declared, set_on, savings, reads),
+                            recommendation: format!("Declare {} `immutable`", name),
+                            line: Some(declared),
+                            effort: Some(Effort::Quick),
+                            ..Default::default()
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Constructor Initialization Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-CONSTRUCTOR-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "State read but never set, shadowing constructor parameters and immutable candidates"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Constant Candidate",
+            "Immutable Candidate",
+            "Constructor Parameter Shadows State Variable",
+            "Constructor Self-Assignment",
+            "Uninitialized State Variable",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// Reports constructor parameters named like a state variable; returns the
+/// shadowed names and the ones assigned to themselves
+fn check_shadowing(contract: &ContractDefinition, bodies: &[Body], source: &str, vulnerabilities: &mut Vec<Vulnerability>) -> (Vec<String>, Vec<String>) {
+    let state = state_variables(contract);
+    let Some(ctor) = bodies.iter().find(|body| std::ptr::eq(body.contract, contract) && body.func.ty == FunctionTy::Constructor) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut shadowed = Vec::new();
+    let mut self_assigned = Vec::new();
+    for param in ctor.func.params.iter().filter_map(|(_, param)| param.as_ref()) {
+        let Some(name) = param.name.as_ref().map(|n| n.name.as_str()) else { continue };
+        let Some((_, var)) = state.iter().find(|(var, _)| var == name) else { continue };
+        shadowed.push(name.to_string());
+        let param_line = line_of(&param.loc, source);
+
+        match self_assignment(ctor.text, name) {
+            Some(offset) => {
+                self_assigned.push(name.to_string());
+                vulnerabilities.push(finding(
+                    "Constructor Self-Assignment",
+                    Severity::High,
+                    format!("`{} = {};` on line {} assigns the constructor parameter {} (line {}) to itself; the state variable is never set and stays {}",
+                        name, name, line_at(source, ctor.start + offset), name, param_line, zero_value(&var.ty)),
+                    &format!("Rename the parameter (e.g. `_{}` or `initial{}`) and assign it to the state variable", name, capitalize(name)),
+                    Some("constructor"),
+                    param_line,
+                ));
+            }
+            None => vulnerabilities.push(finding(
+                "Constructor Parameter Shadows State Variable",
+                Severity::Low,
+                format!("constructor parameter {} (line {}) shadows the state variable of the same name; inside the constructor every use of {} means the parameter",
+                    name, param_line, name),
+                "Rename the parameter so reads and writes reach the state variable",
+                Some("constructor"),
+                param_line,
+            )),
+        }
+    }
+    (shadowed, self_assigned)
+}
+
+/// Offset of `name = name;` in `body`
+fn self_assignment(body: &str, name: &str) -> Option<usize> {
+    body.match_indices(name).map(|(i, _)| i).find(|&i| {
+        if body[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.') {
+            return false;
+        }
+        let Some(rest) = body[i + name.len()..].trim_start().strip_prefix('=') else { return false };
+        let Some(rest) = rest.trim_start().strip_prefix(name) else { return false };
+        rest.trim_start().starts_with(';')
+    })
+}
+
+fn writes_to(body: &str, var: &str) -> bool {
+    assigns(body, var)
+        || mentions(&body.replace(' ', ""), &format!("delete{}", var))
+        || [".push(", ".pop(", "++", "--"].iter().any(|op| body.contains(&format!("{}{}", var, op)))
+        || ["++", "--"].iter().any(|op| body.contains(&format!("{}{}", op, var)))
+}
+
+
+fn is_collection(ty: &Expression) -> bool {
+    matches!(ty, Expression::Type(_, Type::Mapping { .. }) | Expression::ArraySubscript(..))
+}
+
+/// Types `immutable` accepts: elementary value types and contract references
+fn is_value_type(ty: &Expression, contracts: &[&str]) -> bool {
+    match ty {
+        Expression::Type(_, Type::Address | Type::AddressPayable | Type::Bool | Type::Uint(_) | Type::Int(_) | Type::Bytes(_)) => true,
+        Expression::Variable(name) => contracts.contains(&name.name.as_str()),
+        _ => false,
+    }
+}
+
+fn is_literal(expr: &Expression) -> bool {
+    matches!(expr,
+        Expression::NumberLiteral(..) | Expression::HexNumberLiteral(..) | Expression::BoolLiteral(..)
+        | Expression::AddressLiteral(..) | Expression::HexLiteral(..) | Expression::RationalNumberLiteral(..))
+}
+
+fn zero_value(ty: &Expression) -> &'static str {
+    match solidity_type(ty).as_str() {
+        "address" => "address(0)",
+        "bool" => "false",
+        "string" | "bytes" => "empty",
+        t if t.starts_with("uint") || t.starts_with("int") => "0",
+        _ => "zero",
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn finding(name: &str, severity: Severity, risk_description: String, recommendation: &str, function: Option<&str>, line: usize) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        function: function.map(str::to_string),
+        line: (line > 0).then_some(line),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    #[tokio::test]
+    async fn shadowed_constructor_parameters_and_candidates() {
+        let found = found(&ConstructionRule, include_str!("../../test ex/constructor/shadowed_owner.sol")).await;
+        assert_eq!(found.iter().map(|(name, line)| (name.as_str(), *line)).collect::<Vec<_>>(), [
+            ("Immutable Candidate", 12),
+            ("Immutable Candidate", 13),
+            ("Constant Candidate", 14),
+            ("Uninitialized State Variable", 16),
+            ("Constructor Self-Assignment", 18),
+        ]);
+    }
+}
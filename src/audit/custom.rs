@@ -0,0 +1,170 @@
+//! House rules loaded with `--custom-rules`: each entry in a YAML or TOML
+//! file flags the lines its regex matches, unless the same line also
+//! matches its `must_not_match` regex. Loaded rules join the built-in ones
+//! in the analyzer `AnalysisOptions` builds, so ids work with `--only-rules`,
+//! `[rules.severity]` and suppressions
+
+use std::path::Path;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use crate::audit::patterns::{all_rules, severity_level};
+use crate::audit::rules::AuditRule;
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    rules: Vec<Entry>,
+}
+
+/// One rule as written in the file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    id: String,
+    name: String,
+    severity: String,
+    /// Matched against each line, so `^` and `$` anchor to line boundaries
+    regex: String,
+    /// Lines that also match this are not reported, e.g. `// audited:` annotations
+    #[serde(default)]
+    must_not_match: Option<String>,
+    recommendation: String,
+    /// What the match means; also what `rules` prints
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// A rule from the `--custom-rules` file
+#[derive(Clone)]
+pub struct CustomRegexRule {
+    // Leaked once at load: the rules live for the whole run and `AuditRule` hands out `&'static str`
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    severity: Severity,
+    regex: Regex,
+    must_not_match: Option<Regex>,
+    recommendation: String,
+}
+
+#[async_trait]
+impl AuditRule for CustomRegexRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let mut vulnerabilities = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let Some(found) = self.regex.find(line) else { continue };
+            if self.must_not_match.as_ref().is_some_and(|exempt| exempt.is_match(line)) {
+                continue;
+            }
+            vulnerabilities.push(Vulnerability {
+                name: self.name.to_string(),
+                severity: self.severity,
+                risk_description: self.description.to_string(),
+                recommendation: self.recommendation.clone(),
+                line: Some(index + 1),
+                column: Some(line[..found.start()].chars().count() + 1),
+                snippet: Some(line.trim().to_string()),
+                ..Default::default()
+            });
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+/// Reads the rules in `path`, YAML unless it ends in `.toml`. A bad regex or
+/// severity, or an id that is taken, fails naming the entry's id
+pub fn load(path: &Path) -> Result<Vec<CustomRegexRule>> {
+    let text = std::fs::read_to_string(path)?;
+    let fail = |message: String| AnalyzerError::Config(format!("{}: {}", path.display(), message));
+    let file: RulesFile = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| fail(e.message().to_string()))?,
+        _ => serde_yaml::from_str(&text).map_err(|e| fail(e.to_string()))?,
+    };
+
+    let builtin: Vec<&'static str> = all_rules().iter().map(|rule| rule.id()).collect();
+    let mut rules: Vec<CustomRegexRule> = Vec::new();
+    for entry in file.rules {
+        let id = entry.id.trim();
+        if id.is_empty() {
+            return Err(fail(format!("rule \"{}\" has an empty id", entry.name)));
+        }
+        if builtin.iter().chain(rules.iter().map(|rule| &rule.id)).any(|taken| taken.eq_ignore_ascii_case(id)) {
+            return Err(fail(format!("rule {}: the id is already taken", id)));
+        }
+        let compile = |field: &str, pattern: &str| Regex::new(pattern)
+            .map_err(|e| fail(format!("rule {}: invalid `{}`: {}", id, field, e)));
+        let regex = compile("regex", &entry.regex)?;
+        let must_not_match = entry.must_not_match.as_deref().map(|pattern| compile("must_not_match", pattern)).transpose()?;
+        let severity = severity_level(&entry.severity, &format!("{}: rule {}", path.display(), id))?;
+        let description = entry.description
+            .unwrap_or_else(|| format!("Line matches the team rule's pattern `{}`", entry.regex));
+        rules.push(CustomRegexRule {
+            id: leak(id.to_string()),
+            name: leak(entry.name),
+            description: leak(description),
+            severity,
+            regex,
+            must_not_match,
+            recommendation: entry.recommendation,
+        });
+    }
+    Ok(rules)
+}
+
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("test ex/custom").join(name)
+    }
+
+    #[tokio::test]
+    async fn yaml_and_toml_rules_flag_the_same_lines() {
+        let source = include_str!("../../test ex/custom/house_vault.rs");
+        for file in ["team-rules.yaml", "team-rules.toml"] {
+            let rules = load(&fixture(file)).unwrap();
+            let ids: Vec<&str> = rules.iter().map(|rule| rule.id()).collect();
+            assert_eq!(ids, ["TEAM-UNSAFE-001", "TEAM-BANNED-001"]);
+
+            let mut found = Vec::new();
+            for rule in &rules {
+                for vuln in rule.check(source).await.unwrap() {
+                    found.push((rule.id(), vuln.severity, vuln.line.unwrap(), vuln.column.unwrap()));
+                }
+            }
+            // The raw call annotated `// reviewed:` on line 23 is exempt
+            assert_eq!(found, [("TEAM-UNSAFE-001", Severity::High, 18, 18), ("TEAM-BANNED-001", Severity::Low, 17, 9)]);
+        }
+    }
+
+    #[test]
+    fn an_invalid_regex_names_the_rule() {
+        let Err(AnalyzerError::Config(message)) = load(&fixture("bad-rules.yaml")) else { panic!("bad regex loaded") };
+        assert!(message.contains("rule TEAM-BROKEN-001: invalid `regex`"), "{}", message);
+    }
+}
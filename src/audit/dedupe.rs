@@ -0,0 +1,179 @@
+//! Merges findings that several rules report for the same problem in the
+//! same place, e.g. the reentrancy checker and the AI pattern detector both
+//! flagging one external call. Each merged finding keeps the most severe
+//! report and lists every contributing rule id in `detected_by`.
+//! `--no-dedupe` turns this off
+
+use super::AuditResult;
+use super::vulnerabilities::Vulnerability;
+
+/// Problem families; a finding belongs to the first whose keywords its name contains
+const CATEGORIES: &[(&str, &[&str])] = &[
+    ("reentrancy", &["reentran"]),
+    ("delegatecall", &["delegatecall"]),
+    ("access-control", &["access control", "unauthorized", "authoriz"]),
+    ("memory-safety", &["unsafe block", "raw pointer", "memory safety", "uninitialized memory", "memory leak"]),
+    ("arithmetic", &["overflow", "underflow", "arithmetic"]),
+    ("randomness", &["random"]),
+    ("timestamp", &["timestamp"]),
+    ("initializer", &["initializ"]),
+    ("upgrade", &["upgrade"]),
+    ("selfdestruct", &["selfdestruct", "self-destruct", "destruct"]),
+    ("storage-packing", &["packing", "packed", "unpacked"]),
+    ("batching", &["batch"]),
+    ("denial-of-service", &["denial of service", "unbounded"]),
+    ("event-emission", &["missing event", "critical event", "event not emitted"]),
+];
+
+/// Merges findings from different rules with the same category and location.
+/// Locations match when both findings have the same line or both have none;
+/// a whole-file finding also folds into a located one with the same name
+pub fn merge(result: &mut AuditResult) {
+    // Most severe first, so the finding kept is always the most severe one
+    let mut kept: Vec<Vulnerability> = Vec::new();
+    for bucket in [
+        &mut result.critical_vulnerabilities,
+        &mut result.high_vulnerabilities,
+        &mut result.medium_vulnerabilities,
+        &mut result.low_vulnerabilities,
+        &mut result.info_findings,
+    ] {
+        for vuln in bucket.drain(..) {
+            match kept.iter_mut().find(|kept| overlaps(kept, &vuln)) {
+                Some(kept) => absorb(kept, vuln),
+                None => kept.push(vuln),
+            }
+        }
+    }
+    for vuln in kept {
+        result.push(vuln);
+    }
+}
+
+fn overlaps(kept: &Vulnerability, vuln: &Vulnerability) -> bool {
+    let Some(wanted) = category(vuln) else { return false };
+    let rule_id = vuln.rule_id.as_deref().unwrap_or_default();
+    let same_rule = kept.rule_id.as_deref() == Some(rule_id) || kept.detected_by.iter().any(|id| id == rule_id);
+    let same_place = match (kept.line, vuln.line) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => true,
+        _ => kept.name.eq_ignore_ascii_case(&vuln.name),
+    };
+    !same_rule && same_place && category(kept) == Some(wanted)
+}
+
+/// Folds `vuln` into the more severe `kept`, taking its location when `kept` has none
+fn absorb(kept: &mut Vulnerability, vuln: Vulnerability) {
+    if kept.detected_by.is_empty() {
+        kept.detected_by.extend(kept.rule_id.clone());
+    }
+    kept.detected_by.extend(vuln.rule_id);
+    // Rules agreeing make the finding at least as likely as the surest of them
+    kept.confidence = match (kept.confidence, vuln.confidence) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    if kept.line.is_none() && vuln.line.is_some() {
+        kept.line = vuln.line;
+        kept.column = vuln.column;
+        kept.snippet = vuln.snippet;
+        kept.function = kept.function.take().or(vuln.function);
+    }
+    for reference in vuln.references {
+        if !kept.references.contains(&reference) {
+            kept.references.push(reference);
+        }
+    }
+    kept.example = kept.example.take().or(vuln.example);
+    kept.reference = kept.reference.take().or(vuln.reference);
+}
+
+/// The problem family `vuln` belongs to, e.g. `reentrancy`
+pub(crate) fn category(vuln: &Vulnerability) -> Option<&'static str> {
+    let name = vuln.name.to_ascii_lowercase();
+    CATEGORIES.iter()
+        .find(|(_, keywords)| keywords.iter().any(|keyword| name.contains(keyword)))
+        .map(|(category, _)| *category)
+}
+
+/// Whether `name` is one of the problem families
+pub(crate) fn is_category(name: &str) -> bool {
+    CATEGORIES.iter().any(|(category, _)| *category == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::audit::rules::AuditRule;
+    use crate::audit::vulnerabilities::Severity;
+    use crate::audit::AuditAnalyzer;
+    use crate::error::Result;
+
+    fn finding(name: &str, rule_id: &str, severity: Severity, line: Option<usize>) -> Vulnerability {
+        Vulnerability {
+            name: name.to_string(),
+            rule_id: Some(rule_id.to_string()),
+            severity,
+            line,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn the_most_severe_report_is_kept_with_every_rule_that_found_it() {
+        let mut result = AuditResult::default();
+        result.push(finding("Potential Reentrancy", "SA-REENT-001", Severity::Medium, Some(12)));
+        result.push(finding("Reentrancy via External Call", "SA-AI-001", Severity::High, Some(12)));
+        result.push(finding("Cross-Function Reentrancy", "SA-REENT-002", Severity::Low, None));
+        // Same family, different place
+        result.push(finding("Potential Reentrancy", "SA-REENT-001", Severity::Medium, Some(40)));
+        merge(&mut result);
+
+        let merged: Vec<(&str, Severity, Option<usize>, Vec<&str>)> = result.vulnerabilities()
+            .map(|vuln| (vuln.name.as_str(), vuln.severity, vuln.line, vuln.detected_by.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(merged, [
+            ("Reentrancy via External Call", Severity::High, Some(12), vec!["SA-AI-001", "SA-REENT-001"]),
+            ("Potential Reentrancy", Severity::Medium, Some(40), vec![]),
+            ("Cross-Function Reentrancy", Severity::Low, None, vec![]),
+        ]);
+    }
+
+    /// Reports a reentrancy at line 3 under its own id
+    struct Reporter(&'static str, Severity);
+
+    #[async_trait]
+    impl AuditRule for Reporter {
+        async fn check(&self, _content: &str) -> Result<Vec<Vulnerability>> {
+            Ok(vec![Vulnerability { name: format!("{} Reentrancy", self.0), severity: self.1, line: Some(3), ..Default::default() }])
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "Test rule"
+        }
+
+        fn default_severity(&self) -> Severity {
+            self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn an_analyzer_without_dedupe_keeps_every_report() {
+        for (dedupe, expected) in [(true, 1), (false, 2)] {
+            let analyzer = AuditAnalyzer::new().with_dedupe(dedupe).with_ai(false);
+            analyzer.add_rule(Box::new(Reporter("First", Severity::High)));
+            analyzer.add_rule(Box::new(Reporter("Second", Severity::Medium)));
+            let result = analyzer.audit("contract C {}").await.unwrap();
+            assert_eq!(result.vulnerabilities().count(), expected, "dedupe {}", dedupe);
+        }
+    }
+}
@@ -0,0 +1,585 @@
+//! Delegatecall sites and where their target comes from. A delegatecall runs
+//! another contract's code against this contract's storage, so the target
+//! must be fixed or changeable only by an administrator, and a failed call
+//! must revert rather than look like success
+
+use async_trait::async_trait;
+use solang_parser::pt::{CodeLocation, ContractDefinition, ContractPart, FunctionDefinition, FunctionTy, Loc, SourceUnitPart, VariableAttribute};
+use crate::audit::access_control::is_guarded;
+use crate::audit::initializer::is_initializer_name;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{assigns, is_externally_callable, mask, mentions, modifier_names, rust_functions, state_variables};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::Result;
+
+pub const FINDING: &str = "Unsafe Delegatecall";
+/// How many internal calls and local assignments a target is traced back through
+const MAX_DEPTH: usize = 3;
+const RECOMMENDATION: &str = "Use an audited proxy pattern (ERC-1967 UUPS or transparent proxy) whose implementation \
+    only changes through an access-controlled upgrade function, validate new implementation addresses (non-zero, \
+    has code, proxiableUUID), and revert when the delegatecall fails";
+
+/// Where a delegatecall's target comes from
+enum Target {
+    /// Chosen, or changeable, by any caller; describes how
+    User(String),
+    /// A constant, or storage only an administrator or the constructor writes
+    Trusted,
+    /// Couldn't be traced; the target expression
+    Unknown(String),
+}
+
+/// One delegatecall, with its byte offset in the source
+struct Site {
+    function: String,
+    offset: usize,
+    target: Target,
+    checked: bool,
+}
+
+/// Delegatecalls whose target any caller controls, can't be traced, or whose failure is ignored
+pub struct DelegatecallRule;
+
+#[async_trait]
+impl AuditRule for DelegatecallRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let sites = match solang_parser::parse(content, 0) {
+            Ok((unit, _)) => unit.0.iter()
+                .filter_map(|part| match part {
+                    SourceUnitPart::ContractDefinition(contract) => Some(Contract::new(contract, &code).sites()),
+                    _ => None,
+                })
+                .flatten()
+                .collect(),
+            Err(_) if syn::parse_file(content).is_ok() => stylus_sites(&code),
+            Err(_) => Vec::new(),
+        };
+        Ok(sites.into_iter().filter_map(|site| finding(site, content)).collect())
+    }
+
+    fn name(&self) -> &'static str {
+        "Delegatecall Safety Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-DELEGATECALL-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delegatecall and proxy dispatch to caller-controlled targets, or without checking success"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[FINDING]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+}
+
+fn finding(site: Site, content: &str) -> Option<Vulnerability> {
+    let unchecked = if site.checked { "" } else { ", and the call's success is never checked" };
+    let (severity, risk_description) = match &site.target {
+        Target::User(origin) => (Severity::Critical, format!(
+            "delegatecall in {} runs code at an address taken from {}{}; a caller can point it at a contract that rewrites storage or self-destructs",
+            site.function, origin, unchecked)),
+        Target::Unknown(expression) => (Severity::High, format!(
+            "delegatecall in {} targets `{}`, which can't be traced to a constant or access-controlled storage{}",
+            site.function, expression, unchecked)),
+        Target::Trusted if !site.checked => (Severity::High, format!(
+            "delegatecall in {} never checks whether the call succeeded, so a reverting implementation looks like success",
+            site.function)),
+        Target::Trusted => return None,
+    };
+    let line_start = content[..site.offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[site.offset..].find('\n').map_or(content.len(), |i| site.offset + i);
+    Some(Vulnerability {
+        name: FINDING.to_string(),
+        severity,
+        risk_description,
+        recommendation: RECOMMENDATION.to_string(),
+        function: Some(site.function),
+        line: Some(content[..site.offset].matches('\n').count() + 1),
+        column: Some(content[line_start..site.offset].chars().count() + 1),
+        snippet: Some(content[line_start..line_end].trim().to_string()),
+        ..Default::default()
+    })
+}
+
+/// A Solidity function or modifier and the byte range of its body
+struct Function<'a> {
+    name: String,
+    def: &'a FunctionDefinition,
+    start: usize,
+    end: usize,
+}
+
+/// What a Solidity contract's delegatecall targets are traced against
+struct Contract<'a> {
+    code: &'a str,
+    state_vars: Vec<String>,
+    constants: Vec<String>,
+    functions: Vec<Function<'a>>,
+    /// Modifiers whose body compares the caller, e.g. a transparent proxy's `ifAdmin`
+    guard_modifiers: Vec<String>,
+}
+
+impl<'a> Contract<'a> {
+    fn new(contract: &'a ContractDefinition, code: &'a str) -> Self {
+        let mut functions = Vec::new();
+        let mut constants = Vec::new();
+        for part in &contract.parts {
+            match part {
+                ContractPart::FunctionDefinition(func) => {
+                    let Some(Loc::File(_, start, end)) = func.body.as_ref().map(|body| body.loc()) else { continue };
+                    let name = func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string());
+                    functions.push(Function { name, def: func, start, end });
+                }
+                ContractPart::VariableDefinition(var) => {
+                    let in_code = var.attrs.iter().any(|a| matches!(a, VariableAttribute::Constant(_) | VariableAttribute::Immutable(_)));
+                    if let Some(name) = var.name.as_ref().filter(|_| in_code) {
+                        constants.push(name.name.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        let guard_modifiers = functions.iter()
+            .filter(|f| matches!(f.def.ty, FunctionTy::Modifier) && checks_sender(&code[f.start..f.end]))
+            .map(|f| f.name.clone())
+            .collect();
+        let state_vars = state_variables(contract).into_iter().map(|var| var.0).collect();
+        Self { code, state_vars, constants, functions, guard_modifiers }
+    }
+
+    fn sites(&self) -> Vec<Site> {
+        let mut sites = Vec::new();
+        for function in self.functions.iter().filter(|f| !matches!(f.def.ty, FunctionTy::Modifier)) {
+            let body = self.body(function);
+            for (at, target, checked) in solidity_delegatecalls(body) {
+                sites.push(Site {
+                    function: function.name.clone(),
+                    offset: function.start + at,
+                    target: self.classify(target, function, 0),
+                    checked,
+                });
+            }
+        }
+        sites
+    }
+
+    fn body(&self, function: &Function) -> &'a str {
+        &self.code[function.start..function.end]
+    }
+
+    fn classify(&self, expression: &str, function: &Function, depth: usize) -> Target {
+        let expression = strip_casts(expression.trim());
+        let root = leading_identifier(expression);
+        if depth > MAX_DEPTH {
+            return Target::Unknown(expression.to_string());
+        }
+        if self.constants.iter().any(|name| name == root) {
+            return Target::Trusted;
+        }
+        if self.state_vars.iter().any(|name| name == root) {
+            return self.storage(root, |body| assigns(body, root) || body.contains(&format!("delete {}", root)));
+        }
+        if let Some(slot) = expression.strip_prefix("sload(") {
+            let slot = slot.trim_end_matches(')').trim();
+            return self.storage(slot, |body| {
+                let compact = compact(body);
+                compact.contains(&format!("sstore({},", slot)) || compact.contains(&format!("Slot({}).value=", slot))
+            });
+        }
+        if ["calldataload", "msg.data", "msg.sender"].iter().any(|source| expression.contains(source)) {
+            return Target::User("calldata".to_string());
+        }
+        let params = parameter_names(&function.def.params);
+        if let Some(index) = params.iter().position(|param| param == root) {
+            if self.is_exposed(function, 0) {
+                return Target::User(format!("the `{}` argument of {}", root, function.name));
+            }
+            if is_entry_point(function.def) {
+                return Target::Trusted;
+            }
+            // An internal helper: the target is whatever each caller passes
+            let targets = self.functions.iter()
+                .flat_map(|caller| call_arguments(self.body(caller), &function.name).into_iter().map(move |args| (caller, args)))
+                .filter_map(|(caller, args)| args.get(index).map(|arg| self.classify(arg, caller, depth + 1)))
+                .collect();
+            return combine(targets);
+        }
+        if let Some(value) = assigned_value(self.body(function), root) {
+            return self.classify(value, function, depth + 1);
+        }
+        // A getter such as `_implementation()`
+        if expression.ends_with("()") {
+            if let Some(getter) = self.functions.iter().find(|f| f.name == root) {
+                let body = self.body(getter);
+                let returned = parameter_names(&getter.def.returns).iter()
+                    .find_map(|name| assigned_value(body, name))
+                    .or_else(|| body.split_once("return ").and_then(|(_, rest)| rest.split(';').next()));
+                if let Some(value) = returned {
+                    return self.classify(value, getter, depth + 1);
+                }
+            }
+        }
+        Target::Unknown(expression.to_string())
+    }
+
+    /// Trusted unless a function any caller can reach writes `key`
+    fn storage(&self, key: &str, writes: impl Fn(&str) -> bool) -> Target {
+        self.functions.iter()
+            .filter(|f| !matches!(f.def.ty, FunctionTy::Constructor | FunctionTy::Modifier) && !is_initializer(f))
+            .find(|f| writes(self.body(f)) && self.is_exposed(f, 0))
+            .map_or(Target::Trusted, |setter| Target::User(format!("`{}`, which {} lets anyone change", key, setter.name)))
+    }
+
+    /// Whether any caller can run `function`, directly or through unguarded callers
+    fn is_exposed(&self, function: &Function, depth: usize) -> bool {
+        if self.is_guarded(function) {
+            return false;
+        }
+        match function.def.ty {
+            FunctionTy::Fallback | FunctionTy::Receive => true,
+            FunctionTy::Function if is_externally_callable(function.def) => true,
+            FunctionTy::Function if depth < MAX_DEPTH => self.functions.iter()
+                .filter(|caller| caller.name != function.name && !call_arguments(self.body(caller), &function.name).is_empty())
+                .any(|caller| self.is_exposed(caller, depth + 1)),
+            _ => false,
+        }
+    }
+
+    fn is_guarded(&self, function: &Function) -> bool {
+        let by_modifier = modifier_names(function.def).iter().any(|name| {
+            let lower = name.to_lowercase();
+            lower.starts_with("only") || lower.contains("auth") || self.guard_modifiers.contains(name)
+        });
+        let body = self.body(function);
+        // A check factored into a helper, e.g. `_checkAdmin();`
+        let by_helper = self.functions.iter()
+            .any(|helper| !call_arguments(body, &helper.name).is_empty() && checks_sender(self.body(helper)));
+        by_modifier || by_helper || checks_sender(body)
+    }
+}
+
+/// External, public and fallback functions; only an administrator reaches a guarded one
+fn is_entry_point(func: &FunctionDefinition) -> bool {
+    matches!(func.ty, FunctionTy::Fallback | FunctionTy::Receive)
+        || (matches!(func.ty, FunctionTy::Function) && is_externally_callable(func))
+}
+
+fn is_initializer(function: &Function) -> bool {
+    is_initializer_name(&function.name) || modifier_names(function.def).iter().any(|name| name == "initializer")
+}
+
+/// Whether a Solidity body compares `msg.sender` or calls a common ownership or role check
+fn checks_sender(body: &str) -> bool {
+    let compact = compact(body);
+    ["msg.sender==", "==msg.sender", "msg.sender!=", "!=msg.sender"].iter().any(|check| compact.contains(check))
+        || ["_checkOwner(", "_checkRole(", "hasRole(", "_authorizeUpgrade("].iter().any(|check| compact.contains(check))
+}
+
+/// User-controlled if any path is, untraceable if any path is, else trusted
+fn combine(targets: Vec<Target>) -> Target {
+    let mut combined = Target::Trusted;
+    for target in targets {
+        match target {
+            Target::User(_) => return target,
+            Target::Unknown(_) => combined = target,
+            Target::Trusted => {}
+        }
+    }
+    combined
+}
+
+/// `.delegatecall(..)` and Yul `delegatecall(..)` in a masked Solidity body:
+/// offset, target expression and whether success is checked
+fn solidity_delegatecalls(body: &str) -> Vec<(usize, &str, bool)> {
+    let mut found = Vec::new();
+    for (at, call) in body.match_indices("delegatecall(") {
+        let Some((args, _)) = arguments(body, at + call.len()) else { continue };
+        if body[..at].ends_with('.') {
+            let target = operand_before(body, at - 1);
+            found.push((at - 1 - target.len(), target, solidity_checked(body, at)));
+        } else if !body[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            let Some(target) = args.get(1) else { continue };
+            found.push((at, *target, yul_checked(body, at)));
+        }
+    }
+    found
+}
+
+/// `(bool ok, ) = target.delegatecall(data); require(ok)` and calls made inside a check
+fn solidity_checked(body: &str, at: usize) -> bool {
+    let start = body[..at].rfind([';', '{', '}']).map_or(0, |i| i + 1);
+    let statement = &body[start..at];
+    if ["require(", "assert(", "if (", "if(", "return "].iter().any(|check| statement.contains(check)) {
+        return true;
+    }
+    let Some((_, rest)) = statement.split_once("bool ") else { return false };
+    let name = leading_identifier(rest.trim_start());
+    let after = compact(&body[at..]);
+    !name.is_empty() && [format!("require({}", name), format!("assert({}", name), format!("if({}", name),
+        format!("!{}", name), format!("({},", name), format!("({})", name)]
+        .iter().any(|check| after.contains(check.as_str()))
+}
+
+/// `let ok := delegatecall(..)` later switched on or tested, or a call inside `iszero(..)`; `pop(..)` discards it
+fn yul_checked(body: &str, at: usize) -> bool {
+    let line_start = body[..at].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &body[line_start..at];
+    if ["iszero(", "if ", "switch ", "eq("].iter().any(|check| prefix.contains(check)) {
+        return true;
+    }
+    let Some((_, name)) = prefix.split_once("let ") else { return false };
+    let name = leading_identifier(name.trim_start());
+    let after = body[at..].split_whitespace().collect::<Vec<_>>().join(" ");
+    !name.is_empty() && [format!("switch {}", name), format!("iszero({})", name), format!("if {}", name), format!("eq({},", name)]
+        .iter().any(|check| after.contains(check.as_str()))
+}
+
+/// Stylus `delegate_call(ctx, target, data)` and `RawCall::new_delegate()...call(target, data)` sites
+fn stylus_sites(code: &str) -> Vec<Site> {
+    let functions = rust_functions(code);
+    let mut sites = Vec::new();
+    for (name, start, end) in &functions {
+        let body = &code[*start..*end];
+        let mut calls: Vec<(usize, &str, usize)> = Vec::new();
+        for (at, call) in body.match_indices("delegate_call(") {
+            if body[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_') || body[..at].trim_end().ends_with("fn") {
+                continue;
+            }
+            if let Some((args, close)) = arguments(body, at + call.len()) {
+                calls.extend(args.get(1).map(|target| (at, *target, close)));
+            }
+        }
+        for (at, _) in body.match_indices("new_delegate()") {
+            let Some(call) = body[at..].find(".call(").map(|i| at + i + ".call(".len()) else { continue };
+            if let Some((args, close)) = arguments(body, call) {
+                calls.extend(args.first().map(|target| (at, *target, close)));
+            }
+        }
+        for (at, target, close) in calls {
+            sites.push(Site {
+                function: name.clone(),
+                offset: start + at,
+                target: classify_rust(code, &functions, (name, *start, *end), target, 0),
+                checked: rust_checked(body, at, close),
+            });
+        }
+    }
+    sites
+}
+
+/// Propagated with `?`, matched, mapped, returned or bound to a name that's used later
+fn rust_checked(body: &str, at: usize, close: usize) -> bool {
+    let mut start = body[..at].rfind([';', '{', '}']).map_or(0, |i| i + 1);
+    let mut close = close;
+    // `let _ = unsafe { call(..) };` is judged by the statement around the block
+    while start > 0 && body[..start - 1].trim_end().ends_with("unsafe") && body[close + 1..].trim_start().starts_with('}') {
+        let unsafe_at = body[..start - 1].trim_end().len() - "unsafe".len();
+        start = body[..unsafe_at].rfind([';', '{', '}']).map_or(0, |i| i + 1);
+        close += 1 + body[close + 1..].find('}').unwrap_or(0);
+    }
+    let after = body[close + 1..].trim_start();
+    if after.starts_with('?') || after.starts_with('.') {
+        return true;
+    }
+    let statement = body[start..at].trim_start();
+    if statement.starts_with("let _") && !statement["let _".len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return false;
+    }
+    if let Some(binding) = statement.strip_prefix("let ") {
+        let name = leading_identifier(binding.trim_start_matches("mut ").trim_start());
+        return !name.is_empty() && mentions(&body[close..], name);
+    }
+    // A statement of its own throws the result away; anything else returns or passes it on
+    !after.starts_with(';')
+}
+
+fn classify_rust(code: &str, functions: &[(String, usize, usize)], function: (&str, usize, usize), expression: &str, depth: usize) -> Target {
+    let (name, start, end) = function;
+    let expression = expression.trim().trim_start_matches(['&', '*']);
+    let root = leading_identifier(expression);
+    if depth > MAX_DEPTH {
+        return Target::Unknown(expression.to_string());
+    }
+    if let Some(field) = expression.strip_prefix("self.") {
+        return rust_storage(code, functions, leading_identifier(field));
+    }
+    let text = &code[start..end];
+    let signature = &text[..text.find('{').unwrap_or(text.len())];
+    if let Some(index) = rust_parameters(signature).iter().position(|param| *param == root) {
+        let public = text.starts_with("pub ") || attributes(code, start).contains("#[fallback]");
+        if public {
+            return match is_guarded(text) {
+                true => Target::Trusted,
+                false => Target::User(format!("the `{}` argument of {}", root, name)),
+            };
+        }
+        let targets = functions.iter()
+            .flat_map(|caller| call_arguments(&code[caller.1..caller.2], name).into_iter().map(move |args| (caller, args)))
+            .filter_map(|(caller, args)| args.get(index).map(|arg| {
+                classify_rust(code, functions, (&caller.0, caller.1, caller.2), arg, depth + 1)
+            }))
+            .collect();
+        return combine(targets);
+    }
+    match assigned_value(text, root) {
+        Some(value) => classify_rust(code, functions, function, value, depth + 1),
+        None => Target::Unknown(expression.to_string()),
+    }
+}
+
+/// Trusted unless a public, unguarded method other than the constructor or initializer sets `self.field`
+fn rust_storage(code: &str, functions: &[(String, usize, usize)], field: &str) -> Target {
+    let write = format!("self.{}.set(", field);
+    functions.iter()
+        .filter(|(name, start, _)| !(name == "new" || is_initializer_name(name) || attributes(code, *start).contains("#[constructor]")))
+        .find(|(_, start, end)| {
+            let text = &code[*start..*end];
+            let by_attribute = ["#[access_control", "#[only_owner"].iter().any(|guard| attributes(code, *start).contains(guard));
+            text.contains(&write) && text.starts_with("pub ") && !by_attribute && !is_guarded(text)
+        })
+        .map_or(Target::Trusted, |(setter, _, _)| Target::User(format!("`{}`, which {} lets anyone change", field, setter)))
+}
+
+/// Attribute lines directly above the item starting at `start`
+fn attributes(code: &str, start: usize) -> String {
+    code[..start].lines().rev()
+        .map(str::trim)
+        .take_while(|line| line.starts_with("#[") || line.starts_with("///"))
+        .collect()
+}
+
+/// Parameter names in a Rust signature, without `self`
+fn rust_parameters(signature: &str) -> Vec<&str> {
+    let Some(open) = signature.find('(') else { return Vec::new() };
+    let Some((params, _)) = arguments(signature, open + 1) else { return Vec::new() };
+    params.iter()
+        .filter_map(|param| param.split_once(':'))
+        .map(|(name, _)| name.trim().trim_start_matches("mut ").trim())
+        .collect()
+}
+
+fn parameter_names(params: &[(Loc, Option<solang_parser::pt::Parameter>)]) -> Vec<String> {
+    params.iter()
+        .filter_map(|(_, param)| param.as_ref()?.name.as_ref().map(|name| name.name.clone()))
+        .collect()
+}
+
+/// Top-level arguments of the call whose `(` ends just before `open`, and the offset of its `)`
+fn arguments(text: &str, open: usize) -> Option<(Vec<&str>, usize)> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut from = open;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                let last = text[from..open + i].trim();
+                if !last.is_empty() {
+                    args.push(last);
+                }
+                return Some((args, open + i));
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(text[from..open + i].trim());
+                from = open + i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Arguments of every call to `name` in `body`, as a plain or `self.` call
+fn call_arguments<'b>(body: &'b str, name: &str) -> Vec<Vec<&'b str>> {
+    body.match_indices(&format!("{}(", name))
+        .filter(|(at, _)| {
+            let before = &body[..*at];
+            let plain = !before.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.');
+            (plain || before.ends_with("self.")) && !before.trim_end().ends_with("function") && !before.trim_end().ends_with("fn")
+        })
+        .filter_map(|(at, call)| arguments(body, at + call.len()).map(|(args, _)| args))
+        .collect()
+}
+
+/// The value last written to local `name`: `address impl = ..;`, `let impl = ..;` or Yul `impl := ..`
+fn assigned_value<'b>(body: &'b str, name: &str) -> Option<&'b str> {
+    body.match_indices(name)
+        .filter(|(at, _)| !body[..*at].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.'))
+        .filter_map(|(at, _)| {
+            let rest = body[at + name.len()..].trim_start();
+            let value = match (rest.strip_prefix(":="), rest.strip_prefix(':')) {
+                (Some(value), _) => value,
+                // `let target: Address = ..`
+                (None, Some(typed)) => &typed[typed.find('=')? + 1..],
+                (None, None) => rest.strip_prefix('=').filter(|value| !value.starts_with('='))?,
+            };
+            let value = value.split([';', '\n']).next()?.trim();
+            (!value.is_empty()).then_some(value)
+        })
+        .last()
+}
+
+
+/// `impl` in `address(impl)` or `payable(impl)`
+fn strip_casts(expression: &str) -> &str {
+    ["address(", "payable("].iter()
+        .find_map(|cast| expression.strip_prefix(cast).and_then(|inner| inner.strip_suffix(')')))
+        .map_or(expression, |inner| strip_casts(inner.trim()))
+}
+
+fn leading_identifier(text: &str) -> &str {
+    let end = text.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Operand ending at byte `end`: a name, field access or call such as `address(target)`
+fn operand_before(text: &str, end: usize) -> &str {
+    let bytes = text.as_bytes();
+    let mut start = end;
+    let mut depth = 0;
+    while start > 0 {
+        let c = bytes[start - 1];
+        match c {
+            b')' | b']' => depth += 1,
+            b'(' | b'[' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ if c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.') => {}
+            _ => break,
+        }
+        start -= 1;
+    }
+    &text[start..end]
+}
+
+fn compact(text: &str) -> String {
+    text.split_whitespace().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn delegatecall_to_a_caller_chosen_target_is_flagged() {
+        assert_eq!(found(&DelegatecallRule, include_str!("../../test ex/delegatecall/arbitrary_delegate.sol")).await, expected(&[
+            ("Unsafe Delegatecall", 17),
+            ("Unsafe Delegatecall", 25),
+            ("Unsafe Delegatecall", 35),
+        ]));
+        assert_eq!(found(&DelegatecallRule, include_str!("../../test ex/delegatecall/raw_delegate.rs")).await, expected(&[("Unsafe Delegatecall", 33)]));
+    }
+
+    #[tokio::test]
+    async fn an_owner_gated_upgrade_target_is_not() {
+        assert!(found(&DelegatecallRule, include_str!("../../test ex/delegatecall/uups_proxy.sol")).await.is_empty());
+    }
+}
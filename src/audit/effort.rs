@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use clap::ValueEnum;
+use crate::audit::AuditResult;
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+/// Functions longer than this make any fix inside them more involved
+const LONG_FUNCTION_LINES: usize = 300;
+
+/// Bumps the effort of findings inside very long functions, where even a
+/// small fix needs restructuring and careful review.
+pub fn adjust_for_function_size(result: &mut AuditResult, source: &str) {
+    for bucket in [
+        &mut result.critical_vulnerabilities,
+        &mut result.high_vulnerabilities,
+        &mut result.medium_vulnerabilities,
+        &mut result.low_vulnerabilities,
+        &mut result.info_findings,
+    ] {
+        for vuln in bucket.iter_mut() {
+            let Some(function) = vuln.function.as_deref() else { continue };
+            if function_lines(source, function).is_some_and(|lines| lines > LONG_FUNCTION_LINES) {
+                vuln.effort = Some(vuln.effort.unwrap_or(Effort::Moderate).bump());
+            }
+        }
+    }
+}
+
+/// Number of lines spanned by the first `fn name`/`function name` in `source`
+fn function_lines(source: &str, name: &str) -> Option<usize> {
+    let lines: Vec<&str> = source.lines().collect();
+    let start = lines.iter().position(|line| {
+        let mut words = line.split_whitespace();
+        words.any(|w| w == "fn" || w == "function")
+            && words.next().is_some_and(|w| w.starts_with(name) && !w[name.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+    })?;
+
+    let mut depth = 0i32;
+    let mut opened = false;
+    for (offset, line) in lines[start..].iter().enumerate() {
+        for c in line.chars() {
+            match c {
+                '{' => { depth += 1; opened = true; }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if opened && depth <= 0 {
+            return Some(offset + 1);
+        }
+    }
+    None
+}
+
+fn severity_weight(severity: Severity) -> f64 {
+    match severity {
+        Severity::Critical => 10.0,
+        Severity::High => 6.0,
+        Severity::Medium => 3.0,
+        Severity::Low => 1.0,
+        // Informational notes aren't fixes and stay out of the fix order
+        Severity::Info => 0.0,
+    }
+}
+
+fn effort_cost(effort: Effort) -> f64 {
+    match effort {
+        Effort::Quick => 1.0,
+        Effort::Moderate => 2.0,
+        Effort::Involved => 4.0,
+    }
+}
+
+/// Priority score: severity-weighted impact divided by effort
+pub fn priority(vuln: &Vulnerability) -> f64 {
+    severity_weight(vuln.severity) / effort_cost(vuln.effort.unwrap_or(Effort::Moderate))
+}
+
+/// Findings sorted by descending priority; ties keep severity order
+pub fn fix_order(result: &AuditResult) -> Vec<&Vulnerability> {
+    let mut ordered: Vec<&Vulnerability> = result.vulnerabilities().filter(|vuln| vuln.severity != Severity::Info).collect();
+    // Stable sort, so equal scores stay most-severe-first as `vulnerabilities()` yields them
+    ordered.sort_by(|a, b| priority(b).total_cmp(&priority(a)));
+    ordered
+}
+
+/// One-line justification for a finding's place in the fix order
+pub fn explain(vuln: &Vulnerability) -> String {
+    let effort = vuln.effort.unwrap_or(Effort::Moderate);
+    let why = match (vuln.severity, effort) {
+        (Severity::Critical | Severity::High, Effort::Quick) => "high impact, quick to fix",
+        (Severity::Critical | Severity::High, Effort::Moderate) => "high impact, moderate effort",
+        (Severity::Critical | Severity::High, Effort::Involved) => "high impact but needs significant rework",
+        (_, Effort::Quick) => "cheap win",
+        (_, Effort::Moderate) => "worth scheduling",
+        (_, Effort::Involved) => "low payoff for the effort; defer",
+    };
+    format!("{:?} ({}) / {:?} ({}) = {:.1}: {}",
+        vuln.severity, severity_weight(vuln.severity),
+        effort, effort_cost(effort),
+        priority(vuln), why)
+}
+
+/// `name` in kebab case, e.g. "reentrancy-pattern-checker"
+pub fn rule_slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Parses `--effort RULE=EFFORT` overrides, rejecting unknown rules and levels
+pub fn parse_overrides(specs: &[String], rule_names: &[String]) -> Result<HashMap<String, Effort>> {
+    let mut overrides = HashMap::new();
+    for spec in specs {
+        let (rule, level) = spec.rsplit_once('=').ok_or_else(|| {
+            AnalyzerError::Config(format!("--effort expects RULE=EFFORT, got \"{}\"", spec))
+        })?;
+        // Either the rule's name or its slug, which needs no quoting in a shell
+        let rule = rule.trim();
+        let Some(rule) = rule_names.iter().find(|name| *name == rule || rule_slug(name) == rule) else {
+            return Err(AnalyzerError::Config(format!(
+                "--effort: unknown rule \"{}\" (known rules: {})", rule, rule_names.join(", ")
+            )));
+        };
+        let effort = Effort::from_str(level.trim(), true).map_err(|_| {
+            AnalyzerError::Config(format!("--effort: \"{}\" is not one of quick, moderate, involved", level.trim()))
+        })?;
+        overrides.insert(rule.to_string(), effort);
+    }
+    Ok(overrides)
+}
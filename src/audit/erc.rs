@@ -0,0 +1,483 @@
+//! Conformance of a token contract's external surface to ERC-20, ERC-721 or
+//! ERC-1155: required functions and events, and the events the state-changing
+//! functions must emit
+
+use std::collections::HashSet;
+use std::fmt;
+use quote::ToTokens;
+use solang_parser::pt::{CodeLocation, ContractPart, SourceUnitPart};
+use crate::audit::source::{line_of, rust_fn_line, slice};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::compare::abi::{to_camel_case, AbiSurface};
+use crate::error::Result;
+
+/// Rule name conformance findings are attributed to
+pub const RULE_NAME: &str = "ERC Conformance Checker";
+/// Stable id of the conformance findings, like the audit rules' `id()`
+pub const RULE_ID: &str = "SA-ERC-001";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Standard {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+struct RequiredFunction {
+    name: &'static str,
+    inputs: &'static [&'static str],
+    outputs: &'static [&'static str],
+}
+
+struct RequiredEvent {
+    name: &'static str,
+    params: &'static [&'static str],
+}
+
+/// What a standard requires of the implementing contract
+struct Spec {
+    functions: &'static [RequiredFunction],
+    events: &'static [RequiredEvent],
+    /// (function, event it must emit)
+    emissions: &'static [(&'static str, &'static str)],
+    /// Function only this standard has, used for auto-detection
+    marker: &'static str,
+    /// Functions other contracts can't use the token without; missing ones are High
+    essential: &'static [&'static str],
+}
+
+const fn function(name: &'static str, inputs: &'static [&'static str], outputs: &'static [&'static str]) -> RequiredFunction {
+    RequiredFunction { name, inputs, outputs }
+}
+
+const fn event(name: &'static str, params: &'static [&'static str]) -> RequiredEvent {
+    RequiredEvent { name, params }
+}
+
+const ERC20: Spec = Spec {
+    functions: &[
+        function("totalSupply", &[], &["uint256"]),
+        function("balanceOf", &["address"], &["uint256"]),
+        function("transfer", &["address", "uint256"], &["bool"]),
+        function("transferFrom", &["address", "address", "uint256"], &["bool"]),
+        function("approve", &["address", "uint256"], &["bool"]),
+        function("allowance", &["address", "address"], &["uint256"]),
+    ],
+    events: &[
+        event("Transfer", &["address indexed", "address indexed", "uint256"]),
+        event("Approval", &["address indexed", "address indexed", "uint256"]),
+    ],
+    emissions: &[("transfer", "Transfer"), ("transferFrom", "Transfer"), ("approve", "Approval")],
+    marker: "transfer",
+    essential: &["transferFrom", "allowance"],
+};
+
+const ERC721: Spec = Spec {
+    functions: &[
+        function("balanceOf", &["address"], &["uint256"]),
+        function("ownerOf", &["uint256"], &["address"]),
+        function("safeTransferFrom", &["address", "address", "uint256", "bytes"], &[]),
+        function("safeTransferFrom", &["address", "address", "uint256"], &[]),
+        function("transferFrom", &["address", "address", "uint256"], &[]),
+        function("approve", &["address", "uint256"], &[]),
+        function("setApprovalForAll", &["address", "bool"], &[]),
+        function("getApproved", &["uint256"], &["address"]),
+        function("isApprovedForAll", &["address", "address"], &["bool"]),
+        function("supportsInterface", &["bytes4"], &["bool"]),
+    ],
+    events: &[
+        event("Transfer", &["address indexed", "address indexed", "uint256 indexed"]),
+        event("Approval", &["address indexed", "address indexed", "uint256 indexed"]),
+        event("ApprovalForAll", &["address indexed", "address indexed", "bool"]),
+    ],
+    emissions: &[
+        ("transferFrom", "Transfer"),
+        ("safeTransferFrom", "Transfer"),
+        ("approve", "Approval"),
+        ("setApprovalForAll", "ApprovalForAll"),
+    ],
+    marker: "ownerOf",
+    essential: &[],
+};
+
+const ERC1155: Spec = Spec {
+    functions: &[
+        function("safeTransferFrom", &["address", "address", "uint256", "uint256", "bytes"], &[]),
+        function("safeBatchTransferFrom", &["address", "address", "uint256[]", "uint256[]", "bytes"], &[]),
+        function("balanceOf", &["address", "uint256"], &["uint256"]),
+        function("balanceOfBatch", &["address[]", "uint256[]"], &["uint256[]"]),
+        function("setApprovalForAll", &["address", "bool"], &[]),
+        function("isApprovedForAll", &["address", "address"], &["bool"]),
+        function("supportsInterface", &["bytes4"], &["bool"]),
+    ],
+    events: &[
+        event("TransferSingle", &["address indexed", "address indexed", "address indexed", "uint256", "uint256"]),
+        event("TransferBatch", &["address indexed", "address indexed", "address indexed", "uint256[]", "uint256[]"]),
+        event("ApprovalForAll", &["address indexed", "address indexed", "bool"]),
+        event("URI", &["string", "uint256 indexed"]),
+    ],
+    emissions: &[
+        ("safeTransferFrom", "TransferSingle"),
+        ("safeBatchTransferFrom", "TransferBatch"),
+        ("setApprovalForAll", "ApprovalForAll"),
+    ],
+    marker: "balanceOfBatch",
+    essential: &[],
+};
+
+impl Standard {
+    fn spec(self) -> &'static Spec {
+        match self {
+            Standard::Erc20 => &ERC20,
+            Standard::Erc721 => &ERC721,
+            Standard::Erc1155 => &ERC1155,
+        }
+    }
+
+    pub fn reference(self) -> &'static str {
+        match self {
+            Standard::Erc20 => "https://eips.ethereum.org/EIPS/eip-20",
+            Standard::Erc721 => "https://eips.ethereum.org/EIPS/eip-721",
+            Standard::Erc1155 => "https://eips.ethereum.org/EIPS/eip-1155",
+        }
+    }
+}
+
+/// Every finding name `check` can report, for validating severity overrides
+pub fn finding_names() -> impl Iterator<Item = String> {
+    [Standard::Erc20, Standard::Erc721, Standard::Erc1155].into_iter().flat_map(|standard| {
+        ["Event Not Emitted", "Member Missing", "Signature Mismatch"].map(|finding| format!("{} {}", standard, finding))
+    })
+}
+
+impl fmt::Display for Standard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Standard::Erc20 => "ERC-20",
+            Standard::Erc721 => "ERC-721",
+            Standard::Erc1155 => "ERC-1155",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Member {
+    Function,
+    Event,
+    /// A required function emitting its event
+    Emission,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Present,
+    Missing,
+    /// Declared under the right name with the wrong shape; says what was found
+    Mismatched(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ConformanceEntry {
+    pub kind: Member,
+    /// `transfer(address,uint256) returns (bool)`, `event Transfer(...)` or `transfer emits Transfer`
+    pub member: String,
+    pub status: Status,
+    /// Function the entry is about, for emissions and function mismatches
+    pub function: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// Present, missing and mismatched members of one standard
+#[derive(Debug, Clone)]
+pub struct Conformance {
+    pub standard: Standard,
+    /// True when the standard was inferred from function names rather than `--standard`
+    pub detected: bool,
+    pub entries: Vec<ConformanceEntry>,
+}
+
+impl Conformance {
+    /// One finding per missing or mismatched entry
+    pub fn findings(&self) -> Vec<Vulnerability> {
+        let standard = self.standard;
+        self.entries.iter()
+            .filter_map(|entry| {
+                let (name, risk_description, recommendation) = match (&entry.status, entry.kind) {
+                    (Status::Present, _) => return None,
+                    (Status::Missing, Member::Emission) => (
+                        format!("{} Event Not Emitted", standard),
+                        format!("{}; wallets and indexers that follow {} transfers and approvals through logs never see the change", entry.member.replace(" emits ", " doesn't emit "), standard),
+                        format!("Emit the event wherever the state it describes changes, as {} requires", standard),
+                    ),
+                    (Status::Missing, Member::Function | Member::Event) => (
+                        format!("{} Member Missing", standard),
+                        format!("{} is required by {}; integrations that rely on it revert or can't index the contract", entry.member, standard),
+                        format!("Implement the member with the exact {} signature", standard),
+                    ),
+                    (Status::Mismatched(found), _) => (
+                        format!("{} Signature Mismatch", standard),
+                        format!("{} {}; callers compiled against {} decode the wrong selector, topics or return data", entry.member, found, standard),
+                        format!("Match the {} signature exactly, including return values and indexed parameters", standard),
+                    ),
+                };
+                let essential = standard.spec().essential.iter().any(|name| entry.member.starts_with(&format!("{}(", name)));
+                let severity = match (&entry.status, entry.kind) {
+                    (Status::Missing, Member::Function) if essential => Severity::High,
+                    _ => Severity::Medium,
+                };
+                Some(Vulnerability {
+                    name,
+                    severity,
+                    risk_description,
+                    recommendation,
+                    rule: Some(RULE_NAME.to_string()),
+                    rule_id: Some(RULE_ID.to_string()),
+                    function: entry.function.clone(),
+                    line: entry.line,
+                    reference: Some(standard.reference().to_string()),
+                    effort: Some(Effort::Quick),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Standard whose marker function is declared and at least half of whose required functions are
+pub fn detect(surface: &AbiSurface) -> Option<Standard> {
+    let declared = |name: &str| surface.functions.iter().any(|f| f.name == name);
+    [Standard::Erc1155, Standard::Erc721, Standard::Erc20].into_iter().find(|standard| {
+        let spec = standard.spec();
+        let names: HashSet<&str> = spec.functions.iter().map(|f| f.name).collect();
+        declared(spec.marker) && names.iter().filter(|name| declared(name)).count() * 2 >= names.len()
+    })
+}
+
+/// Checks `source` against `standard`, or against the detected standard when
+/// `None`. Returns `None` when nothing is detected or the source doesn't parse
+/// and no standard was asked for.
+pub fn check(source: &str, standard: Option<Standard>) -> Result<Option<Conformance>> {
+    let surface = match (AbiSurface::from_source(source), standard) {
+        (Ok(surface), _) => surface,
+        (Err(_), None) => return Ok(None),
+        (Err(e), Some(_)) => return Err(e),
+    };
+    let (standard, detected) = match standard {
+        Some(standard) => (standard, false),
+        None => match detect(&surface) {
+            Some(standard) => (standard, true),
+            None => return Ok(None),
+        },
+    };
+    let spec = standard.spec();
+    let bodies = function_bodies(source);
+    let mut entries = Vec::new();
+
+    for required in spec.functions {
+        let signature = format!("{}({})", required.name, required.inputs.join(","));
+        let member = if required.outputs.is_empty() {
+            signature.clone()
+        } else {
+            format!("{} returns ({})", signature, required.outputs.join(","))
+        };
+        let same_name: Vec<_> = surface.functions.iter().filter(|f| f.name == required.name).collect();
+        let status = match same_name.iter().find(|f| f.inputs == required.inputs) {
+            Some(f) if f.outputs == required.outputs => Status::Present,
+            Some(f) => Status::Mismatched(format!("— declared returning ({})", f.outputs.join(","))),
+            None => {
+                // Other overloads the standard also requires aren't a wrong version of this one
+                let stray: Vec<String> = same_name.iter()
+                    .filter(|f| !spec.functions.iter().any(|r| r.name == f.name && f.inputs == r.inputs))
+                    .map(|f| f.signature())
+                    .collect();
+                if stray.is_empty() {
+                    Status::Missing
+                } else {
+                    Status::Mismatched(format!("— declared as {}", stray.join(", ")))
+                }
+            }
+        };
+        let line = bodies.iter().find(|b| b.abi_name == required.name).and_then(|b| b.line);
+        entries.push(ConformanceEntry {
+            kind: Member::Function,
+            member,
+            function: (status != Status::Missing).then(|| required.name.to_string()),
+            line: line.filter(|_| status != Status::Missing),
+            status,
+        });
+    }
+
+    for required in spec.events {
+        let member = format!("event {}({})", required.name, required.params.join(","));
+        let status = match surface.events.iter().find(|e| e.name == required.name) {
+            Some(declared) if declared.params == required.params => Status::Present,
+            Some(declared) => Status::Mismatched(format!("— declared as event {}({})", declared.name, declared.params.join(","))),
+            None => Status::Missing,
+        };
+        entries.push(ConformanceEntry { kind: Member::Event, member, status, function: None, line: None });
+    }
+
+    for (function, event) in spec.emissions {
+        // A missing function is already reported above
+        let implementations: Vec<&FunctionBody> = bodies.iter().filter(|b| b.abi_name == *function).collect();
+        if implementations.is_empty() {
+            continue;
+        }
+        let silent = implementations.iter().find(|body| !emits(&bodies, body, event));
+        entries.push(ConformanceEntry {
+            kind: Member::Emission,
+            member: format!("{} emits {}", function, event),
+            status: if silent.is_some() { Status::Missing } else { Status::Present },
+            function: Some(function.to_string()),
+            line: silent.and_then(|body| body.line),
+        });
+    }
+
+    Ok(Some(Conformance { standard, detected, entries }))
+}
+
+/// A function body, under both its source name and its ABI name
+pub(crate) struct FunctionBody {
+    pub name: String,
+    pub abi_name: String,
+    pub body: String,
+    pub line: Option<usize>,
+}
+
+/// Every function body in the file, including internal helpers an emission may live in
+pub(crate) fn function_bodies(source: &str) -> Vec<FunctionBody> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        return unit.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) => Some(contract),
+                _ => None,
+            })
+            .flat_map(|contract| &contract.parts)
+            .filter_map(|part| match part {
+                ContractPart::FunctionDefinition(func) => {
+                    let name = func.name.as_ref()?.name.clone();
+                    let body = slice(&func.body.as_ref()?.loc(), source).to_string();
+                    Some(FunctionBody { abi_name: name.clone(), name, body, line: Some(line_of(&func.loc, source)) })
+                }
+                _ => None,
+            })
+            .collect();
+    }
+    let Ok(file) = syn::parse_file(source) else { return Vec::new() };
+    file.items.iter()
+        .filter_map(|item| match item {
+            syn::Item::Impl(impl_item) => Some(&impl_item.items),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|item| match item {
+            syn::ImplItem::Fn(method) => {
+                let name = method.sig.ident.to_string();
+                Some(FunctionBody {
+                    abi_name: to_camel_case(&name),
+                    line: rust_fn_line(source, &name),
+                    body: method.block.to_token_stream().to_string(),
+                    name,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `start`, or any function it calls in this file, emits `event`.
+/// Solidity uses `emit Event(...)`; Stylus logs `Event { ... }` through `evm::log`.
+pub(crate) fn emits(bodies: &[FunctionBody], start: &FunctionBody, event: &str) -> bool {
+    reaches(bodies, start, |compact| {
+        let logged = compact.contains("log(") && compact.match_indices(&format!("{}{{", event))
+            .any(|(i, _)| !compact[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_'));
+        compact.contains(&format!("emit{}(", event)) || logged
+    })
+}
+
+/// Whether `start`, or any function it calls in this file, emits or logs any event
+pub(crate) fn emits_any(bodies: &[FunctionBody], start: &FunctionBody) -> bool {
+    reaches(bodies, start, |compact| {
+        let emitted = compact.match_indices("emit").any(|(i, _)| {
+            !compact[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                && compact[i + "emit".len()..].starts_with(|c: char| c.is_ascii_uppercase() || c == '!')
+        });
+        emitted || ["log(", "log!(", "raw_log("].iter().any(|call| compact.contains(call))
+    })
+}
+
+/// Whether the whitespace-free body of `start` or of a function it calls satisfies `found`
+fn reaches(bodies: &[FunctionBody], start: &FunctionBody, found: impl Fn(&str) -> bool) -> bool {
+    let mut queue = vec![start];
+    let mut seen = HashSet::new();
+    while let Some(function) = queue.pop() {
+        let compact: String = function.body.split_whitespace().collect();
+        if found(&compact) {
+            return true;
+        }
+        for name in function.body.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            for (i, callee) in bodies.iter().enumerate() {
+                if callee.name == name && seen.insert(i) {
+                    queue.push(callee);
+                }
+            }
+        }
+    }
+    false
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Members that aren't present, with their status
+    fn gaps(conformance: &Conformance) -> Vec<(&str, &Status)> {
+        conformance.entries.iter()
+            .filter(|entry| entry.status != Status::Present)
+            .map(|entry| (entry.member.as_str(), &entry.status))
+            .collect()
+    }
+
+    #[test]
+    fn incomplete_erc20_is_detected_and_its_gaps_listed() {
+        let conformance = check(include_str!("../../test ex/erc/incomplete_erc20.sol"), None).unwrap().unwrap();
+        assert_eq!((conformance.standard, conformance.detected), (Standard::Erc20, true));
+        assert_eq!(gaps(&conformance), [
+            ("transfer(address,uint256) returns (bool)", &Status::Mismatched("— declared returning ()".to_string())),
+            ("transferFrom(address,address,uint256) returns (bool)", &Status::Missing),
+            ("allowance(address,address) returns (uint256)", &Status::Missing),
+            ("event Approval(address indexed,address indexed,uint256)", &Status::Missing),
+            ("approve emits Approval", &Status::Missing),
+        ]);
+        let findings = conformance.findings();
+        let findings: Vec<(&str, Severity, Option<usize>)> = findings.iter()
+            .map(|vuln| (vuln.name.as_str(), vuln.severity, vuln.line))
+            .collect();
+        assert_eq!(findings, [
+            ("ERC-20 Signature Mismatch", Severity::Medium, Some(18)),
+            ("ERC-20 Member Missing", Severity::High, None),
+            ("ERC-20 Member Missing", Severity::High, None),
+            ("ERC-20 Member Missing", Severity::Medium, None),
+            ("ERC-20 Event Not Emitted", Severity::Medium, Some(22)),
+        ]);
+    }
+
+    #[test]
+    fn stylus_erc721_is_checked_through_its_abi() {
+        let conformance = check(include_str!("../../test ex/erc/stylus_erc721.rs"), None).unwrap().unwrap();
+        assert_eq!((conformance.standard, conformance.detected), (Standard::Erc721, true));
+        assert_eq!(gaps(&conformance), [
+            ("safeTransferFrom(address,address,uint256,bytes)", &Status::Missing),
+            ("safeTransferFrom(address,address,uint256)", &Status::Missing),
+            ("supportsInterface(bytes4) returns (bool)", &Status::Missing),
+            ("setApprovalForAll emits ApprovalForAll", &Status::Missing),
+        ]);
+    }
+
+    #[test]
+    fn an_explicit_standard_overrides_detection() {
+        let conformance = check(include_str!("../../test ex/erc/incomplete_erc20.sol"), Some(Standard::Erc721)).unwrap().unwrap();
+        assert_eq!((conformance.standard, conformance.detected), (Standard::Erc721, false));
+    }
+}
@@ -0,0 +1,205 @@
+//! ERC-20 behaviour of contracts that look like tokens. Unlike the ABI
+//! conformance check in `erc`, this looks at every function the contract
+//! defines, exported or not, and at what transfer and approve actually do:
+//! the events they emit, failures reported as `false`, and the approval race
+
+use async_trait::async_trait;
+use crate::audit::erc::{self, emits, function_bodies, FunctionBody, Standard};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::mask;
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::compare::abi::{to_camel_case, AbiSurface};
+use crate::error::Result;
+use crate::parser::ParsedContract;
+
+/// Required function, whether it must return `bool`, and how bad leaving it out is
+const REQUIRED: [(&str, bool, Severity); 6] = [
+    ("totalSupply", false, Severity::Medium),
+    ("balanceOf", false, Severity::Medium),
+    ("transfer", true, Severity::Medium),
+    ("transferFrom", true, Severity::High),
+    ("approve", true, Severity::Medium),
+    ("allowance", false, Severity::High),
+];
+/// (function, event it must emit)
+const EMISSIONS: [(&str, &str); 3] = [("transfer", "Transfer"), ("transferFrom", "Transfer"), ("approve", "Approval")];
+/// Names that mark a contract as an ERC-20 implementation
+const INTERFACES: [&str; 3] = ["IERC20", "ERC20", "Erc20"];
+/// Checks that make overwriting a non-zero allowance impossible
+const ZERO_CHECKS: [&str; 4] = ["==0", "0==", "is_zero()", "==U256::ZERO"];
+
+/// Missing ERC-20 functions, return values and events, and the approval race, in token-like contracts
+pub struct Erc20ComplianceRule;
+
+#[async_trait]
+impl AuditRule for Erc20ComplianceRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Ok(contract) = ParsedContract::new(content.to_string()) else { return Ok(Vec::new()) };
+        let names: Vec<String> = contract.functions.iter().map(|f| abi_name(&f.name)).collect();
+        if !looks_like_token(content, &names) {
+            return Ok(Vec::new());
+        }
+        // ERC-721 and ERC-1155 share function names with ERC-20, and an
+        // ERC-20 the conformance check detects itself is already reported
+        let detected = AbiSurface::from_source(content).ok().and_then(|surface| erc::detect(&surface));
+        if matches!(detected, Some(Standard::Erc721 | Standard::Erc1155)) {
+            return Ok(Vec::new());
+        }
+        let bodies = function_bodies(content);
+        let mut vulnerabilities = Vec::new();
+        if detected != Some(Standard::Erc20) {
+            vulnerabilities.extend(missing_members(&contract, &bodies));
+            vulnerabilities.extend(missing_events(&bodies));
+        }
+        vulnerabilities.extend(silent_failures(&bodies));
+        if !names.iter().any(|name| name == "increaseAllowance" || name == "decreaseAllowance") {
+            vulnerabilities.extend(approval_races(&bodies));
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "ERC-20 Compliance Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ERC20-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "ERC-20 functions, bool returns, Transfer/Approval events and the approval race in token contracts"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "ERC-20 Member Missing",
+            "ERC-20 Signature Mismatch",
+            "ERC-20 Event Not Emitted",
+            "ERC-20 Silent Failure",
+            "ERC-20 Approval Race",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// Names an ERC-20 interface, or has two of transfer, approve, balanceOf and a `balances` store
+fn looks_like_token(content: &str, names: &[String]) -> bool {
+    let code = mask(content);
+    let words: Vec<&str> = code.split(|c: char| !(c.is_alphanumeric() || c == '_')).collect();
+    if words.iter().any(|word| INTERFACES.contains(word)) {
+        return true;
+    }
+    let signals = ["transfer", "approve", "balanceOf"].iter().filter(|name| names.iter().any(|n| n == *name)).count()
+        + usize::from(words.contains(&"balances"));
+    signals >= 2
+}
+
+fn missing_members(contract: &ParsedContract, bodies: &[FunctionBody]) -> Vec<Vulnerability> {
+    let mut vulnerabilities = Vec::new();
+    for (name, returns_bool, severity) in REQUIRED {
+        let Some(function) = contract.functions.iter().find(|f| abi_name(&f.name) == name) else {
+            vulnerabilities.push(Vulnerability {
+                name: "ERC-20 Member Missing".to_string(),
+                severity,
+                risk_description: format!("`{}` is required by ERC-20; wallets, exchanges and other contracts that call it revert", name),
+                recommendation: format!("Implement `{}` with the exact ERC-20 signature", name),
+                reference: Some(Standard::Erc20.reference().to_string()),
+                ..Default::default()
+            });
+            continue;
+        };
+        let method = function.name.rsplit("::").next().unwrap_or(&function.name);
+        let bool_return = function.return_type.as_deref().is_some_and(|ty| ty.to_lowercase().contains("bool"));
+        if returns_bool && !bool_return {
+            vulnerabilities.push(Vulnerability {
+                name: "ERC-20 Signature Mismatch".to_string(),
+                severity: Severity::Medium,
+                risk_description: format!("{} doesn't return a bool; callers compiled against ERC-20 fail to decode the result", method),
+                recommendation: "Return true on success, as ERC-20 requires".to_string(),
+                function: Some(method.to_string()),
+                line: line_of(bodies, method),
+                reference: Some(Standard::Erc20.reference().to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    vulnerabilities
+}
+
+fn missing_events(bodies: &[FunctionBody]) -> Vec<Vulnerability> {
+    EMISSIONS.iter()
+        .filter_map(|(function, event)| {
+            let silent = bodies.iter().find(|body| body.abi_name == *function && !emits(bodies, body, event))?;
+            Some(Vulnerability {
+                name: "ERC-20 Event Not Emitted".to_string(),
+                severity: Severity::Medium,
+                risk_description: format!("{} doesn't emit {}; wallets and indexers that follow balances and allowances through logs never see the change",
+                    silent.name, event),
+                recommendation: format!("Emit {} wherever the state it describes changes", event),
+                function: Some(silent.name.clone()),
+                line: silent.line,
+                reference: Some(Standard::Erc20.reference().to_string()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// transfer and transferFrom reporting failure by returning `false`
+fn silent_failures(bodies: &[FunctionBody]) -> Vec<Vulnerability> {
+    bodies.iter()
+        .filter(|body| body.abi_name == "transfer" || body.abi_name == "transferFrom")
+        .filter(|body| {
+            let compact: String = body.body.split_whitespace().collect();
+            compact.contains("returnfalse") || compact.contains("Ok(false)")
+        })
+        .map(|body| Vulnerability {
+            name: "ERC-20 Silent Failure".to_string(),
+            severity: Severity::Low,
+            risk_description: format!("{} returns false instead of reverting; callers that don't check the result treat a failed transfer as done",
+                body.name),
+            recommendation: "Revert (return Err in Stylus) when a transfer can't be made, and return true only on success".to_string(),
+            function: Some(body.name.clone()),
+            line: body.line,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// approve overwriting an allowance without requiring the old or new value to be zero
+fn approval_races(bodies: &[FunctionBody]) -> Vec<Vulnerability> {
+    bodies.iter()
+        .filter(|body| body.abi_name == "approve")
+        .filter(|body| {
+            let compact: String = body.body.split_whitespace().collect();
+            !ZERO_CHECKS.iter().any(|check| compact.contains(check))
+        })
+        .map(|body| Vulnerability {
+            name: "ERC-20 Approval Race".to_string(),
+            severity: Severity::Medium,
+            risk_description: format!("{} replaces a non-zero allowance in one step; a spender who sees the change coming can spend the old allowance and then the new one",
+                body.name),
+            recommendation: "Add increase_allowance/decrease_allowance (increaseAllowance/decreaseAllowance in Solidity), \
+                or require the allowance to be zero before setting a new non-zero value".to_string(),
+            function: Some(body.name.clone()),
+            line: body.line,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// ERC-20 name of a parsed function; Stylus methods are named `Type::method`
+fn abi_name(name: &str) -> String {
+    to_camel_case(name.rsplit("::").next().unwrap_or(name))
+}
+
+fn line_of(bodies: &[FunctionBody], name: &str) -> Option<usize> {
+    bodies.iter().find(|body| body.name == name).and_then(|body| body.line)
+}
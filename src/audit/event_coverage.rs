@@ -0,0 +1,311 @@
+//! Event coverage for Stylus contracts: public `&mut self` methods that write
+//! storage without logging, and event skeletons that fix them
+
+use syn::{FnArg, Pat};
+use crate::audit::events;
+use crate::audit::fix::TextEdit;
+use crate::audit::source::{mask, matching_brace, public_mutating_methods};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::compare::abi::rust_to_solidity;
+use crate::upgrade::layout;
+
+pub const FINDING: &str = "Missing Event Emission";
+
+/// Calls that log an event
+const EMISSIONS: [&str; 3] = ["log(", "log!(", "emit!("];
+/// Method calls on a storage field that write it
+const WRITES: [&str; 5] = [".set(", ".insert(", ".setter(", ".push(", ".get_mut("];
+
+/// A public mutating method that emits nothing
+#[derive(Debug)]
+pub struct Unlogged {
+    pub function: String,
+    /// 1-based line of the signature
+    pub line: usize,
+    pub signature: String,
+    /// Byte range of the whole function
+    start: usize,
+    end: usize,
+    /// Arguments with an ABI type, as (name, Solidity type)
+    args: Vec<(String, String)>,
+    /// Storage fields written, with the value passed to `.set(..)` if any
+    writes: Vec<(String, Option<String>)>,
+}
+
+/// Public `&mut self` methods in Stylus `source` that write storage and log nothing
+pub fn unlogged_functions(source: &str) -> Vec<Unlogged> {
+    let Ok(file) = syn::parse_file(source) else { return Vec::new() };
+    let code = mask(source);
+    let mut unlogged = Vec::new();
+
+    for (method, start, end) in public_mutating_methods(&file, source) {
+        let name = method.sig.ident.to_string();
+        let text = &source[start..end];
+        let writes = storage_writes(text);
+        if writes.is_empty() || EMISSIONS.iter().any(|call| code[start..end].contains(call)) {
+            continue;
+        }
+
+        let args = method.sig.inputs.iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(arg) => match arg.pat.as_ref() {
+                    Pat::Ident(ident) => Some((ident.ident.to_string(), rust_to_solidity(&arg.ty))),
+                    _ => None,
+                },
+                FnArg::Receiver(_) => None,
+            })
+            .filter(|(_, ty)| is_abi_type(ty))
+            .collect();
+        unlogged.push(Unlogged {
+            function: name,
+            line: source[..start].matches('\n').count() + 1,
+            signature: text.lines().next().unwrap_or_default().trim().trim_end_matches('{').trim().to_string(),
+            start,
+            end,
+            args,
+            writes,
+        });
+    }
+    unlogged
+}
+
+/// One Low finding per unlogged method
+pub fn findings(source: &str) -> Vec<Vulnerability> {
+    let fields = scalar_fields(source);
+    unlogged_functions(source).iter()
+        .map(|function| {
+            let written: Vec<&str> = function.writes.iter().map(|(field, _)| field.as_str()).collect();
+            Vulnerability {
+                name: FINDING.to_string(),
+                severity: Severity::Low,
+                risk_description: format!("{} writes {} without emitting an event, so indexers and monitors never see the change",
+                    function.function, written.join(", ")),
+                recommendation: format!("Emit an event such as `{}` when {} succeeds",
+                    event_name(&function.function, function, &fields, source), function.function),
+                function: Some(function.function.clone()),
+                line: Some(function.line),
+                snippet: Some(function.signature.clone()),
+                effort: Some(Effort::Quick),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Declares an event for `function` and logs it before the function returns
+pub fn emission_skeleton(source: &str, function: &str) -> Vec<TextEdit> {
+    let Some(unlogged) = unlogged_functions(source).into_iter().find(|u| u.function == function) else { return Vec::new() };
+    let fields = scalar_fields(source);
+    let name = event_name(function, &unlogged, &fields, source);
+
+    // Arguments first, then written fields whose new value isn't one of them
+    let mut params: Vec<(String, String, String)> = unlogged.args.iter()
+        .map(|(arg, ty)| (arg.clone(), ty.clone(), arg.clone()))
+        .collect();
+    for (field, value) in &unlogged.writes {
+        let Some((_, ty)) = fields.iter().find(|(name, _)| name == field) else { continue };
+        if value.as_deref().is_some_and(|value| unlogged.args.iter().any(|(arg, _)| arg == value.trim())) {
+            continue;
+        }
+        let param = if params.iter().any(|(p, _, _)| p == field) { format!("new_{}", field) } else { field.clone() };
+        params.push((param, ty.clone(), format!("self.{}.get()", field)));
+    }
+
+    let mut indexed = 0;
+    let declaration = params.iter()
+        .map(|(param, ty, _)| {
+            // Addresses are what log filters select on
+            let index = ty == "address" && indexed < 3;
+            indexed += index as usize;
+            format!("{}{} {}", ty, if index { " indexed" } else { "" }, param)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let declaration = format!("event {}({});", name, declaration);
+    let fields_init = params.iter()
+        .map(|(param, _, value)| if param == value { param.clone() } else { format!("{}: {}", param, value) })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let emission = if fields_init.is_empty() {
+        format!("stylus_sdk::evm::log({} {{}});", name)
+    } else {
+        format!("stylus_sdk::evm::log({} {{ {} }});", name, fields_init)
+    };
+
+    match (declare(source, &declaration), emit(source, &unlogged, &emission)) {
+        (Some(declare), Some(emit)) => vec![declare, emit],
+        _ => Vec::new(),
+    }
+}
+
+/// Fields written through the storage API or by assignment, in order
+pub(crate) fn storage_writes(text: &str) -> Vec<(String, Option<String>)> {
+    let mut writes: Vec<(String, Option<String>)> = Vec::new();
+    for (at, _) in text.match_indices("self.") {
+        let rest = &text[at + 5..];
+        let field: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        let after = &rest[field.len()..];
+        let value = if let Some(args) = after.strip_prefix(".set(") {
+            Some(call_args(args).map(str::to_string))
+        } else if WRITES.iter().any(|write| after.starts_with(write)) {
+            Some(None)
+        } else {
+            let op = after.trim_start();
+            ((op.starts_with('=') && !op.starts_with("==")) || ["+=", "-=", "*=", "/="].iter().any(|o| op.starts_with(o))).then_some(None)
+        };
+        if let Some(value) = value {
+            // `self.0` belongs to a wrapper type, not contract storage
+            let named = field.starts_with(|c: char| c.is_alphabetic() || c == '_');
+            if named && !writes.iter().any(|(f, _)| *f == field) {
+                writes.push((field, value));
+            }
+        }
+    }
+    writes
+}
+
+/// Text up to the `)` closing a call whose `(` was just consumed
+fn call_args(text: &str) -> Option<&str> {
+    let mut depth = 1;
+    text.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(&text[..i])
+    })
+}
+
+fn is_abi_type(ty: &str) -> bool {
+    ["uint", "int", "address", "bool", "bytes", "string"].iter().any(|prefix| ty.starts_with(prefix))
+}
+
+/// Top-level value-type storage fields with their Solidity types
+fn scalar_fields(source: &str) -> Vec<(String, String)> {
+    let Ok(layout) = layout::extract_layout(source) else { return Vec::new() };
+    layout.entries.into_iter()
+        .filter(|entry| !entry.name.contains(['.', '[']) && is_abi_type(&entry.ty) && entry.ty != "string" && entry.ty != "bytes")
+        .map(|entry| (entry.name, entry.ty))
+        .collect()
+}
+
+/// `transfer_ownership` → `OwnershipTransferred`, `increment` writing `value`
+/// → `ValueIncremented`; numbered if the name is already taken
+fn event_name(function: &str, unlogged: &Unlogged, fields: &[(String, String)], source: &str) -> String {
+    let words: Vec<&str> = function.split('_').filter(|w| !w.is_empty()).collect();
+    let Some((verb, object)) = words.split_first() else { return "StateChanged".to_string() };
+    let subject = if object.is_empty() {
+        // `stake` writing `total_staked` is just `Staked`
+        unlogged.writes.iter()
+            .find(|(field, _)| fields.iter().any(|(name, _)| name == field))
+            .filter(|(field, _)| !field.contains(verb.trim_end_matches('e')))
+            .map(|(field, _)| pascal(field))
+            .unwrap_or_default()
+    } else {
+        object.iter().map(|w| pascal(w)).collect()
+    };
+    let base = format!("{}{}", subject, pascal(&past_tense(verb)));
+
+    let taken = |name: &str| events::event_indexing(source).iter().any(|event| event.name == name)
+        || source.match_indices(name).any(|(i, _)| {
+            !source[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                && !source[i + name.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        });
+    if !taken(&base) {
+        return base;
+    }
+    (2..).map(|n| format!("{}{}", base, n)).find(|name| !taken(name)).unwrap_or(base)
+}
+
+fn past_tense(verb: &str) -> String {
+    match verb {
+        "set" | "reset" | "put" => verb.to_string(),
+        "send" => "sent".to_string(),
+        "pay" => "paid".to_string(),
+        "withdraw" => "withdrawn".to_string(),
+        "freeze" => "frozen".to_string(),
+        "transfer" | "submit" | "commit" | "stop" | "drop" => format!("{}{}ed", verb, &verb[verb.len() - 1..]),
+        _ if verb.ends_with('e') => format!("{}d", verb),
+        _ if verb.ends_with('y') && !verb.chars().rev().nth(1).is_some_and(|c| "aeiou".contains(c)) => {
+            format!("{}ied", &verb[..verb.len() - 1])
+        }
+        _ => format!("{}ed", verb),
+    }
+}
+
+fn pascal(word: &str) -> String {
+    word.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Adds the declaration to the file's `sol!` block, or a new block after the imports
+fn declare(source: &str, declaration: &str) -> Option<TextEdit> {
+    let block = source.match_indices("sol!").map(|(i, _)| i)
+        .find(|&i| !source[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_'));
+    if let Some(at) = block {
+        let open = at + source[at..].find('{')?;
+        let close = matching_brace(source, open)?;
+        let inner = &source[open + 1..close];
+        let indent = inner.lines()
+            .find(|line| !line.trim().is_empty())
+            .map_or("    ", |line| &line[..line.len() - line.trim_start().len()]);
+        let line_start = source[..close].rfind('\n').map_or(0, |i| i + 1);
+        // `sol! { ... }` on one line: open it up
+        if !source[line_start..close].trim().is_empty() {
+            return Some(TextEdit { start: close, end: close, replacement: format!("\n{}{}\n", indent, declaration) });
+        }
+        return Some(TextEdit { start: line_start, end: line_start, replacement: format!("{}{}\n", indent, declaration) });
+    }
+
+    // After the last top-level `use`; the macro path needs no import
+    let after_uses = source.lines()
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len() + 1;
+            Some((start, line))
+        })
+        .filter(|(_, line)| line.starts_with("use ") || line.starts_with("pub use "))
+        .last()
+        .map(|(start, line)| (start + line.len() + 1).min(source.len()));
+    let block = format!("alloy_sol_types::sol! {{\n    {}\n}}\n", declaration);
+    Some(match after_uses {
+        Some(at) => TextEdit { start: at, end: at, replacement: format!("\n{}", block) },
+        None => TextEdit { start: 0, end: 0, replacement: format!("{}\n", block) },
+    })
+}
+
+/// Inserts `emission` before the function's tail expression or closing brace
+fn emit(source: &str, unlogged: &Unlogged, emission: &str) -> Option<TextEdit> {
+    let close = unlogged.end - 1;
+    let open = unlogged.start + source[unlogged.start..close].find('{')?;
+    let close_line = source[..close].rfind('\n').map_or(0, |i| i + 1);
+    // One-line bodies have no line to insert before
+    if close_line <= open || !source[close_line..close].trim().is_empty() {
+        return None;
+    }
+
+    let mut at = close_line;
+    let mut indent = format!("{}    ", &source[close_line..close]);
+    let last = source[open + 1..close_line].lines()
+        .scan(open + 1, |offset, line| {
+            let start = *offset;
+            *offset += line.len() + 1;
+            Some((start, line))
+        })
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with("//"))
+        .last();
+    if let Some((start, line)) = last {
+        let code = line.trim();
+        indent = line[..line.len() - line.trim_start().len()].to_string();
+        // A tail expression or explicit return is the function's result
+        if code.starts_with("return") || (!code.ends_with(';') && !code.ends_with('}')) {
+            at = start;
+        }
+    }
+    Some(TextEdit { start: at, end: at, replacement: format!("{}{}\n", indent, emission) })
+}
@@ -0,0 +1,177 @@
+//! Events as off-chain consumers see them: declarations whose indexing is
+//! invalid or unusable, and public functions that move value or hand over
+//! control without emitting anything
+
+use async_trait::async_trait;
+use solang_parser::pt::{ContractPart, FunctionAttribute, FunctionTy, Mutability, SourceUnitPart};
+use crate::audit::erc::{emits_any, function_bodies};
+use crate::audit::event_coverage::{self, storage_writes};
+use crate::audit::events;
+use crate::audit::fix::TextEdit;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{assigns, is_externally_callable, mask, public_mutating_methods, state_variables};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+pub const FINDING: &str = "Missing Critical Event";
+
+/// Storage names that hold balances or other value
+const VALUE_STATE: [&str; 8] = ["balance", "stake", "reward", "supply", "allowance", "deposit", "share", "debt"];
+/// Storage names that hold control of the contract
+const CONTROL_STATE: [&str; 5] = ["owner", "admin", "role", "governor", "guardian"];
+/// Calls that move ether or tokens
+const VALUE_CALLS: [&str; 9] = [
+    ".transfer(", ".send(", ".call{value", "_mint(", "_burn(", "_transfer(", "safeTransfer", "transfer_eth(", ".value(",
+];
+/// Calls that hand over ownership or roles
+const CONTROL_CALLS: [&str; 4] = ["_transferOwnership(", "_grantRole(", "_revokeRole(", "_changeAdmin("];
+
+/// What a silent function does that an event should have announced
+enum Change {
+    Value(String),
+    Control(String),
+}
+
+/// Invalid or unusable event indexing, and value or ownership changes that emit no event
+pub struct EventEmissionRule;
+
+#[async_trait]
+impl AuditRule for EventEmissionRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let mut vulnerabilities = events::indexing_findings(&events::event_indexing(content));
+        let bodies = function_bodies(content);
+        for (function, line, change) in critical_functions(content) {
+            let Some(body) = bodies.iter().find(|body| body.name == function) else { continue };
+            if emits_any(&bodies, body) {
+                continue;
+            }
+            let (what, consequence) = match change {
+                Change::Value(what) => (what, "balances change with no log, so indexers, wallets and accounting tools drift from the chain"),
+                Change::Control(what) => (what, "control of the contract changes hands with no log, so monitoring never alerts on a takeover"),
+            };
+            vulnerabilities.push(Vulnerability {
+                name: FINDING.to_string(),
+                severity: Severity::Medium,
+                risk_description: format!("{} {} without emitting an event; {}", function, what, consequence),
+                recommendation: "Emit an event carrying the accounts and amounts involved (Solidity `emit`, Stylus `evm::log`)".to_string(),
+                function: Some(function),
+                line: Some(line),
+                ..Default::default()
+            });
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Event Emission Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-EVENT-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Event indexing that is invalid or unusable, and value or ownership changes that emit nothing"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[FINDING, "Unindexed Address Event", "Too Many Indexed Parameters", "Indexed Dynamic Type"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+
+    fn suggest_fix(&self, finding: &Vulnerability, source: &str) -> Vec<TextEdit> {
+        match (finding.name.as_str(), &finding.function, finding.line) {
+            ("Unindexed Address Event", _, Some(line)) => events::index_addresses(source, line).into_iter().collect(),
+            (FINDING, Some(function), _) => event_coverage::emission_skeleton(source, function),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Public state-changing functions that move value or change who controls the contract, with their line
+fn critical_functions(content: &str) -> Vec<(String, usize, Change)> {
+    let code = mask(content);
+    let line = |at: usize| content[..at].matches('\n').count() + 1;
+    let mut functions = Vec::new();
+    if let Ok((unit, _)) = solang_parser::parse(content, 0) {
+        for part in &unit.0 {
+            let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+            let state: Vec<String> = state_variables(contract).into_iter().map(|(name, _)| name).collect();
+            for part in &contract.parts {
+                let ContractPart::FunctionDefinition(func) = part else { continue };
+                let (Some(name), Some(body)) = (&func.name, &func.body) else { continue };
+                let read_only = func.attributes.iter()
+                    .any(|attr| matches!(attr, FunctionAttribute::Mutability(Mutability::View(_) | Mutability::Pure(_))));
+                if func.ty != FunctionTy::Function || !is_externally_callable(func) || read_only {
+                    continue;
+                }
+                let text = crate::audit::source::slice(&solang_parser::pt::CodeLocation::loc(body), &code);
+                let written: Vec<&str> = state.iter().map(String::as_str).filter(|var| assigns(text, var)).collect();
+                if let Some(change) = classify(text, &written) {
+                    functions.push((name.name.clone(), line(func.loc.start()), change));
+                }
+            }
+        }
+        return functions;
+    }
+    let Ok(file) = syn::parse_file(content) else { return functions };
+    for (method, start, end) in public_mutating_methods(&file, content) {
+        // Deployment sets the first owner and balances; there is no earlier state to announce a change from
+        if method.attrs.iter().any(|attr| attr.path().is_ident("constructor")) {
+            continue;
+        }
+        let text = &code[start..end];
+        let writes = storage_writes(text);
+        let written: Vec<&str> = writes.iter().map(|(field, _)| field.as_str()).collect();
+        if let Some(change) = classify(text, &written) {
+            functions.push((method.sig.ident.to_string(), line(start), change));
+        }
+    }
+    functions
+}
+
+/// A control change outranks a value move when a function does both
+fn classify(text: &str, written: &[&str]) -> Option<Change> {
+    let compact: String = text.split_whitespace().collect();
+    let named = |words: &[&str]| written.iter().find(|var| {
+        let lower = var.to_lowercase();
+        words.iter().any(|word| lower.contains(word))
+    }).copied();
+    if let Some(var) = named(&CONTROL_STATE) {
+        return Some(Change::Control(format!("writes `{}`", var)));
+    }
+    if let Some(call) = CONTROL_CALLS.iter().find(|call| compact.contains(*call)) {
+        return Some(Change::Control(format!("calls `{})`", call)));
+    }
+    if let Some(var) = named(&VALUE_STATE) {
+        return Some(Change::Value(format!("writes `{}`", var)));
+    }
+    VALUE_CALLS.iter()
+        .find(|call| compact.contains(*call))
+        .map(|call| Change::Value(format!("moves value through `{}`", call.trim_start_matches('.').trim_end_matches('('))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn privileged_changes_without_an_event_are_flagged() {
+        assert_eq!(found(&EventEmissionRule, include_str!("../../test ex/events/logged_vault.rs")).await, expected(&[
+            ("Missing Critical Event", 25),
+            ("Missing Critical Event", 31),
+        ]));
+        assert_eq!(found(&EventEmissionRule, include_str!("../../test ex/events/silent_owner.sol")).await, expected(&[
+            ("Missing Critical Event", 25),
+            ("Missing Critical Event", 30),
+        ]));
+    }
+}
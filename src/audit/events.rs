@@ -0,0 +1,244 @@
+//! Per-event indexing: how many topics each event declares and whether the
+//! indexed parameters are ones a log filter can actually use
+
+use solang_parser::pt::{ContractPart, EventDefinition, SourceUnitPart};
+use crate::audit::fix::TextEdit;
+use crate::audit::source::line_of;
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::compare::abi::solidity_type;
+
+/// Topics left for indexed parameters after the event signature
+const MAX_INDEXED: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct EventParam {
+    pub ty: String,
+    pub name: Option<String>,
+    pub indexed: bool,
+}
+
+impl EventParam {
+    /// Indexed values of these types are stored as their keccak256 hash
+    pub fn is_dynamic(&self) -> bool {
+        self.ty == "string" || self.ty == "bytes" || self.ty.ends_with(']')
+    }
+
+    fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", self.ty, name),
+            None => self.ty.clone(),
+        }
+    }
+}
+
+/// An event declaration and its indexed parameters
+#[derive(Debug, Clone)]
+pub struct EventIndexing {
+    pub name: String,
+    /// 1-based line of the declaration, 0 when unknown
+    pub line: usize,
+    pub anonymous: bool,
+    pub params: Vec<EventParam>,
+}
+
+impl EventIndexing {
+    pub fn indexed(&self) -> usize {
+        self.params.iter().filter(|p| p.indexed).count()
+    }
+
+    /// Anonymous events don't spend a topic on the signature
+    pub fn max_indexed(&self) -> usize {
+        MAX_INDEXED + usize::from(self.anonymous)
+    }
+
+    /// Zero-indexed events that carry an address, which is what logs are usually filtered by
+    pub fn unfilterable(&self) -> bool {
+        self.indexed() == 0 && self.params.iter().any(|p| p.ty == "address")
+    }
+
+    pub fn too_many_indexed(&self) -> bool {
+        self.indexed() > self.max_indexed()
+    }
+
+    pub fn indexed_dynamic(&self) -> impl Iterator<Item = &EventParam> {
+        self.params.iter().filter(|p| p.indexed && p.is_dynamic())
+    }
+
+    fn signature(&self) -> String {
+        format!("{}({})", self.name, self.params.iter().map(|p| p.ty.as_str()).collect::<Vec<_>>().join(","))
+    }
+}
+
+/// Events declared in Solidity `source`, or in the `sol!` blocks of a Stylus contract
+pub fn event_indexing(source: &str) -> Vec<EventIndexing> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        return solidity_events(&unit.0)
+            .into_iter()
+            .map(|event| event_from_definition(event, line_of(&event.loc, source)))
+            .collect();
+    }
+    let Ok(file) = syn::parse_file(source) else { return Vec::new() };
+    file.items.iter()
+        .filter_map(|item| match item {
+            syn::Item::Macro(mac) if mac.mac.path.segments.last().is_some_and(|s| s.ident == "sol") => Some(&mac.mac.tokens),
+            _ => None,
+        })
+        .flat_map(|tokens| {
+            // Token positions are lost once the macro body is re-parsed, so find the declaration by name
+            let wrapped = format!("contract __Events {{ {} }}", tokens);
+            let Ok((unit, _)) = solang_parser::parse(&wrapped, 0) else { return Vec::new() };
+            solidity_events(&unit.0).into_iter()
+                .map(|event| {
+                    let name = event.name.as_ref().map_or("", |n| n.name.as_str());
+                    event_from_definition(event, declaration_line(source, name))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn solidity_events(parts: &[SourceUnitPart]) -> Vec<&EventDefinition> {
+    let mut events = Vec::new();
+    for part in parts {
+        match part {
+            SourceUnitPart::EventDefinition(event) => events.push(event.as_ref()),
+            SourceUnitPart::ContractDefinition(contract) => {
+                events.extend(contract.parts.iter().filter_map(|part| match part {
+                    ContractPart::EventDefinition(event) => Some(event.as_ref()),
+                    _ => None,
+                }));
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn event_from_definition(event: &EventDefinition, line: usize) -> EventIndexing {
+    EventIndexing {
+        name: event.name.as_ref().map(|n| n.name.clone()).unwrap_or_default(),
+        line,
+        anonymous: event.anonymous,
+        params: event.fields.iter()
+            .map(|field| EventParam {
+                ty: solidity_type(&field.ty),
+                name: field.name.as_ref().map(|n| n.name.clone()),
+                indexed: field.indexed,
+            })
+            .collect(),
+    }
+}
+
+fn declaration_line(source: &str, name: &str) -> usize {
+    let needle = format!("event {}", name);
+    source.lines()
+        .position(|line| line.trim_start().starts_with(&needle))
+        .map_or(0, |i| i + 1)
+}
+
+/// Marks the address parameters of the event declared on `line` as `indexed`,
+/// up to the topics left free by the parameters already indexed
+pub fn index_addresses(source: &str, line: usize) -> Option<TextEdit> {
+    let line_start = source.split_inclusive('\n').take(line.checked_sub(1)?).map(str::len).sum::<usize>();
+    let open = line_start + source[line_start..].find("event ")?;
+    let open = open + source[open..].find('(')?;
+    let mut depth = 0;
+    let close = source[open..].char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(open + i)
+    })?;
+
+    let params: Vec<&str> = source[open + 1..close].split(',').collect();
+    let anonymous = source[close..].trim_start().starts_with("anonymous");
+    let indexed = params.iter().filter(|p| p.contains(" indexed ")).count();
+    let mut free = (MAX_INDEXED + usize::from(anonymous)).saturating_sub(indexed);
+    let mut changed = false;
+    let rewritten: Vec<String> = params.iter()
+        .map(|param| {
+            let trimmed = param.trim_start();
+            match trimmed.strip_prefix("address ") {
+                Some(rest) if free > 0 && !param.contains(" indexed ") => {
+                    free -= 1;
+                    changed = true;
+                    let indent = &param[..param.len() - trimmed.len()];
+                    let (ty, name) = match rest.trim_start().strip_prefix("payable ") {
+                        Some(name) => ("address payable", name),
+                        None => ("address", rest.trim_start()),
+                    };
+                    format!("{}{} indexed {}", indent, ty, name)
+                }
+                _ => param.to_string(),
+            }
+        })
+        .collect();
+    changed.then(|| TextEdit { start: open + 1, end: close, replacement: rewritten.join(",") })
+}
+
+/// Findings for events that can't be filtered, don't compile, or index values that can't be read back
+pub fn indexing_findings(events: &[EventIndexing]) -> Vec<Vulnerability> {
+    let mut vulnerabilities = Vec::new();
+    for event in events {
+        let line = (event.line > 0).then_some(event.line);
+        if event.unfilterable() {
+            vulnerabilities.push(Vulnerability {
+                name: "Unindexed Address Event".to_string(),
+                severity: Severity::Low,
+                risk_description: format!("event {} indexes none of its parameters; indexers and wallets can't filter its logs by address and have to scan every one",
+                    event.signature()),
+                recommendation: "Mark the address parameters `indexed` (at most three per event)".to_string(),
+                line,
+                effort: Some(Effort::Quick),
+                ..Default::default()
+            });
+        }
+        if event.too_many_indexed() {
+            vulnerabilities.push(Vulnerability {
+                name: "Too Many Indexed Parameters".to_string(),
+                severity: Severity::Low,
+                risk_description: format!("event {} indexes {} parameters; a log has room for {} indexed topics{}, so the declaration doesn't compile",
+                    event.signature(), event.indexed(), event.max_indexed(),
+                    if event.anonymous { "" } else { " after the signature" }),
+                recommendation: "Index only the parameters logs are filtered by".to_string(),
+                line,
+                effort: Some(Effort::Quick),
+                ..Default::default()
+            });
+        }
+        for param in event.indexed_dynamic() {
+            vulnerabilities.push(Vulnerability {
+                name: "Indexed Dynamic Type".to_string(),
+                severity: Severity::Low,
+                risk_description: format!("event {} indexes `{}`; the topic holds only its keccak256 hash, so the value can't be read back from the log",
+                    event.signature(), param.describe()),
+                recommendation: "Leave the parameter unindexed, or emit an indexed hash next to the unindexed value".to_string(),
+                line,
+                effort: Some(Effort::Quick),
+                ..Default::default()
+            });
+        }
+    }
+    vulnerabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_indexing_problem_is_reported_on_its_declaration() {
+        let events = event_indexing(include_str!("../../test ex/events/indexing.sol"));
+        let declared: Vec<(&str, usize, usize)> = events.iter().map(|event| (event.name.as_str(), event.line, event.indexed())).collect();
+        assert_eq!(declared, [("Transfer", 7, 2), ("Deposit", 8, 0), ("Settled", 9, 4), ("Registered", 10, 2)]);
+
+        let findings: Vec<(String, Option<usize>)> = indexing_findings(&events).into_iter().map(|vuln| (vuln.name, vuln.line)).collect();
+        assert_eq!(findings, [
+            ("Unindexed Address Event".to_string(), Some(8)),
+            ("Too Many Indexed Parameters".to_string(), Some(9)),
+            ("Indexed Dynamic Type".to_string(), Some(10)),
+        ]);
+    }
+}
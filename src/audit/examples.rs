@@ -0,0 +1,227 @@
+//! Before/after code for findings whose fix has a canonical shape. Each
+//! builder adapts the example to the matched code when that is a simple text
+//! transformation and falls back to a generic illustration otherwise
+
+use solang_parser::pt::SourceUnitPart;
+use crate::audit::source::state_variables;
+use crate::audit::vulnerabilities::FixExample;
+use crate::compare::abi::solidity_type;
+
+/// Lines that hand control to another contract
+const EXTERNAL_CALLS: [&str; 6] = [".call{", ".call(", ".transfer(", ".send(", "transfer_eth(", "call(Call::"];
+/// Lines that write state, in Solidity or through the Stylus storage API
+const STATE_WRITES: [&str; 6] = [" = ", " -= ", " += ", ".set(", ".insert(", ".delete("];
+
+/// How many lines after an external call a state write may follow
+const CALL_WINDOW: usize = 6;
+
+/// Checks-effects-interactions: move the state write above the external call
+pub fn reentrancy(source: &str) -> FixExample {
+    let lines: Vec<&str> = source.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        if is_comment(line) || !EXTERNAL_CALLS.iter().any(|call| line.contains(call)) {
+            continue;
+        }
+        let window = lines.iter().enumerate().skip(i + 1).take(CALL_WINDOW);
+        for (j, later) in window {
+            // Leaving the block means the write belongs to other code
+            if later.trim_start().starts_with('}') {
+                break;
+            }
+            if is_state_write(later) {
+                let before = dedent(&lines[i..=j]);
+                let mut moved = vec![lines[j]];
+                moved.extend(&lines[i..j]);
+                return FixExample { before, after: dedent(&moved) };
+            }
+        }
+    }
+
+    if is_rust(source) {
+        FixExample {
+            before: "transfer_eth(to, amount)?;\nself.balances.setter(to).set(U256::ZERO);".to_string(),
+            after: "self.balances.setter(to).set(U256::ZERO);\ntransfer_eth(to, amount)?;".to_string(),
+        }
+    } else {
+        FixExample {
+            before: "(bool ok, ) = msg.sender.call{value: amount}(\"\");\nrequire(ok);\nbalances[msg.sender] = 0;".to_string(),
+            after: "balances[msg.sender] = 0;\n(bool ok, ) = msg.sender.call{value: amount}(\"\");\nrequire(ok);".to_string(),
+        }
+    }
+}
+
+/// Overflow-checked arithmetic: SafeMath for Solidity before 0.8, `checked_*`
+/// for Rust. `code` is the function body or file the finding covers
+pub fn checked_arithmetic(code: &str, rust: bool) -> FixExample {
+    let checked = |lhs: &str, op: &str, rhs: &str| {
+        let method = match op {
+            "+=" => "add",
+            "-=" => "sub",
+            _ => "mul",
+        };
+        if rust {
+            format!("{} = {}.checked_{}({}).ok_or(Error::Overflow)?;", lhs, lhs, method, rhs)
+        } else {
+            format!("{} = {}.{}({});", lhs, lhs, method, rhs)
+        }
+    };
+
+    let adapted = code.lines()
+        .filter(|line| !is_comment(line))
+        .find_map(compound_assignment);
+    let (before, after) = match adapted {
+        Some((lhs, op, rhs)) => (format!("{} {} {};", lhs, op, rhs), checked(lhs, op, rhs)),
+        None => ("total += amount;".to_string(), checked("total", "+=", "amount")),
+    };
+    if rust {
+        FixExample { before, after }
+    } else {
+        FixExample {
+            before,
+            after: format!("using SafeMath for uint256;\n\n{}", after),
+        }
+    }
+}
+
+/// A storage read hoisted out of a loop. `code` is the loop statement as matched
+pub fn cached_storage_read(source: &str, code: &str, variable: &str) -> FixExample {
+    let ty = state_variable_type(source, variable).unwrap_or_else(|| "uint256".to_string());
+    let cached = format!("cached{}", capitalize(variable));
+
+    // Keep the loop header, the lines that read the variable and the closing brace
+    let lines: Vec<&str> = code.lines().collect();
+    let mut kept = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let keep = i == 0 || i + 1 == lines.len() || replace_word(line, variable, "").is_some();
+        if keep {
+            kept.push(line.to_string());
+        } else if kept.last().is_none_or(|last: &String| !last.trim().starts_with("//")) {
+            kept.push(format!("{}// ...", indent_of(line)));
+        }
+    }
+    let before = dedent(&kept.iter().map(String::as_str).collect::<Vec<_>>());
+    let after_loop: Vec<String> = kept.iter()
+        .map(|line| replace_word(line, variable, &cached).unwrap_or_else(|| line.clone()))
+        .collect();
+    let after = format!("{} {} = {};\n{}", ty, cached, variable,
+        dedent(&after_loop.iter().map(String::as_str).collect::<Vec<_>>()));
+    FixExample { before, after }
+}
+
+fn is_rust(source: &str) -> bool {
+    syn::parse_file(source).is_ok()
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("//") || line.starts_with('*') || line.starts_with("/*")
+}
+
+fn is_state_write(line: &str) -> bool {
+    let code = line.trim();
+    // Declarations of locals and comparisons aren't writes
+    let declares = code.starts_with("let ") || code.starts_with('(')
+        || code.split_whitespace().nth(1).is_some_and(|second| ["memory", "storage"].contains(&second))
+        || ["uint", "int", "bool", "address", "bytes", "string"].iter().any(|ty| code.starts_with(ty));
+    !is_comment(line) && !declares && STATE_WRITES.iter().any(|write| code.contains(write))
+}
+
+/// `lhs op= rhs;` on one line
+fn compound_assignment(line: &str) -> Option<(&str, &str, &str)> {
+    let code = line.trim().strip_suffix(';')?;
+    ["+=", "-=", "*="].iter().find_map(|op| {
+        let (lhs, rhs) = code.split_once(&format!(" {} ", op))?;
+        let simple = |side: &str| !side.is_empty() && !side.contains(['=', '{', '}', '(', ';']);
+        (simple(lhs) && simple(rhs)).then_some((lhs.trim(), *op, rhs.trim()))
+    })
+}
+
+/// Type of the state variable `name` in any contract of `source`
+fn state_variable_type(source: &str, name: &str) -> Option<String> {
+    let (unit, _) = solang_parser::parse(source, 0).ok()?;
+    unit.0.iter().find_map(|part| match part {
+        SourceUnitPart::ContractDefinition(contract) => state_variables(contract).into_iter()
+            .find(|(var, _)| var == name)
+            .map(|(_, var)| solidity_type(&var.ty)),
+        _ => None,
+    })
+}
+
+/// `line` with whole-word `name` replaced, or None if it doesn't occur
+fn replace_word(line: &str, name: &str, with: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut last = 0;
+    for (i, _) in line.match_indices(name) {
+        let before_ok = !line[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.');
+        let after_ok = !line[i + name.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        if before_ok && after_ok {
+            out.push_str(&line[last..i]);
+            out.push_str(with);
+            last = i + name.len();
+        }
+    }
+    (last > 0).then(|| out + &line[last..])
+}
+
+fn indent_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Lines joined with their shared indentation removed. A statement sliced
+/// from the source starts mid-line, so its first line doesn't count
+fn dedent(lines: &[&str]) -> String {
+    let first_indent = lines.first().map_or(0, |line| indent_of(line).len());
+    let rest = lines.iter().skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent_of(line).len())
+        .min()
+        .unwrap_or(first_indent);
+    let indent = if first_indent > 0 { rest.min(first_indent) } else { rest };
+    lines.iter()
+        .map(|line| if indent_of(line).len() >= indent { &line[indent..] } else { line.trim_start() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAULT: &str = include_str!("../../test ex/examples/vault.sol");
+
+    #[test]
+    fn reentrancy_moves_the_state_write_above_the_call() {
+        let example = reentrancy(VAULT);
+        assert_eq!(example.before, "(bool ok, ) = msg.sender.call{value: amount}(\"\");\nrequire(ok, \"send failed\");\nbalances[msg.sender] = 0;");
+        assert_eq!(example.after, "balances[msg.sender] = 0;\n(bool ok, ) = msg.sender.call{value: amount}(\"\");\nrequire(ok, \"send failed\");");
+    }
+
+    #[test]
+    fn reentrancy_without_a_match_falls_back_by_language() {
+        assert!(reentrancy("fn f() {}").before.starts_with("transfer_eth("));
+        assert!(reentrancy("contract C {}").before.starts_with("(bool ok, )"));
+    }
+
+    #[test]
+    fn arithmetic_adapts_the_first_compound_assignment() {
+        let example = checked_arithmetic(VAULT, false);
+        assert_eq!(example.before, "balances[msg.sender] += msg.value;");
+        assert_eq!(example.after, "using SafeMath for uint256;\n\nbalances[msg.sender] = balances[msg.sender].add(msg.value);");
+
+        let rust = checked_arithmetic("total -= fee;", true);
+        assert_eq!(rust.after, "total = total.checked_sub(fee).ok_or(Error::Overflow)?;");
+    }
+
+    #[test]
+    fn storage_reads_are_cached_with_their_declared_type() {
+        let code = VAULT.lines().skip(20).take(5).collect::<Vec<_>>().join("\n");
+        let example = cached_storage_read(VAULT, code.trim_start(), "feeBps");
+        assert_eq!(example.before, "for (uint256 i = 0; i < users.length; i++) {\n    uint256 fee = balances[users[i]] * feeBps / 10000;\n    // ...\n}");
+        assert_eq!(example.after, "uint256 cachedFeeBps = feeBps;\nfor (uint256 i = 0; i < users.length; i++) {\n    uint256 fee = balances[users[i]] * cachedFeeBps / 10000;\n    // ...\n}");
+    }
+}
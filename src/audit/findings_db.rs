@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::audit::AuditResult;
+use crate::audit::baseline::rule_set_hash;
+use crate::audit::vulnerabilities::Vulnerability;
+use crate::error::{AnalyzerError, Result};
+
+/// Bumped whenever the findings database layout changes incompatibly
+pub const DB_FORMAT_VERSION: u32 = 2;
+
+/// Long-lived record of findings across runs and machines, keyed by fingerprint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindingsDb {
+    pub format_version: u32,
+    pub tool_version: String,
+    pub rule_set_hash: String,
+    /// Unix seconds of the run that last wrote the database
+    pub updated_at: u64,
+    pub findings: Vec<DbFinding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbFinding {
+    /// File the finding was reported for
+    pub file: String,
+    pub status: FindingStatus,
+    /// Unix seconds of the first run that reported the finding
+    pub first_seen: u64,
+    /// Unix seconds of the latest run that reported the finding
+    pub last_seen: u64,
+    /// Why the finding was triaged the way it was, e.g. a suppression reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(flatten)]
+    pub finding: Vulnerability,
+}
+
+/// Triage state; everything except `Open` and `Resolved` is set by hand in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingStatus {
+    Open,
+    Acknowledged,
+    FalsePositive,
+    Fixed,
+    /// No longer reported by the analyzer
+    Resolved,
+}
+
+impl FindingStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            FindingStatus::Open => "open",
+            FindingStatus::Acknowledged => "acknowledged",
+            FindingStatus::FalsePositive => "false-positive",
+            FindingStatus::Fixed => "fixed",
+            FindingStatus::Resolved => "resolved",
+        }
+    }
+
+    fn is_closed(self) -> bool {
+        matches!(self, FindingStatus::Fixed | FindingStatus::Resolved)
+    }
+}
+
+/// Per-finding triage state for the current run, rendered by the report
+#[derive(Debug, Default)]
+pub struct TriageSummary {
+    /// Status and first-seen time by fingerprint, for findings in this run
+    pub statuses: HashMap<String, (FindingStatus, u64)>,
+    /// Findings of this file that were open in the imported database but not reported now
+    pub resolved: Vec<DbFinding>,
+    /// Merge conflicts and version mismatches worth a look
+    pub warnings: Vec<String>,
+}
+
+impl FindingsDb {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AnalyzerError::Config(format!("cannot read findings database {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AnalyzerError::Config(format!("invalid findings database {}: {}", path.display(), e))
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AnalyzerError::Config(format!("cannot serialize findings database: {}", e)))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Records the findings of `result` for `file` on top of `previous`.
+    ///
+    /// Triage status is carried forward by fingerprint. Findings of `file` that
+    /// `previous` has but this run doesn't are marked resolved; entries for other
+    /// files are kept untouched so one database can span a whole project.
+    pub fn merge(
+        previous: Option<&FindingsDb>,
+        result: &AuditResult,
+        file: &str,
+        rule_names: &[String],
+        now: u64,
+    ) -> (FindingsDb, TriageSummary) {
+        let mut summary = TriageSummary::default();
+        let mut known: HashMap<String, DbFinding> = HashMap::new();
+        let mut order: Vec<String> = Vec::new();
+
+        if let Some(previous) = previous {
+            if previous.format_version != DB_FORMAT_VERSION {
+                summary.warnings.push(format!(
+                    "findings database format v{} differs from v{} used by this tool",
+                    previous.format_version, DB_FORMAT_VERSION
+                ));
+            }
+            if previous.rule_set_hash != rule_set_hash(rule_names) {
+                summary.warnings.push(
+                    "the audit rule set changed since the database was written; findings of removed rules will show as resolved".to_string()
+                );
+            }
+
+            for entry in &previous.findings {
+                match known.get_mut(&entry.finding.fingerprint) {
+                    None => {
+                        order.push(entry.finding.fingerprint.clone());
+                        known.insert(entry.finding.fingerprint.clone(), entry.clone());
+                    }
+                    // Hand-merged databases can list a fingerprint twice; the most recent sighting wins
+                    Some(existing) => {
+                        if existing.status != entry.status {
+                            summary.warnings.push(format!(
+                                "\"{}\" is listed twice with status {} and {}; keeping the most recently seen",
+                                entry.finding.name, existing.status.label(), entry.status.label()
+                            ));
+                        }
+                        let first_seen = existing.first_seen.min(entry.first_seen);
+                        if entry.last_seen > existing.last_seen {
+                            *existing = entry.clone();
+                        }
+                        existing.first_seen = first_seen;
+                    }
+                }
+            }
+        }
+
+        let mut current = HashSet::new();
+        for vuln in result.vulnerabilities() {
+            current.insert(vuln.fingerprint.clone());
+            let entry = match known.get_mut(&vuln.fingerprint) {
+                Some(entry) => {
+                    if entry.status.is_closed() {
+                        summary.warnings.push(format!(
+                            "\"{}\" was marked {} but is reported again; reopened",
+                            vuln.name, entry.status.label()
+                        ));
+                        entry.status = FindingStatus::Open;
+                    }
+                    entry.finding = vuln.clone();
+                    entry.last_seen = now;
+                    entry
+                }
+                None => {
+                    order.push(vuln.fingerprint.clone());
+                    known.entry(vuln.fingerprint.clone()).or_insert(DbFinding {
+                        file: file.to_string(),
+                        status: FindingStatus::Open,
+                        first_seen: now,
+                        last_seen: now,
+                        note: None,
+                        finding: vuln.clone(),
+                    })
+                }
+            };
+            summary.statuses.insert(vuln.fingerprint.clone(), (entry.status, entry.first_seen));
+        }
+
+        for fingerprint in &order {
+            let Some(entry) = known.get_mut(fingerprint) else { continue };
+            let still_open = matches!(entry.status, FindingStatus::Open | FindingStatus::Acknowledged);
+            if entry.file == file && still_open && !current.contains(fingerprint) {
+                entry.status = FindingStatus::Resolved;
+                summary.resolved.push(entry.clone());
+            }
+        }
+
+        let db = FindingsDb {
+            format_version: DB_FORMAT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            rule_set_hash: rule_set_hash(rule_names),
+            updated_at: now,
+            findings: order.into_iter().filter_map(|fingerprint| known.remove(&fingerprint)).collect(),
+        };
+        (db, summary)
+    }
+}
+
+/// Current time in Unix seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Formats Unix seconds as a UTC `YYYY-MM-DD` date
+pub fn format_date(unix: u64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let days = (unix / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::vulnerabilities::Severity;
+
+    fn result(fingerprints: &[&str]) -> AuditResult {
+        let mut result = AuditResult::default();
+        for fingerprint in fingerprints {
+            result.push(Vulnerability {
+                name: format!("Finding {}", fingerprint),
+                severity: Severity::High,
+                fingerprint: fingerprint.to_string(),
+                ..Default::default()
+            });
+        }
+        result
+    }
+
+    #[test]
+    fn an_exported_database_imports_unchanged() {
+        let rules = vec!["Reentrancy Pattern Checker".to_string()];
+        let (mut db, summary) = FindingsDb::merge(None, &result(&["aaa", "bbb"]), "token.rs", &rules, 1_000);
+        assert!(summary.warnings.is_empty());
+        db.findings[1].status = FindingStatus::FalsePositive;
+        db.findings[1].note = Some("guarded by the caller".to_string());
+
+        let path = std::env::temp_dir().join(format!("findings-db-{}.json", std::process::id()));
+        db.save(&path).unwrap();
+        let imported = FindingsDb::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(imported.rule_set_hash, db.rule_set_hash);
+        assert_eq!(imported.updated_at, 1_000);
+        let entries: Vec<(&str, FindingStatus, Option<&str>)> = imported.findings.iter()
+            .map(|entry| (entry.finding.fingerprint.as_str(), entry.status, entry.note.as_deref()))
+            .collect();
+        assert_eq!(entries, [("aaa", FindingStatus::Open, None), ("bbb", FindingStatus::FalsePositive, Some("guarded by the caller"))]);
+        assert_eq!(imported.findings[0].finding.name, "Finding aaa");
+    }
+
+    #[test]
+    fn merging_updates_known_findings_and_resolves_missing_ones() {
+        let rules = vec!["Reentrancy Pattern Checker".to_string()];
+        let (mut previous, _) = FindingsDb::merge(None, &result(&["aaa", "bbb", "ccc"]), "token.rs", &rules, 1_000);
+        previous.findings[1].status = FindingStatus::Acknowledged;
+        previous.findings[2].status = FindingStatus::Fixed;
+        // Another file's findings share the database untouched
+        let (other, _) = FindingsDb::merge(None, &result(&["zzz"]), "vault.rs", &rules, 500);
+        previous.findings.extend(other.findings);
+
+        // aaa is gone, bbb stays, ccc came back and ddd is new
+        let (db, summary) = FindingsDb::merge(Some(&previous), &result(&["bbb", "ccc", "ddd"]), "token.rs", &rules, 2_000);
+        let entries: Vec<(&str, FindingStatus, u64, u64)> = db.findings.iter()
+            .map(|entry| (entry.finding.fingerprint.as_str(), entry.status, entry.first_seen, entry.last_seen))
+            .collect();
+        assert_eq!(entries, [
+            ("aaa", FindingStatus::Resolved, 1_000, 1_000),
+            ("bbb", FindingStatus::Acknowledged, 1_000, 2_000),
+            ("ccc", FindingStatus::Open, 1_000, 2_000),
+            ("zzz", FindingStatus::Open, 500, 500),
+            ("ddd", FindingStatus::Open, 2_000, 2_000),
+        ]);
+        assert_eq!(summary.resolved.len(), 1);
+        assert_eq!(summary.statuses["bbb"], (FindingStatus::Acknowledged, 1_000));
+        assert_eq!(summary.warnings, ["\"Finding ccc\" was marked fixed but is reported again; reopened"]);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::path::Path;
+use sha2::{Digest, Sha256};
+use crate::audit::AuditResult;
+use crate::audit::vulnerabilities::Vulnerability;
+
+/// Assigns a stable fingerprint to every finding in `result`.
+///
+/// The fingerprint combines the rule id, the file path, the enclosing function
+/// and a normalized hash of the matched snippet, so it does not change when
+/// lines are inserted above the finding or the rule is renamed. Findings that
+/// would still collide within the file get an occurrence index, in report order.
+pub fn assign(result: &mut AuditResult, path: &str, source: &str) {
+    let path = stable_path(path);
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+    for bucket in [
+        &mut result.critical_vulnerabilities,
+        &mut result.high_vulnerabilities,
+        &mut result.medium_vulnerabilities,
+        &mut result.low_vulnerabilities,
+        &mut result.info_findings,
+    ] {
+        for vuln in bucket.iter_mut() {
+            if vuln.function.is_none() {
+                vuln.function = enclosing_function(source, vuln);
+            }
+
+            let base = base_fingerprint(vuln, &path);
+            let index = occurrences.entry(base.clone()).or_insert(0);
+            vuln.fingerprint = if *index == 0 {
+                base
+            } else {
+                short_hash(&[base.as_bytes(), index.to_string().as_bytes()])
+            };
+            *index += 1;
+        }
+    }
+}
+
+fn base_fingerprint(vuln: &Vulnerability, path: &str) -> String {
+    // Whole-file rules have no snippet; their description is the closest stable stand-in
+    let matched = match &vuln.snippet {
+        Some(snippet) => normalize(snippet),
+        None => normalize(&format!("{} {}", vuln.name, vuln.risk_description)),
+    };
+
+    let rule = vuln.rule_id.as_deref().or(vuln.rule.as_deref()).unwrap_or(&vuln.name);
+    short_hash(&[
+        rule.as_bytes(),
+        path.as_bytes(),
+        vuln.function.as_deref().unwrap_or("").as_bytes(),
+        short_hash(&[matched.as_bytes()]).as_bytes(),
+    ])
+}
+
+/// `path` as fingerprints record it: relative to the enclosing git checkout,
+/// else to the working directory, with `/` separators. `t.rs`, `./t.rs` and
+/// the absolute path then fingerprint alike. Paths that don't exist on disk,
+/// such as labels for in-memory sources, are only trimmed of a leading `./`
+fn stable_path(path: &str) -> String {
+    let Ok(absolute) = Path::new(path).canonicalize() else {
+        return path.trim_start_matches("./").replace('\\', "/");
+    };
+    let root = absolute.ancestors().skip(1).find(|dir| dir.join(".git").exists()).map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().and_then(|dir| dir.canonicalize()).ok());
+    let relative = root.as_deref().and_then(|root| absolute.strip_prefix(root).ok()).unwrap_or(&absolute);
+    relative.components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Re-tokenizes code so whitespace, indentation and column position don't matter
+fn normalize(code: &str) -> String {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in code.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            current.push(c);
+            continue;
+        }
+        if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if !c.is_whitespace() {
+            tokens.push(c.to_string());
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.join(" ")
+}
+
+/// Name of the nearest `fn`/`function` declared at or above the finding's
+/// line, or above its snippet when the finding has no line. The same snippet
+/// can appear in several functions, so the line is the better anchor
+fn enclosing_function(source: &str, vuln: &Vulnerability) -> Option<String> {
+    let end = match (vuln.line, vuln.snippet.as_deref()) {
+        (Some(line), _) => source.lines().take(line).map(|text| text.len() + 1).sum::<usize>().min(source.len()),
+        (None, Some(snippet)) => source.find(snippet.trim())?,
+        (None, None) => return None,
+    };
+    source[..end].lines().rev().find_map(|line| {
+        let mut words = line.split_whitespace();
+        words.find(|word| *word == "fn" || *word == "function")?;
+        let name: String = words.next()?.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        (!name.is_empty()).then_some(name)
+    })
+}
+
+fn short_hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+        hasher.update([0]);
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::vulnerabilities::Severity;
+
+    fn fingerprint(vuln: Vulnerability, path: &str) -> String {
+        let mut result = AuditResult::default();
+        result.push(vuln);
+        assign(&mut result, path, "fn withdraw() {\n    unsafe { drop() }\n}\n");
+        let fingerprint = result.vulnerabilities().next().unwrap().fingerprint.clone();
+        fingerprint
+    }
+
+    fn finding(rule: &str) -> Vulnerability {
+        Vulnerability {
+            name: "Unsafe Block Usage".to_string(),
+            severity: Severity::Critical,
+            rule: Some(rule.to_string()),
+            rule_id: Some("SA-MEMORY-001".to_string()),
+            snippet: Some("unsafe { drop() }".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spellings_of_one_path_fingerprint_alike() {
+        let typed = fingerprint(finding("Memory Safety Analyzer"), "src/lib.rs");
+        let absolute = Path::new("src/lib.rs").canonicalize().unwrap();
+        assert_eq!(fingerprint(finding("Memory Safety Analyzer"), "./src/lib.rs"), typed);
+        assert_eq!(fingerprint(finding("Memory Safety Analyzer"), &absolute.display().to_string()), typed);
+        assert_ne!(fingerprint(finding("Memory Safety Analyzer"), "src/main.rs"), typed);
+    }
+
+    #[test]
+    fn renaming_the_rule_keeps_the_fingerprint() {
+        assert_eq!(
+            fingerprint(finding("Memory Safety Analyzer"), "contract.rs"),
+            fingerprint(finding("Rust Memory Safety Checker"), "contract.rs"),
+        );
+    }
+
+    #[test]
+    fn fingerprint_survives_line_churn_and_finds_the_function() {
+        let mut result = AuditResult::default();
+        result.push(finding("Memory Safety Analyzer"));
+        assign(&mut result, "contract.rs", "\n\n// moved down\nfn withdraw() {\n        unsafe { drop() }\n}\n");
+        let vuln = result.vulnerabilities().next().unwrap();
+        assert_eq!(vuln.function.as_deref(), Some("withdraw"));
+        assert_eq!(vuln.fingerprint, fingerprint(finding("Memory Safety Analyzer"), "contract.rs"));
+    }
+
+    #[test]
+    fn the_function_comes_from_the_finding_line() {
+        let source = "fn deposit() {\n    self.total.set(self.total.get() + 1);\n}\nfn withdraw() {\n    self.total.set(self.total.get() + 1);\n}\n";
+        let snippet = "self.total.set(self.total.get() + 1);";
+        let mut result = AuditResult::default();
+        for line in [Some(5), Some(2), Some(4), None] {
+            result.push(Vulnerability { line, snippet: Some(snippet.to_string()), ..finding("Overflow") });
+        }
+        assign(&mut result, "contract.rs", source);
+        let functions: Vec<_> = result.vulnerabilities().map(|vuln| vuln.function.as_deref()).collect();
+        // A finding on the declaration line belongs to that function; without a line the first match is used
+        assert_eq!(functions, [Some("withdraw"), Some("deposit"), Some("withdraw"), Some("deposit")]);
+
+        let mut counter = AuditResult::default();
+        counter.push(Vulnerability { line: Some(25), snippet: Some("self.value.get()".to_string()), ..finding("Getter") });
+        assign(&mut counter, "counter.rs", include_str!("../../test ex/counter.rs"));
+        // The snippet first appears in `increment`, but the finding is in `get`
+        assert_eq!(counter.vulnerabilities().next().unwrap().function.as_deref(), Some("get"));
+    }
+
+    #[test]
+    fn repeated_findings_get_distinct_fingerprints() {
+        let mut result = AuditResult::default();
+        result.push(finding("Memory Safety Analyzer"));
+        result.push(finding("Memory Safety Analyzer"));
+        assign(&mut result, "contract.rs", "");
+        let prints: Vec<&str> = result.vulnerabilities().map(|vuln| vuln.fingerprint.as_str()).collect();
+        assert_ne!(prints[0], prints[1]);
+    }
+}
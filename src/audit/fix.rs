@@ -0,0 +1,270 @@
+//! Mechanical fixes for findings whose rule knows the patch: planning them,
+//! applying the ones that don't overlap, and rendering a unified diff
+
+use colored::*;
+use crate::audit::vulnerabilities::Vulnerability;
+use crate::error::{AnalyzerError, Result};
+
+/// Lines of unchanged context around each diff hunk
+const CONTEXT: usize = 3;
+
+/// Replacement of the byte range `start..end` of a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Replaces the first `from` on 1-based `line` of `source` with `to`
+    pub fn on_line(source: &str, line: usize, from: &str, to: impl Into<String>) -> Option<Self> {
+        let line_start = line_start(source, line.checked_sub(1)?)?;
+        let line_end = source[line_start..].find('\n').map_or(source.len(), |i| line_start + i);
+        let at = source[line_start..line_end].find(from)? + line_start;
+        Some(Self { start: at, end: at + from.len(), replacement: to.into() })
+    }
+
+    fn overlaps(&self, other: &TextEdit) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// What `--fix` can do for the findings of one audit
+#[derive(Debug, Default)]
+pub struct FixPlan {
+    /// Each fixable finding with the edits that fix it together
+    pub fixes: Vec<(Vulnerability, Vec<TextEdit>)>,
+    /// Findings whose rule has no automatic fix for them
+    pub manual: Vec<Vulnerability>,
+}
+
+/// Result of applying a plan's edits
+#[derive(Debug)]
+pub struct Applied {
+    pub source: String,
+    /// Edits applied, in source order
+    pub edits: Vec<TextEdit>,
+    /// Findings with an edit that overlapped an earlier fix; none of their edits were applied
+    pub skipped: Vec<Vulnerability>,
+}
+
+impl FixPlan {
+    /// Applies every fix none of whose edits overlap a fix before it in the file
+    pub fn apply(&self, source: &str) -> Applied {
+        let mut fixes: Vec<&(Vulnerability, Vec<TextEdit>)> = self.fixes.iter().collect();
+        fixes.sort_by_key(|(_, edits)| edits.iter().map(|edit| (edit.start, edit.end)).min());
+
+        let mut edits: Vec<TextEdit> = Vec::new();
+        let mut skipped = Vec::new();
+        for (vuln, fix) in fixes {
+            // Two findings can ask for the same patch, e.g. one event flagged twice
+            let fresh: Vec<&TextEdit> = fix.iter().filter(|edit| !edits.contains(edit)).collect();
+            if fresh.iter().any(|edit| edits.iter().any(|applied| applied.overlaps(edit))) {
+                skipped.push(vuln.clone());
+            } else {
+                edits.extend(fresh.into_iter().cloned());
+            }
+        }
+        // Stable, so insertions at the same offset keep their order
+        edits.sort_by_key(|edit| (edit.start, edit.end));
+
+        let mut fixed = source.to_string();
+        for edit in edits.iter().rev() {
+            fixed.replace_range(edit.start..edit.end, &edit.replacement);
+        }
+        Applied { source: fixed, edits, skipped }
+    }
+}
+
+/// Confirms `fixed` still parses with whichever parser accepted `original`
+pub fn check_parses(original: &str, fixed: &str) -> Result<()> {
+    if solang_parser::parse(original, 0).is_ok() {
+        return solang_parser::parse(fixed, 0)
+            .map(|_| ())
+            .map_err(|_| AnalyzerError::Parse("the fixed source no longer parses as Solidity; nothing was written".to_string()));
+    }
+    if syn::parse_file(original).is_ok() {
+        return syn::parse_file(fixed)
+            .map(|_| ())
+            .map_err(|e| AnalyzerError::Parse(format!("the fixed source no longer parses as Rust ({}); nothing was written", e)));
+    }
+    Ok(())
+}
+
+/// Byte offset where 0-based `line` starts
+fn line_start(source: &str, line: usize) -> Option<usize> {
+    if line == 0 {
+        return Some(0);
+    }
+    source.match_indices('\n').nth(line - 1).map(|(i, _)| i + 1)
+}
+
+fn line_of_offset(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count()
+}
+
+/// Unified diff of `edits` (sorted, non-overlapping) against `source`; plain
+/// text so it can be piped to `git apply`
+pub fn unified_diff(path: &str, source: &str, edits: &[TextEdit]) -> String {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+
+    // Old lines each edit replaces, as [first, end); whole lines inserted
+    // at a line start replace none. Edits on the same or adjacent lines are
+    // shown as one change
+    let mut blocks: Vec<(usize, usize, Vec<&TextEdit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of_offset(source, edit.start);
+        let inserts_lines = edit.start == edit.end
+            && (edit.start == 0 || source[..edit.start].ends_with('\n'))
+            && edit.replacement.ends_with('\n');
+        let end = if inserts_lines { first } else { line_of_offset(source, edit.end.saturating_sub(1).max(edit.start)) + 1 };
+        match blocks.last_mut() {
+            Some((_, block_end, block_edits)) if first <= *block_end => {
+                *block_end = (*block_end).max(end);
+                block_edits.push(edit);
+            }
+            _ => blocks.push((first, end, vec![edit])),
+        }
+    }
+
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut shift: isize = 0;
+    let mut i = 0;
+    while i < blocks.len() {
+        // Blocks whose context windows touch share a hunk
+        let mut j = i;
+        while j + 1 < blocks.len() && blocks[j + 1].0 <= blocks[j].1 + 2 * CONTEXT {
+            j += 1;
+        }
+        let start = blocks[i].0.saturating_sub(CONTEXT);
+        let end = (blocks[j].1 + CONTEXT).min(lines.len());
+
+        let mut body = String::new();
+        let (mut old_len, mut new_len) = (0, 0);
+        let mut line = start;
+        for (first, block_end, block_edits) in &blocks[i..=j] {
+            for context in &lines[line..*first] {
+                body.push_str(&format!(" {}", with_newline(context)));
+                old_len += 1;
+                new_len += 1;
+            }
+            let block_start = line_start(source, *first).unwrap_or(source.len());
+            let block_stop = line_start(source, *block_end).unwrap_or(source.len());
+            let mut new_text = source[block_start..block_stop].to_string();
+            for edit in block_edits.iter().rev() {
+                new_text.replace_range(edit.start - block_start..edit.end - block_start, &edit.replacement);
+            }
+            for old in &lines[*first..*block_end] {
+                body.push_str(&format!("-{}", with_newline(old)));
+                old_len += 1;
+            }
+            for new in new_text.split_inclusive('\n') {
+                body.push_str(&format!("+{}", with_newline(new)));
+                new_len += 1;
+            }
+            line = *block_end;
+        }
+        for context in &lines[line..end.max(line)] {
+            body.push_str(&format!(" {}", with_newline(context)));
+            old_len += 1;
+            new_len += 1;
+        }
+
+        let new_start = (start as isize + shift) as usize;
+        diff.push_str(&format!("@@ -{},{} +{},{} @@\n", start + 1, old_len, new_start + 1, new_len));
+        diff.push_str(&body);
+        shift += new_len as isize - old_len as isize;
+        i = j + 1;
+    }
+    diff
+}
+
+fn with_newline(line: &str) -> String {
+    if line.ends_with('\n') {
+        line.to_string()
+    } else {
+        format!("{}\n\\ No newline at end of file\n", line)
+    }
+}
+
+/// Summary of what was (or would be) fixed and what is left for a human
+pub fn format_plan(plan: &FixPlan, applied: &Applied) -> String {
+    let mut output = format!("\n{}\n", "🔧 Automatic fixes".cyan().bold());
+    if plan.fixes.is_empty() {
+        output.push_str("  No finding has an automatic fix\n");
+    }
+    for (vuln, _) in &plan.fixes {
+        if applied.skipped.iter().any(|s| s.fingerprint == vuln.fingerprint) {
+            continue;
+        }
+        output.push_str(&format!("  ✅ {}{}\n", vuln.name, line_suffix(vuln)));
+    }
+    for vuln in &applied.skipped {
+        output.push_str(&format!("  {} {}{} (overlaps another fix; run --fix again)\n", "⏭️".yellow(), vuln.name, line_suffix(vuln)));
+    }
+    if !plan.manual.is_empty() {
+        output.push_str(&format!("\n{}\n", "✍️  Manual fixes needed".yellow().bold()));
+        for vuln in &plan.manual {
+            output.push_str(&format!("  • [{:?}] {}{}\n", vuln.severity, vuln.name, line_suffix(vuln)));
+        }
+    }
+    output
+}
+
+fn line_suffix(vuln: &Vulnerability) -> String {
+    vuln.line.map(|line| format!(" (line {})", line)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditAnalyzer;
+    use crate::audit::event_emission::EventEmissionRule;
+    use crate::audit::l2_patterns::L2OptimizationRule;
+    use crate::audit::memory_safety::MemorySafetyRule;
+
+    async fn fix(source: &str) -> (FixPlan, Applied) {
+        let analyzer = AuditAnalyzer::new();
+        analyzer.add_rule(Box::new(L2OptimizationRule));
+        analyzer.add_rule(Box::new(MemorySafetyRule));
+        analyzer.add_rule(Box::new(EventEmissionRule));
+        let result = analyzer.audit(source).await.unwrap();
+        let plan = analyzer.plan_fixes(&result, source).unwrap();
+        let applied = plan.apply(source);
+        check_parses(source, &applied.source).unwrap();
+        (plan, applied)
+    }
+
+    /// Each line the fix changed, as (before, after)
+    fn changed_lines(before: &str, after: &str) -> Vec<(String, String)> {
+        before.lines().zip(after.lines())
+            .filter(|(old, new)| old != new)
+            .map(|(old, new)| (old.trim().to_string(), new.trim().to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rust_fixes_preallocate_and_drop_needless_clones() {
+        let source = include_str!("../../test ex/fix/mechanical.rs");
+        let (plan, applied) = fix(source).await;
+        assert!(plan.manual.is_empty() && applied.skipped.is_empty());
+        assert_eq!(changed_lines(source, &applied.source), [
+            ("let mut v = Vec::new();".to_string(), "let mut v = Vec::with_capacity(3);".to_string()),
+            ("for value in summed(&values.clone()) {".to_string(), "for value in summed(&values) {".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn solidity_fixes_index_addresses_and_use_calldata() {
+        let source = include_str!("../../test ex/fix/mechanical.sol");
+        let (plan, applied) = fix(source).await;
+        assert!(plan.manual.is_empty() && applied.skipped.is_empty());
+        assert_eq!(changed_lines(source, &applied.source), [
+            ("event Registered(address owner, string label);".to_string(), "event Registered(address indexed owner, string label);".to_string()),
+            ("function register(string memory label) external {".to_string(), "function register(string calldata label) external {".to_string()),
+        ]);
+        let diff = unified_diff("mechanical.sol", source, &applied.edits);
+        assert!(diff.starts_with("--- a/mechanical.sol\n+++ b/mechanical.sol\n@@ -2,11 +2,11 @@\n"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Zero-address and zero-amount checks on the arguments of public functions
+//! that move funds, grant allowances or hand over ownership. A zero owner
+//! locks the contract for good; a transfer to the zero address burns funds
+
+use async_trait::async_trait;
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionAttribute, FunctionTy, Loc, Mutability, SourceUnitPart};
+use crate::audit::erc::{self, Standard};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{identifier_offsets, is_externally_callable, mask, mentions, rust_functions, slice};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::compare::abi::AbiSurface;
+use crate::error::Result;
+
+/// The zero address as Stylus and Solidity spell it
+const ZERO_ADDRESSES: [&str; 5] = ["Address::ZERO", "[0u8;20]", "address(0)", "Address::default()", "ZERO_ADDRESS"];
+const ZERO_AMOUNTS: [&str; 2] = ["0", "U256::ZERO"];
+/// Parameter names that carry an amount
+const AMOUNT_NAMES: [&str; 6] = ["amount", "value", "qty", "quantity", "shares", "wad"];
+/// Words on a line that hands a value to another account
+const VALUE_MOVES: [&str; 6] = ["transfer", "send", "approve", "allowance", "balance", "mint"];
+/// Setting an allowance to zero revokes it, so zero amounts are fine there
+const APPROVALS: [&str; 2] = ["approve", "allowance"];
+/// Addresses value is taken from rather than sent to; a zero one fails the balance check anyway
+const SOURCES: [&str; 3] = ["from", "_from", "sender"];
+/// Roles whose holder a function may replace
+const ROLES: [&str; 4] = ["owner", "admin", "governor", "guardian"];
+
+#[derive(PartialEq)]
+enum Kind {
+    Address,
+    Amount,
+}
+
+/// A public, state-changing function: name, line, signature and body
+struct Function {
+    name: String,
+    line: usize,
+    signature: String,
+    /// Parameter names and whether each is an address or an amount
+    params: Vec<(String, Kind)>,
+    body: String,
+}
+
+/// Public functions that pass unchecked zero addresses or amounts on to transfers, approvals or ownership
+pub struct InputValidationRule;
+
+#[async_trait]
+impl AuditRule for InputValidationRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let (functions, helpers) = match solang_parser::parse(content, 0) {
+            Ok((unit, _)) => solidity_functions(&unit, content, &code),
+            Err(_) if syn::parse_file(content).is_ok() => stylus_functions(content, &code),
+            Err(_) => return Ok(Vec::new()),
+        };
+        // ERC-721 and ERC-1155 clear approvals by approving the zero address
+        let nft = AbiSurface::from_source(content).ok()
+            .and_then(|surface| erc::detect(&surface))
+            .is_some_and(|standard| matches!(standard, Standard::Erc721 | Standard::Erc1155));
+
+        let mut vulnerabilities = Vec::new();
+        for function in functions {
+            let approval = function.name.to_lowercase().contains("approv");
+            if approval && nft {
+                continue;
+            }
+            let mut missing = Vec::new();
+            let mut consequences = Vec::new();
+            let mut ownership = false;
+            for (param, kind) in &function.params {
+                // Approving zero revokes an allowance
+                if approval && *kind == Kind::Amount {
+                    continue;
+                }
+                let Some(sink) = first_sink(&function.body, param, kind) else { continue };
+                if validated(&function.body, param, kind, &helpers) {
+                    continue;
+                }
+                ownership |= sink == Sink::Ownership;
+                let (check, consequence) = match kind {
+                    Kind::Address => (format!("`{}` isn't the zero address", param), "tokens sent or approved to the zero address are lost"),
+                    Kind::Amount => (format!("`{}` isn't zero", param), "a zero amount writes state and emits events for nothing"),
+                };
+                missing.push(check);
+                if !consequences.contains(&consequence) {
+                    consequences.push(consequence);
+                }
+            }
+            if missing.is_empty() {
+                continue;
+            }
+            let (severity, consequence) = match ownership {
+                true => (Severity::High, "a zero owner or admin locks every privileged function for good".to_string()),
+                false => (Severity::Medium, consequences.join(" and ")),
+            };
+            vulnerabilities.push(Vulnerability {
+                name: "Missing Input Validation".to_string(),
+                severity,
+                risk_description: format!("{} never checks that {}; {}", function.name, missing.join(" or "), consequence),
+                recommendation: "Reject zero addresses (Address::ZERO, address(0)) and zero amounts at the top of the function".to_string(),
+                function: Some(function.name),
+                line: Some(function.line),
+                snippet: Some(function.signature),
+                ..Default::default()
+            });
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Input Validation Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-INPUT-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Zero addresses and amounts passed unchecked to transfers, approvals and ownership changes"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Missing Input Validation"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+#[derive(PartialEq)]
+enum Sink {
+    Ownership,
+    ValueMove,
+}
+
+/// What the first line using `param` does with it, if it moves value or replaces a role holder
+fn first_sink(body: &str, param: &str, kind: &Kind) -> Option<Sink> {
+    body.lines()
+        .filter(|line| mentions(line, param))
+        .find_map(|line| {
+            let lower = line.to_lowercase();
+            let write = line.contains(".set(") || line.contains("transferownership") || lower.contains("_transferownership(")
+                || line.split_once('=').is_some_and(|(lhs, rhs)| !rhs.starts_with('=') && !lhs.ends_with(['=', '!', '<', '>']));
+            if ROLES.iter().any(|role| lower.contains(role)) && write {
+                Some(Sink::Ownership)
+            } else if *kind == Kind::Amount && APPROVALS.iter().any(|word| lower.contains(word)) {
+                None
+            } else if VALUE_MOVES.iter().any(|word| lower.contains(word)) {
+                Some(Sink::ValueMove)
+            } else {
+                None
+            }
+        })
+}
+
+/// Whether `body`, or a helper it passes `param` to, compares `param` with zero
+fn validated(body: &str, param: &str, kind: &Kind, helpers: &[(String, String)]) -> bool {
+    let zeros: &[&str] = match kind {
+        Kind::Address => &ZERO_ADDRESSES,
+        Kind::Amount => &ZERO_AMOUNTS,
+    };
+    let ops: &[&str] = match kind {
+        Kind::Address => &["==", "!="],
+        Kind::Amount => &["==", "!=", ">"],
+    };
+    if compares_with_zero(body, Some(param), zeros, ops) {
+        return true;
+    }
+    // `_transfer(from, to, amount)` checking its own arguments
+    helpers.iter()
+        .filter(|(name, _)| identifier_offsets(body, name).any(|at| {
+            let args = body[at + name.len()..].trim_start();
+            args.starts_with('(') && mentions(args.split([';', '{']).next().unwrap_or(args), param)
+        }))
+        .any(|(_, helper)| compares_with_zero(helper, None, zeros, ops))
+}
+
+/// `param != Address::ZERO`, `Address::ZERO != param`, `param > 0` or `param.is_zero()`
+/// in `body`; with no `param`, a comparison of anything with zero
+fn compares_with_zero(body: &str, param: Option<&str>, zeros: &[&str], ops: &[&str]) -> bool {
+    let compact = |text: &str| -> String { text.split_whitespace().collect() };
+    let Some(param) = param else {
+        let body = compact(body);
+        return zeros.iter().any(|zero| ops.iter().any(|op| {
+            let reversed = op.replace('>', "<");
+            body.match_indices(&format!("{}{}", op, zero)).any(|(at, text)| !body[at + text.len()..].starts_with(is_identifier))
+                || body.match_indices(&format!("{}{}", zero, reversed)).any(|(at, _)| !body[..at].ends_with(is_identifier))
+        }));
+    };
+    identifier_offsets(body, param).any(|at| {
+        let after: String = body[at + param.len()..].chars().filter(|c| !c.is_whitespace()).take(24).collect();
+        let mut before: Vec<char> = body[..at].chars().rev().filter(|c| !c.is_whitespace()).take(24).collect();
+        before.reverse();
+        let before: String = before.into_iter().collect();
+        after.starts_with(".is_zero()")
+            || zeros.iter().any(|zero| ops.iter().any(|op| {
+                let following = after.strip_prefix(op).is_some_and(|rest| rest.starts_with(zero) && !rest[zero.len()..].starts_with(is_identifier));
+                let reversed = op.replace('>', "<");
+                following || before.ends_with(&format!("{}{}", zero, reversed))
+            }))
+    })
+}
+
+fn stylus_functions(content: &str, code: &str) -> (Vec<Function>, Vec<(String, String)>) {
+    let mut functions = Vec::new();
+    let mut helpers = Vec::new();
+    for (name, start, end) in rust_functions(code) {
+        let text = &code[start..end];
+        let Some(open) = text.find('{') else { continue };
+        let signature = &text[..open];
+        helpers.push((name.clone(), text[open..].to_string()));
+        // Views take `&self` and associated functions like `new` take no receiver
+        if !text.starts_with("pub ") || !signature.contains("&mut self") {
+            continue;
+        }
+        let params = signature.find('(')
+            .map(|at| parameter_list(&signature[at + 1..]).split(','))
+            .into_iter()
+            .flatten()
+            .filter_map(|param| {
+                let (name, ty) = param.split_once(':')?;
+                let name = name.trim().trim_start_matches("mut ").trim();
+                let ty: String = ty.split_whitespace().collect();
+                classify(name, &ty, ["Address", "[u8;20]"], ["U256", "u64", "u128", "u32"])
+            })
+            .collect();
+        functions.push(Function {
+            name,
+            line: content[..start].matches('\n').count() + 1,
+            signature: content[start..start + open].trim().to_string(),
+            params,
+            body: text[open..].to_string(),
+        });
+    }
+    (functions, helpers)
+}
+
+fn solidity_functions(unit: &solang_parser::pt::SourceUnit, content: &str, code: &str) -> (Vec<Function>, Vec<(String, String)>) {
+    let mut functions = Vec::new();
+    let mut helpers = Vec::new();
+    for part in &unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        for part in &contract.parts {
+            let ContractPart::FunctionDefinition(func) = part else { continue };
+            let (Some(name), Some(body)) = (&func.name, &func.body) else { continue };
+            let Loc::File(_, start, _) = func.loc else { continue };
+            let body = slice(&body.loc(), code).to_string();
+            helpers.push((name.name.clone(), body.clone()));
+            let read_only = func.attributes.iter()
+                .any(|attr| matches!(attr, FunctionAttribute::Mutability(Mutability::View(_) | Mutability::Pure(_))));
+            if func.ty != FunctionTy::Function || !is_externally_callable(func) || read_only {
+                continue;
+            }
+            let params = func.params.iter()
+                .filter_map(|(_, param)| {
+                    let param = param.as_ref()?;
+                    let ty = slice(&param.ty.loc(), code);
+                    classify(&param.name.as_ref()?.name, ty, ["address", "address"], ["uint", "uint256", "uint128", "uint64"])
+                })
+                .collect();
+            functions.push(Function {
+                name: name.name.clone(),
+                line: content[..start].matches('\n').count() + 1,
+                signature: slice(&func.loc, content).lines().next().unwrap_or_default().trim().trim_end_matches('{').trim().to_string(),
+                params,
+                body,
+            });
+        }
+    }
+    (functions, helpers)
+}
+
+/// Text up to the `)` closing a parameter list whose `(` was just consumed
+fn parameter_list(text: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return &text[..i],
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    text
+}
+
+/// An address parameter, or a numeric one whose name says it's an amount
+fn classify(name: &str, ty: &str, addresses: [&str; 2], numbers: [&str; 4]) -> Option<(String, Kind)> {
+    let lower = name.to_lowercase();
+    if addresses.contains(&ty) || ty == "addresspayable" || ty == "address payable" {
+        if SOURCES.contains(&name) {
+            return None;
+        }
+        Some((name.to_string(), Kind::Address))
+    } else if numbers.contains(&ty) && AMOUNT_NAMES.iter().any(|word| lower.contains(word)) {
+        Some((name.to_string(), Kind::Amount))
+    } else {
+        None
+    }
+}
+
+
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn severities(source: &str) -> Vec<(usize, Severity)> {
+        InputValidationRule.check(source).await.unwrap().into_iter()
+            .map(|vuln| (vuln.line.unwrap_or_default(), vuln.severity))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn unchecked_addresses_in_privileged_setters_rank_highest() {
+        assert_eq!(severities(include_str!("../../test ex/validation/ownable.sol")).await, [(13, Severity::High)]);
+        assert_eq!(severities(include_str!("../../test ex/validation/vault.rs")).await, [
+            (20, Severity::High),
+            (29, Severity::Medium),
+        ]);
+    }
+}
@@ -1,81 +1,118 @@
-use crate::audit::vulnerabilities::{Vulnerability, Severity};
+use crate::analyzer::gas_rules::{self, GasRule};
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
 use crate::audit::rules::AuditRule;
-use std::error::Error;
+use crate::audit::examples;
+use crate::audit::fix::TextEdit;
+use crate::audit::source::{find_line, mask};
+use crate::error::Result;
 use async_trait::async_trait;
 
 pub struct L2OptimizationRule;
 
 #[async_trait]
 impl AuditRule for L2OptimizationRule {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
         // Check for batch operation patterns
-        if content.contains("loop") && !content.contains("batch") {
+        if code.contains("loop") && !code.contains("batch") {
             vulnerabilities.push(Vulnerability {
                 name: "Missing Batch Operations".to_string(),
                 severity: Severity::Medium,
                 risk_description: "Non-batched operations may lead to higher gas costs on L2".to_string(),
                 recommendation: "Implement batching for loop operations to optimize gas costs".to_string(),
-            });
+                ..Default::default()
+            }.located(find_line(content, &["loop"])));
         }
 
         // Check for calldata optimization
-        if content.contains("&[u8]") || content.contains("Vec<u8>") {
-            if !content.contains("compression") && !content.contains("compact") {
-                vulnerabilities.push(Vulnerability {
-                    name: "Unoptimized Calldata".to_string(),
-                    severity: Severity::Medium,
-                    risk_description: "Uncompressed calldata increases L1 posting costs".to_string(),
-                    recommendation: "Implement calldata compression for large data structures".to_string(),
-                });
-            }
+        if (code.contains("&[u8]") || code.contains("Vec<u8>"))
+            && !code.contains("compression") && !code.contains("compact") {
+            vulnerabilities.push(Vulnerability {
+                name: "Unoptimized Calldata".to_string(),
+                severity: Severity::Medium,
+                risk_description: "Uncompressed calldata increases L1 posting costs".to_string(),
+                recommendation: "Implement calldata compression for large data structures".to_string(),
+                ..Default::default()
+            }.located(find_line(content, &["&[u8]", "Vec<u8>"])));
         }
 
         // Check for storage slot packing
-        if content.contains("StorageMap") || content.contains("StorageVec") {
-            if !content.contains("packed") && !content.contains("#[repr(packed)]") {
-                vulnerabilities.push(Vulnerability {
-                    name: "Unpacked Storage".to_string(),
-                    severity: Severity::Low,
-                    risk_description: "Inefficient storage slot usage increases gas costs".to_string(),
-                    recommendation: "Pack storage slots efficiently using appropriate data layouts".to_string(),
-                });
-            }
+        if (code.contains("StorageMap") || code.contains("StorageVec"))
+            && !code.contains("packed") && !code.contains("#[repr(packed)]") {
+            vulnerabilities.push(Vulnerability {
+                name: "Unpacked Storage".to_string(),
+                severity: Severity::Low,
+                risk_description: "Inefficient storage slot usage increases gas costs".to_string(),
+                recommendation: "Pack storage slots efficiently using appropriate data layouts".to_string(),
+                ..Default::default()
+            }.located(find_line(content, &["StorageMap", "StorageVec"])));
         }
 
-        // Check for L2-specific event optimization
-        if content.contains("emit!") || content.contains("log!") {
-            if !content.contains("indexed") {
-                vulnerabilities.push(Vulnerability {
-                    name: "Unoptimized Event Indexing".to_string(),
-                    severity: Severity::Low,
-                    risk_description: "Non-indexed events may increase gas costs and reduce searchability".to_string(),
-                    recommendation: "Use indexed parameters for searchable event data".to_string(),
-                });
+        for finding in gas_rules::solidity_gas_findings(content) {
+            match finding.rule {
+                GasRule::CalldataParameter => vulnerabilities.push(Vulnerability {
+                    name: "Memory Parameter Could Be Calldata".to_string(),
+                    severity: Severity::Info,
+                    risk_description: format!("{}; the copy costs ~{} gas per 32-byte word on every call", finding.message, GasRule::CalldataParameter.savings().0),
+                    recommendation: finding.suggestion,
+                    line: Some(finding.line),
+                    snippet: Some(finding.code),
+                    effort: Some(Effort::Quick),
+                    ..Default::default()
+                }),
+                GasRule::CacheStorageRead => {
+                    let Some(variable) = &finding.variable else { continue };
+                    vulnerabilities.push(Vulnerability {
+                        name: "Storage Read in Loop".to_string(),
+                        severity: Severity::Info,
+                        risk_description: format!("{}; each read is an SLOAD, ~{} gas more than a local", finding.message, GasRule::CacheStorageRead.savings().0),
+                        recommendation: finding.suggestion,
+                        line: Some(finding.line),
+                        example: Some(examples::cached_storage_read(content, &finding.code, variable)),
+                        effort: Some(Effort::Quick),
+                        ..Default::default()
+                    });
+                }
+                _ => {}
             }
         }
 
         // Stylus-specific patterns
-        if content.contains("stylus_sdk") {
+        if code.contains("stylus_sdk") {
             // Check for proper memory management
-            if !content.contains("prealloc") && (content.contains("Vec::new") || content.contains("String::new")) {
+            let preallocatable = preallocation_candidates(content);
+            for (line, var, pushes) in &preallocatable {
+                vulnerabilities.push(Vulnerability {
+                    name: "Non-preallocated Collections".to_string(),
+                    severity: Severity::Medium,
+                    risk_description: format!("`{}` starts as Vec::new() and then gets {} pushes, reallocating as it grows", var, pushes),
+                    recommendation: format!("Use Vec::with_capacity({})", pushes),
+                    line: Some(*line),
+                    snippet: Some(format!("let mut {} = Vec::new();", var)),
+                    ..Default::default()
+                });
+            }
+            if preallocatable.is_empty() && !code.contains("prealloc") && (code.contains("Vec::new") || code.contains("String::new")) {
                 vulnerabilities.push(Vulnerability {
                     name: "Non-preallocated Collections".to_string(),
                     severity: Severity::Medium,
                     risk_description: "Dynamic allocation in Stylus contracts can be expensive".to_string(),
                     recommendation: "Use preallocation for collections when size is known".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["Vec::new", "String::new"])));
             }
 
             // Check for cross-contract call optimization
-            if content.contains("call!") && !content.contains("multicall") {
+            if code.contains("call!") && !code.contains("multicall") {
                 vulnerabilities.push(Vulnerability {
                     name: "Unoptimized Cross-Contract Calls".to_string(),
                     severity: Severity::Medium,
                     risk_description: "Multiple separate calls increase L2 operation costs".to_string(),
                     recommendation: "Use multicall pattern for batching cross-contract interactions".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["call!"])));
             }
         }
 
@@ -85,4 +122,67 @@ impl AuditRule for L2OptimizationRule {
     fn name(&self) -> &'static str {
         "L2 Optimization Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-L2-GAS-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Calldata, storage packing and batching patterns that cost extra gas on L2"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Missing Batch Operations",
+            "Unoptimized Calldata",
+            "Unpacked Storage",
+            "Memory Parameter Could Be Calldata",
+            "Storage Read in Loop",
+            "Non-preallocated Collections",
+            "Unoptimized Cross-Contract Calls",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn suggest_fix(&self, finding: &Vulnerability, source: &str) -> Vec<TextEdit> {
+        mechanical_fix(finding, source).into_iter().collect()
+    }
+}
+
+fn mechanical_fix(finding: &Vulnerability, source: &str) -> Option<TextEdit> {
+    let line = finding.line?;
+    match finding.name.as_str() {
+        "Memory Parameter Could Be Calldata" => {
+            let param = finding.snippet.as_deref()?;
+            TextEdit::on_line(source, line, param, param.replacen(" memory ", " calldata ", 1))
+        }
+        "Non-preallocated Collections" => {
+            let (_, _, pushes) = preallocation_candidates(source).into_iter().find(|(l, _, _)| *l == line)?;
+            TextEdit::on_line(source, line, "Vec::new()", format!("Vec::with_capacity({})", pushes))
+        }
+        _ => None,
+    }
+}
+
+/// `let mut v = Vec::new();` followed directly by two or more `v.push(..);`
+/// lines, as (line, variable, number of pushes)
+fn preallocation_candidates(source: &str) -> Vec<(usize, String, usize)> {
+    let lines: Vec<&str> = source.lines().collect();
+    lines.iter().enumerate()
+        .filter_map(|(i, line)| {
+            let declaration = line.trim().strip_prefix("let mut ")?;
+            let (var, init) = declaration.split_once('=')?;
+            if init.trim() != "Vec::new();" {
+                return None;
+            }
+            // `let mut v: Vec<U256> = ...`
+            let var = var.split(':').next()?.trim();
+            let push = format!("{}.push(", var);
+            let pushes = lines[i + 1..].iter().take_while(|next| next.trim().starts_with(&push)).count();
+            (pushes >= 2).then(|| (i + 1, var.to_string(), pushes))
+        })
+        .collect()
 }
\ No newline at end of file
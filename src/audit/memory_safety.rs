@@ -1,93 +1,121 @@
-use crate::audit::vulnerabilities::{Vulnerability, Severity};
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
 use crate::audit::rules::AuditRule;
-use std::error::Error;
+use crate::audit::fix::TextEdit;
+use crate::audit::source::{find_line, find_lines, mask};
+use crate::error::Result;
 
 pub struct MemorySafetyRule;
 
 #[async_trait::async_trait]
 impl AuditRule for MemorySafetyRule {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
         // Check raw pointer usage
-        if content.contains("*mut") || content.contains("*const") {
+        for found in find_lines(content, &["*mut", "*const"]) {
             vulnerabilities.push(Vulnerability {
                 name: "Raw Pointer Usage".to_string(),
                 severity: Severity::High,
                 risk_description: "Raw pointers can lead to memory corruption and undefined behavior".to_string(),
                 recommendation: "Use safe alternatives like references or smart pointers".to_string(),
-            });
+                ..Default::default()
+            }.located(Some(found)));
         }
 
         // Check unsafe block usage
-        if content.contains("unsafe") && !content.contains("unsafe trait") {
+        for found in find_lines(content, &["unsafe"]).into_iter().filter(|found| !found.text.contains("unsafe trait")) {
             vulnerabilities.push(Vulnerability {
                 name: "Unsafe Block Usage".to_string(),
                 severity: Severity::Critical,
                 risk_description: "Unsafe blocks can bypass Rust's memory safety guarantees".to_string(),
                 recommendation: "Remove unsafe blocks or provide strong safety invariants".to_string(),
-            });
+                ..Default::default()
+            }.located(Some(found)));
         }
 
         // Check for potential memory leaks
-        if content.contains("Box::into_raw") || content.contains("ManuallyDrop") {
+        for found in find_lines(content, &["Box::into_raw", "ManuallyDrop"]) {
             vulnerabilities.push(Vulnerability {
                 name: "Potential Memory Leak".to_string(),
                 severity: Severity::High,
                 risk_description: "Memory leaks can cause resource exhaustion and contract failure".to_string(),
                 recommendation: "Ensure proper cleanup of resources and avoid manual memory management".to_string(),
-            });
+                ..Default::default()
+            }.located(Some(found)));
         }
 
         // Check for uninitialized memory usage
-        if content.contains("MaybeUninit") || content.contains("std::mem::uninitialized") {
+        for found in find_lines(content, &["MaybeUninit", "std::mem::uninitialized"]) {
             vulnerabilities.push(Vulnerability {
                 name: "Uninitialized Memory Usage".to_string(),
                 severity: Severity::Critical,
                 risk_description: "Using uninitialized memory leads to undefined behavior".to_string(),
                 recommendation: "Initialize all memory before use and avoid MaybeUninit when possible".to_string(),
-            });
+                ..Default::default()
+            }.located(Some(found)));
         }
 
         // Check for proper lifetime annotations
-        if content.contains("'static") && content.contains("&mut") {
+        if code.contains("'static") && code.contains("&mut") {
             vulnerabilities.push(Vulnerability {
                 name: "Suspicious Lifetime Usage".to_string(),
                 severity: Severity::Medium,
                 risk_description: "Improper lifetime usage can lead to memory safety issues".to_string(),
                 recommendation: "Review lifetime annotations and ensure they are necessary".to_string(),
-            });
+                ..Default::default()
+            }.located(find_line(content, &["'static"])));
         }
 
         // Stylus-specific memory checks
-        if content.contains("stylus_sdk") {
+        if code.contains("stylus_sdk") {
             // Check for large allocations
-            if content.contains("Vec::with_capacity") && content.contains(">1024") {
+            if code.contains("Vec::with_capacity") && code.contains(">1024") {
                 vulnerabilities.push(Vulnerability {
                     name: "Large Memory Allocation".to_string(),
                     severity: Severity::High,
                     risk_description: "Large memory allocations can cause contract execution failures".to_string(),
                     recommendation: "Use smaller, fixed-size allocations or paginate data".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["Vec::with_capacity"])));
             }
 
             // Check for proper storage usage
-            if content.contains("storage::") && !content.contains("try_") {
+            if code.contains("storage::") && !code.contains("try_") {
                 vulnerabilities.push(Vulnerability {
                     name: "Unchecked Storage Access".to_string(),
                     severity: Severity::Medium,
                     risk_description: "Storage operations without error handling may fail silently".to_string(),
                     recommendation: "Use try_ variants for storage operations and handle errors explicitly".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["storage::"])));
             }
 
             // Check for proper error handling in external calls
-            if content.contains("external::") && !content.contains("Result<") {
+            if code.contains("external::") && !code.contains("Result<") {
                 vulnerabilities.push(Vulnerability {
                     name: "Unchecked External Calls".to_string(),
                     severity: Severity::High,
                     risk_description: "External calls without proper error handling can lead to undefined state".to_string(),
                     recommendation: "Always use Result for external calls and handle all error cases".to_string(),
+                    ..Default::default()
+                }.located(find_line(content, &["external::"])));
+            }
+        }
+
+        // `&x.clone()` copies only to borrow the copy
+        if syn::parse_file(content).is_ok() {
+            for (line, expr) in borrowed_clones(content) {
+                let target = expr.trim_start_matches('&').trim_end_matches(".clone()").to_string();
+                vulnerabilities.push(Vulnerability {
+                    name: "Clone Immediately Borrowed".to_string(),
+                    severity: Severity::Low,
+                    risk_description: format!("`{}` clones {} only to take a reference to the copy, paying for an allocation the borrow doesn't need", expr, target),
+                    recommendation: format!("Borrow it directly: `&{}`", target),
+                    line: Some(line),
+                    snippet: Some(expr),
+                    effort: Some(Effort::Quick),
+                    ..Default::default()
                 });
             }
         }
@@ -98,4 +126,68 @@ impl AuditRule for MemorySafetyRule {
     fn name(&self) -> &'static str {
         "Memory Safety Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-MEMORY-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Unsafe blocks, raw pointers, leaks and oversized allocations in Rust contracts"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Raw Pointer Usage",
+            "Unsafe Block Usage",
+            "Potential Memory Leak",
+            "Uninitialized Memory Usage",
+            "Suspicious Lifetime Usage",
+            "Large Memory Allocation",
+            "Unchecked Storage Access",
+            "Unchecked External Calls",
+            "Clone Immediately Borrowed",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
+
+    fn suggest_fix(&self, finding: &Vulnerability, source: &str) -> Vec<TextEdit> {
+        remove_clone(finding, source).into_iter().collect()
+    }
+}
+
+fn remove_clone(finding: &Vulnerability, source: &str) -> Option<TextEdit> {
+    if finding.name != "Clone Immediately Borrowed" {
+        return None;
+    }
+    let expr = finding.snippet.as_deref()?;
+    TextEdit::on_line(source, finding.line?, expr, expr.trim_end_matches(".clone()"))
+}
+
+/// `&path.to.value.clone()` expressions, with their 1-based line
+fn borrowed_clones(source: &str) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        if line.trim_start().starts_with("//") {
+            continue;
+        }
+        for (end, _) in line.match_indices(".clone()") {
+            let path_start = line[..end]
+                .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .map_or(0, |j| j + 1);
+            let before = &line[..path_start];
+            // `a && b.clone()` is a boolean and, not a borrow
+            if path_start == end || !before.ends_with('&') || before.ends_with("&&") {
+                continue;
+            }
+            found.push((i + 1, line[path_start - 1..end + ".clone()".len()].to_string()));
+        }
+    }
+    found
 }
\ No newline at end of file
@@ -1,117 +1,698 @@
-use std::path::PathBuf;
-use std::error::Error;
-use std::sync::RwLock;
-use crate::analyzer::Analyzer;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use crate::error::{AnalyzerError, Result};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tracing::{debug, info};
+use tokio_util::sync::CancellationToken;
+use crate::analyzer::{Analysis, Analyzer};
+use crate::analyzer::output::Finding;
+use crate::cancel::run_or_cancel;
+use crate::input::{LineWindows, STREAMING_THRESHOLD_BYTES};
+use crate::parser::ParsedContract;
 
 pub mod patterns;
+pub mod baseline;
+pub mod command;
+pub(crate) mod dedupe;
+pub mod effort;
+pub mod findings_db;
+pub(crate) mod fingerprint;
+pub(crate) mod examples;
+pub mod fix;
+pub mod triage;
 pub mod rules;
+pub(crate) mod source;
 pub mod report;
+pub mod sarif;
+pub(crate) mod standards;
 pub mod vulnerabilities;
-pub mod ai_patterns;
-pub mod memory_safety;
-pub mod l2_patterns;
-pub mod access_control;
-pub mod test_patterns;
+pub(crate) mod ai_patterns;
+pub(crate) mod memory_safety;
+pub(crate) mod l2_patterns;
+pub(crate) mod access_control;
+pub(crate) mod arithmetic;
+pub mod authority;
+pub(crate) mod bridge;
+pub(crate) mod custom;
+pub(crate) mod delegatecall;
+pub mod plugin;
+pub mod erc;
+pub(crate) mod erc20;
+pub(crate) mod events;
+pub(crate) mod event_coverage;
+pub(crate) mod event_emission;
+pub(crate) mod reentrancy;
+pub(crate) mod signature;
+pub(crate) mod modifiers;
+pub(crate) mod oracle;
+pub(crate) mod origin;
+pub(crate) mod panics;
+pub(crate) mod payable;
+pub(crate) mod assembly;
+pub(crate) mod initializer;
+pub(crate) mod input_validation;
+pub(crate) mod construction;
+pub(crate) mod timelock;
+pub(crate) mod timing;
+pub mod upgrade_safety;
+pub(crate) mod storage_gap;
+pub(crate) mod storage_growth;
+pub(crate) mod solc_version;
+pub(crate) mod test_patterns;
 
-use vulnerabilities::{Vulnerability, Severity};
+use vulnerabilities::{Vulnerability, Severity, Effort};
 use rules::AuditRule;
 use report::generate_full_report;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct AuditResult {
     pub critical_vulnerabilities: Vec<Vulnerability>,
     pub high_vulnerabilities: Vec<Vulnerability>,
     pub medium_vulnerabilities: Vec<Vulnerability>,
     pub low_vulnerabilities: Vec<Vulnerability>,
+    pub info_findings: Vec<Vulnerability>,
+    /// Rules whose check failed; their findings are missing from this result
+    pub errors: Vec<RuleError>,
+    /// Set when the run was cancelled before every rule finished
+    pub interrupted: bool,
+    /// Ids of the rules that ran, in the order they ran
+    pub rules_run: Vec<String>,
+    /// Ids of the rules `--only-rules`, `--skip-rules` or the config file turned off
+    pub rules_skipped: Vec<String>,
+    /// Wall time of each rule that finished, in registration order
+    pub timings: Vec<RuleTiming>,
+    /// Set with `--timings`, which prints the timings in the report
+    pub profile: Option<AuditProfile>,
+    /// Present when findings were compared against a `--baseline` snapshot
+    pub baseline: Option<baseline::BaselineSummary>,
+    /// Present when findings were merged into a `--import-db`/`--export-db` database
+    pub triage: Option<findings_db::TriageSummary>,
+    /// Privileged functions and who can call them; empty for streamed large files
+    pub authority: Vec<authority::Authority>,
+    /// Token standard conformance, when one was requested or detected; `None` for streamed large files
+    pub conformance: Option<erc::Conformance>,
+}
+
+impl AuditResult {
+    /// Fails with the names of the rules that didn't run, for `--strict-rules`
+    pub fn require_all_rules(&self) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        Err(AnalyzerError::Rule {
+            rule: self.errors.iter().map(|e| e.rule.as_str()).collect::<Vec<_>>().join(", "),
+            message: "failed to run (--strict-rules)".to_string(),
+        })
+    }
+
+    /// Files a finding into the bucket matching its severity
+    pub fn push(&mut self, vuln: Vulnerability) {
+        match vuln.severity {
+            Severity::Critical => self.critical_vulnerabilities.push(vuln),
+            Severity::High => self.high_vulnerabilities.push(vuln),
+            Severity::Medium => self.medium_vulnerabilities.push(vuln),
+            Severity::Low => self.low_vulnerabilities.push(vuln),
+            Severity::Info => self.info_findings.push(vuln),
+        }
+    }
+
+    /// All findings, most severe first
+    pub fn vulnerabilities(&self) -> impl Iterator<Item = &Vulnerability> {
+        self.critical_vulnerabilities.iter()
+            .chain(&self.high_vulnerabilities)
+            .chain(&self.medium_vulnerabilities)
+            .chain(&self.low_vulnerabilities)
+            .chain(&self.info_findings)
+    }
+
+    /// All findings, most severe first, without the rest of the result
+    pub fn into_vulnerabilities(self) -> Vec<Vulnerability> {
+        let mut all = self.critical_vulnerabilities;
+        all.extend(self.high_vulnerabilities);
+        all.extend(self.medium_vulnerabilities);
+        all.extend(self.low_vulnerabilities);
+        all.extend(self.info_findings);
+        all
+    }
+
+    /// Findings per rule, most findings first: (rule id, rule name, count,
+    /// highest severity). Merged findings count for the rule that kept them
+    pub fn rule_breakdown(&self) -> Vec<(String, String, usize, Severity)> {
+        let mut rows: Vec<(String, String, usize, Severity)> = Vec::new();
+        for vuln in self.vulnerabilities() {
+            let id = vuln.rule_id.clone().unwrap_or_default();
+            match rows.iter_mut().find(|row| row.0 == id) {
+                Some(row) => {
+                    row.2 += 1;
+                    if !row.3.at_least(vuln.severity) {
+                        row.3 = vuln.severity;
+                    }
+                }
+                None => rows.push((id, vuln.rule.clone().unwrap_or_default(), 1, vuln.severity)),
+            }
+        }
+        // Stable, so rules with as many findings keep report order
+        rows.sort_by_key(|row| std::cmp::Reverse(row.2));
+        rows
+    }
+
+    /// Removes every finding for which `keep` returns false
+    pub fn retain(&mut self, mut keep: impl FnMut(&Vulnerability) -> bool) {
+        self.critical_vulnerabilities.retain(&mut keep);
+        self.high_vulnerabilities.retain(&mut keep);
+        self.medium_vulnerabilities.retain(&mut keep);
+        self.low_vulnerabilities.retain(&mut keep);
+        self.info_findings.retain(&mut keep);
+    }
+}
+
+/// A rule that failed to run, kept so the report can flag the gap in coverage
+#[derive(Debug, Clone)]
+pub struct RuleError {
+    pub rule: String,
+    pub message: String,
+}
+
+/// How long one rule took, for `--timings`
+#[derive(Debug, Clone)]
+pub struct RuleTiming {
+    pub rule_id: String,
+    pub rule: String,
+    pub elapsed: Duration,
+    /// Findings the rule reported, before duplicates across rules were merged
+    pub findings: usize,
+}
+
+/// Wall times `--timings` adds to the report
+#[derive(Debug, Clone, Copy)]
+pub struct AuditProfile {
+    /// The whole audit, from the first rule starting to the last finishing
+    pub total: Duration,
+    /// One Solidity or Rust parse of the source, which each rule that reads
+    /// the syntax tree pays again; None for streamed large files
+    pub parse: Option<Duration>,
 }
 
 pub struct AuditAnalyzer {
-    rules: RwLock<Vec<Box<dyn AuditRule>>>,
+    /// Shared so an audit runs from a snapshot and several audits can run at once
+    rules: RwLock<Vec<Arc<dyn AuditRule>>>,
+    cancel: CancellationToken,
+    effort_overrides: HashMap<String, Effort>,
+    standard: Option<erc::Standard>,
+    selection: patterns::RuleSelection,
+    skipped: Vec<String>,
+    dedupe: bool,
+    ai: bool,
+}
+
+impl Default for AuditAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AuditAnalyzer {
     pub fn new() -> Self {
         Self {
             rules: RwLock::new(Vec::new()),
+            cancel: CancellationToken::new(),
+            effort_overrides: HashMap::new(),
+            standard: None,
+            selection: patterns::RuleSelection::default(),
+            skipped: Vec::new(),
+            dedupe: true,
+            ai: true,
         }
     }
 
+    /// Stops running further rules once `token` is cancelled; findings from
+    /// completed rules are still reported.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Replaces the default remediation effort of the named rules
+    pub fn with_effort_overrides(mut self, overrides: HashMap<String, Effort>) -> Self {
+        self.effort_overrides = overrides;
+        self
+    }
+
+    /// Checks conformance to `standard` instead of detecting it from function names
+    pub fn with_standard(mut self, standard: Option<erc::Standard>) -> Self {
+        self.standard = standard;
+        self
+    }
+
+    /// Reports findings at the severities `selection` sets. Which rules run
+    /// is up to whoever adds them; `skipped` are the ids of those it left out,
+    /// listed in every result
+    pub fn with_rule_selection(mut self, selection: patterns::RuleSelection, skipped: Vec<String>) -> Self {
+        self.selection = selection;
+        self.skipped = skipped;
+        self
+    }
+
+    /// Whether findings several rules report for one problem are merged; on by default
+    pub fn with_dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Whether `audit::command` adds the AI-backed analyses; on by default
+    pub fn with_ai(mut self, ai: bool) -> Self {
+        self.ai = ai;
+        self
+    }
+
     pub fn add_rule(&self, rule: Box<dyn AuditRule>) {
-        self.rules.write().unwrap().push(rule);
+        self.rules.write().unwrap().push(Arc::from(rule));
     }
-}
 
-#[async_trait::async_trait]
-impl Analyzer for AuditAnalyzer {
-    async fn analyze(&self, file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let content = std::fs::read_to_string(file).map_err(|e| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to read file: {}", e)
-            )) as Box<dyn Error + Send + Sync>
-        })?;
-
-        let mut audit_result = AuditResult {
-            critical_vulnerabilities: Vec::new(),
-            high_vulnerabilities: Vec::new(),
-            medium_vulnerabilities: Vec::new(),
-            low_vulnerabilities: Vec::new(),
-        };
-
-        // Get all rules first
-        let rules = {
-            let guard = self.rules.read().map_err(|e| {
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to acquire read lock: {}", e)
-                )) as Box<dyn Error + Send + Sync>
-            })?;
-            guard.iter().map(|rule| rule.name().to_string()).collect::<Vec<_>>()
-        };
-
-        // Process each rule individually with improved error handling
-        for rule_name in rules {
-            let mut rule = {
-                let mut guard = self.rules.write().map_err(|e| {
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Failed to acquire write lock: {}", e)
-                    )) as Box<dyn Error + Send + Sync>
-                })?;
-                let idx = guard.iter().position(|r| r.name() == rule_name).ok_or_else(|| {
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Rule '{}' not found", rule_name)
-                    )) as Box<dyn Error + Send + Sync>
-                })?;
-                guard.swap_remove(idx)
-            };
-
-            match rule.check(&content).await {
-                Ok(vulnerabilities) => {
-                    for vuln in vulnerabilities {
-                        match vuln.severity {
-                            Severity::Critical => audit_result.critical_vulnerabilities.push(vuln),
-                            Severity::High => audit_result.high_vulnerabilities.push(vuln),
-                            Severity::Medium => audit_result.medium_vulnerabilities.push(vuln),
-                            Severity::Low => audit_result.low_vulnerabilities.push(vuln),
+    /// Names of the registered rules, in registration order
+    pub fn rule_names(&self) -> Vec<String> {
+        self.rules.read().unwrap().iter().map(|rule| rule.name().to_string()).collect()
+    }
+
+    /// Audits `content` as [`audit_source`](Self::audit_source) does, with
+    /// findings fingerprinted as belonging to no particular file. Needs a
+    /// tokio runtime
+    pub async fn audit(&self, content: &str) -> Result<AuditResult> {
+        self.audit_source(content, "").await
+    }
+
+    /// Runs every registered rule against `content`, concurrently, and
+    /// collects the findings by severity in rule registration order. Rule
+    /// failures are recorded in `AuditResult::errors` rather than aborting
+    /// the audit. Audits of different sources may run concurrently on one
+    /// analyzer
+    async fn run_rules(&self, content: &str) -> Result<AuditResult> {
+        let mut audit_result = AuditResult { rules_skipped: self.skipped.clone(), ..Default::default() };
+
+        // Rules registered while this audit runs apply from the next one
+        let rules = self.rules.read().map_err(|e| lock_error("*", e))?.clone();
+        if self.cancel.is_cancelled() {
+            audit_result.interrupted = true;
+            return Ok(audit_result);
+        }
+
+        // Each rule is its own task so CPU-bound checks spread over the
+        // runtime's worker threads; dropping the set aborts any still running
+        let content: Arc<str> = Arc::from(content);
+        let mut tasks = JoinSet::new();
+        let mut task_rule = HashMap::new();
+        for (index, rule) in rules.iter().enumerate() {
+            let rule = Arc::clone(rule);
+            let content = Arc::clone(&content);
+            let cancel = self.cancel.clone();
+            let task = tasks.spawn(async move {
+                let started = Instant::now();
+                let outcome = run_or_cancel(&cancel, rule.check(&content)).await;
+                (outcome, started.elapsed())
+            });
+            task_rule.insert(task.id(), index);
+        }
+
+        // (rule index, findings or the failure message, wall time); None when cancelled
+        let mut outcomes = Vec::with_capacity(rules.len());
+        while let Some(joined) = tasks.join_next_with_id().await {
+            match joined {
+                Ok((id, (outcome, elapsed))) => {
+                    outcomes.push((task_rule[&id], outcome.map(|found| found.map_err(|e| e.to_string())), elapsed));
+                }
+                Err(e) => {
+                    let message = if e.is_panic() { "the rule panicked" } else { "the rule was aborted" };
+                    outcomes.push((task_rule[&e.id()], Some(Err(message.to_string())), Duration::ZERO));
+                }
+            }
+        }
+        outcomes.sort_by_key(|(index, ..)| *index);
+
+        for (index, outcome, elapsed) in outcomes {
+            let mut findings = 0;
+            let rule = &rules[index];
+            let rule_name = rule.name().to_string();
+            let rule_id = rule.id();
+            let effort = self.effort_overrides.get(&rule_name).copied()
+                .unwrap_or_else(|| rule.default_effort());
+            let confidence = rule.default_confidence();
+
+            match outcome {
+                None => {
+                    debug!(rule = %rule_name, elapsed_ms = elapsed.as_millis() as u64, "rule cancelled");
+                    audit_result.interrupted = true;
+                    continue;
+                }
+                Some(Ok(vulnerabilities)) => {
+                    debug!(rule = %rule_name, elapsed_ms = elapsed.as_millis() as u64, findings = vulnerabilities.len(), "rule finished");
+                    findings = vulnerabilities.len();
+                    for mut vuln in vulnerabilities {
+                        vuln.rule.get_or_insert_with(|| rule_name.clone());
+                        vuln.rule_id.get_or_insert_with(|| rule_id.to_string());
+                        vuln.effort.get_or_insert(effort);
+                        vuln.confidence.get_or_insert(confidence);
+                        if vuln.references.is_empty() {
+                            vuln.references = standards::for_finding(&vuln.name);
                         }
+                        if let Some(severity) = self.selection.severity_for(&vuln) {
+                            vuln.severity = severity;
+                        }
+                        audit_result.push(vuln);
                     }
                 }
-                Err(e) => {
-                    eprintln!("Error running rule {}: {}", rule_name, e);
+                Some(Err(e)) => {
+                    debug!(rule = %rule_name, elapsed_ms = elapsed.as_millis() as u64, error = %e, "rule failed");
+                    audit_result.errors.push(RuleError {
+                        rule: rule_name.clone(),
+                        message: e,
+                    });
                 }
             }
+            audit_result.rules_run.push(rule_id.to_string());
+            audit_result.timings.push(RuleTiming { rule_id: rule_id.to_string(), rule: rule_name, elapsed, findings });
+        }
+        if self.dedupe {
+            dedupe::merge(&mut audit_result);
+        }
+
+        Ok(audit_result)
+    }
 
-            // Put the rule back
-            self.rules.write().map_err(|e| {
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to acquire write lock: {}", e)
-                )) as Box<dyn Error + Send + Sync>
-            })?.push(rule);
+    /// Asks each finding's rule for a mechanical fix against `source`
+    pub fn plan_fixes(&self, result: &AuditResult, source: &str) -> Result<fix::FixPlan> {
+        let rules = self.rules.read().map_err(|e| lock_error("*", e))?;
+        let mut plan = fix::FixPlan::default();
+        for vuln in result.vulnerabilities() {
+            let rule = rules.iter().find(|rule| vuln.rule.as_deref() == Some(rule.name()));
+            let edits = rule.map(|rule| rule.suggest_fix(vuln, source)).unwrap_or_default();
+            if edits.is_empty() {
+                plan.manual.push(vuln.clone());
+            } else {
+                plan.fixes.push((vuln.clone(), edits));
+            }
         }
+        Ok(plan)
+    }
 
-        Ok(generate_full_report(&audit_result))
+    /// Runs the rules on `content`, then adds token standard conformance,
+    /// fingerprints the findings as belonging to `path`, sizes their effort
+    /// and lists who may call the privileged functions
+    pub async fn audit_source(&self, content: &str, path: &str) -> Result<AuditResult> {
+        let mut result = self.run_rules(content).await?;
+        if let Some(conformance) = erc::check(content, self.standard)? {
+            for mut vuln in conformance.findings() {
+                if let Some(severity) = self.selection.severity_for(&vuln) {
+                    vuln.severity = severity;
+                }
+                result.push(vuln);
+            }
+            result.conformance = Some(conformance);
+        }
+        fingerprint::assign(&mut result, path, content);
+        effort::adjust_for_function_size(&mut result, content);
+        result.authority = authority::enumerate(content);
+        Ok(result)
     }
-}
\ No newline at end of file
+
+    /// Audits a file on disk. Files above `STREAMING_THRESHOLD_BYTES` are
+    /// scanned in overlapping line windows so peak memory stays bounded.
+    pub async fn audit_file(&self, file: &Path) -> Result<AuditResult> {
+        let size = std::fs::metadata(file)?.len();
+        if size <= STREAMING_THRESHOLD_BYTES {
+            let content = std::fs::read_to_string(file)?;
+            return self.audit_source(&content, &file.display().to_string()).await;
+        }
+
+        info!("📦 Large input ({} bytes) - auditing in streaming mode...", size);
+
+        let mut merged = AuditResult::default();
+        let mut seen_findings = HashSet::new();
+        let mut seen_errors = HashSet::new();
+
+        let mut windows = LineWindows::open(file)?;
+        let mut reported_percent = 0;
+        while let Some(window) = windows.next() {
+            let (first_line, window) = window?;
+            let result = self.run_rules(&window).await?;
+            merge_window(&mut merged, result, first_line, &mut seen_findings, &mut seen_errors);
+
+            let percent = windows.bytes_read() * 100 / size.max(1);
+            if percent >= reported_percent + 10 {
+                info!("⏳ Scanned {}%", percent);
+                reported_percent = percent - percent % 10;
+            }
+            if merged.interrupted {
+                break;
+            }
+        }
+
+        // The source isn't kept in memory here, so enclosing functions can't be looked up
+        fingerprint::assign(&mut merged, &file.display().to_string(), "");
+        Ok(merged)
+    }
+}
+
+/// Folds the result for the window starting at file line `first_line` into the
+/// running total, moving its lines from the window to the file. A rule reporting
+/// the same line twice saw it in the overlap of two windows, and a whole-file
+/// heuristic without a line fires once per window, so those are kept once.
+fn merge_window(
+    merged: &mut AuditResult,
+    window: AuditResult,
+    first_line: usize,
+    seen_findings: &mut HashSet<(Option<String>, Option<usize>, String)>,
+    seen_errors: &mut HashSet<String>,
+) {
+    let buckets = [
+        (window.critical_vulnerabilities, &mut merged.critical_vulnerabilities),
+        (window.high_vulnerabilities, &mut merged.high_vulnerabilities),
+        (window.medium_vulnerabilities, &mut merged.medium_vulnerabilities),
+        (window.low_vulnerabilities, &mut merged.low_vulnerabilities),
+        (window.info_findings, &mut merged.info_findings),
+    ];
+    for (found, bucket) in buckets {
+        for mut vuln in found {
+            vuln.line = vuln.line.map(|line| line + first_line - 1);
+            if seen_findings.insert((vuln.rule_id.clone(), vuln.line, vuln.name.clone())) {
+                bucket.push(vuln);
+            }
+        }
+    }
+
+    for error in window.errors {
+        if seen_errors.insert(error.rule.clone()) {
+            merged.errors.push(error);
+        }
+    }
+
+    for rule in window.rules_run {
+        if !merged.rules_run.contains(&rule) {
+            merged.rules_run.push(rule);
+        }
+    }
+    merged.rules_skipped = window.rules_skipped;
+    for timing in window.timings {
+        match merged.timings.iter_mut().find(|merged| merged.rule_id == timing.rule_id) {
+            Some(merged) => {
+                merged.elapsed += timing.elapsed;
+                merged.findings += timing.findings;
+            }
+            None => merged.timings.push(timing),
+        }
+    }
+    merged.interrupted |= window.interrupted;
+}
+
+fn lock_error(rule: &str, e: impl std::fmt::Display) -> AnalyzerError {
+    AnalyzerError::Rule {
+        rule: rule.to_string(),
+        message: format!("rule registry lock poisoned: {}", e),
+    }
+}
+
+#[async_trait::async_trait]
+impl Analyzer for AuditAnalyzer {
+    async fn analyze(&self, file: &Path) -> Result<Analysis> {
+        let audit_result = self.audit_file(file).await?;
+        Ok(audit_analysis(&audit_result, generate_full_report(&audit_result)))
+    }
+
+    async fn analyze_parsed(&self, contract: &ParsedContract, file: &Path) -> Result<Analysis> {
+        let audit_result = self.audit_source(&contract.source, &file.display().to_string()).await?;
+        Ok(audit_analysis(&audit_result, generate_full_report(&audit_result)))
+    }
+}
+
+/// `result` as a structured analysis, rendered as `text` on the terminal
+pub fn audit_analysis(result: &AuditResult, text: String) -> Analysis {
+    let mut analysis = Analysis::new("audit", text)
+        .with_findings(result.vulnerabilities().map(Finding::from).collect())
+        .with_metric("rules_failed", result.errors.len())
+        .with_metric("rules_run", result.rules_run.clone())
+        .with_metric("rules_skipped", result.rules_skipped.clone())
+        .with_metric("findings_by_rule", result.rule_breakdown().into_iter()
+            .map(|(id, _, count, _)| (id, serde_json::Value::from(count)))
+            .collect::<serde_json::Map<_, _>>());
+    if let Some(summary) = &result.baseline {
+        analysis = analysis.with_metric("baseline_suppressed", summary.suppressed.len());
+    }
+    if let Some(profile) = &result.profile {
+        let millis = |duration: Duration| (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0;
+        let mut rules: Vec<&RuleTiming> = result.timings.iter().collect();
+        rules.sort_by_key(|timing| std::cmp::Reverse(timing.elapsed));
+        analysis = analysis.with_metric("timings", serde_json::json!({
+            "total_ms": millis(profile.total),
+            "parse_ms": profile.parse.map(millis),
+            "rules": rules.into_iter().map(|timing| serde_json::json!({
+                "rule_id": timing.rule_id,
+                "rule": timing.rule,
+                "ms": millis(timing.elapsed),
+                "findings": timing.findings,
+            })).collect::<Vec<_>>(),
+        }));
+    }
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails, panics or finds one issue, depending on its name
+    struct Scripted(&'static str);
+
+    #[async_trait::async_trait]
+    impl AuditRule for Scripted {
+        async fn check(&self, _content: &str) -> Result<Vec<Vulnerability>> {
+            match self.0 {
+                "Failing Rule" => Err(AnalyzerError::Parse("unexpected token".to_string())),
+                "Panicking Rule" => panic!("rule bug"),
+                _ => Ok(vec![Vulnerability { name: "Scripted Issue".to_string(), severity: Severity::High, ..Default::default() }]),
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "Test rule"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::High
+        }
+    }
+
+    /// Reports every line holding `MARKER`, at its line in what it was given
+    struct Marker;
+
+    #[async_trait::async_trait]
+    impl AuditRule for Marker {
+        async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+            Ok(content.lines().enumerate()
+                .filter(|(_, line)| line.contains("MARKER"))
+                .map(|(i, _)| Vulnerability { name: "Marker".to_string(), severity: Severity::Low, line: Some(i + 1), ..Default::default() })
+                .collect())
+        }
+
+        fn name(&self) -> &'static str {
+            "Marker Rule"
+        }
+
+        fn id(&self) -> &'static str {
+            "TEST-MARKER-001"
+        }
+
+        fn description(&self) -> &'static str {
+            "Test rule"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Low
+        }
+    }
+
+    fn analyzer(rules: &[&'static str]) -> AuditAnalyzer {
+        let analyzer = AuditAnalyzer::new();
+        for name in rules {
+            analyzer.add_rule(Box::new(Scripted(name)));
+        }
+        analyzer
+    }
+
+    #[tokio::test]
+    async fn failed_rules_are_reported_without_losing_other_findings() {
+        let result = analyzer(&["Failing Rule", "Finding Rule", "Panicking Rule"]).audit("contract C {}").await.unwrap();
+
+        assert_eq!(result.high_vulnerabilities.len(), 1);
+        assert_eq!(result.high_vulnerabilities[0].rule.as_deref(), Some("Finding Rule"));
+        let failed: Vec<(&str, &str)> = result.errors.iter().map(|e| (e.rule.as_str(), e.message.as_str())).collect();
+        assert_eq!(failed, [
+            ("Failing Rule", "Failed to parse contract: unexpected token"),
+            ("Panicking Rule", "the rule panicked"),
+        ]);
+
+        let report = generate_full_report(&result);
+        assert!(report.contains("Rules that failed to run (2)") && report.contains("Findings from these rules are missing"));
+    }
+
+    #[tokio::test]
+    async fn an_empty_result_with_failures_is_not_reported_clean() {
+        let result = analyzer(&["Failing Rule"]).audit("contract C {}").await.unwrap();
+        let report = generate_full_report(&result);
+
+        assert!(!report.contains("No vulnerabilities found!"));
+        assert!(report.contains("No vulnerabilities found by the rules that ran"));
+    }
+
+    #[tokio::test]
+    async fn audit_runs_the_whole_pipeline() {
+        let result = analyzer(&["Finding Rule"]).audit("contract C {}").await.unwrap();
+        let vuln = &result.high_vulnerabilities[0];
+        assert!(!vuln.fingerprint.is_empty() && vuln.effort.is_some());
+        let fingerprinted = analyzer(&["Finding Rule"]).audit_source("contract C {}", "").await.unwrap();
+        assert_eq!(fingerprinted.high_vulnerabilities[0].fingerprint, vuln.fingerprint);
+    }
+
+    #[tokio::test]
+    async fn strict_rules_fails_only_when_a_rule_failed() {
+        let clean = analyzer(&["Finding Rule"]).audit("contract C {}").await.unwrap();
+        assert!(clean.require_all_rules().is_ok());
+
+        let failed = analyzer(&["Failing Rule", "Panicking Rule"]).audit("contract C {}").await.unwrap();
+        let error = failed.require_all_rules().unwrap_err();
+        assert_eq!(error.to_string(), "Audit rule 'Failing Rule, Panicking Rule' failed: failed to run (--strict-rules)");
+        assert_eq!(error.exit_code(), 6);
+    }
+
+    #[tokio::test]
+    async fn a_streamed_file_reports_lines_in_the_file() {
+        // 5 MB of 50-byte lines, so about 50 windows; line 1990 is in the
+        // overlap of the first two
+        let markers = [1, 1990, 2100, 50_000, 104_000];
+        let source: String = (1..=104_000)
+            .map(|line| if markers.contains(&line) { format!("// MARKER {:<38}\n", line) } else { format!("let filler_{:<38};\n", line) })
+            .collect();
+        assert!(source.len() > 5 * 1024 * 1024);
+        let file = std::env::temp_dir().join(format!("streamed-{}.rs", std::process::id()));
+        std::fs::write(&file, &source).unwrap();
+
+        let limits = |force_large| crate::input::InputLimits { max_bytes: crate::input::DEFAULT_MAX_INPUT_BYTES, force_large };
+        assert!(limits(false).check(&file).is_err());
+        assert!(limits(true).check(&file).is_ok());
+        let analyzer = AuditAnalyzer::new();
+        analyzer.add_rule(Box::new(Marker));
+        let result = analyzer.audit_file(&file).await;
+        std::fs::remove_file(&file).unwrap();
+
+        let lines: Vec<usize> = result.unwrap().vulnerabilities().map(|vuln| vuln.line.unwrap()).collect();
+        assert_eq!(lines, markers);
+    }
+}
@@ -0,0 +1,237 @@
+use solang_parser::pt::{CodeLocation, ContractDefinition, ContractPart, ContractTy, FunctionDefinition, FunctionTy, SourceUnitPart};
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{assigns, is_externally_callable, line_of, modifier_names, slice, state_variables};
+use crate::audit::timelock::classify;
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Solidity modifiers that don't do what they look like: no `_;` placeholder,
+/// checks that can't fail, and access modifiers nothing uses
+pub struct ModifierRule;
+
+/// Words in a modifier name that say it restricts the caller
+const ACCESS_HINTS: [&str; 6] = ["only", "auth", "admin", "owner", "role", "governance"];
+
+/// Conditions that hold for every call
+const TRIVIAL_CONDITIONS: [&str; 5] = [
+    "true",
+    "msg.sender==msg.sender",
+    "msg.sender!=address(0)",
+    "tx.origin!=address(0)",
+    "1==1",
+];
+
+#[async_trait]
+impl AuditRule for ModifierRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Ok((unit, _)) = solang_parser::parse(content, 0) else { return Ok(Vec::new()) };
+        let contracts: Vec<&ContractDefinition> = unit.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) if !matches!(contract.ty, ContractTy::Interface(_)) => Some(contract.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        // Modifiers are inherited, so uses are counted across every contract in the file
+        let applied: Vec<String> = contracts.iter()
+            .flat_map(|contract| functions(contract))
+            .filter(|func| func.ty != FunctionTy::Modifier)
+            .flat_map(modifier_names)
+            .collect();
+
+        let mut vulnerabilities = Vec::new();
+        for contract in &contracts {
+            for modifier in functions(contract).filter(|func| func.ty == FunctionTy::Modifier) {
+                check_modifier(contract, modifier, &applied, content, &mut vulnerabilities);
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Modifier Correctness Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-MODIFIER-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Modifiers without `_;`, with checks that can't fail, or never used"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Modifier Missing Placeholder",
+            "Modifier Checks After Placeholder",
+            "Trivially True Modifier Check",
+            "Unused Access Modifier",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+fn functions(contract: &ContractDefinition) -> impl Iterator<Item = &FunctionDefinition> {
+    contract.parts.iter().filter_map(|part| match part {
+        ContractPart::FunctionDefinition(func) => Some(func.as_ref()),
+        _ => None,
+    })
+}
+
+fn is_access_modifier(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    ACCESS_HINTS.iter().any(|hint| lower.contains(hint))
+}
+
+/// Byte offset of the `_;` placeholder in whitespace-free `body`
+fn placeholder(compact: &str) -> Option<usize> {
+    compact.match_indices("_;").map(|(i, _)| i).find(|&i| {
+        !compact[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.')
+    })
+}
+
+fn check_modifier(
+    contract: &ContractDefinition,
+    modifier: &FunctionDefinition,
+    applied: &[String],
+    source: &str,
+    vulnerabilities: &mut Vec<Vulnerability>,
+) {
+    let Some(name) = modifier.name.as_ref().map(|n| n.name.as_str()) else { return };
+    let Some(body) = modifier.body.as_ref().map(|b| slice(&b.loc(), source)) else { return };
+    let compact: String = body.split_whitespace().collect();
+    let line = line_of(&modifier.loc, source);
+
+    let checks: Vec<(usize, &str)> = ["require(", "revert", "assert(", "if("].iter()
+        .flat_map(|kw| compact.match_indices(kw).map(|(i, _)| (i, *kw)))
+        .collect();
+
+    match placeholder(&compact) {
+        None => vulnerabilities.push(finding(
+            "Modifier Missing Placeholder",
+            Severity::High,
+            format!("{} has no `_;`, so the body of every function it guards is skipped and calls succeed without doing anything", name),
+            "Add `_;` after the checks",
+            name,
+            line,
+        )),
+        // `_; require(...)` runs the function first and only then checks the caller
+        Some(at) if !checks.is_empty() && checks.iter().all(|(i, _)| *i > at) => vulnerabilities.push(finding(
+            "Modifier Checks After Placeholder",
+            Severity::Medium,
+            format!("{} runs the guarded function before its check; external calls in the function happen before the caller is validated", name),
+            "Move the checks before `_;`",
+            name,
+            line,
+        )),
+        Some(_) => {}
+    }
+
+    let trivial = compact.match_indices("require(").chain(compact.match_indices("assert("))
+        .filter_map(|(i, kw)| {
+            let args = &compact[i + kw.len()..];
+            let condition = args.split([',', ')']).next()?;
+            // `require(msg.sender != address(0))` splits inside `address(0)`
+            let condition = if condition.ends_with("address(0") { &args[..condition.len() + 1] } else { condition };
+            TRIVIAL_CONDITIONS.iter().any(|t| condition == *t || condition.ends_with(&format!("||{}", t))).then_some(condition)
+        })
+        .next();
+    if let Some(condition) = trivial {
+        vulnerabilities.push(finding(
+            "Trivially True Modifier Check",
+            Severity::Medium,
+            format!("{} checks `{}`, which holds for every caller; the modifier restricts nothing", name, condition),
+            "Compare msg.sender against the stored owner or role instead",
+            name,
+            line,
+        ));
+    } else if checks.is_empty() && is_access_modifier(name) {
+        vulnerabilities.push(finding(
+            "Trivially True Modifier Check",
+            Severity::Medium,
+            format!("{} looks like an access modifier but has no require, revert or if; every caller passes", name),
+            "Check msg.sender against the stored owner or role before `_;`",
+            name,
+            line,
+        ));
+    }
+
+    if is_access_modifier(name) && !applied.iter().any(|m| m == name) {
+        let candidates = unguarded_privileged(contract, source);
+        let hint = if candidates.is_empty() {
+            String::new()
+        } else {
+            format!("; likely candidates: {}", candidates.join(", "))
+        };
+        vulnerabilities.push(finding(
+            "Unused Access Modifier",
+            Severity::Medium,
+            format!("{} is defined but no function uses it{}", name, hint),
+            &format!("Apply {} to the privileged functions, or remove it if access is enforced elsewhere", name),
+            name,
+            line,
+        ));
+    }
+}
+
+/// Externally callable functions that change privileged state without any access check
+fn unguarded_privileged(contract: &ContractDefinition, source: &str) -> Vec<String> {
+    let state_vars: Vec<String> = state_variables(contract).into_iter().map(|var| var.0).collect();
+    functions(contract)
+        .filter(|func| func.ty == FunctionTy::Function && is_externally_callable(func))
+        .filter(|func| !modifier_names(func).iter().any(|m| is_access_modifier(m)))
+        .filter_map(|func| {
+            let name = func.name.as_ref()?.name.as_str();
+            let body = slice(&func.body.as_ref()?.loc(), source);
+            if body.contains("msg.sender ==") || body.contains("msg.sender !=") || body.contains("hasRole") {
+                return None;
+            }
+            let written: Vec<&str> = state_vars.iter().map(String::as_str).filter(|var| assigns(body, var)).collect();
+            let lower = name.to_lowercase();
+            let privileged = classify(name, &written).is_some()
+                || ["mint", "pause", "unpause", "set", "grant", "revoke", "transferownership"].iter().any(|p| lower.starts_with(p));
+            privileged.then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn finding(name: &str, severity: Severity, risk_description: String, recommendation: &str, modifier: &str, line: usize) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        function: Some(modifier.to_string()),
+        line: (line > 0).then_some(line),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    #[tokio::test]
+    async fn each_broken_modifier_is_flagged_and_a_correct_one_is_not() {
+        let cases = [
+            (include_str!("../../test ex/modifiers/correct_modifier.sol"), vec![]),
+            (include_str!("../../test ex/modifiers/missing_placeholder.sol"), vec![("Modifier Missing Placeholder", 13)]),
+            (include_str!("../../test ex/modifiers/trivial_check.sol"),
+                vec![("Trivially True Modifier Check", 13), ("Trivially True Modifier Check", 18)]),
+            (include_str!("../../test ex/modifiers/unused_modifier.sol"), vec![("Unused Access Modifier", 14)]),
+        ];
+        for (source, expected) in cases {
+            let found = found(&ModifierRule, source).await;
+            assert_eq!(found.iter().map(|(name, line)| (name.as_str(), *line)).collect::<Vec<_>>(), expected);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Prices an attacker can move. An AMM's reserves, `slot0` or token balances
+//! give the spot price, which one flash loan shifts for the length of a
+//! transaction; an oracle answer read without checking its age or range
+//! passes on a stale or broken feed as if it were current
+
+use async_trait::async_trait;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{
+    entry_points, mask, reachable, scope_text, solidity_function_spans, stylus_function_spans, FunctionSpan,
+};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+/// Spot prices read straight from a pool, Solidity and Stylus `sol_interface!` spellings
+const SPOT: [&str; 6] = ["getReserves(", "get_reserves(", "slot0(", "slot_0(", "getAmountsOut(", "get_amounts_out("];
+/// Oracle reads: Chainlink feeds and generic `getPrice`-style oracles
+const FEEDS: [&str; 6] = ["latestRoundData(", "latest_round_data(", "latestAnswer(", "latest_answer(", "getPrice(", "get_price("];
+/// Feeds that report when their answer was last updated
+const TIMESTAMPED: [&str; 2] = ["latestRoundData(", "latest_round_data("];
+/// Time-weighted averages: Uniswap v3 `observe`/`OracleLibrary.consult`, v2 cumulative prices, named TWAPs; lowercased
+const TWAP: [&str; 4] = ["observe(", "consult(", "cumulative", "twap"];
+/// Age checks on an oracle answer; lowercased
+const STALENESS: [&str; 4] = ["heartbeat", "stale", "max_delay", "maxdelay"];
+const TIMESTAMPS: [&str; 3] = ["block.timestamp", "block::timestamp()", "block_timestamp()"];
+/// Calls that move tokens or ether, or liquidate or lend against a price; lowercased
+const VALUE_MOVES: [&str; 11] = [
+    ".transfer(", "transferfrom(", ".send(", "call{value", "_mint(", ".mint(", "_burn(", "safetransfer", "transfer_eth(", "liquidat", "borrow(",
+];
+const RECOMMENDATION: &str = "Price from a Chainlink-style feed, rejecting answers older than the feed's heartbeat (updatedAt) \
+    and outside sane bounds, or from a TWAP over a window long enough to make manipulation unprofitable (Uniswap v3 observe/OracleLibrary.consult)";
+
+/// Spot AMM prices without a TWAP, and oracle answers used without staleness or sanity checks
+pub struct OracleSafetyRule;
+
+#[async_trait]
+impl AuditRule for OracleSafetyRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let functions = match solang_parser::parse(content, 0) {
+            Ok((unit, _)) => solidity_function_spans(&unit),
+            Err(_) if syn::parse_file(content).is_ok() => stylus_function_spans(&code),
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut vulnerabilities = Vec::new();
+        for (index, function) in functions.iter().enumerate() {
+            let body = &code[function.start..function.end];
+            // Calls on another contract: `pair.getReserves()`, `feed.latestRoundData()`
+            let sources: Vec<(usize, &str)> = SPOT.iter().chain(&FEEDS)
+                .flat_map(|source| body.match_indices(source))
+                .filter(|(at, _)| body[..*at].ends_with('.'))
+                .chain(balance_pricing(body))
+                .collect();
+            if sources.is_empty() {
+                continue;
+            }
+            // A read-only price function counts through the state-changing functions that call it
+            let entries = entry_points(&functions, &code, index);
+            let Some(scope) = entries.iter()
+                .map(|&entry| scope_text(&functions, &reachable(&functions, &code, entry), &code))
+                .reduce(|all, scope| all + "\n" + &scope) else { continue };
+            let lower = scope.to_lowercase();
+            let moves_value = VALUE_MOVES.iter().any(|call| lower.contains(call));
+            let severity = if moves_value { Severity::High } else { Severity::Medium };
+            let callers = callers(&functions, &entries, function);
+
+            for (at, call) in sources {
+                let source = call.trim_end_matches('(');
+                let mut gaps: Vec<(&str, String)> = Vec::new();
+                if SPOT.contains(&call) || source == "balanceOf" {
+                    if !TWAP.iter().any(|twap| lower.contains(twap)) {
+                        gaps.push(("Spot Price Manipulation", format!("{} prices from `{}`, the pool's spot price; a flash loan moves it within one transaction",
+                            callers, source)));
+                    }
+                } else {
+                    let timestamped = TIMESTAMPED.contains(&call);
+                    if ["latestAnswer", "latest_answer"].contains(&source) || (timestamped && !checks_age(&scope)) {
+                        gaps.push(("Stale Oracle Price", format!("{} uses the `{}` answer without checking when it was last updated, \
+                            so a stalled feed keeps serving an old price", callers, source)));
+                    }
+                    if !bounds_price(&scope) {
+                        gaps.push(("Unbounded Oracle Price", format!("{} trusts the single `{}` answer with no sanity bounds; \
+                            a zero, negative or wildly off answer goes straight into the calculation", callers, source)));
+                    }
+                }
+                for (name, risk) in gaps {
+                    let consequence = match moves_value {
+                        true => "; the price decides how much is transferred, minted, borrowed or liquidated",
+                        false => "",
+                    };
+                    vulnerabilities.push(Vulnerability {
+                        name: name.to_string(),
+                        severity,
+                        risk_description: format!("{}{}", risk, consequence),
+                        recommendation: RECOMMENDATION.to_string(),
+                        function: Some(function.name.clone()),
+                        ..Vulnerability::at(content, function.start + at)
+                    });
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Oracle Safety Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ORACLE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Spot AMM prices without a TWAP, and oracle answers without staleness checks or sanity bounds"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Spot Price Manipulation", "Stale Oracle Price", "Unbounded Oracle Price"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
+}
+
+/// `balanceOf` calls in a statement that divides one balance by another, as pricing from pool balances does
+fn balance_pricing(body: &str) -> impl Iterator<Item = (usize, &str)> + '_ {
+    ["balanceOf(", "balance_of("].into_iter()
+        .flat_map(move |call| body.match_indices(call))
+        .filter(move |(at, _)| {
+            let start = body[..*at].rfind([';', '{', '}']).map_or(0, |i| i + 1);
+            let end = body[*at..].find([';', '{', '}']).map_or(body.len(), |i| at + i);
+            let statement = &body[start..end];
+            // Reported once per statement, at its first balance read
+            let first = !body[start..*at].contains("alanceOf(") && !body[start..*at].contains("alance_of(");
+            first && statement.contains('/') && statement.matches("alance").count() >= 2
+        })
+        .map(|(at, _)| (at, "balanceOf"))
+}
+
+/// `f`, or the state-changing functions that reach it when it only reads: `liquidate (through `price`)`
+fn callers(functions: &[FunctionSpan], entries: &[usize], function: &FunctionSpan) -> String {
+    let names: Vec<&str> = entries.iter().map(|&i| functions[i].name.as_str()).collect();
+    match names.as_slice() {
+        [only] if *only == function.name => function.name.clone(),
+        _ => format!("{} (through `{}`)", names.join(", "), function.name),
+    }
+}
+
+/// `updatedAt` compared with the block timestamp, or a named heartbeat or staleness limit
+fn checks_age(scope: &str) -> bool {
+    let lower = scope.to_lowercase();
+    STALENESS.iter().any(|word| lower.contains(word)) || scope.lines().any(|line| {
+        let lower = line.to_lowercase();
+        (lower.contains("updatedat") || lower.contains("updated_at")) && TIMESTAMPS.iter().any(|timestamp| line.contains(timestamp))
+    })
+}
+
+/// A guard comparing the price or answer, e.g. `require(answer > 0)` or `if price > MAX_PRICE`
+fn bounds_price(scope: &str) -> bool {
+    scope.lines().any(|line| {
+        let lower = line.to_lowercase();
+        let guard = ["require(", "if ", "if(", "assert(", "assert!("].iter().any(|guard| lower.contains(guard));
+        let compared = lower.replace("=>", "").replace("->", "").contains(['<', '>']);
+        guard && compared && (lower.contains("price") || lower.contains("answer"))
+    })
+}
+
+/// A finding at byte `at` of `content`
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn spot_prices_and_unchecked_feeds_are_flagged() {
+        assert_eq!(found(&OracleSafetyRule, include_str!("../../test ex/oracle/spot_lending.sol")).await, expected(&[
+            ("Spot Price Manipulation", 28),
+            ("Spot Price Manipulation", 41),
+            ("Stale Oracle Price", 52),
+            ("Unbounded Oracle Price", 52),
+        ]));
+        assert_eq!(found(&OracleSafetyRule, include_str!("../../test ex/oracle/stylus_spot.rs")).await,
+            expected(&[("Spot Price Manipulation", 31)]));
+    }
+
+    #[tokio::test]
+    async fn a_twap_with_checked_feeds_is_not() {
+        assert!(found(&OracleSafetyRule, include_str!("../../test ex/oracle/twap_lending.sol")).await.is_empty());
+    }
+}
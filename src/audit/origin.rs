@@ -0,0 +1,218 @@
+//! Authorization against the wrong caller: `tx.origin` (or Stylus
+//! `tx::origin()`) checks that a malicious contract in the middle passes, and
+//! Stylus contracts that save `msg::sender()` at construction and later
+//! authorize against that saved value without looking at the live caller
+
+use async_trait::async_trait;
+use crate::audit::initializer::is_initializer_name;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{mask, rust_functions};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::Result;
+
+const ORIGINS: [&str; 2] = ["tx.origin", "tx::origin()"];
+const SENDERS: [&str; 3] = ["msg.sender", "msg::sender()", "msg_sender()"];
+
+/// `tx.origin` authentication, and authorization against a stale saved sender
+pub struct OriginAuthRule;
+
+#[async_trait]
+impl AuditRule for OriginAuthRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let mut vulnerabilities = origin_checks(content, &code);
+        if syn::parse_file(content).is_ok() {
+            vulnerabilities.extend(stale_sender_checks(content, &code));
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Caller Authentication Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ORIGIN-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Authorization through tx.origin or a msg::sender() saved at construction"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["tx.origin Authentication", "tx.origin Logged", "Stale Sender Authorization"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+}
+
+fn origin_checks(content: &str, code: &str) -> Vec<Vulnerability> {
+    let mut vulnerabilities = Vec::new();
+    for (i, (masked, line)) in code.lines().zip(content.lines()).enumerate() {
+        let Some(at) = ORIGINS.iter().filter_map(|origin| masked.find(origin)).min() else { continue };
+        let text = masked.trim_start();
+        let logged = text.starts_with("emit ") || text.contains("log(") || text.contains("log!(");
+        let comparison = comparison_with(masked, &ORIGINS);
+        let guard = ["require(", "require!(", "assert(", "assert!(", "if "].iter().any(|check| text.contains(check));
+
+        let found = match (comparison, logged) {
+            // `tx.origin == msg.sender` rejects contract callers; it doesn't authenticate anyone
+            (Some((from, to)), _) if SENDERS.iter().any(|sender| masked[from..to].contains(sender)) => None,
+            (Some((from, to)), _) => Some((Severity::High, from, line[from..to].to_string())),
+            (None, false) if guard => Some((Severity::High, at, line.trim().to_string())),
+            (None, true) => Some((Severity::Low, at, line.trim().to_string())),
+            (None, false) => None,
+        };
+        let Some((severity, column, expression)) = found else { continue };
+        let vulnerability = match severity {
+            Severity::Low => Vulnerability {
+                name: "tx.origin Logged".to_string(),
+                severity,
+                risk_description: format!("`{}` records the transaction's originator; fine for logs, but a sign it may be trusted elsewhere", expression),
+                recommendation: "Log msg.sender instead unless the originator is what indexers need".to_string(),
+                ..Default::default()
+            },
+            _ => Vulnerability {
+                name: "tx.origin Authentication".to_string(),
+                severity,
+                risk_description: format!("`{}` authorizes the transaction's originator, so any contract the owner calls can act as the owner", expression),
+                recommendation: "Authorize msg.sender (msg::sender() in Stylus) instead of tx.origin".to_string(),
+                ..Default::default()
+            },
+        };
+        vulnerabilities.push(Vulnerability {
+            line: Some(i + 1),
+            column: Some(line[..column].chars().count() + 1),
+            snippet: Some(line.trim().to_string()),
+            ..vulnerability
+        });
+    }
+    vulnerabilities
+}
+
+/// Fields a constructor or initializer sets to `msg::sender()`, and later
+/// comparisons against them that don't involve the live caller
+fn stale_sender_checks(content: &str, code: &str) -> Vec<Vulnerability> {
+    let functions = rust_functions(content);
+    let is_setup = |name: &str, start: usize| {
+        name == "new" || is_initializer_name(name) || code[..start].trim_end().ends_with("#[constructor]")
+    };
+    let mut saved: Vec<String> = Vec::new();
+    for (name, start, end) in &functions {
+        if !is_setup(name, *start) {
+            continue;
+        }
+        for line in code[*start..*end].lines().filter(|line| SENDERS.iter().any(|sender| line.contains(sender))) {
+            if let Some(field) = sender_field(line) {
+                saved.push(field);
+            }
+        }
+    }
+
+    let mut vulnerabilities = Vec::new();
+    for (name, start, end) in &functions {
+        if is_setup(name, *start) {
+            continue;
+        }
+        let first_line = content[..*start].matches('\n').count() + 1;
+        for (i, (masked, line)) in code[*start..*end].lines().zip(content[*start..*end].lines()).enumerate() {
+            let reads: Vec<String> = saved.iter().map(|field| format!("self.{}", field)).collect();
+            let reads: Vec<&str> = reads.iter().map(String::as_str).collect();
+            let Some((from, to)) = comparison_with(masked, &reads) else { continue };
+            if SENDERS.iter().any(|sender| masked.contains(sender)) {
+                continue;
+            }
+            let expression = &line[from..to];
+            vulnerabilities.push(Vulnerability {
+                name: "Stale Sender Authorization".to_string(),
+                severity: Severity::High,
+                risk_description: format!("{} authorizes with `{}`, comparing against the deployer saved at construction instead of the current caller",
+                    name, expression),
+                recommendation: "Compare the saved address with msg::sender() on every call".to_string(),
+                function: Some(name.clone()),
+                line: Some(first_line + i),
+                column: Some(line[..from].chars().count() + 1),
+                snippet: Some(line.trim().to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    vulnerabilities
+}
+
+/// `owner` in `self.owner.set(msg::sender())` or `owner: StorageAddress::new(msg::sender())`
+fn sender_field(line: &str) -> Option<String> {
+    let text = line.trim();
+    let field = match text.strip_prefix("self.") {
+        Some(rest) => rest.split('.').next()?,
+        None => text.split_once(':').map(|(field, _)| field.trim())?,
+    };
+    let simple = !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_');
+    simple.then(|| field.to_string())
+}
+
+/// Byte range of the first `==`/`!=` comparison with one of `subjects` on either side
+fn comparison_with(line: &str, subjects: &[&str]) -> Option<(usize, usize)> {
+    ["==", "!="].iter()
+        .flat_map(|op| line.match_indices(op))
+        .map(|(at, _)| {
+            // Rust conditions have no parentheses: `if self.owner.get() != caller {`
+            let start = operand_start(line, at);
+            let keyword = ["if ", "while ", "return "].iter().find(|k| line[start..].starts_with(**k)).map_or(0, |k| k.len());
+            (start + keyword, operand_end(line, at + 2))
+        })
+        .find(|(from, to)| subjects.iter().any(|subject| line[*from..*to].contains(subject)))
+}
+
+/// Start of the left operand of the operator at `at`
+fn operand_start(line: &str, at: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in line[..at].char_indices().rev() {
+        match c {
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth == 0 => return i + 1 + (line[i + 1..at].len() - line[i + 1..at].trim_start().len()),
+            '(' | '[' => depth -= 1,
+            ',' | '&' | '|' | '!' | '{' if depth == 0 => return i + 1 + (line[i + 1..at].len() - line[i + 1..at].trim_start().len()),
+            _ => {}
+        }
+    }
+    line.len() - line.trim_start().len()
+}
+
+/// End of the right operand starting at `at`
+fn operand_end(line: &str, at: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in line[at..].char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth == 0 => return at + line[at..at + i].trim_end().len(),
+            ')' | ']' => depth -= 1,
+            ',' | ';' | '&' | '|' | '{' if depth == 0 => return at + line[at..at + i].trim_end().len(),
+            _ => {}
+        }
+    }
+    at + line[at..].trim_end().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn origin_checks_are_flagged_but_logging_it_is_lower_risk() {
+        assert_eq!(found(&OriginAuthRule, include_str!("../../test ex/origin/origin_wallet.sol")).await, expected(&[
+            ("tx.origin Logged", 16),
+            ("tx.origin Authentication", 20),
+            ("tx.origin Authentication", 25),
+        ]));
+        assert_eq!(found(&OriginAuthRule, include_str!("../../test ex/origin/logged_origin.sol")).await, expected(&[("tx.origin Logged", 9)]));
+    }
+
+    #[tokio::test]
+    async fn a_sender_saved_before_a_call_is_stale() {
+        assert_eq!(found(&OriginAuthRule, include_str!("../../test ex/origin/stale_sender.rs")).await, expected(&[("Stale Sender Authorization", 26)]));
+    }
+}
@@ -0,0 +1,176 @@
+//! Panics in public Stylus functions. A panicking contract burns the gas it
+//! was given and reverts without a reason the caller can decode
+
+use async_trait::async_trait;
+use crate::audit::rules::{AuditRule, HEURISTIC_CONFIDENCE};
+use crate::audit::source::{mask, matching_brace, rust_functions};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+pub const FINDING: &str = "Panic Path";
+
+/// Calls that panic, with the advice for each
+const PANICS: [(&str, &str); 4] = [
+    (".unwrap()", "Return a Result and propagate the error with `?` instead of unwrapping"),
+    (".expect(", "Return a Result and propagate the error with `?` instead of expect"),
+    ("panic!(", "Return `Err(..)` with a revert reason instead of panicking"),
+    ("unreachable!(", "Return `Err(..)` with a revert reason instead of panicking"),
+];
+/// Words suggesting a function moves value, where an abort costs the most
+const VALUE_MOVES: [&str; 3] = ["transfer", "send", "balance"];
+
+/// `.unwrap()`, `.expect(`, `panic!`, `unreachable!`, bare `assert!` and indexing in public functions
+pub struct PanicPathRule;
+
+#[async_trait]
+impl AuditRule for PanicPathRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        if syn::parse_file(content).is_err() {
+            return Ok(Vec::new());
+        }
+        let code = mask(content);
+        let tests = test_modules(&code);
+        let mut vulnerabilities = Vec::new();
+
+        for (function, start, end) in rust_functions(content) {
+            if !code[start..].starts_with("pub ") || tests.iter().any(|(from, to)| (*from..*to).contains(&start)) {
+                continue;
+            }
+            let body = &code[start..end];
+            let severity = match VALUE_MOVES.iter().any(|word| body.to_lowercase().contains(word)) {
+                true => Severity::High,
+                false => Severity::Medium,
+            };
+            // From the body on, so array types such as `[u8; 20]` in the signature aren't read as indexing
+            let Some(open) = body.find('{').map(|i| start + i) else { continue };
+            let first_line = content[..open].matches('\n').count() + 1;
+            let lines = code[open..end].lines().zip(content[open..end].lines()).enumerate();
+
+            for (i, (masked, line)) in lines {
+                let mut found: Vec<(usize, String, &str, f64)> = PANICS.iter()
+                    .filter_map(|(call, advice)| masked.find(call).map(|at| {
+                        (at, format!("`{}` in {}", call.trim_start_matches('.').trim_end_matches('('), function), *advice, 1.0)
+                    }))
+                    .collect();
+                if let Some(at) = bare_assert(masked) {
+                    found.push((at, format!("`assert!` without a message in {}", function),
+                        "Return `Err(..)` when the condition fails, or at least give assert! a message", 1.0));
+                }
+                // A bounds check often precedes the index, which this can't see
+                if let Some(at) = index_expression(masked) {
+                    found.push((at, format!("Indexing `{}` in {}", expression_at(line, at), function),
+                        "Use `.get(i)` and handle `None` instead of indexing", HEURISTIC_CONFIDENCE));
+                }
+
+                for (at, what, advice, confidence) in found {
+                    vulnerabilities.push(Vulnerability {
+                        name: FINDING.to_string(),
+                        severity,
+                        risk_description: format!("{} can panic, which burns the call's gas and reverts without a reason", what),
+                        recommendation: advice.to_string(),
+                        function: Some(function.clone()),
+                        line: Some(first_line + i),
+                        column: Some(line[..at].chars().count() + 1),
+                        snippet: Some(line.trim().to_string()),
+                        confidence: Some(confidence),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Panic Path Detector"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-PANIC-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "unwrap, expect, panic!, bare assert! and indexing in public Stylus functions"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[FINDING]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// Byte ranges of `#[cfg(test)]` modules
+fn test_modules(code: &str) -> Vec<(usize, usize)> {
+    code.match_indices("#[cfg(test)]")
+        .filter_map(|(at, _)| {
+            let rest = &code[at..];
+            let module = rest.find("mod ")?;
+            let open = at + module + rest[module..].find(['{', ';'])?;
+            let close = code[open..].starts_with('{').then(|| matching_brace(code, open))??;
+            Some((at, close))
+        })
+        .collect()
+}
+
+/// Offset of an `assert!` with a single argument, i.e. no message
+fn bare_assert(line: &str) -> Option<usize> {
+    let at = line.find("assert!(")?;
+    if at > 0 && line[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let args = &line[at + "assert!(".len()..];
+    let mut depth = 0;
+    for c in args.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => return Some(at),
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    // Arguments continue on the next line; assume a message follows
+    None
+}
+
+/// Offset of the first `value[..]` index; `#[attr]`, `vec![..]`, array types and literals aren't indexing
+fn index_expression(line: &str) -> Option<usize> {
+    line.match_indices('[')
+        .find(|(at, _)| line[..*at].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ')' || c == ']'))
+        .map(|(at, _)| at)
+}
+
+/// `balances[i]` for the `[` at `open`
+fn expression_at(line: &str, open: usize) -> &str {
+    let start = line[..open].rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).map_or(0, |i| i + 1);
+    let end = line[open..].find(']').map_or(line.len(), |i| open + i + 1);
+    &line[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn public_entry_points_only_and_messages_lower_the_severity() {
+        let vulns = PanicPathRule.check(include_str!("../../test ex/panics/panicky_vault.rs")).await.unwrap();
+        let found: Vec<_> = vulns.iter().map(|vuln| (vuln.line.unwrap_or_default(), vuln.severity)).collect();
+        // The asserted `set_limit`, the private helper and the test module stay quiet
+        assert_eq!(found, [
+            (22, Severity::High),
+            (24, Severity::High),
+            (29, Severity::Medium),
+            (30, Severity::Medium),
+            (31, Severity::Medium),
+        ]);
+        assert!(vulns.iter().all(|vuln| vuln.name == "Panic Path"));
+        assert_eq!(vulns[3].snippet.as_deref(), Some("let index = ids[0] as usize;"));
+    }
+}
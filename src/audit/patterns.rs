@@ -1,11 +1,45 @@
-use crate::audit::vulnerabilities::{Vulnerability, Severity};
-use crate::audit::rules::AuditRule;
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
+use crate::audit::event_coverage;
+use crate::audit::examples;
+use crate::audit::erc;
+use crate::audit::bridge::{self, Direction, SenderGap};
+use crate::audit::reentrancy;
+use crate::audit::timing::{self, TimingKind};
+use crate::audit::fix::TextEdit;
+use crate::audit::source::{find_line, mask};
+use crate::audit::rules::{AuditRule, HEURISTIC_CONFIDENCE};
 use crate::audit::memory_safety::MemorySafetyRule;
+use crate::audit::assembly::AssemblyRule;
 use crate::audit::l2_patterns::L2OptimizationRule;
 use crate::audit::access_control::AccessControlRule;
+use crate::audit::arithmetic::ArithmeticSafetyRule;
+use crate::audit::modifiers::ModifierRule;
+use crate::audit::delegatecall::DelegatecallRule;
+use crate::audit::origin::OriginAuthRule;
+use crate::audit::panics::PanicPathRule;
+use crate::audit::storage_growth::StorageGrowthRule;
+use crate::audit::erc20::Erc20ComplianceRule;
+use crate::audit::initializer::InitializerRule;
+use crate::audit::input_validation::InputValidationRule;
+use crate::audit::event_emission::EventEmissionRule;
+use crate::audit::payable::PayableValueRule;
+use crate::audit::signature::SignatureReplayRule;
+use crate::audit::oracle::OracleSafetyRule;
+use crate::audit::construction::ConstructionRule;
+use crate::audit::timelock::TimelockRule;
+use crate::audit::authority::UpgradeAuthorityRule;
+use crate::audit::upgrade_safety::UpgradeSafetyRule;
+use crate::audit::solc_version::{ArithmeticRule, DestructionRule, RandomnessRule};
+use crate::audit::storage_gap::StorageGapRule;
 use crate::audit::test_patterns::TestPatternRule;
 use crate::audit::ai_patterns::AIPatternDetector;
-use std::error::Error;
+use crate::audit::dedupe;
+use crate::audit::effort::rule_slug;
+use crate::error::{AnalyzerError, Result};
+use clap::ValueEnum;
+use colored::*;
+use serde::Serialize;
+use std::collections::HashMap;
 
 pub struct ReentrancyPattern;
 pub struct L2SpecificPattern;
@@ -15,16 +49,20 @@ pub struct CrossChainVulnerabilityPattern;
 
 #[async_trait::async_trait]
 impl AuditRule for ReentrancyPattern {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
 
-        if content.contains("external") && content.contains("call") {
+        for found in reentrancy::violations(content) {
             vulnerabilities.push(Vulnerability {
                 name: "Potential Reentrancy".to_string(),
                 severity: Severity::High,
-                risk_description: "External call detected before state changes".to_string(),
-                recommendation: "Implement checks-effects-interactions pattern".to_string(),
-            });
+                risk_description: format!("{} writes state on line {} after the external call on line {}, so a reentrant call sees the old state",
+                    found.function, found.write.line, found.call.line),
+                recommendation: format!("Move `{}` above the external call, or add a reentrancy guard to {}", found.write.text, found.function),
+                function: Some(found.function),
+                example: Some(examples::reentrancy(&found.code)),
+                ..Default::default()
+            }.located(Some(found.call)));
         }
 
         Ok(vulnerabilities)
@@ -33,20 +71,64 @@ impl AuditRule for ReentrancyPattern {
     fn name(&self) -> &'static str {
         "Reentrancy Pattern Checker"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-REENTRANCY-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "External calls made before the contract's state is updated"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Potential Reentrancy"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
 }
 
 #[async_trait::async_trait]
 impl AuditRule for L2SpecificPattern {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
 
-        if content.contains("block.number") || content.contains("block.timestamp") {
+        for found in timing::uses(content) {
+            let (name, severity, risk_description, recommendation) = match found.kind {
+                TimingKind::Randomness => (
+                    "Block Value Randomness",
+                    Severity::Critical,
+                    format!("{} derives a random value from {}; the sequencer sets it and anyone can compute the result before acting",
+                        found.function, found.value),
+                    "Use a VRF (e.g. Chainlink VRF) or a commit-reveal scheme instead of block values",
+                ),
+                TimingKind::Deadline { strict } => (
+                    "Deadline Without Tolerance",
+                    Severity::Medium,
+                    format!("{} compares {} against a deadline{}; Arbitrum timestamps can lag or lead real time and block numbers follow L1",
+                        found.function, found.value, if strict { " with an exact match that a given block may never hit" } else { " with no grace period" }),
+                    "Allow a grace period around deadlines, compare with >=/<= rather than ==, and measure time with block.timestamp rather than block numbers",
+                ),
+                TimingKind::BlockNumber => (
+                    "L2 Timing Assumptions",
+                    Severity::Low,
+                    format!("{} reads {}, which on Arbitrum is an approximate L1 block number, not the L2 block", found.function, found.value),
+                    "Use ArbSys(100).arbBlockNumber() for the L2 block number, or block.timestamp for durations",
+                ),
+            };
             vulnerabilities.push(Vulnerability {
-                name: "L2 Timing Assumptions".to_string(),
-                severity: Severity::Medium,
-                risk_description: "Usage of block.number or block.timestamp in L2 context".to_string(),
-                recommendation: "Use L2-specific timing mechanisms or account for L2 block timing".to_string(),
-            });
+                name: name.to_string(),
+                severity,
+                risk_description,
+                recommendation: recommendation.to_string(),
+                function: Some(found.function),
+                ..Default::default()
+            }.located(Some(found.at)));
         }
 
         Ok(vulnerabilities)
@@ -55,16 +137,33 @@ impl AuditRule for L2SpecificPattern {
     fn name(&self) -> &'static str {
         "L2-Specific Pattern Checker"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-L2-TIMING-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Block timestamps and numbers used as randomness, as exact deadlines, or as L2 block numbers"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Block Value Randomness", "Deadline Without Tolerance", "L2 Timing Assumptions"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
 }
 
 #[async_trait::async_trait]
 impl AuditRule for StorageSecurityPattern {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
-        if content.contains("StorageMap") || content.contains("StorageVec") {
-            let has_bounds_check = content.contains(".get_or_default()") || content.contains("if let Some");
-            let has_access_control = content.contains("#[authorize") || content.contains("require!(");
+        if code.contains("StorageMap") || code.contains("StorageVec") {
+            let has_bounds_check = code.contains(".get_or_default()") || code.contains("if let Some");
+            let has_access_control = code.contains("#[authorize") || code.contains("require!(");
 
             if !has_bounds_check {
                 vulnerabilities.push(Vulnerability {
@@ -72,7 +171,8 @@ impl AuditRule for StorageSecurityPattern {
                     severity: Severity::High,
                     risk_description: "Storage access without bounds checking".to_string(),
                     recommendation: "Implement bounds checking with get_or_default() or Option handling".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["StorageMap", "StorageVec"])));
             }
 
             if !has_access_control {
@@ -81,7 +181,8 @@ impl AuditRule for StorageSecurityPattern {
                     severity: Severity::High,
                     risk_description: "Storage modification without access control".to_string(),
                     recommendation: "Add access control checks using authorize attribute or require macro".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["StorageMap", "StorageVec"])));
             }
         }
 
@@ -91,16 +192,37 @@ impl AuditRule for StorageSecurityPattern {
     fn name(&self) -> &'static str {
         "Storage Security Pattern Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-STORAGE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "StorageMap and StorageVec access without bounds checks or access control"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Unsafe Storage Access", "Missing Storage Access Control"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_confidence(&self) -> f64 {
+        HEURISTIC_CONFIDENCE
+    }
 }
 
 #[async_trait::async_trait]
 impl AuditRule for StateTransitionPattern {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
-        if content.contains("pub fn") && (content.contains("mut self") || content.contains("&mut self")) {
-            let has_state_validation = content.contains("ensure!(") || content.contains("require!(");
-            let has_event_emission = content.contains("emit!(") || content.contains("log!(");
+        if code.contains("pub fn") && (code.contains("mut self") || code.contains("&mut self")) {
+            let has_state_validation = code.contains("ensure!(") || code.contains("require!(");
+            let has_event_emission = code.contains("emit!(") || code.contains("log!(");
 
             if !has_state_validation {
                 vulnerabilities.push(Vulnerability {
@@ -108,15 +230,22 @@ impl AuditRule for StateTransitionPattern {
                     severity: Severity::Medium,
                     risk_description: "State transition without proper validation".to_string(),
                     recommendation: "Add state validation using ensure! or require! macros".to_string(),
-                });
+                    confidence: Some(HEURISTIC_CONFIDENCE),
+                    ..Default::default()
+                }.located(find_line(content, &["&mut self", "mut self"])));
             }
 
-            if !has_event_emission {
+            // Per method when the source parses, so each finding can carry a fix
+            if syn::parse_file(content).is_ok() {
+                vulnerabilities.extend(event_coverage::findings(content));
+            } else if !has_event_emission {
                 vulnerabilities.push(Vulnerability {
-                    name: "Missing Event Emission".to_string(),
+                    name: event_coverage::FINDING.to_string(),
                     severity: Severity::Low,
                     risk_description: "State change without event emission".to_string(),
                     recommendation: "Emit events for all important state transitions".to_string(),
+                    confidence: Some(HEURISTIC_CONFIDENCE),
+                    ..Default::default()
                 });
             }
         }
@@ -127,16 +256,47 @@ impl AuditRule for StateTransitionPattern {
     fn name(&self) -> &'static str {
         "State Transition Pattern Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-STATE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "State-changing methods that neither validate input nor emit events"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Missing State Validation", event_coverage::FINDING]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn suggest_fix(&self, finding: &Vulnerability, source: &str) -> Vec<TextEdit> {
+        match (finding.name.as_str(), &finding.function) {
+            (event_coverage::FINDING, Some(function)) => event_coverage::emission_skeleton(source, function),
+            _ => Vec::new(),
+        }
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
 }
 
 #[async_trait::async_trait]
 impl AuditRule for CrossChainVulnerabilityPattern {
-    async fn check(&mut self, content: &str) -> Result<Vec<Vulnerability>, Box<dyn Error + Send + Sync>> {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
         let mut vulnerabilities = Vec::new();
+        let code = mask(content);
 
-        if content.contains("cross_chain") || content.contains("bridge") || content.contains("L1_to_L2") {
-            let has_delay = content.contains("delay") || content.contains("timelock");
-            let has_verification = content.contains("verify_proof") || content.contains("verify_message");
+        if code.contains("cross_chain") || code.contains("bridge") || code.contains("L1_to_L2") {
+            let has_delay = code.contains("delay") || code.contains("timelock");
+            // Arbitrum's own sender checks count, and are looked at per handler below
+            let lower = code.to_lowercase();
+            let has_verification = code.contains("verify_proof") || code.contains("verify_message")
+                || ["applyl1tol2alias", "apply_l1_to_l2_alias", "l2tol1sender", "l2_to_l1_sender"].iter().any(|check| lower.contains(check));
 
             if !has_delay {
                 vulnerabilities.push(Vulnerability {
@@ -144,7 +304,8 @@ impl AuditRule for CrossChainVulnerabilityPattern {
                     severity: Severity::High,
                     risk_description: "Cross-chain operation without delay mechanism".to_string(),
                     recommendation: "Implement timelock or delay mechanism for cross-chain operations".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["cross_chain", "bridge", "L1_to_L2"])));
             }
 
             if !has_verification {
@@ -153,19 +314,214 @@ impl AuditRule for CrossChainVulnerabilityPattern {
                     severity: Severity::Critical,
                     risk_description: "Cross-chain message without proper verification".to_string(),
                     recommendation: "Add proper verification for all cross-chain messages".to_string(),
-                });
+                    ..Default::default()
+                }.located(find_line(content, &["cross_chain", "bridge", "L1_to_L2"])));
             }
         }
 
+        for handler in bridge::sender_gaps(content) {
+            let (name, risk_description, recommendation) = match handler.gap {
+                SenderGap::Unvalidated(Direction::L1ToL2) => (
+                    "Unvalidated L1 Message Sender",
+                    format!("{} handles messages from L1 without checking msg.sender, so any L2 account can call it as if it were the L1 contract",
+                        handler.function),
+                    "Require msg.sender == AddressAliasHelper.applyL1ToL2Alias(l1Counterpart); retryable tickets arrive from the aliased L1 address",
+                ),
+                SenderGap::Unaliased => (
+                    "Unaliased L1 Sender Check",
+                    format!("{} compares msg.sender with the L1 address directly (`{}`); retryable tickets arrive from the aliased address, \
+                        so real messages fail, and an L2 contract deployed at that address passes", handler.function, handler.at.text),
+                    "Compare msg.sender with AddressAliasHelper.applyL1ToL2Alias(l1Counterpart) instead of the L1 address itself",
+                ),
+                SenderGap::Unvalidated(Direction::L2ToL1) => (
+                    "Missing Outbox Verification",
+                    format!("{} handles messages from L2 without checking msg.sender, so anyone on L1 can call it as if it were the L2 contract",
+                        handler.function),
+                    "Require msg.sender == address(bridge), then IOutbox(bridge.activeOutbox()).l2ToL1Sender() == l2Counterpart",
+                ),
+                SenderGap::NoOutbox => (
+                    "Missing Outbox Verification",
+                    format!("{} checks msg.sender (`{}`) but never asks the Outbox which L2 contract sent the message; \
+                        any L2 account can have its message executed here", handler.function, handler.at.text),
+                    "Require msg.sender == address(bridge), then IOutbox(bridge.activeOutbox()).l2ToL1Sender() == l2Counterpart",
+                ),
+            };
+            vulnerabilities.push(Vulnerability {
+                name: name.to_string(),
+                severity: Severity::Critical,
+                risk_description,
+                recommendation: recommendation.to_string(),
+                function: Some(handler.function),
+                ..Default::default()
+            }.located(Some(handler.at)));
+        }
+
         Ok(vulnerabilities)
     }
 
     fn name(&self) -> &'static str {
         "Cross-Chain Vulnerability Analyzer"
     }
+
+    fn id(&self) -> &'static str {
+        "SA-CROSSCHAIN-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Bridge and cross-chain messages without a delay or proof verification, and Arbitrum handlers that skip the aliased L1 sender or Outbox check"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "Missing Cross-Chain Delay",
+            "Insufficient Cross-Chain Verification",
+            "Unvalidated L1 Message Sender",
+            "Unaliased L1 Sender Check",
+            "Missing Outbox Verification",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+
+    fn default_confidence(&self) -> f64 {
+        HEURISTIC_CONFIDENCE
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
+}
+
+/// Which rules run, and severities changed per rule, from the `[rules]`
+/// table of the config file and the audit flags
+#[derive(Debug, Clone, Default)]
+pub struct RuleSelection {
+    /// When set, only these rules run
+    pub only: Option<Vec<String>>,
+    pub disabled: Vec<String>,
+    /// Severity every finding of the rule is reported at
+    pub severity: HashMap<String, Severity>,
+    /// Severity by lowercased finding name or problem family, whichever rule
+    /// reports it; outranks the rule's
+    pub finding_severity: HashMap<String, Severity>,
 }
 
-pub fn create_default_rules() -> Vec<Box<dyn AuditRule>> {
+impl RuleSelection {
+    pub fn allows(&self, rule: &str) -> bool {
+        self.only.as_ref().is_none_or(|only| only.iter().any(|name| name == rule))
+            && !self.disabled.iter().any(|name| name == rule)
+    }
+
+    /// Reports findings matching `key` at `level`. The key is a rule id, name
+    /// or slug, a problem family such as `reentrancy`, or the name of a
+    /// finding some rule reports, such as "Missing Fuzz Testing". `source`
+    /// names the flag or config key in the error for a key that is none of these.
+    /// `rules` are the rules the key may name
+    pub fn remap_severity(&mut self, key: &str, level: Severity, source: &str, rules: &[Box<dyn AuditRule>]) -> Result<()> {
+        let key = key.trim();
+        if let Some(rule) = rules.iter()
+            .find(|rule| rule.name() == key || rule_slug(rule.name()) == key || rule.id().eq_ignore_ascii_case(key))
+        {
+            self.severity.insert(rule.name().to_string(), level);
+            return Ok(());
+        }
+        let finding = rules.iter().flat_map(|rule| rule.finding_names().iter().map(|name| name.to_string()))
+            .chain(erc::finding_names())
+            .any(|name| name.eq_ignore_ascii_case(key));
+        if !finding && !dedupe::is_category(&key.to_lowercase()) {
+            let known: Vec<&str> = rules.iter().map(|rule| rule.id()).collect();
+            let hint = match closest_id(key, &known) {
+                Some(close) => format!("did you mean {}? ", close),
+                None => String::new(),
+            };
+            return Err(AnalyzerError::Config(format!(
+                "{}: \"{}\" is not a rule id, problem family or finding name; {}`rules` lists the rule ids", source, key, hint
+            )));
+        }
+        self.finding_severity.insert(key.to_lowercase(), level);
+        Ok(())
+    }
+
+    /// The configured severity for `vuln`, if any: by its name, else its problem family, else its rule
+    pub fn severity_for(&self, vuln: &Vulnerability) -> Option<Severity> {
+        self.finding_severity.get(&vuln.name.to_lowercase())
+            .or_else(|| dedupe::category(vuln).and_then(|category| self.finding_severity.get(category)))
+            .or_else(|| vuln.rule.as_ref().and_then(|rule| self.severity.get(rule)))
+            .copied()
+    }
+}
+
+/// Parses a severity level; `source` names the flag or config key in the error
+pub fn severity_level(level: &str, source: &str) -> Result<Severity> {
+    Severity::from_str(level.trim(), true).map_err(|_| AnalyzerError::Config(format!(
+        "{}: \"{}\" is not one of critical, high, medium, low, info", source, level.trim()
+    )))
+}
+
+/// The names of the rules among `rules` with these ids, compared
+/// case-insensitively. `flag` names the option in the error for an unknown id
+pub fn rules_by_id(ids: &[String], flag: &str, rules: &[Box<dyn AuditRule>]) -> Result<Vec<String>> {
+    ids.iter()
+        .map(|id| {
+            let rule = rules.iter().find(|rule| rule.id().eq_ignore_ascii_case(id.trim())).ok_or_else(|| {
+                let known: Vec<&str> = rules.iter().map(|rule| rule.id()).collect();
+                let hint = match closest_id(id.trim(), &known) {
+                    Some(close) => format!("did you mean {}? `rules` lists them all", close),
+                    None => format!("known ids: {}", known.join(", ")),
+                };
+                AnalyzerError::Config(format!("{}: unknown rule id \"{}\"; {}", flag, id, hint))
+            })?;
+            Ok(rule.name().to_string())
+        })
+        .collect()
+}
+
+/// The id `typed` most likely meant: one containing it ("reentrancy"), or
+/// one a few typos away
+fn closest_id<'a>(typed: &str, known: &[&'a str]) -> Option<&'a str> {
+    let typed = typed.to_ascii_uppercase();
+    if typed.len() >= 3 {
+        let containing: Vec<&str> = known.iter().copied().filter(|id| id.contains(&typed)).collect();
+        if let [only] = containing[..] {
+            return Some(only);
+        }
+    }
+    known.iter()
+        .map(|id| (edit_distance(&typed, id), *id))
+        .filter(|(distance, id)| *distance <= (id.len() / 4).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, id)| id)
+}
+
+/// Levenshtein distance between two ASCII strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Stable id of the built-in rule called `name`
+pub fn rule_id(name: &str) -> Option<&'static str> {
+    all_rules().iter().find(|rule| rule.name() == name).map(|rule| rule.id())
+}
+
+/// Name of the built-in rule with this id
+pub fn rule_name(id: &str) -> Option<&'static str> {
+    all_rules().iter().find(|rule| rule.id() == id).map(|rule| rule.name())
+}
+
+/// Every built-in rule, whatever the config file says
+pub fn all_rules() -> Vec<Box<dyn AuditRule>> {
     vec![
         Box::new(ReentrancyPattern),
         Box::new(L2SpecificPattern),
@@ -173,9 +529,192 @@ pub fn create_default_rules() -> Vec<Box<dyn AuditRule>> {
         Box::new(StateTransitionPattern),
         Box::new(CrossChainVulnerabilityPattern),
         Box::new(MemorySafetyRule),
+        Box::new(AssemblyRule),
+        Box::new(DelegatecallRule),
         Box::new(L2OptimizationRule),
         Box::new(AccessControlRule),
+        Box::new(OriginAuthRule),
+        Box::new(InputValidationRule),
+        Box::new(EventEmissionRule),
+        Box::new(PayableValueRule),
+        Box::new(SignatureReplayRule),
+        Box::new(OracleSafetyRule),
+        Box::new(ModifierRule),
+        Box::new(InitializerRule),
+        Box::new(ConstructionRule),
+        Box::new(TimelockRule),
+        Box::new(UpgradeAuthorityRule),
+        Box::new(UpgradeSafetyRule),
+        Box::new(StorageGapRule),
+        Box::new(ArithmeticRule),
+        Box::new(ArithmeticSafetyRule),
+        Box::new(PanicPathRule),
+        Box::new(StorageGrowthRule),
+        Box::new(Erc20ComplianceRule),
+        Box::new(RandomnessRule),
+        Box::new(DestructionRule),
         Box::new(TestPatternRule),
         Box::new(AIPatternDetector::new()),
     ]
-}
\ No newline at end of file
+}
+
+/// A built-in rule as `rules` lists it
+#[derive(Debug, Serialize)]
+pub struct RuleInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// The name as `--effort` and the config file also accept it
+    pub slug: String,
+    pub default_severity: Severity,
+    pub default_effort: Effort,
+    pub description: &'static str,
+    /// False when the config file's `[rules]` table turns the rule off
+    pub enabled: bool,
+}
+
+/// Every built-in rule, in the order the audit runs them, as enabled by `selection`
+pub fn rule_list(selection: &RuleSelection) -> Vec<RuleInfo> {
+    all_rules().iter()
+        .map(|rule| RuleInfo {
+            id: rule.id(),
+            name: rule.name(),
+            slug: rule_slug(rule.name()),
+            default_severity: rule.default_severity(),
+            default_effort: rule.default_effort(),
+            description: rule.description(),
+            enabled: selection.allows(rule.name()),
+        })
+        .collect()
+}
+
+pub fn format_rule_list(rules: &[RuleInfo]) -> String {
+    let mut output = format!("\n{}\n", format!("📚 Audit Rules ({})", rules.len()).bright_green().bold());
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    for rule in rules {
+        let severity = format!("{:<8}", format!("{:?}", rule.default_severity));
+        let severity = match rule.default_severity {
+            Severity::Critical => severity.red().bold(),
+            Severity::High => severity.red(),
+            Severity::Medium => severity.yellow(),
+            Severity::Low => severity.blue(),
+            Severity::Info => severity.dimmed(),
+        };
+        let disabled = if rule.enabled { String::new() } else { format!(" {}", "(disabled in config)".dimmed()) };
+        output.push_str(&format!("{:<20} {} {}{}\n", rule.id.cyan(), severity, rule.name.bold(), disabled));
+        output.push_str(&format!("{:<20} {} {}\n", "", " ".repeat(8), rule.description.dimmed()));
+    }
+    output
+}
+
+pub fn format_rule_list_json(rules: &[RuleInfo]) -> Result<String> {
+    serde_json::to_string_pretty(rules)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize rule list: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures(dir: &std::path::Path, found: &mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                fixtures(&path, found);
+            } else if path.extension().is_some_and(|ext| ext == "rs" || ext == "sol") {
+                found.push(path);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_only_emit_the_names_they_declare() {
+        let mut files = Vec::new();
+        fixtures(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test ex"), &mut files);
+        let mut undeclared = std::collections::BTreeSet::new();
+        for file in &files {
+            let Ok(source) = std::fs::read_to_string(file) else { continue };
+            for rule in all_rules() {
+                for vuln in rule.check(&source).await.unwrap() {
+                    if !rule.finding_names().contains(&vuln.name.as_str()) {
+                        undeclared.insert(format!("{}: {}", rule.id(), vuln.name));
+                    }
+                }
+            }
+        }
+        assert!(undeclared.is_empty(), "{:#?}", undeclared);
+    }
+
+    #[tokio::test]
+    async fn reentrancy_needs_a_state_write_after_the_call() {
+        use crate::audit::rules::found;
+
+        let reentrancy = |line| vec![("Potential Reentrancy".to_string(), line)];
+        assert_eq!(found(&ReentrancyPattern, include_str!("../../test ex/reentrancy/vulnerable_bank.rs")).await, reentrancy(25));
+        assert_eq!(found(&ReentrancyPattern, include_str!("../../test ex/reentrancy/vulnerable_bank.sol")).await, reentrancy(16));
+        assert!(found(&ReentrancyPattern, include_str!("../../test ex/reentrancy/safe_bank.rs")).await.is_empty());
+        assert!(found(&ReentrancyPattern, include_str!("../../test ex/reentrancy/safe_bank.sol")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn block_values_are_neither_random_nor_exact_on_l2() {
+        use crate::audit::rules::{expected, found};
+
+        assert_eq!(found(&L2SpecificPattern, include_str!("../../test ex/timing/coin_flip.rs")).await, expected(&[
+            ("Block Value Randomness", 28),
+            ("Deadline Without Tolerance", 38),
+            ("L2 Timing Assumptions", 45),
+        ]));
+        assert!(found(&L2SpecificPattern, include_str!("../../test ex/timing/grace_auction.sol")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn l1_senders_must_be_aliased_and_outbox_verified() {
+        use crate::audit::rules::{expected, found};
+
+        let rule = CrossChainVulnerabilityPattern;
+        assert_eq!(found(&rule, include_str!("../../test ex/crosschain/direct_receiver.sol")).await, expected(&[
+            ("Unaliased L1 Sender Check", 16),
+            ("Unvalidated L1 Message Sender", 21),
+        ]));
+        assert_eq!(found(&rule, include_str!("../../test ex/crosschain/l1_receiver.sol")).await, expected(&[
+            ("Missing Cross-Chain Delay", 14),
+            ("Missing Outbox Verification", 32),
+        ]));
+        assert_eq!(found(&rule, include_str!("../../test ex/crosschain/stylus_receiver.rs")).await,
+            expected(&[("Unaliased L1 Sender Check", 28)]));
+        assert!(found(&rule, include_str!("../../test ex/crosschain/aliased_receiver.sol")).await.is_empty());
+    }
+
+    #[test]
+    fn remaps_rules_families_and_finding_names() {
+        let mut selection = RuleSelection::default();
+        selection.remap_severity("SA-ORIGIN-001", Severity::Low, "test", &all_rules()).unwrap();
+        selection.remap_severity("reentrancy", Severity::Info, "test", &all_rules()).unwrap();
+        selection.remap_severity("Missing Fuzz Testing", Severity::Info, "test", &all_rules()).unwrap();
+        selection.remap_severity("ERC-721 Member Missing", Severity::Low, "test", &all_rules()).unwrap();
+
+        assert_eq!(selection.severity.get("Caller Authentication Checker").copied(), Some(Severity::Low));
+        assert_eq!(selection.finding_severity.get("reentrancy").copied(), Some(Severity::Info));
+        assert_eq!(selection.finding_severity.get("missing fuzz testing").copied(), Some(Severity::Info));
+        assert!(selection.finding_severity.contains_key("erc-721 member missing"));
+    }
+
+    #[test]
+    fn misspelled_override_key_is_a_config_error() {
+        let mut selection = RuleSelection::default();
+        let err = selection.remap_severity("Missing Fuzz Tests", Severity::Info, "--severity-override", &all_rules()).unwrap_err();
+        assert!(matches!(err, AnalyzerError::Config(ref message) if message.starts_with("--severity-override: \"Missing Fuzz Tests\"")));
+        assert!(selection.finding_severity.is_empty());
+
+        let err = selection.remap_severity("SA-ORIGN-001", Severity::Info, "[rules] severity", &all_rules()).unwrap_err();
+        assert!(err.to_string().contains("did you mean SA-ORIGIN-001?"));
+    }
+
+    #[test]
+    fn the_rule_list_shows_what_a_selection_turns_off() {
+        let selection = RuleSelection { disabled: vec!["Caller Authentication Checker".to_string()], ..Default::default() };
+        let disabled: Vec<&str> = rule_list(&selection).iter().filter(|rule| !rule.enabled).map(|rule| rule.id).collect();
+        assert_eq!(disabled, ["SA-ORIGIN-001"]);
+        assert!(rule_list(&RuleSelection::default()).iter().all(|rule| rule.enabled));
+    }
+}
@@ -0,0 +1,377 @@
+//! Ether handling that `msg.value` gets wrong: reading it once per loop
+//! iteration, so one payment is counted again for every item bought; reading
+//! it in a function that can't receive ether, where it is always zero; and
+//! refunding other accounts by pushing ether to them, where one reverting
+//! recipient blocks everyone else
+
+use async_trait::async_trait;
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionAttribute, FunctionTy, Loc, Mutability, SourceUnitPart};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{is_externally_callable, mask, matching_brace, modifier_names, public_mutating_methods, rust_functions, state_variables};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+const SOLIDITY_VALUE: &str = "msg.value";
+/// `msg::value()` in the function-style SDK, `self.vm().msg_value()` in the host-style one
+const STYLUS_VALUE: [&str; 2] = ["msg::value()", "msg_value()"];
+/// Recipients the deployer controls; paying them isn't a refund to an arbitrary account
+const PRIVILEGED: [&str; 3] = ["owner", "admin", "treasury"];
+const PULL_PAYMENT: &str = "Credit what each account is owed in a mapping (pendingReturns[account] += amount) \
+    and let accounts withdraw it themselves, zeroing the credit before sending";
+
+/// A function and the byte range of its body in the masked source
+struct Function {
+    name: String,
+    start: usize,
+    end: usize,
+    external: bool,
+    payable: bool,
+    restricted: bool,
+}
+
+/// `msg.value` read inside loops or in non-payable functions, and push-style ether refunds
+pub struct PayableValueRule;
+
+#[async_trait]
+impl AuditRule for PayableValueRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        if let Ok((unit, _)) = solang_parser::parse(content, 0) {
+            return Ok(solidity_findings(&unit, content, &code));
+        }
+        if syn::parse_file(content).is_ok() {
+            return Ok(stylus_findings(content, &code));
+        }
+        Ok(Vec::new())
+    }
+
+    fn name(&self) -> &'static str {
+        "Payable Value Handling Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-VALUE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "msg.value read in loops or non-payable functions, and ether refunds pushed to other accounts"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["msg.value in Loop", "msg.value in Non-Payable Function", "Push-Style Ether Refund"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Moderate
+    }
+}
+
+fn solidity_findings(unit: &solang_parser::pt::SourceUnit, content: &str, code: &str) -> Vec<Vulnerability> {
+    let mut vulnerabilities = Vec::new();
+    for part in &unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        let state: Vec<String> = state_variables(contract).into_iter().map(|(name, _)| name).collect();
+        let functions: Vec<Function> = contract.parts.iter()
+            .filter_map(|part| match part {
+                ContractPart::FunctionDefinition(func) => Some(func),
+                _ => None,
+            })
+            .filter_map(|func| {
+                let Some(Loc::File(_, start, end)) = func.body.as_ref().map(|body| body.loc()) else { return None };
+                Some(Function {
+                    name: func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string()),
+                    start,
+                    end,
+                    external: match func.ty {
+                        FunctionTy::Function => is_externally_callable(func),
+                        FunctionTy::Fallback | FunctionTy::Receive => true,
+                        _ => false,
+                    },
+                    payable: func.attributes.iter().any(|attr| matches!(attr, FunctionAttribute::Mutability(Mutability::Payable(_)))),
+                    restricted: !modifier_names(func).is_empty(),
+                })
+            })
+            .collect();
+        // Internal helpers that read msg.value, e.g. `_buy` called from a batch loop
+        let readers: Vec<&Function> = functions.iter()
+            .filter(|f| !f.external && reads(&code[f.start..f.end], &[SOLIDITY_VALUE]).is_some())
+            .collect();
+
+        for function in &functions {
+            let body = &code[function.start..function.end];
+            let mut reported = Vec::new();
+            for (_, loop_start, loop_end) in loops(body, false) {
+                let inner = &body[loop_start..loop_end];
+                let found = reads(inner, &[SOLIDITY_VALUE])
+                    .map(|at| (at, "reads msg.value".to_string()))
+                    .or_else(|| readers.iter().filter(|reader| reader.name != function.name).find_map(|reader| {
+                        called(inner, &reader.name).map(|at| (at, format!("calls `{}`, which reads msg.value,", reader.name)))
+                    }));
+                let Some((at, what)) = found else { continue };
+                let at = function.start + loop_start + at;
+                // A nested loop is inside its outer loop too
+                if reported.contains(&at) {
+                    continue;
+                }
+                reported.push(at);
+                vulnerabilities.push(Vulnerability {
+                    name: "msg.value in Loop".to_string(),
+                    severity: Severity::High,
+                    risk_description: format!("{} {} once per loop iteration; every iteration sees the whole payment, so one payment is counted again for each item",
+                        function.name, what),
+                    recommendation: format!("Check msg.value once against the total cost before the loop and track what is left in a local; \
+                        return any excess through pull payment. {}", PULL_PAYMENT),
+                    function: Some(function.name.clone()),
+                    ..Vulnerability::at(content, at)
+                });
+            }
+            if function.external && !function.payable {
+                if let Some(at) = reads(body, &[SOLIDITY_VALUE]) {
+                    vulnerabilities.push(Vulnerability {
+                        name: "msg.value in Non-Payable Function".to_string(),
+                        severity: Severity::Medium,
+                        risk_description: format!("{} reads msg.value but isn't payable; calls sending ether revert, so the value is always zero \
+                            (recent solc rejects it outright)", function.name),
+                        recommendation: "Mark the function `payable`, or drop the msg.value logic if it isn't meant to receive ether".to_string(),
+                        function: Some(function.name.clone()),
+                        ..Vulnerability::at(content, function.start + at)
+                    });
+                }
+            }
+            if function.external && !function.restricted {
+                vulnerabilities.extend(push_refunds(body, &state, content, function));
+            }
+        }
+    }
+    vulnerabilities
+}
+
+/// Ether sent from a public function to an account the caller doesn't control:
+/// a stored address such as the previous bidder, or every recipient of a loop
+fn push_refunds(body: &str, state: &[String], content: &str, function: &Function) -> Vec<Vulnerability> {
+    let loops = loops(body, false);
+    let mut vulnerabilities = Vec::new();
+    for (at, recipient) in ether_sends(body) {
+        let root: String = recipient.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if recipient.starts_with("msg.sender") || recipient.starts_with("tx.origin")
+            || PRIVILEGED.iter().any(|word| root.to_lowercase().contains(word)) {
+            continue;
+        }
+        let in_loop = loops.iter().any(|(_, start, end)| (*start..*end).contains(&at));
+        if !in_loop && !state.contains(&root) {
+            continue;
+        }
+        let consequence = match in_loop {
+            true => "one recipient that reverts or burns the gas stops the whole loop, and every other recipient with it",
+            false => "a recipient contract that reverts on receipt makes this function revert for everyone",
+        };
+        vulnerabilities.push(Vulnerability {
+            name: "Push-Style Ether Refund".to_string(),
+            severity: Severity::Medium,
+            risk_description: format!("{} pushes ether to `{}`, an account other than the caller; {}", function.name, recipient, consequence),
+            recommendation: PULL_PAYMENT.to_string(),
+            function: Some(function.name.clone()),
+            ..Vulnerability::at(content, function.start + at)
+        });
+    }
+    vulnerabilities
+}
+
+/// Ether transfers as (offset, recipient): `to.transfer(x)`, `to.send(x)` and
+/// `to.call{value: x}(..)`, with any `payable(..)` wrapper removed. Token
+/// transfers take two arguments and are skipped
+fn ether_sends(body: &str) -> Vec<(usize, String)> {
+    let mut sends = Vec::new();
+    for call in [".transfer(", ".send(", ".call{value"] {
+        for (at, _) in body.match_indices(call) {
+            if call != ".call{value" {
+                let open = at + call.len() - 1;
+                let Some(close) = matching_paren(body, open) else { continue };
+                if top_level_commas(&body[open + 1..close]) > 0 {
+                    continue;
+                }
+            }
+            let start = receiver_start(body, at);
+            let mut recipient = body[start..at].trim();
+            if let Some(inner) = recipient.strip_prefix("payable(").and_then(|rest| rest.strip_suffix(')')) {
+                recipient = inner.trim();
+            }
+            if !recipient.is_empty() {
+                sends.push((start, recipient.to_string()));
+            }
+        }
+    }
+    sends.sort_by_key(|(at, _)| *at);
+    sends
+}
+
+/// Start of the receiver expression ending at `end`: identifiers, member
+/// accesses, subscripts and call parentheses such as `payable(bidders[i])`
+fn receiver_start(body: &str, end: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in body[..end].char_indices().rev() {
+        match c {
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth > 0 => depth -= 1,
+            c if depth > 0 || c.is_alphanumeric() || c == '_' || c == '.' => {}
+            _ => return i + c.len_utf8(),
+        }
+    }
+    0
+}
+
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    text[open..].char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(open + i)
+    })
+}
+
+fn top_level_commas(text: &str) -> usize {
+    let mut depth = 0;
+    text.chars().filter(|c| {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        *c == ',' && depth == 0
+    }).count()
+}
+
+fn stylus_findings(content: &str, code: &str) -> Vec<Vulnerability> {
+    let mut vulnerabilities = Vec::new();
+    for (name, start, end) in rust_functions(code) {
+        let body = &code[start..end];
+        let mut reported = Vec::new();
+        for (_, loop_start, loop_end) in loops(body, true) {
+            let Some(at) = reads(&body[loop_start..loop_end], &STYLUS_VALUE) else { continue };
+            let at = start + loop_start + at;
+            // A nested loop is inside its outer loop too
+            if reported.contains(&at) {
+                continue;
+            }
+            reported.push(at);
+            vulnerabilities.push(Vulnerability {
+                name: "msg.value in Loop".to_string(),
+                severity: Severity::High,
+                risk_description: format!("{} reads msg::value() on every loop iteration; each read returns the whole payment, \
+                    so one payment is counted again for each item", name),
+                recommendation: format!("Read msg::value() once before the loop, check it against the total cost and track what is left in a local. {}",
+                    PULL_PAYMENT),
+                function: Some(name.clone()),
+                ..Vulnerability::at(content, at)
+            });
+        }
+    }
+    let Ok(file) = syn::parse_file(content) else { return vulnerabilities };
+    for (method, start, end) in public_mutating_methods(&file, content) {
+        if method.attrs.iter().any(|attr| attr.path().is_ident("payable") || attr.path().is_ident("constructor")) {
+            continue;
+        }
+        let Some(at) = reads(&code[start..end], &STYLUS_VALUE) else { continue };
+        let name = method.sig.ident.to_string();
+        vulnerabilities.push(Vulnerability {
+            name: "msg.value in Non-Payable Function".to_string(),
+            severity: Severity::Medium,
+            risk_description: format!("{} reads msg::value() but isn't #[payable]; the SDK rejects calls that send ether, so the value is always zero", name),
+            recommendation: "Mark the method #[payable], or drop the msg::value() logic if it isn't meant to receive ether".to_string(),
+            function: Some(name),
+            ..Vulnerability::at(content, start + at)
+        });
+    }
+    vulnerabilities
+}
+
+/// Offset of the first read of any of `values` in `text`
+fn reads(text: &str, values: &[&str]) -> Option<usize> {
+    values.iter()
+        .flat_map(|value| text.match_indices(value))
+        .filter(|(at, _)| !text[..*at].ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+        .map(|(at, _)| at)
+        .min()
+}
+
+/// Offset of a call to `name` in `text`
+fn called(text: &str, name: &str) -> Option<usize> {
+    text.match_indices(name)
+        .find(|(at, _)| {
+            !text[..*at].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.')
+                && text[at + name.len()..].trim_start().starts_with('(')
+        })
+        .map(|(at, _)| at)
+}
+
+/// Loops in `body` as (keyword offset, body start, body end). Solidity loops
+/// without braces run to the end of their single statement; the `while` that
+/// closes a `do` block has no body of its own
+fn loops(body: &str, stylus: bool) -> Vec<(usize, usize, usize)> {
+    let keywords: &[&str] = if stylus { &["for", "while", "loop"] } else { &["for", "while", "do"] };
+    let mut loops = Vec::new();
+    for keyword in keywords {
+        for (at, _) in body.match_indices(keyword) {
+            let after = at + keyword.len();
+            let boundary = !body[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                && !body[after..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+            if !boundary {
+                continue;
+            }
+            let rest = body[after..].trim_start();
+            let header_end = match (stylus, *keyword) {
+                (true, _) => body[after..].find('{').map(|i| after + i),
+                (false, "do") => rest.starts_with('{').then(|| body.len() - rest.len()),
+                (false, _) if rest.starts_with('(') => matching_paren(body, body.len() - rest.len()).map(|close| close + 1),
+                _ => None,
+            };
+            let Some(header_end) = header_end else { continue };
+            let statement = body[header_end..].trim_start();
+            let open = body.len() - statement.len();
+            let range = match statement.chars().next() {
+                Some('{') => matching_brace(body, open).map(|close| (open, close)),
+                Some(';') => None,
+                Some(_) => statement.find(';').map(|end| (open, open + end)),
+                None => None,
+            };
+            if let Some((start, end)) = range {
+                loops.push((at, start, end));
+            }
+        }
+    }
+    loops.sort();
+    loops
+}
+
+/// A finding at byte `at` of `content`
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn value_reuse_and_push_refunds_are_flagged() {
+        assert_eq!(found(&PayableValueRule, include_str!("../../test ex/payable/crowdsale.sol")).await, expected(&[
+            ("msg.value in Loop", 19),
+            ("msg.value in Loop", 26),
+            ("msg.value in Non-Payable Function", 32),
+            ("Push-Style Ether Refund", 39),
+        ]));
+        assert_eq!(found(&PayableValueRule, include_str!("../../test ex/payable/stylus_crowdsale.rs")).await, expected(&[
+            ("msg.value in Loop", 19),
+            ("msg.value in Non-Payable Function", 31),
+        ]));
+    }
+
+    #[tokio::test]
+    async fn value_read_once_and_pulled_refunds_are_not() {
+        assert!(found(&PayableValueRule, include_str!("../../test ex/payable/crowdsale_safe.sol")).await.is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+//! Rules from dynamic libraries, loaded with `--plugin`, so teams can keep
+//! proprietary checks out of this crate. A plugin is a `cdylib` exporting a
+//! C ABI, which any language that can export C functions can implement:
+//!
+//! - `STYLUS_ANALYZER_PLUGIN_ABI: u32`, the ABI version it was built against,
+//!   read before anything else is called
+//! - `register_rules(host_abi: u32) -> *mut c_char`, a JSON description of
+//!   the plugin, its capabilities and its rules:
+//!
+//! ```json
+//! {"name": "todo-plugin", "capabilities": ["check"],
+//!  "rules": [{"id": "TODO-001", "name": "Unresolved TODO", "severity": "low"}]}
+//! ```
+//!
+//! - `check_rule(rule: *const c_char, content: *const u8, len: usize) -> *mut c_char`,
+//!   which runs one rule on the UTF-8 source and answers `{"findings": [...]}`,
+//!   each finding in the shape of [`PluginFinding`], or `{"error": "..."}`
+//! - `free_string(*mut c_char)`, which frees the strings the other two return
+//!
+//! `check_rule` may be called from several threads at once and must not
+//! unwind. A library built against another ABI version, without the `check`
+//! capability, or naming a rule id that is taken fails the load with exit
+//! code 2. `examples/todo_plugin.rs` is a complete plugin
+
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use libloading::Library;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use crate::audit::rules::AuditRule;
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+/// Version of the symbols and JSON shapes above. Bumped only when one's
+/// meaning changes; new optional fields keep the version
+pub const ABI_VERSION: u32 = 1;
+
+const ABI_SYMBOL: &[u8] = b"STYLUS_ANALYZER_PLUGIN_ABI\0";
+const REGISTER_SYMBOL: &[u8] = b"register_rules\0";
+const CHECK_SYMBOL: &[u8] = b"check_rule\0";
+const FREE_SYMBOL: &[u8] = b"free_string\0";
+
+type RegisterFn = unsafe extern "C" fn(u32) -> *mut c_char;
+type CheckFn = unsafe extern "C" fn(*const c_char, *const u8, usize) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// Answer to `register_rules`
+#[derive(Deserialize)]
+struct Description {
+    name: String,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    rules: Vec<RuleEntry>,
+}
+
+#[derive(Deserialize)]
+struct RuleEntry {
+    id: String,
+    name: String,
+    severity: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Answer to `check_rule`
+#[derive(Deserialize)]
+struct CheckResponse {
+    #[serde(default)]
+    findings: Vec<PluginFinding>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// One finding as a plugin reports it. Fields this version doesn't know are
+/// ignored, so plugins can add their own
+#[derive(Debug, Deserialize)]
+pub struct PluginFinding {
+    pub name: String,
+    /// critical, high, medium, low or info; the rule's severity when absent
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// The rule's description when absent
+    #[serde(default)]
+    pub risk_description: Option<String>,
+    pub recommendation: String,
+    /// 1-based
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// 1-based
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub snippet: Option<String>,
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// A loaded library and the functions rules call into
+struct Plugin {
+    name: String,
+    check: CheckFn,
+    free: FreeFn,
+    // Declared last so it's unloaded after nothing can call into it
+    _library: Library,
+}
+
+impl Plugin {
+    /// Reads and frees a JSON string the plugin returned
+    fn response<T: DeserializeOwned>(&self, raw: *mut c_char) -> std::result::Result<T, String> {
+        if raw.is_null() {
+            return Err("returned no response".to_string());
+        }
+        // SAFETY: the ABI has the plugin return NUL-terminated strings it
+        // allocated, freed once through its own `free_string`
+        let text = unsafe { CStr::from_ptr(raw) }.to_string_lossy().into_owned();
+        unsafe { (self.free)(raw) };
+        serde_json::from_str(&text).map_err(|e| format!("invalid response: {}", e))
+    }
+}
+
+/// A rule a plugin registered
+#[derive(Clone)]
+pub struct PluginRule {
+    plugin: Arc<Plugin>,
+    // Leaked once at load, as the `AuditRule` accessors are `'static`
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    severity: Severity,
+}
+
+#[async_trait]
+impl AuditRule for PluginRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let plugin = self.plugin.clone();
+        let rule = CString::new(self.id).map_err(|e| self.failed(e.to_string()))?;
+        let content = content.to_string();
+        let response: CheckResponse = tokio::task::spawn_blocking(move || {
+            // SAFETY: `rule` is NUL-terminated and `content` outlives the call
+            let raw = unsafe { (plugin.check)(rule.as_ptr(), content.as_ptr(), content.len()) };
+            plugin.response(raw)
+        })
+            .await
+            .map_err(|e| self.failed(e.to_string()))?
+            .map_err(|e| self.failed(e))?;
+        if let Some(error) = response.error {
+            return Err(self.failed(error));
+        }
+
+        response.findings.into_iter()
+            .map(|finding| {
+                let severity = match &finding.severity {
+                    Some(level) => severity_level(level).map_err(|e| self.failed(e))?,
+                    None => self.severity,
+                };
+                Ok(Vulnerability {
+                    name: finding.name,
+                    severity,
+                    risk_description: finding.risk_description.unwrap_or_else(|| self.description.to_string()),
+                    recommendation: finding.recommendation,
+                    origin: Some(format!("plugin {}", self.plugin.name)),
+                    line: finding.line,
+                    column: finding.column,
+                    snippet: finding.snippet,
+                    reference: finding.reference,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl PluginRule {
+    fn failed(&self, message: String) -> AnalyzerError {
+        AnalyzerError::Rule { rule: self.name.to_string(), message: format!("plugin {}: {}", self.plugin.name, message) }
+    }
+}
+
+/// Loads each plugin library in `paths` and asks it for its rules. A library
+/// that doesn't load, was built against another ABI version or can't check
+/// fails naming its path, as does a rule id one of `taken` or another plugin
+/// already uses
+pub fn load(paths: &[PathBuf], taken: &[Box<dyn AuditRule>]) -> Result<Vec<PluginRule>> {
+    let mut rules = Vec::new();
+    for path in paths {
+        let loaded = load_one(path, taken, &rules)?;
+        rules.extend(loaded);
+    }
+    Ok(rules)
+}
+
+fn load_one(path: &Path, taken: &[Box<dyn AuditRule>], loaded: &[PluginRule]) -> Result<Vec<PluginRule>> {
+    let fail = |message: String| AnalyzerError::Config(format!("plugin {}: {}", path.display(), message));
+    // SAFETY: loading a library runs its initializers; `--plugin` is the
+    // user vouching for it. Symbols are only called with the types the ABI
+    // declares, after the version check
+    let library = unsafe { Library::new(path) }.map_err(|e| fail(format!("cannot load: {}", e)))?;
+    let abi = unsafe { library.get::<*const u32>(ABI_SYMBOL) }.ok().map(|symbol| unsafe { **symbol });
+    handshake(abi).map_err(fail)?;
+    let (register, check, free) = unsafe {
+        let symbol = |name: &[u8]| String::from_utf8_lossy(&name[..name.len() - 1]).into_owned();
+        (
+            *library.get::<RegisterFn>(REGISTER_SYMBOL).map_err(|_| fail(format!("doesn't export {}", symbol(REGISTER_SYMBOL))))?,
+            *library.get::<CheckFn>(CHECK_SYMBOL).map_err(|_| fail(format!("doesn't export {}", symbol(CHECK_SYMBOL))))?,
+            *library.get::<FreeFn>(FREE_SYMBOL).map_err(|_| fail(format!("doesn't export {}", symbol(FREE_SYMBOL))))?,
+        )
+    };
+    let mut plugin = Plugin { name: String::new(), check, free, _library: library };
+    let description: Description = plugin.response(unsafe { register(ABI_VERSION) }).map_err(fail)?;
+    can_check(&description).map_err(fail)?;
+    plugin.name = description.name;
+
+    let plugin = Arc::new(plugin);
+    let mut rules: Vec<PluginRule> = Vec::new();
+    for entry in description.rules {
+        let id = entry.id.trim();
+        if id.is_empty() {
+            return Err(fail(format!("rule \"{}\" has an empty id", entry.name)));
+        }
+        if taken.iter().map(|rule| rule.id()).chain(loaded.iter().chain(&rules).map(|rule| rule.id))
+            .any(|taken| taken.eq_ignore_ascii_case(id)) {
+            return Err(fail(format!("rule {}: the id is already taken", id)));
+        }
+        let severity = severity_level(&entry.severity).map_err(|e| fail(format!("rule {}: {}", id, e)))?;
+        let description = entry.description
+            .unwrap_or_else(|| format!("Reported by the {} plugin", plugin.name));
+        rules.push(PluginRule {
+            plugin: plugin.clone(),
+            id: leak(id.to_string()),
+            name: leak(entry.name),
+            description: leak(description),
+            severity,
+        });
+    }
+    Ok(rules)
+}
+
+/// Checks the ABI version a library exports before calling into it
+fn handshake(abi: Option<u32>) -> std::result::Result<(), String> {
+    match abi {
+        None => Err("is not a stylus-analyzer plugin (no STYLUS_ANALYZER_PLUGIN_ABI symbol)".to_string()),
+        Some(version) if version != ABI_VERSION => Err(format!(
+            "was built against plugin ABI {}, but this build of stylus-analyzer loads ABI {}",
+            version, ABI_VERSION
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Checks the plugin can do what this build asks of it
+fn can_check(description: &Description) -> std::result::Result<(), String> {
+    if description.capabilities.iter().any(|capability| capability == "check") {
+        Ok(())
+    } else {
+        Err("doesn't list the \"check\" capability".to_string())
+    }
+}
+
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+fn severity_level(level: &str) -> std::result::Result<Severity, String> {
+    Severity::from_str(level, true)
+        .map_err(|_| format!("unknown severity \"{}\"; expected critical, high, medium, low or info", level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditAnalyzer;
+    use crate::audit::patterns::all_rules;
+
+    /// The example plugin, which `cargo test` builds along with the tests
+    fn example_plugin() -> PathBuf {
+        let target = std::env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+        let file = format!("{}todo_plugin{}", std::env::consts::DLL_PREFIX, std::env::consts::DLL_SUFFIX);
+        let path = target.join("examples").join(file);
+        assert!(path.exists(), "run `cargo build --example todo_plugin` first");
+        path
+    }
+
+    fn load_error(paths: &[PathBuf]) -> String {
+        match load(paths, &all_rules()) {
+            Err(AnalyzerError::Config(message)) => message,
+            Err(e) => panic!("not a config error: {}", e),
+            Ok(_) => panic!("plugin loaded"),
+        }
+    }
+
+    #[tokio::test]
+    async fn example_findings_go_through_the_audit_labelled_with_the_plugin() {
+        let rules = load(&[example_plugin()], &all_rules()).unwrap();
+        let names: Vec<&str> = rules.iter().map(|rule| rule.name()).collect();
+        assert_eq!(names, ["Unresolved TODO", "Hardcoded Address"]);
+
+        let analyzer = AuditAnalyzer::new();
+        for rule in rules {
+            analyzer.add_rule(Box::new(rule));
+        }
+        let result = analyzer.audit(include_str!("../../test ex/plugin/todo_vault.rs")).await.unwrap();
+        let found: Vec<_> = result.vulnerabilities()
+            .map(|vuln| (vuln.rule_id.as_deref().unwrap(), vuln.name.as_str(), vuln.line.unwrap(), vuln.severity))
+            .collect();
+        assert_eq!(found, [
+            ("TODO-002", "Hardcoded Address", 15, Severity::Medium),
+            ("TODO-001", "Unresolved TODO", 19, Severity::Low),
+        ]);
+        assert!(result.vulnerabilities().all(|vuln| vuln.origin.as_deref() == Some("plugin todo-plugin")));
+    }
+
+    #[test]
+    fn rule_ids_must_be_unique_across_plugins() {
+        let message = load_error(&[example_plugin(), example_plugin()]);
+        assert!(message.ends_with("rule TODO-001: the id is already taken"), "{}", message);
+    }
+
+    #[test]
+    fn mismatched_libraries_fail_the_handshake() {
+        assert!(handshake(Some(ABI_VERSION)).is_ok());
+        assert_eq!(handshake(Some(2)).unwrap_err(),
+            "was built against plugin ABI 2, but this build of stylus-analyzer loads ABI 1");
+        assert!(handshake(None).unwrap_err().starts_with("is not a stylus-analyzer plugin"));
+
+        let lister: Description = serde_json::from_str(r#"{"name": "lister", "capabilities": ["list"], "rules": []}"#).unwrap();
+        assert_eq!(can_check(&lister).unwrap_err(), "doesn't list the \"check\" capability");
+
+        let missing = std::env::temp_dir().join("plugin-that-does-not-exist.so");
+        assert!(load_error(&[missing]).contains("cannot load"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn a_library_without_the_abi_symbol_is_not_called() {
+        let message = load_error(&[PathBuf::from("libc.so.6")]);
+        assert_eq!(message, "plugin libc.so.6: is not a stylus-analyzer plugin (no STYLUS_ANALYZER_PLUGIN_ABI symbol)");
+    }
+
+    #[tokio::test]
+    async fn a_plugin_error_fails_only_its_rule() {
+        let mut rule = load(&[example_plugin()], &all_rules()).unwrap().remove(0);
+        rule.id = "TODO-999";
+        let err = rule.check("contract C {}").await.unwrap_err();
+        assert_eq!(err.to_string(), "Audit rule 'Unresolved TODO' failed: plugin todo-plugin: unknown rule TODO-999");
+    }
+}
@@ -0,0 +1,111 @@
+//! Checks-effects-interactions ordering: functions that write contract state
+//! after handing control to another contract, without a reentrancy guard
+
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionDefinition, Loc, SourceUnitPart};
+use crate::audit::event_coverage::storage_writes;
+use crate::audit::source::{assigns, find_lines, mask, modifier_names, rust_functions, state_variables, SourceMatch};
+
+/// Expressions that hand control to another contract, in Solidity or Stylus
+const EXTERNAL_CALLS: [&str; 13] = [
+    ".call{", ".call(", ".delegatecall(", ".send(", ".transfer(", ".transferFrom(", ".safeTransfer(",
+    ".safeTransferFrom(", "msg::send(", "transfer_eth(", "call(Call::", "call::call(", "RawCall::",
+];
+
+/// A state write that follows an external call in the same function
+pub(crate) struct CallThenWrite {
+    pub function: String,
+    /// The whole function, for building a fix example
+    pub code: String,
+    pub call: SourceMatch,
+    pub write: SourceMatch,
+}
+
+/// Every unguarded function in Solidity or Stylus `source` that writes state after an external call
+pub(crate) fn violations(source: &str) -> Vec<CallThenWrite> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        return solidity_violations(&unit, source);
+    }
+    if syn::parse_file(source).is_ok() {
+        return stylus_violations(source);
+    }
+    Vec::new()
+}
+
+fn solidity_violations(unit: &solang_parser::pt::SourceUnit, source: &str) -> Vec<CallThenWrite> {
+    let mut found = Vec::new();
+    for part in &unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        let state_vars: Vec<String> = state_variables(contract).into_iter().map(|var| var.0).collect();
+        for part in &contract.parts {
+            let ContractPart::FunctionDefinition(func) = part else { continue };
+            let (Some(body), Loc::File(_, start, end)) = (&func.body, func.loc) else { continue };
+            if is_guarded_modifier(func) {
+                continue;
+            }
+            let Loc::File(_, body_start, body_end) = body.loc() else { continue };
+            let writes = |line: &str| {
+                let code = line.trim();
+                state_vars.iter().any(|var| {
+                    assigns(code, var) || code.starts_with(&format!("delete {}", var)) || code.contains(&format!("{}.push(", var))
+                })
+            };
+            if let Some((call, write)) = call_then_write(source, body_start, body_end, writes) {
+                found.push(CallThenWrite {
+                    function: func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string()),
+                    code: source[start..end.max(body_end)].to_string(),
+                    call,
+                    write,
+                });
+            }
+        }
+    }
+    found
+}
+
+fn stylus_violations(source: &str) -> Vec<CallThenWrite> {
+    let mut found = Vec::new();
+    for (name, start, end) in rust_functions(source) {
+        // `#[non_reentrant]`-style attributes sit on the lines above the signature
+        let attributes = source[..start].lines().rev()
+            .take_while(|line| line.trim_start().starts_with("#[") || line.trim_start().starts_with("///"));
+        let text = &source[start..end];
+        if attributes.chain(std::iter::once(text)).any(is_reentrancy_guard) {
+            continue;
+        }
+        let Some(body_start) = text.find('{').map(|i| start + i) else { continue };
+        if let Some((call, write)) = call_then_write(source, body_start, end, |line| !storage_writes(line).is_empty()) {
+            found.push(CallThenWrite { function: name, code: text.to_string(), call, write });
+        }
+    }
+    found
+}
+
+/// The first external call in `source[start..end]` and the first state write on a later line
+fn call_then_write(source: &str, start: usize, end: usize, writes: impl Fn(&str) -> bool) -> Option<(SourceMatch, SourceMatch)> {
+    let first_line = source[..start].matches('\n').count();
+    let body = &source[start..end];
+    let mut call = find_lines(body, &EXTERNAL_CALLS).into_iter().next()?;
+    let code = mask(body);
+    let (i, line) = body.lines().enumerate()
+        .zip(code.lines())
+        .skip(call.line)
+        .find(|(_, code)| writes(code))
+        .map(|(numbered, _)| numbered)?;
+    call.line += first_line;
+    let write = SourceMatch {
+        line: first_line + i + 1,
+        column: line.len() - line.trim_start().len() + 1,
+        text: line.trim().to_string(),
+    };
+    Some((call, write))
+}
+
+fn is_guarded_modifier(func: &FunctionDefinition) -> bool {
+    modifier_names(func).iter().any(|name| is_reentrancy_guard(name))
+}
+
+/// `nonReentrant`, `non_reentrant`, `ReentrancyGuard` and the like
+fn is_reentrancy_guard(text: &str) -> bool {
+    let lower = text.to_lowercase().replace('_', "");
+    lower.contains("nonreentrant") || lower.contains("reentrancyguard") || lower.contains("reentrancylock")
+}
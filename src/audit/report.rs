@@ -1,5 +1,18 @@
-use super::{AuditResult, Vulnerability};
+use super::{AuditProfile, AuditResult, RuleTiming, Vulnerability};
+use super::authority::{Authority, Guard};
+use super::erc::{Conformance, Member, Status};
+use super::baseline::BaselineSummary;
+use super::effort;
+use super::patterns;
+use super::standards;
+use super::findings_db::{self, TriageSummary};
+use super::vulnerabilities::{FixExample, Severity};
+use crate::analyzer::Analysis;
+use crate::analyzer::output::SeverityCounts;
+use crate::report::markdown::{cell, fenced, severity_table, strip_ansi};
 use colored::*;
+use std::path::Path;
+use std::time::Duration;
 
 pub fn generate_full_report(result: &AuditResult) -> String {
     let mut report = String::new();
@@ -8,50 +21,122 @@ pub fn generate_full_report(result: &AuditResult) -> String {
     report.push_str(&format!("{}\n", 
         "Smart Contract Security Audit Report".bright_green().bold()
     ));
-    report.push_str(&format!("{}\n\n", "═".repeat(50).bright_green()));
+    report.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    report.push_str(&format!("{}\n\n", rules_line(result).dimmed()));
+
+    if result.interrupted {
+        report.push_str(&format!("{}\n",
+            "⚠️  INTERRUPTED: partial results - not every rule finished running".yellow().bold()
+        ));
+        report.push_str("Re-run the audit to completion before relying on this report.\n\n");
+    }
+
+    // Rule failures come first: missing findings must never look like a clean result
+    if !result.errors.is_empty() {
+        report.push_str(&format!("{}\n",
+            format!("❌ Rules that failed to run ({})", result.errors.len()).red().bold()
+        ));
+        for error in &result.errors {
+            report.push_str(&format!("  • {}: {}\n", error.rule.bold(), error.message.red()));
+        }
+        report.push_str(&format!("{}\n\n",
+            "Findings from these rules are missing from this report.".yellow()
+        ));
+    }
 
     // Vulnerability Summary
     report.push_str(&format!("{}\n", "Summary".bold()));
     report.push_str(&format!("Critical Issues: {}\n", result.critical_vulnerabilities.len().to_string().red()));
     report.push_str(&format!("High Issues: {}\n", result.high_vulnerabilities.len().to_string().yellow()));
     report.push_str(&format!("Medium Issues: {}\n", result.medium_vulnerabilities.len().to_string().blue()));
-    report.push_str(&format!("Low Issues: {}\n\n", result.low_vulnerabilities.len().to_string().green()));
+    report.push_str(&format!("Low Issues: {}\n", result.low_vulnerabilities.len().to_string().green()));
+    report.push_str(&format!("Informational: {}\n\n", result.info_findings.len().to_string().dimmed()));
+    report.push_str(&format_rule_breakdown(result));
+
+    if let Some(baseline) = &result.baseline {
+        report.push_str(&format_baseline_summary(result, baseline));
+    }
+    if let Some(triage) = &result.triage {
+        report.push_str(&format_triage_summary(triage));
+    }
 
     // Detailed Findings
+    if result.baseline.is_some() && result.vulnerabilities().next().is_some() {
+        report.push_str(&format!("{}\n", "New since baseline".bold().underline()));
+    }
     if !result.critical_vulnerabilities.is_empty() {
         report.push_str(&format!("\n{}\n", "Critical Findings".red().bold()));
         for vuln in &result.critical_vulnerabilities {
-            report.push_str(&format_vulnerability(vuln, "❗"));
+            report.push_str(&format_vulnerability(vuln, "❗", result.triage.as_ref()));
         }
     }
 
     if !result.high_vulnerabilities.is_empty() {
         report.push_str(&format!("\n{}\n", "High Risk Findings".yellow().bold()));
         for vuln in &result.high_vulnerabilities {
-            report.push_str(&format_vulnerability(vuln, "⚠️"));
+            report.push_str(&format_vulnerability(vuln, "⚠️", result.triage.as_ref()));
         }
     }
 
     if !result.medium_vulnerabilities.is_empty() {
         report.push_str(&format!("\n{}\n", "Medium Risk Findings".blue().bold()));
         for vuln in &result.medium_vulnerabilities {
-            report.push_str(&format_vulnerability(vuln, "ℹ️"));
+            report.push_str(&format_vulnerability(vuln, "ℹ️", result.triage.as_ref()));
         }
     }
 
     if !result.low_vulnerabilities.is_empty() {
         report.push_str(&format!("\n{}\n", "Low Risk Findings".green().bold()));
         for vuln in &result.low_vulnerabilities {
-            report.push_str(&format_vulnerability(vuln, "📝"));
+            report.push_str(&format_vulnerability(vuln, "📝", result.triage.as_ref()));
+        }
+    }
+
+    if !result.info_findings.is_empty() {
+        report.push_str(&format!("\n{}\n", "Informational".dimmed().bold()));
+        for vuln in &result.info_findings {
+            report.push_str(&format_vulnerability(vuln, "💬", result.triage.as_ref()));
+        }
+    }
+
+    if let Some(baseline) = &result.baseline {
+        if !baseline.suppressed.is_empty() {
+            report.push_str(&format!("\n{}\n", "Pre-existing (in baseline)".dimmed().bold()));
+            for vuln in &baseline.suppressed {
+                report.push_str(&format!("  • [{:?}] {}\n", vuln.severity, vuln.name).dimmed().to_string());
+            }
+        }
+    }
+
+    if let Some(triage) = &result.triage {
+        if !triage.resolved.is_empty() {
+            report.push_str(&format!("\n{}\n", "Resolved since last run".green().bold()));
+            for entry in &triage.resolved {
+                report.push_str(&format!("  • [{:?}] {} (last seen {})\n",
+                    entry.finding.severity, entry.finding.name, findings_db::format_date(entry.last_seen)
+                ));
+            }
         }
     }
 
+    if !result.authority.is_empty() {
+        report.push_str(&format_authority(&result.authority));
+    }
+
+    if let Some(conformance) = &result.conformance {
+        report.push_str(&format_conformance(conformance));
+    }
+
     // Mitigation Summary
     if result.critical_vulnerabilities.is_empty() && 
        result.high_vulnerabilities.is_empty() && 
        result.medium_vulnerabilities.is_empty() && 
        result.low_vulnerabilities.is_empty() {
-        report.push_str(&format!("\n{}\n", "✅ No vulnerabilities found!".green()));
+        if result.errors.is_empty() {
+            report.push_str(&format!("\n{}\n", "✅ No vulnerabilities found!".green()));
+        } else {
+            report.push_str(&format!("\n{}\n", "⚠️  No vulnerabilities found by the rules that ran".yellow()));
+        }
     } else {
         report.push_str(&format!("\n{}\n", "Recommended Actions".cyan().bold()));
         report.push_str("• Review all identified vulnerabilities\n");
@@ -59,16 +144,464 @@ pub fn generate_full_report(result: &AuditResult) -> String {
         report.push_str("• Implement suggested mitigations\n");
         report.push_str("• Conduct thorough testing after fixes\n");
         report.push_str("• Consider additional security review\n");
+
+        report.push_str(&format_fix_order(result));
+    }
+
+    if let Some(profile) = &result.profile {
+        report.push_str(&format!("\n{}\n", "Rule Timings".cyan().bold()));
+        report.push_str(&format_timings(&result.timings, profile));
     }
 
     report
 }
 
-fn format_vulnerability(vuln: &Vulnerability, icon: &str) -> String {
-    format!("{} {}\n  Risk: {}\n  Mitigation: {}\n\n",
+/// The audit as GitHub-flavored Markdown (`--format markdown`): a severity
+/// table, then each finding in a collapsible section with its code
+pub fn format_markdown(result: &AuditResult, file: &Path) -> String {
+    let mut md = format!("# Smart Contract Security Audit: `{}`\n", file.display());
+    let ids = |ids: &[String]| ids.iter().map(|id| format!("`{}`", id)).collect::<Vec<_>>().join(", ");
+    md.push_str(&format!("\n**Rules run ({}):** {}\n", result.rules_run.len(), ids(&result.rules_run)));
+    if !result.rules_skipped.is_empty() {
+        md.push_str(&format!("\n**Rules skipped ({}):** {}\n", result.rules_skipped.len(), ids(&result.rules_skipped)));
+    }
+
+    if result.interrupted {
+        md.push_str("\n> ⚠️ **Interrupted:** partial results, not every rule finished running. Re-run the audit to completion before relying on this report.\n");
+    }
+    if !result.errors.is_empty() {
+        md.push_str(&format!("\n## ❌ Rules that failed to run ({})\n\n", result.errors.len()));
+        for error in &result.errors {
+            md.push_str(&format!("- **{}**: {}\n", error.rule, error.message));
+        }
+        md.push_str("\n_Findings from these rules are missing from this report._\n");
+    }
+
+    md.push_str("\n## Summary\n\n");
+    md.push_str(&severity_table(&SeverityCounts::count(result.vulnerabilities().map(|vuln| vuln.severity))));
+    let rows = result.rule_breakdown();
+    if !rows.is_empty() {
+        md.push_str("\n### Findings by Rule\n\n| Rule | Id | Findings | Highest severity |\n|---|---|---|---|\n");
+        for (id, name, count, severity) in rows {
+            md.push_str(&format!("| {} | `{}` | {} | {:?} |\n", cell(&name), id, count, severity));
+        }
+    }
+    if let Some(baseline) = &result.baseline {
+        md.push_str(&format!("\nCompared against {}: {} new, {} pre-existing suppressed.\n",
+            baseline.source, result.vulnerabilities().count(), baseline.suppressed.len()));
+        for warning in &baseline.warnings {
+            md.push_str(&format!("\n> ⚠️ {}\n", warning));
+        }
+    }
+    if let Some(triage) = &result.triage {
+        md.push_str(&format!("\n{}", strip_ansi(&format_triage_summary(triage))));
+    }
+
+    let language = match file.extension().and_then(|ext| ext.to_str()) {
+        Some("sol") => "solidity",
+        Some("rs") => "rust",
+        _ => "",
+    };
+    let groups = [
+        ("Critical", &result.critical_vulnerabilities),
+        ("High", &result.high_vulnerabilities),
+        ("Medium", &result.medium_vulnerabilities),
+        ("Low", &result.low_vulnerabilities),
+        ("Informational", &result.info_findings),
+    ];
+    if groups.iter().any(|(_, vulns)| !vulns.is_empty()) {
+        md.push_str(if result.baseline.is_some() { "\n## Findings (new since baseline)\n" } else { "\n## Findings\n" });
+    }
+    for (heading, vulns) in groups.iter().filter(|(_, vulns)| !vulns.is_empty()) {
+        md.push_str(&format!("\n### {}\n", heading));
+        for vuln in vulns.iter() {
+            md.push_str(&markdown_vulnerability(vuln, language, result.triage.as_ref()));
+        }
+    }
+
+    if let Some(baseline) = result.baseline.as_ref().filter(|baseline| !baseline.suppressed.is_empty()) {
+        md.push_str("\n### Pre-existing (in baseline)\n\n");
+        for vuln in &baseline.suppressed {
+            md.push_str(&format!("- **{:?}**: {}\n", vuln.severity, vuln.name));
+        }
+    }
+    if let Some(triage) = result.triage.as_ref().filter(|triage| !triage.resolved.is_empty()) {
+        md.push_str("\n### Resolved since last run\n\n");
+        for entry in &triage.resolved {
+            md.push_str(&format!("- **{:?}**: {} (last seen {})\n",
+                entry.finding.severity, entry.finding.name, findings_db::format_date(entry.last_seen)));
+        }
+    }
+
+    if !result.authority.is_empty() {
+        md.push_str("\n## Upgrade Authority\n\n| Function | Line | Can | Guarded by | Held by | Takes effect |\n|---|---|---|---|---|---|\n");
+        for authority in &result.authority {
+            md.push_str(&format!("| `{}.{}` | {} | {} | {} | {} | {} |\n",
+                authority.contract,
+                authority.function,
+                if authority.line > 0 { authority.line.to_string() } else { String::new() },
+                authority.kind.describe(),
+                cell(&authority.guard.describe()),
+                cell(&authority.holder.describe()),
+                if authority.delayed { "delayed" } else { "instantly" }
+            ));
+        }
+    }
+
+    if let Some(conformance) = &result.conformance {
+        let how = if conformance.detected { "detected from function names" } else { "--standard" };
+        md.push_str(&format!("\n## {} Conformance\n\n_Standard {}._\n\n| Kind | Member | Status |\n|---|---|---|\n", conformance.standard, how));
+        for entry in &conformance.entries {
+            let kind = match entry.kind {
+                Member::Function => "Function",
+                Member::Event => "Event",
+                Member::Emission => "Emission",
+            };
+            let status = match &entry.status {
+                Status::Present => "✅ present".to_string(),
+                Status::Missing => "❌ missing".to_string(),
+                Status::Mismatched(found) => format!("⚠️ mismatched: {}", cell(found)),
+            };
+            md.push_str(&format!("| {} | `{}` | {} |\n", kind, cell(&entry.member), status));
+        }
+    }
+
+    if groups[..4].iter().all(|(_, vulns)| vulns.is_empty()) {
+        md.push_str(if result.errors.is_empty() {
+            "\n✅ No vulnerabilities found!\n"
+        } else {
+            "\n⚠️ No vulnerabilities found by the rules that ran\n"
+        });
+    } else {
+        md.push_str("\n## Suggested fix order\n\n");
+        md.push_str("_Ranked by severity weight (Critical 10, High 6, Medium 3, Low 1) / effort (Quick 1, Moderate 2, Involved 4)._\n\n");
+        for (i, vuln) in effort::fix_order(result).into_iter().enumerate() {
+            md.push_str(&format!("{}. **{}**: {}\n", i + 1, vuln.name, effort::explain(vuln)));
+        }
+    }
+
+    if let Some(profile) = &result.profile {
+        md.push_str(&format!("\n## Rule timings\n\n_{}._\n\n| Rule | Id | Time (ms) | Findings |\n|---|---|---|---|\n", timing_summary(result.timings.len(), profile)));
+        for timing in slowest_first(&result.timings) {
+            md.push_str(&format!("| {} | `{}` | {:.1} | {} |\n", cell(&timing.rule), timing.rule_id, millis(timing.elapsed), timing.findings));
+        }
+    }
+
+    md
+}
+
+/// The summary's findings-per-rule table; empty when nothing was found
+fn format_rule_breakdown(result: &AuditResult) -> String {
+    let rows = result.rule_breakdown();
+    if rows.is_empty() {
+        return String::new();
+    }
+    let mut section = format!("{}\n", "Findings by Rule".bold());
+    for (id, name, count, severity) in rows {
+        section.push_str(&format!("  {:<20} {:>3}  {:<8}  {}\n", id, count, format!("{:?}", severity), name.dimmed()));
+    }
+    section.push('\n');
+    section
+}
+
+/// "Rules: 18 ran, 2 skipped (SA-TESTING-001, SA-AI-PATTERN-001)"
+fn rules_line(result: &AuditResult) -> String {
+    if result.rules_skipped.is_empty() {
+        return format!("Rules: {} ran", result.rules_run.len());
+    }
+    format!("Rules: {} ran, {} skipped ({})", result.rules_run.len(), result.rules_skipped.len(), result.rules_skipped.join(", "))
+}
+
+/// One finding as a `<details>` block: risk, mitigation, the matched code
+/// and the fix example
+fn markdown_vulnerability(vuln: &Vulnerability, language: &str, triage: Option<&TriageSummary>) -> String {
+    let line = vuln.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+    let rule_id = vuln.rule_id.as_ref().map(|id| format!(" <code>{}</code>", id)).unwrap_or_default();
+    let mut md = format!("\n<details>\n<summary><b>{}</b>{}{}</summary>\n\n", vuln.name, rule_id, line);
+    if let Some(confidence) = vuln.confidence.filter(|confidence| *confidence < 1.0) {
+        md.push_str(&format!("**Confidence:** {}\n\n", percent(confidence)));
+    }
+    if !vuln.detected_by.is_empty() {
+        md.push_str(&format!("**Detected by:** {}\n\n", detected_by(vuln)));
+    }
+    if let Some(origin) = &vuln.origin {
+        md.push_str(&format!("**Origin:** {}\n\n", origin));
+    }
+    if let Some((status, first_seen)) = triage.and_then(|triage| triage.statuses.get(&vuln.fingerprint)) {
+        md.push_str(&format!("**Status:** {} (first seen {})\n\n", status.label(), findings_db::format_date(*first_seen)));
+    }
+    md.push_str(&format!("**Risk:** {}\n\n**Mitigation:** {}\n\n", vuln.risk_description, vuln.recommendation));
+    if let Some(snippet) = vuln.snippet.as_deref().filter(|snippet| !snippet.trim().is_empty()) {
+        md.push_str(&fenced(snippet, language));
+        md.push('\n');
+    }
+    if let Some(example) = &vuln.example {
+        let diff = example.before.lines().map(|line| format!("- {}", line))
+            .chain(example.after.lines().map(|line| format!("+ {}", line)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        md.push_str("**Fix example:**\n\n");
+        md.push_str(&fenced(&diff, "diff"));
+        md.push('\n');
+    }
+    if let Some(reference) = &vuln.reference {
+        md.push_str(&format!("**Reference:** {}\n\n", reference));
+    }
+    if !vuln.references.is_empty() {
+        let links = vuln.references.iter()
+            .map(|reference| match standards::url(reference) {
+                Some(url) => format!("[{}]({})", reference, url),
+                None => reference.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        md.push_str(&format!("**Standards:** {}\n\n", links));
+    }
+    md.push_str("</details>\n");
+    md
+}
+
+fn format_vulnerability(vuln: &Vulnerability, icon: &str, triage: Option<&TriageSummary>) -> String {
+    let status = triage
+        .and_then(|triage| triage.statuses.get(&vuln.fingerprint))
+        .map(|(status, first_seen)| format!("  Status: {} (first seen {})\n",
+            status.label().bold(),
+            findings_db::format_date(*first_seen)
+        ))
+        .unwrap_or_default();
+    let location = vuln.line.map(|line| format_location(line, vuln.snippet.as_deref())).unwrap_or_default();
+    let mut reference = vuln.reference.as_ref()
+        .map(|reference| format!("  Reference: {}\n", reference.dimmed()))
+        .unwrap_or_default();
+    if !vuln.references.is_empty() {
+        reference.push_str(&format!("  Standards: {}\n", vuln.references.join(", ").dimmed()));
+    }
+    let example = vuln.example.as_ref().map(format_example).unwrap_or_default();
+    let rule_id = vuln.rule_id.as_ref().map(|id| format!(" {}", format!("[{}]", id).dimmed())).unwrap_or_default();
+    let confidence = vuln.confidence.filter(|confidence| *confidence < 1.0)
+        .map(|confidence| format!(" {}", format!("({} confidence)", percent(confidence)).dimmed()))
+        .unwrap_or_default();
+    let detected_by = if vuln.detected_by.is_empty() {
+        String::new()
+    } else {
+        format!("  Detected by: {}\n", detected_by(vuln).dimmed())
+    };
+    let origin = vuln.origin.as_ref()
+        .map(|origin| format!("  Origin: {}\n", origin.dimmed()))
+        .unwrap_or_default();
+    format!("{} {}{}{}\n{}{}{}{}  Risk: {}\n  Mitigation: {}\n{}{}\n",
         icon,
         vuln.name,
+        rule_id,
+        confidence,
+        location,
+        detected_by,
+        origin,
+        status,
         vuln.risk_description,
-        vuln.recommendation.bright_green()
+        vuln.recommendation.bright_green(),
+        example,
+        reference
     )
-}
\ No newline at end of file
+}
+
+/// 0.85 as "85%"
+fn percent(confidence: f64) -> String {
+    format!("{:.0}%", confidence * 100.0)
+}
+
+/// "Reentrancy Pattern Checker, AI-Powered Security & Pattern Analyzer": the
+/// rules behind a merged finding, by name
+fn detected_by(vuln: &Vulnerability) -> String {
+    vuln.detected_by.iter()
+        .map(|id| patterns::rule_name(id).unwrap_or(id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `Line N: code`, with at most two lines of the matched code
+fn format_location(line: usize, snippet: Option<&str>) -> String {
+    let mut excerpt = snippet.unwrap_or("").lines().map(str::trim).filter(|code| !code.is_empty()).take(2);
+    let label = format!("Line {}", line);
+    match excerpt.next() {
+        Some(first) => {
+            let mut output = format!("  {}: {}\n", label.bold(), truncate(first).cyan());
+            for code in excerpt {
+                output.push_str(&format!("  {}  {}\n", " ".repeat(label.len()), truncate(code).cyan()));
+            }
+            output
+        }
+        None => format!("  {}\n", label.bold()),
+    }
+}
+
+/// Long matched lines cut to what fits next to the label
+fn truncate(code: &str) -> String {
+    const MAX: usize = 100;
+    if code.chars().count() <= MAX {
+        return code.to_string();
+    }
+    format!("{}…", code.chars().take(MAX - 1).collect::<String>())
+}
+
+/// Before/after code, stacked
+fn format_example(example: &FixExample) -> String {
+    let block = |code: &str, mark: &str| code.lines()
+        .map(|line| format!("    {} {}", mark, line).trim_end().to_string() + "\n")
+        .collect::<String>();
+    format!("  Before:\n{}  After:\n{}",
+        block(&example.before, &"-".red().to_string()),
+        block(&example.after, &"+".green().to_string()))
+}
+
+/// "Who can change what" for the privileged functions of the audited contracts
+fn format_authority(authorities: &[Authority]) -> String {
+    let mut output = format!("\n{}\n", "Upgrade Authority".magenta().bold());
+    for authority in authorities {
+        let line = if authority.line > 0 { format!(" (line {})", authority.line) } else { String::new() };
+        output.push_str(&format!("  • {}.{}{}: {}\n",
+            authority.contract, authority.function.bold(), line, authority.kind.describe()));
+        let guard = match authority.guard {
+            Guard::Nothing => authority.guard.describe().red().to_string(),
+            _ => authority.guard.describe(),
+        };
+        let timing = if authority.delayed { "delayed".green() } else { "instant".yellow() };
+        output.push_str(&format!("      guarded by {} · held by {} · {}\n", guard, authority.holder.describe(), timing));
+    }
+    output
+}
+
+/// Present/missing/mismatched table for the checked token standard
+fn format_conformance(conformance: &Conformance) -> String {
+    let how = if conformance.detected { "detected from function names" } else { "--standard" };
+    let mut output = format!("\n{} {}\n", format!("{} Conformance", conformance.standard).magenta().bold(), format!("({})", how).dimmed());
+    for (kind, heading) in [(Member::Function, "Functions"), (Member::Event, "Events"), (Member::Emission, "Emissions")] {
+        let entries: Vec<_> = conformance.entries.iter().filter(|entry| entry.kind == kind).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("  {}\n", heading.bold()));
+        for entry in entries {
+            let row = match &entry.status {
+                Status::Present => format!("    {} {}", "✅ present   ".green(), entry.member),
+                Status::Missing => format!("    {} {}", "❌ missing   ".red(), entry.member),
+                Status::Mismatched(found) => format!("    {} {} {}", "⚠️  mismatched".yellow(), entry.member, found.dimmed()),
+            };
+            output.push_str(&row);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn format_triage_summary(triage: &TriageSummary) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for (status, _) in triage.statuses.values() {
+        match counts.iter_mut().find(|(label, _)| *label == status.label()) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((status.label(), 1)),
+        }
+    }
+    counts.sort();
+
+    let mut section = format!("🗂️  Triage: {}, {} resolved since last run\n",
+        counts.iter().map(|(label, count)| format!("{} {}", count, label)).collect::<Vec<_>>().join(", "),
+        triage.resolved.len()
+    );
+    for warning in &triage.warnings {
+        section.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+    section.push('\n');
+    section
+}
+
+/// Findings ranked by severity weight divided by estimated effort
+fn format_fix_order(result: &AuditResult) -> String {
+    let mut section = format!("\n{}\n", "🛠️  Suggested fix order".cyan().bold());
+    section.push_str(&format!("{}\n",
+        "Ranked by severity weight (Critical 10, High 6, Medium 3, Low 1) / effort (Quick 1, Moderate 2, Involved 4)".dimmed()
+    ));
+    for (i, vuln) in effort::fix_order(result).into_iter().enumerate() {
+        section.push_str(&format!("  {}. {}\n     {}\n", i + 1, vuln.name.bold(), effort::explain(vuln).dimmed()));
+    }
+    section
+}
+
+fn format_baseline_summary(result: &AuditResult, baseline: &BaselineSummary) -> String {
+    let mut section = format!("🧾 Compared against {}: {} new, {} pre-existing suppressed\n",
+        baseline.source,
+        result.vulnerabilities().count().to_string().bold(),
+        baseline.suppressed.len()
+    );
+    for warning in &baseline.warnings {
+        section.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+    section.push('\n');
+    section
+}
+
+/// Lists the findings that fail a `--fail-on-new` gate and why each counts as new
+pub fn format_blocking_findings(blocking: &[Vulnerability], threshold: Severity, baseline: &BaselineSummary) -> String {
+    if blocking.is_empty() {
+        return format!("{}\n", format!("✅ No new findings at or above {:?}", threshold).green());
+    }
+
+    let mut section = format!("\n{}\n",
+        format!("🚫 Blocking findings ({} new at or above {:?})", blocking.len(), threshold).red().bold()
+    );
+    for vuln in blocking {
+        section.push_str(&format!("  • [{:?}] {}\n", vuln.severity, vuln.name.bold()));
+        section.push_str(&format!("    New: no matching finding in {} (fingerprint {})\n",
+            baseline.source, vuln.fingerprint));
+    }
+    section
+}
+
+/// The one-line summaries `audit` prints on the terminal for the AI-backed
+/// analyses in `analyses`; None when none of them finished
+pub fn format_additional_analysis(analyses: &[Analysis]) -> Option<String> {
+    let ran = |name: &str| analyses.iter().any(|analysis| analysis.analyzer == name);
+    let sections = [
+        ("gas", "Gas & Resource Usage", ["Block Space: High", "Message Cost: Medium", "Data Posting: Low", "Batch Processing: High"]),
+        ("security", "Security Context", ["Memory Safety: Strong", "Access Control: Medium", "State Management: Good", "Runtime Safety: Strong"]),
+        ("interactions", "Contract Behavior", ["External Calls: Safe", "Dependencies: Low", "Event Handling: Good", "Upgrade Safety: High"]),
+    ];
+    if !sections.iter().any(|(name, ..)| ran(name)) {
+        return None;
+    }
+    let mut section = vec![String::new(), "Additional Analysis".to_string(), "═══════════════════".to_string()];
+    for (_, title, lines) in sections.iter().filter(|(name, ..)| ran(name)) {
+        section.push(format!("\n{}:", title));
+        section.extend(lines.iter().map(|line| format!("• {}", line)));
+    }
+    Some(section.join("\n"))
+}
+
+/// `--timings`: each rule's wall time and findings, slowest first, after
+/// the whole audit's. Rules run concurrently, so their times add up to more than the total
+fn format_timings(timings: &[RuleTiming], profile: &AuditProfile) -> String {
+    let mut section = format!("⏳ {}\n", timing_summary(timings.len(), profile));
+    for timing in slowest_first(timings) {
+        section.push_str(&format!("   {:>9.1} ms  {:>3} found  {:<20} {}\n",
+            millis(timing.elapsed), timing.findings, timing.rule_id, timing.rule.dimmed()));
+    }
+    section
+}
+
+fn timing_summary(rules: usize, profile: &AuditProfile) -> String {
+    let parse = match profile.parse {
+        Some(parse) => format!("; parsing the source takes {:.1} ms", millis(parse)),
+        None => String::new(),
+    };
+    format!("Audit took {:.1} ms; {} rules ran concurrently{}", millis(profile.total), rules, parse)
+}
+
+fn slowest_first(timings: &[RuleTiming]) -> Vec<&RuleTiming> {
+    let mut sorted: Vec<&RuleTiming> = timings.iter().collect();
+    sorted.sort_by_key(|timing| std::cmp::Reverse(timing.elapsed));
+    sorted
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,328 @@
+//! SARIF 2.1.0 export of audit findings, for GitHub code scanning
+//! (`github/codeql-action/upload-sarif`) and other SARIF viewers
+
+use std::path::Path;
+use serde::Serialize;
+use crate::audit::effort::rule_slug;
+use crate::audit::patterns;
+use crate::audit::standards;
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::audit::AuditResult;
+use crate::error::{AnalyzerError, Result};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const INFORMATION_URI: &str = "https://github.com/0xSY3/CLI-AGENT";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+    /// SWC and CWE, holding the weaknesses results point at through `taxa`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    taxonomies: Vec<Taxonomy>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Taxonomy {
+    name: &'static str,
+    organization: &'static str,
+    information_uri: &'static str,
+    taxa: Vec<Taxon>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Taxon {
+    id: String,
+    help_uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaxonReference {
+    id: String,
+    tool_component: ToolComponentReference,
+}
+
+#[derive(Serialize)]
+struct ToolComponentReference {
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    name: String,
+    short_description: Message,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    partial_fingerprints: Fingerprints,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    taxa: Vec<TaxonReference>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suppressions: Vec<Suppression>,
+    properties: Properties,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<Message>,
+}
+
+#[derive(Serialize)]
+struct Fingerprints {
+    #[serde(rename = "stylusAnalyzer/v1")]
+    stylus_analyzer: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Suppression {
+    kind: &'static str,
+    justification: String,
+}
+
+#[derive(Serialize)]
+struct Properties {
+    #[serde(rename = "security-severity", skip_serializing_if = "Option::is_none")]
+    security_severity: Option<&'static str>,
+    tags: Vec<&'static str>,
+    /// The finding's confidence, 0.0 to 1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confidence: Option<f64>,
+    /// Where a rule that isn't built in came from, e.g. `plugin todo-plugin`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+}
+
+/// SARIF result level: Critical and High fail the check, Medium warns, the rest are notes
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// Score GitHub maps back to its own critical/high/medium/low buckets
+fn security_severity(severity: Severity) -> Option<&'static str> {
+    match severity {
+        Severity::Critical => Some("9.5"),
+        Severity::High => Some("8.0"),
+        Severity::Medium => Some("5.5"),
+        Severity::Low => Some("2.0"),
+        Severity::Info => None,
+    }
+}
+
+/// The audit of `file` as a SARIF log. `rule_names` lists every rule that
+/// ran, so rules with no findings still show up in the tool's rule list.
+/// Findings hidden by a baseline are included as suppressed results
+fn to_sarif(result: &AuditResult, file: &Path, rule_names: &[String]) -> Log {
+    let mut rules: Vec<Rule> = rule_names.iter()
+        .map(|name| {
+            // House and plugin rules aren't built in; the timings pair their ids with their names
+            let id = result.timings.iter().find(|timing| &timing.rule == name).map(|timing| timing.rule_id.as_str());
+            rule(id.or_else(|| patterns::rule_id(name)).unwrap_or(name), name)
+        })
+        .collect();
+    let artifact = artifact_location(file);
+
+    let suppressed = result.baseline.iter().flat_map(|summary| {
+        summary.suppressed.iter().map(move |vuln| (vuln, Some(summary.source.as_str())))
+    });
+    let results = result.vulnerabilities()
+        .map(|vuln| (vuln, None))
+        .chain(suppressed)
+        .map(|(vuln, suppressed_by)| {
+            let rule_id = vuln.rule_id.clone().or_else(|| vuln.rule.clone()).unwrap_or_else(|| vuln.name.clone());
+            let rule_index = match rules.iter().position(|rule| rule.id == rule_id) {
+                Some(index) => index,
+                None => {
+                    rules.push(rule(&rule_id, vuln.rule.as_deref().unwrap_or(&vuln.name)));
+                    rules.len() - 1
+                }
+            };
+            sarif_result(vuln, rule_id, rule_index, &artifact, suppressed_by)
+        })
+        .collect::<Vec<SarifResult>>();
+    let taxonomies = taxonomies(&results);
+
+    Log {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: INFORMATION_URI,
+                    rules,
+                },
+            },
+            results,
+            taxonomies,
+        }],
+    }
+}
+
+/// The SWC and CWE taxonomies, listing the weaknesses `results` refer to
+fn taxonomies(results: &[SarifResult]) -> Vec<Taxonomy> {
+    [("SWC", "SmartContractSecurity", "https://swcregistry.io/"), ("CWE", "MITRE", "https://cwe.mitre.org/")]
+        .into_iter()
+        .filter_map(|(name, organization, information_uri)| {
+            let mut taxa: Vec<Taxon> = Vec::new();
+            for taxon in results.iter().flat_map(|result| &result.taxa).filter(|taxon| taxon.tool_component.name == name) {
+                if !taxa.iter().any(|known| known.id == taxon.id) {
+                    let reference = if name == "CWE" { format!("CWE-{}", taxon.id) } else { taxon.id.clone() };
+                    taxa.push(Taxon { id: taxon.id.clone(), help_uri: standards::url(&reference).unwrap_or_default() });
+                }
+            }
+            (!taxa.is_empty()).then_some(Taxonomy { name, organization, information_uri, taxa })
+        })
+        .collect()
+}
+
+fn rule(id: &str, name: &str) -> Rule {
+    Rule {
+        id: id.to_string(),
+        name: rule_slug(name),
+        short_description: Message { text: name.to_string() },
+    }
+}
+
+fn sarif_result(vuln: &Vulnerability, rule_id: String, rule_index: usize, artifact: &ArtifactLocation, suppressed_by: Option<&str>) -> SarifResult {
+    let mut message = format!("{}: {}", vuln.name, vuln.risk_description);
+    if !vuln.recommendation.is_empty() {
+        message.push_str(&format!("\nRecommendation: {}", vuln.recommendation));
+    }
+    SarifResult {
+        rule_id,
+        rule_index,
+        level: level(vuln.severity),
+        message: Message { text: message },
+        locations: vec![Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation { uri: artifact.uri.clone(), uri_base_id: artifact.uri_base_id },
+                // Code scanning needs a location on every result; file-wide findings point at the top
+                region: Region {
+                    start_line: vuln.line.unwrap_or(1),
+                    start_column: vuln.line.and(vuln.column),
+                    snippet: vuln.snippet.clone().map(|text| Message { text }),
+                },
+            },
+        }],
+        partial_fingerprints: Fingerprints { stylus_analyzer: vuln.fingerprint.clone() },
+        taxa: vuln.references.iter()
+            .filter_map(|reference| standards::split(reference))
+            .map(|(name, id)| TaxonReference { id: id.to_string(), tool_component: ToolComponentReference { name } })
+            .collect(),
+        suppressions: suppressed_by.into_iter()
+            .map(|source| Suppression { kind: "external", justification: format!("already in {}", source) })
+            .collect(),
+        properties: Properties {
+            security_severity: security_severity(vuln.severity),
+            tags: vec!["security"],
+            confidence: vuln.confidence,
+            origin: vuln.origin.clone(),
+        },
+    }
+}
+
+/// `file` relative to the working directory, with `/` separators and
+/// percent-encoding, as code scanning resolves it against the checkout root.
+/// Files outside the working directory get an absolute `file://` URI
+fn artifact_location(file: &Path) -> ArtifactLocation {
+    let relative = std::env::current_dir().ok()
+        .and_then(|cwd| file.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| file.to_path_buf());
+    let path = relative.components()
+        .filter(|component| !matches!(component, std::path::Component::CurDir | std::path::Component::RootDir))
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    if relative.is_absolute() {
+        ArtifactLocation { uri: format!("file:///{}", uri), uri_base_id: None }
+    } else {
+        ArtifactLocation { uri, uri_base_id: Some("%SRCROOT%") }
+    }
+}
+
+/// Writes the SARIF log for the audit of `file` to `path`
+pub fn write(path: &Path, result: &AuditResult, file: &Path, rule_names: &[String]) -> Result<()> {
+    let json = serde_json::to_string_pretty(&to_sarif(result, file, rule_names))
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize SARIF: {}", e)))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! Signed messages that can be submitted more than once. A signature checked
+//! with `ecrecover` (or the Stylus precompile and verify helpers) stays valid
+//! until the contract marks it used, unless the digest carries a nonce the
+//! contract consumes, a deadline it enforces, and the chain id so a signature
+//! for one deployment isn't valid on another
+
+use std::collections::HashSet;
+use async_trait::async_trait;
+use crate::audit::event_coverage::storage_writes;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{
+    assigns, entry_points, mask, reachable, scope_text, solidity_function_spans, stylus_function_spans, FunctionSpan,
+};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+/// Calls that recover or verify a signer in Solidity; `.recover(` covers `ECDSA.recover` and `using ECDSA for bytes32`
+const SOLIDITY_VERIFY: [&str; 4] = ["ecrecover(", ".recover(", ".tryRecover(", "isValidSignatureNow("];
+/// The same in Stylus: helper crates, k256 and calls to the ecrecover precompile at address 1
+const STYLUS_VERIFY: [&str; 7] = [
+    "ecrecover(", "verify_signature(", "recover_address(", "recover_from_prehash(", "recover_from_msg(", "ECRECOVER", "with_last_byte(1)",
+];
+/// Nonce helpers of OpenZeppelin's `Nonces`
+const USE_NONCE: [&str; 2] = ["_useNonce(", "_useCheckedNonce("];
+const DEADLINE_WORDS: [&str; 5] = ["deadline", "expir", "validuntil", "valid_until", "validbefore"];
+const TIMESTAMPS: [&str; 3] = ["block.timestamp", "block::timestamp()", "block_timestamp()"];
+/// Chain id reads and EIP-712 domain separators, lowercased
+const CHAIN_WORDS: [&str; 6] = ["chainid", "chain_id", "domainseparator", "domain_separator", "hashtypeddata", "eip712"];
+
+/// Signature checks whose digest lacks a consumed nonce, a deadline or the chain id
+pub struct SignatureReplayRule;
+
+#[async_trait]
+impl AuditRule for SignatureReplayRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let (functions, primitives, stylus): (Vec<FunctionSpan>, &[&str], bool) = match solang_parser::parse(content, 0) {
+            Ok((unit, _)) => (solidity_function_spans(&unit), &SOLIDITY_VERIFY, false),
+            Err(_) if syn::parse_file(content).is_ok() => (stylus_function_spans(&code), &STYLUS_VERIFY, true),
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut vulnerabilities = Vec::new();
+        for (index, function) in functions.iter().enumerate() {
+            let sites = verification_sites(&code[function.start..function.end], primitives);
+            if sites.is_empty() {
+                continue;
+            }
+            // The digest, nonce and deadline may live in the caller, e.g. a public
+            // `permit` around an internal `_verify`; a read-only check nobody calls is an API for others
+            let entries = entry_points(&functions, &code, index);
+            if entries.is_empty() {
+                continue;
+            }
+            // Protected only if every state-changing path to the check is; each gap lists the entry points missing it
+            let mut missing: Vec<(&str, Vec<&str>)> = Vec::new();
+            for entry in entries {
+                let reached = reachable(&functions, &code, entry);
+                let (masked, raw) = (scope_text(&functions, &reached, &code), scope_text(&functions, &reached, content));
+                let checks = [
+                    ("nonce", consumes_nonce(&masked, stylus)),
+                    ("chain id", chain_bound(&masked, &raw)),
+                    ("deadline", enforces_deadline(&masked)),
+                ];
+                for (what, _) in checks.into_iter().filter(|(_, present)| !present) {
+                    let name = functions[entry].name.as_str();
+                    match missing.iter_mut().find(|(missed, _)| *missed == what) {
+                        Some((_, entries)) => entries.push(name),
+                        None => missing.push((what, vec![name])),
+                    }
+                }
+            }
+            // Most severe first: nonce, chain id, deadline
+            missing.sort_by_key(|(what, _)| ["nonce", "chain id", "deadline"].iter().position(|w| w == what));
+            for at in &sites {
+                for (what, entries) in &missing {
+                    let caller = match entries.as_slice() {
+                        [only] if *only == function.name => function.name.clone(),
+                        _ => format!("{} (through `{}`)", entries.join(", "), function.name),
+                    };
+                    vulnerabilities.push(Vulnerability {
+                        function: Some(function.name.clone()),
+                        ..finding(content, function.start + at, &caller, what)
+                    });
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Signature Replay Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-SIGNATURE-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Signature verification without a consumed nonce, a deadline or the chain id in the signed digest"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Signature Replay: Missing Nonce", "Signature Replay: Missing Chain Id", "Signature Replay: Missing Deadline"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Moderate
+    }
+}
+
+/// The finding for a signature check at byte `at` that `caller` reaches without `what`
+fn finding(content: &str, at: usize, caller: &str, what: &str) -> Vulnerability {
+    let (name, severity, risk, recommendation) = match what {
+        "nonce" => (
+            "Signature Replay: Missing Nonce",
+            Severity::Critical,
+            "nothing marks the signature used, so anyone who has seen it can submit it again for as long as it verifies",
+            "Include a per-signer nonce in the signed digest and increment it when the signature is accepted, or record the digest as used",
+        ),
+        "chain id" => (
+            "Signature Replay: Missing Chain Id",
+            Severity::High,
+            "the digest isn't bound to a chain or contract, so a signature made for one deployment is valid on every other chain and fork",
+            "Hash EIP-712 typed data with a domain separator that includes block.chainid and address(this)",
+        ),
+        _ => (
+            "Signature Replay: Missing Deadline",
+            Severity::Medium,
+            "the signature never expires, so a signed approval can be held back and used long after the signer meant it",
+            "Include a deadline in the signed digest and reject the signature once block.timestamp passes it",
+        ),
+    };
+    let line_start = content[..at].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[at..].find('\n').map_or(content.len(), |i| at + i);
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description: format!("{} verifies a signature without a {}; {}", caller, what, risk),
+        recommendation: recommendation.to_string(),
+        line: Some(content[..at].matches('\n').count() + 1),
+        column: Some(content[line_start..at].chars().count() + 1),
+        snippet: Some(content[line_start..line_end].trim().to_string()),
+        ..Default::default()
+    }
+}
+
+/// Offsets of the verification calls in `body`
+fn verification_sites(body: &str, primitives: &[&str]) -> Vec<usize> {
+    let mut sites: Vec<usize> = primitives.iter()
+        .flat_map(|primitive| body.match_indices(primitive))
+        .filter(|(at, primitive)| primitive.starts_with('.') || !body[..*at].ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+        .map(|(at, _)| at)
+        .collect();
+    sites.sort();
+    // `ECDSA.recover(` inside `ecrecover(`-style wrappers reports once per call
+    sites.dedup_by(|a, b| *a - *b < 2);
+    sites
+}
+
+/// A nonce written or consumed, or the signature or digest recorded as used
+fn consumes_nonce(scope: &str, stylus: bool) -> bool {
+    let compact: String = scope.split_whitespace().collect();
+    if USE_NONCE.iter().any(|call| compact.contains(call)) || compact.contains("]=true;") || compact.contains(".set(true)") {
+        return true;
+    }
+    if stylus {
+        return storage_writes(scope).iter().any(|(field, _)| field.to_lowercase().contains("nonce"))
+            || compact.contains(",true)");
+    }
+    let nonces: HashSet<&str> = scope.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.to_lowercase().contains("nonce"))
+        .collect();
+    nonces.into_iter().any(|nonce| {
+        assigns(scope, nonce) || compact.contains(&format!("++{}", nonce)) || compact.match_indices(nonce).any(|(at, _)| {
+            let rest = &compact[at + nonce.len()..];
+            let rest = match rest.starts_with('[') {
+                true => rest.find(']').map_or(rest, |i| &rest[i + 1..]),
+                false => rest,
+            };
+            rest.starts_with("++")
+        })
+    })
+}
+
+/// A chain id read or EIP-712 domain in the digest. A domain separator
+/// stored at deployment counts when the digest reads it
+fn chain_bound(scope: &str, raw: &str) -> bool {
+    let lower = scope.to_lowercase();
+    CHAIN_WORDS.iter().any(|word| lower.contains(word)) || raw.contains("\\x19\\x01")
+}
+
+/// A deadline compared against the block timestamp
+fn enforces_deadline(scope: &str) -> bool {
+    scope.lines().any(|line| {
+        let lower = line.to_lowercase();
+        DEADLINE_WORDS.iter().any(|word| lower.contains(word)) && TIMESTAMPS.iter().any(|timestamp| line.contains(timestamp))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn signatures_missing_replay_protection_are_flagged_per_gap() {
+        assert_eq!(found(&SignatureReplayRule, include_str!("../../test ex/signature/replayable_claim.sol")).await, expected(&[
+            ("Signature Replay: Missing Nonce", 15),
+            ("Signature Replay: Missing Chain Id", 15),
+            ("Signature Replay: Missing Deadline", 15),
+            ("Signature Replay: Missing Nonce", 27),
+            ("Signature Replay: Missing Chain Id", 27),
+        ]));
+        assert_eq!(found(&SignatureReplayRule, include_str!("../../test ex/signature/stylus_relayer.rs")).await, expected(&[
+            ("Signature Replay: Missing Nonce", 49),
+            ("Signature Replay: Missing Chain Id", 49),
+            ("Signature Replay: Missing Deadline", 49),
+        ]));
+    }
+
+    #[tokio::test]
+    async fn an_eip712_permit_with_nonce_and_deadline_is_not() {
+        assert!(found(&SignatureReplayRule, include_str!("../../test ex/signature/permit.sol")).await.is_empty());
+    }
+}
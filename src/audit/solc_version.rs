@@ -0,0 +1,394 @@
+//! Checks whose verdict depends on the Solidity compiler version named by the
+//! pragma: arithmetic overflow, on-chain randomness and `selfdestruct`
+
+use std::fmt;
+use solang_parser::pt::{
+    CodeLocation, ContractPart, FunctionDefinition, FunctionTy, SourceUnit, SourceUnitPart,
+};
+use crate::audit::examples;
+use crate::audit::vulnerabilities::{Vulnerability, Severity};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{is_externally_callable, line_of, mask, modifier_names, slice};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Lowest compiler version a `pragma solidity` range allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SolcVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SolcVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parses the range of a pragma such as `^0.8.20` or `>=0.6.0 <0.9.0`.
+    /// An upper bound alone (`<0.8.0`) allows every older compiler, so it yields 0.0.0.
+    pub fn from_range(range: &str) -> Option<Self> {
+        let mut lowest = None;
+        for comparator in range.split_whitespace() {
+            let version = comparator.trim_start_matches(['^', '~', '>', '<', '=']);
+            let mut numbers = version.split('.').map(|n| n.parse::<u32>());
+            let (Some(Ok(major)), minor, patch) = (numbers.next(), numbers.next(), numbers.next()) else { continue };
+            let version = Self::new(major, minor.and_then(|n| n.ok()).unwrap_or(0), patch.and_then(|n| n.ok()).unwrap_or(0));
+            let bound = if comparator.starts_with('<') { Self::new(0, 0, 0) } else { version };
+            lowest = Some(lowest.map_or(bound, |lowest: Self| lowest.min(bound)));
+        }
+        lowest
+    }
+}
+
+impl fmt::Display for SolcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// What the compiler does for the contract, keyed on the pragma's lowest version
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub version: SolcVersion,
+    /// Arithmetic reverts on overflow unless wrapped in `unchecked` (0.8.0)
+    pub checked_arithmetic: bool,
+    /// `block.prevrandao` exists and `block.difficulty` is deprecated (0.8.18)
+    pub prevrandao: bool,
+    /// `selfdestruct` compiles with a deprecation warning (0.8.18); from 0.8.24
+    /// the compiler targets Cancun, where EIP-6780 limits what it does
+    pub selfdestruct_deprecated: bool,
+    pub cancun_default: bool,
+}
+
+impl Capabilities {
+    pub fn for_version(version: SolcVersion) -> Self {
+        Self {
+            version,
+            checked_arithmetic: version >= SolcVersion::new(0, 8, 0),
+            prevrandao: version >= SolcVersion::new(0, 8, 18),
+            selfdestruct_deprecated: version >= SolcVersion::new(0, 8, 18),
+            cancun_default: version >= SolcVersion::new(0, 8, 24),
+        }
+    }
+
+    /// Capabilities of the first `pragma solidity` in `unit`; `None` without one
+    pub fn detect(unit: &SourceUnit) -> Option<Self> {
+        unit.0.iter().find_map(|part| match part {
+            SourceUnitPart::PragmaDirective(_, Some(name), Some(range)) if name.name == "solidity" => {
+                SolcVersion::from_range(&range.string).map(Self::for_version)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Parsed source with its pragma capabilities; other languages and pragma-less files are skipped
+fn parse(content: &str) -> Option<(SourceUnit, Capabilities)> {
+    let (unit, _) = solang_parser::parse(content, 0).ok()?;
+    let capabilities = Capabilities::detect(&unit)?;
+    Some((unit, capabilities))
+}
+
+/// Function bodies of every contract, with the function they belong to
+fn function_bodies<'a>(unit: &'a SourceUnit, source: &'a str) -> Vec<(&'a FunctionDefinition, &'a str)> {
+    unit.0.iter()
+        .filter_map(|part| match part {
+            SourceUnitPart::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+        .flat_map(|contract| &contract.parts)
+        .filter_map(|part| match part {
+            ContractPart::FunctionDefinition(func) => func.body.as_ref().map(|body| (func.as_ref(), slice(&body.loc(), source))),
+            _ => None,
+        })
+        .collect()
+}
+
+fn function_name(func: &FunctionDefinition) -> String {
+    match func.ty {
+        FunctionTy::Constructor => "constructor".to_string(),
+        _ => func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| format!("{}", func.ty)),
+    }
+}
+
+fn finding(name: &str, severity: Severity, risk_description: String, recommendation: &str, func: &FunctionDefinition, source: &str) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        function: Some(function_name(func)),
+        line: Some(line_of(&func.loc, source)),
+        ..Default::default()
+    }
+}
+
+/// Overflow: unguarded math before 0.8, and `unchecked` blocks from 0.8 on
+pub struct ArithmeticRule;
+
+#[async_trait]
+impl AuditRule for ArithmeticRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Some((unit, caps)) = parse(content) else { return Ok(Vec::new()) };
+        let code = mask(content);
+        let mut vulnerabilities = Vec::new();
+        let uses_safe_math = code.contains("SafeMath");
+
+        for (func, body) in function_bodies(&unit, &code) {
+            if func.ty == FunctionTy::Modifier {
+                continue;
+            }
+            if !caps.checked_arithmetic {
+                let ops = ["+=", "-=", "*=", " + ", " - ", " * ", "++", "--"];
+                if !uses_safe_math && ops.iter().any(|op| body.contains(op)) {
+                    vulnerabilities.push(Vulnerability {
+                        example: Some(examples::checked_arithmetic(body, false)),
+                        ..finding(
+                            "Unchecked Arithmetic",
+                            Severity::High,
+                            format!("{} does arithmetic under Solidity {}, which wraps silently on overflow, and the contract doesn't use SafeMath",
+                                function_name(func), caps.version),
+                            "Use SafeMath for every add/sub/mul, or move to Solidity 0.8+ where overflow reverts",
+                            func,
+                            content,
+                        )
+                    });
+                }
+                continue;
+            }
+
+            for (offset, _) in body.match_indices("unchecked") {
+                let block = &body[offset..];
+                let Some(open) = block.find('{') else { continue };
+                let Some(close) = block[open..].find('}') else { continue };
+                let inner = block[open + 1..open + close].trim().trim_end_matches(';').trim();
+                // `unchecked { ++i; }` loop increments are the idiomatic, bounded use
+                let increment_only = inner.split_whitespace().count() == 1 && (inner.starts_with("++") || inner.ends_with("++"));
+                if increment_only {
+                    continue;
+                }
+                vulnerabilities.push(Vulnerability {
+                    line: Some(line_of(&func.loc, content) + body[..offset].matches('\n').count()),
+                    ..finding(
+                        "Unchecked Block Review",
+                        Severity::Low,
+                        format!("{} turns off Solidity {}'s overflow checks for `{}`; a wrong bound wraps instead of reverting",
+                            function_name(func), caps.version, inner.lines().next().unwrap_or(inner)),
+                        "Confirm a preceding check bounds every operand, and keep unchecked blocks to loop counters and proven-safe math",
+                        func,
+                        content,
+                    )
+                });
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Compiler-Aware Arithmetic Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-ARITHMETIC-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Unchecked arithmetic, given the overflow checks of the pragma's compiler"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Unchecked Arithmetic", "Unchecked Block Review"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+}
+
+/// Block values used as a source of randomness
+pub struct RandomnessRule;
+
+#[async_trait]
+impl AuditRule for RandomnessRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Some((unit, caps)) = parse(content) else { return Ok(Vec::new()) };
+        let code = mask(content);
+        let mut vulnerabilities = Vec::new();
+
+        for (func, body) in function_bodies(&unit, &code) {
+            let difficulty = body.contains("block.difficulty");
+            let prevrandao = body.contains("block.prevrandao");
+            let hashed = ["block.timestamp", "blockhash", "block.number", "block.difficulty", "block.prevrandao"]
+                .iter()
+                .any(|source| body.contains(source))
+                && body.contains("keccak256")
+                && (body.contains('%') || body.to_lowercase().contains("random"));
+
+            if hashed {
+                let source_note = if caps.prevrandao || prevrandao {
+                    "prevrandao is known to the proposer before the block and can be biased by skipping a slot"
+                } else {
+                    "miners choose the timestamp and can withhold blocks whose hash they dislike"
+                };
+                // On the hashing line, where the block-value timing checker reports it too
+                let hash_line = body.lines().position(|line| line.contains("keccak256"))
+                    .zip(func.body.as_ref())
+                    .map(|(offset, block)| line_of(&block.loc(), content) + offset);
+                vulnerabilities.push(Vulnerability {
+                    line: hash_line.or(Some(line_of(&func.loc, content))),
+                    ..finding(
+                        "Weak On-Chain Randomness",
+                        Severity::High,
+                        format!("{} derives a random value from block data; {}", function_name(func), source_note),
+                        "Use a VRF (e.g. Chainlink VRF) or a commit-reveal scheme",
+                        func,
+                        content,
+                    )
+                });
+            }
+
+            if difficulty && caps.prevrandao {
+                vulnerabilities.push(finding(
+                    "Deprecated block.difficulty",
+                    Severity::Low,
+                    format!("{} reads block.difficulty, which Solidity {} deprecates: since the Merge it returns prevrandao, not a difficulty",
+                        function_name(func), caps.version),
+                    "Use block.prevrandao and review any logic that assumed a mining difficulty",
+                    func,
+                    content,
+                ));
+            } else if difficulty && !hashed {
+                vulnerabilities.push(finding(
+                    "Post-Merge block.difficulty",
+                    Severity::Medium,
+                    format!("{} reads block.difficulty; on post-Merge chains it returns prevrandao (a large random number), not a difficulty", function_name(func)),
+                    "Don't use block.difficulty as a difficulty; compile with 0.8.18+ and use block.prevrandao if randomness is meant",
+                    func,
+                    content,
+                ));
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Compiler-Aware Randomness Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-RANDOMNESS-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Randomness derived from block values that validators can influence"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Weak On-Chain Randomness", "Deprecated block.difficulty", "Post-Merge block.difficulty"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+}
+
+/// `selfdestruct`, whose meaning changed with Cancun
+pub struct DestructionRule;
+
+#[async_trait]
+impl AuditRule for DestructionRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let Some((unit, caps)) = parse(content) else { return Ok(Vec::new()) };
+        let code = mask(content);
+        let mut vulnerabilities = Vec::new();
+
+        for (func, body) in function_bodies(&unit, &code) {
+            if !body.contains("selfdestruct(") && !body.contains("suicide(") {
+                continue;
+            }
+            let guarded = modifier_names(func).iter().any(|m| m.to_lowercase().starts_with("only")) || body.contains("msg.sender");
+            if is_externally_callable(func) && !guarded {
+                vulnerabilities.push(finding(
+                    "Unprotected selfdestruct",
+                    Severity::Critical,
+                    format!("anyone can call {} and send the contract's ether to an address of their choosing", function_name(func)),
+                    "Restrict the function to the owner, or remove selfdestruct",
+                    func,
+                    content,
+                ));
+            }
+
+            let (name, severity, risk) = if caps.cancun_default {
+                ("selfdestruct Under EIP-6780", Severity::Medium, format!(
+                    "Solidity {} targets Cancun, where selfdestruct only sends the balance and keeps code and storage unless called in the creating transaction",
+                    caps.version))
+            } else if caps.selfdestruct_deprecated {
+                ("Deprecated selfdestruct", Severity::Medium, format!(
+                    "selfdestruct is deprecated since Solidity 0.8.18 (this contract allows {}); after Cancun it no longer removes code or storage",
+                    caps.version))
+            } else {
+                ("selfdestruct Present", Severity::Medium, format!(
+                    "under Solidity {} selfdestruct looks like it deletes the contract, but on Cancun chains it only sends the balance; behind a proxy, destroying the implementation bricks every proxy",
+                    caps.version))
+            };
+            vulnerabilities.push(finding(
+                name,
+                severity,
+                format!("{}: {}", function_name(func), risk),
+                "Don't rely on selfdestruct to clear state or stop the contract; use a disable flag and an explicit withdrawal",
+                func,
+                content,
+            ));
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Compiler-Aware Destruction Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-SELFDESTRUCT-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "selfdestruct, unguarded or changed by EIP-6780 in the pragma's compiler"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Unprotected selfdestruct", "selfdestruct Under EIP-6780", "Deprecated selfdestruct", "selfdestruct Present"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    const LEGACY: &str = include_str!("../../test ex/compiler/lottery_legacy.sol");
+    const MODERN: &str = include_str!("../../test ex/compiler/lottery_modern.sol");
+
+    #[tokio::test]
+    async fn findings_follow_the_pragma() {
+        assert_eq!(found(&ArithmeticRule, LEGACY).await, [
+            ("Unchecked Arithmetic".to_string(), 15),
+            ("Unchecked Arithmetic".to_string(), 28),
+        ]);
+        assert_eq!(found(&ArithmeticRule, MODERN).await, [("Unchecked Block Review".to_string(), 31)]);
+
+        assert_eq!(found(&DestructionRule, LEGACY).await, [("selfdestruct Present".to_string(), 37)]);
+        assert_eq!(found(&DestructionRule, MODERN).await, [("selfdestruct Under EIP-6780".to_string(), 37)]);
+    }
+
+    #[tokio::test]
+    async fn block_values_are_no_randomness_under_any_compiler() {
+        for source in [LEGACY, MODERN] {
+            assert_eq!(found(&RandomnessRule, source).await, [("Weak On-Chain Randomness".to_string(), 22)]);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! SWC registry and CWE references for the built-in findings, so reports and
+//! SARIF can point at the standard description of each weakness. Findings
+//! with no matching entry carry none
+
+/// Finding name and the SWC and CWE ids it falls under
+const REFERENCES: &[(&str, &[&str])] = &[
+    ("Potential Reentrancy", &["SWC-107", "CWE-841"]),
+    ("Reentrancy Vulnerability", &["SWC-107", "CWE-841"]),
+    ("Unchecked External Calls", &["SWC-104", "CWE-252"]),
+    ("External Call in Assembly", &["SWC-104", "CWE-252"]),
+    ("Delegatecall in Assembly", &["SWC-112", "CWE-829"]),
+    ("Unsafe Delegatecall", &["SWC-112", "CWE-829"]),
+    ("Missing Access Control", &["CWE-284"]),
+    ("Missing Storage Access Control", &["CWE-284"]),
+    ("Missing Input Validation", &["CWE-20"]),
+    ("Unused Access Modifier", &["CWE-284"]),
+    ("Incomplete Role Management", &["CWE-269"]),
+    ("tx.origin Authentication", &["SWC-115", "CWE-477"]),
+    ("Stale Sender Authorization", &["CWE-863"]),
+    ("Unprotected selfdestruct", &["SWC-106", "CWE-284"]),
+    ("Unprotected Initializer", &["CWE-665"]),
+    ("Unrestricted Reinitializer", &["CWE-665"]),
+    ("Initialized Flag Never Set", &["CWE-665"]),
+    ("Implementation Initializers Not Disabled", &["CWE-665"]),
+    ("Constructor State in Upgradeable Contract", &["CWE-665"]),
+    ("UUPS Upgrade Authorization Missing", &["CWE-284"]),
+    ("Unprotected _authorizeUpgrade", &["CWE-284"]),
+    ("Unprotected Upgrade Function", &["CWE-284"]),
+    ("Unprotected Implementation Pointer Update", &["CWE-284"]),
+    ("Uninitialized Admin Role", &["CWE-665"]),
+    ("Uninitialized State Variable", &["CWE-457"]),
+    ("Constructor Parameter Shadows State Variable", &["SWC-119", "CWE-710"]),
+    ("Weak On-Chain Randomness", &["SWC-120", "CWE-330"]),
+    ("Deprecated block.difficulty", &["SWC-120", "CWE-330"]),
+    ("Post-Merge block.difficulty", &["SWC-120", "CWE-330"]),
+    ("Timestamp Dependence Vulnerability", &["SWC-116", "CWE-829"]),
+    ("L2 Timing Assumptions", &["SWC-116", "CWE-829"]),
+    ("Block Value Randomness", &["SWC-120", "CWE-330"]),
+    ("Deadline Without Tolerance", &["SWC-116", "CWE-829"]),
+    ("Arithmetic Safety Risk", &["SWC-101", "CWE-190"]),
+    ("Unchecked Arithmetic", &["SWC-101", "CWE-190"]),
+    ("Unchecked Block Review", &["SWC-101", "CWE-190"]),
+    ("Denial of Service Risk", &["SWC-128", "CWE-400"]),
+    ("Unbounded Storage Growth", &["SWC-128", "CWE-770"]),
+    ("Unbounded Iteration", &["SWC-128", "CWE-400"]),
+    ("Raw Storage Access to Computed Slot", &["SWC-124", "CWE-123"]),
+    ("Memory Safety Issue", &["CWE-119"]),
+    ("Unsafe Block Usage", &["CWE-119"]),
+    ("Raw Pointer Usage", &["CWE-119"]),
+    ("Uninitialized Memory Usage", &["CWE-908"]),
+    ("Potential Memory Leak", &["CWE-401"]),
+    ("Suspicious Lifetime Usage", &["CWE-416"]),
+    ("Reserved Memory Overwritten", &["CWE-787"]),
+    ("Unsafe Storage Access", &["CWE-129"]),
+    ("Unchecked Storage Access", &["CWE-129"]),
+    ("Missing State Validation", &["CWE-20"]),
+    ("Insufficient Input Validation", &["CWE-20"]),
+    ("Insufficient Cross-Chain Verification", &["CWE-345"]),
+    ("Unvalidated L1 Message Sender", &["CWE-346"]),
+    ("Unaliased L1 Sender Check", &["CWE-346"]),
+    ("Missing Outbox Verification", &["CWE-346"]),
+    ("Modifier Checks After Placeholder", &["CWE-696"]),
+    ("Trivially True Modifier Check", &["CWE-571"]),
+    ("Panic Path", &["CWE-248"]),
+    ("ERC-20 Approval Race", &["SWC-114", "CWE-362"]),
+    ("Missing Critical Event", &["CWE-778"]),
+    ("msg.value in Loop", &["CWE-837"]),
+    ("Push-Style Ether Refund", &["SWC-113", "CWE-400"]),
+    ("Signature Replay: Missing Nonce", &["SWC-121", "CWE-294"]),
+    ("Signature Replay: Missing Chain Id", &["SWC-121", "CWE-294"]),
+    ("Signature Replay: Missing Deadline", &["CWE-613"]),
+    ("Spot Price Manipulation", &["CWE-682"]),
+    ("Stale Oracle Price", &["CWE-672"]),
+    ("Unbounded Oracle Price", &["CWE-20"]),
+];
+
+/// SWC and CWE ids for the finding called `name`, e.g. `["SWC-107", "CWE-841"]`
+pub fn for_finding(name: &str) -> Vec<String> {
+    REFERENCES.iter()
+        .find(|(finding, _)| *finding == name)
+        .map(|(_, ids)| ids.iter().map(|id| id.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// The taxonomy and the id within it: ("SWC", "SWC-107") or ("CWE", "841")
+pub fn split(reference: &str) -> Option<(&'static str, &str)> {
+    if reference.starts_with("SWC-") {
+        Some(("SWC", reference))
+    } else {
+        reference.strip_prefix("CWE-").map(|number| ("CWE", number))
+    }
+}
+
+/// Page describing `reference` in its registry
+pub fn url(reference: &str) -> Option<String> {
+    match split(reference)? {
+        ("SWC", id) => Some(format!("https://swcregistry.io/docs/{}", id)),
+        (_, number) => Some(format!("https://cwe.mitre.org/data/definitions/{}.html", number)),
+    }
+}
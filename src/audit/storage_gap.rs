@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use crate::audit::vulnerabilities::{Vulnerability, Severity, Effort};
+use crate::audit::rules::AuditRule;
+use crate::error::Result;
+use crate::upgrade::layout::{extract_layout, StorageEntry, GAP_REFERENCE};
+use crate::upgrade::proxy::is_upgradeable;
+use async_trait::async_trait;
+
+/// Append-only storage discipline for upgradeable contracts: base contracts and
+/// embedded storage structs need reserved gap space so later versions can add
+/// variables without shifting everything declared after them
+pub struct StorageGapRule;
+
+#[async_trait]
+impl AuditRule for StorageGapRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        // Immutable contracts never change layout; gaps would only be noise there
+        if !is_upgradeable(content) {
+            return Ok(Vec::new());
+        }
+        // As in the initializer rule, windows of large files that fail to parse are skipped
+        let Ok(layout) = extract_layout(content) else { return Ok(Vec::new()) };
+        let stylus = solang_parser::parse(content, 0).is_err();
+
+        let top_level: Vec<&StorageEntry> = layout.entries.iter().filter(|e| !e.relative && !e.name.contains('.')).collect();
+        let mut vulnerabilities = Vec::new();
+
+        // Bases need their own gap; the most-derived contract can always append at its end
+        let mut bases: Vec<&str> = Vec::new();
+        for entry in &top_level {
+            if entry.declared_in != layout.contract && !bases.contains(&entry.declared_in.as_str()) {
+                bases.push(&entry.declared_in);
+            }
+        }
+        for base in bases {
+            let declared: Vec<&&StorageEntry> = top_level.iter().filter(|e| e.declared_in == base).collect();
+            if declared.iter().any(|e| e.is_gap()) {
+                continue;
+            }
+            vulnerabilities.push(gap_finding(
+                "Missing Storage Gap",
+                Severity::Medium,
+                format!("base contract {} declares {} storage variable(s) and no reserved gap; adding one in a later version shifts every variable of {}",
+                    base, declared.len(), layout.contract),
+                "Add `uint256[50] private __gap;` after the last variable and shrink it by one per variable added",
+                line_of_decl(content, base),
+            ));
+        }
+
+        // A struct stored inline can't gain members unless nothing follows it in the same contract
+        let structs: HashSet<&str> = layout.entries.iter()
+            .filter(|e| !e.relative && e.name.contains('.'))
+            .filter_map(|e| e.name.split('.').next())
+            .collect();
+        for (i, entry) in top_level.iter().enumerate() {
+            if !structs.contains(entry.name.as_str()) {
+                continue;
+            }
+            let followed = top_level[i + 1..].iter().any(|next| next.declared_in == entry.declared_in && !next.is_gap());
+            let prefix = format!("{}.", entry.name);
+            let reserved = layout.entries.iter().any(|e| e.name.starts_with(&prefix) && e.is_gap());
+            if !followed || reserved {
+                continue;
+            }
+            // Stylus has no inheritance; an embedded (`#[borrow]`) storage struct plays the base contract's role
+            let finding = if stylus {
+                gap_finding(
+                    "Missing Storage Gap",
+                    Severity::Medium,
+                    format!("{} embeds storage struct {} with fields after it and no reserved gap; adding a field to {} later shifts them", layout.contract, entry.ty, entry.ty),
+                    "Give the embedded struct a trailing `__gap: StorageArray<StorageU256, 50>` field and shrink it as fields are added",
+                    line_of_decl(content, &entry.name),
+                )
+            } else {
+                gap_finding(
+                    "Inline Struct Without Reserved Space",
+                    Severity::Low,
+                    format!("{} ({}) is stored inline with variables after it; adding a member to {} later shifts them", entry.name, entry.ty, entry.ty),
+                    "Store the struct in a mapping or behind a namespaced slot, or give it a trailing `__gap` member",
+                    line_of_decl(content, &entry.name),
+                )
+            };
+            vulnerabilities.push(finding);
+        }
+
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Append-Only Storage Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-STORAGE-002"
+    }
+
+    fn description(&self) -> &'static str {
+        "Upgradeable base contracts and storage structs without a storage gap"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Missing Storage Gap", "Inline Struct Without Reserved Space"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// First line declaring `name` as a contract, struct or variable
+fn line_of_decl(source: &str, name: &str) -> Option<usize> {
+    source.lines()
+        .position(|line| {
+            let words: Vec<&str> = line.split(|c: char| !(c.is_alphanumeric() || c == '_')).collect();
+            words.contains(&name) && !line.trim_start().starts_with("//")
+        })
+        .map(|i| i + 1)
+}
+
+fn gap_finding(name: &str, severity: Severity, risk_description: String, recommendation: &str, line: Option<usize>) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        line,
+        reference: Some(GAP_REFERENCE.to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    #[tokio::test]
+    async fn inherited_storage_needs_reserved_space() {
+        assert_eq!(found(&StorageGapRule, include_str!("../../test ex/upgrade/stylus_gap.rs")).await,
+            [("Missing Storage Gap".to_string(), 24)]);
+        assert_eq!(found(&StorageGapRule, include_str!("../../test ex/upgrade/staking_gap_v2.sol")).await,
+            [("Inline Struct Without Reserved Space".to_string(), 23)]);
+    }
+}
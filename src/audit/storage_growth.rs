@@ -0,0 +1,255 @@
+//! Storage collections that public functions only ever grow. Each element
+//! costs storage forever, and any loop over the collection eventually runs
+//! out of gas, which on an L2 turns a holder list into a denial of service
+
+use async_trait::async_trait;
+use solang_parser::pt::{CodeLocation, ContractPart, FunctionAttribute, FunctionTy, Loc, Mutability, SourceUnitPart};
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{is_externally_callable, mask, rust_functions, state_variables};
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+
+/// Calls that add an element; `insert` only counts on sequences, since maps can't be iterated
+const GROWTH: [&str; 3] = ["push", "grow", "insert"];
+const REMOVALS: [&str; 7] = ["pop", "remove", "swap_remove", "truncate", "clear", "erase", "shrink"];
+const RECOMMENDATION: &str = "Track members in a mapping plus a counter instead of an ever-growing array, remove \
+    entries that leave (swap-and-pop), and paginate reads instead of looping over the whole collection";
+
+/// A function and the byte range of its body
+struct Function {
+    name: String,
+    start: usize,
+    end: usize,
+    /// Callable by anyone and allowed to change state
+    public_mutating: bool,
+}
+
+/// Storage collections that grow in public functions and never shrink, and loops over them
+pub struct StorageGrowthRule;
+
+#[async_trait]
+impl AuditRule for StorageGrowthRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        let code = mask(content);
+        let (functions, collections, stylus) = match solang_parser::parse(content, 0) {
+            Ok((unit, _)) => {
+                let (functions, collections) = solidity_functions(&unit, &code);
+                (functions, collections, false)
+            }
+            Err(_) if syn::parse_file(content).is_ok() => (stylus_functions(&code), stylus_sequences(&code), true),
+            Err(_) => return Ok(Vec::new()),
+        };
+        let receiver = |field: &str| match stylus {
+            true => format!("self.{}", field),
+            false => field.to_string(),
+        };
+
+        // Collections grown by a public function, at the first place each one grows
+        let mut grown: Vec<(String, String, usize)> = Vec::new();
+        for function in functions.iter().filter(|f| f.public_mutating) {
+            for (at, field, method) in collection_calls(&code[function.start..function.end], stylus) {
+                // Stylus growth goes through `self.`; in Solidity only state arrays are storage
+                let sequence = match stylus {
+                    true => method != "insert" || collections.contains(&field),
+                    false => collections.contains(&field),
+                };
+                if GROWTH.contains(&method.as_str()) && sequence && !grown.iter().any(|(f, _, _)| *f == field) {
+                    grown.push((field, function.name.clone(), function.start + at));
+                }
+            }
+        }
+        let removed: Vec<String> = collection_calls(&code, stylus).into_iter()
+            .filter(|(_, _, method)| REMOVALS.contains(&method.as_str()))
+            .map(|(_, field, _)| field)
+            .chain(deleted(&code, &collections))
+            .collect();
+        grown.retain(|(field, _, _)| !removed.contains(field));
+
+        let mut vulnerabilities = Vec::new();
+        for (field, function, at) in &grown {
+            vulnerabilities.push(Vulnerability {
+                name: "Unbounded Storage Growth".to_string(),
+                risk_description: format!("{} adds to `{}` on every call and nothing in the contract removes elements, so it grows without limit",
+                    function, receiver(field)),
+                severity: Severity::Medium,
+                recommendation: RECOMMENDATION.to_string(),
+                function: Some(function.clone()),
+                ..Vulnerability::at(content, *at)
+            });
+        }
+        for function in &functions {
+            for (at, field) in loops_over(&code[function.start..function.end], stylus) {
+                if grown.iter().any(|(grown, _, _)| *grown == field) {
+                    vulnerabilities.push(Vulnerability {
+                        name: "Unbounded Iteration".to_string(),
+                        risk_description: format!("{} loops over every element of `{}`, which only grows; once it is large enough the call runs out of gas",
+                            function.name, receiver(&field)),
+                        severity: Severity::Medium,
+                        recommendation: RECOMMENDATION.to_string(),
+                        function: Some(function.name.clone()),
+                        ..Vulnerability::at(content, function.start + at)
+                    });
+                }
+            }
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Unbounded Storage Growth Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-DOS-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Storage collections that public functions grow but nothing shrinks, and loops over them"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Unbounded Storage Growth", "Unbounded Iteration"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Involved
+    }
+}
+
+/// A Medium finding at byte `at` of `content`
+/// Functions, and the state arrays that growth and iteration are looked for on
+fn solidity_functions(unit: &solang_parser::pt::SourceUnit, code: &str) -> (Vec<Function>, Vec<String>) {
+    let mut functions = Vec::new();
+    let mut arrays = Vec::new();
+    for part in &unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        arrays.extend(state_variables(contract).into_iter()
+            .filter(|(_, var)| code.get(var.ty.loc().start()..var.ty.loc().end()).is_some_and(|ty| ty.ends_with(']')))
+            .map(|(name, _)| name));
+        for part in &contract.parts {
+            let ContractPart::FunctionDefinition(func) = part else { continue };
+            let Some(Loc::File(_, start, end)) = func.body.as_ref().map(|body| body.loc()) else { continue };
+            let read_only = func.attributes.iter()
+                .any(|attr| matches!(attr, FunctionAttribute::Mutability(Mutability::View(_) | Mutability::Pure(_))));
+            let entry = match func.ty {
+                FunctionTy::Function => is_externally_callable(func),
+                FunctionTy::Fallback | FunctionTy::Receive => true,
+                _ => false,
+            };
+            let name = func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string());
+            functions.push(Function { name, start, end, public_mutating: entry && !read_only });
+        }
+    }
+    (functions, arrays)
+}
+
+fn stylus_functions(code: &str) -> Vec<Function> {
+    rust_functions(code).into_iter()
+        .map(|(name, start, end)| {
+            let text = &code[start..end];
+            let signature = &text[..text.find('{').unwrap_or(text.len())];
+            let public_mutating = text.starts_with("pub ") && signature.contains("&mut self");
+            Function { name, start, end, public_mutating }
+        })
+        .collect()
+}
+
+/// Fields declared as vectors or sets: `holders: StorageVec<..>` or `address[] holders;` in `sol_storage!`
+fn stylus_sequences(code: &str) -> Vec<String> {
+    code.lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            if let Some((field, ty)) = line.trim_start_matches("pub ").split_once(':') {
+                let field = field.trim();
+                let simple = !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_');
+                return (simple && ["Vec<", "Set<"].iter().any(|kind| ty.contains(kind))).then(|| field.to_string());
+            }
+            let (ty, field) = line.strip_suffix(';')?.rsplit_once(' ')?;
+            ty.ends_with(']').then(|| field.to_string())
+        })
+        .collect()
+}
+
+/// Method calls on storage fields: `self.holders.push(..)` or `self.holders.0.push(..)` in
+/// Stylus, `holders.push(..)` in Solidity, as (offset, field, method)
+fn collection_calls(body: &str, stylus: bool) -> Vec<(usize, String, String)> {
+    let mut calls = Vec::new();
+    for (at, _) in body.match_indices('.') {
+        let method: String = body[at + 1..].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if !body[at + 1 + method.len()..].starts_with('(') || method.is_empty() {
+            continue;
+        }
+        if let Some((start, field)) = storage_field(body, at, stylus) {
+            calls.push((start, field, method));
+        }
+    }
+    calls
+}
+
+/// The field a receiver ending at `end` names, and where the receiver starts
+fn storage_field(body: &str, end: usize, stylus: bool) -> Option<(usize, String)> {
+    let mut start = body[..end].rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).map_or(0, |i| i + 1);
+    let field = match stylus {
+        // `self.holders`, or `self.holders.0` for a newtype wrapper; a range such as `0..self.holders` comes along
+        true => {
+            start += body[start..end].find("self.")?;
+            body[start + "self.".len()..end].trim_end_matches(".0")
+        }
+        false => &body[start..end],
+    };
+    let simple = !field.is_empty() && field.chars().all(|c| c.is_alphanumeric() || c == '_');
+    simple.then(|| (start, field.to_string()))
+}
+
+/// Solidity `delete holders` and `delete holders[i]`
+fn deleted<'a>(code: &'a str, collections: &'a [String]) -> impl Iterator<Item = String> + 'a {
+    collections.iter()
+        .filter(|field| code.match_indices(&format!("delete {}", field)).any(|(at, text)| {
+            !code[at + text.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        }))
+        .cloned()
+}
+
+/// `for` and `while` headers bounded by a collection's length, as (offset, field)
+fn loops_over(body: &str, stylus: bool) -> Vec<(usize, String)> {
+    let mut loops = Vec::new();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let text = line.trim();
+        if text.starts_with("for ") || text.starts_with("for(") || text.starts_with("while ") || text.starts_with("while(") {
+            let bounds: &[&str] = if stylus { &[".len()", ".iter()"] } else { &[".length"] };
+            let mut fields: Vec<String> = bounds.iter()
+                .flat_map(|bound| text.match_indices(bound))
+                .filter_map(|(at, _)| storage_field(text, at, stylus).map(|(_, field)| field))
+                .collect();
+            fields.dedup();
+            loops.extend(fields.into_iter().map(|field| (offset + indent, field)));
+        }
+        offset += line.len();
+    }
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn a_list_that_only_grows_is_flagged_where_it_grows_and_is_walked() {
+        assert_eq!(found(&StorageGrowthRule, include_str!("../../test ex/dos/holder_registry.sol")).await, expected(&[
+            ("Unbounded Storage Growth", 11),
+            ("Unbounded Iteration", 16),
+        ]));
+    }
+
+    #[tokio::test]
+    async fn swap_and_pop_removal_bounds_the_list() {
+        assert!(found(&StorageGrowthRule, include_str!("../../test ex/dos/bounded_registry.sol")).await.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use quote::ToTokens;
+use solang_parser::pt::{CodeLocation, ContractDefinition, ContractPart, ContractTy, FunctionTy, SourceUnitPart};
+use crate::audit::vulnerabilities::{Vulnerability, Severity};
+use crate::audit::rules::AuditRule;
+use crate::audit::initializer::is_initializer_name;
+use crate::audit::source::{assigns, is_externally_callable, line_of, modifier_names, rust_fn_line, slice, state_variables};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Privileged functions that swap implementations, change fees or move
+/// treasury funds with immediate effect instead of behind a delay
+pub struct TimelockRule;
+
+/// What a privileged mutator changes; decides the severity of an instant-effect finding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Privileged {
+    Upgrade,
+    Treasury,
+    Parameter,
+}
+
+impl Privileged {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Privileged::Upgrade => "swaps the implementation",
+            Privileged::Treasury => "moves treasury funds",
+            Privileged::Parameter => "changes fee or protocol parameters",
+        }
+    }
+
+    /// Whether the function controls code or funds rather than tunable parameters
+    pub fn is_value_bearing(self) -> bool {
+        matches!(self, Privileged::Upgrade | Privileged::Treasury)
+    }
+
+    fn severity(self) -> Severity {
+        match self {
+            Privileged::Upgrade | Privileged::Treasury => Severity::High,
+            Privileged::Parameter => Severity::Medium,
+        }
+    }
+}
+
+/// Classifies by function name, falling back to the storage the function writes
+pub(crate) fn classify(name: &str, written: &[&str]) -> Option<Privileged> {
+    let lower = name.to_lowercase();
+    let words = |hints: &[&str], text: &str| hints.iter().any(|hint| text.contains(hint));
+
+    if lower.starts_with("upgrade") || words(&["implementation", "setbeacon", "diamondcut"], &lower) {
+        return Some(Privileged::Upgrade);
+    }
+    if words(&["treasury", "sweep", "rescue", "withdrawfees", "withdraw_fees", "emergencywithdraw", "emergency_withdraw"], &lower) {
+        return Some(Privileged::Treasury);
+    }
+    if lower.starts_with("set") && words(&["fee", "rate", "param", "limit", "oracle", "threshold"], &lower) {
+        return Some(Privileged::Parameter);
+    }
+
+    let written = written.iter().map(|var| var.to_lowercase()).collect::<Vec<_>>();
+    if written.iter().any(|var| var.contains("implementation")) {
+        Some(Privileged::Upgrade)
+    } else if written.iter().any(|var| var.contains("treasury")) {
+        Some(Privileged::Treasury)
+    } else if written.iter().any(|var| var.contains("fee") || var.contains("rate")) {
+        Some(Privileged::Parameter)
+    } else {
+        None
+    }
+}
+
+/// Body text that enforces a delay: an `eta`/ready-time compared against the clock
+pub(crate) fn enforces_delay(body: &str) -> bool {
+    let clock = body.contains("block.timestamp") || body.contains("block :: timestamp") || body.contains("block_timestamp");
+    let delay = ["eta", "delay", "readyat", "ready_at", "unlock", "queued", "timelock"]
+        .iter()
+        .any(|hint| body.to_lowercase().contains(hint));
+    clock && delay
+}
+
+#[async_trait]
+impl AuditRule for TimelockRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        if let Ok((unit, _)) = solang_parser::parse(content, 0) {
+            let mut vulnerabilities = Vec::new();
+            for part in &unit.0 {
+                if let SourceUnitPart::ContractDefinition(contract) = part {
+                    check_contract(contract, content, &mut vulnerabilities);
+                }
+            }
+            return Ok(vulnerabilities);
+        }
+        if let Ok(file) = syn::parse_file(content) {
+            return Ok(check_stylus(&file, content));
+        }
+        Ok(Vec::new())
+    }
+
+    fn name(&self) -> &'static str {
+        "Timelock Enforcement Analyzer"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-TIMELOCK-001"
+    }
+
+    fn description(&self) -> &'static str {
+        "Upgrades, fee changes and treasury moves that take effect without a delay"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &["Timelock Expected From Owner", "Privileged Change Without Timelock"]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::High
+    }
+}
+
+fn check_contract(contract: &ContractDefinition, source: &str, vulnerabilities: &mut Vec<Vulnerability>) {
+    if matches!(contract.ty, ContractTy::Interface(_) | ContractTy::Library(_)) {
+        return;
+    }
+    let text = slice(&contract.loc, source);
+    let state_vars: Vec<String> = state_variables(contract).into_iter().map(|var| var.0).collect();
+
+    // Modifiers that enforce a delay, e.g. `modifier timelocked(bytes32 id) { require(block.timestamp >= eta[id]); _; }`
+    let delay_modifiers: Vec<String> = contract.parts.iter()
+        .filter_map(|part| match part {
+            ContractPart::FunctionDefinition(func) if func.ty == FunctionTy::Modifier => {
+                let body = func.body.as_ref().map(|b| slice(&b.loc(), source)).unwrap_or("");
+                enforces_delay(body).then(|| func.name.as_ref().map(|n| n.name.clone())).flatten()
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut instant = Vec::new();
+    for part in &contract.parts {
+        let ContractPart::FunctionDefinition(func) = part else { continue };
+        if func.ty != FunctionTy::Function || !is_externally_callable(func) {
+            continue;
+        }
+        let (Some(name), Some(body)) = (&func.name, &func.body) else { continue };
+        if is_initializer_name(&name.name) {
+            continue;
+        }
+        let body = slice(&body.loc(), source);
+        let modifiers = modifier_names(func);
+
+        let written: Vec<&str> = state_vars.iter().map(String::as_str).filter(|var| assigns(body, var)).collect();
+        let Some(kind) = classify(&name.name, &written) else { continue };
+
+        // Only privileged functions are in scope; unrestricted ones are an access-control problem
+        let privileged = modifiers.iter().any(|m| m.to_lowercase().starts_with("only")) || body.contains("msg.sender");
+        let delayed = enforces_delay(body)
+            || modifiers.iter().any(|m| delay_modifiers.contains(m) || m.to_lowercase().contains("timelock"));
+        if privileged && !delayed {
+            instant.push((name.name.clone(), kind, line_of(&func.loc, source)));
+        }
+    }
+    if instant.is_empty() {
+        return;
+    }
+
+    // Owned by a TimelockController: the delay lives outside this contract
+    let owner_is_timelock = text.contains("TimelockController")
+        || state_vars.iter().any(|var| var.to_lowercase().contains("timelock"))
+        || text.contains("onlyTimelock");
+    report(instant, owner_is_timelock, vulnerabilities);
+}
+
+fn check_stylus(file: &syn::File, source: &str) -> Vec<Vulnerability> {
+    let owner_is_timelock = source.lines().any(|line| line.to_lowercase().contains("timelock") && line.contains("Storage"));
+
+    let mut instant = Vec::new();
+    for item in &file.items {
+        let syn::Item::Impl(impl_item) = item else { continue };
+        for impl_fn in &impl_item.items {
+            let syn::ImplItem::Fn(method) = impl_fn else { continue };
+            if !matches!(method.vis, syn::Visibility::Public(_)) {
+                continue;
+            }
+            let name = method.sig.ident.to_string();
+            // One-shot setup isn't a privileged change; the initializer rule covers it
+            if is_initializer_name(&name) {
+                continue;
+            }
+            let body = method.block.to_token_stream().to_string();
+            // `self . fee_bps . set (` -> writes `fee_bps`
+            let written: Vec<&str> = body.match_indices(" . set (")
+                .filter_map(|(i, _)| body[..i].rsplit(' ').next())
+                .collect();
+            let Some(kind) = classify(&name, &written) else { continue };
+
+            let privileged = body.contains("msg :: sender") || body.contains("msg_sender") || body.contains("only_owner");
+            if privileged && !enforces_delay(&body) {
+                instant.push((name.clone(), kind, rust_fn_line(source, &name).unwrap_or(0)));
+            }
+        }
+    }
+    if instant.is_empty() {
+        return Vec::new();
+    }
+
+    let mut vulnerabilities = Vec::new();
+    report(instant, owner_is_timelock, &mut vulnerabilities);
+    vulnerabilities
+}
+
+fn report(instant: Vec<(String, Privileged, usize)>, owner_is_timelock: bool, vulnerabilities: &mut Vec<Vulnerability>) {
+    if owner_is_timelock {
+        let names = instant.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+        vulnerabilities.push(Vulnerability {
+            name: "Timelock Expected From Owner".to_string(),
+            severity: Severity::Low,
+            risk_description: format!("{} take effect immediately, but the owner looks like a timelock contract that delays them", names),
+            recommendation: "Verify the deployed owner is the timelock and that no other address holds the privileged role".to_string(),
+            ..Default::default()
+        });
+        return;
+    }
+
+    for (name, kind, line) in instant {
+        vulnerabilities.push(Vulnerability {
+            name: "Privileged Change Without Timelock".to_string(),
+            severity: kind.severity(),
+            risk_description: format!("{} {} with immediate effect; users get no window to exit before a malicious or mistaken change", name, kind.describe()),
+            recommendation: "Queue the change with an eta and enforce `block.timestamp >= eta` before executing, or make a TimelockController the owner".to_string(),
+            line: (line > 0).then_some(line),
+            function: Some(name),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::found;
+
+    #[tokio::test]
+    async fn privileged_changes_need_a_delay() {
+        let instant = found(&TimelockRule, include_str!("../../test ex/timelock/instant_admin.sol")).await;
+        assert_eq!(instant, [
+            ("Privileged Change Without Timelock".to_string(), 22),
+            ("Privileged Change Without Timelock".to_string(), 26),
+            ("Privileged Change Without Timelock".to_string(), 30),
+        ]);
+        assert!(found(&TimelockRule, include_str!("../../test ex/timelock/delayed_admin.sol")).await.is_empty());
+    }
+}
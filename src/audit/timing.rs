@@ -0,0 +1,165 @@
+//! What contracts do with block timestamps and numbers: derive randomness
+//! from them, compare them against deadlines, or assume L1 block timing.
+//! Values only written to events or logs are left alone
+
+use solang_parser::pt::{CodeLocation, ContractPart, Loc, SourceUnitPart};
+use crate::audit::source::{mask, mentions, rust_functions, SourceMatch};
+
+/// Block values in Solidity, and in Stylus through `stylus_sdk::block` or the VM context
+const BLOCK_VALUES: [&str; 6] = [
+    "block.timestamp", "block.number", "block::timestamp()", "block::number()", "block_timestamp()", "block_number()",
+];
+/// Calls that turn a block value into a "random" number; reducing it modulo counts too
+const HASHES: [&str; 4] = ["keccak256(", "keccak(", "sha256(", "blockhash("];
+/// Words that show a deadline allows for timestamp drift
+const TOLERANCES: [&str; 7] = ["grace", "tolerance", "buffer", "slack", "drift", "margin", "leeway"];
+
+pub(crate) enum TimingKind {
+    /// Hashed or reduced modulo, directly or through a local
+    Randomness,
+    /// Compared against a deadline with no tolerance; `strict` for `==`/`!=`
+    Deadline { strict: bool },
+    /// Any other use of the block number, which on Arbitrum follows L1
+    BlockNumber,
+}
+
+pub(crate) struct TimingUse {
+    pub kind: TimingKind,
+    pub function: String,
+    /// The block value as written, e.g. `block.timestamp`
+    pub value: String,
+    pub at: SourceMatch,
+}
+
+/// Every risky use of a block value in the functions of Solidity or Stylus `source`
+pub(crate) fn uses(source: &str) -> Vec<TimingUse> {
+    let code = mask(source);
+    let mut found = Vec::new();
+    for (function, start, end) in function_ranges(source) {
+        let first_line = source[..start].matches('\n').count() + 1;
+        let mut uses: Vec<Option<TimingUse>> = Vec::new();
+        // Locals holding a block value, and the use their assignment was reported as
+        let mut locals: Vec<(String, &str, Option<usize>)> = Vec::new();
+
+        for (i, (masked, line)) in code[start..end].lines().zip(source[start..end].lines()).enumerate() {
+            let direct = BLOCK_VALUES.iter().find(|value| masked.contains(*value)).copied();
+            let via = locals.iter().position(|(name, _, _)| mentions(masked, name));
+            let Some(value) = direct.or_else(|| via.map(|local| locals[local].1)) else { continue };
+            let text = masked.trim_start();
+            if text.starts_with("emit ") || text.contains("log(") || text.contains("log!(") {
+                continue;
+            }
+
+            let local = via.map(|local| locals[local].0.as_str());
+            let kind = if direct.is_some_and(|value| randomizes(masked, value, false)) || local.is_some_and(|name| randomizes(masked, name, true)) {
+                // A block number read into a local is reported here instead
+                if let Some(earlier) = via.filter(|_| direct.is_none()).and_then(|local| locals[local].2) {
+                    uses[earlier] = None;
+                }
+                Some(TimingKind::Randomness)
+            } else if direct.is_none() {
+                None
+            } else if let Some(strict) = comparison(masked, value) {
+                let lower = masked.to_lowercase();
+                (!TOLERANCES.iter().any(|word| lower.contains(word))).then_some(TimingKind::Deadline { strict })
+            } else if value.contains("number") {
+                Some(TimingKind::BlockNumber)
+            } else {
+                None
+            };
+            // A local holding a hash is already reported; its later uses aren't new findings
+            let hashed = matches!(kind, Some(TimingKind::Randomness));
+            if let Some(local) = direct.filter(|_| !hashed).and_then(|_| assigned_local(masked)) {
+                let reported = matches!(kind, Some(TimingKind::BlockNumber)).then_some(uses.len());
+                locals.push((local.to_string(), value, reported));
+            }
+            let Some(kind) = kind else { continue };
+            let column = masked.find(value).or_else(|| masked.find(|c: char| !c.is_whitespace())).unwrap_or(0);
+            uses.push(Some(TimingUse {
+                kind,
+                function: function.clone(),
+                value: value.to_string(),
+                at: SourceMatch { line: first_line + i, column: line[..column].chars().count() + 1, text: line.trim().to_string() },
+            }));
+        }
+        found.extend(uses.into_iter().flatten());
+    }
+    found
+}
+
+/// Name and byte range of every function body, Solidity or Rust
+fn function_ranges(source: &str) -> Vec<(String, usize, usize)> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        return unit.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) => Some(&contract.parts),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part {
+                ContractPart::FunctionDefinition(func) => {
+                    let Loc::File(_, start, end) = func.body.as_ref()?.loc() else { return None };
+                    let name = func.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| func.ty.to_string());
+                    Some((name, start, end))
+                }
+                _ => None,
+            })
+            .collect();
+    }
+    match syn::parse_file(source) {
+        Ok(_) => rust_functions(source),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Whether `needle` (a block value, or a local when `identifier`) is hashed or reduced modulo on `line`
+fn randomizes(line: &str, needle: &str, identifier: bool) -> bool {
+    let contains = |text: &str| if identifier { mentions(text, needle) } else { text.contains(needle) };
+    let hashed = HASHES.iter()
+        .flat_map(|hash| line.match_indices(hash))
+        .any(|(at, hash)| contains(call_arguments(&line[at + hash.len()..])));
+    hashed || clauses(line).any(|clause| contains(clause) && (clause.contains(" % ") || clause.contains("%=")))
+}
+
+/// Text up to the `)` closing a call whose `(` was just consumed, or the rest of the line
+fn call_arguments(text: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return &text[..i],
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    text
+}
+
+/// A line split at `&&`, `||`, commas and semicolons
+fn clauses(line: &str) -> impl Iterator<Item = &str> {
+    line.split("&&").flat_map(|part| part.split("||")).flat_map(|part| part.split([',', ';']))
+}
+
+/// `Some(true)` when the clause holding `value` is an `==`/`!=` comparison, `Some(false)` for an ordering
+fn comparison(line: &str, value: &str) -> Option<bool> {
+    let clause = clauses(line).find(|clause| clause.contains(value))?;
+    if clause.contains("==") || clause.contains("!=") {
+        return Some(true);
+    }
+    ["<=", ">=", " < ", " > "].iter().any(|op| clause.contains(op)).then_some(false)
+}
+
+/// `seed` in `uint256 seed = block.timestamp;` or `let seed = block::timestamp();`
+fn assigned_local(line: &str) -> Option<&str> {
+    let at = line.char_indices().find(|(i, c)| {
+        *c == '=' && !line[..*i].ends_with(['=', '<', '>', '!', '+', '-', '*', '/', '%']) && !line[i + 1..].starts_with(['=', '>'])
+    })?.0;
+    // Without a Rust type annotation: `let seed: u64`
+    let target = line[..at].split(':').next()?.trim();
+    if target.contains(['(', '.', '[']) {
+        return None;
+    }
+    let name = target.rsplit(char::is_whitespace).next()?;
+    (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')).then_some(name)
+}
+
@@ -0,0 +1,222 @@
+//! Interactive triage: steps through the open findings of an audit and records
+//! a decision for each in a findings database, so the next run resumes with
+//! the findings nobody has looked at yet
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+use colored::*;
+use crate::audit::baseline::{Baseline, BaselineEntry};
+use crate::audit::findings_db::{FindingStatus, FindingsDb};
+use crate::audit::fix::{self, FixPlan, TextEdit};
+use crate::audit::vulnerabilities::Vulnerability;
+use crate::audit::AuditResult;
+use crate::error::Result;
+
+/// Where a triage session reads and writes
+pub struct Triage<'a> {
+    /// Contract file the audit ran on; accepted fixes are written here
+    pub file: &'a Path,
+    /// Findings database holding the decisions
+    pub db_path: &'a Path,
+    /// Baseline that suppressed findings are added to, if any
+    pub baseline: Option<&'a Path>,
+    pub rule_names: &'a [String],
+}
+
+/// What one session decided
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub accepted: Vec<Vulnerability>,
+    pub suppressed: Vec<Vulnerability>,
+    pub fixed: Vec<Vulnerability>,
+    pub skipped: Vec<Vulnerability>,
+    /// Findings triaged in an earlier session
+    pub already_triaged: usize,
+    /// Findings not reached because the session was quit
+    pub remaining: usize,
+}
+
+enum Choice {
+    Accept,
+    Suppress,
+    Fix,
+    Skip,
+    Quit,
+}
+
+impl Triage<'_> {
+    /// Walks through the findings of `result` still open in `db`, reading one
+    /// answer per line from `input`. The database is saved after every decision
+    pub fn run(&self, result: &AuditResult, plan: &FixPlan, source: &str, db: &mut FindingsDb,
+        input: &mut impl BufRead, output: &mut impl Write) -> Result<Outcome> {
+        let mut outcome = Outcome::default();
+        let pending: Vec<&Vulnerability> = result.vulnerabilities()
+            .filter(|vuln| status(db, vuln) == Some(FindingStatus::Open))
+            .collect();
+        outcome.already_triaged = result.vulnerabilities().count() - pending.len();
+        if pending.is_empty() {
+            writeln!(output, "\n{}", "🗳️  Every finding has already been triaged".green())?;
+            return Ok(outcome);
+        }
+
+        // Fixes confirmed so far, kept against the original source and reapplied together
+        let mut confirmed: Vec<(Vulnerability, Vec<TextEdit>)> = Vec::new();
+        for (i, vuln) in pending.iter().enumerate() {
+            let edits = plan.fixes.iter()
+                .find(|(fixable, _)| fixable.fingerprint == vuln.fingerprint)
+                .map(|(_, edits)| edits);
+            writeln!(output, "\n{}", format_finding(vuln, i + 1, pending.len()))?;
+
+            loop {
+                let options = if edits.is_some() { "(a)ccept, (s)uppress, (f)ix, s(k)ip, (q)uit" } else { "(a)ccept, (s)uppress, s(k)ip, (q)uit" };
+                let Some(choice) = ask(input, output, &format!("  {} ", options.bold()))? else {
+                    outcome.remaining = pending.len() - i;
+                    return Ok(outcome);
+                };
+                match parse_choice(&choice, edits.is_some()) {
+                    Some(Choice::Accept) => {
+                        self.record(db, vuln, FindingStatus::Acknowledged, None)?;
+                        outcome.accepted.push((*vuln).clone());
+                    }
+                    Some(Choice::Suppress) => {
+                        let Some(reason) = ask(input, output, "  Reason: ")? else {
+                            outcome.remaining = pending.len() - i;
+                            return Ok(outcome);
+                        };
+                        if reason.is_empty() {
+                            writeln!(output, "  A suppression needs a reason")?;
+                            continue;
+                        }
+                        self.record(db, vuln, FindingStatus::FalsePositive, Some(reason))?;
+                        self.add_to_baseline(vuln)?;
+                        outcome.suppressed.push((*vuln).clone());
+                    }
+                    Some(Choice::Fix) => {
+                        let edits = edits.cloned().unwrap_or_default();
+                        writeln!(output, "\n{}", fix::unified_diff(&self.file.display().to_string(), source, &edits))?;
+                        let Some(answer) = ask(input, output, "  Apply this fix? [y/N] ")? else {
+                            outcome.remaining = pending.len() - i;
+                            return Ok(outcome);
+                        };
+                        if !answer.eq_ignore_ascii_case("y") {
+                            continue;
+                        }
+
+                        confirmed.push(((*vuln).clone(), edits));
+                        let fixes = FixPlan { fixes: confirmed.clone(), manual: Vec::new() };
+                        let applied = fixes.apply(source);
+                        if !applied.skipped.is_empty() {
+                            confirmed.pop();
+                            writeln!(output, "  {}", "This fix overlaps one applied earlier; run --triage again to fix it".yellow())?;
+                            continue;
+                        }
+                        fix::check_parses(source, &applied.source)?;
+                        std::fs::write(self.file, &applied.source)?;
+                        writeln!(output, "  ✍️  Fixed in {}", self.file.display())?;
+                        self.record(db, vuln, FindingStatus::Fixed, None)?;
+                        outcome.fixed.push((*vuln).clone());
+                    }
+                    Some(Choice::Skip) => outcome.skipped.push((*vuln).clone()),
+                    Some(Choice::Quit) => {
+                        outcome.remaining = pending.len() - i;
+                        return Ok(outcome);
+                    }
+                    None => {
+                        writeln!(output, "  Answer with one of the letters in brackets")?;
+                        continue;
+                    }
+                }
+                break;
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn record(&self, db: &mut FindingsDb, vuln: &Vulnerability, status: FindingStatus, note: Option<String>) -> Result<()> {
+        if let Some(entry) = db.findings.iter_mut().find(|entry| entry.finding.fingerprint == vuln.fingerprint) {
+            entry.status = status;
+            entry.note = note;
+        }
+        db.save(self.db_path)
+    }
+
+    fn add_to_baseline(&self, vuln: &Vulnerability) -> Result<()> {
+        let Some(path) = self.baseline else { return Ok(()) };
+        let mut baseline = if path.exists() {
+            Baseline::load(path)?
+        } else {
+            Baseline::from_result(&AuditResult::default(), self.rule_names)
+        };
+        if !baseline.findings.iter().any(|entry| entry.fingerprint == vuln.fingerprint) {
+            baseline.findings.push(BaselineEntry {
+                fingerprint: vuln.fingerprint.clone(),
+                name: vuln.name.clone(),
+                severity: vuln.severity,
+            });
+            baseline.save(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn status(db: &FindingsDb, vuln: &Vulnerability) -> Option<FindingStatus> {
+    db.findings.iter()
+        .find(|entry| entry.finding.fingerprint == vuln.fingerprint)
+        .map(|entry| entry.status)
+}
+
+fn parse_choice(answer: &str, fixable: bool) -> Option<Choice> {
+    match answer.to_ascii_lowercase().as_str() {
+        "a" | "accept" => Some(Choice::Accept),
+        "s" | "suppress" => Some(Choice::Suppress),
+        "f" | "fix" if fixable => Some(Choice::Fix),
+        "k" | "skip" => Some(Choice::Skip),
+        "q" | "quit" => Some(Choice::Quit),
+        _ => None,
+    }
+}
+
+/// Prints `prompt` and reads the trimmed answer; `None` at end of input
+fn ask(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> Result<Option<String>> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        writeln!(output)?;
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
+}
+
+fn format_finding(vuln: &Vulnerability, index: usize, total: usize) -> String {
+    let line = vuln.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+    let snippet = vuln.snippet.as_ref()
+        .map(|snippet| snippet.lines().map(|line| format!("    {}\n", line.dimmed())).collect::<String>())
+        .unwrap_or_default();
+    format!("{} [{:?}] {}{}\n{}  Risk: {}\n  Mitigation: {}",
+        format!("[{}/{}]", index, total).cyan().bold(),
+        vuln.severity,
+        vuln.name.bold(),
+        line,
+        snippet,
+        vuln.risk_description,
+        vuln.recommendation.bright_green())
+}
+
+/// Counts of what the session decided, and where the decisions went
+pub fn format_outcome(outcome: &Outcome, db_path: &Path) -> String {
+    let mut output = format!("\n{}\n", "🗳️  Triage summary".cyan().bold());
+    output.push_str(&format!("  ✅ Accepted: {}\n", outcome.accepted.len()));
+    output.push_str(&format!("  🔕 Suppressed: {}\n", outcome.suppressed.len()));
+    output.push_str(&format!("  🔧 Fixed: {}\n", outcome.fixed.len()));
+    output.push_str(&format!("  ⏭️  Skipped: {}\n", outcome.skipped.len()));
+    if outcome.already_triaged > 0 {
+        output.push_str(&format!("  Triaged in earlier sessions: {}\n", outcome.already_triaged));
+    }
+    if outcome.remaining > 0 {
+        output.push_str(&format!("  {}\n", format!("Not reviewed yet: {} (run --triage again to continue)", outcome.remaining).yellow()));
+    }
+    output.push_str(&format!("  Decisions saved to {}\n", db_path.display()));
+    output
+}
+
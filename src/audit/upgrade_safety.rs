@@ -0,0 +1,174 @@
+//! The properties every upgradeable contract needs, checked together: an
+//! initializer that runs once, no state set only by the constructor, room
+//! in the storage layout for later versions, and an upgrade path only the
+//! admin can take. The `upgrade` command prints these next to its proxy report
+
+use async_trait::async_trait;
+use crate::audit::initializer::InitializerRule;
+use crate::audit::rules::AuditRule;
+use crate::audit::source::{mask, rust_functions};
+use crate::audit::storage_gap::StorageGapRule;
+use crate::audit::vulnerabilities::{Effort, Severity, Vulnerability};
+use crate::error::Result;
+use crate::upgrade::layout::{extract_layout, GAP_REFERENCE};
+use crate::upgrade::proxy::{analyze_source, is_upgradeable};
+
+/// Identifiers that mark a contract as taking part in an upgrade scheme
+const UPGRADE_WORDS: [&str; 3] = ["proxy", "upgrade", "implementation"];
+/// Annotations and attributes of upgradeable contracts
+const UPGRADE_ATTRIBUTES: [&str; 3] = ["@custom:oz-upgrades", "@custom:storage-location", "#[upgradeable]"];
+/// Proxy report findings about who may replace the implementation
+const AUTHORIZATION: [&str; 4] = [
+    "UUPS Upgrade Authorization Missing", "Unprotected _authorizeUpgrade", "Unprotected Upgrade Function",
+    "Unprotected Implementation Pointer Update",
+];
+/// Initializer findings that let it run more than once
+const REPEATABLE: [&str; 2] = ["Unprotected Initializer", "Initialized Flag Never Set"];
+const CONSTRUCTOR_STATE: &str = "Constructor State in Upgradeable Contract";
+/// Layouts that don't shift when a version adds variables: ERC-7201 namespaces and diamond storage
+const NAMESPACED: [&str; 4] = ["@custom:storage-location", "erc7201", "StorageSlot", ".slot :="];
+
+/// Initializer, constructor, storage layout and upgrade authorization of upgradeable contracts
+pub struct UpgradeSafetyRule;
+
+#[async_trait]
+impl AuditRule for UpgradeSafetyRule {
+    async fn check(&self, content: &str) -> Result<Vec<Vulnerability>> {
+        if !upgrade_related(content) {
+            return Ok(Vec::new());
+        }
+        let mut vulnerabilities: Vec<Vulnerability> = analyze_source(content, "").map(|report| report.findings).unwrap_or_default()
+            .into_iter()
+            .filter(|finding| AUTHORIZATION.contains(&finding.name.as_str()))
+            .collect();
+        vulnerabilities.extend(InitializerRule.check(content).await?.into_iter()
+            .filter(|finding| REPEATABLE.contains(&finding.name.as_str()) || finding.name == CONSTRUCTOR_STATE));
+        if solang_parser::parse(content, 0).is_err() {
+            vulnerabilities.extend(stylus_constructor_state(content));
+        }
+        // Missing gaps in base contracts are reported per base by the append-only storage rule
+        if StorageGapRule.check(content).await?.is_empty() {
+            vulnerabilities.extend(unreserved_layout(content));
+        }
+        Ok(vulnerabilities)
+    }
+
+    fn name(&self) -> &'static str {
+        "Upgrade Safety Checker"
+    }
+
+    fn id(&self) -> &'static str {
+        "SA-UPGRADE-002"
+    }
+
+    fn description(&self) -> &'static str {
+        "Repeatable initializers, constructor-only state, layouts without reserved space and unauthorized upgrades"
+    }
+
+    fn finding_names(&self) -> &'static [&'static str] {
+        &[
+            "UUPS Upgrade Authorization Missing", "Unprotected _authorizeUpgrade", "Unprotected Upgrade Function",
+            "Unprotected Implementation Pointer Update", "Unprotected Initializer", "Initialized Flag Never Set",
+            "Constructor State in Upgradeable Contract", "No Reserved Storage Layout",
+        ]
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Critical
+    }
+
+    fn default_effort(&self) -> Effort {
+        Effort::Quick
+    }
+}
+
+/// A recognized proxy pattern or initializer, upgrade annotations, or identifiers naming proxies, upgrades or implementations
+fn upgrade_related(content: &str) -> bool {
+    if is_upgradeable(content) || UPGRADE_ATTRIBUTES.iter().any(|attribute| content.contains(attribute)) {
+        return true;
+    }
+    mask(content).split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map(str::to_lowercase)
+        .any(|word| UPGRADE_WORDS.iter().any(|hint| word.contains(hint)))
+}
+
+/// Stylus `#[constructor]` methods writing storage, which only the implementation ever sees
+fn stylus_constructor_state(content: &str) -> Vec<Vulnerability> {
+    let code = mask(content);
+    rust_functions(&code).into_iter()
+        .filter(|(_, start, _)| {
+            let before = code[..*start].trim_end();
+            before.ends_with("#[constructor]")
+        })
+        .filter_map(|(name, start, end)| {
+            let body = &code[start..end];
+            let written: Vec<&str> = body.match_indices("self.")
+                .filter_map(|(at, _)| {
+                    let field = body[at + "self.".len()..].split(|c: char| !(c.is_alphanumeric() || c == '_')).next()?;
+                    let rest = &body[at + "self.".len() + field.len()..];
+                    [".set(", ".push(", ".insert(", ".setter("].iter().any(|write| rest.starts_with(write)).then_some(field)
+                })
+                .fold(Vec::new(), |mut fields, field| {
+                    if !fields.contains(&field) {
+                        fields.push(field);
+                    }
+                    fields
+                });
+            (!written.is_empty()).then(|| Vulnerability {
+                name: CONSTRUCTOR_STATE.to_string(),
+                severity: Severity::High,
+                risk_description: format!("the constructor writes {}; behind a proxy this lands in the implementation's storage and the proxy never sees it",
+                    written.join(", ")),
+                recommendation: "Move the writes into an init function guarded against re-initialization".to_string(),
+                function: Some(name),
+                line: Some(content[..start].matches('\n').count() + 1),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// A layout with storage but neither a gap, a namespaced slot nor a versioned storage struct
+fn unreserved_layout(content: &str) -> Option<Vulnerability> {
+    let layout = extract_layout(content).ok()?;
+    let storage: Vec<_> = layout.entries.iter().filter(|entry| !entry.relative && !entry.name.contains('.')).collect();
+    if storage.is_empty() || layout.entries.iter().any(|entry| entry.is_gap()) {
+        return None;
+    }
+    if NAMESPACED.iter().any(|marker| content.contains(marker)) || versioned(content) {
+        return None;
+    }
+    let first = &storage[0].name;
+    Some(Vulnerability {
+        name: "No Reserved Storage Layout".to_string(),
+        severity: Severity::Low,
+        risk_description: format!("{} declares {} storage variable(s) with no gap, namespaced slot or versioned layout; a later version can only append, \
+            and any base contract it adds shifts every variable", layout.contract, storage.len()),
+        recommendation: "Reserve space with a trailing `__gap` array, or keep state in an ERC-7201 namespaced struct or a versioned storage struct (StorageV1, StorageV2)".to_string(),
+        line: content.lines().position(|line| {
+            line.split(|c: char| !(c.is_alphanumeric() || c == '_')).any(|word| word == first) && !line.trim_start().starts_with("//")
+        }).map(|i| i + 1),
+        reference: Some(GAP_REFERENCE.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Storage kept in structs or contracts versioned by name, e.g. `StorageV1` or `LayoutV2`
+fn versioned(content: &str) -> bool {
+    mask(content).split(|c: char| !(c.is_alphanumeric() || c == '_')).any(|word| {
+        let Some((stem, version)) = word.rsplit_once('V') else { return false };
+        !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) && (stem.ends_with("Storage") || stem.ends_with("Layout"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::rules::{expected, found};
+
+    #[tokio::test]
+    async fn state_set_in_a_stylus_constructor_is_lost_behind_a_proxy() {
+        assert_eq!(found(&UpgradeSafetyRule, include_str!("../../test ex/upgrade/stylus_constructor.rs")).await,
+            expected(&[("Constructor State in Upgradeable Contract", 25)]));
+    }
+}
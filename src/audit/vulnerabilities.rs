@@ -1,15 +1,124 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+use crate::audit::source::{match_at, SourceMatch};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 pub enum Severity {
     Critical,
     High,
     Medium,
+    #[default]
     Low,
+    /// Worth knowing during review; not a vulnerability on its own
+    Info,
+}
+
+impl Severity {
+    fn rank(self) -> u8 {
+        match self {
+            Severity::Critical => 4,
+            Severity::High => 3,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+            Severity::Info => 0,
+        }
+    }
+
+    /// True if this severity is `threshold` or more severe
+    pub fn at_least(self, threshold: Severity) -> bool {
+        self.rank() >= threshold.rank()
+    }
+}
+
+/// Rough remediation effort, used with severity to suggest a fix order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Effort {
+    Quick,
+    Moderate,
+    Involved,
+}
+
+impl Effort {
+    /// One step more effort, saturating at `Involved`
+    pub fn bump(self) -> Effort {
+        match self {
+            Effort::Quick => Effort::Moderate,
+            Effort::Moderate | Effort::Involved => Effort::Involved,
+        }
+    }
+}
+
+/// Code showing the recommendation applied: the matched code when the rule
+/// could adapt it, a generic illustration otherwise
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixExample {
+    pub before: String,
+    pub after: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Vulnerability {
     pub name: String,
     pub severity: Severity,
     pub risk_description: String,
     pub recommendation: String,
-}
\ No newline at end of file
+    /// Rule that produced the finding; filled in by the audit analyzer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Stable id of that rule, e.g. `SA-REENTRANCY-001`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// Where a rule that isn't built in came from, e.g. `plugin todo-plugin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// Ids of every rule that reported this finding, when several did; see `audit::dedupe`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub detected_by: Vec<String>,
+    /// Function the finding is in, when the rule can tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// 1-based line of the finding, when the rule can tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based column of the match on `line`, when the rule can tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Code the rule matched, when the rule can tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Link to background reading on why the finding matters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// SWC registry and CWE ids, e.g. `SWC-107`, `CWE-841`; see `audit::standards`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// Remediation effort; defaults to the rule's `default_effort`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<Effort>,
+    /// How likely the finding is real, 0.0 to 1.0; defaults to the rule's `default_confidence`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Before/after code for the recommendation, when the fix has a canonical shape
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<FixExample>,
+    /// Identity that survives line churn, see `audit::fingerprint`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub fingerprint: String,
+}
+
+impl Vulnerability {
+    /// Points the finding at the line a rule matched; whole-file findings
+    /// with nothing to point at keep no location
+    pub(crate) fn located(mut self, found: Option<SourceMatch>) -> Self {
+        if let Some(found) = found {
+            self.line = Some(found.line);
+            self.column = Some(found.column);
+            self.snippet = Some(found.text);
+        }
+        self
+    }
+
+    /// A finding located at byte `at` of `content`
+    pub(crate) fn at(content: &str, at: usize) -> Self {
+        Self::default().located(Some(match_at(content, at)))
+    }
+}
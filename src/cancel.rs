@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Exit code used when a run is interrupted with Ctrl-C (128 + SIGINT)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How long in-flight work may keep running after cancellation is requested
+pub const GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// Installs the Ctrl-C handler: the first interrupt requests cooperative
+/// cancellation, a second one force-quits immediately.
+pub fn install_ctrl_c_handler(token: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        warn!("⚠️  Interrupt received - finishing in-flight work and writing partial results; press Ctrl-C again to force quit");
+        token.cancel();
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("❌ Forced quit");
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    });
+}
+
+/// Runs `fut` until it completes or cancellation is requested. After
+/// cancellation the future gets `GRACE_PERIOD` to finish before being dropped,
+/// in which case `None` is returned.
+pub async fn run_or_cancel<F: Future>(token: &CancellationToken, fut: F) -> Option<F::Output> {
+    if token.is_cancelled() {
+        return None;
+    }
+
+    tokio::pin!(fut);
+    tokio::select! {
+        output = &mut fut => Some(output),
+        _ = token.cancelled() => tokio::time::timeout(GRACE_PERIOD, fut).await.ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::audit::rules::AuditRule;
+    use crate::audit::vulnerabilities::{Severity, Vulnerability};
+    use crate::audit::AuditAnalyzer;
+    use crate::error::{AnalyzerError, Result};
+
+    /// Cancels `token` shortly after the work under test has started
+    fn cancel_soon(token: &CancellationToken) {
+        let token = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            token.cancel();
+        });
+    }
+
+    #[tokio::test]
+    async fn work_finishing_within_the_grace_period_is_kept() {
+        let token = CancellationToken::new();
+        cancel_soon(&token);
+        let output = run_or_cancel(&token, async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "done"
+        }).await;
+        assert_eq!(output, Some("done"));
+
+        // Once cancelled, new work doesn't start at all
+        assert_eq!(run_or_cancel(&token, async { "late" }).await, None);
+    }
+
+    #[tokio::test]
+    async fn work_outlasting_the_grace_period_is_dropped() {
+        let token = CancellationToken::new();
+        cancel_soon(&token);
+        let started = Instant::now();
+        assert_eq!(run_or_cancel(&token, std::future::pending::<()>()).await, None);
+        assert!(started.elapsed() >= GRACE_PERIOD);
+    }
+
+    /// A rule that reports one finding at once, or never finishes
+    struct Timed(&'static str);
+
+    #[async_trait::async_trait]
+    impl AuditRule for Timed {
+        async fn check(&self, _content: &str) -> Result<Vec<Vulnerability>> {
+            if self.0 == "Endless Rule" {
+                std::future::pending::<()>().await;
+            }
+            Ok(vec![Vulnerability { name: "Quick Issue".to_string(), severity: Severity::Medium, ..Default::default() }])
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "Test rule"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Medium
+        }
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_audit_keeps_the_findings_of_finished_rules() {
+        let token = CancellationToken::new();
+        let analyzer = AuditAnalyzer::new().with_cancellation(token.clone());
+        analyzer.add_rule(Box::new(Timed("Quick Rule")));
+        analyzer.add_rule(Box::new(Timed("Endless Rule")));
+        cancel_soon(&token);
+
+        let result = analyzer.audit("contract C {}").await.unwrap();
+        assert!(result.interrupted);
+        assert_eq!(result.rules_run, ["Quick Rule"]);
+        let names: Vec<&str> = result.vulnerabilities().map(|vuln| vuln.name.as_str()).collect();
+        assert_eq!(names, ["Quick Issue"]);
+        // The binary turns an interrupted run into this error, exiting 128 + SIGINT
+        assert_eq!(AnalyzerError::Interrupted.exit_code(), 130);
+    }
+}
@@ -1,15 +1,138 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use stylus_analyzer::ai::backend::Provider;
+use stylus_analyzer::ai::cache::DEFAULT_TTL_HOURS;
+use stylus_analyzer::audit::erc::Standard;
+use stylus_analyzer::audit::vulnerabilities::Severity;
+use stylus_analyzer::input::DEFAULT_MAX_INPUT_BYTES;
+use stylus_analyzer::report::DEFAULT_JOBS;
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(name = "stylus-analyzer")]
 #[command(about = "AI-powered Arbitrum Stylus smart contract analyzer", long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Refuse contract files larger than this many bytes
+    #[arg(long, global = true, value_name = "BYTES", default_value_t = DEFAULT_MAX_INPUT_BYTES)]
+    pub max_input_size: u64,
+
+    /// Analyze files over --max-input-size anyway (static scanners stream them)
+    #[arg(long, global = true)]
+    pub force_large: bool,
+
+    /// More diagnostics on stderr: -v for rule timings, -vv for AI request sizes
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Print nothing but the report and errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Color the output: `auto` when printing to a terminal and NO_COLOR is unset
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorMode::Auto)]
+    pub color: ColorMode,
+
+    /// Replace emoji and box-drawing characters with ASCII (default when the locale isn't UTF-8)
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Run only the static analyses; no source is sent to the AI provider
+    #[arg(long, global = true)]
+    pub no_ai: bool,
+
+    /// Send source to the AI without masking private keys, API keys and seed phrases
+    #[arg(long, global = true)]
+    pub no_redact: bool,
+
+    /// Where AI requests go; `ollama` keeps the source on this machine
+    #[arg(long, global = true, value_enum, value_name = "PROVIDER")]
+    pub provider: Option<Provider>,
+
+    /// AI model for every request; overrides `model` in the config file
+    #[arg(long, global = true, value_name = "NAME")]
+    pub model: Option<String>,
+
+    /// Ask the AI provider again instead of reusing answers for unchanged contracts
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Reuse cached AI answers for this many hours
+    #[arg(long, global = true, value_name = "HOURS", default_value_t = DEFAULT_TTL_HOURS)]
+    pub cache_ttl: u64,
+
+    /// Write the report to this file instead of stdout; progress stays on stderr
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Replace the --output file, or the config file `init` writes, if it exists
+    #[arg(long, global = true)]
+    pub force: bool,
+
+    /// Keep running and analyze again whenever the contract, or a source file under the directory, is saved
+    #[arg(long, global = true)]
+    pub watch: bool,
+
+    /// Report matching findings at this severity, e.g. "SA-TESTING-001=info" or "reentrancy=critical";
+    /// keys as in [rules.severity], comma-separated or repeated, over the config file
+    #[arg(long, global = true, value_name = "RULE=SEVERITY", value_delimiter = ',')]
+    pub severity_override: Vec<String>,
+
+    /// Print colored text for people, versioned JSON for scripts, or Markdown or an HTML page to share (report and audit)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+impl Cli {
+    /// Whether stdout carries JSON that scripts parse
+    pub fn structured_output(&self) -> bool {
+        self.format == OutputFormat::Json || self.command.structured_output()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Forces colors on or off for the report; `auto` leaves the terminal and
+    /// NO_COLOR checks to `colored`, and keeps escape codes out of `--output` files
+    pub fn apply(self, to_file: bool) {
+        match self {
+            ColorMode::Auto if to_file => colored::control::set_override(false),
+            ColorMode::Auto => {}
+            ColorMode::Always => colored::control::set_override(true),
+            ColorMode::Never => colored::control::set_override(false),
+        }
+    }
+
+    /// Whether diagnostics on stderr get ANSI colors
+    pub fn stderr(self) -> bool {
+        match self {
+            ColorMode::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Markdown,
+    Html,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Clone)]
+// Parsed once per run, so the size of `Audit`'s many flags costs nothing
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Analyze gas usage in the contract
     Analyze {
@@ -20,6 +143,70 @@ pub enum Commands {
     Audit {
         /// Path to the Stylus contract file
         file: PathBuf,
+        /// Exit with an error if any audit rule fails to run
+        #[arg(long)]
+        strict_rules: bool,
+        /// Only report findings that are not in this baseline snapshot
+        #[arg(long, value_name = "PATH")]
+        baseline: Option<PathBuf>,
+        /// Rewrite the --baseline snapshot with the current findings
+        #[arg(long, alias = "write-baseline", requires = "baseline")]
+        update_baseline: bool,
+        /// Compare against the file as of this git ref instead of a baseline file
+        #[arg(long, value_name = "REF", conflicts_with = "baseline")]
+        diff: Option<String>,
+        /// Exit with code 1 if findings at or above this severity are new relative to --baseline or --diff
+        #[arg(long, value_name = "SEVERITY", ignore_case = true)]
+        fail_on_new: Option<Severity>,
+        /// Exit with code 1 if any finding is at or above this severity
+        #[arg(long, value_name = "SEVERITY", ignore_case = true)]
+        fail_on: Option<Severity>,
+        /// Run only these rules, by id (see `rules`); comma-separated or repeated
+        #[arg(long, visible_alias = "only", value_name = "ID", value_delimiter = ',')]
+        only_rules: Vec<String>,
+        /// Don't run these rules, by id; comma-separated or repeated
+        #[arg(long, visible_alias = "skip", value_name = "ID", value_delimiter = ',')]
+        skip_rules: Vec<String>,
+        /// Also run the regex rules in this YAML or TOML file (see README)
+        #[arg(long, value_name = "PATH")]
+        custom_rules: Option<PathBuf>,
+        /// Hide findings whose confidence is below this, from 0 to 1, e.g. 0.8
+        #[arg(long, value_name = "CONFIDENCE")]
+        min_confidence: Option<f64>,
+        /// End the report with each rule's wall time and findings, slowest first, and the total and parse times
+        #[arg(long)]
+        timings: bool,
+        /// Report every rule's finding separately, even when several rules flag the same code
+        #[arg(long)]
+        no_dedupe: bool,
+        /// Override a rule's remediation effort by name or slug, e.g. "reentrancy-pattern-checker=quick"; repeatable
+        #[arg(long, value_name = "RULE=EFFORT")]
+        effort: Vec<String>,
+        /// Merge triage status from this findings database
+        #[arg(long, value_name = "PATH")]
+        import_db: Option<PathBuf>,
+        /// Write the merged findings database, with status and first/last-seen times
+        #[arg(long, value_name = "PATH")]
+        export_db: Option<PathBuf>,
+        /// Check conformance to this token standard instead of detecting it from function names
+        #[arg(long, value_enum)]
+        standard: Option<Standard>,
+        /// Also run the rules of this plugin library (see README); repeatable
+        #[arg(long, value_name = "PATH")]
+        plugin: Vec<PathBuf>,
+        /// Print a unified diff fixing the findings that have a mechanical fix
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, write the fixes to the file instead of printing the diff
+        #[arg(long, requires = "fix")]
+        apply: bool,
+        /// Also write the findings as SARIF 2.1.0 for GitHub code scanning
+        #[arg(long, value_name = "PATH")]
+        sarif: Option<PathBuf>,
+        /// Step through the findings one by one, saving each decision to this findings database
+        #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = ".analyzer-triage.json",
+            conflicts_with_all = ["fix", "import_db", "export_db"])]
+        triage: Option<PathBuf>,
     },
     /// Analyze contract size
     Size {
@@ -30,16 +217,41 @@ pub enum Commands {
     Secure {
         /// Path to the Stylus contract file
         file: PathBuf,
+        /// Exit with code 1 if any finding is at or above this severity
+        #[arg(long, value_name = "SEVERITY", ignore_case = true)]
+        fail_on: Option<Severity>,
     },
     /// Generate comprehensive report
     Report {
         /// Path to the Stylus contract file
         file: PathBuf,
+        /// Analyzers to run at once; each one is a separate AI request
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
     },
     /// Analyze upgrade patterns
     Upgrade {
-        /// Path to the Stylus contract file
+        /// Contract files; pass every Diamond facet to check for selector clashes
+        #[arg(required = true, num_args = 1..)]
+        files: Vec<PathBuf>,
+        /// Compare the storage layouts of two versions: upgrade --layout-compare OLD NEW
+        #[arg(long)]
+        layout_compare: bool,
+    },
+    /// Check that a new version keeps the old storage layout; exits 1 on a breaking change
+    UpgradeCheck {
+        /// Path to the deployed version of the contract
+        old: PathBuf,
+        /// Path to the version that will replace it
+        new: PathBuf,
+    },
+    /// Print the ordered storage layout (slot, offset, type, name)
+    Layout {
+        /// Path to the Solidity or Stylus contract file
         file: PathBuf,
+        /// Print the layout as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Analyze function complexity
     Complexity {
@@ -55,5 +267,226 @@ pub enum Commands {
     Quality {
         /// Path to the Stylus contract file
         file: PathBuf,
+        /// Exit with code 1 when Solidity NatSpec coverage is below this percentage
+        #[arg(long, value_name = "PERCENT")]
+        min_doc_coverage: Option<f64>,
     },
-}
\ No newline at end of file
+    /// Ask the AI model to rewrite the function one audit finding is in
+    Fix {
+        /// Path to the contract file
+        file: PathBuf,
+        /// Finding to fix: its number in the audit report or a fingerprint prefix
+        #[arg(long, value_name = "INDEX|FINGERPRINT")]
+        finding: String,
+        /// Write the validated fix into the file instead of printing the diff
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Plan a Solidity-to-Stylus migration
+    Migrate {
+        /// Path to the Solidity contract file
+        file: PathBuf,
+        /// Write a scaffolded .rs file (defaults to the input path with a .rs extension)
+        #[arg(long, value_name = "PATH", num_args = 0..=1)]
+        write: Option<Option<PathBuf>>,
+    },
+    /// Compare two versions of a contract (static analyses only)
+    #[command(visible_alias = "diff")]
+    Compare {
+        /// Path to the previous version of the contract
+        old: PathBuf,
+        /// Path to the new version of the contract
+        new: PathBuf,
+        /// Only diff the external ABI surface (functions, events, errors)
+        #[arg(long)]
+        abi: bool,
+        /// Print the diff as JSON for CI
+        #[arg(long)]
+        json: bool,
+        /// Exit with code 1 if the new version introduces findings at or above this severity
+        #[arg(long, value_name = "SEVERITY", ignore_case = true, conflicts_with = "abi")]
+        fail_on_new: Option<Severity>,
+    },
+    /// Print shell completions; rule names are completed from this build's registry
+    Completions {
+        shell: Shell,
+        /// Write the script to the shell's per-user completion directory instead
+        #[arg(long)]
+        install: bool,
+    },
+    /// Check the API key, provider, external tools and parsers
+    Doctor {
+        /// Don't contact the AI provider; a missing key is then only a warning
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Count contracts, functions, storage and lines without running any analysis
+    Stats {
+        /// Contract file, or a directory to walk for .sol and .rs files
+        path: PathBuf,
+    },
+    /// List the audit rules with their ids, default severities and what they look for
+    Rules {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write a commented .stylus-analyzer.toml to the current directory
+    Init,
+    /// Manage the on-disk cache of AI answers
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Print the man page
+    Man {
+        /// Write the main page and one per command into this directory
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+        /// Write the pages to the per-user man directory (~/.local/share/man/man1)
+        #[arg(long, conflicts_with = "out_dir")]
+        install: bool,
+    },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub enum CacheAction {
+    /// Delete every cached AI answer
+    Clear,
+}
+
+impl Commands {
+    /// Whether the command's own `--json` flag is set
+    pub fn structured_output(&self) -> bool {
+        matches!(self, Commands::Layout { json: true, .. } | Commands::Compare { json: true, .. } | Commands::Rules { json: true })
+    }
+
+    /// The contract path of commands that also take a directory or glob and
+    /// run once per file
+    pub fn target_mut(&mut self) -> Option<&mut PathBuf> {
+        match self {
+            Commands::Analyze { file }
+            | Commands::Audit { file, .. }
+            | Commands::Size { file }
+            | Commands::Secure { file, .. }
+            | Commands::Report { file, .. }
+            | Commands::Complexity { file }
+            | Commands::Interactions { file }
+            | Commands::Quality { file, .. } => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Severity at or above which any finding fails the run
+    pub fn fail_on(&self) -> Option<Severity> {
+        match self {
+            Commands::Audit { fail_on, .. } | Commands::Secure { fail_on, .. } => *fail_on,
+            _ => None,
+        }
+    }
+
+    /// Options given that read or write one file for the whole run, which
+    /// don't make sense once per contract
+    pub fn single_file_options(&self) -> Vec<&'static str> {
+        let Commands::Audit { baseline, import_db, export_db, fix, triage, sarif, .. } = self else { return Vec::new() };
+        [
+            (baseline.is_some(), "--baseline"),
+            (import_db.is_some(), "--import-db"),
+            (export_db.is_some(), "--export-db"),
+            (*fix, "--fix"),
+            (triage.is_some(), "--triage"),
+            (sarif.is_some(), "--sarif"),
+        ]
+        .into_iter()
+        .filter_map(|(given, option)| given.then_some(option))
+        .collect()
+    }
+
+    /// Whether the command can print `--format json`; the others only print text
+    pub fn supports_json(&self) -> bool {
+        match self {
+            Commands::Audit { fix, triage, .. } => !fix && triage.is_none(),
+            Commands::Fix { .. } | Commands::Migrate { .. } | Commands::Upgrade { .. }
+            | Commands::Completions { .. } | Commands::Man { .. } | Commands::Doctor { .. }
+            | Commands::Cache { .. } | Commands::Init => false,
+            _ => true,
+        }
+    }
+
+    /// Whether the command can print `--format markdown` and `--format html`
+    pub fn supports_documents(&self) -> bool {
+        match self {
+            Commands::Audit { fix, triage, .. } => !fix && triage.is_none(),
+            Commands::Report { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether `--watch` can re-run the command; `--fix` and `--triage` change
+    /// files or wait for input, so they don't repeat on every save
+    pub fn watchable(&self) -> bool {
+        match self {
+            Commands::Audit { fix, triage, .. } => !fix && triage.is_none(),
+            Commands::Analyze { .. } | Commands::Size { .. } | Commands::Secure { .. } | Commands::Report { .. }
+            | Commands::Complexity { .. } | Commands::Interactions { .. } | Commands::Quality { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the command calls the AI provider unless `--no-ai` is given
+    pub fn uses_ai(&self) -> bool {
+        match self {
+            Commands::Analyze { .. } | Commands::Audit { .. } | Commands::Size { .. } | Commands::Secure { .. }
+            | Commands::Report { .. } | Commands::Complexity { .. } | Commands::Interactions { .. }
+            | Commands::Quality { .. } | Commands::Fix { .. } => true,
+            Commands::Upgrade { layout_compare, .. } => !layout_compare,
+            _ => false,
+        }
+    }
+
+    /// Where the search for `.stylus-analyzer.toml` starts: the analyzed
+    /// file, directory or glob, or the current directory
+    pub fn config_start(&self) -> Option<&Path> {
+        match self {
+            Commands::Stats { path } => Some(path),
+            _ => self.input_files().first().copied(),
+        }
+    }
+
+    /// The contract files the command operates on
+    pub fn input_files(&self) -> Vec<&Path> {
+        match self {
+            Commands::Analyze { file }
+            | Commands::Audit { file, .. }
+            | Commands::Size { file }
+            | Commands::Secure { file, .. }
+            | Commands::Report { file, .. }
+            | Commands::Complexity { file }
+            | Commands::Interactions { file }
+            | Commands::Quality { file, .. }
+            | Commands::Fix { file, .. }
+            | Commands::Migrate { file, .. }
+            | Commands::Layout { file, .. } => vec![file],
+            Commands::Compare { old, new, .. } | Commands::UpgradeCheck { old, new } => vec![old, new],
+            Commands::Upgrade { files, .. } => files.iter().map(PathBuf::as_path).collect(),
+            // Directories are walked by the command itself
+            Commands::Stats { path } if path.is_file() => vec![path],
+            Commands::Stats { .. } | Commands::Completions { .. } | Commands::Man { .. } | Commands::Doctor { .. }
+            | Commands::Cache { .. } | Commands::Rules { .. } | Commands::Init => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_baseline_is_an_alias_of_update_baseline() {
+        for flag in ["--update-baseline", "--write-baseline"] {
+            let cli = Cli::try_parse_from(["stylus-analyzer", "audit", "t.rs", "--baseline", "b.json", flag]).unwrap();
+            assert!(matches!(cli.command, Commands::Audit { update_baseline: true, .. }), "{}", flag);
+        }
+        assert!(Cli::try_parse_from(["stylus-analyzer", "audit", "t.rs", "--write-baseline"]).is_err());
+    }
+}
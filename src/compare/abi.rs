@@ -0,0 +1,555 @@
+use std::collections::BTreeMap;
+use colored::*;
+use quote::ToTokens;
+use serde::Serialize;
+use solang_parser::pt::{
+    ContractPart, ContractTy, Expression, FunctionAttribute, FunctionTy, SourceUnitPart,
+    Type, VariableAttribute, Visibility,
+};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub selector: String,
+}
+
+impl AbiFunction {
+    fn new(name: String, inputs: Vec<String>, outputs: Vec<String>) -> Self {
+        let selector = selector(&format!("{}({})", name, inputs.join(",")));
+        Self { name, inputs, outputs, selector }
+    }
+
+    /// Canonical signature the selector is derived from, e.g. `transfer(address,uint256)`
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.inputs.join(","))
+    }
+
+    fn display(&self) -> String {
+        if self.outputs.is_empty() {
+            format!("{} [{}]", self.signature(), self.selector)
+        } else {
+            format!("{} returns ({}) [{}]", self.signature(), self.outputs.join(","), self.selector)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AbiEvent {
+    pub name: String,
+    /// Parameter types, with ` indexed` appended for topics
+    pub params: Vec<String>,
+}
+
+impl AbiEvent {
+    fn display(&self) -> String {
+        format!("event {}({})", self.name, self.params.join(","))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AbiError {
+    pub name: String,
+    pub inputs: Vec<String>,
+}
+
+impl AbiError {
+    fn display(&self) -> String {
+        format!("error {}({})", self.name, self.inputs.join(","))
+    }
+}
+
+/// Externally callable interface of a contract
+#[derive(Debug, Default, Serialize)]
+pub struct AbiSurface {
+    pub functions: Vec<AbiFunction>,
+    pub events: Vec<AbiEvent>,
+    pub errors: Vec<AbiError>,
+}
+
+impl AbiSurface {
+    /// Extracts the surface from Solidity (public/external members) or Stylus
+    /// (methods of `#[public]`/`#[external]` impls, events and errors in `sol!`)
+    pub fn from_source(source: &str) -> Result<Self> {
+        if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+            return Ok(Self::from_solidity(unit.0, true));
+        }
+        if let Ok(file) = syn::parse_file(source) {
+            return Ok(Self::from_stylus(&file));
+        }
+        Err(AnalyzerError::Parse("input is neither valid Solidity nor Rust".to_string()))
+    }
+
+    fn from_solidity(parts: Vec<SourceUnitPart>, require_visibility: bool) -> Self {
+        let mut surface = Self::default();
+
+        for part in parts {
+            let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+            // Library functions are called internally, not through the ABI
+            if matches!(contract.ty, ContractTy::Library(_)) {
+                continue;
+            }
+
+            for part in contract.parts {
+                match part {
+                    ContractPart::FunctionDefinition(func) if func.ty == FunctionTy::Function => {
+                        let external = func.attributes.iter().any(|a| matches!(a,
+                            FunctionAttribute::Visibility(Visibility::Public(_) | Visibility::External(_))
+                        ));
+                        if require_visibility && !external {
+                            continue;
+                        }
+                        let Some(name) = func.name else { continue };
+                        let types = |params: &solang_parser::pt::ParameterList| params.iter()
+                            .filter_map(|(_, p)| p.as_ref().map(|p| solidity_type(&p.ty)))
+                            .collect::<Vec<_>>();
+                        surface.functions.push(AbiFunction::new(name.name, types(&func.params), types(&func.returns)));
+                    }
+                    ContractPart::VariableDefinition(var) => {
+                        let public = var.attrs.iter().any(|a| matches!(a, VariableAttribute::Visibility(Visibility::Public(_))));
+                        if let (true, Some(name)) = (public, var.name) {
+                            let (inputs, output) = getter_types(&var.ty);
+                            surface.functions.push(AbiFunction::new(name.name, inputs, vec![output]));
+                        }
+                    }
+                    ContractPart::EventDefinition(event) => {
+                        let Some(name) = event.name else { continue };
+                        surface.events.push(AbiEvent {
+                            name: name.name,
+                            params: event.fields.iter()
+                                .map(|f| if f.indexed {
+                                    format!("{} indexed", solidity_type(&f.ty))
+                                } else {
+                                    solidity_type(&f.ty)
+                                })
+                                .collect(),
+                        });
+                    }
+                    ContractPart::ErrorDefinition(error) => {
+                        let Some(name) = error.name else { continue };
+                        surface.errors.push(AbiError {
+                            name: name.name,
+                            inputs: error.fields.iter().map(|f| solidity_type(&f.ty)).collect(),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        surface
+    }
+
+    fn from_stylus(file: &syn::File) -> Self {
+        let mut surface = Self::default();
+
+        for item in &file.items {
+            match item {
+                syn::Item::Impl(impl_item) if is_external_impl(&impl_item.attrs) => {
+                    for impl_fn in &impl_item.items {
+                        let syn::ImplItem::Fn(method) = impl_fn else { continue };
+                        // Only `pub` methods taking `self` are exported; associated functions aren't
+                        if !matches!(method.vis, syn::Visibility::Public(_)) || method.sig.receiver().is_none() {
+                            continue;
+                        }
+                        surface.functions.push(stylus_function(method));
+                    }
+                }
+                syn::Item::Macro(mac) if mac.mac.path.segments.last().is_some_and(|s| s.ident == "sol") => {
+                    // `sol!` bodies are Solidity declarations; parse them as a contract body
+                    let wrapped = format!("contract __Abi {{ {} }}", mac.mac.tokens);
+                    if let Ok((unit, _)) = solang_parser::parse(&wrapped, 0) {
+                        let declared = Self::from_solidity(unit.0, false);
+                        surface.events.extend(declared.events);
+                        surface.errors.extend(declared.errors);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        surface
+    }
+}
+
+/// Impl blocks whose methods the Stylus SDK exports as contract entrypoints
+pub(crate) fn is_external_impl(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().segments.last().is_some_and(|s| {
+            s.ident == "public" || s.ident == "external" || s.ident == "contractimpl"
+        })
+    })
+}
+
+fn stylus_function(method: &syn::ImplItemFn) -> AbiFunction {
+    // `#[selector(name = "...")]` overrides the camelCase name the SDK derives
+    let renamed = method.attrs.iter()
+        .filter(|attr| attr.path().is_ident("selector"))
+        .find_map(|attr| {
+            let mut name = None;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                }
+                Ok(())
+            });
+            name
+        });
+    let name = renamed.unwrap_or_else(|| to_camel_case(&method.sig.ident.to_string()));
+
+    let inputs = method.sig.inputs.iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat) => Some(rust_to_solidity(&pat.ty)),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect();
+
+    let outputs = match &method.sig.output {
+        syn::ReturnType::Default => Vec::new(),
+        syn::ReturnType::Type(_, ty) => match result_ok_type(ty) {
+            syn::Type::Tuple(tuple) => tuple.elems.iter().map(rust_to_solidity).collect(),
+            other => vec![rust_to_solidity(other)],
+        },
+    };
+
+    AbiFunction::new(name, inputs, outputs)
+}
+
+/// Unwraps `Result<T, E>` to `T`; reverts aren't part of the return ABI
+fn result_ok_type(ty: &syn::Type) -> &syn::Type {
+    if let syn::Type::Path(path) = ty {
+        if let Some(last) = path.path.segments.last() {
+            if last.ident == "Result" {
+                if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+                    if let Some(syn::GenericArgument::Type(ok)) = args.args.first() {
+                        return ok;
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
+
+/// Solidity ABI type for a Rust type in a Stylus method signature
+pub(crate) fn rust_to_solidity(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Reference(reference) => rust_to_solidity(&reference.elem),
+        syn::Type::Array(array) => format!("{}[{}]", rust_to_solidity(&array.elem), array.len.to_token_stream()),
+        syn::Type::Tuple(tuple) => format!("({})", tuple.elems.iter().map(rust_to_solidity).collect::<Vec<_>>().join(",")),
+        syn::Type::Path(path) => {
+            let Some(last) = path.path.segments.last() else { return "unknown".to_string() };
+            let ident = last.ident.to_string();
+            let generic = || match &last.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.first().map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => rust_to_solidity(ty),
+                    other => other.to_token_stream().to_string(),
+                }),
+                _ => None,
+            };
+
+            match ident.as_str() {
+                "Address" => "address".to_string(),
+                "bool" => "bool".to_string(),
+                "String" | "str" => "string".to_string(),
+                "Bytes" => "bytes".to_string(),
+                "Vec" => format!("{}[]", generic().unwrap_or_default()),
+                "FixedBytes" => format!("bytes{}", generic().unwrap_or_default()),
+                "B256" => "bytes32".to_string(),
+                _ => integer_type(&ident).unwrap_or(ident),
+            }
+        }
+        other => other.to_token_stream().to_string(),
+    }
+}
+
+/// Maps `u64`/`U256`/`i128`/`I256` style names to `uint64`/`uint256`/...
+fn integer_type(ident: &str) -> Option<String> {
+    let (signed, bits) = match ident.chars().next()? {
+        'u' | 'U' => (false, &ident[1..]),
+        'i' | 'I' => (true, &ident[1..]),
+        _ => return None,
+    };
+    let bits: u16 = if bits == "size" { 256 } else { bits.parse().ok()? };
+    Some(format!("{}{}", if signed { "int" } else { "uint" }, bits))
+}
+
+pub(crate) fn solidity_type(ty: &Expression) -> String {
+    match ty {
+        Expression::Type(_, Type::AddressPayable) => "address".to_string(),
+        Expression::Type(_, Type::Uint(bits)) => format!("uint{}", bits),
+        Expression::Type(_, Type::Int(bits)) => format!("int{}", bits),
+        Expression::ArraySubscript(_, inner, None) => format!("{}[]", solidity_type(inner)),
+        Expression::ArraySubscript(_, inner, Some(len)) => format!("{}[{}]", solidity_type(inner), len),
+        other => other.to_string(),
+    }
+}
+
+/// Parameters and return type of the getter Solidity generates for a public state variable
+fn getter_types(ty: &Expression) -> (Vec<String>, String) {
+    match ty {
+        Expression::Type(_, Type::Mapping { key, value, .. }) => {
+            let (mut inputs, output) = getter_types(value);
+            inputs.insert(0, solidity_type(key));
+            (inputs, output)
+        }
+        Expression::ArraySubscript(_, inner, _) => {
+            let (mut inputs, output) = getter_types(inner);
+            inputs.insert(0, "uint256".to_string());
+            (inputs, output)
+        }
+        other => (Vec::new(), solidity_type(other)),
+    }
+}
+
+pub(crate) fn to_camel_case(name: &str) -> String {
+    let mut camel = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' && !camel.is_empty() {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+fn selector(signature: &str) -> String {
+    format!("0x{}", hex::encode(ethers::utils::id(signature)))
+}
+
+/// Breaking and non-breaking interface changes between two versions
+#[derive(Debug, Serialize)]
+pub struct AbiDiff {
+    pub old_file: String,
+    pub new_file: String,
+    /// Breaking changes: removed functions, changed signatures and event shapes
+    pub findings: Vec<Vulnerability>,
+    pub added_functions: Vec<String>,
+    pub added_events: Vec<String>,
+    pub added_errors: Vec<String>,
+    pub unchanged: usize,
+}
+
+pub fn diff_surfaces(old: &AbiSurface, new: &AbiSurface, old_file: &str, new_file: &str) -> AbiDiff {
+    let mut diff = AbiDiff {
+        old_file: old_file.to_string(),
+        new_file: new_file.to_string(),
+        findings: Vec::new(),
+        added_functions: Vec::new(),
+        added_events: Vec::new(),
+        added_errors: Vec::new(),
+        unchanged: 0,
+    };
+
+    let new_by_signature: BTreeMap<String, &AbiFunction> = new.functions.iter().map(|f| (f.signature(), f)).collect();
+    let old_by_signature: BTreeMap<String, &AbiFunction> = old.functions.iter().map(|f| (f.signature(), f)).collect();
+
+    for old_fn in &old.functions {
+        match new_by_signature.get(&old_fn.signature()) {
+            Some(new_fn) if new_fn.outputs == old_fn.outputs => diff.unchanged += 1,
+            Some(new_fn) => diff.findings.push(breaking(
+                "ABI Return Type Changed",
+                format!("{} now returns ({}) instead of ({}); callers decoding the old return data break",
+                    old_fn.signature(), new_fn.outputs.join(","), old_fn.outputs.join(",")),
+                "Keep the old return type or add a new function alongside the old one",
+            )),
+            None => {
+                // Same name, different parameters: the selector changed under callers
+                let replacements = new.functions.iter()
+                    .filter(|f| f.name == old_fn.name && !old_by_signature.contains_key(&f.signature()))
+                    .map(|f| f.display())
+                    .collect::<Vec<_>>();
+                if replacements.is_empty() {
+                    diff.findings.push(breaking(
+                        "ABI Function Removed",
+                        format!("{} was removed; existing callers will revert", old_fn.display()),
+                        "Keep a deprecated wrapper with the old signature until integrators migrate",
+                    ));
+                } else {
+                    diff.findings.push(breaking(
+                        "ABI Function Signature Changed",
+                        format!("{} changed to {}; the selector no longer matches existing callers",
+                            old_fn.display(), replacements.join(" / ")),
+                        "Keep the old signature as a wrapper or version the function name",
+                    ));
+                }
+            }
+        }
+    }
+
+    for new_fn in &new.functions {
+        let replaces_old = old.functions.iter().any(|f| f.name == new_fn.name);
+        if !old_by_signature.contains_key(&new_fn.signature()) && !replaces_old {
+            diff.added_functions.push(new_fn.display());
+        }
+    }
+
+    for old_event in &old.events {
+        match new.events.iter().find(|e| e.name == old_event.name) {
+            Some(new_event) if new_event == old_event => diff.unchanged += 1,
+            Some(new_event) => diff.findings.push(breaking(
+                "ABI Event Shape Changed",
+                format!("{} changed to {}; indexers and log decoders will miss or misread it",
+                    old_event.display(), new_event.display()),
+                "Emit a new event name instead of changing an existing event's parameters",
+            )),
+            None => diff.findings.push(breaking(
+                "ABI Event Removed",
+                format!("{} is no longer declared; off-chain consumers listening for it stop receiving data", old_event.display()),
+                "Keep emitting the old event until consumers migrate",
+            )),
+        }
+    }
+    for new_event in &new.events {
+        if !old.events.iter().any(|e| e.name == new_event.name) {
+            diff.added_events.push(new_event.display());
+        }
+    }
+
+    for old_error in &old.errors {
+        match new.errors.iter().find(|e| e.name == old_error.name) {
+            Some(new_error) if new_error == old_error => diff.unchanged += 1,
+            Some(new_error) => diff.findings.push(Vulnerability {
+                name: "ABI Error Changed".to_string(),
+                severity: Severity::Medium,
+                risk_description: format!("{} changed to {}; clients decoding revert data break",
+                    old_error.display(), new_error.display()),
+                recommendation: "Declare a new error instead of changing an existing one".to_string(),
+                ..Default::default()
+            }),
+            None => diff.findings.push(Vulnerability {
+                name: "ABI Error Removed".to_string(),
+                severity: Severity::Medium,
+                risk_description: format!("{} is no longer declared; clients matching on it need updating", old_error.display()),
+                recommendation: "Document the change for integrators".to_string(),
+                ..Default::default()
+            }),
+        }
+    }
+    for new_error in &new.errors {
+        if !old.errors.iter().any(|e| e.name == new_error.name) {
+            diff.added_errors.push(new_error.display());
+        }
+    }
+
+    diff
+}
+
+fn breaking(name: &str, risk_description: String, recommendation: &str) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity: Severity::High,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn format_abi_diff(diff: &AbiDiff) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("\n{}\n", "🔌 ABI Surface Comparison".bright_green().bold()));
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    output.push_str(&format!("Old: {}\nNew: {}\n\n", diff.old_file, diff.new_file));
+
+    if diff.findings.is_empty() {
+        output.push_str(&format!("{}\n", "✅ No breaking interface changes".green()));
+    } else {
+        output.push_str(&format!("{}\n", format!("🚨 Breaking changes ({})", diff.findings.len()).red().bold()));
+        for finding in &diff.findings {
+            let icon = if finding.severity == Severity::High { "⚠️" } else { "ℹ️" };
+            output.push_str(&format!("{} [{:?}] {}\n", icon, finding.severity, finding.name.bold()));
+            output.push_str(&format!("  Risk: {}\n", finding.risk_description));
+            output.push_str(&format!("  Mitigation: {}\n\n", finding.recommendation));
+        }
+    }
+
+    let added = [
+        ("Functions", &diff.added_functions),
+        ("Events", &diff.added_events),
+        ("Errors", &diff.added_errors),
+    ];
+    if added.iter().any(|(_, items)| !items.is_empty()) {
+        output.push_str(&format!("\n{}\n", "➕ Added (non-breaking)".cyan().bold()));
+        for (label, items) in added {
+            for item in items {
+                output.push_str(&format!("  • {}: {}\n", label, item));
+            }
+        }
+    }
+
+    output.push_str(&format!("\n{} unchanged interface item(s)\n", diff.unchanged));
+    output
+}
+
+pub fn format_abi_json(diff: &AbiDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize ABI diff: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"
+pragma solidity ^0.8.20;
+contract Vault {
+    uint256 public totalShares;
+    event Deposited(address indexed account, uint256 amount);
+    error Paused();
+    function deposit(uint256 amount) external {}
+    function withdraw(uint256 shares) external returns (uint256) {}
+    function sweep() external {}
+    function previewRedeem(uint256 shares) external view returns (uint256) {}
+}
+"#;
+
+    const NEW: &str = r#"
+pragma solidity ^0.8.20;
+contract Vault {
+    uint256 public totalShares;
+    event Deposited(address account, uint256 amount);
+    error Paused();
+    error Capped(uint256 cap);
+    function deposit(uint256 amount, address receiver) external {}
+    function withdraw(uint256 shares) external returns (uint256, uint256) {}
+    function previewRedeem(uint256 shares) external view returns (uint256) {}
+    function maxDeposit(address account) external view returns (uint256) {}
+}
+"#;
+
+    #[test]
+    fn additions_removals_and_changes_are_told_apart() {
+        let old = AbiSurface::from_source(OLD).unwrap();
+        let new = AbiSurface::from_source(NEW).unwrap();
+        let diff = diff_surfaces(&old, &new, "old.sol", "new.sol");
+
+        let breaking: Vec<&str> = diff.findings.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(breaking, ["ABI Function Signature Changed", "ABI Return Type Changed", "ABI Function Removed", "ABI Event Shape Changed"]);
+        assert!(diff.findings.iter().all(|f| f.severity == Severity::High));
+        assert!(diff.findings[0].risk_description.contains("changed to deposit(uint256,address)"), "{}", diff.findings[0].risk_description);
+
+        assert_eq!(diff.added_functions.len(), 1);
+        assert!(diff.added_functions[0].starts_with("maxDeposit(address) returns (uint256) [0x"), "{:?}", diff.added_functions);
+        assert_eq!(diff.added_errors, ["error Capped(uint256)"]);
+        assert!(diff.added_events.is_empty());
+        // totalShares, previewRedeem and Paused
+        assert_eq!(diff.unchanged, 3);
+    }
+
+    #[test]
+    fn an_identical_surface_has_no_findings() {
+        let surface = AbiSurface::from_source(OLD).unwrap();
+        let diff = diff_surfaces(&surface, &AbiSurface::from_source(OLD).unwrap(), "a.sol", "b.sol");
+        assert!(diff.findings.is_empty() && diff.added_functions.is_empty());
+        assert_eq!(diff.unchanged, 7);
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use colored::*;
+use serde::Serialize;
+use crate::audit::{AuditAnalyzer, AuditResult};
+use crate::audit::fingerprint;
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+use crate::options::AnalysisOptions;
+use crate::parser::ParsedContract;
+
+pub mod abi;
+
+/// Before/after pair for a single metric
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Delta {
+    pub old: i64,
+    pub new: i64,
+}
+
+impl Delta {
+    fn new(old: impl TryInto<i64>, new: impl TryInto<i64>) -> Self {
+        Self {
+            old: old.try_into().unwrap_or(i64::MAX),
+            new: new.try_into().unwrap_or(i64::MAX),
+        }
+    }
+
+    pub fn change(&self) -> i64 {
+        self.new - self.old
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub gas_estimate: u64,
+    pub complexity: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionDelta {
+    pub name: String,
+    pub gas_estimate: Delta,
+    pub complexity: Delta,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Improved,
+    Regressed,
+    Mixed,
+    Unchanged,
+}
+
+/// Everything that changed between two versions of a contract
+#[derive(Debug, Serialize)]
+pub struct ContractDiff {
+    pub old_file: String,
+    pub new_file: String,
+    pub source_bytes: Delta,
+    pub code_size: Delta,
+    pub total_gas_estimate: Delta,
+    pub functions_added: Vec<FunctionMetrics>,
+    pub functions_removed: Vec<FunctionMetrics>,
+    pub functions_changed: Vec<FunctionDelta>,
+    pub functions_unchanged: usize,
+    pub findings_fixed: Vec<Vulnerability>,
+    pub findings_introduced: Vec<Vulnerability>,
+    pub findings_unchanged: Vec<Vulnerability>,
+    /// Audit rules that failed on either side; the findings diff may be incomplete
+    pub warnings: Vec<String>,
+    pub verdict: Verdict,
+}
+
+/// Runs the static size, gas, complexity and audit analyses on both versions
+/// and diffs the results, auditing with the rules `options` selects. No AI
+/// calls are made.
+pub async fn compare_contracts(old: &Path, new: &Path, options: &AnalysisOptions) -> Result<ContractDiff> {
+    let old_source = std::fs::read_to_string(old)?;
+    let new_source = std::fs::read_to_string(new)?;
+    let old_contract = ParsedContract::new(old_source.clone())?;
+    let new_contract = ParsedContract::new(new_source.clone())?;
+
+    // Fingerprint both sides as the same logical file so renames between versions don't matter
+    let label = new.display().to_string();
+    let analyzer = options.analyzer()?;
+    let mut old_audit = run_audit(&analyzer, old).await?;
+    let mut new_audit = run_audit(&analyzer, new).await?;
+    fingerprint::assign(&mut old_audit, &label, &old_source);
+    fingerprint::assign(&mut new_audit, &label, &new_source);
+
+    let mut warnings = Vec::new();
+    for (label, audit) in [("old", &old_audit), ("new", &new_audit)] {
+        for error in &audit.errors {
+            warnings.push(format!("rule '{}' failed on the {} version: {}", error.rule, label, error.message));
+        }
+    }
+
+    let old_functions = function_metrics(&old_contract);
+    let new_functions = function_metrics(&new_contract);
+
+    let mut functions_added = Vec::new();
+    let mut functions_removed = Vec::new();
+    let mut functions_changed = Vec::new();
+    let mut functions_unchanged = 0;
+
+    for (name, new_metrics) in &new_functions {
+        match old_functions.get(name) {
+            None => functions_added.push(new_metrics.clone()),
+            Some(old_metrics) if old_metrics.gas_estimate == new_metrics.gas_estimate
+                && old_metrics.complexity == new_metrics.complexity => functions_unchanged += 1,
+            Some(old_metrics) => functions_changed.push(FunctionDelta {
+                name: name.clone(),
+                gas_estimate: Delta::new(old_metrics.gas_estimate, new_metrics.gas_estimate),
+                complexity: Delta::new(old_metrics.complexity, new_metrics.complexity),
+            }),
+        }
+    }
+    for (name, old_metrics) in &old_functions {
+        if !new_functions.contains_key(name) {
+            functions_removed.push(old_metrics.clone());
+        }
+    }
+
+    let (findings_fixed, findings_introduced, findings_unchanged) = diff_findings(&old_audit, &new_audit);
+
+    let total_gas = |functions: &BTreeMap<String, FunctionMetrics>| {
+        functions.values().map(|f| f.gas_estimate).sum::<u64>()
+    };
+    let total_gas_estimate = Delta::new(total_gas(&old_functions), total_gas(&new_functions));
+
+    let verdict = verdict(&findings_fixed, &findings_introduced, &total_gas_estimate);
+
+    Ok(ContractDiff {
+        old_file: old.display().to_string(),
+        new_file: new.display().to_string(),
+        source_bytes: Delta::new(old_source.len(), new_source.len()),
+        code_size: Delta::new(code_size(&old_contract), code_size(&new_contract)),
+        total_gas_estimate,
+        functions_added,
+        functions_removed,
+        functions_changed,
+        functions_unchanged,
+        findings_fixed,
+        findings_introduced,
+        findings_unchanged,
+        warnings,
+        verdict,
+    })
+}
+
+/// Diffs only the external interface (functions, events, errors) of two versions
+pub fn compare_abi(old: &Path, new: &Path) -> Result<abi::AbiDiff> {
+    let old_surface = abi::AbiSurface::from_source(&std::fs::read_to_string(old)?)?;
+    let new_surface = abi::AbiSurface::from_source(&std::fs::read_to_string(new)?)?;
+    Ok(abi::diff_surfaces(&old_surface, &new_surface, &old.display().to_string(), &new.display().to_string()))
+}
+
+async fn run_audit(analyzer: &AuditAnalyzer, file: &Path) -> Result<AuditResult> {
+    let result = analyzer.audit_file(file).await?;
+    if result.interrupted {
+        // A half-audited side would show bogus fixed/introduced findings
+        return Err(AnalyzerError::Interrupted);
+    }
+    Ok(result)
+}
+
+fn function_metrics(contract: &ParsedContract) -> BTreeMap<String, FunctionMetrics> {
+    contract.functions.iter()
+        .map(|function| (function.name.clone(), FunctionMetrics {
+            name: function.name.clone(),
+            gas_estimate: function.estimated_gas(),
+            complexity: function.cyclomatic_complexity(),
+        }))
+        .collect()
+}
+
+// Same component breakdown the size analyzer reports
+fn code_size(contract: &ParsedContract) -> usize {
+    contract.get_function_size().unwrap_or(0)
+        + contract.get_storage_size().unwrap_or(0)
+        + contract.get_event_size().unwrap_or(0)
+}
+
+/// Splits findings into (fixed, introduced, unchanged) by fingerprint
+fn diff_findings(old: &AuditResult, new: &AuditResult) -> (Vec<Vulnerability>, Vec<Vulnerability>, Vec<Vulnerability>) {
+    let old_prints: HashSet<&str> = old.vulnerabilities().map(|v| v.fingerprint.as_str()).collect();
+    let new_prints: HashSet<&str> = new.vulnerabilities().map(|v| v.fingerprint.as_str()).collect();
+
+    let fixed = old.vulnerabilities()
+        .filter(|v| !new_prints.contains(v.fingerprint.as_str()))
+        .cloned()
+        .collect();
+    let (unchanged, introduced) = new.vulnerabilities()
+        .cloned()
+        .partition(|v| old_prints.contains(v.fingerprint.as_str()));
+
+    (fixed, introduced, unchanged)
+}
+
+fn verdict(fixed: &[Vulnerability], introduced: &[Vulnerability], gas: &Delta) -> Verdict {
+    let better = !fixed.is_empty() || gas.change() < 0;
+    let worse = !introduced.is_empty() || gas.change() > 0;
+
+    match (better, worse) {
+        (true, true) => Verdict::Mixed,
+        (true, false) => Verdict::Improved,
+        (false, true) => Verdict::Regressed,
+        (false, false) => Verdict::Unchanged,
+    }
+}
+
+pub fn format_json(diff: &ContractDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize comparison: {}", e)))
+}
+
+pub fn format_diff(diff: &ContractDiff) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("\n{}\n", "🔀 Contract Comparison Report".bright_green().bold()));
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    output.push_str(&format!("Old: {}\nNew: {}\n\n", diff.old_file, diff.new_file));
+
+    let critical = diff.findings_introduced.iter().filter(|vuln| vuln.severity == Severity::Critical).count();
+    if critical > 0 {
+        output.push_str(&format!("{}\n\n",
+            format!("🚨 This change introduces {} Critical finding(s); see Introduced below", critical).red().bold()
+        ));
+    }
+
+    for warning in &diff.warnings {
+        output.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+    if !diff.warnings.is_empty() {
+        output.push('\n');
+    }
+
+    output.push_str(&format!("{}\n", "📏 Size".yellow().bold()));
+    output.push_str(&format!("  • Source: {}\n", format_delta(&diff.source_bytes, "bytes")));
+    output.push_str(&format!("  • Code size: {}\n\n", format_delta(&diff.code_size, "bytes")));
+
+    output.push_str(&format!("{}\n", "⛽ Gas & Complexity".yellow().bold()));
+    output.push_str(&format!("  • Total gas estimate: {}\n", format_delta(&diff.total_gas_estimate, "gas")));
+    if diff.functions_changed.is_empty() {
+        output.push_str("  • No per-function changes\n");
+    }
+    for function in &diff.functions_changed {
+        output.push_str(&format!("  📝 {}\n", function.name.cyan().bold()));
+        output.push_str(&format!("     Gas: {}\n", format_delta(&function.gas_estimate, "gas")));
+        output.push_str(&format!("     Complexity: {}\n", format_delta(&function.complexity, "")));
+    }
+    output.push_str(&format!("  • {} function(s) unchanged\n\n", diff.functions_unchanged));
+
+    if !diff.functions_added.is_empty() {
+        output.push_str(&format!("{}\n", "➕ Functions Added".green().bold()));
+        for function in &diff.functions_added {
+            output.push_str(&format!("  • {} (gas ~{}, complexity {})\n",
+                function.name, function.gas_estimate, function.complexity));
+        }
+        output.push('\n');
+    }
+
+    if !diff.functions_removed.is_empty() {
+        output.push_str(&format!("{}\n", "➖ Functions Removed".red().bold()));
+        for function in &diff.functions_removed {
+            output.push_str(&format!("  • {} (gas ~{}, complexity {})\n",
+                function.name, function.gas_estimate, function.complexity));
+        }
+        output.push('\n');
+    }
+
+    output.push_str(&format!("{}\n", "🛡️  Findings".yellow().bold()));
+    output.push_str(&format_findings("✅ Fixed", &diff.findings_fixed, Color::Green));
+    output.push_str(&format_findings("🚨 Introduced", &diff.findings_introduced, Color::Red));
+    output.push_str(&format!("  {} unchanged finding(s)\n\n", diff.findings_unchanged.len()));
+
+    let verdict = match diff.verdict {
+        Verdict::Improved => "✅ Verdict: IMPROVED".green().bold(),
+        Verdict::Regressed => "🚨 Verdict: REGRESSED".red().bold(),
+        Verdict::Mixed => "⚖️  Verdict: MIXED".yellow().bold(),
+        Verdict::Unchanged => "➖ Verdict: UNCHANGED".normal().bold(),
+    };
+    output.push_str(&format!("{} ({} fixed, {} introduced, gas {:+})\n",
+        verdict,
+        diff.findings_fixed.len(),
+        diff.findings_introduced.len(),
+        diff.total_gas_estimate.change()
+    ));
+
+    output
+}
+
+fn format_delta(delta: &Delta, unit: &str) -> String {
+    let change = delta.change();
+    let change_text = format!("{:+}", change);
+    let colored_change = match change.signum() {
+        1 => change_text.red(),
+        -1 => change_text.green(),
+        _ => change_text.normal(),
+    };
+    format!("{} → {} {} ({})", delta.old, delta.new, unit, colored_change)
+        .replace("  (", " (")
+}
+
+fn format_findings(label: &str, findings: &[Vulnerability], color: Color) -> String {
+    let mut output = format!("  {} ({})\n", label.color(color).bold(), findings.len());
+    for vuln in findings {
+        output.push_str(&format!("    • [{:?}] {}\n", vuln.severity, vuln.name));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn the_v2_token_trades_a_cheaper_transfer_for_an_unguarded_burn() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test ex");
+        let diff = compare_contracts(&dir.join("inefficient_token.rs"), &dir.join("inefficient_token_v2.rs"), &AnalysisOptions::new().ai(false))
+            .await.unwrap();
+
+        let added: Vec<&str> = diff.functions_added.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(added, ["InefficientToken::burn_from"]);
+        assert!(diff.functions_removed.is_empty());
+        let [transfer] = &diff.functions_changed[..] else { panic!("{:?}", diff.functions_changed) };
+        assert_eq!(transfer.name, "InefficientToken::transfer");
+        assert!(transfer.gas_estimate.change() < 0);
+
+        // burn_from starts at line 77 of v2
+        let introduced: Vec<(&str, Option<usize>)> = diff.findings_introduced.iter().map(|v| (v.name.as_str(), v.line)).collect();
+        for expected in [("Missing Access Control", Some(77)), ("Raw Pointer Usage", Some(79))] {
+            assert!(introduced.contains(&expected), "{:?} missing from {:?}", expected, introduced);
+        }
+        assert!(!diff.findings_fixed.is_empty());
+        assert!(!diff.findings_unchanged.is_empty());
+        assert!(diff.warnings.is_empty(), "{:?}", diff.warnings);
+        assert_eq!(diff.verdict, Verdict::Mixed);
+    }
+
+    #[test]
+    fn verdicts_weigh_findings_and_gas() {
+        let finding = [Vulnerability::default()];
+        assert_eq!(verdict(&finding, &[], &Delta::new(10, 10)), Verdict::Improved);
+        assert_eq!(verdict(&[], &[], &Delta::new(10, 12)), Verdict::Regressed);
+        assert_eq!(verdict(&finding, &finding, &Delta::new(10, 10)), Verdict::Mixed);
+        assert_eq!(verdict(&[], &[], &Delta::new(10, 10)), Verdict::Unchanged);
+    }
+}
@@ -0,0 +1,130 @@
+//! Shell completions and man pages, generated from the CLI definition. Values
+//! that come from registries, like rule names, are filled in at generation
+//! time so the completions list whatever this build actually ships
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use clap::builder::PossibleValuesParser;
+use clap::{Command, CommandFactory, ValueEnum};
+use clap_complete::Shell;
+use stylus_analyzer::audit::effort::rule_slug;
+use stylus_analyzer::audit::patterns::all_rules;
+use stylus_analyzer::audit::vulnerabilities::Effort;
+use crate::cli::Cli;
+use stylus_analyzer::error::{AnalyzerError, Result};
+
+const BIN: &str = "stylus-analyzer";
+
+/// The CLI definition with registry-backed argument values attached
+pub fn command() -> Command {
+    let mut command = Cli::command();
+    let values = dynamic_values();
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in subcommands {
+        for (arg, candidates) in &values {
+            let has_arg = command.find_subcommand(&name)
+                .is_some_and(|sub| sub.get_arguments().any(|a| a.get_id() == *arg));
+            if has_arg {
+                let candidates = candidates.clone();
+                command = command.mut_subcommand(&name, |sub| {
+                    sub.mut_arg(*arg, |a| a.value_parser(PossibleValuesParser::new(candidates)))
+                });
+            }
+        }
+    }
+    command
+}
+
+/// Completion candidates by argument id, from the registries
+fn dynamic_values() -> Vec<(&'static str, Vec<String>)> {
+    let rules: Vec<String> = all_rules().iter().map(|rule| rule_slug(rule.name())).collect();
+    let efforts: Vec<String> = Effort::value_variants().iter()
+        .filter_map(|effort| effort.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    let effort_overrides = rules.iter()
+        .flat_map(|rule| efforts.iter().map(move |effort| format!("{}={}", rule, effort)))
+        .collect();
+    let ids: Vec<String> = all_rules().iter().map(|rule| rule.id().to_string()).collect();
+    vec![("effort", effort_overrides), ("only_rules", ids.clone()), ("skip_rules", ids)]
+}
+
+/// Writes the completion script for `shell`
+pub fn generate(shell: Shell, out: &mut impl Write) {
+    clap_complete::generate(shell, &mut command(), BIN, out);
+}
+
+/// Writes the completion script where `shell` looks for per-user completions
+pub fn install(shell: Shell) -> Result<PathBuf> {
+    let path = match shell {
+        Shell::Bash => data_home()?.join("bash-completion/completions").join(BIN),
+        Shell::Zsh => home()?.join(".zfunc").join(format!("_{}", BIN)),
+        Shell::Fish => config_home()?.join("fish/completions").join(format!("{}.fish", BIN)),
+        _ => return Err(AnalyzerError::Config(format!(
+            "{} has no per-user completion directory; redirect `completions {}` into your profile instead", shell, shell
+        ))),
+    };
+    write_file(&path, |out| {
+        generate(shell, out);
+        Ok(())
+    })?;
+    Ok(path)
+}
+
+/// Writes the top-level man page
+pub fn man(out: &mut impl Write) -> Result<()> {
+    clap_mangen::Man::new(command()).render(out)?;
+    Ok(())
+}
+
+/// Writes `stylus-analyzer.1` and a `stylus-analyzer-<command>.1` page per
+/// subcommand into `dir`
+pub fn man_pages(dir: &Path) -> Result<Vec<PathBuf>> {
+    let command = command();
+    let mut written = Vec::new();
+    let path = dir.join(format!("{}.1", BIN));
+    write_file(&path, man)?;
+    written.push(path);
+    for sub in command.get_subcommands() {
+        let name = format!("{}-{}", BIN, sub.get_name());
+        let path = dir.join(format!("{}.1", name));
+        write_file(&path, |out| Ok(clap_mangen::Man::new(sub.clone().name(name.clone())).render(out)?))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Per-user man page directory
+pub fn man_dir() -> Result<PathBuf> {
+    Ok(data_home()?.join("man/man1"))
+}
+
+fn write_file(path: &Path, render: impl FnOnce(&mut Vec<u8>) -> Result<()>) -> Result<()> {
+    let mut content = Vec::new();
+    render(&mut content)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn home() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| AnalyzerError::Config("HOME is not set; cannot find where to install".to_string()))
+}
+
+fn data_home() -> Result<PathBuf> {
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(home()?.join(".local/share")),
+    }
+}
+
+fn config_home() -> Result<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(home()?.join(".config")),
+    }
+}
@@ -0,0 +1,179 @@
+//! Settings read from the nearest `.stylus-analyzer.toml`, looked up from the
+//! analyzed file towards the filesystem root, or else from
+//! `$XDG_CONFIG_HOME/stylus-analyzer/config.toml`. Command-line flags and
+//! environment variables override them
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use tracing::warn;
+use crate::ai::backend::Provider;
+use crate::audit::effort::rule_slug;
+use crate::audit::patterns::{all_rules, severity_level, RuleSelection};
+use crate::audit::rules::AuditRule;
+use crate::error::{AnalyzerError, Result};
+
+/// Project config file, looked up in the analyzed file's directory and its parents
+pub const PROJECT_FILE: &str = ".stylus-analyzer.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// `openai` (the default) or `ollama`
+    pub provider: Option<Provider>,
+    /// Model every AI request goes to, e.g. `gpt-4o`
+    pub model: Option<String>,
+    /// Endpoint of the provider, e.g. `http://localhost:8000/v1` for vLLM
+    pub base_url: Option<String>,
+    /// `--format` when the flag isn't given: text, json, markdown or html
+    pub format: Option<String>,
+    /// Globs, relative to the config file, of files that directory and glob runs skip
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    #[serde(default)]
+    pub rules: RuleConfig,
+    /// Directory of the file the settings came from; None for defaults
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+    #[serde(skip)]
+    ignore_patterns: Vec<glob::Pattern>,
+}
+
+/// The `[rules]` table: audit rules by id, name or slug
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// When not empty, only these rules run
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Severity findings are reported at, by rule, problem family or finding
+    /// name, e.g. `testing-pattern-analyzer = "info"` or `reentrancy = "critical"`
+    #[serde(default)]
+    pub severity: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// The nearest project config at or above `start` (the current directory
+    /// when None), else the user config, else defaults
+    pub fn discover(start: Option<&Path>) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let start = match start {
+            Some(path) => cwd.join(path),
+            None => cwd.clone(),
+        };
+        // A file or a glob pattern starts the search in its directory
+        let start = if start.is_dir() { start.as_path() } else { start.parent().unwrap_or(&cwd) };
+        let project = start.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file());
+        match project.or_else(|| user_config_path().filter(|path| path.is_file())) {
+            Some(path) => Self::from_path(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let fail = |message: String| AnalyzerError::Config(format!("{}: {}", path.display(), message));
+        let mut config: Self = toml::from_str(&text).map_err(|e| fail(e.message().to_string()))?;
+        config.ignore_patterns = config.ignore.iter()
+            .map(|pattern| glob::Pattern::new(pattern).map_err(|e| fail(format!("invalid ignore glob '{}': {}", pattern, e))))
+            .collect::<Result<_>>()?;
+        config.root = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Whether `path` matches an `ignore` glob
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.ignore_patterns.is_empty() {
+            return false;
+        }
+        let absolute = std::env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf());
+        let relative = self.root.as_deref()
+            .and_then(|root| absolute.strip_prefix(root).ok())
+            .unwrap_or(path);
+        self.ignore_patterns.iter().any(|pattern| pattern.matches_path(relative))
+    }
+
+    /// The `[rules]` table resolved against the built-in rules
+    pub fn rule_selection(&self) -> Result<RuleSelection> {
+        self.rules.selection(&all_rules())
+    }
+}
+
+impl RuleConfig {
+    /// The table resolved against `rules`. Unknown rule names only warn, so
+    /// a config shared between versions keeps working
+    pub fn selection(&self, rules: &[Box<dyn AuditRule>]) -> Result<RuleSelection> {
+        let names: Vec<String> = rules.iter().map(|rule| rule.name().to_string()).collect();
+        let resolve = |rule: &str, key: &str| {
+            let found = rules.iter()
+                .find(|known| known.name() == rule || rule_slug(known.name()) == rule || known.id().eq_ignore_ascii_case(rule))
+                .map(|known| known.name().to_string());
+            if found.is_none() {
+                warn!("⚠️  Unknown rule \"{}\" in [rules] {}; known rules: {}", rule, key,
+                    names.iter().map(|name| rule_slug(name)).collect::<Vec<_>>().join(", "));
+            }
+            found
+        };
+
+        let enabled: Vec<String> = self.enabled.iter().filter_map(|rule| resolve(rule, "enabled")).collect();
+        let mut selection = RuleSelection {
+            // Every name unknown leaves nothing to restrict to; that's a typo, not a request for no rules
+            only: (!enabled.is_empty()).then_some(enabled),
+            disabled: self.disabled.iter().filter_map(|rule| resolve(rule, "disabled")).collect(),
+            ..Default::default()
+        };
+        for (key, level) in &self.severity {
+            let level = severity_level(level, "[rules] severity")?;
+            selection.remap_severity(key, level, "[rules] severity", rules)?;
+        }
+        Ok(selection)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/stylus-analyzer/config.toml`, falling back to `~/.config`
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME").filter(|home| !home.is_empty())?).join(".config"),
+    };
+    Some(base.join("stylus-analyzer").join("config.toml"))
+}
+
+/// Commented starting point for `init`, listing this build's rules
+pub fn template() -> String {
+    let rules = all_rules().iter()
+        .map(|rule| format!("#   {:<20} {}\n", rule.id(), rule_slug(rule.name())))
+        .collect::<String>();
+    format!(r#"# stylus-analyzer settings for this project. Command-line flags and
+# environment variables override everything here.
+
+# AI provider: "openai" (needs OPENAI_API_KEY) or "ollama" (runs locally)
+# provider = "openai"
+# model = "gpt-4o"
+# base_url = "http://localhost:8000/v1"
+
+# Output when --format isn't given: "text", "json", "markdown" or "html"
+# format = "text"
+
+# Files that directory and glob runs skip, relative to this file
+# ignore = ["contracts/mocks/**", "**/*.t.sol"]
+
+[rules]
+# Audit rules by id, name or slug. The rules in this build:
+{rules}
+# Run only these rules
+# enabled = ["reentrancy-pattern-checker"]
+# Never run these rules
+# disabled = ["testing-pattern-analyzer"]
+
+# Report findings at this severity: critical, high, medium, low or info. Keys
+# are rules, problem families (reentrancy, access-control, arithmetic, ...) or
+# finding names; a finding name wins over its family, and a family over its rule
+[rules.severity]
+# testing-pattern-analyzer = "info"
+# reentrancy = "critical"
+# "Missing Fuzz Testing" = "info"
+"#)
+}
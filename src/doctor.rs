@@ -0,0 +1,258 @@
+//! `doctor`: checks the environment the analyzer runs in and says what to fix
+
+use std::process::Command;
+use std::time::Duration;
+use colored::*;
+use dotenv::dotenv;
+use stylus_analyzer::ai;
+use stylus_analyzer::ai::backend::Provider;
+use stylus_analyzer::upgrade::layout::extract_layout;
+
+/// How long the provider ping may take
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SOLIDITY_FIXTURE: &str = "\
+pragma solidity ^0.8.20;
+contract Probe {
+    address owner;
+    uint256 total;
+    function bump(uint256 amount) external { total += amount; }
+}
+";
+
+const RUST_FIXTURE: &str = "\
+use stylus_sdk::prelude::*;
+sol_storage! {
+    #[entrypoint]
+    pub struct Probe {
+        address owner;
+        uint256 total;
+    }
+}
+#[public]
+impl Probe {
+    pub fn bump(&mut self, amount: U256) { let total = self.total.get(); self.total.set(total + amount); }
+}
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Skip,
+    Warn,
+    /// A hard requirement is missing; doctor exits non-zero
+    Fail,
+}
+
+/// Outcome of one item on the checklist
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into(), hint: None }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Runs the checklist; `offline` skips everything that needs the AI provider
+pub async fn run(offline: bool) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if ai::provider() == Provider::Ollama {
+        checks.push(Check::new("OpenAI API key", Status::Skip, "not needed with --provider ollama"));
+        checks.push(if offline {
+            Check::new("AI provider", Status::Skip, "not contacted (--offline)")
+        } else {
+            ping_ollama().await
+        });
+    } else {
+        let key = api_key(offline, &mut checks);
+        checks.push(match (offline, key) {
+            (true, _) => Check::new("AI provider", Status::Skip, "not contacted (--offline)"),
+            (false, None) => Check::new("AI provider", Status::Skip, "not contacted: no API key"),
+            (false, Some(key)) => ping(&key).await,
+        });
+    }
+    checks.push(tool("git", &["--version"], "needed by `audit --diff` to read older revisions"));
+    checks.push(tool("cargo-stylus", &["stylus", "--version"], "optional; `cargo stylus check` validates Stylus contracts on-chain"));
+    checks.push(tool("solc", &["--version"], "optional; compiling the Solidity contracts you audit confirms they build"));
+    checks.push(locale());
+    checks.push(write_access());
+    checks.push(parsers());
+    checks
+}
+
+/// Number of failed hard requirements
+pub fn failures(checks: &[Check]) -> usize {
+    checks.iter().filter(|check| check.status == Status::Fail).count()
+}
+
+/// Checks whether an OpenAI key is configured and returns it for the ping;
+/// the key itself is never printed
+fn api_key(offline: bool, checks: &mut Vec<Check>) -> Option<String> {
+    let from_environment = std::env::var("OPENAI_API_KEY").is_ok();
+    dotenv().ok();
+    let Ok(key) = std::env::var("OPENAI_API_KEY") else {
+        let status = if offline { Status::Warn } else { Status::Fail };
+        checks.push(Check::new("OpenAI API key", status, "OPENAI_API_KEY is not set")
+            .with_hint("Export OPENAI_API_KEY or add it to a .env file; static commands and --offline work without it"));
+        return None;
+    };
+    let source = if from_environment { "environment" } else { ".env" };
+    let check = if key.trim().is_empty() {
+        Check::new("OpenAI API key", if offline { Status::Warn } else { Status::Fail }, format!("OPENAI_API_KEY is empty (from {})", source))
+            .with_hint("Set it to the key from platform.openai.com")
+    } else if key.trim() != key {
+        Check::new("OpenAI API key", Status::Warn, format!("set from {}, {} characters, with surrounding whitespace", source, key.len()))
+            .with_hint("Remove the spaces or newline around the key; the provider rejects it as is")
+    } else {
+        Check::new("OpenAI API key", Status::Pass, format!("set from {}, {} characters", source, key.len()))
+    };
+    checks.push(check);
+    Some(key)
+}
+
+/// Lists models with the key: the cheapest request that proves both the
+/// endpoint and the key work
+async fn ping(key: &str) -> Check {
+    let client = match reqwest::Client::builder().timeout(PING_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return Check::new("AI provider", Status::Fail, format!("cannot build an HTTP client: {}", e)),
+    };
+    let base_url = ai::base_url();
+    let host = base_url.split("://").nth(1).unwrap_or(&base_url).to_string();
+    match client.get(format!("{}/models", base_url)).bearer_auth(key.trim()).send().await {
+        Ok(response) if response.status().is_success() => {
+            Check::new("AI provider", Status::Pass, format!("{} answered and accepted the key (model {})", host, ai::model()))
+        }
+        Ok(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED => {
+            Check::new("AI provider", Status::Fail, format!("{} rejected the key (401)", host))
+                .with_hint("Check that the key is current and belongs to an active project")
+        }
+        Ok(response) => Check::new("AI provider", Status::Warn, format!("{} answered {}", host, response.status()))
+            .with_hint("The provider may be rate limiting or degraded; try again later"),
+        Err(e) => Check::new("AI provider", Status::Fail, format!("{} is unreachable: {}", host, e))
+            .with_hint("Check your network, proxy (HTTPS_PROXY), firewall and OPENAI_BASE_URL; use --offline to skip AI analyses"),
+    }
+}
+
+/// Lists the local models: proves the server is up and the model is pulled
+async fn ping_ollama() -> Check {
+    #[derive(serde::Deserialize)]
+    struct Tags { models: Vec<Tag> }
+    #[derive(serde::Deserialize)]
+    struct Tag { name: String }
+
+    let client = match reqwest::Client::builder().timeout(PING_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return Check::new("AI provider", Status::Fail, format!("cannot build an HTTP client: {}", e)),
+    };
+    let base_url = ai::base_url();
+    let model = ai::model();
+    let tags = match client.get(format!("{}/api/tags", base_url)).send().await {
+        Ok(response) => response.json::<Tags>().await,
+        Err(e) => {
+            return Check::new("AI provider", Status::Fail, format!("Ollama at {} is unreachable: {}", base_url, e))
+                .with_hint("Start it with `ollama serve`, or point OLLAMA_HOST at the server");
+        }
+    };
+    match tags {
+        // Ollama lists `codellama` as `codellama:latest`
+        Ok(tags) if tags.models.iter().any(|tag| tag.name == model || tag.name.strip_suffix(":latest") == Some(model)) => {
+            Check::new("AI provider", Status::Pass, format!("Ollama at {} has model {}", base_url, model))
+        }
+        Ok(_) => Check::new("AI provider", Status::Fail, format!("Ollama at {} has no model {}", base_url, model))
+            .with_hint(format!("Run `ollama pull {}`, or pick a pulled model with --model", model)),
+        Err(e) => Check::new("AI provider", Status::Warn, format!("Ollama at {} answered unexpectedly: {}", base_url, e)),
+    }
+}
+
+/// First line of `program args` output, or a warning with what the tool is for
+fn tool(name: &'static str, args: &[&str], purpose: &str) -> Check {
+    let program = if name == "cargo-stylus" { "cargo" } else { name };
+    match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            let version = version.lines().find(|line| !line.trim().is_empty()).unwrap_or("installed").trim().to_string();
+            Check::new(name, Status::Pass, version)
+        }
+        _ => Check::new(name, Status::Warn, "not found").with_hint(purpose.to_string()),
+    }
+}
+
+fn locale() -> Check {
+    if crate::glyphs::detect_ascii() {
+        Check::new("Locale", Status::Warn, "not UTF-8; output falls back to ASCII glyphs")
+            .with_hint("Set LANG=en_US.UTF-8 (or another UTF-8 locale) for emoji and box drawing")
+    } else {
+        Check::new("Locale", Status::Pass, "UTF-8")
+    }
+}
+
+/// Baselines, findings databases and fixes are written next to where the tool runs
+fn write_access() -> Check {
+    let probe = std::path::PathBuf::from(format!(".stylus-analyzer-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::new("Write access", Status::Pass, "current directory is writable")
+        }
+        Err(e) => Check::new("Write access", Status::Warn, format!("cannot write to the current directory: {}", e))
+            .with_hint("Baselines, findings databases and --fix --apply need a writable directory; pass paths elsewhere"),
+    }
+}
+
+/// Parses the embedded fixtures and reads their storage layout
+fn parsers() -> Check {
+    let mut problems = Vec::new();
+    if let Err(errors) = solang_parser::parse(SOLIDITY_FIXTURE, 0) {
+        problems.push(format!("Solidity parser: {} diagnostics", errors.len()));
+    }
+    if let Err(e) = syn::parse_file(RUST_FIXTURE) {
+        problems.push(format!("Rust parser: {}", e));
+    }
+    for (language, fixture) in [("Solidity", SOLIDITY_FIXTURE), ("Rust", RUST_FIXTURE)] {
+        match extract_layout(fixture) {
+            Ok(layout) if layout.entries.len() == 2 => {}
+            Ok(layout) => problems.push(format!("{} layout: expected 2 slots, read {}", language, layout.entries.len())),
+            Err(e) => problems.push(format!("{} layout: {}", language, e)),
+        }
+    }
+    if problems.is_empty() {
+        Check::new("Parsers", Status::Pass, "Solidity and Rust fixtures parse")
+    } else {
+        Check::new("Parsers", Status::Fail, problems.join("; "))
+            .with_hint("This build is broken; reinstall stylus-analyzer and report the issue if it persists")
+    }
+}
+
+/// The checklist, one line per item with hints under warnings and failures
+pub fn format_checks(checks: &[Check]) -> String {
+    let mut output = format!("\n{}\n", "🩺 Environment check".cyan().bold());
+    for check in checks {
+        let (icon, detail) = match check.status {
+            Status::Pass => ("✅", check.detail.normal()),
+            Status::Skip => ("⏭️ ", check.detail.dimmed()),
+            Status::Warn => ("⚠️ ", check.detail.yellow()),
+            Status::Fail => ("❌", check.detail.red()),
+        };
+        output.push_str(&format!("  {} {}: {}\n", icon, check.name.bold(), detail));
+        if let Some(hint) = &check.hint {
+            output.push_str(&format!("     {}\n", hint.dimmed()));
+        }
+    }
+    let failed = failures(checks);
+    let warned = checks.iter().filter(|check| check.status == Status::Warn).count();
+    output.push_str(&format!("\n  {} passed, {} warning(s), {} failed\n",
+        checks.iter().filter(|check| check.status == Status::Pass).count(), warned, failed));
+    output
+}
@@ -0,0 +1,121 @@
+use thiserror::Error;
+use crate::audit::vulnerabilities::Severity;
+use crate::cancel::INTERRUPTED_EXIT_CODE;
+
+/// Crate-wide error type shared by the analyzers, audit rules and CLI
+#[derive(Debug, Error)]
+pub enum AnalyzerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse contract: {0}")]
+    Parse(String),
+
+    #[error("AI analysis failed: {0}")]
+    Ai(String),
+
+    #[error("OPENAI_API_KEY is not set")]
+    MissingApiKey,
+
+    #[error("Fix rejected: {0}")]
+    FixRejected(String),
+
+    #[error("Audit rule '{rule}' failed: {message}")]
+    Rule { rule: String, message: String },
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("{path} is {size} bytes, over the {limit} byte limit")]
+    InputTooLarge { path: String, size: u64, limit: u64 },
+
+    #[error("Analysis interrupted")]
+    Interrupted,
+
+    #[error("{count} blocking finding(s) at or above {threshold:?} severity")]
+    Findings { count: usize, threshold: Severity },
+
+    #[error("NatSpec coverage {coverage:.1}% is below the required {min:.1}%")]
+    DocCoverage { coverage: f64, min: f64 },
+
+    #[error("{failed} environment check(s) failed")]
+    Doctor { failed: usize },
+}
+
+pub type Result<T> = std::result::Result<T, AnalyzerError>;
+
+impl AnalyzerError {
+    /// Process exit code for this error. Exit code 1 is reserved for runs that
+    /// completed but found problems, so scripts can tell "vulnerable" from "broken".
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AnalyzerError::Config(_) | AnalyzerError::Doctor { .. } => 2,
+            AnalyzerError::Io(_) => 3,
+            AnalyzerError::Parse(_) => 4,
+            AnalyzerError::Ai(_) | AnalyzerError::FixRejected(_) => 5,
+            AnalyzerError::Rule { .. } => 6,
+            AnalyzerError::InputTooLarge { .. } => 7,
+            AnalyzerError::MissingApiKey => 8,
+            AnalyzerError::Interrupted => INTERRUPTED_EXIT_CODE,
+            AnalyzerError::Findings { .. } | AnalyzerError::DocCoverage { .. } => 1,
+        }
+    }
+
+    /// Short hint telling the user what to do about the error
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AnalyzerError::Io(_) => Some("Check that the contract path exists and is readable."),
+            AnalyzerError::Parse(_) => Some("Only Solidity and Rust (Stylus) sources are supported."),
+            AnalyzerError::Ai(_) => Some("Check OPENAI_API_KEY (or OLLAMA_HOST), the model name (--model) and your network connection."),
+            AnalyzerError::MissingApiKey => Some("Export OPENAI_API_KEY=<key> or add it to a .env file, pass --provider ollama to use a local model, or --no-ai to run only the static analyses."),
+            AnalyzerError::FixRejected(_) => Some("The file was not changed. Run again for a different proposal, or fix it by hand."),
+            AnalyzerError::Rule { .. } => Some("Re-run without --strict-rules to see the partial audit."),
+            AnalyzerError::Config(_) => None,
+            AnalyzerError::InputTooLarge { .. } => Some("Pass --force-large (or raise --max-input-size) to analyze it anyway; AI analyses stay capped."),
+            AnalyzerError::Interrupted => Some("Results printed above are partial."),
+            AnalyzerError::Findings { .. } => Some("Fix the blocking findings listed above, or record reviewed ones with --update-baseline."),
+            AnalyzerError::DocCoverage { .. } => Some("Add @notice/@param/@return comments to the functions listed above."),
+            AnalyzerError::Doctor { .. } => Some("Follow the hints under the failed checks above, then run doctor again."),
+        }
+    }
+}
+
+impl From<rig::completion::PromptError> for AnalyzerError {
+    fn from(e: rig::completion::PromptError) -> Self {
+        AnalyzerError::Ai(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_tell_findings_from_failures() {
+        let codes = [
+            (AnalyzerError::Findings { count: 2, threshold: Severity::High }, 1),
+            (AnalyzerError::DocCoverage { coverage: 40.0, min: 80.0 }, 1),
+            (AnalyzerError::Config("bad key".to_string()), 2),
+            (AnalyzerError::Doctor { failed: 1 }, 2),
+            (AnalyzerError::Io(std::io::Error::from(std::io::ErrorKind::NotFound)), 3),
+            (AnalyzerError::Parse("unexpected token".to_string()), 4),
+            (AnalyzerError::Ai("timeout".to_string()), 5),
+            (AnalyzerError::FixRejected("declined".to_string()), 5),
+            (AnalyzerError::Rule { rule: "SA-X".to_string(), message: "failed".to_string() }, 6),
+            (AnalyzerError::InputTooLarge { path: "big.rs".to_string(), size: 2, limit: 1 }, 7),
+            (AnalyzerError::MissingApiKey, 8),
+            (AnalyzerError::Interrupted, INTERRUPTED_EXIT_CODE),
+        ];
+        for (error, code) in codes {
+            assert_eq!(error.exit_code(), code, "{}", error);
+        }
+    }
+
+    #[test]
+    fn messages_name_the_cause() {
+        let error = AnalyzerError::InputTooLarge { path: "big.rs".to_string(), size: 20, limit: 10 };
+        assert_eq!(error.to_string(), "big.rs is 20 bytes, over the 10 byte limit");
+        assert_eq!(AnalyzerError::Config("x".to_string()).hint(), None);
+        assert!(AnalyzerError::MissingApiKey.hint().is_some_and(|hint| hint.contains("--no-ai")));
+    }
+}
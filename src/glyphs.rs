@@ -0,0 +1,122 @@
+//! Emoji and box-drawing characters, and their ASCII stand-ins for terminals
+//! and log viewers that can't show them. Formatters keep writing the Unicode
+//! glyphs; output goes through `render`, which swaps them when ASCII mode is on
+
+use std::borrow::Cow;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Unicode glyph and its ASCII replacement. Emoji take two terminal columns,
+/// so they get two characters to keep columns lined up; single-width
+/// characters get one, except arrows which read better as `->`.
+/// Variants with a trailing U+FE0F come before the bare character
+const TABLE: &[(&str, &str)] = &[
+    // Warnings and failures
+    ("⚠️", "!!"), ("⚠", "!!"), ("❗", "!!"), ("🚨", "!!"), ("💥", "!!"),
+    ("❌", "XX"), ("🚫", "XX"),
+    // Success and highlights
+    ("✅", "OK"), ("✔️", "OK"), ("✔", "OK"),
+    ("✨", "**"), ("🌟", "**"), ("💫", "**"), ("💎", "**"),
+    // Progress
+    ("🔍", ">>"), ("🔄", ">>"), ("🔁", ">>"), ("⏳", ">>"), ("⚡", ">>"), ("🚀", ">>"),
+    ("🤖", ">>"), ("🧠", ">>"), ("📦", ">>"), ("📏", ">>"), ("📊", ">>"), ("📈", ">>"), ("🎯", ">>"),
+    // Notes and tips
+    ("💡", "=>"), ("ℹ️", "--"), ("ℹ", "--"), ("📝", "--"), ("💬", "--"), ("📣", "--"), ("📚", "--"),
+    ("🔸", "--"), ("🔹", "--"), ("🔗", "--"), ("🔕", "--"),
+    // Topic markers
+    ("🔧", "::"), ("🛠️", "::"), ("✍️", "::"), ("🛡️", "::"), ("🔒", "::"), ("🔑", "::"), ("🔌", "::"),
+    ("🔋", "::"), ("🔀", "::"), ("⛽", "::"), ("💰", "::"), ("💨", "::"), ("⚖️", "::"), ("🧾", "::"),
+    ("🗳️", "::"), ("🗄️", "::"), ("🗂️", "::"), ("🧪", "::"), ("🌱", "::"), ("🟢", "::"), ("🩺", "::"),
+    ("➕", "+ "), ("➖", "- "), ("⏭️", ">|"), ("❓", "??"), ("🤔", "??"),
+    // Keycap digits: "1️⃣" becomes "1."
+    ("\u{FE0F}\u{20E3}", "."),
+    // Single-width symbols and box drawing
+    ("•", "*"), ("·", "."), ("×", "x"), ("✓", "v"), ("—", "-"), ("…", "..."),
+    ("→", "->"), ("←", "<-"),
+    ("═", "="), ("─", "-"), ("│", "|"), ("┌", "+"), ("└", "+"), ("█", "#"),
+];
+
+/// Turns ASCII mode on or off for the rest of the run
+pub fn set_ascii(on: bool) {
+    ASCII.store(on, Ordering::Relaxed);
+}
+
+pub fn ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Whether the locale says the terminal can't show UTF-8: the first of
+/// `LC_ALL`, `LC_CTYPE` and `LANG` that is set must name a UTF-8 charmap.
+/// `TERM=dumb` is ASCII too
+pub fn detect_ascii() -> bool {
+    if cfg!(windows) {
+        return false;
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        }
+        None => true,
+    }
+}
+
+/// `text` with every glyph swapped for its ASCII stand-in in ASCII mode.
+/// Anything else outside ASCII, such as characters quoted from a contract,
+/// becomes `?`
+pub fn render(text: &str) -> Cow<'_, str> {
+    if !ascii() || text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii() {
+            out.push(c);
+            rest = &rest[1..];
+            continue;
+        }
+        if let Some((glyph, replacement)) = TABLE.iter().find(|(glyph, _)| rest.starts_with(glyph)) {
+            out.push_str(replacement);
+            rest = &rest[glyph.len()..];
+            continue;
+        }
+        // Variation selectors and joiners only modify the glyph before them
+        if !matches!(c, '\u{FE0F}' | '\u{200D}') {
+            out.push('?');
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    Cow::Owned(out)
+}
+
+/// Writer that passes everything through `render`
+pub struct GlyphWriter<W: Write>(pub W);
+
+impl<W: Write> Write for GlyphWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(render(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// `println!` through `render`, to stdout or the `--output` file
+macro_rules! outln {
+    () => { $crate::sink::line("") };
+    ($($arg:tt)*) => { $crate::sink::line(&$crate::glyphs::render(&format!($($arg)*))) };
+}
@@ -0,0 +1,182 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use crate::error::{AnalyzerError, Result};
+
+/// Default cap on contract size; larger inputs need `--force-large`
+pub const DEFAULT_MAX_INPUT_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Files above this size are audited in line windows instead of being loaded whole
+pub const STREAMING_THRESHOLD_BYTES: u64 = 1024 * 1024;
+
+/// Largest contract we are willing to send to the AI provider in one prompt
+pub const MAX_AI_INPUT_BYTES: usize = 256 * 1024;
+
+const WINDOW_LINES: usize = 2_000;
+const WINDOW_OVERLAP_LINES: usize = 50;
+
+#[derive(Debug, Clone, Copy)]
+pub struct InputLimits {
+    pub max_bytes: u64,
+    pub force_large: bool,
+}
+
+impl InputLimits {
+    /// Returns the size of `path`, rejecting it if it exceeds the cap and
+    /// `--force-large` was not given.
+    pub fn check(&self, path: &Path) -> Result<u64> {
+        let size = std::fs::metadata(path)?.len();
+        if size > self.max_bytes && !self.force_large {
+            return Err(AnalyzerError::InputTooLarge {
+                path: path.display().to_string(),
+                size,
+                limit: self.max_bytes,
+            });
+        }
+        Ok(size)
+    }
+}
+
+/// Rejects content too large to send to the AI provider
+pub fn check_ai_input(content: &str) -> Result<()> {
+    if content.len() > MAX_AI_INPUT_BYTES {
+        return Err(AnalyzerError::InputTooLarge {
+            path: "<AI prompt>".to_string(),
+            size: content.len() as u64,
+            limit: MAX_AI_INPUT_BYTES as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Reads a file as overlapping windows of lines so static scanners can process
+/// very large inputs with bounded memory. Consecutive windows share
+/// `WINDOW_OVERLAP_LINES` lines so patterns spanning a boundary are not lost.
+/// Each window comes with the 1-based file line it starts at.
+pub struct LineWindows {
+    reader: BufReader<File>,
+    carry: Vec<String>,
+    bytes_read: u64,
+    lines_read: usize,
+    done: bool,
+}
+
+impl LineWindows {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            carry: Vec::new(),
+            bytes_read: 0,
+            lines_read: 0,
+            done: false,
+        })
+    }
+
+    /// Bytes consumed from the file so far, for progress reporting
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+impl Iterator for LineWindows {
+    type Item = Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut lines = std::mem::take(&mut self.carry);
+        let carried = lines.len();
+        let first_line = self.lines_read - carried + 1;
+        let mut line = String::new();
+
+        while lines.len() < WINDOW_LINES {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(n) => {
+                    self.bytes_read += n as u64;
+                    self.lines_read += 1;
+                    lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+
+        // Nothing new beyond the overlap carried from the previous window
+        if lines.len() == carried {
+            return None;
+        }
+
+        if !self.done {
+            self.carry = lines[lines.len().saturating_sub(WINDOW_OVERLAP_LINES)..].to_vec();
+        }
+
+        Some(Ok((first_line, lines.join("\n"))))
+    }
+}
+
+/// Directories never descended into: build output, dependencies, tests and anything hidden
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "tests"];
+
+/// Whether `path` names several files: a directory or a glob pattern
+pub fn is_multi_file(path: &Path) -> bool {
+    path.is_dir() || (!path.exists() && path.to_str().is_some_and(|p| p.contains(['*', '?', '['])))
+}
+
+/// The contract files `path` names: the file itself, every `.sol` and `.rs`
+/// file under a directory, or the files matching a glob such as
+/// `contracts/**/*.rs`. Sorted so output is stable between runs
+pub fn collect_sources(path: &Path) -> Result<Vec<std::path::PathBuf>> {
+    if !is_multi_file(path) {
+        std::fs::metadata(path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        return glob_sources(path);
+    }
+    let mut files = Vec::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry_path = entry?.path();
+            let name = entry_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if entry_path.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name) {
+                    pending.push(entry_path);
+                }
+            } else if matches!(entry_path.extension().and_then(|ext| ext.to_str()), Some("sol" | "rs")) {
+                files.push(entry_path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn glob_sources(pattern: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let pattern = pattern.to_string_lossy();
+    let paths = glob::glob(&pattern)
+        .map_err(|e| AnalyzerError::Config(format!("invalid glob pattern '{}': {}", pattern, e)))?;
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.map_err(|e| AnalyzerError::Io(e.into()))?;
+        let skipped = path.components().any(|component| {
+            let name = component.as_os_str().to_str().unwrap_or_default();
+            SKIPPED_DIRS.contains(&name) || (name.starts_with('.') && name.len() > 1 && name != "..")
+        });
+        let is_source = matches!(path.extension().and_then(|ext| ext.to_str()), Some("sol" | "rs"));
+        if path.is_file() && is_source && !skipped {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
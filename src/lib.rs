@@ -0,0 +1,76 @@
+//! Static and AI-assisted analysis of Arbitrum Stylus and Solidity contracts.
+//!
+//! The `stylus-analyzer` binary is a thin CLI over this crate. Every analysis
+//! returns data: an [`AuditResult`] of [`Vulnerability`] findings for the
+//! rule-based audit, and an [`Analysis`] with structured findings, metrics
+//! and the rendered terminal report for the other [`Analyzer`]s. Nothing here
+//! prints to stdout; progress goes through `tracing`.
+//!
+//! The types re-exported at the crate root are the stable API. The public
+//! modules serve the binary and callers that need more, such as the report
+//! renderers in `audit::report`, and may change between releases; the
+//! individual rules and other internals are private to the crate.
+//!
+//! Audit a source string and get typed findings back, ready to serialize:
+//!
+//! ```
+//! use stylus_analyzer::{AnalysisOptions, ParsedContract, Severity, Vulnerability};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> stylus_analyzer::Result<()> {
+//! let source = r#"
+//! pragma solidity ^0.8.20;
+//! contract Bank {
+//!     mapping(address => uint256) balances;
+//!     function withdraw() external {
+//!         (bool ok, ) = msg.sender.call{value: balances[msg.sender]}("");
+//!         require(ok);
+//!         balances[msg.sender] = 0;
+//!     }
+//! }
+//! "#;
+//! let parsed = ParsedContract::new(source.to_string())?;
+//! assert_eq!(parsed.function_count(), 1);
+//!
+//! let result = AnalysisOptions::new().audit(source).await?;
+//! let findings: Vec<&Vulnerability> = result.vulnerabilities().collect();
+//! let reentrancy = findings.iter().find(|vuln| vuln.name == "Potential Reentrancy").unwrap();
+//! assert_eq!(reentrancy.severity, Severity::High);
+//! assert!(!reentrancy.fingerprint.is_empty());
+//!
+//! let json = serde_json::to_value(&findings).unwrap();
+//! assert!(json.as_array().unwrap().iter().any(|finding| finding["name"] == "Potential Reentrancy"));
+//!
+//! let count = findings.len();
+//! let owned: Vec<Vulnerability> = result.into_vulnerabilities();
+//! assert_eq!(owned.len(), count);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Rules are [`AuditRule`]s; add your own to the [`AuditAnalyzer`] that
+//! [`AnalysisOptions::analyzer`] builds. Set `OPENAI_API_KEY` for the
+//! AI-backed analyzers.
+
+pub mod ai;
+pub mod analyzer;
+pub mod audit;
+pub mod cancel;
+pub mod compare;
+pub mod config;
+pub mod error;
+pub mod input;
+pub mod migrate;
+mod options;
+pub mod parser;
+pub mod report;
+pub mod stats;
+pub mod upgrade;
+
+pub use analyzer::{Analysis, Analyzer};
+pub use audit::{AuditAnalyzer, AuditResult};
+pub use audit::rules::AuditRule;
+pub use audit::vulnerabilities::{Effort, FixExample, Severity, Vulnerability};
+pub use error::{AnalyzerError, Result};
+pub use options::AnalysisOptions;
+pub use parser::ParsedContract;
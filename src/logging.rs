@@ -0,0 +1,34 @@
+//! Diagnostics on stderr through `tracing`. Reports go to stdout with
+//! `println!`; everything about how the run is going goes through here
+
+use tracing_subscriber::EnvFilter;
+use crate::glyphs::GlyphWriter;
+
+/// Installs the stderr subscriber. `RUST_LOG` wins over the flags when set.
+///
+/// Progress is info, rule timings and cache hits are debug, AI request and
+/// response sizes are trace. Commands printing JSON only show warnings by
+/// default so scripts reading both streams get no chatter. `ansi` comes
+/// from `--color`
+pub fn init(verbose: u8, quiet: bool, structured_output: bool, ansi: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => "off",
+        (false, 0) if structured_output => "warn",
+        (false, 0) => "info",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("stylus_analyzer={}", level)));
+
+    // Plain progress lines by default; levels and targets once debugging
+    let detailed = verbose > 0;
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(|| GlyphWriter(std::io::stderr()))
+        .with_ansi(ansi)
+        .without_time()
+        .with_level(detailed)
+        .with_target(detailed)
+        .init();
+}
@@ -1,15 +1,23 @@
-use std::error::Error;
-use clap::Parser;
+use std::path::{Path, PathBuf};
+use clap::{CommandFactory, FromArgMatches, ValueEnum};
+use clap::parser::ValueSource;
+use colored::*;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
+#[macro_use]
+mod glyphs;
 mod cli;
-mod analyzer;
-mod report;
-mod ai;
-mod parser;
-mod audit;
-
-use cli::{Cli, Commands};
-use analyzer::{
+mod completions;
+mod doctor;
+mod logging;
+mod sink;
+mod watch;
+
+use cli::{CacheAction, Cli, Commands, OutputFormat};
+use stylus_analyzer::{ai, audit, cancel, compare, input, migrate, report, stats, upgrade};
+use stylus_analyzer::{AnalysisOptions, AnalyzerError, ParsedContract, Result};
+use stylus_analyzer::analyzer::{
     Analyzer, 
     gas::GasAnalyzer, 
     size::SizeAnalyzer, 
@@ -17,126 +25,647 @@ use analyzer::{
     complexity::ComplexityAnalyzer, 
     interactions::InteractionsAnalyzer,
     quality::QualityAnalyzer,
+    natspec,
+    output::{self, Analysis, SeverityCounts},
 };
-use audit::{AuditAnalyzer, patterns};
+use stylus_analyzer::ai::backend::Provider;
+use stylus_analyzer::ai::cache::ResponseCache;
+use stylus_analyzer::audit::patterns;
+use stylus_analyzer::audit::rules::AuditRule;
+use stylus_analyzer::audit::upgrade_safety::UpgradeSafetyRule;
+use stylus_analyzer::audit::vulnerabilities::Severity;
+use stylus_analyzer::cancel::run_or_cancel;
+use stylus_analyzer::config::{self, Config};
+use stylus_analyzer::input::InputLimits;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
-    let cli = Cli::parse();
+async fn main() {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // Loaded before logging starts because it can set --format; errors are reported with the rest
+    let config = load_config(&mut cli, matches.value_source("format") != Some(ValueSource::CommandLine));
+    cli.color.apply(cli.output.is_some());
+    if matches!(cli.format, OutputFormat::Markdown | OutputFormat::Html) {
+        colored::control::set_override(false);
+    }
+    glyphs::set_ascii(cli.ascii || glyphs::detect_ascii());
+    logging::init(cli.verbose, cli.quiet, cli.structured_output(), cli.color.stderr());
+    if cli.no_ai {
+        ai::disable();
+    }
+    if cli.no_redact {
+        ai::redact::disable();
+    }
+    if cli.no_cache {
+        ai::cache::disable();
+    }
+    ai::cache::set_ttl_hours(cli.cache_ttl);
 
-    match cli.command {
-        Commands::Analyze { file } => {
-            println!("Analyzing gas usage for file: {}", file.display());
-            let analyzer = GasAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
+    let cancel = CancellationToken::new();
+    cancel::install_ctrl_c_handler(cancel.clone());
+
+    // A watch ends with Ctrl-C and reports every run as it goes, so neither fails it
+    let watching = cli.watch;
+    let fail_on = cli.command.fail_on().filter(|_| !watching);
+    let ready = config.and_then(|config| {
+        apply_config(&cli, &config)?;
+        if let Some(path) = &cli.output {
+            sink::to_file(path, cli.force)?;
         }
-        Commands::Audit { file } => {
-            println!("Performing security audit for file: {}", file.display());
+        Ok(config)
+    });
+    let result = match ready {
+        Ok(config) if watching => run_watch(cli, &config, &cancel).await,
+        Ok(config) => run(cli, &config, &cancel).await,
+        Err(e) => Err(e),
+    };
+    let result = match result {
+        Ok(_) if cancel.is_cancelled() && !watching => Err(AnalyzerError::Interrupted),
+        Ok(counts) => match fail_on.map(|threshold| (counts.at_least(threshold), threshold)) {
+            Some((count, threshold)) if count > 0 => Err(AnalyzerError::Findings { count, threshold }),
+            _ => Ok(counts),
+        },
+        other => other,
+    };
+    // The report is kept when the run found issues or was interrupted, like on stdout
+    let result = match sink::finish(result.is_ok()) {
+        Ok(Some(path)) => {
+            info!("📝 Report written to {}", path.display());
+            result
+        }
+        Ok(None) => result,
+        Err(e) => result.and(Err(e)),
+    };
 
-            // Run comprehensive security audit
-            let analyzer = AuditAnalyzer::new();
-            for rule in patterns::create_default_rules() {
-                analyzer.add_rule(rule);
-            }
+    if let Err(e) = result {
+        print_error(&e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn print_error(e: &AnalyzerError) {
+    eprintln!("{}", glyphs::render(&format!("{} {}", "❌ Error:".red().bold(), e)));
+    if let Some(hint) = e.hint() {
+        eprintln!("   {}", glyphs::render(hint));
+    }
+}
+
+/// `--watch`: runs the command, then again after every save until Ctrl-C. A
+/// run that fails, say on a half-written file that doesn't parse, is reported
+/// and the next save tries again; only setup errors end the watch
+async fn run_watch(cli: Cli, config: &Config, cancel: &CancellationToken) -> Result<SeverityCounts> {
+    if !cli.command.watchable() {
+        return Err(AnalyzerError::Config(
+            "--watch re-runs analyze, audit, size, secure, report, complexity, interactions and quality, without --fix or --triage".to_string()
+        ));
+    }
+    if cli.output.is_some() {
+        return Err(AnalyzerError::Config("--watch prints every run as it happens; drop --output".to_string()));
+    }
+    let mut command = cli.command.clone();
+    let Some(target) = command.target_mut().cloned() else { return Ok(SeverityCounts::default()) };
+    let mut watcher = watch::Watcher::new(&target)?;
+    info!("🔍 Watching {} for changes; press Ctrl-C to stop", target.display());
+    if cli.command.uses_ai() && ai::enabled() {
+        info!("💡 Each save with changes is a new AI request (unchanged contracts come from the cache); pass --no-ai to run only the static analyses");
+    }
+
+    let text = cli.format == OutputFormat::Text;
+    let mut counts = SeverityCounts::default();
+    for runs in 1.. {
+        if text {
+            outln!("\n{}", watch::delimiter(runs).cyan().bold());
+            outln!("{}", "═".repeat(60).cyan());
+        } else {
+            info!("{}", watch::delimiter(runs));
+        }
+        match Box::pin(run(cli.clone(), config, cancel)).await {
+            Ok(run_counts) => counts = run_counts,
+            // Saving the file again can't fix these
+            Err(e @ (AnalyzerError::Config(_) | AnalyzerError::MissingApiKey)) => return Err(e),
+            Err(e) => print_error(&e),
+        }
+        tokio::select! {
+            changed = watcher.changed(config) => changed?,
+            _ = cancel.cancelled() => break,
+        }
+    }
+    info!("✅ Stopped watching {}", target.display());
+    Ok(counts)
+}
+
+/// Runs a single-analyzer command, printing its output unless cancelled first.
+/// JSON output is printed either way so scripts always get a document
+async fn run_single(analyzer: &dyn Analyzer, command: &str, file: &Path, format: OutputFormat, cancel: &CancellationToken) -> Result<SeverityCounts> {
+    let analysis = run_or_cancel(cancel, analyzer.analyze(file)).await.transpose()?;
+    let counts = analysis.as_ref().map(|analysis| SeverityCounts::of(&analysis.findings)).unwrap_or_default();
+    match (format, analysis) {
+        (OutputFormat::Json, analysis) => {
+            let interrupted = analysis.is_none();
+            sink::line(&output::to_json(command, file, analysis.as_slice(), interrupted)?);
+        }
+        // Markdown and HTML are refused before single-analyzer commands run
+        (_, Some(analysis)) => outln!("{}", analysis.text),
+        (_, None) => {}
+    }
+    Ok(counts)
+}
+
+/// Runs the command once per file in `files`, each under its own heading,
+/// then prints the findings per severity across all of them. A file that
+/// fails doesn't stop the others; the first failure is returned at the end
+async fn run_many(cli: Cli, config: &Config, files: Vec<PathBuf>, cancel: &CancellationToken) -> Result<SeverityCounts> {
+    let text = cli.format == OutputFormat::Text;
+    let mut results = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        if text {
+            outln!("\n{}", format!("📄 {} ({}/{})", file.display(), i + 1, files.len()).cyan().bold());
+            outln!("{}", "═".repeat(60).cyan());
+        }
+        let mut per_file = cli.clone();
+        if let Some(target) = per_file.command.target_mut() {
+            *target = file.clone();
+        }
+        let result = Box::pin(run(per_file, config, cancel)).await;
+        if let Err(e) = &result {
+            warn!("❌ {}: {}", file.display(), e);
+        }
+        results.push((file.clone(), result));
+    }
+
+    let mut total = SeverityCounts::default();
+    let mut failures = Vec::new();
+    for (file, result) in results.iter() {
+        match result {
+            Ok(counts) => total.add(*counts),
+            Err(e) => failures.push((file, e)),
+        }
+    }
+    if text {
+        outln!("{}", report::format_multi_file_summary(&results, files.len(), &total));
+    } else {
+        info!("📊 {} file(s): {} critical, {} high, {} medium, {} low, {} failed",
+            results.len(), total.critical, total.high, total.medium, total.low, failures.len());
+    }
+    match results.into_iter().find_map(|(_, result)| result.err()) {
+        Some(e) => Err(e),
+        None => Ok(total),
+    }
+}
 
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
+/// The AI sections `report` adds after the analyzers, as (JSON key, title,
+/// text); sections cut short by `cancel` come back empty
+async fn ai_report_sections(content: &str, cancel: &CancellationToken) -> Result<Vec<(&'static str, &'static str, String)>> {
+    let stylus_analysis = run_or_cancel(cancel, ai::analyze_stylus_patterns(content)).await.transpose()?.unwrap_or_default();
+    let error_analysis = run_or_cancel(cancel, ai::analyze_error_patterns(content)).await.transpose()?.unwrap_or_default();
+    let quality_analysis = run_or_cancel(cancel, ai::analyze_code_quality(content)).await.transpose()?.unwrap_or_default();
+    Ok(vec![
+        ("stylus_patterns", "Stylus-Specific Analysis", stylus_analysis),
+        ("error_handling", "Error Handling Analysis", error_analysis),
+        ("code_quality", "Code Quality Analysis", quality_analysis),
+    ])
+}
 
-            // Run specialized analyses
-            let gas_analysis = GasAnalyzer.analyze(&file).await?;
-            let security_analysis = SecurityAnalyzer.analyze(&file).await?;
-            let interaction_analysis = InteractionsAnalyzer.analyze(&file).await?;
+/// The config file for the analyzed path. Its `format` replaces `--format`
+/// unless the flag was given (`format_is_default`) or the command can't
+/// print that format
+fn load_config(cli: &mut Cli, format_is_default: bool) -> Result<Config> {
+    let config = Config::discover(cli.command.config_start())?;
+    if let (Some(format), true) = (&config.format, format_is_default) {
+        let format = OutputFormat::from_str(format, true).map_err(|_| AnalyzerError::Config(format!(
+            "format = \"{}\" in the config file is not one of text, json, markdown, html", format
+        )))?;
+        let supported = match format {
+            OutputFormat::Text => true,
+            OutputFormat::Json => cli.command.supports_json(),
+            OutputFormat::Markdown | OutputFormat::Html => cli.command.supports_documents(),
+        };
+        if supported {
+            cli.format = format;
+        }
+    }
+    Ok(config)
+}
+
+/// Settings the flags and environment leave to the config file: the AI
+/// provider, model and endpoint. Which audit rules run is up to each
+/// command's `AnalysisOptions`
+fn apply_config(cli: &Cli, config: &Config) -> Result<()> {
+    let from_env = std::env::var("STYLUS_ANALYZER_PROVIDER").ok();
+    ai::set_provider(Provider::select(cli.provider, from_env.as_deref(), config.provider)?);
+    if let Some(model) = cli.model.as_deref().or(config.model.as_deref()) {
+        ai::set_model(model);
+    }
+    if let Some(url) = &config.base_url {
+        ai::set_base_url(url);
+    }
+    Ok(())
+}
+
+/// Audit settings from the config file and the flags every auditing command
+/// shares; `audit` adds its own
+fn analysis_options(cli: &Cli, config: &Config, cancel: &CancellationToken) -> AnalysisOptions {
+    AnalysisOptions::new()
+        .rules(config.rules.clone())
+        .severity_overrides(&cli.severity_override)
+        .cancellation(cancel.clone())
+}
+
+async fn run(cli: Cli, config: &Config, cancel: &CancellationToken) -> Result<SeverityCounts> {
+    let limits = InputLimits {
+        max_bytes: cli.max_input_size,
+        force_large: cli.force_large,
+    };
+    let format = cli.format;
+    let force = cli.force;
+    if format == OutputFormat::Json && !cli.command.supports_json() {
+        return Err(AnalyzerError::Config("this command only prints text; drop --format json".to_string()));
+    }
+    if matches!(format, OutputFormat::Markdown | OutputFormat::Html) && !cli.command.supports_documents() {
+        return Err(AnalyzerError::Config("only report and audit print Markdown or HTML; use --format text or json".to_string()));
+    }
+    // Fail before any parsing, not halfway through the first analyzer
+    if cli.command.uses_ai() && ai::enabled() && ai::provider().needs_api_key() {
+        ai::api_key()?;
+    }
+    let mut command = cli.command.clone();
+    let single_file = command.single_file_options();
+    if let Some(target) = command.target_mut().filter(|target| input::is_multi_file(target)) {
+        if !single_file.is_empty() {
+            return Err(AnalyzerError::Config(format!(
+                "{} can't be combined with a directory or glob; audit the files one at a time instead", single_file.join(", ")
+            )));
+        }
+        let files: Vec<PathBuf> = input::collect_sources(target)?.into_iter()
+            .filter(|file| !config.is_ignored(file))
+            .collect();
+        if files.is_empty() {
+            return Err(AnalyzerError::Config(format!(
+                "no .sol or .rs files in {} (target/, tests/, node_modules/, hidden directories and `ignore` globs in the config file are skipped)", target.display()
+            )));
+        }
+        return run_many(cli, config, files, cancel).await;
+    }
+    let mut counts = SeverityCounts::default();
+    for file in cli.command.input_files() {
+        limits.check(file)?;
+    }
 
-            // Consolidated Analysis Section
-            if !gas_analysis.is_empty() || !security_analysis.is_empty() || !interaction_analysis.is_empty() {
-                println!("\nAdditional Analysis");
-                println!("═══════════════════");
+    let options = analysis_options(&cli, config, cancel);
+    match cli.command {
+        Commands::Analyze { file } => {
+            info!("Analyzing gas usage for file: {}", file.display());
+            counts = run_single(&GasAnalyzer, "analyze", &file, format, cancel).await?;
+        }
+        Commands::Audit {
+            file, strict_rules, baseline, update_baseline, diff, fail_on_new, effort, import_db, export_db, standard, plugin,
+            custom_rules, only_rules, skip_rules, no_dedupe, fix, apply, triage, sarif, timings, min_confidence, ..
+        } => {
+            let options = plugin.into_iter()
+                .fold(options, AnalysisOptions::plugin)
+                .custom_rules(custom_rules)
+                .only_rules(only_rules)
+                .skip_rules(skip_rules)
+                .dedupe(!no_dedupe)
+                .ai(ai::enabled())
+                .effort(effort)
+                .standard(standard);
+            let request = audit::command::AuditRequest {
+                baseline, update_baseline, diff, fail_on_new, import_db, export_db, triage, sarif, fix, apply, timings, min_confidence,
+            };
+            let mut run = audit::command::run(&options, &file, &request).await?;
 
-                if !gas_analysis.is_empty() {
-                    println!("\nGas & Resource Usage:");
-                    println!("• Block Space: High");
-                    println!("• Message Cost: Medium");
-                    println!("• Data Posting: Low");
-                    println!("• Batch Processing: High");
+            match format {
+                OutputFormat::Text => outln!("{}", audit::report::generate_full_report(&run.result)),
+                OutputFormat::Markdown => sink::line(&audit::report::format_markdown(&run.result, &file)),
+                OutputFormat::Json | OutputFormat::Html => {}
+            }
+            if let Some(fixed) = &run.fix {
+                if let Some(diff) = &fixed.diff {
+                    outln!("\n{}", diff);
                 }
+                match fixed.remaining.as_deref() {
+                    Some([]) => outln!("{}", "✅ Re-audit: every fixed finding is gone".green()),
+                    Some(remaining) => outln!("{}", format!("⚠️  Re-audit still reports: {}", remaining.join(", ")).yellow()),
+                    None => {}
+                }
+                outln!("{}", audit::fix::format_plan(&fixed.plan, &fixed.applied));
+            }
+            if let Some(db_path) = &request.triage {
+                let mut output = glyphs::GlyphWriter(std::io::stdout());
+                if let Some(outcome) = run.triage(db_path, request.baseline.as_deref(), &mut std::io::stdin().lock(), &mut output)? {
+                    outln!("{}", audit::triage::format_outcome(&outcome, db_path));
+                }
+            }
 
-                if !security_analysis.is_empty() {
-                    println!("\nSecurity Context:");
-                    println!("• Memory Safety: Strong");
-                    println!("• Access Control: Medium");
-                    println!("• State Management: Good");
-                    println!("• Runtime Safety: Strong");
+            counts = SeverityCounts::count(run.result.vulnerabilities().map(|vuln| vuln.severity));
+            if let (Some((blocking, threshold)), Some(summary), OutputFormat::Text) = (&run.blocking, &run.result.baseline, format) {
+                outln!("{}", audit::report::format_blocking_findings(blocking, *threshold, summary));
+            }
+            match format {
+                OutputFormat::Text => {
+                    if let Some(additional) = audit::report::format_additional_analysis(&run.analyses) {
+                        outln!("{}", additional);
+                    }
                 }
+                OutputFormat::Json => sink::line(&output::to_json("audit", &file, &run.analyses, run.interrupted)?),
+                OutputFormat::Html => sink::line(&report::html::format_html("audit", &file, &run.analyses, run.interrupted)?),
+                OutputFormat::Markdown => {}
+            }
 
-                if !interaction_analysis.is_empty() {
-                    println!("\nContract Behavior:");
-                    println!("• External Calls: Safe");
-                    println!("• Dependencies: Low");
-                    println!("• Event Handling: Good");
-                    println!("• Upgrade Safety: High");
+            if strict_rules {
+                run.result.require_all_rules()?;
+            }
+            if let Some((blocking, threshold)) = &run.blocking {
+                if !blocking.is_empty() {
+                    return Err(AnalyzerError::Findings { count: blocking.len(), threshold: *threshold });
                 }
             }
         }
         Commands::Size { file } => {
-            println!("Analyzing contract size for file: {}", file.display());
-            let analyzer = SizeAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
-        }
-        Commands::Secure { file } => {
-            println!("Performing security analysis for file: {}", file.display());
-            let analyzer = SecurityAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
-        }
-        Commands::Report { file } => {
-            println!("Generating report for file: {}", file.display());
-            let content = std::fs::read_to_string(&file)?;
-            let report = report::generate_full_report(&file).await?;
+            info!("Analyzing contract size for file: {}", file.display());
+            counts = run_single(&SizeAnalyzer, "size", &file, format, cancel).await?;
+        }
+        Commands::Secure { file, .. } => {
+            info!("Performing security analysis for file: {}", file.display());
+            counts = run_single(&SecurityAnalyzer, "secure", &file, format, cancel).await?;
+        }
+        Commands::Report { file, jobs } => {
+            if jobs == 0 {
+                return Err(AnalyzerError::Config("--jobs must be at least 1".to_string()));
+            }
+            info!("Generating report for file: {}", file.display());
+            let contract = ParsedContract::new(std::fs::read_to_string(&file)?)?;
+            let mut report = report::generate_full_report(&contract, &file, jobs, cancel).await?;
 
-            println!("{}", report);
+            if format == OutputFormat::Text {
+                outln!("{}", report.text);
+            }
 
             // Show additional analyses only if they have findings
-            let stylus_analysis = ai::analyze_stylus_patterns(&content).await?;
-            let error_analysis = ai::analyze_error_patterns(&content).await?;
-            let quality_analysis = ai::analyze_code_quality(&content).await?;
-
-            if !stylus_analysis.is_empty() {
-                println!("\nStylus-Specific Analysis:\n{}", stylus_analysis);
+            let sections = if ai::enabled() { ai_report_sections(&contract.source, cancel).await? } else { Vec::new() };
+            for analysis in &report.analyses {
+                counts.add(SeverityCounts::of(&analysis.findings));
             }
-            if !error_analysis.is_empty() {
-                println!("\nError Handling Analysis:\n{}", error_analysis);
+            match format {
+                OutputFormat::Json | OutputFormat::Html => {
+                    for (analyzer, _, text) in sections.iter().filter(|(_, _, text)| !text.is_empty()) {
+                        report.analyses.push(Analysis::new(analyzer, String::new()).with_findings(output::findings_from_text(text)));
+                    }
+                    let interrupted = report.interrupted || cancel.is_cancelled();
+                    if format == OutputFormat::Json {
+                        sink::line(&output::to_json("report", &file, &report.analyses, interrupted)?);
+                    } else {
+                        sink::line(&report::html::format_html("report", &file, &report.analyses, interrupted)?);
+                    }
+                }
+                OutputFormat::Text => {
+                    for (_, title, text) in sections.iter().filter(|(_, _, text)| !text.is_empty()) {
+                        outln!("\n{}:\n{}", title, text);
+                    }
+                }
+                OutputFormat::Markdown => {
+                    for (_, title, text) in sections.into_iter().filter(|(_, _, text)| !text.is_empty()) {
+                        let findings = output::findings_from_text(&text);
+                        report.sections.push(report::Section { title, text, findings: Some(findings) });
+                    }
+                    sink::line(&report::markdown::format_markdown(&report, &file));
+                }
             }
-            if !quality_analysis.is_empty() {
-                println!("\nCode Quality Analysis:\n{}", quality_analysis);
+        }
+        Commands::Upgrade { files, layout_compare: true } => {
+            let [old, new] = files.as_slice() else {
+                return Err(AnalyzerError::Config("--layout-compare takes exactly two files: OLD NEW".to_string()));
+            };
+            info!("Comparing storage layouts {} → {}", old.display(), new.display());
+            let old_layout = upgrade::layout::extract_layout(&std::fs::read_to_string(old)?)?;
+            let new_layout = upgrade::layout::extract_layout(&std::fs::read_to_string(new)?)?;
+            let diff = upgrade::layout::compare_layouts(&old_layout, &new_layout);
+            outln!("{}", upgrade::layout::format_layout_diff(&diff, &old_layout, &new_layout));
+        }
+        Commands::UpgradeCheck { old, new } => {
+            let old_layout = upgrade::layout::extract_layout(&std::fs::read_to_string(&old)?)?;
+            let new_layout = upgrade::layout::extract_layout(&std::fs::read_to_string(&new)?)?;
+            let diff = upgrade::layout::compare_layouts(&old_layout, &new_layout);
+            match format {
+                OutputFormat::Json => outln!("{}", upgrade::layout::format_layout_diff_json(&diff)?),
+                _ => outln!("{}", upgrade::layout::format_layout_table(&diff, &old_layout, &new_layout)),
+            }
+            let count = diff.rows.iter().filter(|row| row.verdict.breaking()).count();
+            if count > 0 {
+                return Err(AnalyzerError::Findings { count, threshold: Severity::Critical });
             }
         }
-        Commands::Upgrade { file } => {
-            println!("Analyzing upgrade patterns for file: {}", file.display());
-            let content = std::fs::read_to_string(&file)?;
-            let analysis = ai::analyze_upgrade_patterns(&content).await?;
-            println!("{}", analysis);
+        Commands::Upgrade { files, layout_compare: false } => {
+            let mut sources = Vec::new();
+            let mut reports = Vec::new();
+            for file in &files {
+                info!("Analyzing upgrade patterns for file: {}", file.display());
+                let content = std::fs::read_to_string(file)?;
+                let mut report = upgrade::proxy::analyze_source(&content, &file.display().to_string())?;
+                // Hard findings next to the AI's, so the report says something with --no-ai too
+                for finding in UpgradeSafetyRule.check(&content).await? {
+                    if !report.findings.iter().any(|f| f.name == finding.name && f.line == finding.line) {
+                        report.findings.push(finding);
+                    }
+                }
+                reports.push(report);
+                sources.push((file.display().to_string(), content));
+            }
+            let clashes = if sources.len() > 1 {
+                upgrade::proxy::selector_clashes(&sources)?
+            } else {
+                Vec::new()
+            };
+            outln!("{}", upgrade::proxy::format_proxy_report(&reports, &clashes));
+
+            for (_, content) in sources.iter().filter(|_| ai::enabled()) {
+                if let Some(analysis) = run_or_cancel(cancel, ai::analyze_upgrade_patterns(content)).await {
+                    outln!("{}", analysis?);
+                }
+            }
         }
         Commands::Complexity { file } => {
-            println!("Analyzing function complexity for file: {}", file.display());
-            let analyzer = ComplexityAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
+            info!("Analyzing function complexity for file: {}", file.display());
+            counts = run_single(&ComplexityAnalyzer, "complexity", &file, format, cancel).await?;
         }
         Commands::Interactions { file } => {
-            println!("Analyzing cross-contract interactions for file: {}", file.display());
-            let analyzer = InteractionsAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
+            info!("Analyzing cross-contract interactions for file: {}", file.display());
+            counts = run_single(&InteractionsAnalyzer, "interactions", &file, format, cancel).await?;
+        }
+        Commands::Quality { file, min_doc_coverage } => {
+            if let Some(min) = min_doc_coverage {
+                if !(0.0..=100.0).contains(&min) {
+                    return Err(AnalyzerError::Config(format!("--min-doc-coverage must be between 0 and 100, got {}", min)));
+                }
+                let parsed = ParsedContract::new(std::fs::read_to_string(&file)?)?;
+                let coverage = natspec::doc_coverage(&parsed).ok_or_else(|| {
+                    AnalyzerError::Config("--min-doc-coverage only applies to Solidity contracts".to_string())
+                })?;
+                // Gate before the AI analysis so CI doesn't pay for a run that fails anyway
+                if coverage.percent() < min {
+                    match format {
+                        OutputFormat::Json => {
+                            let analysis = Analysis::new("quality", String::new()).with_metric("doc_coverage_percent", coverage.percent());
+                            sink::line(&output::to_json("quality", &file, &[analysis], false)?);
+                        }
+                        _ => outln!("{}", natspec::format_doc_coverage(&coverage)),
+                    }
+                    return Err(AnalyzerError::DocCoverage { coverage: coverage.percent(), min });
+                }
+            }
+            info!("Analyzing code quality metrics for file: {}", file.display());
+            counts = run_single(&QualityAnalyzer, "quality", &file, format, cancel).await?;
+        }
+        Commands::Fix { file, finding, apply } => {
+            if !ai::enabled() {
+                return Err(AnalyzerError::Config("fix asks the AI model for a rewrite, so it can't run with --no-ai; `audit --fix` applies the mechanical fixes offline".to_string()));
+            }
+            let path = file.display().to_string();
+            let source = std::fs::read_to_string(&file)?;
+            let result = AnalysisOptions::new().cancellation(cancel.clone()).analyzer()?.audit_source(&source, &path).await?;
+            let Some(vuln) = ai::fix::select(&result, &finding) else {
+                outln!("Findings in {}:\n{}", path, ai::fix::format_choices(&result));
+                return Err(AnalyzerError::Config(format!("no single finding matches '{}'; pick a number or fingerprint from the list above", finding)));
+            };
+            let target = ai::fix::enclosing_function(&source, vuln).ok_or_else(|| AnalyzerError::Config(
+                format!("{} isn't tied to a function, so there is nothing to rewrite", vuln.name)))?;
+
+            info!("🤖 Asking for a fix of {} in `{}` (lines {}-{})...", vuln.name, target.name, target.lines.0, target.lines.1);
+            let response = ai::complete(&ai::fix::prompt(vuln, &source, &target)).await?;
+            let edit = ai::fix::validate(vuln, &source, &target, &response).await?;
+            outln!("{}", "✅ The fix parses and the finding no longer fires".green());
+
+            if apply {
+                let mut fixed = source.clone();
+                fixed.replace_range(edit.start..edit.end, &edit.replacement);
+                std::fs::write(&file, fixed)?;
+                info!("✍️  Fix written to {}", path);
+            } else {
+                outln!("\n{}", audit::fix::unified_diff(&path, &source, &[edit]));
+            }
+        }
+        Commands::Migrate { file, write } => {
+            let content = std::fs::read_to_string(&file)?;
+            let plans = migrate::plan_migration(&content)?;
+            match write {
+                Some(target) => {
+                    let written = migrate::write_scaffold(&plans, &file, target)?;
+                    info!("📝 Scaffold written to {}", written.display());
+                }
+                None => outln!("{}", migrate::format_markdown(&plans, &file)),
+            }
+            outln!("{}", migrate::format_summary(&plans));
+        }
+        Commands::Layout { file, json } => {
+            let json = json || format == OutputFormat::Json;
+            let layout = upgrade::layout::extract_layout(&std::fs::read_to_string(&file)?)?;
+            if json {
+                outln!("{}", upgrade::layout::format_layout_json(&layout)?);
+            } else {
+                outln!("{}", upgrade::layout::format_layout(&layout));
+            }
+        }
+        Commands::Compare { old, new, abi, json, fail_on_new } => {
+            let json = json || format == OutputFormat::Json;
+            if !json {
+                info!("Comparing {} → {}", old.display(), new.display());
+            }
+            if abi {
+                let diff = compare::compare_abi(&old, &new)?;
+                if json {
+                    outln!("{}", compare::abi::format_abi_json(&diff)?);
+                } else {
+                    outln!("{}", compare::abi::format_abi_diff(&diff));
+                }
+                return Ok(counts);
+            }
+            let diff = compare::compare_contracts(&old, &new, &options).await?;
+            if json {
+                outln!("{}", compare::format_json(&diff)?);
+            } else {
+                outln!("{}", compare::format_diff(&diff));
+            }
+            if let Some(threshold) = fail_on_new {
+                let count = diff.findings_introduced.iter().filter(|vuln| vuln.severity.at_least(threshold)).count();
+                if count > 0 {
+                    return Err(AnalyzerError::Findings { count, threshold });
+                }
+            }
+        }
+        Commands::Rules { json } => {
+            let rules = patterns::rule_list(&config.rule_selection()?);
+            if json || format == OutputFormat::Json {
+                outln!("{}", patterns::format_rule_list_json(&rules)?);
+            } else {
+                outln!("{}", patterns::format_rule_list(&rules));
+            }
+        }
+        Commands::Completions { shell, install: false } => {
+            completions::generate(shell, &mut sink::Out);
         }
-        Commands::Quality { file } => {
-            println!("Analyzing code quality metrics for file: {}", file.display());
-            let analyzer = QualityAnalyzer;
-            let analysis = analyzer.analyze(&file).await?;
-            println!("{}", analysis);
+        Commands::Completions { shell, install: true } => {
+            let path = completions::install(shell)?;
+            info!("📝 {} completions written to {}", shell, path.display());
+            if shell == clap_complete::Shell::Zsh {
+                info!("   Add `fpath+=~/.zfunc` before `compinit` in ~/.zshrc to load them");
+            }
+        }
+        Commands::Doctor { offline } => {
+            let checks = doctor::run(offline || !ai::enabled()).await;
+            outln!("{}", doctor::format_checks(&checks));
+            let failed = doctor::failures(&checks);
+            if failed > 0 {
+                return Err(AnalyzerError::Doctor { failed });
+            }
+        }
+        Commands::Stats { path } => {
+            let mut files = input::collect_sources(&path)?;
+            // A file named on the command line is analyzed even if ignored
+            if input::is_multi_file(&path) {
+                files.retain(|file| !config.is_ignored(file));
+            }
+            if files.is_empty() {
+                return Err(AnalyzerError::Config(format!("no .sol or .rs files under {}", path.display())));
+            }
+            info!("📊 Counting {} file(s)...", files.len());
+            let stats = stats::Stats::collect(&files);
+            match format {
+                OutputFormat::Json => sink::line(&stats::format_json(&stats)?),
+                _ => outln!("{}", stats::format_stats(&stats)),
+            }
+            if !stats.errors.is_empty() {
+                return Err(AnalyzerError::Parse(format!("{} of {} files failed to parse", stats.errors.len(), files.len())));
+            }
+        }
+        Commands::Init => {
+            let path = Path::new(config::PROJECT_FILE);
+            if path.exists() && !force {
+                return Err(AnalyzerError::Config(format!("{} already exists; pass --force to overwrite it", path.display())));
+            }
+            std::fs::write(path, config::template())?;
+            info!("📝 Wrote {}; uncomment the settings you want", path.display());
+        }
+        Commands::Cache { action: CacheAction::Clear } => {
+            let dir = ai::cache::default_dir()
+                .ok_or_else(|| AnalyzerError::Config("HOME is not set; cannot find the cache".to_string()))?;
+            let removed = ResponseCache::new(&dir, 0).clear()?;
+            info!("🗂️  Removed {} cached AI answer(s) from {}", removed, dir.display());
+        }
+        Commands::Man { out_dir, install } => {
+            let dir = if install { Some(completions::man_dir()?) } else { out_dir };
+            match dir {
+                Some(dir) => {
+                    let written = completions::man_pages(&dir)?;
+                    info!("📝 {} man pages written to {}", written.len(), dir.display());
+                }
+                None => completions::man(&mut sink::Out)?,
+            }
         }
     }
 
-    Ok(())
+    Ok(counts)
 }
\ No newline at end of file
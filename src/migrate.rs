@@ -0,0 +1,518 @@
+use std::path::{Path, PathBuf};
+use colored::*;
+use solang_parser::pt::{
+    ContractDefinition, ContractPart, ContractTy, Expression, FunctionAttribute,
+    FunctionDefinition, FunctionTy, Mutability, ParameterList, SourceUnitPart,
+    Statement, Type, VariableAttribute, Visibility,
+};
+use crate::audit::source::{line_of, slice};
+use crate::error::{AnalyzerError, Result};
+
+/// Solidity-to-Stylus migration plan for one contract
+#[derive(Debug, Default)]
+pub struct MigrationPlan {
+    pub contract: String,
+    pub storage: Vec<Mapping>,
+    pub events: Vec<Mapping>,
+    pub errors: Vec<Mapping>,
+    pub functions: Vec<FunctionSkeleton>,
+    /// Constructs with no direct Stylus equivalent that need manual porting
+    pub manual: Vec<ManualItem>,
+}
+
+/// A Solidity declaration next to its Stylus counterpart
+#[derive(Debug)]
+pub struct Mapping {
+    pub solidity: String,
+    pub stylus: String,
+}
+
+#[derive(Debug)]
+pub struct FunctionSkeleton {
+    pub name: String,
+    pub solidity: String,
+    pub rust_signature: String,
+    /// Goes in the `#[public]` impl block; internal/private helpers don't
+    pub external: bool,
+    pub payable: bool,
+}
+
+#[derive(Debug)]
+pub struct ManualItem {
+    pub line: usize,
+    pub construct: String,
+    pub note: String,
+}
+
+/// Parses a Solidity file and builds a migration plan for every contract in it
+pub fn plan_migration(source: &str) -> Result<Vec<MigrationPlan>> {
+    let (unit, _) = solang_parser::parse(source, 0).map_err(|diagnostics| {
+        let first = diagnostics.first().map(|d| d.message.clone()).unwrap_or_default();
+        AnalyzerError::Parse(format!("migrate expects a Solidity source file: {}", first))
+    })?;
+
+    let mut plans = Vec::new();
+    for part in unit.0 {
+        if let SourceUnitPart::ContractDefinition(contract) = part {
+            plans.push(plan_contract(&contract, source));
+        }
+    }
+
+    if plans.is_empty() {
+        return Err(AnalyzerError::Parse("no contract definitions found".to_string()));
+    }
+    Ok(plans)
+}
+
+fn plan_contract(contract: &ContractDefinition, source: &str) -> MigrationPlan {
+    let mut plan = MigrationPlan {
+        contract: contract.name.as_ref().map(|n| n.name.clone()).unwrap_or_else(|| "Contract".to_string()),
+        ..Default::default()
+    };
+
+    match contract.ty {
+        ContractTy::Interface(_) => plan.manual.push(ManualItem {
+            line: line_of(&contract.loc, source),
+            construct: format!("interface {}", plan.contract),
+            note: "Declare it with `sol_interface!` and call it through the generated type".to_string(),
+        }),
+        ContractTy::Library(_) => plan.manual.push(ManualItem {
+            line: line_of(&contract.loc, source),
+            construct: format!("library {}", plan.contract),
+            note: "Port to a plain Rust module; `using ... for` has no equivalent".to_string(),
+        }),
+        _ => {}
+    }
+
+    if !contract.base.is_empty() {
+        let bases = contract.base.iter().map(|b| b.name.to_string()).collect::<Vec<_>>().join(", ");
+        plan.manual.push(ManualItem {
+            line: line_of(&contract.loc, source),
+            construct: format!("inheritance from {}", bases),
+            note: "Compose the parent storage with `#[borrow]` and `#[inherit]` instead".to_string(),
+        });
+    }
+
+    for part in &contract.parts {
+        match part {
+            ContractPart::VariableDefinition(var) => {
+                let name = var.name.as_ref().map(|n| n.name.clone()).unwrap_or_default();
+                let constant = var.attrs.iter().any(|a| matches!(a, VariableAttribute::Constant(_) | VariableAttribute::Immutable(_)));
+                if constant {
+                    plan.manual.push(ManualItem {
+                        line: line_of(&var.loc, source),
+                        construct: format!("constant/immutable {}", name),
+                        note: format!("Use a Rust `const {}: {}` (immutables need an initializer)",
+                            name.to_uppercase(), abi_type(&var.ty)),
+                    });
+                    continue;
+                }
+                plan.storage.push(Mapping {
+                    stylus: format!("{}: {}", to_snake_case(&name), storage_type(&var.ty)),
+                    solidity: format!("{} {}", var.ty, name),
+                });
+            }
+            ContractPart::EventDefinition(event) => {
+                // `sol!` takes Solidity event syntax verbatim
+                plan.events.push(Mapping { solidity: event.to_string(), stylus: event.to_string() });
+            }
+            ContractPart::ErrorDefinition(error) => {
+                plan.errors.push(Mapping { solidity: error.to_string(), stylus: error.to_string() });
+            }
+            ContractPart::StructDefinition(def) => {
+                let name = def.name.as_ref().map(|n| n.name.clone()).unwrap_or_default();
+                plan.manual.push(ManualItem {
+                    line: line_of(&def.loc, source),
+                    construct: format!("struct {}", name),
+                    note: "Stored structs become `#[storage]` structs with Storage* fields; ABI structs go in `sol!`".to_string(),
+                });
+            }
+            ContractPart::FunctionDefinition(func) => plan_function(&mut plan, func, source),
+            _ => {}
+        }
+    }
+
+    plan
+}
+
+fn plan_function(plan: &mut MigrationPlan, func: &FunctionDefinition, source: &str) {
+    let name = func.name.as_ref().map(|n| n.name.clone()).unwrap_or_default();
+    let line = line_of(&func.loc, source);
+
+    match func.ty {
+        FunctionTy::Constructor => plan.manual.push(ManualItem {
+            line,
+            construct: "constructor".to_string(),
+            note: "Move the setup into an `init` method that can only run once".to_string(),
+        }),
+        FunctionTy::Modifier => plan.manual.push(ManualItem {
+            line,
+            construct: format!("modifier {}", name),
+            note: "Turn it into a helper returning `Result` and call it at the top of each guarded function".to_string(),
+        }),
+        FunctionTy::Fallback | FunctionTy::Receive => plan.manual.push(ManualItem {
+            line,
+            construct: format!("{:?}", func.ty).to_lowercase(),
+            note: "Needs a `#[fallback]`/`#[receive]` handler; check your stylus-sdk version supports it".to_string(),
+        }),
+        FunctionTy::Function => {
+            let mut visibility = None;
+            let mut mutability = None;
+            for attr in &func.attributes {
+                match attr {
+                    FunctionAttribute::Visibility(v) => visibility = Some(v.clone()),
+                    FunctionAttribute::Mutability(m) => mutability = Some(m.clone()),
+                    FunctionAttribute::BaseOrModifier(_, base) => plan.manual.push(ManualItem {
+                        line,
+                        construct: format!("modifier {} on {}", base.name, name),
+                        note: "Call the ported modifier helper explicitly".to_string(),
+                    }),
+                    _ => {}
+                }
+            }
+
+            let receiver = match mutability {
+                Some(Mutability::Pure(_)) | Some(Mutability::View(_)) | Some(Mutability::Constant(_)) => "&self",
+                _ => "&mut self",
+            };
+            let mut params = vec![receiver.to_string()];
+            params.extend(parameters(&func.params));
+
+            let returns = func.returns.iter()
+                .filter_map(|(_, p)| p.as_ref().map(|p| abi_type(&p.ty)))
+                .collect::<Vec<_>>();
+            let ok_type = match returns.len() {
+                0 => "()".to_string(),
+                1 => returns[0].clone(),
+                _ => format!("({})", returns.join(", ")),
+            };
+
+            // Signature only, without the body
+            let solidity = func.to_string();
+            let solidity = solidity.split(" {").next().unwrap_or(&solidity).to_string();
+
+            plan.functions.push(FunctionSkeleton {
+                rust_signature: format!("fn {}({}) -> Result<{}, Vec<u8>>", to_snake_case(&name), params.join(", "), ok_type),
+                solidity,
+                external: matches!(visibility, Some(Visibility::External(_)) | Some(Visibility::Public(_))),
+                payable: matches!(mutability, Some(Mutability::Payable(_))),
+                name: name.clone(),
+            });
+        }
+    }
+
+    if let Some(body) = &func.body {
+        collect_manual_statements(plan, body, source, &name);
+    }
+}
+
+/// Walks a function body looking for statements Stylus can't express directly
+fn collect_manual_statements(plan: &mut MigrationPlan, statement: &Statement, source: &str, function: &str) {
+    match statement {
+        Statement::Assembly { loc, .. } => plan.manual.push(ManualItem {
+            line: line_of(loc, source),
+            construct: format!("inline assembly in {}", function),
+            note: "Rewrite in Rust; use stylus_sdk::evm / call / storage APIs for the low-level parts".to_string(),
+        }),
+        Statement::Try(loc, _, returns, catches) => {
+            plan.manual.push(ManualItem {
+                line: line_of(loc, source),
+                construct: format!("try/catch in {}", function),
+                note: "Match on the `Result` returned by the external call instead".to_string(),
+            });
+            if let Some((_, body)) = returns {
+                collect_manual_statements(plan, body, source, function);
+            }
+            for catch in catches {
+                let body = match catch {
+                    solang_parser::pt::CatchClause::Simple(_, _, body) => body,
+                    solang_parser::pt::CatchClause::Named(_, _, _, body) => body,
+                };
+                collect_manual_statements(plan, body, source, function);
+            }
+        }
+        Statement::Block { statements, .. } => {
+            for inner in statements {
+                collect_manual_statements(plan, inner, source, function);
+            }
+        }
+        Statement::If(_, _, then, otherwise) => {
+            collect_manual_statements(plan, then, source, function);
+            if let Some(otherwise) = otherwise {
+                collect_manual_statements(plan, otherwise, source, function);
+            }
+        }
+        Statement::While(_, _, body) | Statement::DoWhile(_, body, _) => {
+            collect_manual_statements(plan, body, source, function);
+        }
+        Statement::For(_, _, _, _, Some(body)) => {
+            collect_manual_statements(plan, body, source, function);
+        }
+        _ => {}
+    }
+
+    let text = match statement {
+        Statement::Expression(loc, _) | Statement::VariableDefinition(loc, _, _) | Statement::Return(loc, _) => {
+            slice(loc, source)
+        }
+        _ => "",
+    };
+    for (needle, note) in [
+        ("delegatecall", "Use `stylus_sdk::call::delegate_call` (unsafe) and review storage layout compatibility"),
+        ("selfdestruct", "Not supported by Stylus; remove or replace with a disable flag"),
+        ("tx.origin", "Use `msg::sender()`; `tx::origin()` exists but shouldn't be used for auth"),
+    ] {
+        if text.contains(needle) {
+            if let Statement::Expression(loc, _) | Statement::VariableDefinition(loc, _, _) | Statement::Return(loc, _) = statement {
+                plan.manual.push(ManualItem {
+                    line: line_of(loc, source),
+                    construct: format!("{} in {}", needle, function),
+                    note: note.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn parameters(params: &ParameterList) -> Vec<String> {
+    params.iter()
+        .enumerate()
+        .filter_map(|(i, (_, p))| p.as_ref().map(|p| {
+            let name = p.name.as_ref().map(|n| to_snake_case(&n.name)).unwrap_or_else(|| format!("arg{}", i));
+            format!("{}: {}", name, abi_type(&p.ty))
+        }))
+        .collect()
+}
+
+/// Rust type used for a Solidity type in function signatures and memory
+fn abi_type(ty: &Expression) -> String {
+    match ty {
+        Expression::Type(_, ty) => match ty {
+            Type::Address | Type::AddressPayable => "Address".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::String => "String".to_string(),
+            Type::Int(bits) => format!("I{}", bits),
+            Type::Uint(bits) => format!("U{}", bits),
+            Type::Bytes(n) => format!("FixedBytes<{}>", n),
+            Type::DynamicBytes => "Bytes".to_string(),
+            other => format!("/* TODO: {} */", other),
+        },
+        Expression::ArraySubscript(_, inner, None) => format!("Vec<{}>", abi_type(inner)),
+        Expression::ArraySubscript(_, inner, Some(len)) => format!("[{}; {}]", abi_type(inner), len),
+        Expression::Variable(ident) => ident.name.clone(),
+        other => format!("/* TODO: {} */", other),
+    }
+}
+
+/// Stylus storage type for a Solidity state variable type
+fn storage_type(ty: &Expression) -> String {
+    match ty {
+        Expression::Type(_, ty) => match ty {
+            Type::Address | Type::AddressPayable => "StorageAddress".to_string(),
+            Type::Bool => "StorageBool".to_string(),
+            Type::String => "StorageString".to_string(),
+            Type::Int(bits) => format!("StorageI{}", bits),
+            Type::Uint(bits) => format!("StorageU{}", bits),
+            Type::Bytes(n) => format!("StorageFixedBytes<{}>", n),
+            Type::DynamicBytes => "StorageBytes".to_string(),
+            Type::Mapping { key, value, .. } => format!("StorageMap<{}, {}>", abi_type(key), storage_type(value)),
+            other => format!("/* TODO: {} */", other),
+        },
+        Expression::ArraySubscript(_, inner, None) => format!("StorageVec<{}>", storage_type(inner)),
+        Expression::ArraySubscript(_, inner, Some(len)) => format!("StorageArray<{}, {}>", storage_type(inner), len),
+        // User-defined structs must themselves be #[storage] structs
+        Expression::Variable(ident) => ident.name.clone(),
+        other => format!("/* TODO: {} */", other),
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+
+/// Renders the plan as Markdown
+pub fn format_markdown(plans: &[MigrationPlan], file: &Path) -> String {
+    let mut md = format!("# Stylus migration plan for `{}`\n", file.display());
+
+    for plan in plans {
+        md.push_str(&format!("\n## Contract `{}`\n", plan.contract));
+
+        md.push_str("\n### Storage\n\n");
+        if plan.storage.is_empty() {
+            md.push_str("_No state variables._\n");
+        } else {
+            md.push_str("| Solidity | Stylus |\n|---|---|\n");
+            for var in &plan.storage {
+                md.push_str(&format!("| `{}` | `{}` |\n", var.solidity, var.stylus));
+            }
+        }
+
+        if !plan.events.is_empty() || !plan.errors.is_empty() {
+            md.push_str("\n### Events and errors\n\nDeclare these inside `sol! { ... }`:\n\n```rust\n");
+            for item in plan.events.iter().chain(&plan.errors) {
+                md.push_str(&format!("{}\n", item.stylus));
+            }
+            md.push_str("```\n");
+        }
+
+        md.push_str("\n### Functions\n\n");
+        if plan.functions.is_empty() {
+            md.push_str("_No functions._\n");
+        }
+        for func in &plan.functions {
+            md.push_str(&format!("- `{}`\n  → `{}{}`{}\n",
+                func.solidity,
+                if func.external { "pub " } else { "" },
+                func.rust_signature,
+                if func.payable { " (`#[payable]`)" } else { "" }
+            ));
+        }
+
+        md.push_str("\n### Needs manual work\n\n");
+        if plan.manual.is_empty() {
+            md.push_str("_Nothing flagged._\n");
+        }
+        for item in &plan.manual {
+            md.push_str(&format!("- line {}: **{}** — {}\n", item.line, item.construct, item.note));
+        }
+    }
+
+    md
+}
+
+/// Renders a compilable-shaped Rust skeleton with `todo!()` bodies
+pub fn format_scaffold(plans: &[MigrationPlan], file: &Path) -> String {
+    let mut rs = format!(
+        "//! Stylus scaffold generated by `stylus-analyzer migrate {}`.\n\
+         //! Function bodies are left as `todo!()`; see the migration plan for items needing manual work.\n\
+         #![cfg_attr(not(feature = \"export-abi\"), no_main)]\n\
+         extern crate alloc;\n\n\
+         use alloy_primitives::{{Address, FixedBytes, U256}};\n\
+         use alloy_sol_types::sol;\n\
+         use stylus_sdk::{{abi::Bytes, prelude::*, storage::*}};\n",
+        file.display()
+    );
+
+    for (index, plan) in plans.iter().enumerate() {
+        if !plan.events.is_empty() || !plan.errors.is_empty() {
+            rs.push_str("\nsol! {\n");
+            for item in plan.events.iter().chain(&plan.errors) {
+                rs.push_str(&format!("    {}\n", item.stylus));
+            }
+            rs.push_str("}\n");
+        }
+
+        rs.push_str("\n#[storage]\n");
+        // Only one contract per crate can be the entrypoint
+        if index == 0 {
+            rs.push_str("#[entrypoint]\n");
+        }
+        rs.push_str(&format!("pub struct {} {{\n", plan.contract));
+        for var in &plan.storage {
+            rs.push_str(&format!("    {},\n", var.stylus));
+        }
+        rs.push_str("}\n");
+
+        for (public, functions) in [
+            (true, plan.functions.iter().filter(|f| f.external).collect::<Vec<_>>()),
+            (false, plan.functions.iter().filter(|f| !f.external).collect::<Vec<_>>()),
+        ] {
+            if functions.is_empty() {
+                continue;
+            }
+            rs.push_str(if public { "\n#[public]\n" } else { "\n" });
+            rs.push_str(&format!("impl {} {{\n", plan.contract));
+            for func in functions {
+                rs.push_str(&format!("    /// `{}`\n", func.solidity));
+                if func.payable {
+                    rs.push_str("    #[payable]\n");
+                }
+                rs.push_str(&format!("    {}{} {{\n        todo!(\"port {}\")\n    }}\n\n",
+                    if public { "pub " } else { "" }, func.rust_signature, func.name));
+            }
+            rs.truncate(rs.trim_end().len());
+            rs.push_str("\n}\n");
+        }
+    }
+
+    rs
+}
+
+/// Writes the scaffold to `target` (default: the input path with a `.rs` extension)
+pub fn write_scaffold(plans: &[MigrationPlan], file: &Path, target: Option<PathBuf>) -> Result<PathBuf> {
+    let target = target.unwrap_or_else(|| file.with_extension("rs"));
+    if target.exists() {
+        return Err(AnalyzerError::Config(format!(
+            "{} already exists; pass a different path to --write", target.display()
+        )));
+    }
+    std::fs::write(&target, format_scaffold(plans, file))?;
+    Ok(target)
+}
+
+pub fn format_summary(plans: &[MigrationPlan]) -> String {
+    let manual: usize = plans.iter().map(|p| p.manual.len()).sum();
+    let functions: usize = plans.iter().map(|p| p.functions.len()).sum();
+    let line = format!("🔁 {} contract(s), {} function(s) mapped, {} item(s) need manual work",
+        plans.len(), functions, manual);
+    if manual > 0 { line.yellow().to_string() } else { line.green().to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_fixture_maps_to_a_stylus_plan() {
+        let plans = plan_migration(include_str!("../test ex/simple_erc20.sol")).unwrap();
+        assert_eq!(plans.len(), 1);
+        let plan = &plans[0];
+        assert_eq!(plan.contract, "SimpleToken");
+
+        let storage: Vec<&str> = plan.storage.iter().map(|mapping| mapping.stylus.as_str()).collect();
+        assert_eq!(storage[..4], ["name: StorageString", "total_supply: StorageU256", "owner: StorageAddress", "balance_of: StorageMap<Address, StorageU256>"]);
+        assert!(storage.contains(&"allowance: StorageMap<Address, StorageMap<Address, StorageU256>>"));
+        assert!(storage.contains(&"holders: StorageVec<StorageAddress>"));
+        assert_eq!(plan.events.len(), 2);
+        assert_eq!(plan.errors.len(), 1);
+
+        let signature = |name: &str| plan.functions.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(signature("transfer").rust_signature, "fn transfer(&mut self, to: Address, amount: U256) -> Result<bool, Vec<u8>>");
+        assert_eq!(signature("balanceAndSupply").rust_signature, "fn balance_and_supply(&self, account: Address) -> Result<(U256, U256), Vec<u8>>");
+        assert!(signature("deposit").payable);
+        assert!(!signature("_transfer").external);
+
+        let manual: Vec<(usize, &str)> = plan.manual.iter().map(|item| (item.line, item.construct.as_str())).collect();
+        for expected in [(6, "constant/immutable decimals"), (18, "modifier onlyOwner"), (23, "constructor"),
+            (39, "modifier onlyOwner on mint"), (54, "inline assembly in codeSize")] {
+            assert!(manual.contains(&expected), "{:?} missing from {:?}", expected, manual);
+        }
+    }
+
+    #[test]
+    fn declarations_at_column_zero_report_their_own_line() {
+        let source = "pragma solidity ^0.8.20;\n\ninterface IToken {}\nlibrary Math {}\ncontract Child is Base {}\n";
+        let lines: Vec<(usize, String)> = plan_migration(source).unwrap().iter()
+            .flat_map(|plan| plan.manual.iter().map(|item| (item.line, item.construct.clone())))
+            .collect();
+        assert_eq!(lines, [(3, "interface IToken".to_string()), (4, "library Math".to_string()), (5, "inheritance from Base".to_string())]);
+    }
+
+    #[test]
+    fn non_solidity_input_is_rejected() {
+        let Err(AnalyzerError::Parse(message)) = plan_migration(include_str!("../test ex/counter.rs")) else { panic!("Rust parsed as Solidity") };
+        assert!(message.starts_with("migrate expects a Solidity source file"), "{}", message);
+    }
+}
@@ -0,0 +1,234 @@
+//! What an audit runs and how, built up the way the `audit` flags are. The
+//! binary fills one in from its flags; library callers start from
+//! `AnalysisOptions::new()`. Nothing here is process-wide, so callers with
+//! different options can audit side by side.
+
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+use crate::audit::{custom, effort, patterns, plugin, AuditAnalyzer, AuditResult};
+use crate::audit::erc::Standard;
+use crate::audit::rules::AuditRule;
+use crate::config::RuleConfig;
+use crate::error::{AnalyzerError, Result};
+
+/// Audit settings, turned into an analyzer by [`analyzer`](Self::analyzer)
+/// or used directly by [`audit`](Self::audit)
+///
+/// ```
+/// use stylus_analyzer::AnalysisOptions;
+///
+/// let options = AnalysisOptions::new().effort(["Reentrancy Pattern Checker=quick"]);
+/// let analyzer = options.analyzer()?;
+/// assert!(analyzer.rule_names().iter().any(|name| name == "Reentrancy Pattern Checker"));
+/// # Ok::<(), stylus_analyzer::AnalyzerError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptions {
+    plugins: Vec<PathBuf>,
+    custom_rules: Option<PathBuf>,
+    rules: RuleConfig,
+    only_rules: Vec<String>,
+    skip_rules: Vec<String>,
+    severity: Vec<String>,
+    no_dedupe: bool,
+    no_ai: bool,
+    effort: Vec<String>,
+    standard: Option<Standard>,
+    cancel: Option<CancellationToken>,
+}
+
+impl AnalysisOptions {
+    /// The default rules, no overrides
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also runs the rules of this plugin library; see `audit::plugin`
+    pub fn plugin(mut self, path: impl Into<PathBuf>) -> Self {
+        self.plugins.push(path.into());
+        self
+    }
+
+    /// Also runs the house rules in this YAML or TOML file: regexes with an
+    /// id, name, severity and recommendation, as `--custom-rules` reads them
+    pub fn custom_rules(mut self, path: Option<PathBuf>) -> Self {
+        self.custom_rules = path;
+        self
+    }
+
+    /// Which rules run and at what severity, as the `[rules]` table of the config file says
+    pub fn rules(mut self, rules: RuleConfig) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Runs only the rules with these ids, in place of the `[rules]` table's `enabled`
+    pub fn only_rules<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.only_rules.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Leaves out the rules with these ids, as well as those the `[rules]` table disables
+    pub fn skip_rules<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.skip_rules.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Overrides severities, each as `KEY=SEVERITY` with a rule id, name or
+    /// slug, a problem family or a finding name; later ones win
+    pub fn severity_overrides<I: IntoIterator<Item = S>, S: Into<String>>(mut self, specs: I) -> Self {
+        self.severity.extend(specs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether findings several rules report for one problem are merged; on by default
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.no_dedupe = !dedupe;
+        self
+    }
+
+    /// Whether `audit::command` runs the AI-backed analyses; on by default.
+    /// The rules themselves never call the AI provider
+    pub fn ai(mut self, ai: bool) -> Self {
+        self.no_ai = !ai;
+        self
+    }
+
+    /// Overrides rules' remediation effort, each as `RULE=EFFORT` with the rule's name or slug
+    pub fn effort<I: IntoIterator<Item = S>, S: Into<String>>(mut self, specs: I) -> Self {
+        self.effort.extend(specs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Checks conformance to `standard` instead of detecting it from function names
+    pub fn standard(mut self, standard: Option<Standard>) -> Self {
+        self.standard = standard;
+        self
+    }
+
+    /// Stops running further rules once `token` is cancelled
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// An analyzer with the built-in, house and plugin rules the selection
+    /// leaves on, and this severity, effort, standard and cancellation. Fails
+    /// on rules that don't load and on ids, keys or overrides naming no rule
+    pub fn analyzer(&self) -> Result<AuditAnalyzer> {
+        let mut rules = patterns::all_rules();
+        // Loaded first so the selection below can name them
+        if let Some(path) = &self.custom_rules {
+            rules.extend(custom::load(path)?.into_iter().map(|rule| Box::new(rule) as Box<dyn AuditRule>));
+        }
+        let plugins = plugin::load(&self.plugins, &rules)?;
+        rules.extend(plugins.into_iter().map(|rule| Box::new(rule) as Box<dyn AuditRule>));
+
+        // The flags replace `enabled` and add to `disabled` from the config file
+        let mut selection = self.rules.selection(&rules)?;
+        if !self.only_rules.is_empty() {
+            selection.only = Some(patterns::rules_by_id(&self.only_rules, "--only-rules", &rules)?);
+        }
+        selection.disabled.extend(patterns::rules_by_id(&self.skip_rules, "--skip-rules", &rules)?);
+        for spec in &self.severity {
+            let (key, level) = spec.rsplit_once('=').ok_or_else(|| {
+                AnalyzerError::Config(format!("--severity-override expects RULE=SEVERITY, got \"{}\"", spec))
+            })?;
+            let level = patterns::severity_level(level, "--severity-override")?;
+            selection.remap_severity(key, level, "--severity-override", &rules)?;
+        }
+
+        let mut analyzer = AuditAnalyzer::new();
+        if let Some(token) = &self.cancel {
+            analyzer = analyzer.with_cancellation(token.clone());
+        }
+        let mut skipped = Vec::new();
+        for rule in rules {
+            if selection.allows(rule.name()) {
+                analyzer.add_rule(rule);
+            } else {
+                skipped.push(rule.id().to_string());
+            }
+        }
+        let overrides = effort::parse_overrides(&self.effort, &analyzer.rule_names())?;
+        Ok(analyzer
+            .with_rule_selection(selection, skipped)
+            .with_dedupe(!self.no_dedupe)
+            .with_ai(!self.no_ai)
+            .with_effort_overrides(overrides)
+            .with_standard(self.standard))
+    }
+
+    /// Audits `source`, Solidity or Stylus Rust, as the `audit` command does
+    /// a file: token conformance, fingerprints, effort and upgrade authority
+    /// included. Fingerprints don't name a file; use
+    /// [`audit_file`](Self::audit_file) for ones a baseline can match.
+    /// Needs a tokio runtime
+    ///
+    /// ```
+    /// use stylus_analyzer::AnalysisOptions;
+    /// use stylus_analyzer::audit::erc::Standard;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> stylus_analyzer::Result<()> {
+    /// let source = r#"
+    /// pragma solidity ^0.8.20;
+    /// contract Token {
+    ///     mapping(address => uint256) public balanceOf;
+    ///     function transfer(address to, uint256 amount) external returns (bool) {
+    ///         balanceOf[msg.sender] -= amount;
+    ///         balanceOf[to] += amount;
+    ///         return true;
+    ///     }
+    /// }
+    /// "#;
+    /// let result = AnalysisOptions::new().standard(Some(Standard::Erc20)).audit(source).await?;
+    /// assert_eq!(result.conformance.as_ref().unwrap().standard, Standard::Erc20);
+    /// assert!(result.vulnerabilities().any(|vuln| vuln.name.ends_with("Member Missing") && vuln.risk_description.contains("approve")));
+    /// assert!(result.vulnerabilities().all(|vuln| vuln.effort.is_some() && !vuln.fingerprint.is_empty()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn audit(&self, source: &str) -> Result<AuditResult> {
+        self.analyzer()?.audit_source(source, "").await
+    }
+
+    /// Audits the file at `path`, streaming it in line windows when it is large
+    pub async fn audit_file(&self, path: &Path) -> Result<AuditResult> {
+        self.analyzer()?.audit_file(path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::vulnerabilities::Severity;
+
+    #[tokio::test]
+    async fn each_analyzer_keeps_its_own_rule_selection() {
+        let house = Path::new(env!("CARGO_MANIFEST_DIR")).join("test ex/custom/team-rules.yaml");
+        let source = include_str!("../test ex/custom/house_vault.rs");
+        let selective = AnalysisOptions::new()
+            .custom_rules(Some(house.clone()))
+            .skip_rules(["TEAM-BANNED-001"])
+            .severity_overrides(["TEAM-UNSAFE-001=info"])
+            .analyzer()
+            .unwrap();
+        let plain = AnalysisOptions::new().custom_rules(Some(house)).analyzer().unwrap();
+        // Built first, audited second: neither analyzer's settings leak into the other
+        let (selective, plain) = (selective.audit(source).await.unwrap(), plain.audit(source).await.unwrap());
+
+        let house_findings = |result: &AuditResult| result.vulnerabilities()
+            .filter(|vuln| vuln.rule_id.as_deref().is_some_and(|id| id.starts_with("TEAM-")))
+            .map(|vuln| (vuln.rule_id.clone().unwrap(), vuln.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(selective.rules_skipped, ["TEAM-BANNED-001"]);
+        assert_eq!(house_findings(&selective), [("TEAM-UNSAFE-001".to_string(), Severity::Info)]);
+        assert!(plain.rules_skipped.is_empty());
+        assert_eq!(house_findings(&plain), [
+            ("TEAM-UNSAFE-001".to_string(), Severity::High),
+            ("TEAM-BANNED-001".to_string(), Severity::Low),
+        ]);
+        assert!(AnalysisOptions::new().only_rules(["TEAM-UNSAFE-001"]).analyzer().is_err());
+    }
+}
@@ -1,8 +1,9 @@
-use solang_parser::pt::{SourceUnit, FunctionTy};
+use solang_parser::doccomment::{parse_doccomments, DocCommentTag};
+use solang_parser::pt::{CodeLocation, Comment, FunctionAttribute, FunctionDefinition, SourceUnit, Visibility};
 use solang_parser::parse;
 use syn::{File as RustFile, Item};
 use quote::ToTokens;
-use std::error::Error;
+use crate::error::{AnalyzerError, Result};
 
 #[derive(Debug)]
 pub enum ContractType {
@@ -18,9 +19,71 @@ pub struct Function {
     pub visibility: String,
     pub params: Vec<String>,
     pub return_type: Option<String>,
+    /// Number of return values; Solidity only
+    pub returns: usize,
+    /// NatSpec tags (`notice`, `param`, ...) from the doc comment above the function; Solidity only
+    pub docs: Vec<DocCommentTag>,
     pub body: String,
 }
 
+// Rough per-operation costs used by the static gas estimate
+const BASE_GAS: u64 = 100;
+const STORAGE_READ_GAS: u64 = 2_100;
+const STORAGE_WRITE_GAS: u64 = 20_000;
+const EXTERNAL_CALL_GAS: u64 = 2_600;
+const EVENT_GAS: u64 = 375;
+const LOOP_GAS: u64 = 5_000;
+
+impl Function {
+    /// Static gas estimate from the operations in the body. Only meaningful
+    /// relative to other functions or other versions of the same contract.
+    pub fn estimated_gas(&self) -> u64 {
+        body_words(&self.body).fold(BASE_GAS, |gas, word| {
+            gas + match word {
+                "get" | "getter" | "sload" | "Sload" => STORAGE_READ_GAS,
+                "set" | "insert" | "push" | "setter" | "sstore" | "Sstore" => STORAGE_WRITE_GAS,
+                "call" | "static_call" | "delegate_call" | "transfer_eth" | "FunctionCallBlock" => EXTERNAL_CALL_GAS,
+                "emit" | "log" | "Emit" => EVENT_GAS,
+                "for" | "while" | "loop" | "For" | "While" | "DoWhile" => LOOP_GAS,
+                _ => 0,
+            }
+        })
+    }
+
+    /// Cyclomatic complexity: one plus the number of decision points in the body
+    pub fn cyclomatic_complexity(&self) -> u32 {
+        let branches = body_words(&self.body)
+            .filter(|word| matches!(*word,
+                "if" | "for" | "while" | "loop" | "match" | "&&" | "||" | "?"
+                | "If" | "For" | "While" | "DoWhile" | "And" | "Or" | "ConditionalOperator"
+            ))
+            .count();
+        1 + branches as u32
+    }
+}
+
+/// Splits a function body into identifier-like words and the `&&`/`||`/`?`
+/// operators. Rust bodies are token streams and Solidity bodies are AST debug
+/// output, so keywords show up lowercase for Rust and as variant names for Solidity.
+fn body_words(body: &str) -> impl Iterator<Item = &str> {
+    body.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '&' || c == '|' || c == '?'))
+        .flat_map(|chunk| {
+            // Operators can be glued to identifiers in debug output
+            let trimmed = chunk.trim_matches(|c: char| c == '&' || c == '|' || c == '?');
+            let op = if chunk.contains("&&") {
+                Some("&&")
+            } else if chunk.contains("||") {
+                Some("||")
+            } else if chunk.contains('?') {
+                Some("?")
+            } else {
+                None
+            };
+            std::iter::once(trimmed).chain(op)
+        })
+        .filter(|word| !word.is_empty())
+}
+
 /// Represents a structure in a smart contract
 #[derive(Debug)]
 #[allow(dead_code)]  // Fields are used in analysis
@@ -40,10 +103,10 @@ pub struct ParsedContract {
 }
 
 impl ParsedContract {
-    pub fn new(content: String) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new(content: String) -> Result<Self> {
         // Try parsing as Solidity first
-        if let Ok((source_unit, _)) = parse(&content, 0) {
-            return Ok(Self::from_solidity(source_unit, content));
+        if let Ok((source_unit, comments)) = parse(&content, 0) {
+            return Ok(Self::from_solidity(source_unit, &comments, content));
         }
 
         // If not Solidity, try parsing as Rust
@@ -51,23 +114,40 @@ impl ParsedContract {
             return Ok(Self::from_rust(rust_file, content));
         }
 
-        Err("Failed to parse contract as either Solidity or Rust".into())
+        Err(AnalyzerError::Parse("input is neither valid Solidity nor Rust".to_string()))
     }
 
-    fn get_visibility_string(_: &FunctionTy) -> String {
-        // Simplified approach: return default visibility
-        "internal".to_string()
+    fn get_visibility_string(func: &FunctionDefinition) -> String {
+        func.attributes.iter()
+            .find_map(|attr| match attr {
+                FunctionAttribute::Visibility(visibility) => Some(match visibility {
+                    Visibility::External(_) => "external",
+                    Visibility::Public(_) => "public",
+                    Visibility::Internal(_) => "internal",
+                    Visibility::Private(_) => "private",
+                }),
+                _ => None,
+            })
+            // Functions without a visibility are public in older Solidity
+            .unwrap_or("public")
+            .to_string()
     }
 
-    fn from_solidity(source_unit: SourceUnit, content: String) -> Self {
+    fn from_solidity(source_unit: SourceUnit, comments: &[Comment], content: String) -> Self {
         let mut functions = Vec::new();
         let mut structs = Vec::new();
 
         for part in source_unit.0 {
             if let solang_parser::pt::SourceUnitPart::ContractDefinition(contract) = part {
+                // Doc comments of a part sit between the end of the previous part and its start
+                let mut previous_end = contract.loc.start();
                 for part in contract.parts {
+                    let docs = parse_doccomments(comments, previous_end, part.loc().start());
+                    previous_end = part.loc().end();
                     match part {
                         solang_parser::pt::ContractPart::FunctionDefinition(func) => {
+                            let visibility = Self::get_visibility_string(&func);
+                            let returns = func.returns.len();
                             if let Some(name) = func.name {
                                 let mut params = Vec::new();
                                 for (_, param_opt) in func.params {
@@ -91,9 +171,11 @@ impl ParsedContract {
 
                                 functions.push(Function {
                                     name: name.name,
-                                    visibility: Self::get_visibility_string(&func.ty),
+                                    visibility,
                                     params,
                                     return_type,
+                                    returns,
+                                    docs: docs.into_iter().flat_map(|doc| doc.into_comments()).collect(),
                                     body,
                                 });
                             }
@@ -147,9 +229,45 @@ impl ParsedContract {
                             .map(|arg| arg.to_token_stream().to_string())
                             .collect(),
                         return_type: Some(func.sig.output.to_token_stream().to_string()),
+                        returns: 0,
+                        docs: Vec::new(),
                         body: func.block.to_token_stream().to_string(),
                     });
                 }
+                Item::Impl(impl_item) => {
+                    // Methods are named `Type::method` so impls of different types don't collide
+                    let owner = match impl_item.self_ty.as_ref() {
+                        syn::Type::Path(path) => path.path.segments.last().map(|seg| seg.ident.to_string()),
+                        _ => None,
+                    };
+
+                    for impl_fn in &impl_item.items {
+                        if let syn::ImplItem::Fn(method) = impl_fn {
+                            let visibility = if matches!(method.vis, syn::Visibility::Public(_)) {
+                                "public"
+                            } else {
+                                "private"
+                            }.to_string();
+
+                            let name = match &owner {
+                                Some(owner) => format!("{}::{}", owner, method.sig.ident),
+                                None => method.sig.ident.to_string(),
+                            };
+
+                            functions.push(Function {
+                                name,
+                                visibility,
+                                params: method.sig.inputs.iter()
+                                    .map(|arg| arg.to_token_stream().to_string())
+                                    .collect(),
+                                return_type: Some(method.sig.output.to_token_stream().to_string()),
+                                returns: 0,
+                                docs: Vec::new(),
+                                body: method.block.to_token_stream().to_string(),
+                            });
+                        }
+                    }
+                }
                 Item::Struct(struct_item) => {
                     let fields = struct_item.fields.iter()
                         .filter_map(|field| {
@@ -296,7 +414,7 @@ impl ParsedContract {
         patterns
     }
 
-    pub fn get_function_size(&self) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    pub fn get_function_size(&self) -> Result<usize> {
         let mut size = 0;
         for function in &self.functions {
             size += function.name.len();
@@ -311,7 +429,7 @@ impl ParsedContract {
         Ok(size)
     }
 
-    pub fn get_storage_size(&self) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    pub fn get_storage_size(&self) -> Result<usize> {
         let mut size = 0;
         for structure in &self.structs {
             size += structure.name.len();
@@ -322,7 +440,7 @@ impl ParsedContract {
         Ok(size)
     }
 
-    pub fn get_event_size(&self) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    pub fn get_event_size(&self) -> Result<usize> {
         // Calculate event size from source code
         let mut size = 0;
         let event_lines: Vec<&str> = self.source.lines()
@@ -0,0 +1,218 @@
+//! `--format html`: one self-contained page (inline CSS and JS, nothing
+//! fetched) with a summary dashboard and a findings table that filters by
+//! severity and analyzer. The `--format json` document is embedded as-is in
+//! `<script id="report-data">`, so the page is machine-readable too
+
+use std::path::Path;
+use crate::analyzer::Analysis;
+use crate::analyzer::output::{self, SeverityCounts};
+use crate::error::Result;
+use super::calculate_risk_score;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0 auto; max-width: 1100px; padding: 24px; color: #1f2328; }
+h1 { font-size: 1.6em; margin-bottom: 4px; }
+.muted { color: #656d76; }
+.banner { background: #fff8c5; border: 1px solid #d4a72c; border-radius: 6px; padding: 8px 12px; margin: 12px 0; }
+.cards { display: flex; flex-wrap: wrap; gap: 12px; margin: 20px 0; }
+.card { border: 1px solid #d0d7de; border-radius: 6px; padding: 12px 16px; min-width: 110px; }
+.card .value { font-size: 1.8em; font-weight: 600; }
+.filters { display: flex; flex-wrap: wrap; gap: 16px; align-items: center; margin: 16px 0; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 6px 10px; border-bottom: 1px solid #d0d7de; vertical-align: top; }
+tr.finding { cursor: pointer; }
+tr.finding:hover, tr.finding.open { background: #f6f8fa; }
+tr.detail td { background: #f6f8fa; padding: 4px 24px 12px; }
+.sev { font-weight: 600; white-space: nowrap; }
+.example { margin: 0 0 8px; padding: 8px 12px; background: #fff; border: 1px solid #d0d7de; border-radius: 6px; overflow-x: auto; }
+.example .removed { color: #a40e26; } .example .added { color: #1a7f37; }
+.critical { color: #a40e26; } .high { color: #bc4c00; } .medium { color: #9a6700; } .low { color: #1a7f37; } .info { color: #656d76; }
+"#;
+
+const SCRIPT: &str = r#"
+const data = JSON.parse(document.getElementById('report-data').textContent);
+const order = { Critical: 0, High: 1, Medium: 2, Low: 3, Info: 4 };
+const findings = data.analyses.flatMap(analysis => analysis.findings.map(finding => ({ analyzer: analysis.analyzer, ...finding })));
+findings.sort((a, b) => order[a.severity] - order[b.severity]);
+
+function element(tag, text, className) {
+  const el = document.createElement(tag);
+  el.textContent = text ?? '';
+  if (className) el.className = className;
+  return el;
+}
+
+const body = document.getElementById('findings');
+for (const finding of findings) {
+  const row = document.createElement('tr');
+  row.className = 'finding';
+  row.dataset.severity = finding.severity;
+  row.dataset.analyzer = finding.analyzer;
+  row.append(element('td', finding.severity, 'sev ' + finding.severity.toLowerCase()),
+    element('td', finding.analyzer), element('td', finding.title), element('td', finding.line));
+
+  const detail = document.createElement('tr');
+  detail.className = 'detail';
+  detail.hidden = true;
+  const cell = document.createElement('td');
+  cell.colSpan = 4;
+  if (finding.description) cell.append(element('p', finding.description));
+  if (finding.recommendation) {
+    const recommendation = element('p', ' ' + finding.recommendation);
+    recommendation.prepend(element('strong', 'Recommendation:'));
+    cell.append(recommendation);
+  }
+  if (finding.example) {
+    cell.append(element('strong', 'Fix example:'));
+    const code = element('pre', null, 'example');
+    for (const [lines, sign, className] of [[finding.example.before, '- ', 'removed'], [finding.example.after, '+ ', 'added']]) {
+      for (const line of lines.split('\n')) code.append(element('span', sign + line + '\n', className));
+    }
+    cell.append(code);
+  }
+  if (finding.references.length) {
+    const standards = element('p', 'Standards: ', 'muted');
+    finding.references.forEach((reference, index) => {
+      const link = element('a', reference);
+      const cwe = reference.match(/^CWE-(\d+)$/);
+      link.href = cwe ? 'https://cwe.mitre.org/data/definitions/' + cwe[1] + '.html' : 'https://swcregistry.io/docs/' + reference;
+      standards.append(index ? ', ' : '', link);
+    });
+    cell.append(standards);
+  }
+  if (finding.rule) cell.append(element('p', 'Rule: ' + finding.rule, 'muted'));
+  if (!cell.childElementCount) cell.append(element('p', 'No further detail.', 'muted'));
+  detail.append(cell);
+  row.addEventListener('click', () => {
+    detail.hidden = !detail.hidden;
+    row.classList.toggle('open', !detail.hidden);
+  });
+  body.append(row, detail);
+}
+
+function filter() {
+  const severities = new Set([...document.querySelectorAll('input[name=severity]:checked')].map(input => input.value));
+  const analyzer = document.getElementById('analyzer').value;
+  let shown = 0;
+  for (const row of document.querySelectorAll('tr.finding')) {
+    const visible = severities.has(row.dataset.severity) && (!analyzer || row.dataset.analyzer === analyzer);
+    row.hidden = !visible;
+    if (!visible) {
+      row.nextElementSibling.hidden = true;
+      row.classList.remove('open');
+    }
+    shown += visible;
+  }
+  document.getElementById('shown').textContent = shown + ' of ' + findings.length + ' findings';
+}
+document.querySelectorAll('input[name=severity], #analyzer').forEach(input => input.addEventListener('change', filter));
+filter();
+"#;
+
+/// The page for `command` run on `file`; `analyses` are the same ones
+/// `--format json` prints
+pub fn format_html(command: &str, file: &Path, analyses: &[Analysis], interrupted: bool) -> Result<String> {
+    // `<` only occurs inside JSON strings, where < means the same, so the
+    // data can't close the script element
+    let data = output::to_json(command, file, analyses, interrupted)?.replace('<', "\\u003c");
+
+    let sections: Vec<SeverityCounts> = analyses.iter().map(|analysis| SeverityCounts::of(&analysis.findings)).collect();
+    let mut total = SeverityCounts::default();
+    for counts in &sections {
+        total.add(*counts);
+    }
+    let score = calculate_risk_score(&sections);
+
+    let title = format!("stylus-analyzer {}: {}", command, file.display());
+    let banner = if interrupted {
+        "<p class=\"banner\">⚠️ <strong>Interrupted:</strong> partial results. Re-run to completion before relying on this report.</p>\n"
+    } else {
+        ""
+    };
+    let cards = [
+        ("Risk score", format!("{}/10", score), ""),
+        ("Critical", total.critical.to_string(), "critical"),
+        ("High", total.high.to_string(), "high"),
+        ("Medium", total.medium.to_string(), "medium"),
+        ("Low", total.low.to_string(), "low"),
+        ("Info", total.info.to_string(), "info"),
+    ]
+    .iter()
+    .map(|(label, value, class)| format!(
+        "<div class=\"card\"><div class=\"muted\">{}</div><div class=\"value {}\">{}</div></div>\n", label, class, value
+    ))
+    .collect::<String>();
+    let severities = ["Critical", "High", "Medium", "Low", "Info"].iter()
+        .map(|severity| format!(
+            "<label><input type=\"checkbox\" name=\"severity\" value=\"{0}\" checked> <span class=\"sev {1}\">{0}</span></label>\n",
+            severity, severity.to_ascii_lowercase()
+        ))
+        .collect::<String>();
+    let analyzers = analyses.iter()
+        .map(|analysis| format!("<option value=\"{0}\">{0}</option>\n", escape(analysis.analyzer)))
+        .collect::<String>();
+
+    Ok(format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<h1>Smart Contract Analysis: <code>{file}</code></h1>
+<p class="muted">{tool} {version} · <code>{command}</code></p>
+{banner}<div class="cards">
+{cards}</div>
+<div class="filters">
+{severities}<label>Analyzer <select id="analyzer"><option value="">All</option>
+{analyzers}</select></label>
+<span class="muted" id="shown"></span>
+</div>
+<table>
+<thead><tr><th>Severity</th><th>Analyzer</th><th>Finding</th><th>Line</th></tr></thead>
+<tbody id="findings"></tbody>
+</table>
+<script type="application/json" id="report-data">
+{data}
+</script>
+<script>{SCRIPT}</script>
+</body>
+</html>
+"#,
+        title = escape(&title),
+        file = escape(&file.display().to_string()),
+        tool = env!("CARGO_PKG_NAME"),
+        version = env!("CARGO_PKG_VERSION"),
+        command = escape(command),
+    ))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::output::Finding;
+    use crate::audit::vulnerabilities::{FixExample, Severity, Vulnerability};
+
+    #[test]
+    fn fix_examples_reach_the_page_and_its_script() {
+        let vuln = Vulnerability {
+            name: "Unchecked Arithmetic".to_string(),
+            severity: Severity::High,
+            example: Some(FixExample { before: "let x = a - b;".to_string(), after: "let x = a.checked_sub(b)?;".to_string() }),
+            ..Default::default()
+        };
+        let analyses = [Analysis::new("audit", String::new()).with_findings(vec![Finding::from(&vuln)])];
+        let page = format_html("audit", Path::new("<vault>.rs"), &analyses, false).unwrap();
+
+        assert!(page.contains(r#""after": "let x = a.checked_sub(b)?;""#));
+        assert!(page.contains("finding.example.before") && page.contains("'Fix example:'"));
+        // Neither the file name nor the embedded data can open a tag
+        assert!(page.contains("<code>&lt;vault&gt;.rs</code>") && page.contains(r#""file": "\u003cvault>.rs""#));
+    }
+}
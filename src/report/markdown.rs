@@ -0,0 +1,130 @@
+//! `--format markdown`: the full report as GitHub-flavored Markdown, for
+//! pasting into issues, pull requests and Notion. Analyzer output keeps its
+//! layout in fenced blocks; everything else becomes headings and tables
+
+use std::path::Path;
+use crate::analyzer::output::{Finding, SeverityCounts};
+use super::{calculate_risk_score, extract_recommendations, highest_severity, severity_label, FullReport};
+
+/// The report as Markdown; `file` names the analyzed contract in the title
+pub fn format_markdown(report: &FullReport, file: &Path) -> String {
+    let mut md = format!("# Smart Contract Analysis Report: `{}`\n\n", file.display());
+    md.push_str(if report.ai {
+        "_AI-powered analysis._\n"
+    } else {
+        "_Static analysis only: the AI review was skipped (`--no-ai`)._\n"
+    });
+    if report.interrupted {
+        md.push_str(&format!(
+            "\n> ⚠️ **Interrupted:** only {} analyses finished. Re-run the report to completion before relying on it.\n",
+            report.sections.iter().filter(|section| section.findings.is_some()).count()
+        ));
+    }
+
+    let counts: Vec<Option<SeverityCounts>> = report.sections.iter()
+        .map(|section| section.findings.as_deref().map(SeverityCounts::of))
+        .collect();
+    let mut total = SeverityCounts::default();
+    for section in counts.iter().flatten() {
+        total.add(*section);
+    }
+
+    md.push_str("\n## Executive Summary\n\n");
+    md.push_str(&format!("**Overall Risk Score:** {}/10\n\n", calculate_risk_score(counts.iter().flatten())));
+    md.push_str(&severity_table(&total));
+
+    md.push_str("\n### Key Findings\n\n| Analysis | Result |\n|---|---|\n");
+    for (section, counts) in report.sections.iter().zip(&counts) {
+        let result = counts.as_ref().map_or("**Analysis failed**", |counts| severity_label(highest_severity(counts)));
+        md.push_str(&format!("| {} | {} |\n", section.title, result));
+    }
+
+    md.push_str(if report.ai { "\n### AI Recommendations\n\n" } else { "\n### Recommendations\n\n" });
+    let reports: Vec<(&str, String)> = report.sections.iter()
+        .map(|section| (section.title, strip_ansi(&section.text)))
+        .collect();
+    md.push_str(&bullets(extract_recommendations(&reports).iter().take(3), "_None._"));
+
+    md.push_str("\n## Smart Contract Patterns\n\n");
+    md.push_str(&bullets(report.patterns.iter(), "_No significant patterns detected._"));
+    md.push_str("\n## Gas Usage Patterns\n\n");
+    md.push_str(&bullets(report.gas_patterns.iter(), "_No gas-specific patterns detected._"));
+
+    md.push_str("\n## Detailed Analysis\n");
+    for (section, counts) in report.sections.iter().zip(&counts) {
+        let result = counts.as_ref().map_or("Analysis failed", |counts| severity_label(highest_severity(counts)));
+        md.push_str(&format!("\n<details>\n<summary><b>{}</b>: {}</summary>\n\n", section.title, result));
+        if let Some(findings) = section.findings.as_deref().filter(|findings| !findings.is_empty()) {
+            md.push_str(&findings_table(findings));
+            md.push('\n');
+        }
+        md.push_str(&fenced(&strip_ansi(&section.text), "text"));
+        md.push_str("\n</details>\n");
+    }
+
+    md
+}
+
+/// `| Severity | Count |` for every severity
+pub(crate) fn severity_table(counts: &SeverityCounts) -> String {
+    format!(
+        "| Severity | Count |\n|---|---|\n| Critical | {} |\n| High | {} |\n| Medium | {} |\n| Low | {} |\n| Informational | {} |\n",
+        counts.critical, counts.high, counts.medium, counts.low, counts.info
+    )
+}
+
+fn findings_table(findings: &[Finding]) -> String {
+    let mut table = String::from("| Severity | Line | Finding |\n|---|---|---|\n");
+    for finding in findings {
+        table.push_str(&format!("| {:?} | {} | {} |\n",
+            finding.severity,
+            finding.line.map(|line| line.to_string()).unwrap_or_default(),
+            cell(&strip_ansi(&finding.title))
+        ));
+    }
+    table
+}
+
+fn bullets<'a>(items: impl Iterator<Item = &'a String>, empty: &str) -> String {
+    let list: String = items.map(|item| format!("- {}\n", strip_ansi(item).trim())).collect();
+    if list.is_empty() { format!("{}\n", empty) } else { list }
+}
+
+/// `text` made safe for one table cell: no pipes, no line breaks
+pub(crate) fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// `code` in a fenced block whose fence is longer than any backtick run
+/// inside it, so code containing ``` can't close the block early
+pub(crate) fn fenced(code: &str, language: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat((longest + 1).max(3));
+    format!("{}{}\n{}\n{}\n", fence, language, code.trim_matches('\n'), fence)
+}
+
+/// `text` without ANSI escape sequences, for output built with colors on
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            plain.push(c);
+            continue;
+        }
+        // CSI sequences run from `ESC [` up to a final byte in @..~
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
@@ -1,6 +1,8 @@
-use std::path::PathBuf;
-use std::error::Error;
+use std::path::{Path, PathBuf};
+use crate::error::Result;
 use colored::*;
+use futures::stream::{self, StreamExt};
+use tokio_util::sync::CancellationToken;
 use crate::analyzer::{
     gas::GasAnalyzer,
     size::SizeAnalyzer, 
@@ -9,18 +11,62 @@ use crate::analyzer::{
     interactions::InteractionsAnalyzer,
     quality::QualityAnalyzer
 };
-use crate::analyzer::Analyzer;
+use crate::analyzer::{Analysis, Analyzer};
+use crate::analyzer::output::{Finding, SeverityCounts};
+use crate::audit::vulnerabilities::Severity;
+use crate::cancel::run_or_cancel;
 use crate::parser::ParsedContract;
+use tracing::{info, warn};
 
-pub async fn generate_full_report(file: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
-    println!("\n🤖 Starting AI-Powered Smart Contract Analysis...");
-    println!("📝 Loading analyzers and preparing context...\n");
+pub mod html;
+pub mod markdown;
+
+/// Analyzers `report` runs at once unless `--jobs` says otherwise
+pub const DEFAULT_JOBS: usize = 3;
+
+/// The full report and the analyses it was assembled from
+pub struct FullReport {
+    pub text: String,
+    pub analyses: Vec<Analysis>,
+    /// Set when `cancel` fired before every analyzer finished
+    pub interrupted: bool,
+    /// Each analyzer's part of the report, in report order
+    pub sections: Vec<Section>,
+    pub patterns: Vec<String>,
+    pub gas_patterns: Vec<String>,
+    /// False under `--no-ai`
+    pub ai: bool,
+}
+
+/// One analyzer's part of the report
+pub struct Section {
+    pub title: &'static str,
+    /// The analyzer's report, or the error when it failed
+    pub text: String,
+    /// None when the analyzer failed
+    pub findings: Option<Vec<Finding>>,
+}
+
+/// Runs every analyzer on `contract`, parsed once from `file`, up to `jobs` at
+/// a time, and assembles the full report in a fixed section order. A failing
+/// analyzer becomes an error note in its section. If `cancel` fires, the
+/// analyzers that already finished are still rendered, under an "interrupted"
+/// banner.
+pub async fn generate_full_report(contract: &ParsedContract, file: &Path, jobs: usize, cancel: &CancellationToken) -> Result<FullReport> {
+    let ai = crate::ai::enabled();
+    if ai {
+        info!("🤖 Starting AI-Powered Smart Contract Analysis...");
+    } else {
+        info!("🔍 Starting static Smart Contract Analysis (--no-ai)...");
+    }
+    info!("📝 Loading analyzers and preparing context...");
 
-    let contract = ParsedContract::new(std::fs::read_to_string(file)?)?;
     let patterns = contract.analyze_patterns();
     let gas_patterns = contract.analyze_gas_patterns();
 
-    println!("🔍 Running deep analysis with multiple AI agents...\n");
+    if ai {
+        info!("🔍 Running deep analysis with multiple AI agents...");
+    }
 
     let analyzers: Vec<(&str, Box<dyn Analyzer>)> = vec![
         ("Gas Optimization", Box::new(GasAnalyzer)),
@@ -31,38 +77,114 @@ pub async fn generate_full_report(file: &PathBuf) -> Result<String, Box<dyn Erro
         ("Code Quality", Box::new(QualityAnalyzer)),
     ];
 
+    let total = analyzers.len();
+    // `buffered` yields in submission order, so sections keep their place however the calls finish
+    let results: Vec<_> = stream::iter(analyzers.iter().map(|(name, analyzer)| async move {
+            info!("🧠 Analyzing {name}...");
+            (*name, run_or_cancel(cancel, analyzer.analyze_parsed(contract, file)).await)
+        }))
+        .buffered(jobs.max(1))
+        .collect()
+        .await;
+
     let mut reports = Vec::new();
-    for (name, analyzer) in analyzers {
-        println!("🧠 AI Agent analyzing {name}...");
-        let content = analyzer.analyze(file).await?;
-        reports.push((name, content));
+    let mut sections = Vec::new();
+    let mut analyses = Vec::new();
+    // Parallel to `reports`; None for an analyzer that failed
+    let mut counts = Vec::new();
+    for (name, result) in results {
+        match result {
+            Some(Ok(analysis)) => {
+                reports.push((name, analysis.text.clone()));
+                counts.push(Some(SeverityCounts::of(&analysis.findings)));
+                sections.push(Section { title: name, text: analysis.text.clone(), findings: Some(analysis.findings.clone()) });
+                analyses.push(analysis);
+            }
+            Some(Err(e)) => {
+                warn!("❌ {name} analysis failed: {e}");
+                reports.push((name, format!("{}\n", format!("❌ {name} analysis failed: {e}").red())));
+                counts.push(None);
+                sections.push(Section { title: name, text: format!("❌ {name} analysis failed: {e}"), findings: None });
+            }
+            None => {}
+        }
     }
 
-    println!("\n✨ Analysis complete! Generating comprehensive report...\n");
+    let interrupted = reports.len() < total;
+    if interrupted {
+        warn!("⚠️  Analysis interrupted! Generating partial report...");
+    } else {
+        info!("✨ Analysis complete! Generating comprehensive report...");
+    }
 
-    let report = format!(
-        "{}\n{}\n\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}",
+    let text = format!(
+        "{}\n{}\n{}\n\n{}\n\n{}\n{}\n\n{}\n{}\n\n{}",
         "===========================================".bright_green(),
-        "🤖 AI-Powered Smart Contract Analysis Report".bright_green().bold(),
-        format_executive_summary(&reports),
+        if ai {
+            "🤖 AI-Powered Smart Contract Analysis Report".bright_green().bold()
+        } else {
+            "🔍 Smart Contract Analysis Report (static analysis only)".bright_green().bold()
+        },
+        format_interrupted_banner(interrupted, reports.len(), total),
+        format_executive_summary(&reports, &counts, ai),
         "🔍 Smart Contract Patterns".bright_yellow().bold(),
         format_patterns(&patterns),
         "⚡ Gas Usage Patterns".bright_yellow().bold(),
         format_gas_patterns(&gas_patterns),
-        format_detailed_analysis(&reports)
+        format_detailed_analysis(&reports, ai)
     );
 
-    Ok(report)
+    Ok(FullReport { text, analyses, interrupted, sections, patterns, gas_patterns, ai })
 }
 
-fn format_executive_summary(reports: &[(&str, String)]) -> String {
+/// Findings per severity for each file of a directory or glob run, and
+/// across all of them. `total_files` counts files the run never reached
+pub fn format_multi_file_summary(results: &[(PathBuf, Result<SeverityCounts>)], total_files: usize, total: &SeverityCounts) -> String {
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    let mut output = format!("\n{}\n{}\n",
+        format!("📊 Summary: {} file(s) analyzed, {} failed", results.len() - failed, failed).bright_yellow().bold(),
+        "═".repeat(40).bright_yellow());
+    output.push_str(&format!("{}\n{}\n{}\n{}\n",
+        format!("🚨 Critical: {}", total.critical).red().bold(),
+        format!("⚠️  High: {}", total.high).yellow().bold(),
+        format!("ℹ️  Medium: {}", total.medium).blue(),
+        format!("✅ Low: {}", total.low).green()));
+
+    let width = results.iter().map(|(file, _)| file.display().to_string().chars().count()).max().unwrap_or(0);
+    output.push_str(&format!("\n{}\n", "Per file:".bold()));
+    for (file, result) in results {
+        let file = format!("{:<width$}", file.display(), width = width);
+        match result {
+            Ok(counts) => output.push_str(&format!("  {}  C {}  H {}  M {}  L {}\n",
+                file, counts.critical, counts.high, counts.medium, counts.low)),
+            Err(e) => output.push_str(&format!("  {}  {}\n", file, format!("❌ {}", e).red())),
+        }
+    }
+    if results.len() < total_files {
+        output.push_str(&format!("\n{}\n",
+            format!("⚠️  Interrupted: {} file(s) not analyzed", total_files - results.len()).yellow().bold()));
+    }
+    output
+}
+
+fn format_interrupted_banner(interrupted: bool, completed: usize, total: usize) -> String {
+    if !interrupted {
+        return String::new();
+    }
+
+    format!("{}\n",
+        format!("⚠️  INTERRUPTED: partial report ({} of {} analyzers completed)", completed, total)
+            .yellow().bold())
+}
+
+fn format_executive_summary(reports: &[(&str, String)], counts: &[Option<SeverityCounts>], ai: bool) -> String {
     let mut summary = String::new();
     summary.push_str(&format!("{}\n{}\n\n", 
         "Executive Summary".bright_yellow().bold(),
         "----------------".bright_yellow()));
 
-    // Risk Score calculation based on findings
-    let risk_score = calculate_risk_score(reports);
+    // Risk Score calculation based on findings; failed sections don't count
+    let risk_score = calculate_risk_score(counts.iter().flatten());
     summary.push_str(&format!("🎯 Overall Risk Score: {}/10\n", 
         if risk_score > 7.0 { risk_score.to_string().red() }
         else if risk_score > 4.0 { risk_score.to_string().yellow() }
@@ -70,14 +192,17 @@ fn format_executive_summary(reports: &[(&str, String)]) -> String {
 
     // Key findings summary
     summary.push_str("\n🔑 Key Findings:\n");
-    for (category, content) in reports {
-        let severity = get_highest_severity(content);
+    for ((category, _), counts) in reports.iter().zip(counts) {
+        let Some(counts) = counts else {
+            summary.push_str(&format!("• {}: {}\n", category, "Analysis Failed".red()));
+            continue;
+        };
         summary.push_str(&format!("• {}: {}\n", 
             category,
-            format_severity(&severity)));
+            format_severity(highest_severity(counts))));
     }
 
-    summary.push_str("\n💡 AI Recommendations:\n");
+    summary.push_str(if ai { "\n💡 AI Recommendations:\n" } else { "\n💡 Recommendations:\n" });
     let recommendations = extract_recommendations(reports);
     for rec in recommendations.iter().take(3) {
         summary.push_str(&format!("• {}\n", rec));
@@ -86,37 +211,47 @@ fn format_executive_summary(reports: &[(&str, String)]) -> String {
     summary
 }
 
-fn calculate_risk_score(reports: &[(&str, String)]) -> f32 {
+/// 10 minus a penalty per section for its most severe finding
+fn calculate_risk_score<'a>(sections: impl IntoIterator<Item = &'a SeverityCounts>) -> f32 {
     let mut score: f32 = 10.0;
-    for (_, content) in reports {
-        if content.contains("Critical") { score -= 2.0; }
-        else if content.contains("High") { score -= 1.0; }
-        else if content.contains("Medium") { score -= 0.5; }
+    for counts in sections {
+        match highest_severity(counts) {
+            Severity::Critical => score -= 2.0,
+            Severity::High => score -= 1.0,
+            Severity::Medium => score -= 0.5,
+            _ => {}
+        }
     }
     score.max(0.0)
 }
 
-fn format_severity(severity: &str) -> colored::ColoredString {
+fn format_severity(severity: Severity) -> colored::ColoredString {
+    let label = severity_label(severity);
     match severity {
-        "Critical" => "Critical Issues Found".red().bold(),
-        "High" => "High Risk Areas".yellow().bold(),
-        "Medium" => "Medium Concerns".yellow(),
-        _ => "Low/No Issues".green(),
+        Severity::Critical => label.red().bold(),
+        Severity::High => label.yellow().bold(),
+        Severity::Medium => label.yellow(),
+        _ => label.green(),
     }
 }
 
-fn get_highest_severity(content: &str) -> String {
-    if content.contains("Critical") {
-        "Critical".to_string()
-    } else if content.contains("High") {
-        "High".to_string()
-    } else if content.contains("Medium") {
-        "Medium".to_string()
-    } else {
-        "Low".to_string()
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "Critical Issues Found",
+        Severity::High => "High Risk Areas",
+        Severity::Medium => "Medium Concerns",
+        _ => "Low/No Issues",
     }
 }
 
+/// Low when a section has no findings at all
+fn highest_severity(counts: &SeverityCounts) -> Severity {
+    [Severity::Critical, Severity::High, Severity::Medium]
+        .into_iter()
+        .find(|&severity| counts.at_least(severity) > 0)
+        .unwrap_or(Severity::Low)
+}
+
 fn format_patterns(patterns: &[String]) -> String {
     if patterns.is_empty() {
         "No significant patterns detected.".dimmed().to_string()
@@ -139,13 +274,18 @@ fn format_gas_patterns(patterns: &[String]) -> String {
     }
 }
 
-fn format_detailed_analysis(reports: &[(&str, String)]) -> String {
+/// Each analyzer's report under its own heading; `ai` is false under `--no-ai`
+fn format_detailed_analysis(reports: &[(&str, String)], ai: bool) -> String {
     let mut analysis = String::new();
 
     for (category, content) in reports {
         analysis.push_str(&format!("\n{}\n{}\n{}\n",
             "─".repeat(50).bright_blue(),
-            format!("[ 🤖 AI Analysis: {} ]", category).bright_yellow().bold(),
+            if ai {
+                format!("[ 🤖 AI Analysis: {} ]", category).bright_yellow().bold()
+            } else {
+                format!("[ 🔍 Static Analysis: {} ]", category).bright_yellow().bold()
+            },
             "─".repeat(50).bright_blue()));
 
         // Format the content with improved readability
@@ -164,7 +304,7 @@ fn format_detailed_analysis(reports: &[(&str, String)]) -> String {
         }
 
         // Add AI insights section
-        analysis.push_str("\n🤖 AI Agent Insights:\n");
+        analysis.push_str(if ai { "\n🤖 AI Agent Insights:\n" } else { "\n🔍 Insights:\n" });
         let insights = extract_ai_insights(content);
         for insight in insights {
             analysis.push_str(&format!("  • {}\n", insight.cyan()));
@@ -0,0 +1,80 @@
+//! Where the report goes: stdout, or the file named by `--output`. Progress
+//! and errors stay on stderr either way, so the file holds only the report
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use stylus_analyzer::error::{AnalyzerError, Result};
+
+/// The `--output` path and the report gathered for it. The file is written
+/// once the command finishes, so a run that fails early leaves none behind
+static FILE: OnceLock<(PathBuf, Mutex<Vec<u8>>)> = OnceLock::new();
+
+/// Sends the report to `path` instead of stdout; an existing file is only
+/// replaced with `force`
+pub fn to_file(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(AnalyzerError::Config(format!("{} already exists; pass --force to overwrite it", path.display())));
+    }
+    if path.is_dir() {
+        return Err(AnalyzerError::Config(format!("{} is a directory", path.display())));
+    }
+    let _ = FILE.set((path.to_path_buf(), Mutex::new(Vec::new())));
+    Ok(())
+}
+
+/// Writes one line of the report
+pub fn line(text: &str) {
+    match FILE.get() {
+        Some((_, buffer)) => {
+            let mut buffer = buffer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            buffer.extend_from_slice(text.as_bytes());
+            buffer.push(b'\n');
+        }
+        None => {
+            let written = writeln!(std::io::stdout().lock(), "{}", text);
+            if let Err(e) = written {
+                // The reader went away, e.g. `| head`; the rest of the report has nowhere to go
+                if e.kind() == std::io::ErrorKind::BrokenPipe {
+                    std::process::exit(0);
+                }
+                panic!("failed printing to stdout: {}", e);
+            }
+        }
+    }
+}
+
+/// Writes the gathered report to the `--output` file. `keep_empty` creates
+/// the file even when the command printed nothing. Returns the path written
+pub fn finish(keep_empty: bool) -> Result<Option<PathBuf>> {
+    let Some((path, buffer)) = FILE.get() else { return Ok(None) };
+    let buffer = buffer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if buffer.is_empty() && !keep_empty {
+        return Ok(None);
+    }
+    std::fs::write(path, &*buffer)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("cannot write {}: {}", path.display(), e)))?;
+    Ok(Some(path.clone()))
+}
+
+/// `Write` handle on the report, for code that writes rather than prints lines
+pub struct Out;
+
+impl Write for Out {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match FILE.get() {
+            Some((_, buffer)) => {
+                buffer.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            None => std::io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match FILE.get() {
+            Some(_) => Ok(()),
+            None => std::io::stdout().flush(),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! `stats`: the shape of a contract or a codebase from parsing alone, with no
+//! rules and no AI. Doubles as the cheapest check that everything parses
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use colored::*;
+use serde::Serialize;
+use solang_parser::pt::{CodeLocation, ContractPart, ContractTy, SourceUnitPart};
+use crate::analyzer::output::SCHEMA_VERSION;
+use crate::audit::source;
+use crate::compare::abi::{is_external_impl, AbiSurface};
+use crate::error::{AnalyzerError, Result};
+use crate::parser::{ContractType, ParsedContract};
+use crate::upgrade::layout::extract_layout;
+
+/// Inventory of one source file
+#[derive(Debug, Serialize)]
+pub struct FileStats {
+    pub path: String,
+    pub language: &'static str,
+    pub contracts: Vec<String>,
+    /// Function count by visibility (`public`, `external`, `internal`, `private`)
+    pub functions: BTreeMap<String, usize>,
+    pub state_variables: usize,
+    pub structs: usize,
+    pub events: usize,
+    /// Lines that aren't blank or comments
+    pub loc: usize,
+    /// Mean lines per function body, signature included
+    pub average_function_lines: f64,
+    /// External signatures, e.g. `transfer(address,uint256) returns (bool)`
+    pub interface: Vec<String>,
+    /// Total lines over all function bodies, for weighting averages
+    #[serde(skip)]
+    function_lines: usize,
+    #[serde(skip)]
+    function_bodies: usize,
+}
+
+impl FileStats {
+    pub fn function_count(&self) -> usize {
+        self.functions.values().sum()
+    }
+}
+
+/// Sums over every file that parsed
+#[derive(Debug, Default, Serialize)]
+pub struct Totals {
+    pub files: usize,
+    pub contracts: usize,
+    pub functions: BTreeMap<String, usize>,
+    pub state_variables: usize,
+    pub structs: usize,
+    pub events: usize,
+    pub loc: usize,
+    pub average_function_lines: f64,
+    pub interface: usize,
+}
+
+/// A file that couldn't be read or parsed
+#[derive(Debug, Serialize)]
+pub struct FileError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub files: Vec<FileStats>,
+    pub totals: Totals,
+    pub errors: Vec<FileError>,
+}
+
+impl Stats {
+    /// Collects statistics for each of `files`; parse failures are recorded, not fatal
+    pub fn collect(files: &[impl AsRef<Path>]) -> Self {
+        let mut stats = Stats::default();
+        for file in files {
+            let path = file.as_ref().display().to_string();
+            let result = std::fs::read_to_string(file.as_ref())
+                .map_err(AnalyzerError::from)
+                .and_then(|content| file_stats(&path, content));
+            match result {
+                Ok(file_stats) => stats.files.push(file_stats),
+                Err(e) => stats.errors.push(FileError { path, message: e.to_string() }),
+            }
+        }
+        stats.totals = totals(&stats.files);
+        stats
+    }
+}
+
+/// Statistics of one file's source
+pub fn file_stats(path: &str, content: String) -> Result<FileStats> {
+    let contract = ParsedContract::new(content)?;
+    let source = contract.source.as_str();
+
+    let mut functions = BTreeMap::new();
+    for function in &contract.functions {
+        *functions.entry(function.visibility.clone()).or_insert(0) += 1;
+    }
+    let surface = AbiSurface::from_source(source)?;
+    let interface = surface.functions.iter()
+        .map(|function| match function.outputs.is_empty() {
+            true => function.signature(),
+            false => format!("{} returns ({})", function.signature(), function.outputs.join(",")),
+        })
+        .collect();
+
+    let (language, contracts, state_variables, events, spans) = match contract.contract_type {
+        ContractType::Solidity => {
+            let (contracts, state_variables, events, spans) = solidity_shape(source);
+            ("Solidity", contracts, state_variables, events, spans)
+        }
+        ContractType::Stylus => {
+            let (contracts, state_variables) = stylus_shape(source);
+            let spans = source::rust_functions(source).into_iter().map(|(_, start, end)| (start, end)).collect();
+            ("Stylus (Rust)", contracts, state_variables, surface.events.len(), spans)
+        }
+    };
+    let function_lines: usize = spans.iter().map(|&(start, end)| line_count(&source[start..end])).sum();
+
+    Ok(FileStats {
+        path: path.to_string(),
+        language,
+        contracts,
+        functions,
+        state_variables,
+        structs: contract.struct_count(),
+        events,
+        loc: code_lines(source),
+        average_function_lines: average(function_lines, spans.len()),
+        interface,
+        function_lines,
+        function_bodies: spans.len(),
+    })
+}
+
+/// Contract names, state variables, events and function byte spans of a Solidity file
+fn solidity_shape(source: &str) -> (Vec<String>, usize, usize, Vec<(usize, usize)>) {
+    let Ok((unit, _)) = solang_parser::parse(source, 0) else { return Default::default() };
+    let mut contracts = Vec::new();
+    let mut state_variables = 0;
+    let mut events = 0;
+    let mut spans = Vec::new();
+    for part in &unit.0 {
+        match part {
+            SourceUnitPart::ContractDefinition(contract) => {
+                let Some(name) = &contract.name else { continue };
+                contracts.push(match contract.ty {
+                    ContractTy::Contract(_) => name.name.clone(),
+                    ContractTy::Abstract(_) => format!("{} (abstract)", name.name),
+                    ContractTy::Interface(_) => format!("{} (interface)", name.name),
+                    ContractTy::Library(_) => format!("{} (library)", name.name),
+                });
+                state_variables += source::state_variables(contract).len();
+                for part in &contract.parts {
+                    match part {
+                        ContractPart::EventDefinition(_) => events += 1,
+                        ContractPart::FunctionDefinition(func) => {
+                            if let Some(body) = &func.body {
+                                spans.push((func.loc.start(), body.loc().end()));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            SourceUnitPart::EventDefinition(_) => events += 1,
+            _ => {}
+        }
+    }
+    (contracts, state_variables, events, spans)
+}
+
+/// Contract names and top-level storage fields of a Stylus file. Contracts are
+/// the types with exported impls, or else the storage struct
+fn stylus_shape(source: &str) -> (Vec<String>, usize) {
+    let layout = extract_layout(source).ok();
+    let mut contracts: Vec<String> = Vec::new();
+    if let Ok(file) = syn::parse_file(source) {
+        for item in &file.items {
+            let syn::Item::Impl(impl_item) = item else { continue };
+            let syn::Type::Path(path) = impl_item.self_ty.as_ref() else { continue };
+            let Some(name) = path.path.segments.last().map(|seg| seg.ident.to_string()) else { continue };
+            if is_external_impl(&impl_item.attrs) && !contracts.contains(&name) {
+                contracts.push(name);
+            }
+        }
+    }
+    if contracts.is_empty() {
+        contracts.extend(layout.as_ref().map(|layout| layout.contract.clone()));
+    }
+    let state_variables = layout
+        .map(|layout| layout.entries.iter().filter(|entry| !entry.name.contains(['.', '['])).count())
+        .unwrap_or(0);
+    (contracts, state_variables)
+}
+
+fn totals(files: &[FileStats]) -> Totals {
+    let mut totals = Totals { files: files.len(), ..Default::default() };
+    let (mut function_lines, mut function_bodies) = (0, 0);
+    for file in files {
+        totals.contracts += file.contracts.len();
+        for (visibility, count) in &file.functions {
+            *totals.functions.entry(visibility.clone()).or_insert(0) += count;
+        }
+        totals.state_variables += file.state_variables;
+        totals.structs += file.structs;
+        totals.events += file.events;
+        totals.loc += file.loc;
+        totals.interface += file.interface.len();
+        function_lines += file.function_lines;
+        function_bodies += file.function_bodies;
+    }
+    totals.average_function_lines = average(function_lines, function_bodies);
+    totals
+}
+
+fn line_count(text: &str) -> usize {
+    text.lines().count()
+}
+
+/// Lines that are neither blank nor only a comment
+fn code_lines(source: &str) -> usize {
+    source.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("/*") && !line.starts_with('*'))
+        .count()
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        // One decimal is plenty and keeps JSON output stable
+        (total as f64 / count as f64 * 10.0).round() / 10.0
+    }
+}
+
+fn format_functions(functions: &BTreeMap<String, usize>) -> String {
+    let total: usize = functions.values().sum();
+    if functions.is_empty() {
+        return "0".to_string();
+    }
+    let by_visibility = functions.iter()
+        .map(|(visibility, count)| format!("{} {}", visibility, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} ({})", total, by_visibility)
+}
+
+/// Full inventory of one file, or a table with a totals row for several
+pub fn format_stats(stats: &Stats) -> String {
+    let mut output = String::new();
+    match stats.files.as_slice() {
+        [file] if stats.errors.is_empty() => output.push_str(&format_file(file)),
+        files => {
+            output.push_str(&format!("\n{}\n", "📊 Codebase statistics".cyan().bold()));
+            if !files.is_empty() {
+                output.push_str(&format_table(files, &stats.totals));
+            }
+        }
+    }
+    if !stats.errors.is_empty() {
+        output.push_str(&format!("\n{}\n", format!("❌ {} file(s) failed to parse", stats.errors.len()).red().bold()));
+        for error in &stats.errors {
+            output.push_str(&format!("  • {}: {}\n", error.path, error.message));
+        }
+    }
+    output
+}
+
+fn format_file(file: &FileStats) -> String {
+    let mut output = format!("\n{}\n", format!("📊 Contract statistics: {}", file.path).cyan().bold());
+    let contracts = if file.contracts.is_empty() { "-".to_string() } else { file.contracts.join(", ") };
+    let rows = [
+        ("Language", file.language.to_string()),
+        ("Contracts", contracts),
+        ("Functions", format_functions(&file.functions)),
+        ("State variables", file.state_variables.to_string()),
+        ("Structs", file.structs.to_string()),
+        ("Events", file.events.to_string()),
+        ("Lines of code", file.loc.to_string()),
+        ("Avg function length", format!("{:.1} lines", file.average_function_lines)),
+    ];
+    for (label, value) in rows {
+        output.push_str(&format!("  {:<20} {}\n", format!("{}:", label), value));
+    }
+    output.push_str(&format!("\n  {}\n", "External interface".bold()));
+    if file.interface.is_empty() {
+        output.push_str("    (none)\n");
+    }
+    for signature in &file.interface {
+        output.push_str(&format!("    • {}\n", signature));
+    }
+    output
+}
+
+fn format_table(files: &[FileStats], totals: &Totals) -> String {
+    let header = ["File", "Language", "Contracts", "Functions", "Vars", "Structs", "Events", "LOC", "Avg fn"];
+    let mut rows: Vec<[String; 9]> = files.iter()
+        .map(|file| [
+            file.path.clone(),
+            file.language.to_string(),
+            file.contracts.len().to_string(),
+            file.function_count().to_string(),
+            file.state_variables.to_string(),
+            file.structs.to_string(),
+            file.events.to_string(),
+            file.loc.to_string(),
+            format!("{:.1}", file.average_function_lines),
+        ])
+        .collect();
+    rows.push([
+        format!("Total ({} files)", totals.files),
+        String::new(),
+        totals.contracts.to_string(),
+        totals.functions.values().sum::<usize>().to_string(),
+        totals.state_variables.to_string(),
+        totals.structs.to_string(),
+        totals.events.to_string(),
+        totals.loc.to_string(),
+        format!("{:.1}", totals.average_function_lines),
+    ]);
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let mut out = String::from(" ");
+        for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            // Text columns left-aligned, counts right-aligned
+            if i < 2 {
+                out.push_str(&format!(" {:<width$}", cell, width = width));
+            } else {
+                out.push_str(&format!(" {:>width$}", cell, width = width));
+            }
+        }
+        out.trim_end().to_string() + "\n"
+    };
+
+    let mut output = line(&header.map(String::from)).bold().to_string();
+    output.push_str(&format!("  {}\n", "─".repeat(widths.iter().sum::<usize>() + widths.len() - 1)));
+    let (total, per_file) = rows.split_last().expect("totals row");
+    for row in per_file {
+        output.push_str(&line(row));
+    }
+    output.push_str(&format!("  {}\n", "─".repeat(widths.iter().sum::<usize>() + widths.len() - 1)));
+    output.push_str(&line(total).bold().to_string());
+    output.push_str(&format!("\n  Functions by visibility: {}\n", format_functions(&totals.functions)));
+    output
+}
+
+/// Statistics as JSON, versioned like the analyzers' `--format json` output
+pub fn format_json(stats: &Stats) -> Result<String> {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        schema_version: u32,
+        #[serde(flatten)]
+        stats: &'a Stats,
+    }
+    serde_json::to_string_pretty(&Document { schema_version: SCHEMA_VERSION, stats })
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize statistics: {}", e)))
+}
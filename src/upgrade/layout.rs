@@ -0,0 +1,995 @@
+use std::collections::{HashMap, HashSet};
+use colored::*;
+use quote::ToTokens;
+use serde::Serialize;
+use solang_parser::pt::{
+    ContractPart, ContractTy, Expression, SourceUnitPart, Type, VariableAttribute,
+};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::error::{AnalyzerError, Result};
+
+/// One storage variable, or one member of a struct stored in a variable
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageEntry {
+    /// `owner`, `config.fee`, or `positions[].amount` for members of mapping values and array elements
+    pub name: String,
+    /// Solidity type name
+    pub ty: String,
+    pub slot: u64,
+    /// Byte offset within the slot
+    pub offset: u32,
+    pub bytes: u32,
+    /// Whole slots spanned; 1 for packed value types
+    pub slots: u64,
+    /// Slot counts from the mapping value or array element rather than from slot 0
+    pub relative: bool,
+    /// Contract or storage struct that declares the variable
+    pub declared_in: String,
+}
+
+impl StorageEntry {
+    fn position(&self) -> u64 {
+        self.slot * 32 + self.offset as u64
+    }
+
+    /// Byte position just past the entry
+    fn end(&self) -> u64 {
+        if self.bytes < 32 {
+            self.position() + self.bytes as u64
+        } else {
+            (self.slot + self.slots) * 32
+        }
+    }
+
+    /// Reserved space such as `uint256[50] __gap`, consumed by later appends
+    pub fn is_gap(&self) -> bool {
+        let last = self.name.rsplit('.').next().unwrap_or(&self.name);
+        matches!(last, "__gap" | "_gap" | "gap" | "__reserved") || last.ends_with("__gap")
+    }
+
+    fn key(&self) -> (&str, &str) {
+        (&self.declared_in, &self.name)
+    }
+
+    /// Name of the variable or struct path this entry lives in; `""` for top-level variables
+    fn scope(&self) -> &str {
+        self.name.rfind('.').map(|i| &self.name[..i]).unwrap_or("")
+    }
+}
+
+/// Ordered storage layout of a contract, including inherited variables
+#[derive(Debug, Serialize)]
+pub struct StorageLayout {
+    pub contract: String,
+    pub entries: Vec<StorageEntry>,
+    /// Types or base contracts that couldn't be resolved from this file
+    pub warnings: Vec<String>,
+}
+
+/// Storage type, reduced to what decides slot assignment
+#[derive(Debug, Clone)]
+enum Ty {
+    /// Packable value type and its size in bytes
+    Value(String, u32),
+    Mapping(String, Box<Ty>),
+    /// `string`, `bytes` (no element) or `T[]`
+    Dynamic(String, Option<Box<Ty>>),
+    Fixed(String, Box<Ty>, u64),
+    Struct(String),
+}
+
+impl Ty {
+    fn name(&self) -> String {
+        match self {
+            Ty::Value(name, _) | Ty::Mapping(name, _) | Ty::Dynamic(name, _) | Ty::Fixed(name, _, _) | Ty::Struct(name) => name.clone(),
+        }
+    }
+}
+
+/// Member names and types of a struct, in declaration order
+type Fields = Vec<(String, Ty)>;
+type Structs = HashMap<String, Fields>;
+
+/// Extracts the layout of the most-derived Solidity contract, or of the Stylus
+/// `#[entrypoint]` storage struct
+pub fn extract_layout(source: &str) -> Result<StorageLayout> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        return Ok(solidity_layout(unit.0));
+    }
+    if let Ok(file) = syn::parse_file(source) {
+        return stylus_layout(&file);
+    }
+    Err(AnalyzerError::Parse("input is neither valid Solidity nor Rust".to_string()))
+}
+
+struct SolidityContract {
+    bases: Vec<String>,
+    variables: Vec<(String, Expression)>,
+}
+
+fn solidity_layout(parts: Vec<SourceUnitPart>) -> StorageLayout {
+    let mut contracts: Vec<(String, SolidityContract)> = Vec::new();
+    let mut struct_defs = Vec::new();
+    let mut enums = HashSet::new();
+    // Interfaces and libraries have no storage of their own
+    let mut stateless = HashSet::new();
+
+    for part in parts {
+        match part {
+            SourceUnitPart::StructDefinition(def) => struct_defs.push(*def),
+            SourceUnitPart::EnumDefinition(def) => enums.extend(def.name.map(|n| n.name)),
+            SourceUnitPart::ContractDefinition(contract) => {
+                let Some(name) = contract.name.map(|n| n.name) else { continue };
+                if matches!(contract.ty, ContractTy::Interface(_) | ContractTy::Library(_)) {
+                    stateless.insert(name);
+                    continue;
+                }
+                let mut variables = Vec::new();
+                for part in contract.parts {
+                    match part {
+                        ContractPart::StructDefinition(def) => struct_defs.push(*def),
+                        ContractPart::EnumDefinition(def) => enums.extend(def.name.map(|n| n.name)),
+                        ContractPart::VariableDefinition(var) => {
+                            // Constants and immutables live in bytecode, not storage
+                            let in_code = var.attrs.iter().any(|a| matches!(a, VariableAttribute::Constant(_) | VariableAttribute::Immutable(_)));
+                            if let (false, Some(name)) = (in_code, var.name) {
+                                variables.push((name.name, var.ty));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let bases = contract.base.iter()
+                    .filter_map(|base| base.name.identifiers.last().map(|id| id.name.clone()))
+                    .collect();
+                contracts.push((name, SolidityContract { bases, variables }));
+            }
+            _ => {}
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let struct_names: HashSet<String> = struct_defs.iter().filter_map(|def| def.name.as_ref().map(|n| n.name.clone())).collect();
+    let resolve = |ty: &Expression, warnings: &mut Vec<String>| solidity_ty(ty, &struct_names, &enums, warnings);
+
+    let mut structs = Structs::new();
+    for def in &struct_defs {
+        let Some(name) = &def.name else { continue };
+        let fields = def.fields.iter()
+            .filter_map(|field| field.name.as_ref().map(|n| (n.name.clone(), resolve(&field.ty, &mut warnings))))
+            .collect();
+        structs.insert(name.name.clone(), fields);
+    }
+
+    // The most-derived contract is the last one no other contract in the file inherits from
+    let inherited: HashSet<&String> = contracts.iter().flat_map(|(_, c)| &c.bases).collect();
+    let Some(primary) = contracts.iter().rev().find(|(name, _)| !inherited.contains(name)).map(|(name, _)| name.clone()) else {
+        return StorageLayout { contract: String::new(), entries: Vec::new(), warnings };
+    };
+
+    // Base-first depth-first walk; matches C3 linearization for the usual inheritance shapes
+    let by_name: HashMap<&str, &SolidityContract> = contracts.iter().map(|(name, c)| (name.as_str(), c)).collect();
+    let mut order = Vec::new();
+    let mut visited = stateless;
+    linearize(&primary, &by_name, &mut visited, &mut order, &mut warnings);
+
+    let mut layouter = Layouter::new(&structs);
+    for contract in order {
+        for (name, ty) in &by_name[contract.as_str()].variables {
+            let ty = resolve(ty, &mut warnings);
+            layouter.place(name, &ty, &contract, false);
+        }
+    }
+
+    StorageLayout { contract: primary, entries: layouter.entries, warnings }
+}
+
+fn linearize(
+    name: &str,
+    contracts: &HashMap<&str, &SolidityContract>,
+    visited: &mut HashSet<String>,
+    order: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    if !visited.insert(name.to_string()) {
+        return;
+    }
+    let Some(contract) = contracts.get(name) else {
+        warnings.push(format!("base contract {} is not in this file; its storage variables are not counted and later slots may be off", name));
+        return;
+    };
+    for base in &contract.bases {
+        linearize(base, contracts, visited, order, warnings);
+    }
+    order.push(name.to_string());
+}
+
+fn solidity_ty(ty: &Expression, structs: &HashSet<String>, enums: &HashSet<String>, warnings: &mut Vec<String>) -> Ty {
+    match ty {
+        Expression::Type(_, Type::Bool) => Ty::Value("bool".to_string(), 1),
+        Expression::Type(_, Type::Address | Type::AddressPayable) => Ty::Value("address".to_string(), 20),
+        Expression::Type(_, Type::Uint(bits)) => Ty::Value(format!("uint{}", bits), (*bits / 8) as u32),
+        Expression::Type(_, Type::Int(bits)) => Ty::Value(format!("int{}", bits), (*bits / 8) as u32),
+        Expression::Type(_, Type::Bytes(n)) => Ty::Value(format!("bytes{}", n), *n as u32),
+        Expression::Type(_, Type::String) => Ty::Dynamic("string".to_string(), None),
+        Expression::Type(_, Type::DynamicBytes) => Ty::Dynamic("bytes".to_string(), None),
+        Expression::Type(_, Type::Mapping { key, value, .. }) => {
+            let key = solidity_ty(key, structs, enums, warnings);
+            let value = solidity_ty(value, structs, enums, warnings);
+            Ty::Mapping(format!("mapping({} => {})", key.name(), value.name()), Box::new(value))
+        }
+        Expression::ArraySubscript(_, elem, None) => {
+            let elem = solidity_ty(elem, structs, enums, warnings);
+            Ty::Dynamic(format!("{}[]", elem.name()), Some(Box::new(elem)))
+        }
+        Expression::ArraySubscript(_, elem, Some(len)) => {
+            let elem = solidity_ty(elem, structs, enums, warnings);
+            let len = match len.as_ref() {
+                Expression::NumberLiteral(_, digits, _, _) => digits.parse().unwrap_or(1),
+                _ => 1,
+            };
+            Ty::Fixed(format!("{}[{}]", elem.name(), len), Box::new(elem), len)
+        }
+        Expression::Variable(id) | Expression::MemberAccess(_, _, id) => user_ty(&id.name, structs, enums, warnings),
+        other => Ty::Value(other.to_string(), 32),
+    }
+}
+
+/// Structs and enums declared in the file; anything else is taken to be a contract reference
+fn user_ty(name: &str, structs: &HashSet<String>, enums: &HashSet<String>, warnings: &mut Vec<String>) -> Ty {
+    if structs.contains(name) {
+        Ty::Struct(name.to_string())
+    } else if enums.contains(name) {
+        Ty::Value(name.to_string(), 1)
+    } else {
+        let warning = format!("type {} is not declared in this file; assumed to be a contract (20 bytes)", name);
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+        Ty::Value(name.to_string(), 20)
+    }
+}
+
+fn stylus_layout(file: &syn::File) -> Result<StorageLayout> {
+    let mut structs = Structs::new();
+    let mut order = Vec::new();
+    let mut entrypoint = None;
+    let mut warnings = Vec::new();
+
+    for item in &file.items {
+        match item {
+            syn::Item::Macro(mac) if mac.mac.path.segments.last().is_some_and(|s| s.ident == "sol_storage") => {
+                for (name, fields, is_entry) in parse_sol_storage(&mac.mac.tokens.to_string(), &mut warnings)? {
+                    if is_entry {
+                        entrypoint = Some(name.clone());
+                    }
+                    order.push(name.clone());
+                    structs.insert(name, fields);
+                }
+            }
+            syn::Item::Struct(def) => {
+                let attr = |ident: &str| def.attrs.iter().any(|a| a.path().segments.last().is_some_and(|s| s.ident == ident));
+                let storage_fields = def.fields.iter().any(|f| f.ty.to_token_stream().to_string().starts_with("Storage"));
+                if !(attr("storage") || attr("solidity_storage") || attr("entrypoint") || storage_fields) {
+                    continue;
+                }
+                let name = def.ident.to_string();
+                let fields = def.fields.iter()
+                    .filter_map(|f| f.ident.as_ref().map(|ident| (ident.to_string(), &f.ty)))
+                    .map(|(field, ty)| (field, rust_storage_ty(ty)))
+                    .collect();
+                if attr("entrypoint") {
+                    entrypoint = Some(name.clone());
+                }
+                order.push(name.clone());
+                structs.insert(name, fields);
+            }
+            _ => {}
+        }
+    }
+
+    // Types that name another storage struct are nested structs, e.g. `#[borrow]` parents
+    let names: HashSet<String> = structs.keys().cloned().collect();
+    for fields in structs.values_mut() {
+        for (_, ty) in fields.iter_mut() {
+            resolve_structs(ty, &names);
+        }
+    }
+
+    let Some(primary) = entrypoint.or_else(|| order.last().cloned()) else {
+        return Err(AnalyzerError::Parse("no Stylus storage struct found (sol_storage!, #[storage] or #[entrypoint])".to_string()));
+    };
+
+    let mut layouter = Layouter::new(&structs);
+    for (name, ty) in &structs[&primary] {
+        layouter.place(name, ty, &primary, false);
+    }
+    Ok(StorageLayout { contract: primary, entries: layouter.entries, warnings })
+}
+
+/// Structs declared in a `sol_storage!` body, with whether each is the `#[entrypoint]`
+fn parse_sol_storage(tokens: &str, warnings: &mut Vec<String>) -> Result<Vec<(String, Fields, bool)>> {
+    // Attributes become `# [entrypoint]` / `# [borrow]` in the token text; solang can't parse them
+    let mut body = String::new();
+    let mut entry_structs = HashSet::new();
+    let mut pending_entry = false;
+    let mut rest = tokens;
+    while let Some(start) = rest.find("# [") {
+        body.push_str(&rest[..start]);
+        let end = rest[start..].find(']').map(|i| start + i + 1).unwrap_or(rest.len());
+        pending_entry |= rest[start..end].contains("entrypoint");
+        rest = &rest[end..];
+        if pending_entry {
+            if let Some(name) = rest.split_whitespace().skip_while(|w| *w != "struct").nth(1) {
+                entry_structs.insert(name.to_string());
+                pending_entry = false;
+            }
+        }
+    }
+    body.push_str(rest);
+    let body = body.replace("pub struct", "struct");
+
+    let wrapped = format!("contract __Storage {{ {} }}", body);
+    let (unit, _) = solang_parser::parse(&wrapped, 0)
+        .map_err(|_| AnalyzerError::Parse("cannot parse sol_storage! declarations".to_string()))?;
+
+    let mut defs = Vec::new();
+    for part in unit.0 {
+        let SourceUnitPart::ContractDefinition(contract) = part else { continue };
+        for part in contract.parts {
+            if let ContractPart::StructDefinition(def) = part {
+                defs.push(*def);
+            }
+        }
+    }
+    let struct_names: HashSet<String> = defs.iter().filter_map(|d| d.name.as_ref().map(|n| n.name.clone())).collect();
+    // Nested structs may come from other sol_storage! blocks; resolve_structs fixes those up later
+    let mut local_warnings = Vec::new();
+
+    let parsed = defs.iter()
+        .filter_map(|def| {
+            let name = def.name.as_ref()?.name.clone();
+            let fields = def.fields.iter()
+                .filter_map(|f| f.name.as_ref().map(|n| (n.name.clone(), solidity_ty(&f.ty, &struct_names, &HashSet::new(), &mut local_warnings))))
+                .collect();
+            let is_entry = entry_structs.contains(&name);
+            Some((name, fields, is_entry))
+        })
+        .collect();
+
+    // Types assumed to be contracts are usually `#[borrow]`ed structs from elsewhere in the file
+    warnings.extend(local_warnings.into_iter().filter(|w| !w.contains("assumed to be a contract")));
+    Ok(parsed)
+}
+
+/// Re-types values that name a known storage struct as nested structs
+fn resolve_structs(ty: &mut Ty, names: &HashSet<String>) {
+    match ty {
+        Ty::Value(name, _) if names.contains(name.as_str()) => *ty = Ty::Struct(name.clone()),
+        Ty::Mapping(_, value) => resolve_structs(value, names),
+        Ty::Dynamic(_, Some(elem)) | Ty::Fixed(_, elem, _) => resolve_structs(elem, names),
+        _ => {}
+    }
+}
+
+/// Storage type for a Stylus SDK `Storage*` field type
+fn rust_storage_ty(ty: &syn::Type) -> Ty {
+    let syn::Type::Path(path) = ty else {
+        if let syn::Type::Array(array) = ty {
+            // Plain `[u8; 20]` fields show up in mock contracts
+            let elem = rust_storage_ty(&array.elem);
+            let len = array.len.to_token_stream().to_string().parse().unwrap_or(1);
+            if let Ty::Value(name, bytes) = &elem {
+                if name == "uint8" && len <= 32 {
+                    return Ty::Value(format!("bytes{}", len), *bytes * len as u32);
+                }
+            }
+            return Ty::Fixed(format!("{}[{}]", elem.name(), len), Box::new(elem), len);
+        }
+        return Ty::Value(ty.to_token_stream().to_string(), 32);
+    };
+    let Some(last) = path.path.segments.last() else { return Ty::Value("unknown".to_string(), 32) };
+    let ident = last.ident.to_string();
+    let generics: Vec<&syn::GenericArgument> = match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().collect(),
+        _ => Vec::new(),
+    };
+    let generic_ty = |i: usize| match generics.get(i) {
+        Some(syn::GenericArgument::Type(ty)) => rust_storage_ty(ty),
+        _ => Ty::Value("unknown".to_string(), 32),
+    };
+    let generic_const = |i: usize| generics.get(i).map(|g| g.to_token_stream().to_string()).and_then(|s| s.parse::<u64>().ok());
+
+    match ident.as_str() {
+        "StorageAddress" | "Address" => Ty::Value("address".to_string(), 20),
+        "StorageBool" | "bool" => Ty::Value("bool".to_string(), 1),
+        "StorageString" => Ty::Dynamic("string".to_string(), None),
+        "StorageBytes" => Ty::Dynamic("bytes".to_string(), None),
+        "StorageB256" | "B256" => Ty::Value("bytes32".to_string(), 32),
+        "StorageFixedBytes" | "FixedBytes" => {
+            let n = generic_const(0).unwrap_or(32);
+            Ty::Value(format!("bytes{}", n), n as u32)
+        }
+        "StorageUint" | "Uint" => integer_ty(false, generic_const(0).unwrap_or(256)),
+        "StorageSigned" | "Signed" => integer_ty(true, generic_const(0).unwrap_or(256)),
+        "StorageMap" | "HashMap" => {
+            let key = generic_ty(0);
+            let value = generic_ty(1);
+            Ty::Mapping(format!("mapping({} => {})", key.name(), value.name()), Box::new(value))
+        }
+        "StorageVec" | "Vec" => {
+            let elem = generic_ty(0);
+            Ty::Dynamic(format!("{}[]", elem.name()), Some(Box::new(elem)))
+        }
+        "StorageArray" => {
+            let elem = generic_ty(0);
+            let len = generic_const(1).unwrap_or(1);
+            Ty::Fixed(format!("{}[{}]", elem.name(), len), Box::new(elem), len)
+        }
+        _ => {
+            // StorageU256, StorageI64, U256, u64, ...
+            let bare = ident.strip_prefix("Storage").unwrap_or(&ident);
+            let (signed, bits) = match bare.chars().next() {
+                Some('u' | 'U') => (false, &bare[1..]),
+                Some('i' | 'I') => (true, &bare[1..]),
+                _ => return Ty::Value(ident.clone(), 32),
+            };
+            match bits.parse() {
+                Ok(bits) => integer_ty(signed, bits),
+                Err(_) => Ty::Value(ident.clone(), 32),
+            }
+        }
+    }
+}
+
+fn integer_ty(signed: bool, bits: u64) -> Ty {
+    let prefix = if signed { "int" } else { "uint" };
+    Ty::Value(format!("{}{}", prefix, bits), (bits / 8).max(1) as u32)
+}
+
+/// Assigns slots with Solidity's packing rules, which Stylus storage follows too
+struct Layouter<'a> {
+    structs: &'a Structs,
+    entries: Vec<StorageEntry>,
+    slot: u64,
+    offset: u32,
+}
+
+impl<'a> Layouter<'a> {
+    fn new(structs: &'a Structs) -> Self {
+        Self { structs, entries: Vec::new(), slot: 0, offset: 0 }
+    }
+
+    fn place(&mut self, name: &str, ty: &Ty, declared_in: &str, relative: bool) {
+        if let Ty::Value(ty_name, bytes) = ty {
+            if self.offset + bytes > 32 {
+                self.slot += 1;
+                self.offset = 0;
+            }
+            self.push(name, ty_name, *bytes, 1, declared_in, relative);
+            self.offset += bytes;
+            return;
+        }
+
+        // Everything else starts and ends on a slot boundary
+        if self.offset > 0 {
+            self.slot += 1;
+            self.offset = 0;
+        }
+        let slots = self.slots(ty, &mut Vec::new());
+        self.push(name, &ty.name(), 32, slots, declared_in, relative);
+        match ty {
+            Ty::Struct(struct_name) => {
+                self.place_members(&format!("{}.", name), struct_name, self.slot, declared_in, relative);
+            }
+            Ty::Mapping(_, value) | Ty::Dynamic(_, Some(value)) => {
+                if let Ty::Struct(struct_name) = value.as_ref() {
+                    self.place_members(&format!("{}[].", name), struct_name, 0, declared_in, true);
+                }
+            }
+            _ => {}
+        }
+        self.slot += slots;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push(&mut self, name: &str, ty: &str, bytes: u32, slots: u64, declared_in: &str, relative: bool) {
+        self.entries.push(StorageEntry {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            slot: self.slot,
+            offset: self.offset,
+            bytes,
+            slots,
+            relative,
+            declared_in: declared_in.to_string(),
+        });
+    }
+
+    fn place_members(&mut self, prefix: &str, struct_name: &str, start: u64, declared_in: &str, relative: bool) {
+        let Some(fields) = self.structs.get(struct_name) else { return };
+        let mut inner = Layouter { structs: self.structs, entries: Vec::new(), slot: start, offset: 0 };
+        for (field, ty) in fields {
+            inner.place(&format!("{}{}", prefix, field), ty, declared_in, relative);
+        }
+        self.entries.extend(inner.entries);
+    }
+
+    /// Whole slots taken by a non-value type; `seen` guards against recursive structs
+    fn slots(&self, ty: &Ty, seen: &mut Vec<String>) -> u64 {
+        match ty {
+            Ty::Value(..) | Ty::Mapping(..) | Ty::Dynamic(..) => 1,
+            Ty::Fixed(_, elem, len) => match elem.as_ref() {
+                Ty::Value(_, bytes) => len.div_ceil((32 / (*bytes).max(1)) as u64),
+                other => len * self.slots(other, seen),
+            },
+            Ty::Struct(name) => {
+                if seen.contains(name) {
+                    return 1;
+                }
+                let Some(fields) = self.structs.get(name) else { return 1 };
+                seen.push(name.clone());
+                let (mut slot, mut offset) = (0u64, 0u32);
+                for (_, field) in fields {
+                    match field {
+                        Ty::Value(_, bytes) => {
+                            if offset + bytes > 32 {
+                                slot += 1;
+                                offset = 0;
+                            }
+                            offset += bytes;
+                        }
+                        other => {
+                            if offset > 0 {
+                                slot += 1;
+                                offset = 0;
+                            }
+                            slot += self.slots(other, seen);
+                        }
+                    }
+                }
+                seen.pop();
+                (slot + u64::from(offset > 0)).max(1)
+            }
+        }
+    }
+}
+
+pub fn format_layout(layout: &StorageLayout) -> String {
+    let mut output = format!("\n{}\n", format!("🗄️  Storage layout: {}", layout.contract).bright_green().bold());
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    output.push_str(&format!("{:<6} {:<6} {:<5} {:<32} {}\n", "Slot", "Offset", "Bytes", "Type", "Name"));
+
+    for entry in &layout.entries {
+        let slot = if entry.relative { format!("+{}", entry.slot) } else { entry.slot.to_string() };
+        let inherited = if entry.declared_in != layout.contract {
+            format!(" (from {})", entry.declared_in).dimmed().to_string()
+        } else {
+            String::new()
+        };
+        output.push_str(&format!("{:<6} {:<6} {:<5} {:<32} {}{}\n", slot, entry.offset, entry.bytes, entry.ty, entry.name, inherited));
+    }
+
+    for warning in &layout.warnings {
+        output.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+    output
+}
+
+pub fn format_layout_json(layout: &StorageLayout) -> Result<String> {
+    serde_json::to_string_pretty(layout)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize storage layout: {}", e)))
+}
+
+pub fn format_layout_diff_json(diff: &LayoutDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff)
+        .map_err(|e| AnalyzerError::Config(format!("cannot serialize layout comparison: {}", e)))
+}
+
+/// Storage changes between two versions of an upgradeable contract
+#[derive(Debug, Serialize)]
+pub struct LayoutDiff {
+    /// Changes that aren't pure appends; each can corrupt existing state on upgrade
+    pub findings: Vec<Vulnerability>,
+    /// Variables added after all existing ones or inside a reserved gap, which is upgrade-safe
+    pub appended: Vec<String>,
+    pub unchanged: usize,
+    /// Every old and new entry side by side, in slot order
+    pub rows: Vec<LayoutRow>,
+}
+
+/// One storage position in both versions and what happened to it
+#[derive(Debug, Serialize)]
+pub struct LayoutRow {
+    /// `slot 3 offset 0`, with `+` for slots relative to a mapping value or array element
+    pub position: String,
+    /// `name (type)` in the old version
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub verdict: Verdict,
+}
+
+/// What an upgrade does to one storage entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verdict {
+    Unchanged,
+    Appended,
+    /// A gap shrank by the slots the new variables took
+    GapShrunk,
+    Renamed,
+    Retyped,
+    Moved,
+    Removed,
+    Inserted,
+    GapResized,
+}
+
+impl Verdict {
+    /// Whether existing data ends up read as something else
+    pub fn breaking(self) -> bool {
+        matches!(self, Verdict::Retyped | Verdict::Moved | Verdict::Removed | Verdict::Inserted | Verdict::GapResized)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Verdict::Unchanged => "unchanged",
+            Verdict::Appended => "appended (safe)",
+            Verdict::GapShrunk => "gap shrunk (safe)",
+            Verdict::Renamed => "renamed",
+            Verdict::Retyped => "retyped",
+            Verdict::Moved => "moved",
+            Verdict::Removed => "removed",
+            Verdict::Inserted => "inserted",
+            Verdict::GapResized => "gap resized",
+        }
+    }
+}
+
+/// Why append-only layout evolution matters
+pub const LAYOUT_REFERENCE: &str = "https://docs.openzeppelin.com/upgrades-plugins/writing-upgradeable#modifying-your-contracts";
+/// How reserved storage gaps keep base contracts extendable
+pub const GAP_REFERENCE: &str = "https://docs.openzeppelin.com/upgrades-plugins/writing-upgradeable#storage-gaps";
+
+pub fn compare_layouts(old: &StorageLayout, new: &StorageLayout) -> LayoutDiff {
+    let mut diff = LayoutDiff { findings: Vec::new(), appended: Vec::new(), unchanged: 0, rows: Vec::new() };
+    // Rows with the entry that orders them
+    let mut rows: Vec<(&StorageEntry, LayoutRow)> = Vec::new();
+    let row = |at: &StorageEntry, old: Option<&StorageEntry>, new: Option<&StorageEntry>, verdict| LayoutRow {
+        position: match (old, new) {
+            (Some(old), Some(new)) if (old.slot, old.offset) != (new.slot, new.offset) => format!("{} -> {}", position(old), position(new)),
+            _ => position(at),
+        },
+        old: old.map(describe),
+        new: new.map(describe),
+        verdict,
+    };
+    let new_by_key: HashMap<(&str, &str), &StorageEntry> = new.entries.iter().map(|e| (e.key(), e)).collect();
+    let old_by_key: HashMap<(&str, &str), &StorageEntry> = old.entries.iter().map(|e| (e.key(), e)).collect();
+
+    let mut removed = Vec::new();
+    for old_entry in &old.entries {
+        let Some(new_entry) = new_by_key.get(&old_entry.key()) else {
+            removed.push(old_entry);
+            continue;
+        };
+        let unchanged = new_entry.ty == old_entry.ty && (new_entry.slot, new_entry.offset) == (old_entry.slot, old_entry.offset);
+        let verdict = if unchanged {
+            Verdict::Unchanged
+        } else if old_entry.is_gap() && new_entry.end() == old_entry.end() && new_entry.slots <= old_entry.slots {
+            Verdict::GapShrunk
+        } else if old_entry.is_gap() {
+            Verdict::GapResized
+        } else if new_entry.ty != old_entry.ty {
+            Verdict::Retyped
+        } else {
+            Verdict::Moved
+        };
+        rows.push((old_entry, row(old_entry, Some(old_entry), Some(new_entry), verdict)));
+        if unchanged {
+            diff.unchanged += 1;
+        } else if verdict == Verdict::GapShrunk {
+            // Shrinking a gap by exactly the slots the new variables take is the intended way to use it
+            diff.appended.push(format!("{} shrank from {} to {} ({} slot(s) used)",
+                new_entry.name, old_entry.ty, new_entry.ty, old_entry.slots - new_entry.slots));
+        } else if verdict == Verdict::GapResized {
+            diff.findings.push(collision(
+                "Storage Gap Resized Incorrectly",
+                format!("{} now ends at slot {} instead of slot {}; every variable after it shifts",
+                    old_entry.name, new_entry.slot + new_entry.slots, old_entry.slot + old_entry.slots),
+                "Shrink the gap by exactly the number of slots the new variables occupy",
+            ));
+        } else if verdict == Verdict::Retyped {
+            diff.findings.push(collision(
+                "Storage Variable Retyped",
+                format!("{} changed from {} to {}; existing data at {} is reinterpreted as the new type",
+                    old_entry.name, old_entry.ty, new_entry.ty, position(old_entry)),
+                "Keep the old type and add a new variable at the end of the layout",
+            ));
+        } else {
+            diff.findings.push(collision(
+                "Storage Variable Moved",
+                format!("{} moved from {} to {}; it will read whatever the old layout stored there",
+                    old_entry.name, position(old_entry), position(new_entry)),
+                "Restore the original declaration order; only append new variables at the end",
+            ));
+        }
+    }
+
+    let mut inserted = Vec::new();
+    for new_entry in &new.entries {
+        if old_by_key.contains_key(&new_entry.key()) {
+            continue;
+        }
+        // Members of a brand-new variable are covered by reporting the variable itself
+        let scope = new_entry.scope();
+        if !scope.is_empty() && !old_by_key.contains_key(&(new_entry.declared_in.as_str(), scope.trim_end_matches("[]"))) {
+            continue;
+        }
+        let in_scope = |e: &&StorageEntry| e.scope() == scope && e.relative == new_entry.relative;
+        let old_end = old.entries.iter().filter(in_scope).map(|e| e.end()).max().unwrap_or(0);
+        let in_gap = old.entries.iter()
+            .filter(in_scope)
+            .filter(|e| e.is_gap())
+            .find(|gap| new_entry.position() >= gap.position() && new_entry.end() <= gap.end());
+
+        if let Some(gap) = in_gap {
+            diff.appended.push(format!("{} ({}) at {}, in the reserved {}", new_entry.name, new_entry.ty, position(new_entry), gap.name));
+            rows.push((new_entry, row(new_entry, None, Some(new_entry), Verdict::Appended)));
+        } else if new_entry.position() >= old_end {
+            diff.appended.push(format!("{} ({}) at {}", new_entry.name, new_entry.ty, position(new_entry)));
+            rows.push((new_entry, row(new_entry, None, Some(new_entry), Verdict::Appended)));
+        } else {
+            inserted.push(new_entry);
+        }
+    }
+
+    // Same slot and type under a new name only changes the source, not the stored data
+    removed.retain(|old_entry| {
+        let renamed = inserted.iter().position(|new_entry| {
+            (new_entry.slot, new_entry.offset, &new_entry.ty, new_entry.relative)
+                == (old_entry.slot, old_entry.offset, &old_entry.ty, old_entry.relative)
+        });
+        match renamed {
+            Some(i) => {
+                let new_entry = inserted.remove(i);
+                rows.push((new_entry, row(new_entry, Some(old_entry), Some(new_entry), Verdict::Renamed)));
+                diff.findings.push(Vulnerability {
+                    severity: Severity::Low,
+                    ..collision(
+                        "Storage Variable Renamed",
+                        format!("{} was renamed to {} at {}; the data is kept, but off-chain tools keyed by name need updating",
+                            old_entry.name, new_entry.name, position(new_entry)),
+                        "No storage change needed; double-check the rename was intended",
+                    )
+                });
+                false
+            }
+            None => true,
+        }
+    });
+
+    for old_entry in removed {
+        rows.push((old_entry, row(old_entry, Some(old_entry), None, Verdict::Removed)));
+        diff.findings.push(collision(
+            "Storage Variable Removed",
+            format!("{} ({}) at {} was removed; its slot keeps the old value and the next variable placed there reads it",
+                old_entry.name, old_entry.ty, position(old_entry)),
+            "Keep the variable (rename it to __deprecated_* if unused) so later slots don't shift",
+        ));
+    }
+    for new_entry in inserted {
+        rows.push((new_entry, row(new_entry, None, Some(new_entry), Verdict::Inserted)));
+        diff.findings.push(collision(
+            "Storage Variable Inserted",
+            format!("{} ({}) was inserted at {}, in the middle of the existing layout; every later variable shifts",
+                new_entry.name, new_entry.ty, position(new_entry)),
+            "Move the new variable after all existing ones (or into a storage gap)",
+        ));
+    }
+
+    // Top-level variables first, then members relative to a mapping value or array element
+    rows.sort_by_key(|(entry, _)| (entry.relative, entry.slot, entry.offset));
+    diff.rows = rows.into_iter().map(|(_, row)| row).collect();
+    diff
+}
+
+fn describe(entry: &StorageEntry) -> String {
+    format!("{} ({})", entry.name, entry.ty)
+}
+
+fn position(entry: &StorageEntry) -> String {
+    let slot = if entry.relative { format!("+{}", entry.slot) } else { entry.slot.to_string() };
+    format!("slot {} offset {}", slot, entry.offset)
+}
+
+fn collision(name: &str, risk_description: String, recommendation: &str) -> Vulnerability {
+    Vulnerability {
+        name: name.to_string(),
+        severity: Severity::Critical,
+        risk_description,
+        recommendation: recommendation.to_string(),
+        reference: Some(LAYOUT_REFERENCE.to_string()),
+        ..Default::default()
+    }
+}
+
+/// The rows of `diff` as a table of position, old field, new field and verdict
+pub fn format_layout_table(diff: &LayoutDiff, old: &StorageLayout, new: &StorageLayout) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("\n{}\n", "🗄️  Storage Layout Compatibility".bright_green().bold()));
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    output.push_str(&format!("Old: {}\nNew: {}\n\n", old.contract, new.contract));
+
+    let width = |column: fn(&LayoutRow) -> Option<&String>, title: &str| {
+        diff.rows.iter().filter_map(column).map(|text| text.chars().count()).max().unwrap_or(0).max(title.len())
+    };
+    let old_width = width(|row| row.old.as_ref(), "Old field");
+    let new_width = width(|row| row.new.as_ref(), "New field");
+    let position_width = diff.rows.iter().map(|row| row.position.chars().count()).max().unwrap_or(0).max("Slot".len());
+    output.push_str(&format!("{:<position_width$}  {:<old_width$}  {:<new_width$}  {}\n", "Slot", "Old field", "New field", "Verdict"));
+    for row in &diff.rows {
+        let verdict = match row.verdict {
+            verdict if verdict.breaking() => verdict.label().red().bold(),
+            Verdict::Renamed => row.verdict.label().yellow(),
+            Verdict::Unchanged => row.verdict.label().normal(),
+            _ => row.verdict.label().green(),
+        };
+        output.push_str(&format!("{:<position_width$}  {:<old_width$}  {:<new_width$}  {}\n",
+            row.position, row.old.as_deref().unwrap_or("-"), row.new.as_deref().unwrap_or("-"), verdict));
+    }
+
+    let new_only = new.warnings.iter().filter(|warning| !old.warnings.contains(warning));
+    for warning in old.warnings.iter().chain(new_only) {
+        output.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+    let breaking = diff.rows.iter().filter(|row| row.verdict.breaking()).count();
+    output.push('\n');
+    if breaking == 0 {
+        output.push_str(&format!("{}\n", "✅ Existing storage is untouched; the upgrade is layout-safe".green()));
+    } else {
+        output.push_str(&format!("{}\n", format!("🚨 {} breaking change(s); existing data would be read as something else", breaking).red().bold()));
+    }
+    output
+}
+
+pub fn format_layout_diff(diff: &LayoutDiff, old: &StorageLayout, new: &StorageLayout) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("\n{}\n", "🗄️  Storage Layout Comparison".bright_green().bold()));
+    output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+    output.push_str(&format!("Old: {} ({} entries)\nNew: {} ({} entries)\n\n",
+        old.contract, old.entries.len(), new.contract, new.entries.len()));
+
+    let new_only = new.warnings.iter().filter(|warning| !old.warnings.contains(warning));
+    for warning in old.warnings.iter().chain(new_only) {
+        output.push_str(&format!("{}\n", format!("⚠️  {}", warning).yellow()));
+    }
+
+    if diff.findings.is_empty() {
+        output.push_str(&format!("{}\n", "✅ Existing storage is untouched; the upgrade is layout-safe".green()));
+    } else {
+        output.push_str(&format!("{}\n", format!("🚨 Not a pure append ({})", diff.findings.len()).red().bold()));
+        for finding in &diff.findings {
+            let icon = if finding.severity == Severity::Critical { "❗" } else { "📝" };
+            output.push_str(&format!("{} [{:?}] {}\n", icon, finding.severity, finding.name.bold()));
+            output.push_str(&format!("  Risk: {}\n", finding.risk_description));
+            output.push_str(&format!("  Mitigation: {}\n", finding.recommendation.bright_green()));
+            if let Some(reference) = &finding.reference {
+                output.push_str(&format!("  Reference: {}\n", reference.dimmed()));
+            }
+            output.push('\n');
+        }
+    }
+
+    if !diff.appended.is_empty() {
+        output.push_str(&format!("\n{}\n", "➕ Appended (safe)".cyan().bold()));
+        for item in &diff.appended {
+            output.push_str(&format!("  • {}\n", item));
+        }
+    }
+
+    output.push_str(&format!("\n{} unchanged storage entr{}\n", diff.unchanged, if diff.unchanged == 1 { "y" } else { "ies" }));
+    output
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each row's entry name, new name if it has one, and verdict
+    fn verdicts(old: &str, new: &str) -> (LayoutDiff, Vec<(String, Verdict)>) {
+        let diff = compare_layouts(&extract_layout(old).unwrap(), &extract_layout(new).unwrap());
+        let rows = diff.rows.iter()
+            .map(|row| {
+                let entry = row.new.as_ref().or(row.old.as_ref()).unwrap();
+                (entry.split(' ').next().unwrap().to_string(), row.verdict)
+            })
+            .filter(|(_, verdict)| *verdict != Verdict::Unchanged)
+            .collect();
+        (diff, rows)
+    }
+
+    #[test]
+    fn extracts_packed_slots_struct_members_and_mapping_values() {
+        let layout = extract_layout(include_str!("../../test ex/upgrade/staking_layout_v1.sol")).unwrap();
+        let entries: Vec<(&str, u64, u32, bool)> = layout.entries.iter()
+            .map(|entry| (entry.name.as_str(), entry.slot, entry.offset, entry.relative))
+            .collect();
+        assert_eq!(entries, [
+            ("owner", 0, 0, false), ("paused", 0, 20, false), ("totalStaked", 1, 0, false),
+            ("config", 2, 0, false), ("config.rewardRate", 2, 0, false), ("config.lockPeriod", 2, 8, false),
+            ("config.treasury", 3, 0, false), ("positions", 4, 0, false),
+            ("positions[].amount", 0, 0, true), ("positions[].since", 0, 16, true), ("stakers", 5, 0, false),
+        ]);
+    }
+
+    #[test]
+    fn only_appends_survive_an_upgrade() {
+        let (diff, rows) = verdicts(
+            include_str!("../../test ex/upgrade/staking_layout_v1.sol"),
+            include_str!("../../test ex/upgrade/staking_layout_v2.sol"),
+        );
+        assert_eq!(rows, [
+            ("totalStaked".to_string(), Verdict::Retyped),
+            ("config.lockPeriod".to_string(), Verdict::Moved),
+            ("config.maxStake".to_string(), Verdict::Inserted),
+            ("version".to_string(), Verdict::Appended),
+            ("positions[].rewardDebt".to_string(), Verdict::Appended),
+        ]);
+        let findings: Vec<(&str, Severity)> = diff.findings.iter().map(|f| (f.name.as_str(), f.severity)).collect();
+        assert_eq!(findings, [
+            ("Storage Variable Retyped", Severity::Critical),
+            ("Storage Variable Moved", Severity::Critical),
+            ("Storage Variable Inserted", Severity::Critical),
+        ]);
+        assert_eq!(diff.unchanged, 9);
+    }
+
+    #[test]
+    fn variables_taken_from_a_gap_shrink_it_safely() {
+        let (diff, rows) = verdicts(
+            include_str!("../../test ex/upgrade/staking_gap_v1.sol"),
+            include_str!("../../test ex/upgrade/staking_gap_v2.sol"),
+        );
+        assert_eq!(rows, [
+            ("__gap".to_string(), Verdict::GapShrunk),
+            ("guardian".to_string(), Verdict::Appended),
+            ("balances".to_string(), Verdict::Renamed),
+            ("version".to_string(), Verdict::Appended),
+        ]);
+        assert!(rows.iter().all(|(_, verdict)| !verdict.breaking()));
+        assert_eq!(diff.appended[1], "guardian (address) at slot 1 offset 0, in the reserved __gap");
+    }
+
+    #[test]
+    fn stylus_struct_fields_are_compared_in_declaration_order() {
+        let (diff, rows) = verdicts(
+            include_str!("../../test ex/upgrade/stylus_vault_v1.rs"),
+            include_str!("../../test ex/upgrade/stylus_vault_v2.rs"),
+        );
+        assert_eq!(rows, [
+            ("paused".to_string(), Verdict::Retyped),
+            ("total".to_string(), Verdict::Moved),
+            ("fee_bps".to_string(), Verdict::Inserted),
+            ("balances".to_string(), Verdict::Moved),
+            ("treasury".to_string(), Verdict::Appended),
+        ]);
+        assert_eq!(diff.findings.len(), 4);
+        assert!(diff.findings.iter().all(|finding| finding.severity == Severity::Critical));
+    }
+
+    #[test]
+    fn gaps_in_stylus_storage_structs_are_recognized() {
+        let layout = extract_layout(include_str!("../../test ex/upgrade/stylus_gap.rs")).unwrap();
+        let entries: Vec<(&str, u64, u64, bool)> = layout.entries.iter()
+            .map(|entry| (entry.name.as_str(), entry.slot, entry.slots, entry.is_gap()))
+            .collect();
+        assert_eq!(entries, [
+            ("ownable", 0, 1, false), ("ownable.owner", 0, 1, false), ("ownable.initialized", 0, 1, false),
+            ("limits", 1, 50, false), ("limits.max_deposit", 1, 1, false), ("limits.__gap", 2, 49, true),
+            ("total", 51, 1, false),
+        ]);
+    }
+}
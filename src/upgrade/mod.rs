@@ -0,0 +1,4 @@
+//! Static checks for upgradeable contracts
+
+pub mod layout;
+pub mod proxy;
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use colored::*;
+use quote::ToTokens;
+use serde::Serialize;
+use solang_parser::pt::{
+    CodeLocation, ContractDefinition, ContractPart, FunctionAttribute, FunctionDefinition, FunctionTy,
+    SourceUnitPart, VariableAttribute,
+};
+use crate::audit::source::{line_of, slice};
+use crate::audit::vulnerabilities::{Severity, Vulnerability};
+use crate::compare::abi::AbiSurface;
+use crate::error::{AnalyzerError, Result};
+
+/// Upgradeability pattern recognized from the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProxyPattern {
+    Uups,
+    Transparent,
+    Beacon,
+    Diamond,
+    /// Stylus contract that stores an implementation address and forwards via `delegate_call`
+    StylusDelegate,
+}
+
+impl ProxyPattern {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProxyPattern::Uups => "UUPS (ERC-1822/ERC-1967)",
+            ProxyPattern::Transparent => "Transparent proxy",
+            ProxyPattern::Beacon => "Beacon proxy",
+            ProxyPattern::Diamond => "Diamond (ERC-2535)",
+            ProxyPattern::StylusDelegate => "Stylus implementation pointer + delegate_call",
+        }
+    }
+}
+
+/// Patterns and pattern-specific findings for one file
+#[derive(Debug, Serialize)]
+pub struct ProxyReport {
+    pub file: String,
+    pub patterns: Vec<ProxyPattern>,
+    pub findings: Vec<Vulnerability>,
+}
+
+/// ERC-1967 slot labels; each slot is `keccak256(label) - 1`
+const ERC1967_SLOTS: [(&str, &str); 3] = [
+    ("IMPLEMENTATION_SLOT", "eip1967.proxy.implementation"),
+    ("ADMIN_SLOT", "eip1967.proxy.admin"),
+    ("BEACON_SLOT", "eip1967.proxy.beacon"),
+];
+
+/// Functions that replace the code a proxy runs and so must be access-controlled
+const UPGRADE_ENTRYPOINTS: [&str; 8] = [
+    "upgradeTo", "upgradeToAndCall", "changeAdmin", "setImplementation",
+    "setBeacon", "upgradeBeaconToAndCall", "diamondCut", "_authorizeUpgrade",
+];
+
+/// Words in a modifier name that suggest it restricts the caller
+const GUARD_MODIFIER_HINTS: [&str; 5] = ["only", "admin", "owner", "auth", "role"];
+
+/// Recognizes upgrade patterns in Solidity or Stylus `source` and runs the
+/// checks that apply to each
+pub fn analyze_source(source: &str, file: &str) -> Result<ProxyReport> {
+    if let Ok((unit, _)) = solang_parser::parse(source, 0) {
+        let mut report = ProxyReport { file: file.to_string(), patterns: Vec::new(), findings: Vec::new() };
+        for part in unit.0 {
+            if let SourceUnitPart::ContractDefinition(contract) = part {
+                analyze_contract(&contract, source, &mut report);
+            }
+        }
+        report.patterns.dedup();
+        return Ok(report);
+    }
+    if let Ok(parsed) = syn::parse_file(source) {
+        return Ok(analyze_stylus(&parsed, source, file));
+    }
+    Err(AnalyzerError::Parse(format!("{} is neither valid Solidity nor Rust", file)))
+}
+
+/// Whether `source` looks like it sits behind a proxy: a recognized proxy
+/// pattern, an OpenZeppelin upgradeable base, or an initializer
+pub fn is_upgradeable(source: &str) -> bool {
+    let proxied = analyze_source(source, "").is_ok_and(|report| !report.patterns.is_empty());
+    proxied
+        || source.contains("Initializable")
+        || source.contains("Upgradeable")
+        || ["function initialize", "fn initialize", "fn init("].iter().any(|hint| source.contains(hint))
+}
+
+fn analyze_contract(contract: &ContractDefinition, source: &str, report: &mut ProxyReport) {
+    let text = slice(&contract.loc, source);
+    let bases: Vec<String> = contract.base.iter()
+        .filter_map(|base| base.name.identifiers.last().map(|id| id.name.clone()))
+        .collect();
+    let inherits = |needle: &str| bases.iter().any(|base| base.contains(needle));
+    let functions: Vec<&FunctionDefinition> = contract.parts.iter()
+        .filter_map(|part| match part {
+            ContractPart::FunctionDefinition(func) => Some(func.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let defines = |name: &str| functions.iter().any(|func| func_name(func) == name);
+    let forwards = text.contains("delegatecall") || functions.iter().any(|func| func.ty == FunctionTy::Fallback);
+
+    let transparent = inherits("TransparentUpgradeableProxy")
+        || (forwards && (text.contains("ADMIN_SLOT") || text.contains("ifAdmin")));
+    let beacon = inherits("BeaconProxy") || inherits("UpgradeableBeacon")
+        || text.contains("BEACON_SLOT") || text.contains("IBeacon");
+    let diamond = defines("diamondCut") || text.contains("IDiamondCut") || text.contains("LibDiamond");
+    let uups = inherits("UUPSUpgradeable") || defines("_authorizeUpgrade")
+        || ((defines("upgradeTo") || defines("upgradeToAndCall")) && !transparent && !beacon && !diamond);
+
+    for (detected, pattern) in [
+        (uups, ProxyPattern::Uups),
+        (transparent, ProxyPattern::Transparent),
+        (beacon, ProxyPattern::Beacon),
+        (diamond, ProxyPattern::Diamond),
+    ] {
+        if detected && !report.patterns.contains(&pattern) {
+            report.patterns.push(pattern);
+        }
+    }
+
+    if uups && !defines("_authorizeUpgrade") {
+        report.findings.push(Vulnerability {
+            name: "UUPS Upgrade Authorization Missing".to_string(),
+            severity: Severity::Critical,
+            risk_description: format!("{} is upgradeable via UUPS but does not define _authorizeUpgrade, so nothing restricts who can upgrade",
+                contract.name.as_ref().map(|n| n.name.as_str()).unwrap_or("contract")),
+            recommendation: "Override _authorizeUpgrade(address) with an access-control modifier such as onlyOwner".to_string(),
+            line: Some(line_of(&contract.loc, source)),
+            ..Default::default()
+        });
+    }
+
+    for func in &functions {
+        let name = func_name(func);
+        if !UPGRADE_ENTRYPOINTS.contains(&name) || func.body.is_none() || is_guarded(func, source) {
+            continue;
+        }
+        let (title, recommendation) = if name == "_authorizeUpgrade" {
+            ("Unprotected _authorizeUpgrade", "Add onlyOwner/onlyRole or an explicit msg.sender check to _authorizeUpgrade")
+        } else {
+            ("Unprotected Upgrade Function", "Restrict the function to the proxy admin or owner")
+        };
+        report.findings.push(Vulnerability {
+            name: title.to_string(),
+            severity: Severity::Critical,
+            risk_description: format!("{} has no access control; anyone can replace the implementation", name),
+            recommendation: recommendation.to_string(),
+            function: Some(name.to_string()),
+            line: Some(line_of(&func.loc, source)),
+            ..Default::default()
+        });
+    }
+
+    check_slot_constants(contract, source, report);
+}
+
+fn func_name(func: &FunctionDefinition) -> &str {
+    func.name.as_ref().map(|n| n.name.as_str()).unwrap_or("")
+}
+
+/// True if the function has an access-control modifier, checks the caller,
+/// or delegates to the UUPS authorization hook
+fn is_guarded(func: &FunctionDefinition, source: &str) -> bool {
+    let has_guard_modifier = func.attributes.iter().any(|attr| match attr {
+        FunctionAttribute::BaseOrModifier(_, base) => base.name.identifiers.iter().any(|id| {
+            let lower = id.name.to_lowercase();
+            GUARD_MODIFIER_HINTS.iter().any(|hint| lower.contains(hint))
+        }),
+        _ => false,
+    });
+    let body = func.body.as_ref().map(|body| slice(&body.loc(), source)).unwrap_or("");
+    has_guard_modifier
+        || body.contains("msg.sender")
+        || body.contains("_authorizeUpgrade")
+        || body.contains("_checkOwner")
+        || body.contains("_checkRole")
+        || body.contains("enforceIsContractOwner")
+}
+
+/// Flags ERC-1967 slot constants whose value isn't the standard slot
+fn check_slot_constants(contract: &ContractDefinition, source: &str, report: &mut ProxyReport) {
+    for part in &contract.parts {
+        let ContractPart::VariableDefinition(var) = part else { continue };
+        if !var.attrs.iter().any(|attr| matches!(attr, VariableAttribute::Constant(_))) {
+            continue;
+        }
+        let (Some(name), Some(init)) = (&var.name, &var.initializer) else { continue };
+        let Some((_, label)) = ERC1967_SLOTS.iter().find(|(suffix, _)| name.name.ends_with(suffix)) else { continue };
+
+        let expected = erc1967_slot(label);
+        let value = slice(&init.loc(), source);
+        let correct = if let Some(hex) = value.split(|c: char| !c.is_ascii_alphanumeric()).find(|w| w.len() == 66 && w.starts_with("0x")) {
+            hex.eq_ignore_ascii_case(&expected)
+        } else {
+            // `bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)` form
+            value.contains(&format!("\"{}\"", label)) && value.replace(' ', "").contains("-1")
+        };
+
+        if !correct {
+            report.findings.push(Vulnerability {
+                name: "Incorrect ERC-1967 Slot".to_string(),
+                severity: Severity::Critical,
+                risk_description: format!("{} is not the ERC-1967 slot for {}; proxy and implementation storage can collide and tooling won't find the pointer",
+                    name.name, label),
+                recommendation: format!("Use {} (keccak256(\"{}\") - 1)", expected, label),
+                line: Some(line_of(&var.loc, source)),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// `keccak256(label) - 1` as a 0x-prefixed hex string
+fn erc1967_slot(label: &str) -> String {
+    let mut slot = ethers::utils::keccak256(label.as_bytes());
+    // Subtract one, borrowing through trailing zero bytes
+    for byte in slot.iter_mut().rev() {
+        let (value, borrow) = byte.overflowing_sub(1);
+        *byte = value;
+        if !borrow {
+            break;
+        }
+    }
+    format!("0x{}", hex::encode(slot))
+}
+
+/// Stylus proxies keep the implementation in a storage address and forward with `delegate_call`
+fn analyze_stylus(file: &syn::File, source: &str, path: &str) -> ProxyReport {
+    let mut report = ProxyReport { file: path.to_string(), patterns: Vec::new(), findings: Vec::new() };
+    if !source.contains("delegate_call") {
+        return report;
+    }
+
+    let pointer = ["implementation", "logic", "target", "impl_address"].into_iter().find(|field| {
+        source.lines().any(|line| {
+            line.contains(field) && (line.contains("StorageAddress") || line.contains("address "))
+        })
+    });
+    let Some(pointer) = pointer else { return report };
+    report.patterns.push(ProxyPattern::StylusDelegate);
+
+    let setter = format!(". {} . set (", pointer);
+    for item in &file.items {
+        let syn::Item::Impl(impl_item) = item else { continue };
+        for impl_fn in &impl_item.items {
+            let syn::ImplItem::Fn(method) = impl_fn else { continue };
+            let body = method.block.to_token_stream().to_string();
+            if !body.contains(&setter) {
+                continue;
+            }
+            let guarded = ["msg :: sender", "msg_sender", "only_owner", "only_admin", "ensure", "require", "assert"]
+                .iter()
+                .any(|guard| body.contains(guard));
+            if guarded {
+                continue;
+            }
+            let name = method.sig.ident.to_string();
+            report.findings.push(Vulnerability {
+                name: "Unprotected Implementation Pointer Update".to_string(),
+                severity: Severity::Critical,
+                risk_description: format!("{} writes the {} address used by delegate_call without checking the caller; anyone can take over the proxy",
+                    name, pointer),
+                recommendation: "Check msg::sender() against the stored owner/admin before updating the implementation".to_string(),
+                line: source.lines().position(|line| line.contains(&format!("fn {}", name))).map(|i| i + 1),
+                function: Some(name),
+                ..Default::default()
+            });
+        }
+    }
+
+    report
+}
+
+/// Selectors exported by more than one facet; a Diamond can only route each selector to one facet
+pub fn selector_clashes(files: &[(String, String)]) -> Result<Vec<Vulnerability>> {
+    let mut owners: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for (file, source) in files {
+        let surface = AbiSurface::from_source(source)?;
+        for func in surface.functions {
+            let entry = owners.entry(func.selector.clone()).or_default();
+            if !entry.iter().any(|(owner, _)| owner == file) {
+                entry.push((file.clone(), func.signature()));
+            }
+        }
+    }
+
+    Ok(owners.into_iter()
+        .filter(|(_, owners)| owners.len() > 1)
+        .map(|(selector, owners)| Vulnerability {
+            name: "Facet Selector Clash".to_string(),
+            severity: Severity::Critical,
+            risk_description: format!("selector {} is exported by {}; diamondCut will reject the cut or route calls to the wrong facet",
+                selector,
+                owners.iter().map(|(file, sig)| format!("{} in {}", sig, file)).collect::<Vec<_>>().join(" and ")),
+            recommendation: "Rename one of the functions or remove it from all but one facet".to_string(),
+            ..Default::default()
+        })
+        .collect())
+}
+
+pub fn format_proxy_report(reports: &[ProxyReport], clashes: &[Vulnerability]) -> String {
+    let mut output = String::new();
+
+    for report in reports {
+        let patterns = if report.patterns.is_empty() {
+            "no upgrade pattern recognized".to_string()
+        } else {
+            report.patterns.iter().map(|p| p.label()).collect::<Vec<_>>().join(", ")
+        };
+        output.push_str(&format!("\n{}\n", format!("🔁 Upgrade pattern: {}", patterns).bright_green().bold()));
+        output.push_str(&format!("{}\n", "═".repeat(50).bright_green()));
+        output.push_str(&format!("File: {}\n\n", report.file));
+
+        if report.findings.is_empty() && !report.patterns.is_empty() {
+            output.push_str(&format!("{}\n", "✅ Pattern-specific checks passed".green()));
+        }
+        for finding in &report.findings {
+            output.push_str(&format_finding(finding));
+        }
+    }
+
+    if reports.len() > 1 {
+        output.push_str(&format!("\n{}\n", "💎 Facet selector check".cyan().bold()));
+        if clashes.is_empty() {
+            output.push_str(&format!("{}\n", "✅ No selector is exported by more than one file".green()));
+        }
+        for clash in clashes {
+            output.push_str(&format_finding(clash));
+        }
+    }
+
+    output
+}
+
+fn format_finding(finding: &Vulnerability) -> String {
+    let line = finding.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+    format!("❗ [{:?}] {}{}\n  Risk: {}\n  Mitigation: {}\n\n",
+        finding.severity,
+        finding.name.bold(),
+        line,
+        finding.risk_description,
+        finding.recommendation.bright_green()
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(source: &str, file: &str) -> (Vec<ProxyPattern>, Vec<(String, Option<usize>)>) {
+        let report = analyze_source(source, file).unwrap();
+        (report.patterns, report.findings.into_iter().map(|v| (v.name, v.line)).collect())
+    }
+
+    #[test]
+    fn recognizes_each_proxy_pattern_and_its_flaws() {
+        let cases = [
+            (include_str!("../../test ex/upgrade/uups_vault.sol"), ProxyPattern::Uups, Some(("Unprotected _authorizeUpgrade", 20))),
+            (include_str!("../../test ex/upgrade/transparent_proxy.sol"), ProxyPattern::Transparent, Some(("Incorrect ERC-1967 Slot", 7))),
+            (include_str!("../../test ex/upgrade/upgradeable_beacon.sol"), ProxyPattern::Beacon, Some(("Unprotected Upgrade Function", 22))),
+            (include_str!("../../test ex/upgrade/diamond.sol"), ProxyPattern::Diamond, None),
+            (include_str!("../../test ex/upgrade/stylus_proxy.rs"), ProxyPattern::StylusDelegate,
+                Some(("Unprotected Implementation Pointer Update", 32))),
+        ];
+        for (source, pattern, flaw) in cases {
+            let (patterns, findings) = found(source, "fixture");
+            assert_eq!(patterns, [pattern]);
+            let flaws: Vec<(&str, usize)> = findings.iter()
+                .filter(|(name, _)| name != "No Reserved Storage Layout")
+                .map(|(name, line)| (name.as_str(), line.unwrap_or_default()))
+                .collect();
+            assert_eq!(flaws, flaw.into_iter().collect::<Vec<_>>(), "{}", pattern.label());
+        }
+    }
+
+    #[test]
+    fn facets_exporting_the_same_selector_clash() {
+        let staking = include_str!("../../test ex/upgrade/staking_facet.sol");
+        let token = include_str!("../../test ex/upgrade/token_facet.sol");
+        assert!(found(staking, "staking_facet.sol").0.is_empty());
+        assert!(found(token, "token_facet.sol").0.is_empty());
+
+        let clashes = selector_clashes(&[
+            ("staking_facet.sol".to_string(), staking.to_string()),
+            ("token_facet.sol".to_string(), token.to_string()),
+        ]).unwrap();
+        assert_eq!(clashes.len(), 1);
+        assert_eq!(clashes[0].name, "Facet Selector Clash");
+        assert!(clashes[0].risk_description.starts_with("selector 0x70a08231 is exported by balanceOf(address) in staking_facet.sol"));
+    }
+}
@@ -0,0 +1,111 @@
+//! `--watch`: keeps running and re-runs the command each time the contract,
+//! or a source file under the watched directory or glob, is saved
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use notify::event::ModifyKind;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tracing::warn;
+use stylus_analyzer::audit::findings_db;
+use stylus_analyzer::config::Config;
+use stylus_analyzer::error::{AnalyzerError, Result};
+use stylus_analyzer::input;
+
+/// Saves this close together count as one; editors often write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub struct Watcher {
+    target: PathBuf,
+    events: UnboundedReceiver<notify::Result<Event>>,
+    /// Watching stops when this is dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl Watcher {
+    /// Starts watching `target`: a contract file, a directory or a glob
+    pub fn new(target: &Path) -> Result<Self> {
+        let fail = |e: notify::Error| AnalyzerError::Config(format!("cannot watch {}: {}", target.display(), e));
+        let (sender, events) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .map_err(fail)?;
+        // A single file is watched through its directory: editors that save by
+        // writing a new file and renaming it over the old one replace the inode
+        let (root, mode) = if input::is_multi_file(target) {
+            (glob_root(target), RecursiveMode::Recursive)
+        } else {
+            let parent = target.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            (parent.to_path_buf(), RecursiveMode::NonRecursive)
+        };
+        watcher.watch(&root, mode).map_err(fail)?;
+        Ok(Self { target: target.to_path_buf(), events, _watcher: watcher })
+    }
+
+    /// Waits until a contract the command analyzes is written, created or
+    /// removed, then until saves stop for `DEBOUNCE`. Files the run itself
+    /// skips (other files next to a single contract, `ignore` globs) don't count
+    pub async fn changed(&mut self, config: &Config) -> Result<()> {
+        loop {
+            let Some(event) = self.events.recv().await else {
+                return Err(AnalyzerError::Config(format!("stopped watching {}", self.target.display())));
+            };
+            let mut paths = self.content_changes(event);
+            if paths.is_empty() {
+                continue;
+            }
+            while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {
+                paths.extend(self.content_changes(event));
+            }
+
+            // Collected again each time, so files created since the last run count
+            let multi_file = input::is_multi_file(&self.target);
+            let sources: Vec<PathBuf> = input::collect_sources(&self.target).unwrap_or_default().into_iter()
+                .filter(|file| !(multi_file && config.is_ignored(file)))
+                .map(|file| canonical(&file))
+                .collect();
+            if paths.iter().any(|path| sources.contains(&canonical(path))) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Paths whose contents `event` changed. Opening and reading a file, as
+    /// every run does, is an event too and must not trigger the next run
+    fn content_changes(&self, event: notify::Result<Event>) -> Vec<PathBuf> {
+        match event {
+            Ok(event) => match event.kind {
+                EventKind::Create(_) | EventKind::Remove(_) => event.paths,
+                EventKind::Modify(kind) if !matches!(kind, ModifyKind::Metadata(_)) => event.paths,
+                _ => Vec::new(),
+            },
+            Err(e) => {
+                warn!("⚠️  Watching {}: {}", self.target.display(), e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Line printed before each run: "🔄 Run 3 · 14:02:11 UTC"
+pub fn delimiter(run: usize) -> String {
+    let seconds = findings_db::now() % 86_400;
+    format!("🔄 Run {} · {:02}:{:02}:{:02} UTC", run, seconds / 3_600, seconds / 60 % 60, seconds % 60)
+}
+
+/// The directory a glob pattern searches: its components before the first wildcard
+fn glob_root(pattern: &Path) -> PathBuf {
+    if pattern.is_dir() {
+        return pattern.to_path_buf();
+    }
+    let root: PathBuf = pattern.components()
+        .take_while(|component| !component.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect();
+    if root.as_os_str().is_empty() { PathBuf::from(".") } else { root }
+}
+
+/// `path` resolved through symlinks and `..`, or as given once it's deleted
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Router that forwards calls through inline assembly. Expected findings: the
+/// assembly note, the delegatecall, a computed-slot sstore and a clobbered
+/// free memory pointer.
+contract DelegatingRouter {
+    address public owner;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    // assembly { this comment is not a block }
+    function forward(address target, bytes calldata data) external returns (bytes memory result) {
+        require(msg.sender == owner, "not owner");
+        assembly {
+            let ptr := mload(0x40)
+            calldatacopy(ptr, data.offset, data.length)
+            let ok := delegatecall(gas(), target, ptr, data.length, 0, 0)
+            if iszero(ok) { revert(0, 0) }
+            mstore(0x40, add(ptr, returndatasize()))
+        }
+        return result;
+    }
+
+    function setCounter(uint256 key, uint256 value) external {
+        assembly {
+            sstore(add(key, 7), value)
+        }
+    }
+
+    function counter(uint256 key) external view returns (uint256 value) {
+        assembly ("memory-safe") {
+            value := sload(keccak256(0, 32))
+            mstore(0x00, key)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Admin functions with every kind of guard. upgradeTo is callable instantly by
+/// the deployer key (High); setOracle has no guard at all.
+contract MixedAdminVault {
+    bytes32 public constant FEE_ROLE = keccak256("FEE_ROLE");
+
+    address public owner;
+    address public implementation;
+    address public treasury;
+    address public oracle;
+    uint256 public feeBps;
+    mapping(bytes32 => mapping(address => bool)) private roles;
+    mapping(bytes32 => uint256) public eta;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    modifier onlyRole(bytes32 role) {
+        require(roles[role][msg.sender], "missing role");
+        _;
+    }
+
+    modifier afterDelay(bytes32 id) {
+        require(eta[id] != 0 && block.timestamp >= eta[id], "not ready");
+        _;
+    }
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    function upgradeTo(address newImplementation) external onlyOwner {
+        implementation = newImplementation;
+    }
+
+    function setFeeBps(uint256 newFee) external onlyRole(FEE_ROLE) {
+        feeBps = newFee;
+    }
+
+    function sweepTreasury(uint256 amount) external onlyOwner afterDelay(keccak256("sweep")) {
+        payable(treasury).transfer(amount);
+    }
+
+    function setOracle(address newOracle) external {
+        oracle = newOracle;
+    }
+
+    function withdrawFees(address to) external {
+        require(msg.sender == treasury, "not treasury");
+        payable(to).transfer(address(this).balance);
+    }
+}
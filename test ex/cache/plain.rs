@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) Example Labs. All rights reserved; shared header for every contract in the project.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::U256, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Plain {
+        uint256 count;
+    }
+}
+
+#[public]
+impl Plain {
+    pub fn count(&self) -> U256 {
+        self.count.get()
+    }
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) Example Labs. All rights reserved; shared header for every contract in the project.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::U256, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Raw {
+        uint256 count;
+    }
+}
+
+#[public]
+impl Raw {
+    pub fn bump(&mut self, ptr: *mut u8) {
+        unsafe { *ptr = 1; }
+        let current = self.count.get();
+        self.count.set(current + U256::from(1));
+    }
+}
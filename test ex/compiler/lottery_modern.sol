@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+/// Same contract as lottery_legacy.sol under 0.8.24: overflow reverts, but the
+/// unchecked refund and the Cancun selfdestruct semantics get flagged.
+contract Lottery {
+    address public owner;
+    address[] public players;
+    uint256 public pot;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    function enter() external payable {
+        players.push(msg.sender);
+        pot += msg.value;
+    }
+
+    function pickWinner() external {
+        require(msg.sender == owner, "not owner");
+        uint256 random = uint256(keccak256(abi.encodePacked(block.prevrandao, block.timestamp, players.length)));
+        address winner = players[random % players.length];
+        pot = 0;
+        payable(winner).transfer(address(this).balance);
+    }
+
+    function refund(uint256 amount) external {
+        for (uint256 i = 0; i < players.length; i++) {
+            if (players[i] == msg.sender) {
+                unchecked { pot -= amount; }
+                payable(msg.sender).transfer(amount);
+            }
+        }
+    }
+
+    function close() external {
+        require(msg.sender == owner, "not owner");
+        selfdestruct(payable(owner));
+    }
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IOracle {
+    function price() external view returns (uint256);
+}
+
+/// The classic constructor bug: `owner = owner;` assigns the parameter to
+/// itself, so owner stays address(0) and onlyOwner can never pass
+contract FeeVault {
+    address public owner;
+    address public treasury;
+    IOracle public oracle;
+    uint256 public feeBps = 30;
+    uint256 public collected;
+    address public guardian;
+
+    constructor(address owner, address _treasury, IOracle _oracle) {
+        owner = owner;
+        treasury = _treasury;
+        oracle = _oracle;
+    }
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    function pause() external {
+        require(msg.sender == guardian, "not guardian");
+        collected = 0;
+    }
+
+    function collect() external payable {
+        collected += msg.value * oracle.price() * feeBps / 10_000;
+    }
+
+    function sweep() external onlyOwner {
+        payable(treasury).transfer(address(this).balance);
+    }
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+library AddressAliasHelper {
+    uint160 internal constant OFFSET = uint160(0x1111000000000000000000000000000000001111);
+
+    function applyL1ToL2Alias(address l1Address) internal pure returns (address l2Address) {
+        unchecked {
+            l2Address = address(uint160(l1Address) + OFFSET);
+        }
+    }
+}
+
+// L2 side of a bridge: retryable tickets from the L1 counterpart, checked against its alias
+contract AliasedReceiver {
+    address public immutable l1Counterpart;
+    mapping(address => uint256) public minted;
+
+    constructor(address _l1Counterpart) {
+        l1Counterpart = _l1Counterpart;
+    }
+
+    modifier onlyFromL1Counterpart() {
+        require(msg.sender == AddressAliasHelper.applyL1ToL2Alias(l1Counterpart), "not from L1 counterpart");
+        _;
+    }
+
+    function finalizeDepositFromL1(address to, uint256 amount) external onlyFromL1Counterpart {
+        minted[to] += amount;
+    }
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// L2 side of a bridge that gets the sender check wrong
+contract DirectReceiver {
+    address public immutable l1Counterpart;
+    mapping(address => uint256) public minted;
+    uint256 public rate;
+
+    constructor(address _l1Counterpart) {
+        l1Counterpart = _l1Counterpart;
+    }
+
+    // Compares with the L1 address itself: retryables arrive from its alias
+    function finalizeDepositFromL1(address to, uint256 amount) external {
+        require(msg.sender == l1Counterpart, "not from L1 counterpart");
+        minted[to] += amount;
+    }
+
+    // No sender check at all
+    function setRateFromL1(uint256 newRate) external {
+        rate = newRate;
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IBridge {
+    function activeOutbox() external view returns (address);
+}
+
+interface IOutbox {
+    function l2ToL1Sender() external view returns (address);
+}
+
+// L1 side: messages from L2 are executed through the Outbox
+contract L1Receiver {
+    IBridge public immutable bridge;
+    address public immutable l2Counterpart;
+    mapping(address => uint256) public released;
+
+    constructor(IBridge _bridge, address _l2Counterpart) {
+        bridge = _bridge;
+        l2Counterpart = _l2Counterpart;
+    }
+
+    // Checks the bridge and the Outbox's record of the L2 sender
+    function releaseFromL2(address to, uint256 amount) external {
+        require(msg.sender == address(bridge), "not bridge");
+        require(IOutbox(bridge.activeOutbox()).l2ToL1Sender() == l2Counterpart, "not from L2 counterpart");
+        released[to] += amount;
+    }
+
+    // Trusts any message the bridge delivers, whoever sent it on L2
+    function claimFromL2(address to, uint256 amount) external {
+        require(msg.sender == address(bridge), "not bridge");
+        released[to] += amount;
+    }
+}
@@ -0,0 +1,40 @@
+// Stylus L2 receiver for retryable tickets from an L1 contract
+use stylus_sdk::{alloy_primitives::{Address, U160, U256}, msg, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Receiver {
+        address l1_counterpart;
+        mapping(address => uint256) minted;
+    }
+}
+
+const ALIAS_OFFSET: U160 = U160::from_limbs([0x0000000000001111, 0x0000000000000000, 0x11110000]);
+
+#[public]
+impl Receiver {
+    /// Compares with the aliased L1 address
+    pub fn mint_from_l1(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        if msg::sender() != apply_l1_to_l2_alias(self.l1_counterpart.get()) {
+            return Err(b"not from L1 counterpart".to_vec());
+        }
+        let minted = self.minted.get(to);
+        self.minted.insert(to, minted + amount);
+        Ok(())
+    }
+
+    /// Compares with the L1 address itself
+    pub fn burn_from_l1(&mut self, from: Address, amount: U256) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.l1_counterpart.get() {
+            return Err(b"not from L1 counterpart".to_vec());
+        }
+        let minted = self.minted.get(from);
+        self.minted.insert(from, minted - amount);
+        Ok(())
+    }
+}
+
+fn apply_l1_to_l2_alias(l1: Address) -> Address {
+    let aliased = U160::from_be_bytes(l1.into_array()).wrapping_add(ALIAS_OFFSET);
+    Address::from(aliased.to_be_bytes())
+}
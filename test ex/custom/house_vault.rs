@@ -0,0 +1,25 @@
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::Address, call::RawCall, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct HouseVault {
+        address target;
+    }
+}
+
+#[public]
+impl HouseVault {
+    pub fn forward(&mut self, data: Vec<u8>) -> Result<Vec<u8>, Vec<u8>> {
+        let target = self.target.get();
+        dbg!(&data);
+        unsafe { RawCall::new().call(target, &data) }
+    }
+
+    pub fn forward_reviewed(&mut self, data: Vec<u8>) -> Result<Vec<u8>, Vec<u8>> {
+        let target: Address = self.target.get();
+        unsafe { RawCall::new().call(target, &data) } // reviewed: target is fixed at deployment
+    }
+}
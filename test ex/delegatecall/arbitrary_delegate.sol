@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Seeded flaws: `execute` delegatecalls any address the caller names and
+/// ignores the result; `setLogic` lets anyone replace the logic contract that
+/// `fallback` dispatches to; `callLogic` drops the success flag
+contract ArbitraryDelegate {
+    address public owner;
+    address public logic;
+
+    constructor(address _logic) {
+        owner = msg.sender;
+        logic = _logic;
+    }
+
+    function execute(address target, bytes calldata data) external {
+        target.delegatecall(data);
+    }
+
+    function setLogic(address newLogic) external {
+        logic = newLogic;
+    }
+
+    function callLogic(bytes calldata data) external {
+        (bool ok, ) = logic.delegatecall(data);
+        emit Called(data.length);
+    }
+
+    event Called(uint256 size);
+
+    fallback() external payable {
+        address impl = logic;
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            pop(delegatecall(gas(), impl, 0, calldatasize(), 0, 0))
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Stylus router that delegatecalls a caller-chosen module.
+//! Seeded flaws: `run_module` takes the target from its arguments and drops
+//! the result; `run_default` uses owner-set storage, which is fine.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{
+    alloy_primitives::Address,
+    call::RawCall,
+    msg,
+    prelude::*,
+};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Router {
+        address default_module;
+        address owner;
+    }
+}
+
+#[public]
+impl Router {
+    pub fn set_default_module(&mut self, module: Address) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.owner.get() {
+            return Err(b"not owner".to_vec());
+        }
+        self.default_module.set(module);
+        Ok(())
+    }
+
+    pub fn run_module(&mut self, module: Address, data: Vec<u8>) -> Result<(), Vec<u8>> {
+        let _ = unsafe { RawCall::new_delegate().call(module, &data) };
+        Ok(())
+    }
+
+    pub fn run_default(&mut self, data: Vec<u8>) -> Result<Vec<u8>, Vec<u8>> {
+        let module = self.default_module.get();
+        let output = unsafe { RawCall::new_delegate().call(module, &data)? };
+        Ok(output)
+    }
+}
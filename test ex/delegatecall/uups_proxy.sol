@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// UUPS-style proxy: the implementation lives in the ERC-1967 slot, only the
+/// owner can change it, and a failed delegatecall reverts. Nothing to report
+contract UupsProxy {
+    bytes32 internal constant IMPLEMENTATION_SLOT = 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+    address public owner;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    constructor(address implementation) {
+        owner = msg.sender;
+        _setImplementation(implementation);
+    }
+
+    function upgradeTo(address newImplementation) external onlyOwner {
+        _setImplementation(newImplementation);
+    }
+
+    function _setImplementation(address newImplementation) internal {
+        require(newImplementation.code.length > 0, "not a contract");
+        assembly {
+            sstore(IMPLEMENTATION_SLOT, newImplementation)
+        }
+    }
+
+    function _implementation() internal view returns (address impl) {
+        assembly {
+            impl := sload(IMPLEMENTATION_SLOT)
+        }
+    }
+
+    function callImplementation(bytes calldata data) external onlyOwner returns (bytes memory) {
+        (bool success, bytes memory result) = _implementation().delegatecall(data);
+        require(success, "delegatecall failed");
+        return result;
+    }
+
+    fallback() external payable {
+        address impl = _implementation();
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            let result := delegatecall(gas(), impl, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch result
+            case 0 { revert(0, returndatasize()) }
+            default { return(0, returndatasize()) }
+        }
+    }
+}
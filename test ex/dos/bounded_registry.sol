@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Holders leave through swap-and-pop, so the list only holds current members.
+/// Nothing to report
+contract BoundedRegistry {
+    address[] public holders;
+    mapping(address => uint256) public indexOf;
+
+    function join() external {
+        require(indexOf[msg.sender] == 0, "member");
+        holders.push(msg.sender);
+        indexOf[msg.sender] = holders.length;
+    }
+
+    function leave() external {
+        uint256 index = indexOf[msg.sender];
+        require(index != 0, "not a member");
+        address last = holders[holders.length - 1];
+        holders[index - 1] = last;
+        indexOf[last] = index;
+        holders.pop();
+        delete indexOf[msg.sender];
+    }
+
+    function memberCount() external view returns (uint256) {
+        return holders.length;
+    }
+}
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Seeded flaw: `register` appends to `holders` forever and `totalBalance`
+/// loops over all of them
+contract HolderRegistry {
+    address[] public holders;
+    mapping(address => uint256) public balances;
+
+    function register(uint256 amount) external {
+        holders.push(msg.sender);
+        balances[msg.sender] += amount;
+    }
+
+    function totalBalance() external view returns (uint256 total) {
+        for (uint256 i = 0; i < holders.length; i++) {
+            total += balances[holders[i]];
+        }
+    }
+}
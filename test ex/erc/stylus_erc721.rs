@@ -0,0 +1,61 @@
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::sol;
+use stylus_sdk::{evm, msg, prelude::*};
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 indexed token_id);
+    event Approval(address indexed owner, address indexed approved, uint256 indexed token_id);
+    event ApprovalForAll(address indexed owner, address indexed operator, bool approved);
+}
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Collectible {
+        mapping(uint256 => address) owners;
+        mapping(address => uint256) balances;
+        mapping(uint256 => address) approvals;
+        mapping(address => mapping(address => bool)) operators;
+    }
+}
+
+#[public]
+impl Collectible {
+    pub fn balance_of(&self, owner: Address) -> U256 {
+        self.balances.get(owner)
+    }
+
+    pub fn owner_of(&self, token_id: U256) -> Address {
+        self.owners.get(token_id)
+    }
+
+    pub fn transfer_from(&mut self, from: Address, to: Address, token_id: U256) {
+        self.move_token(from, to, token_id);
+    }
+
+    pub fn approve(&mut self, approved: Address, token_id: U256) {
+        self.approvals.insert(token_id, approved);
+        evm::log(Approval { owner: msg::sender(), approved, token_id });
+    }
+
+    pub fn set_approval_for_all(&mut self, operator: Address, approved: bool) {
+        self.operators.setter(msg::sender()).insert(operator, approved);
+    }
+
+    pub fn get_approved(&self, token_id: U256) -> Address {
+        self.approvals.get(token_id)
+    }
+
+    pub fn is_approved_for_all(&self, owner: Address, operator: Address) -> bool {
+        self.operators.getter(owner).get(operator)
+    }
+}
+
+impl Collectible {
+    fn move_token(&mut self, from: Address, to: Address, token_id: U256) {
+        self.owners.insert(token_id, to);
+        evm::log(Transfer { from, to, token_id });
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// One event per indexing case: well indexed, unfilterable, too many topics,
+/// and an indexed string that only survives as a hash
+contract EventIndexing {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Deposit(address account, uint256 amount);
+    event Settled(address indexed buyer, address indexed seller, uint256 indexed orderId, uint256 indexed price);
+    event Registered(string indexed name, address indexed owner);
+
+    function deposit() external payable {
+        emit Deposit(msg.sender, msg.value);
+    }
+}
@@ -0,0 +1,38 @@
+// Stylus vault where only deposits are logged
+use stylus_sdk::{alloy_primitives::{Address, U256}, alloy_sol_types::sol, evm, msg, prelude::*};
+
+sol! {
+    event Deposited(address indexed account, uint256 amount);
+}
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Vault {
+        address admin;
+        mapping(address => uint256) balances;
+    }
+}
+
+#[public]
+impl Vault {
+    pub fn deposit(&mut self, amount: U256) {
+        let account = msg::sender();
+        let balance = self.balances.get(account);
+        self.balances.insert(account, balance + amount);
+        evm::log(Deposited { account, amount });
+    }
+
+    pub fn withdraw(&mut self, amount: U256) {
+        let account = msg::sender();
+        let balance = self.balances.get(account);
+        self.balances.insert(account, balance - amount);
+    }
+
+    pub fn set_admin(&mut self, admin: Address) {
+        self.admin.set(admin);
+    }
+
+    pub fn balance_of(&self, account: Address) -> U256 {
+        self.balances.get(account)
+    }
+}
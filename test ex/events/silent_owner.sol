@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract Treasury {
+    address public owner;
+    mapping(address => uint256) public balances;
+
+    event Deposited(address indexed account, uint256 amount);
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    // Emits through a helper: not reported
+    function deposit() external payable {
+        _credit(msg.sender, msg.value);
+    }
+
+    // Hands over control silently: reported
+    function setOwner(address next) external onlyOwner {
+        owner = next;
+    }
+
+    // Moves ether silently: reported
+    function sweep(address payable to) external onlyOwner {
+        to.transfer(address(this).balance);
+    }
+
+    function balanceOf(address account) external view returns (uint256) {
+        return balances[account];
+    }
+
+    function _credit(address account, uint256 amount) internal {
+        balances[account] += amount;
+        emit Deposited(account, amount);
+    }
+}
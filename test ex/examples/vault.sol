@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.7.6;
+
+contract Vault {
+    mapping(address => uint256) public balances;
+    uint256 public feeBps;
+    uint256 public totalFees;
+
+    function deposit() external payable {
+        balances[msg.sender] += msg.value;
+    }
+
+    function withdraw() external {
+        uint256 amount = balances[msg.sender];
+        (bool ok, ) = msg.sender.call{value: amount}("");
+        require(ok, "send failed");
+        balances[msg.sender] = 0;
+    }
+
+    function chargeAll(address[] calldata users) external {
+        for (uint256 i = 0; i < users.length; i++) {
+            uint256 fee = balances[users[i]] * feeBps / 10000;
+            balances[users[i]] -= fee;
+            totalFees += fee;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use stylus_sdk::prelude::*;
+use stylus_sdk::alloy_primitives::U256;
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Batch {
+        uint256 total;
+    }
+}
+
+#[public]
+impl Batch {
+    pub fn defaults(&self) -> Vec<U256> {
+        let mut v = Vec::new();
+        v.push(U256::from(1));
+        v.push(U256::from(2));
+        v.push(U256::from(3));
+        v
+    }
+
+    pub fn sum(&self, values: Vec<U256>) -> U256 {
+        let mut total = U256::ZERO;
+        for value in summed(&values.clone()) {
+            total += value;
+        }
+        total
+    }
+}
+
+fn summed(values: &[U256]) -> Vec<U256> {
+    values.to_vec()
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract Registry {
+    event Registered(address owner, string label);
+
+    mapping(bytes32 => address) public owners;
+
+    function register(string memory label) external {
+        bytes32 key = keccak256(bytes(label));
+        require(owners[key] == address(0), "taken");
+        owners[key] = msg.sender;
+        emit Registered(msg.sender, label);
+    }
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.10;
+
+/// Each function triggers one micro-optimization; the `Tuned` variants below
+/// are the fixed versions and must not trigger anything.
+contract Payroll {
+    address[] public staff;
+    uint256 public rate;
+    mapping(address => uint256) public paid;
+
+    function payAll() external {
+        for (uint256 i = 0; i < staff.length; i++) {
+            paid[staff[i]] += rate;
+        }
+    }
+
+    function register(address[] memory people) external {
+        for (uint256 i; i < 10; ++i) {
+            staff.push(people[i]);
+        }
+    }
+
+    function withdraw(uint256 amount) external {
+        require(amount > 0, "Payroll: withdrawal amount must be greater than zero");
+        paid[msg.sender] -= amount;
+    }
+
+    function payAllTuned() external {
+        uint256 cachedRate = rate;
+        uint256 len = staff.length;
+        for (uint256 i = 0; i < len; ++i) {
+            paid[staff[i]] += cachedRate;
+        }
+    }
+
+    function registerTuned(address[] calldata people) external {
+        for (uint256 i; i < 10; ++i) {
+            staff.push(people[i]);
+        }
+    }
+
+    function trim(uint256[] memory amounts) external pure returns (uint256) {
+        amounts[0] = 0;
+        return amounts.length;
+    }
+
+    function withdrawTuned(uint256 amount) external {
+        require(amount != 0, "zero amount");
+        paid[msg.sender] -= amount;
+    }
+}
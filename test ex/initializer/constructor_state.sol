@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Broken: the constructor sets the fee, which the proxy never sees, and the
+/// hand-rolled flag is checked but never set, so initialize() can be re-run.
+contract FeeVaultConstructor {
+    bool private initialized;
+    address public owner;
+    uint256 public feeBps;
+
+    constructor() {
+        feeBps = 30;
+    }
+
+    function initialize(address owner_) external {
+        require(!initialized, "already initialized");
+        owner = owner_;
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+
+/// Correct OZ-style initializer: `initializer` modifier, no constructor state,
+/// and initializers disabled on the implementation.
+contract FeeVault is Initializable {
+    address public owner;
+    uint256 public feeBps;
+
+    /// @custom:oz-upgrades-unsafe-allow constructor
+    constructor() {
+        _disableInitializers();
+    }
+
+    function initialize(address owner_, uint256 feeBps_) external initializer {
+        owner = owner_;
+        feeBps = feeBps_;
+    }
+
+    function setFee(uint256 feeBps_) external {
+        require(msg.sender == owner, "not owner");
+        feeBps = feeBps_;
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Broken: initialize() has no guard, so anyone can call it again and become owner.
+contract FeeVaultUnguarded {
+    address public owner;
+    uint256 public feeBps;
+
+    function initialize(address owner_, uint256 feeBps_) external {
+        owner = owner_;
+        feeBps = feeBps_;
+    }
+
+    function setFee(uint256 feeBps_) external {
+        require(msg.sender == owner, "not owner");
+        feeBps = feeBps_;
+    }
+}
@@ -0,0 +1,28 @@
+//! A read-only counter whose only mentions of unsafe code, external calls and
+//! the owner are in comments and strings. The memory safety, reentrancy and
+//! access control rules report nothing for it
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use alloc::string::String;
+use stylus_sdk::{alloy_primitives::U256, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Counter {
+    count: StorageU256,
+}
+
+#[public]
+impl Counter {
+    /* Nothing here is unsafe: there is no unsafe block, no raw pointer and
+       no *mut or *const, and no msg::send or call(Call::new()) either */
+    pub fn count(&self) -> U256 {
+        self.count.get() // read-only, so no only_owner check is needed
+    }
+
+    /// Describes the contract, e.g. "not unsafe; never calls out; no owner"
+    pub fn describe(&self) -> String {
+        String::from("not unsafe; never calls msg::send; no owner or admin role")
+    }
+}
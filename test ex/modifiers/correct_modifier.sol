@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Reference case: the modifier checks the caller before `_;` and is applied.
+contract CorrectModifier {
+    address public owner;
+    uint256 public feeBps;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    function setFeeBps(uint256 newFee) external onlyOwner {
+        feeBps = newFee;
+    }
+}
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// onlyOwner forgets `_;`: setFee always succeeds and never changes anything.
+contract MissingPlaceholder {
+    address public owner;
+    uint256 public feeBps;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+    }
+
+    function setFee(uint256 newFee) external onlyOwner {
+        feeBps = newFee;
+    }
+}
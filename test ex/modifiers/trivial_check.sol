@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Both modifiers let every caller through.
+contract TrivialCheck {
+    address public admin;
+    address public treasury;
+
+    constructor() {
+        admin = msg.sender;
+    }
+
+    modifier onlyAdmin() {
+        require(msg.sender != address(0), "zero sender");
+        _;
+    }
+
+    modifier onlyTreasury() {
+        _;
+    }
+
+    function setTreasury(address newTreasury) external onlyAdmin {
+        treasury = newTreasury;
+    }
+
+    function sweep() external onlyTreasury {
+        payable(treasury).transfer(address(this).balance);
+    }
+}
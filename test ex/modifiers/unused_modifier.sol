@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// onlyOwner is defined but never applied; setFeeBps and pause are open to anyone.
+contract UnusedModifier {
+    address public owner;
+    uint256 public feeBps;
+    bool public paused;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    function setFeeBps(uint256 newFee) external {
+        feeBps = newFee;
+    }
+
+    function pause() external {
+        paused = true;
+    }
+
+    function deposit() external payable {}
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title Vault with half of its external surface documented
+contract HalfDocumentedVault {
+    mapping(address => uint256) public balances;
+
+    /// @notice Deposit ether for the caller
+    function deposit() external payable {
+        balances[msg.sender] += msg.value;
+    }
+
+    /// @notice Withdraw part of the caller's balance
+    /// @param amount Wei to withdraw
+    function withdraw(uint256 amount) external {
+        balances[msg.sender] -= amount;
+        payable(msg.sender).transfer(amount);
+    }
+
+    /// @notice Balance of an account
+    /// @param account Account to look up
+    /// @return Wei held for the account
+    function balanceOf(address account) external view returns (uint256) {
+        return balances[account];
+    }
+
+    /// @dev Missing @param for `to` and `amount`
+    function transfer(address to, uint256 amount) external {
+        balances[msg.sender] -= amount;
+        balances[to] += amount;
+    }
+
+    function sweep(address to) external {
+        payable(to).transfer(address(this).balance);
+    }
+
+    function totalHeld() public view returns (uint256) {
+        return address(this).balance;
+    }
+
+    function _credit(address account, uint256 amount) internal {
+        balances[account] += amount;
+    }
+}
@@ -0,0 +1,39 @@
+// Stylus vault minting shares at the pool's spot price
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*};
+
+sol_interface! {
+    interface IPair {
+        function getReserves() external view returns (uint112, uint112, uint32);
+    }
+}
+
+sol_storage! {
+    #[entrypoint]
+    pub struct SpotVault {
+        address pair;
+        mapping(address => uint256) shares;
+    }
+}
+
+#[public]
+impl SpotVault {
+    pub fn deposit(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        let price = self.spot_price()?;
+        let minted = amount * price / U256::from(10).pow(U256::from(18));
+        self._mint(msg::sender(), minted);
+        Ok(())
+    }
+}
+
+impl SpotVault {
+    fn spot_price(&mut self) -> Result<U256, Vec<u8>> {
+        let pair = IPair::new(self.pair.get());
+        let (reserve0, reserve1, _) = pair.get_reserves(self)?;
+        Ok(U256::from(reserve1) * U256::from(10).pow(U256::from(18)) / U256::from(reserve0))
+    }
+
+    fn _mint(&mut self, to: Address, amount: U256) {
+        let held = self.shares.get(to);
+        self.shares.insert(to, held + amount);
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IERC20 {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+interface IFeed {
+    function latestRoundData() external view returns (uint80, int256, uint256, uint256, uint80);
+}
+
+library OracleLibrary {
+    function consult(address pool, uint32 secondsAgo) internal view returns (int24 tick, uint128 liquidity) {}
+    function getQuoteAtTick(int24 tick, uint128 amount, address base, address quote) internal pure returns (uint256) {}
+}
+
+// Prices collateral from a 30-minute TWAP and a checked Chainlink feed; nothing to report
+contract TwapLending {
+    uint32 public constant TWAP_WINDOW = 1800;
+    uint256 public constant HEARTBEAT = 3600;
+    address public pool;
+    address public collateral;
+    address public quoteToken;
+    IERC20 public stable;
+    IFeed public feed;
+    mapping(address => uint256) public deposits;
+    mapping(address => uint256) public debt;
+
+    function twapPrice() public view returns (uint256) {
+        (int24 tick, ) = OracleLibrary.consult(pool, TWAP_WINDOW);
+        return OracleLibrary.getQuoteAtTick(tick, 1e18, collateral, quoteToken);
+    }
+
+    function feedPrice() public view returns (uint256) {
+        (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData();
+        require(block.timestamp - updatedAt <= HEARTBEAT, "stale price");
+        require(answer > 0, "bad price");
+        return uint256(answer);
+    }
+
+    function borrow(uint256 amount) external {
+        uint256 value = deposits[msg.sender] * twapPrice() / 1e18;
+        require(value * 1e18 / feedPrice() > 0, "no value");
+        require(debt[msg.sender] + amount <= value / 2, "undercollateralized");
+        debt[msg.sender] += amount;
+        stable.transfer(msg.sender, amount);
+    }
+}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Only logs tx.origin, which is reported as Low
+contract Tipper {
+    event Tipped(address indexed sender, address indexed origin, uint256 amount);
+
+    function tip() external payable {
+        emit Tipped(msg.sender, tx.origin, msg.value);
+    }
+}
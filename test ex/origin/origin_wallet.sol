@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Wallet that authenticates with tx.origin: transfer and setOwner are
+/// flagged High, the event only Low, and the EOA check not at all
+contract OriginWallet {
+    address public owner;
+
+    event Deposited(address indexed origin, uint256 amount);
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    receive() external payable {
+        emit Deposited(tx.origin, msg.value);
+    }
+
+    function transfer(address payable to, uint256 amount) external {
+        require(tx.origin == owner, "not owner");
+        to.transfer(amount);
+    }
+
+    function setOwner(address newOwner) external {
+        if (owner != tx.origin) {
+            revert("not owner");
+        }
+        owner = newOwner;
+    }
+
+    function mintForHumans() external view returns (bool) {
+        require(tx.origin == msg.sender, "no contracts");
+        return true;
+    }
+}
@@ -0,0 +1,39 @@
+//! Stylus treasury that saves the deployer at construction and authorizes
+//! `sweep` against `operator`, a copy of it, without looking at the caller.
+//! `set_operator` checks msg::sender() and isn't flagged
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use stylus_sdk::{alloy_primitives::{Address, U256}, call::transfer_eth, msg, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Treasury {
+    owner: StorageAddress,
+    operator: StorageAddress,
+}
+
+#[public]
+impl Treasury {
+    #[constructor]
+    pub fn constructor(&mut self) {
+        self.owner.set(msg::sender());
+        self.operator.set(msg::sender());
+    }
+
+    pub fn sweep(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        if self.operator.get() != self.owner.get() {
+            return Err(b"not authorized".to_vec());
+        }
+        transfer_eth(to, amount)
+    }
+
+    pub fn set_operator(&mut self, operator: Address) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.owner.get() {
+            return Err(b"not owner".to_vec());
+        }
+        self.operator.set(operator);
+        Ok(())
+    }
+}
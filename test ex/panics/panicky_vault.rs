@@ -0,0 +1,53 @@
+//! Stylus vault with panic paths in its public functions. Flagged: the
+//! unwrap, expect, bare assert!, panic! and indexing in the pub fns. Exempt:
+//! assert! with a message, the private helper and the test module
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use stylus_sdk::{alloy_primitives::{Address, U256}, call::transfer_eth, msg, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Vault {
+    balances: StorageMap<Address, StorageU256>,
+    admins: StorageVec<StorageAddress>,
+    limit: StorageU256,
+}
+
+#[public]
+impl Vault {
+    pub fn withdraw(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        let balance = self.balances.get(msg::sender());
+        let left = balance.checked_sub(amount).unwrap();
+        self.balances.setter(msg::sender()).set(left);
+        transfer_eth(msg::sender(), amount).expect("transfer failed");
+        Ok(())
+    }
+
+    pub fn first_admin(&self, ids: Vec<u32>) -> Address {
+        assert!(!ids.is_empty());
+        let index = ids[0] as usize;
+        self.admins.get(index).unwrap_or_else(|| panic!("no admin"))
+    }
+
+    pub fn set_limit(&mut self, limit: U256) {
+        assert!(limit > U256::ZERO, "limit must be positive");
+        self.limit.set(limit);
+    }
+}
+
+impl Vault {
+    fn parse_limit(raw: &[u8]) -> U256 {
+        U256::from_be_slice(&raw[..32]).checked_add(U256::ZERO).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub fn first(values: Vec<u64>) -> u64 {
+        values[0] + values.first().copied().unwrap()
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract Crowdsale {
+    uint256 public constant PRICE = 0.1 ether;
+    address public owner;
+    address public highestBidder;
+    uint256 public highestBid;
+    mapping(address => uint256) public tokens;
+    mapping(address => uint256) public contributed;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    // One payment buys `count` tokens: every iteration checks the same msg.value
+    function buyMany(uint256 count) external payable {
+        for (uint256 i = 0; i < count; i++) {
+            _buy(msg.sender);
+        }
+    }
+
+    // The whole payment is credited to every beneficiary
+    function contributeFor(address[] calldata beneficiaries) external payable {
+        for (uint256 i = 0; i < beneficiaries.length; i++) {
+            contributed[beneficiaries[i]] += msg.value;
+        }
+    }
+
+    // Not payable, so msg.value is always zero here
+    function tip() external {
+        contributed[msg.sender] += msg.value;
+    }
+
+    // Refunds the previous bidder by pushing ether to them
+    function bid() external payable {
+        require(msg.value > highestBid, "bid too low");
+        if (highestBidder != address(0)) {
+            payable(highestBidder).transfer(highestBid);
+        }
+        highestBidder = msg.sender;
+        highestBid = msg.value;
+    }
+
+    // Pays the caller: not a push refund
+    function withdraw() external {
+        uint256 amount = contributed[msg.sender];
+        contributed[msg.sender] = 0;
+        payable(msg.sender).transfer(amount);
+    }
+
+    function _buy(address buyer) internal {
+        require(msg.value >= PRICE, "underpaid");
+        tokens[buyer] += 1;
+    }
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract SafeCrowdsale {
+    uint256 public constant PRICE = 0.1 ether;
+    address public highestBidder;
+    uint256 public highestBid;
+    mapping(address => uint256) public tokens;
+    mapping(address => uint256) public pendingReturns;
+
+    // The total is checked once, before the loop
+    function buyMany(uint256 count) external payable {
+        require(msg.value == PRICE * count, "wrong payment");
+        for (uint256 i = 0; i < count; i++) {
+            tokens[msg.sender] += 1;
+        }
+    }
+
+    // The previous bidder is credited and withdraws later
+    function bid() external payable {
+        require(msg.value > highestBid, "bid too low");
+        pendingReturns[highestBidder] += highestBid;
+        highestBidder = msg.sender;
+        highestBid = msg.value;
+    }
+
+    function withdraw() external {
+        uint256 amount = pendingReturns[msg.sender];
+        pendingReturns[msg.sender] = 0;
+        payable(msg.sender).transfer(amount);
+    }
+}
@@ -0,0 +1,37 @@
+// Stylus crowdsale that reads msg::value() on every loop iteration
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Crowdsale {
+        uint256 price;
+        mapping(address => uint256) tokens;
+        mapping(address => uint256) contributed;
+    }
+}
+
+#[public]
+impl Crowdsale {
+    #[payable]
+    pub fn buy_many(&mut self, count: u32) -> Result<(), Vec<u8>> {
+        let buyer = msg::sender();
+        for _ in 0..count {
+            if msg::value() < self.price.get() {
+                return Err(b"underpaid".to_vec());
+            }
+            let held = self.tokens.get(buyer);
+            self.tokens.insert(buyer, held + U256::from(1));
+        }
+        Ok(())
+    }
+
+    pub fn contribute(&mut self) {
+        let sender = msg::sender();
+        let total = self.contributed.get(sender);
+        self.contributed.insert(sender, total + msg::value());
+    }
+
+    pub fn tokens_of(&self, account: Address) -> U256 {
+        self.tokens.get(account)
+    }
+}
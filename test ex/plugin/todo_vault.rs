@@ -0,0 +1,24 @@
+//! A vault with work left for later and a treasury written into the code.
+//! The todo-plugin example reports the TODO on line 19 and the address on line 15
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::{address, Address, U256}, msg, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct TodoVault {
+        mapping(address => uint256) balances;
+    }
+}
+
+const TREASURY: Address = address!("0x1111000000000000000000000000000000001111");
+
+#[public]
+impl TodoVault {
+    // TODO: charge the fee and send it to TREASURY
+    pub fn deposit(&mut self, amount: U256) {
+        let balance = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(balance + amount);
+    }
+}
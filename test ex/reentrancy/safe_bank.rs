@@ -0,0 +1,31 @@
+//! The Stylus bank written safely: withdraw zeroes the balance before paying
+//! out. Nothing is flagged
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::{Address, U256}, call::transfer_eth, msg, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Bank {
+    balances: StorageMap<Address, StorageU256>,
+    total_deposits: StorageU256,
+}
+
+#[public]
+impl Bank {
+    #[payable]
+    pub fn deposit(&mut self) {
+        let balance = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(balance + msg::value());
+        self.total_deposits.set(self.total_deposits.get() + msg::value());
+    }
+
+    pub fn withdraw(&mut self) -> Result<(), Vec<u8>> {
+        let amount = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(U256::ZERO);
+        self.total_deposits.set(self.total_deposits.get() - amount);
+        transfer_eth(msg::sender(), amount)?;
+        Ok(())
+    }
+}
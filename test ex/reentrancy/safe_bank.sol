@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts/utils/ReentrancyGuard.sol";
+
+/// Same bank written safely: withdraw updates state before paying out and
+/// sweep, which writes after its call, is nonReentrant. Nothing is flagged
+contract SafeBank is ReentrancyGuard {
+    mapping(address => uint256) public balances;
+    uint256 public totalDeposits;
+    uint256 public swept;
+
+    function deposit() external payable {
+        balances[msg.sender] += msg.value;
+        totalDeposits += msg.value;
+    }
+
+    function withdraw() external {
+        uint256 amount = balances[msg.sender];
+        balances[msg.sender] = 0;
+        totalDeposits -= amount;
+        (bool ok, ) = msg.sender.call{value: amount}("");
+        require(ok, "send failed");
+    }
+
+    function sweep(address payable to) external nonReentrant {
+        uint256 extra = address(this).balance - totalDeposits;
+        to.transfer(extra);
+        swept += extra;
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Pays out before zeroing the balance; withdraw is reentrant
+contract VulnerableBank {
+    mapping(address => uint256) public balances;
+    uint256 public totalDeposits;
+
+    function deposit() external payable {
+        balances[msg.sender] += msg.value;
+        totalDeposits += msg.value;
+    }
+
+    function withdraw() external {
+        uint256 amount = balances[msg.sender];
+        (bool ok, ) = msg.sender.call{value: amount}("");
+        require(ok, "send failed");
+        balances[msg.sender] = 0;
+        totalDeposits -= amount;
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract Airdrop {
+    address public signer;
+    mapping(address => uint256) public claimed;
+
+    constructor(address _signer) {
+        signer = _signer;
+    }
+
+    // The digest is only (account, amount): no nonce, no deadline, no chain id
+    function claim(uint256 amount, uint8 v, bytes32 r, bytes32 s) external {
+        bytes32 digest = keccak256(abi.encodePacked(msg.sender, amount));
+        require(ecrecover(digest, v, r, s) == signer, "bad signature");
+        claimed[msg.sender] += amount;
+    }
+
+    // Has a deadline, but still replayable and chain-agnostic
+    function claimBefore(uint256 amount, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external {
+        require(block.timestamp <= deadline, "expired");
+        require(_verify(keccak256(abi.encodePacked(msg.sender, amount, deadline)), v, r, s), "bad signature");
+        claimed[msg.sender] += amount;
+    }
+
+    function _verify(bytes32 digest, uint8 v, bytes32 r, bytes32 s) internal view returns (bool) {
+        return ecrecover(digest, v, r, s) == signer;
+    }
+
+    // Read-only check for off-chain callers: not reported
+    function isValid(bytes32 digest, uint8 v, bytes32 r, bytes32 s) external view returns (bool) {
+        return ecrecover(digest, v, r, s) == signer;
+    }
+}
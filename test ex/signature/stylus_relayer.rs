@@ -0,0 +1,52 @@
+// Stylus relayer that checks signatures through the ecrecover precompile
+use stylus_sdk::{alloy_primitives::{Address, B256, U256}, block, call::{static_call, Call}, crypto::keccak, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Relayer {
+        address signer;
+        mapping(address => uint256) nonces;
+        mapping(address => uint256) credits;
+    }
+}
+
+#[public]
+impl Relayer {
+    /// Replayable: nothing consumed, no deadline, no chain id
+    pub fn credit(&mut self, account: Address, amount: U256, signature: Vec<u8>) -> Result<(), Vec<u8>> {
+        let digest = keccak([account.as_slice(), &amount.to_be_bytes::<32>()].concat());
+        if self.recover(digest, &signature)? != self.signer.get() {
+            return Err(b"bad signature".to_vec());
+        }
+        let credit = self.credits.get(account);
+        self.credits.insert(account, credit + amount);
+        Ok(())
+    }
+
+    /// Consumes a nonce and binds the chain id and a deadline
+    pub fn credit_once(&mut self, account: Address, amount: U256, deadline: U256, signature: Vec<u8>) -> Result<(), Vec<u8>> {
+        if U256::from(block::timestamp()) > deadline {
+            return Err(b"expired".to_vec());
+        }
+        let nonce = self.nonces.get(account);
+        let chain = U256::from(block::chainid());
+        let digest = keccak([account.as_slice(), &amount.to_be_bytes::<32>(), &nonce.to_be_bytes::<32>(),
+            &chain.to_be_bytes::<32>(), &deadline.to_be_bytes::<32>()].concat());
+        if self.recover(digest, &signature)? != self.signer.get() {
+            return Err(b"bad signature".to_vec());
+        }
+        self.nonces.insert(account, nonce + U256::from(1));
+        let credit = self.credits.get(account);
+        self.credits.insert(account, credit + amount);
+        Ok(())
+    }
+}
+
+impl Relayer {
+    fn recover(&self, digest: B256, signature: &[u8]) -> Result<Address, Vec<u8>> {
+        let mut input = digest.to_vec();
+        input.extend_from_slice(signature);
+        let output = static_call(Call::new(), Address::with_last_byte(1), &input)?;
+        Ok(Address::from_slice(&output[12..32]))
+    }
+}
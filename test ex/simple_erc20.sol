@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract SimpleToken {
+    string public name;
+    uint8 public constant decimals = 18;
+    uint256 public totalSupply;
+    address public owner;
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+    address[] public holders;
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    error InsufficientBalance(uint256 available, uint256 required);
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    constructor(string memory tokenName) {
+        name = tokenName;
+        owner = msg.sender;
+    }
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        _transfer(msg.sender, to, amount);
+        return true;
+    }
+
+    function approve(address spender, uint256 amount) external returns (bool) {
+        allowance[msg.sender][spender] = amount;
+        emit Approval(msg.sender, spender, amount);
+        return true;
+    }
+
+    function mint(address to, uint256 amount) external onlyOwner {
+        totalSupply += amount;
+        balanceOf[to] += amount;
+        emit Transfer(address(0), to, amount);
+    }
+
+    function balanceAndSupply(address account) public view returns (uint256, uint256) {
+        return (balanceOf[account], totalSupply);
+    }
+
+    function deposit() external payable {
+        balanceOf[msg.sender] += msg.value;
+    }
+
+    function codeSize(address account) external view returns (uint256 size) {
+        assembly {
+            size := extcodesize(account)
+        }
+    }
+
+    function _transfer(address from, address to, uint256 amount) internal {
+        uint256 fromBalance = balanceOf[from];
+        if (fromBalance < amount) {
+            revert InsufficientBalance(fromBalance, amount);
+        }
+        balanceOf[from] = fromBalance - amount;
+        balanceOf[to] += amount;
+        emit Transfer(from, to, amount);
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Guarded: upgrades and fee changes are queued and only execute after MIN_DELAY.
+contract DelayedAdmin {
+    uint256 public constant MIN_DELAY = 2 days;
+
+    address public owner;
+    address public implementation;
+    uint256 public feeBps;
+    mapping(bytes32 => uint256) public eta;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    modifier timelocked(bytes32 id) {
+        require(eta[id] != 0 && block.timestamp >= eta[id], "not ready");
+        delete eta[id];
+        _;
+    }
+
+    constructor(address implementation_) {
+        owner = msg.sender;
+        implementation = implementation_;
+    }
+
+    function queue(bytes32 id) external onlyOwner {
+        eta[id] = block.timestamp + MIN_DELAY;
+    }
+
+    function upgradeTo(address newImplementation) external onlyOwner timelocked(keccak256(abi.encode("upgrade", newImplementation))) {
+        implementation = newImplementation;
+    }
+
+    function setFeeBps(uint256 newFee) external onlyOwner {
+        require(eta[keccak256(abi.encode("fee", newFee))] != 0, "not queued");
+        require(block.timestamp >= eta[keccak256(abi.encode("fee", newFee))], "too early");
+        feeBps = newFee;
+    }
+}
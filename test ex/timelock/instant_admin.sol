@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Unguarded: the owner can upgrade, change the fee and drain the treasury instantly.
+contract InstantAdmin {
+    address public owner;
+    address public implementation;
+    address public treasury;
+    uint256 public feeBps;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+
+    constructor(address implementation_, address treasury_) {
+        owner = msg.sender;
+        implementation = implementation_;
+        treasury = treasury_;
+    }
+
+    function upgradeTo(address newImplementation) external onlyOwner {
+        implementation = newImplementation;
+    }
+
+    function setFeeBps(uint256 newFee) external onlyOwner {
+        feeBps = newFee;
+    }
+
+    function sweepTreasury(address to) external onlyOwner {
+        payable(to).transfer(address(this).balance);
+    }
+}
@@ -0,0 +1,47 @@
+//! Stylus coin flip with every kind of block value use.
+//! Seeded flaws: `flip` derives its outcome from the block timestamp, `claim`
+//! needs an exact timestamp, and `checkpoint` records the L1 block number.
+//! The timestamp in `Flipped` is only logged and isn't reported.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use alloy_sol_types::sol;
+use stylus_sdk::{alloy_primitives::U256, block, evm, msg, prelude::*};
+
+sol! {
+    event Flipped(address indexed player, bool won, uint256 at);
+}
+
+sol_storage! {
+    #[entrypoint]
+    pub struct CoinFlip {
+        uint256 unlock_at;
+        uint256 last_checkpoint;
+        mapping(address => uint256) wins;
+    }
+}
+
+#[public]
+impl CoinFlip {
+    pub fn flip(&mut self) -> bool {
+        let seed = block::timestamp();
+        let won = seed % 2 == 0;
+        if won {
+            let wins = self.wins.get(msg::sender());
+            self.wins.insert(msg::sender(), wins + U256::from(1));
+        }
+        evm::log(Flipped { player: msg::sender(), won, at: U256::from(block::timestamp()) });
+        won
+    }
+
+    pub fn claim(&mut self) -> Result<(), Vec<u8>> {
+        if U256::from(block::timestamp()) != self.unlock_at.get() {
+            return Err(b"not now".to_vec());
+        }
+        Ok(())
+    }
+
+    pub fn checkpoint(&mut self) {
+        self.last_checkpoint.set(U256::from(block::number()));
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Deadlines allow for timestamp drift and the timestamp is only logged.
+/// Nothing to report
+contract GraceAuction {
+    uint256 public constant GRACE_PERIOD = 15 minutes;
+    uint256 public endsAt;
+    uint256 public highestBid;
+
+    event Bid(address indexed bidder, uint256 amount, uint256 at);
+
+    constructor(uint256 duration) {
+        endsAt = block.timestamp + duration;
+    }
+
+    function bid() external payable {
+        require(block.timestamp <= endsAt + GRACE_PERIOD, "ended");
+        require(msg.value > highestBid, "too low");
+        highestBid = msg.value;
+        emit Bid(msg.sender, msg.value, block.timestamp);
+    }
+}
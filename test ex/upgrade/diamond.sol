@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IDiamondCut {
+    enum FacetCutAction { Add, Replace, Remove }
+    struct FacetCut {
+        address facetAddress;
+        FacetCutAction action;
+        bytes4[] functionSelectors;
+    }
+}
+
+contract Diamond {
+    address public contractOwner;
+    mapping(bytes4 => address) public facets;
+
+    constructor() {
+        contractOwner = msg.sender;
+    }
+
+    function diamondCut(IDiamondCut.FacetCut[] calldata cuts) external {
+        require(msg.sender == contractOwner, "not owner");
+        for (uint256 i = 0; i < cuts.length; i++) {
+            for (uint256 j = 0; j < cuts[i].functionSelectors.length; j++) {
+                facets[cuts[i].functionSelectors[j]] = cuts[i].facetAddress;
+            }
+        }
+    }
+
+    fallback() external payable {
+        address facet = facets[msg.sig];
+        require(facet != address(0), "no facet");
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            let result := delegatecall(gas(), facet, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch result
+            case 0 { revert(0, returndatasize()) }
+            default { return(0, returndatasize()) }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Seeded flaw: balanceOf(address) clashes with TokenFacet
+contract StakingFacet {
+    function balanceOf(address staker) external view returns (uint256) {
+        return uint256(uint160(staker)) % 500;
+    }
+
+    function stake() external payable {}
+}
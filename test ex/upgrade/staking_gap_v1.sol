@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+
+/// Base storage reserves a gap. Config is stored inline before `stakes`, which is flagged.
+contract OwnableStorage is Initializable {
+    address public owner;
+    bool public paused;
+    uint256[49] private __gap;
+}
+
+contract StakingPool is OwnableStorage {
+    struct Config {
+        uint64 rewardRate;
+        uint64 lockPeriod;
+        address treasury;
+    }
+
+    uint256 public totalStaked;
+    Config public config;
+    mapping(address => uint128) public stakes;
+
+    function initialize(address _owner) external initializer {
+        owner = _owner;
+    }
+}
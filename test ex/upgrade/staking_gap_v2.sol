@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+
+/// v2 takes one slot from the base gap for `guardian` and appends `version`:
+/// a pure append. `stakes` is renamed to `balances`, which keeps the data.
+contract OwnableStorage is Initializable {
+    address public owner;
+    bool public paused;
+    address public guardian;
+    uint256[48] private __gap;
+}
+
+contract StakingPool is OwnableStorage {
+    struct Config {
+        uint64 rewardRate;
+        uint64 lockPeriod;
+        address treasury;
+    }
+
+    uint256 public totalStaked;
+    Config public config;
+    mapping(address => uint128) public balances;
+    uint32 public version;
+
+    function initialize(address _owner) external initializer {
+        owner = _owner;
+    }
+}
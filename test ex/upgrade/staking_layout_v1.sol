@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract OwnableStorage {
+    address public owner;
+    bool public paused;
+}
+
+contract StakingPool is OwnableStorage {
+    struct Config {
+        uint64 rewardRate;
+        uint64 lockPeriod;
+        address treasury;
+    }
+
+    struct Position {
+        uint128 amount;
+        uint64 since;
+    }
+
+    uint256 public totalStaked;
+    Config public config;
+    mapping(address => Position) public positions;
+    address[] public stakers;
+}
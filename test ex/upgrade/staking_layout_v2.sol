@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract OwnableStorage {
+    address public owner;
+    bool public paused;
+}
+
+/// v2 seeds two collisions: totalStaked is retyped and Config gains a member mid-struct.
+/// rewardDebt and version are appended and safe.
+contract StakingPool is OwnableStorage {
+    struct Config {
+        uint64 rewardRate;
+        uint128 maxStake;
+        uint64 lockPeriod;
+        address treasury;
+    }
+
+    struct Position {
+        uint128 amount;
+        uint64 since;
+        uint64 rewardDebt;
+    }
+
+    uint128 public totalStaked;
+    Config public config;
+    mapping(address => Position) public positions;
+    address[] public stakers;
+    uint32 public version;
+}
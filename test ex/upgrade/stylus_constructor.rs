@@ -0,0 +1,42 @@
+//! Stylus implementation meant to sit behind a proxy.
+//! Seeded flaw: the constructor sets the owner and fee, which the proxy never sees.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    msg,
+    prelude::*,
+};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct FeeImplementation {
+        address owner;
+        uint256 fee_bps;
+        bool initialized;
+        uint256[48] __gap;
+    }
+}
+
+#[public]
+impl FeeImplementation {
+    #[constructor]
+    pub fn constructor(&mut self, fee_bps: U256) {
+        self.owner.set(msg::sender());
+        self.fee_bps.set(fee_bps);
+    }
+
+    pub fn initialize(&mut self, owner: Address) -> Result<(), Vec<u8>> {
+        if self.initialized.get() {
+            return Err(b"already initialized".to_vec());
+        }
+        self.initialized.set(true);
+        self.owner.set(owner);
+        Ok(())
+    }
+
+    pub fn fee_bps(&self) -> U256 {
+        self.fee_bps.get()
+    }
+}
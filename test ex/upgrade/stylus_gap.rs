@@ -0,0 +1,47 @@
+//! Upgradeable Stylus vault whose embedded `Ownable` storage has no reserved
+//! gap; `Limits` reserves one and isn't flagged.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*, storage::*};
+
+#[storage]
+pub struct Ownable {
+    owner: StorageAddress,
+    initialized: StorageBool,
+}
+
+#[storage]
+pub struct Limits {
+    max_deposit: StorageU256,
+    __gap: StorageArray<StorageU256, 49>,
+}
+
+#[storage]
+#[entrypoint]
+pub struct Vault {
+    #[borrow]
+    ownable: Ownable,
+    limits: Limits,
+    total: StorageU256,
+}
+
+#[public]
+impl Vault {
+    pub fn initialize(&mut self) -> Result<(), Vec<u8>> {
+        if self.ownable.initialized.get() {
+            return Err(b"already initialized".to_vec());
+        }
+        self.ownable.owner.set(msg::sender());
+        self.ownable.initialized.set(true);
+        Ok(())
+    }
+
+    pub fn owner(&self) -> Address {
+        self.ownable.owner.get()
+    }
+
+    pub fn total(&self) -> U256 {
+        self.total.get()
+    }
+}
@@ -0,0 +1,42 @@
+//! Minimal Stylus proxy that forwards every call to a stored implementation.
+//! Seeded flaw: `set_implementation` doesn't check the caller.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{
+    alloy_primitives::Address,
+    call::delegate_call,
+    msg,
+    prelude::*,
+};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Proxy {
+        address implementation;
+        address owner;
+    }
+}
+
+#[public]
+impl Proxy {
+    pub fn init(&mut self, implementation: Address) -> Result<(), Vec<u8>> {
+        if self.owner.get() != Address::ZERO {
+            return Err(b"already initialized".to_vec());
+        }
+        self.owner.set(msg::sender());
+        self.implementation.set(implementation);
+        Ok(())
+    }
+
+    pub fn set_implementation(&mut self, implementation: Address) -> Result<(), Vec<u8>> {
+        self.implementation.set(implementation);
+        Ok(())
+    }
+
+    #[fallback]
+    fn forward(&mut self, calldata: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        let target = self.implementation.get();
+        unsafe { delegate_call(self, target, calldata) }
+    }
+}
@@ -0,0 +1,28 @@
+//! First deployed version of an upgradeable Stylus vault; compare with
+//! stylus_vault_v2.rs using `upgrade-check`.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Vault {
+    owner: StorageAddress,
+    paused: StorageBool,
+    total: StorageU256,
+    balances: StorageMap<Address, StorageU256>,
+}
+
+#[public]
+impl Vault {
+    pub fn total(&self) -> U256 {
+        self.total.get()
+    }
+
+    pub fn deposit(&mut self, amount: U256) {
+        let balance = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(balance + amount);
+        self.total.set(self.total.get() + amount);
+    }
+}
@@ -0,0 +1,31 @@
+//! Second version of the Stylus vault in stylus_vault_v1.rs.
+//! Seeded flaws: `fee_bps` is inserted before `total`, and `paused` became a U8.
+#![cfg_attr(not(feature = "export-abi"), no_main)]
+extern crate alloc;
+
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*, storage::*};
+
+#[storage]
+#[entrypoint]
+pub struct Vault {
+    owner: StorageAddress,
+    paused: StorageU8,
+    fee_bps: StorageU256,
+    total: StorageU256,
+    balances: StorageMap<Address, StorageU256>,
+    treasury: StorageAddress,
+}
+
+#[public]
+impl Vault {
+    pub fn total(&self) -> U256 {
+        self.total.get()
+    }
+
+    pub fn deposit(&mut self, amount: U256) {
+        let fee = amount * self.fee_bps.get() / U256::from(10_000);
+        let balance = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(balance + amount - fee);
+        self.total.set(self.total.get() + amount);
+    }
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract TokenFacet {
+    function balanceOf(address account) external view returns (uint256) {
+        return uint256(uint160(account)) % 1000;
+    }
+
+    function totalSupply() external pure returns (uint256) {
+        return 1000;
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IBeacon {
+    function implementation() external view returns (address);
+}
+
+/// Seeded flaw: anyone can point every beacon proxy at new code
+contract SimpleBeacon is IBeacon {
+    address private _implementation;
+    address public owner;
+
+    constructor(address implementation_) {
+        _implementation = implementation_;
+        owner = msg.sender;
+    }
+
+    function implementation() external view returns (address) {
+        return _implementation;
+    }
+
+    function upgradeTo(address newImplementation) external {
+        _implementation = newImplementation;
+    }
+}
@@ -0,0 +1,50 @@
+//! Vault with unchecked and checked admin and payout paths
+
+use stylus_sdk::{alloy_primitives::{Address, U256}, msg, prelude::*};
+
+sol_storage! {
+    #[entrypoint]
+    pub struct Vault {
+        address admin;
+        mapping(address => uint256) balances;
+    }
+}
+
+#[public]
+impl Vault {
+    pub fn admin(&self) -> Address {
+        self.admin.get()
+    }
+
+    /// Flagged High: the new admin may be Address::ZERO
+    pub fn set_admin(&mut self, new_admin: Address) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.admin.get() {
+            return Err(b"not admin".to_vec());
+        }
+        self.admin.set(new_admin);
+        Ok(())
+    }
+
+    /// Flagged Medium: neither `to` nor `amount` is checked
+    pub fn withdraw_to(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        let balance = self.balances.get(msg::sender());
+        self.balances.setter(msg::sender()).set(balance - amount);
+        self.balances.setter(to).set(self.balances.get(to) + amount);
+        Ok(())
+    }
+
+    /// Clean: both arguments are checked before the transfer
+    pub fn transfer(&mut self, to: Address, amount: U256) -> Result<bool, Vec<u8>> {
+        if to == Address::ZERO || amount == U256::ZERO {
+            return Err(b"zero".to_vec());
+        }
+        self.move_balance(msg::sender(), to, amount);
+        Ok(true)
+    }
+
+    fn move_balance(&mut self, from: Address, to: Address, amount: U256) {
+        let balance = self.balances.get(from);
+        self.balances.setter(from).set(balance - amount);
+        self.balances.setter(to).set(self.balances.get(to) + amount);
+    }
+}